@@ -71,7 +71,7 @@ fn bench(c: &mut Criterion) {
                 |(env, triggers)| {
                     let config = runtime::Config::default().with_fast_forward(true);
                     let mut sched = runtime::Scheduler::new(env, triggers, config);
-                    sched.event_loop();
+                    sched.event_loop().unwrap();
                 },
                 BatchSize::SmallInput,
             );
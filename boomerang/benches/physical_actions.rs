@@ -103,7 +103,7 @@ fn bench(c: &mut Criterion) {
                     .with_fast_forward(false)
                     .with_timeout(Duration::seconds(1));
                 let mut sched = runtime::Scheduler::new(env, triggers, config);
-                sched.event_loop();
+                sched.event_loop().unwrap();
             },
             criterion::BatchSize::NumIterations(10),
         );
@@ -53,7 +53,7 @@ impl runtime::Trigger<Ping> for ReactionInStart<'_> {
         // reset local state
         state.pings_left = state.count;
         // start execution
-        self.serve.schedule(ctx, (), None);
+        self.serve.schedule(ctx, (), None).unwrap();
     }
 }
 
@@ -82,7 +82,7 @@ impl runtime::Trigger<Ping> for ReactionInPong<'_> {
         if state.pings_left == 0 {
             *self.out_finished = Some(());
         } else {
-            self.serve.schedule(ctx, (), None);
+            self.serve.schedule(ctx, (), None).unwrap();
         }
     }
 }
@@ -178,7 +178,7 @@ fn bench(c: &mut Criterion) {
                 |(env, triggers)| {
                     let config = runtime::Config::default().with_fast_forward(true);
                     let mut sched = runtime::Scheduler::new(env, triggers, config);
-                    sched.event_loop();
+                    sched.event_loop().unwrap();
 
                     // validate the end state
                     let env = sched.into_env();
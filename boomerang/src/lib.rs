@@ -60,7 +60,7 @@
 //! let (mut env, triggers, _) = env_builder.into_runtime_parts().unwrap();
 //! let config = runtime::Config::default().with_fast_forward(true);
 //! let mut sched = runtime::Scheduler::new(env, triggers, config);
-//! sched.event_loop();
+//! sched.event_loop().unwrap();
 //! ```
 //!
 //! ## Feature flags
@@ -69,6 +69,8 @@
 #![deny(clippy::all)]
 
 pub mod flatten_transposed;
+#[cfg(feature = "plugin")]
+pub mod plugin;
 
 // Re-exports
 pub use boomerang_builder as builder;
@@ -78,13 +80,14 @@ pub mod prelude {
     //! Re-exported common types and traits for Boomerang
 
     pub use super::builder::{
-        BuilderError, BuilderFqn, EnvBuilder, Input, Logical, Output, Physical, Reactor,
-        TimerActionKey, TypedActionKey, TypedPortKey,
+        AccumPort, BuilderError, BuilderFqn, Coalesce, EnvBuilder, Implements, Input, Logical,
+        MergePolicy, Output, Physical, Reactor, ReactorInterface, TimerActionKey, TypedActionKey,
+        TypedPortKey,
     };
 
-    pub use super::runtime::{self, ContextCommon, Duration, FromRefs};
+    pub use super::runtime::{self, BankInputs, ContextCommon, Duration, FromRefs, StateMachine};
 
-    pub use boomerang_derive::{Reaction, Reactor};
+    pub use boomerang_derive::{Reaction, Reactor, ReactorFsm, ReactorInterface};
 }
 
 #[cfg(feature = "derive")]
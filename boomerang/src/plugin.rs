@@ -0,0 +1,312 @@
+//! Reactors distributed as dynamically loaded plugins.
+//!
+//! A plugin is a crate compiled as a `cdylib` that exports its reactor builders with [`export_reactors!`]. The macro
+//! generates a [`PluginDeclaration`] static named `BOOMERANG_PLUGIN`, through which the host registers each exported
+//! builder under a type name. A host program loads plugins at startup with a [`PluginHost`], and then builds the
+//! registered reactors by type name into its [`EnvBuilder`].
+//!
+//! The builders are called with the host's [`EnvBuilder`], so plugins and host must be compiled with the same compiler,
+//! the same version of `boomerang` and the same features. [`PluginHost::load`] checks the [`PLUGIN_ABI_VERSION`] and the
+//! version of `boomerang`, but it can't detect other mismatches, which is why loading a plugin is `unsafe`.
+//!
+//! Since the reactors built from a plugin run code from its library, loaded libraries are never unloaded.
+//!
+//! ## Example:
+//!
+//! In the plugin crate, with `crate-type = ["cdylib"]`:
+//!
+//! ```rust,ignore
+//! boomerang::export_reactors! {
+//!     "Counter" => CounterBuilder = Counter::default(),
+//! }
+//! ```
+//!
+//! In the host:
+//!
+//! ```rust,ignore
+//! let mut host = PluginHost::new();
+//! unsafe { host.load_dir("plugins")? };
+//! let counter = host.build("Counter", "counter", None, &mut env_builder)?;
+//! ```
+
+#![allow(unsafe_code)]
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::builder::{BuilderError, BuilderFqnSegment, BuilderReactorKey, EnvBuilder};
+
+/// The version of the plugin ABI, incremented on every incompatible change to [`PluginDeclaration`] or
+/// [`PluginRegistrar`].
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// The version of `boomerang`, which must match between plugins and host.
+pub const BOOMERANG_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The name of the [`PluginDeclaration`] symbol exported by plugins.
+pub const PLUGIN_SYMBOL: &[u8] = b"BOOMERANG_PLUGIN";
+
+/// Builds an instance of a reactor exported by a plugin, returning the key of the new reactor.
+pub type PluginBuilderFn = fn(
+    name: &str,
+    parent: Option<BuilderReactorKey>,
+    env: &mut EnvBuilder,
+) -> Result<BuilderReactorKey, BuilderError>;
+
+/// The entry point of a plugin, exported as `BOOMERANG_PLUGIN` by [`export_reactors!`].
+///
+/// `abi_version` is always the first field, so the host can check it before relying on the rest of the layout. All
+/// fields have a C layout, the version of `boomerang` is stored as a pointer and length of its UTF-8 bytes.
+#[repr(C)]
+#[derive(Debug)]
+pub struct PluginDeclaration {
+    pub abi_version: u32,
+    boomerang_version_ptr: *const u8,
+    boomerang_version_len: usize,
+    /// Registers the reactor builders of the plugin
+    pub register: extern "C" fn(&mut PluginRegistrar),
+}
+
+// SAFETY: The version is only ever read, and points to a `&'static str`.
+unsafe impl Sync for PluginDeclaration {}
+
+impl PluginDeclaration {
+    /// Declare a plugin of the current [`PLUGIN_ABI_VERSION`] and [`BOOMERANG_VERSION`].
+    pub const fn new(register: extern "C" fn(&mut PluginRegistrar)) -> Self {
+        Self {
+            abi_version: PLUGIN_ABI_VERSION,
+            boomerang_version_ptr: BOOMERANG_VERSION.as_ptr(),
+            boomerang_version_len: BOOMERANG_VERSION.len(),
+            register,
+        }
+    }
+
+    /// The version of `boomerang` the plugin was built with.
+    pub fn boomerang_version(&self) -> &str {
+        // SAFETY: The pointer and length are only set by `new`, from a `&'static str`. Libraries are never unloaded,
+        // so a declaration loaded from a plugin stays valid.
+        unsafe {
+            std::str::from_utf8_unchecked(std::slice::from_raw_parts(
+                self.boomerang_version_ptr,
+                self.boomerang_version_len,
+            ))
+        }
+    }
+}
+
+/// Collects the reactor builders of a plugin during registration.
+#[derive(Debug, Default)]
+pub struct PluginRegistrar {
+    builders: Vec<(String, PluginBuilderFn)>,
+}
+
+impl PluginRegistrar {
+    /// Register `build` under the given reactor type name.
+    pub fn register(&mut self, type_name: &str, build: PluginBuilderFn) {
+        self.builders.push((type_name.to_owned(), build));
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum PluginError {
+    #[error("Unable to load plugin {path:?}: {source}")]
+    Load {
+        path: PathBuf,
+        source: libloading::Error,
+    },
+
+    #[error("Plugin ABI version {found} is not supported, expected {expected}")]
+    AbiMismatch { found: u32, expected: u32 },
+
+    #[error("Plugin was built with boomerang {found}, expected {expected}")]
+    VersionMismatch {
+        found: String,
+        expected: &'static str,
+    },
+
+    #[error("A reactor type named '{0}' was already registered")]
+    DuplicateReactorType(String),
+
+    #[error("No plugin registered a reactor type named '{0}'")]
+    UnknownReactorType(String),
+
+    #[error(transparent)]
+    Builder(#[from] BuilderError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Loads plugins and builds the reactors they export, see the [module documentation](self).
+#[derive(Debug, Default)]
+pub struct PluginHost {
+    builders: BTreeMap<String, PluginBuilderFn>,
+    /// The paths of all loaded libraries
+    loaded: Vec<PathBuf>,
+}
+
+impl PluginHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the plugin library at `path` and register its reactor builders, returning their type names.
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initialization code. The plugin must have been compiled with the same compiler,
+    /// `boomerang` version and features as the host, see the [module documentation](self).
+    pub unsafe fn load(&mut self, path: impl AsRef<Path>) -> Result<Vec<String>, PluginError> {
+        let path = path.as_ref();
+        let load_error = |source| PluginError::Load {
+            path: path.to_owned(),
+            source,
+        };
+        let library = libloading::Library::new(path).map_err(load_error)?;
+        let declaration: *const PluginDeclaration = *library
+            .get::<*const PluginDeclaration>(PLUGIN_SYMBOL)
+            .map_err(load_error)?;
+
+        let abi_version = (*declaration).abi_version;
+        if abi_version != PLUGIN_ABI_VERSION {
+            return Err(PluginError::AbiMismatch {
+                found: abi_version,
+                expected: PLUGIN_ABI_VERSION,
+            });
+        }
+
+        // The reactors built from the plugin run code from the library, so it must never be unloaded.
+        std::mem::forget(library);
+        let type_names = self.register(&*declaration)?;
+        self.loaded.push(path.to_owned());
+        Ok(type_names)
+    }
+
+    /// Load all plugin libraries in `dir`, in order of their file names, returning the type names of all registered
+    /// reactors.
+    ///
+    /// # Safety
+    ///
+    /// See [`PluginHost::load`].
+    pub unsafe fn load_dir(&mut self, dir: impl AsRef<Path>) -> Result<Vec<String>, PluginError> {
+        let mut paths = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.retain(|path| {
+            path.is_file()
+                && path.extension().and_then(|ext| ext.to_str())
+                    == Some(std::env::consts::DLL_EXTENSION)
+        });
+        paths.sort();
+
+        let mut type_names = Vec::new();
+        for path in paths {
+            type_names.extend(self.load(path)?);
+        }
+        Ok(type_names)
+    }
+
+    /// Register the reactor builders of a declaration, e.g. of a plugin linked statically into the host, returning
+    /// their type names.
+    pub fn register(
+        &mut self,
+        declaration: &PluginDeclaration,
+    ) -> Result<Vec<String>, PluginError> {
+        if declaration.abi_version != PLUGIN_ABI_VERSION {
+            return Err(PluginError::AbiMismatch {
+                found: declaration.abi_version,
+                expected: PLUGIN_ABI_VERSION,
+            });
+        }
+        if declaration.boomerang_version() != BOOMERANG_VERSION {
+            return Err(PluginError::VersionMismatch {
+                found: declaration.boomerang_version().to_owned(),
+                expected: BOOMERANG_VERSION,
+            });
+        }
+
+        let mut registrar = PluginRegistrar::default();
+        (declaration.register)(&mut registrar);
+
+        if let Some((type_name, _)) = registrar
+            .builders
+            .iter()
+            .find(|(type_name, _)| self.builders.contains_key(type_name))
+        {
+            return Err(PluginError::DuplicateReactorType(type_name.clone()));
+        }
+        let type_names = registrar
+            .builders
+            .iter()
+            .map(|(type_name, _)| type_name.clone())
+            .collect();
+        self.builders.extend(registrar.builders);
+        Ok(type_names)
+    }
+
+    /// The type names of all registered reactors.
+    pub fn reactor_types(&self) -> impl Iterator<Item = &str> {
+        self.builders.keys().map(String::as_str)
+    }
+
+    /// The paths of all loaded plugin libraries.
+    pub fn loaded(&self) -> &[PathBuf] {
+        &self.loaded
+    }
+
+    /// Build an instance of the reactor registered as `type_name`, returning the key of the new reactor.
+    pub fn build(
+        &self,
+        type_name: &str,
+        name: &str,
+        parent: Option<BuilderReactorKey>,
+        env: &mut EnvBuilder,
+    ) -> Result<BuilderReactorKey, PluginError> {
+        let build = self
+            .builders
+            .get(type_name)
+            .ok_or_else(|| PluginError::UnknownReactorType(type_name.to_owned()))?;
+        Ok(build(name, parent, env)?)
+    }
+}
+
+/// Find the key of the reactor just built as `name` under `parent`, used by [`export_reactors!`].
+#[doc(hidden)]
+pub fn built_reactor_key(
+    env: &EnvBuilder,
+    name: &str,
+    parent: Option<BuilderReactorKey>,
+) -> Result<BuilderReactorKey, BuilderError> {
+    let segment = BuilderFqnSegment::try_from(name)?;
+    let fqn = match parent {
+        Some(parent) => env.reactor_fqn(parent, false)?.append(segment)?,
+        None => std::iter::once(segment).collect(),
+    };
+    env.find_reactor_by_fqn(fqn)
+}
+
+/// Export reactor builders from a plugin crate, see the [module documentation](self).
+///
+/// Each entry registers the reactor `$reactor` under the type name `$type_name`, built with the initial state
+/// `$state`.
+#[macro_export]
+macro_rules! export_reactors {
+    ($($type_name:literal => $reactor:ty = $state:expr),+ $(,)?) => {
+        #[allow(unsafe_code)]
+        #[no_mangle]
+        pub static BOOMERANG_PLUGIN: $crate::plugin::PluginDeclaration =
+            $crate::plugin::PluginDeclaration::new(__boomerang_plugin_register);
+
+        #[allow(improper_ctypes_definitions)]
+        extern "C" fn __boomerang_plugin_register(registrar: &mut $crate::plugin::PluginRegistrar) {
+            $(
+                registrar.register($type_name, |name, parent, env| {
+                    <$reactor as $crate::builder::Reactor>::build(name, $state, parent, None, env)?;
+                    $crate::plugin::built_reactor_key(env, name, parent)
+                });
+            )+
+        }
+    };
+}
@@ -0,0 +1,109 @@
+//! Checks that an `AccumPort` delivers the values pushed during a tag as one batch, directly and after a delay.
+
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionTick")]
+struct Detector {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    detections: AccumPort<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Detector", triggers(action = "tick"))]
+struct ReactionTick<'a> {
+    detections: runtime::OutputRef<'a, runtime::Accum<u32>>,
+}
+
+impl runtime::Trigger<u32> for ReactionTick<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, frame: &mut u32) {
+        // Nothing is detected in odd frames, `frame` detections in even ones
+        if frame.is_multiple_of(2) {
+            for i in 0..*frame {
+                self.detections.push(*frame * 10 + i);
+            }
+        }
+        *frame += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Batches {
+    direct: Vec<Vec<u32>>,
+    delayed: Vec<Vec<u32>>,
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "Batches",
+    reaction = "ReactionDirect",
+    reaction = "ReactionDelayed"
+)]
+struct Tracker {
+    direct: AccumPort<u32, Input>,
+    delayed: AccumPort<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Tracker")]
+struct ReactionDirect<'a> {
+    direct: runtime::InputRef<'a, runtime::Accum<u32>>,
+}
+
+impl runtime::Trigger<Batches> for ReactionDirect<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut Batches) {
+        let batch = self.direct.as_ref().expect("Triggered without a batch");
+        assert!(!batch.is_empty(), "Empty batches should not be sent");
+        state.direct.push(batch.to_vec());
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Tracker")]
+struct ReactionDelayed<'a> {
+    delayed: runtime::InputRef<'a, runtime::Accum<u32>>,
+}
+
+impl runtime::Trigger<Batches> for ReactionDelayed<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut Batches) {
+        let batch = self.delayed.as_ref().expect("Triggered without a batch");
+        state.delayed.push(batch.iter().copied().collect());
+    }
+}
+
+#[derive(Reactor)]
+#[allow(clippy::duplicated_attributes)]
+#[reactor(
+    state = "()",
+    connection(from = "detector.detections", to = "tracker.direct"),
+    connection(from = "detector.detections", to = "tracker.delayed", after = "1 msec")
+)]
+struct Main {
+    #[reactor(child = "0")]
+    detector: Detector,
+    #[reactor(child = "Batches::default()")]
+    tracker: Tracker,
+}
+
+#[test]
+fn accum_port() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(6));
+    let (_, sched, _) =
+        boomerang_util::runner::build_and_test_reactor_typed::<Main>("main", (), config).unwrap();
+    let env = sched.into_env();
+    let state = env
+        .find_reactor_by_name("tracker")
+        .and_then(|reactor| reactor.get_state::<Batches>())
+        .unwrap();
+    let expected = vec![
+        vec![20, 21],
+        vec![40, 41, 42, 43],
+        vec![60, 61, 62, 63, 64, 65],
+    ];
+    assert_eq!(state.direct, expected);
+    // The last batch is delivered after the timeout
+    assert_eq!(state.delayed, expected[..2]);
+}
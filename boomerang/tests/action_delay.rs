@@ -32,7 +32,7 @@ struct ReactionYIn<'a> {
 impl runtime::Trigger<GeneratedDelayState> for ReactionYIn<'_> {
     fn trigger(mut self, ctx: &mut runtime::Context, state: &mut GeneratedDelayState) {
         state.y_state = self.y_in.unwrap();
-        self.act.schedule(ctx, (), None);
+        self.act.schedule(ctx, (), None).unwrap();
     }
 }
 
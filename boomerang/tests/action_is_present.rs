@@ -30,7 +30,9 @@ impl runtime::Trigger<ActionIsPresentState> for ReactionStartup<'_> {
         if !self.a.is_present(ctx) {
             assert!(!state.success, "Unexpected");
             println!("Hello startup!");
-            self.a.schedule(ctx, (), Some(Duration::nanoseconds(1)));
+            self.a
+                .schedule(ctx, (), Some(Duration::nanoseconds(1)))
+                .unwrap();
         } else {
             println!("Hello a!");
             state.success = true;
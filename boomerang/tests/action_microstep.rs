@@ -0,0 +1,118 @@
+//! Checks scheduling actions a number of microsteps ahead, with repeated zero-delay self-rescheduling and a `min_delay`.
+
+use boomerang::prelude::*;
+
+#[derive(Debug, Default)]
+struct State {
+    /// The (offset, microstep) tags at which `again` was triggered, with its value
+    again: Vec<(Duration, usize, u32)>,
+    /// The tags at which `later` was triggered
+    later: Vec<(Duration, usize)>,
+    /// The tags at which `delayed` was triggered
+    delayed: Vec<(Duration, usize)>,
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "State",
+    reaction = "ReactionStartup",
+    reaction = "ReactionAgain",
+    reaction = "ReactionLater",
+    reaction = "ReactionDelayed"
+)]
+struct Microsteps {
+    again: TypedActionKey<u32>,
+    later: TypedActionKey,
+    #[reactor(action(min_delay = "1 msec"))]
+    delayed: TypedActionKey,
+}
+
+fn tag(ctx: &runtime::Context) -> (Duration, usize) {
+    let tag = ctx.get_tag();
+    (tag.offset(), tag.microstep())
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Microsteps", triggers(startup))]
+struct ReactionStartup<'a> {
+    #[reaction(effects)]
+    again: runtime::ActionRef<'a, u32>,
+    #[reaction(effects)]
+    later: runtime::ActionRef<'a>,
+    #[reaction(effects)]
+    delayed: runtime::ActionRef<'a>,
+}
+
+impl runtime::Trigger<State> for ReactionStartup<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut State) {
+        assert!(matches!(
+            self.later.schedule_microstep(ctx, (), 0),
+            Err(runtime::RuntimeError::TagNotInFuture { .. })
+        ));
+        self.again.schedule_microstep(ctx, 0, 1).unwrap();
+        self.later.schedule_microstep(ctx, (), 3).unwrap();
+        self.delayed.schedule_microstep(ctx, (), 2).unwrap();
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Microsteps")]
+struct ReactionAgain<'a> {
+    #[reaction(triggers, effects)]
+    again: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<State> for ReactionAgain<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut State) {
+        let value = *self.again.get_value(ctx).unwrap();
+        let (offset, microstep) = tag(ctx);
+        state.again.push((offset, microstep, value));
+        if value < 4 {
+            self.again.schedule_microstep(ctx, value + 1, 1).unwrap();
+        }
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Microsteps", triggers(action = "later"))]
+struct ReactionLater;
+
+impl runtime::Trigger<State> for ReactionLater {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut State) {
+        state.later.push(tag(ctx));
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Microsteps", triggers(action = "delayed"))]
+struct ReactionDelayed;
+
+impl runtime::Trigger<State> for ReactionDelayed {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut State) {
+        state.delayed.push(tag(ctx));
+    }
+}
+
+#[test]
+fn action_microstep() {
+    let config = runtime::Config::default().with_fast_forward(true);
+    let (_, sched, key) = boomerang_util::runner::build_and_test_reactor_typed::<Microsteps>(
+        "microsteps",
+        State::default(),
+        config,
+    )
+    .unwrap();
+    let env = sched.into_env();
+    let state = env.state(key);
+
+    // Each reaction reschedules itself at the next microstep, without advancing logical time
+    assert_eq!(
+        state.again,
+        (1..=5)
+            .map(|microstep| (Duration::ZERO, microstep, microstep as u32 - 1))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(state.later, [(Duration::ZERO, 3)]);
+    // With a `min_delay`, the first microstep is microstep 0 of the delayed time
+    assert_eq!(state.delayed, [(Duration::milliseconds(1), 1)]);
+}
@@ -0,0 +1,186 @@
+//! Test scheduling actions at an absolute tag.
+
+use boomerang::prelude::*;
+
+#[derive(Debug, Default)]
+struct State {
+    tags: Vec<runtime::Tag>,
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "State",
+    reaction = "ReactionStartup",
+    reaction = "ReactionAct"
+)]
+struct ScheduleAt {
+    #[reactor(action(min_delay = "100 msec"))]
+    act: TypedActionKey<u32>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ScheduleAt", triggers(startup))]
+struct ReactionStartup<'a> {
+    act: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<State> for ReactionStartup<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut State) {
+        // The current tag is not in the future
+        let res = self.act.schedule_at(ctx, 0, ctx.get_tag());
+        assert!(matches!(
+            res,
+            Err(runtime::RuntimeError::TagNotInFuture { .. })
+        ));
+
+        // `min_delay` is not applied to absolute tags
+        self.act
+            .schedule_at(ctx, 1, runtime::Tag::new(Duration::milliseconds(50), 0))
+            .unwrap();
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ScheduleAt")]
+struct ReactionAct<'a> {
+    #[reaction(triggers)]
+    act: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<State> for ReactionAct<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut State) {
+        let tag = ctx.get_tag();
+        state.tags.push(tag);
+        match self.act.get_value(ctx).copied() {
+            Some(1) => {
+                // Jump ahead by several microsteps at the same time offset
+                self.act
+                    .schedule_at(ctx, 2, runtime::Tag::new(tag.offset(), 3))
+                    .unwrap();
+            }
+            Some(2) => {
+                assert!(self
+                    .act
+                    .schedule_at(ctx, 3, runtime::Tag::new(tag.offset(), 1))
+                    .is_err());
+            }
+            value => panic!("Unexpected value {value:?}"),
+        }
+    }
+}
+
+#[test]
+fn action_schedule_at() {
+    tracing_subscriber::fmt::init();
+    let config = runtime::Config::default().with_fast_forward(true);
+    let (_, sched) = boomerang_util::runner::build_and_test_reactor::<ScheduleAt>(
+        "action_schedule_at",
+        State::default(),
+        config,
+    )
+    .unwrap();
+
+    let env = sched.into_env();
+    let state = env
+        .find_reactor_by_name("action_schedule_at")
+        .and_then(|reactor| reactor.get_state::<State>())
+        .unwrap();
+    assert_eq!(
+        state.tags,
+        vec![
+            runtime::Tag::new(Duration::milliseconds(50), 0),
+            runtime::Tag::new(Duration::milliseconds(50), 3)
+        ]
+    );
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "State",
+    reaction = "ReactionAsyncStartup",
+    reaction = "ReactionTick",
+    reaction = "ReactionRx"
+)]
+struct AsyncScheduleAt {
+    #[reactor(action(min_delay = "100 msec"))]
+    tick: TypedActionKey,
+    rx: TypedActionKey<u32, Physical>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "AsyncScheduleAt", triggers(startup))]
+struct ReactionAsyncStartup<'a> {
+    tick: runtime::ActionRef<'a>,
+}
+
+impl runtime::Trigger<State> for ReactionAsyncStartup<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut State) {
+        self.tick.schedule(ctx, (), None).unwrap();
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "AsyncScheduleAt", triggers(action = "tick"))]
+struct ReactionTick {
+    rx: runtime::AsyncActionRef<u32>,
+}
+
+impl runtime::Trigger<State> for ReactionTick {
+    fn trigger(self, ctx: &mut runtime::Context, _state: &mut State) {
+        let send_ctx = ctx.make_send_context();
+        // In fast-forward mode this is after the current physical time, but behind logical time
+        self.rx
+            .schedule_at(
+                &send_ctx,
+                1,
+                runtime::Tag::new(Duration::milliseconds(50), 0),
+            )
+            .unwrap();
+        self.rx
+            .schedule_at(
+                &send_ctx,
+                2,
+                runtime::Tag::new(Duration::milliseconds(200), 0),
+            )
+            .unwrap();
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "AsyncScheduleAt")]
+struct ReactionRx<'a> {
+    #[reaction(triggers)]
+    rx: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<State> for ReactionRx<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut State) {
+        assert_eq!(self.rx.get_value(ctx), Some(&2));
+        state.tags.push(ctx.get_tag());
+    }
+}
+
+/// An absolute tag behind logical time is dropped by the scheduler, rather than moved to the next microstep.
+#[test]
+fn async_schedule_at_behind_logical_time() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_keep_alive(true)
+        .with_timeout(Duration::milliseconds(300));
+    let (_, sched) = boomerang_util::runner::build_and_test_reactor::<AsyncScheduleAt>(
+        "async_schedule_at",
+        State::default(),
+        config,
+    )
+    .unwrap();
+
+    let env = sched.into_env();
+    let state = env
+        .find_reactor_by_name("async_schedule_at")
+        .and_then(|reactor| reactor.get_state::<State>())
+        .unwrap();
+    assert_eq!(
+        state.tags,
+        vec![runtime::Tag::new(Duration::milliseconds(200), 0)]
+    );
+}
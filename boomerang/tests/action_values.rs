@@ -30,10 +30,11 @@ struct ReactionStartup<'a> {
 impl<'a> runtime::Trigger<State> for ReactionStartup<'a> {
     fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut State) {
         // scheduled in 100 ms
-        self.act.schedule(ctx, 100, None);
+        self.act.schedule(ctx, 100, None).unwrap();
         // scheduled in 150 ms, value is overwritten
         self.act
-            .schedule(ctx, -100, Some(Duration::milliseconds(50)));
+            .schedule(ctx, -100, Some(Duration::milliseconds(50)))
+            .unwrap();
     }
 }
 
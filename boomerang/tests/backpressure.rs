@@ -0,0 +1,103 @@
+//! Checks that the events of physical actions are dropped or coalesced while the queue to the scheduler is full.
+
+use boomerang::prelude::*;
+
+const EVENTS: usize = 5;
+
+#[derive(Debug, Default)]
+struct State {
+    latest: Vec<usize>,
+    lossy: Vec<usize>,
+    coalesced: usize,
+    dropped: usize,
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "State",
+    reaction = "ReactionStartup",
+    reaction = "ReactionLatest",
+    reaction = "ReactionLossy"
+)]
+struct Main {
+    latest: TypedActionKey<usize, Physical>,
+    lossy: TypedActionKey<usize, Physical>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Main", triggers(startup))]
+struct ReactionStartup {
+    latest: runtime::AsyncActionRef<usize>,
+    lossy: runtime::AsyncActionRef<usize>,
+}
+
+impl runtime::Trigger<State> for ReactionStartup {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut State) {
+        // The scheduler doesn't receive any event until this reaction completes
+        let send_ctx = ctx.make_send_context();
+        for i in 0..EVENTS {
+            self.latest.schedule(&send_ctx, i, None);
+        }
+        for i in 0..EVENTS {
+            self.lossy.schedule(&send_ctx, i, None);
+        }
+        state.coalesced = self.latest.coalesced();
+        state.dropped = self.lossy.dropped();
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Main", triggers(action = "latest"))]
+struct ReactionLatest<'a> {
+    latest: runtime::ActionRef<'a, usize>,
+}
+
+impl runtime::Trigger<State> for ReactionLatest<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut State) {
+        state.latest.extend(self.latest.get_value(ctx).copied());
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Main", triggers(action = "lossy"))]
+struct ReactionLossy<'a> {
+    lossy: runtime::ActionRef<'a, usize>,
+}
+
+impl runtime::Trigger<State> for ReactionLossy<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut State) {
+        state.lossy.extend(self.lossy.get_value(ctx).copied());
+    }
+}
+
+#[test]
+fn backpressure() {
+    let mut env_builder = EnvBuilder::new();
+    let _ = Main::build("main", State::default(), None, None, &mut env_builder).unwrap();
+    for (name, policy) in [
+        ("main::latest", runtime::BackpressurePolicy::Coalesce),
+        ("main::lossy", runtime::BackpressurePolicy::Drop),
+    ] {
+        let action = env_builder.find_physical_action_by_fqn(name).unwrap();
+        env_builder.set_backpressure(action, policy).unwrap();
+    }
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    // No timeout, as the shutdown event would take a place in the queue
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_queue_size(2);
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+
+    let env = sched.into_env();
+    let state = env
+        .find_reactor_by_name("main")
+        .and_then(|reactor| reactor.get_state::<State>())
+        .unwrap();
+    // The pending event of `latest` takes one place, the first event of `lossy` the other
+    assert_eq!(state.latest, [EVENTS - 1]);
+    assert_eq!(state.coalesced, EVENTS - 1);
+    assert_eq!(state.lossy, [0]);
+    assert_eq!(state.dropped, EVENTS - 1);
+}
@@ -0,0 +1,145 @@
+//! Checks that unbalanced fork-join delays are reported, and that balancing them aligns the joined values.
+
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionStartup")]
+struct Source {
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(startup))]
+struct ReactionStartup<'a> {
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionStartup<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.out = Some(42);
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionRelay")]
+struct Relay {
+    inp: TypedPortKey<u32, Input>,
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Relay")]
+struct ReactionRelay<'a> {
+    inp: runtime::InputRef<'a, u32>,
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionRelay<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.out = self.inp.map(|v| v + 1);
+    }
+}
+
+/// The values present at each invocation of the join, the relayed value is incremented.
+type Joined = Vec<(Duration, Vec<u32>)>;
+
+#[derive(Reactor)]
+#[reactor(state = "Joined", reaction = "ReactionJoin")]
+struct Join {
+    a: TypedPortKey<u32, Input>,
+    b: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Join")]
+struct ReactionJoin<'a> {
+    a: runtime::InputRef<'a, u32>,
+    b: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<Joined> for ReactionJoin<'_> {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut Joined) {
+        let mut values: Vec<u32> = self.a.iter().chain(self.b.iter()).copied().collect();
+        values.sort();
+        state.push((ctx.get_elapsed_logical_time(), values));
+    }
+}
+
+/// `source` forks into a path delayed through `relay`, and a direct path, which rejoin at `join`.
+#[allow(clippy::duplicated_attributes)]
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to = "relay.inp", after = "2 msec"),
+    connection(from = "relay.out", to = "join.a"),
+    connection(from = "source.out", to = "join.b")
+)]
+struct Main {
+    #[reactor(child = ())]
+    source: Source,
+    #[reactor(child = ())]
+    relay: Relay,
+    #[reactor(child = Joined::new())]
+    join: Join,
+}
+
+fn run(balance: bool) -> Joined {
+    let mut env_builder = EnvBuilder::new();
+    let _ = Main::build("main", (), None, None, &mut env_builder).unwrap();
+
+    let report = env_builder.delay_report().unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].fork_fqn, "main::source::ReactionStartup");
+    assert_eq!(report[0].join_fqn, "main::join::ReactionJoin");
+    assert_eq!(
+        report[0].delays,
+        vec![Duration::ZERO, Duration::milliseconds(2)]
+    );
+
+    if balance {
+        let adjustments = env_builder.balance_delays().unwrap();
+        assert_eq!(adjustments.len(), 1);
+        assert_eq!(
+            adjustments[0].target,
+            env_builder.find_port_by_fqn("main::join::b").unwrap()
+        );
+        assert_eq!(adjustments[0].after, Duration::milliseconds(2));
+        assert!(env_builder.delay_report().unwrap().is_empty());
+    }
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let mut sched = runtime::Scheduler::new(
+        env,
+        graph,
+        runtime::Config::default().with_fast_forward(true),
+    );
+    sched.event_loop().unwrap();
+    sched
+        .into_env()
+        .find_reactor_by_name("join")
+        .and_then(|reactor| reactor.get_state::<Joined>())
+        .cloned()
+        .unwrap()
+}
+
+#[test]
+fn unbalanced_fork_join() {
+    assert_eq!(
+        run(false),
+        vec![
+            (Duration::ZERO, vec![42]),
+            (Duration::milliseconds(2), vec![43])
+        ]
+    );
+}
+
+#[test]
+fn balanced_fork_join() {
+    let joined = run(true);
+    assert!(joined
+        .iter()
+        .all(|(tag, _)| *tag == Duration::milliseconds(2)));
+    let mut values: Vec<u32> = joined.into_iter().flat_map(|(_, values)| values).collect();
+    values.sort();
+    assert_eq!(values, vec![42, 43]);
+}
@@ -0,0 +1,87 @@
+//! Checks bank-wide reductions over the present values of a bank of inputs.
+
+use boomerang::prelude::*;
+
+/// Votes with ten times its bank index, but only odd members vote.
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionVote")]
+struct Voter {
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Voter", triggers(startup))]
+struct ReactionVote<'a> {
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionVote<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut ()) {
+        let index = ctx.get_bank_index().unwrap();
+        if index % 2 == 1 {
+            *self.out = Some(index as u32 * 10);
+        }
+    }
+}
+
+/// The reductions over the votes: the number of votes, their sum, and the index of the highest vote.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Tallied {
+    count: usize,
+    sum: u32,
+    highest: Option<usize>,
+}
+
+#[derive(Reactor)]
+#[reactor(state = "Tallied", reaction = "ReactionTally<N>")]
+struct Tally<const N: usize> {
+    inp: [TypedPortKey<u32, Input>; N],
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Tally<N>")]
+struct ReactionTally<'a, const N: usize> {
+    inp: [runtime::InputRef<'a, u32>; N],
+}
+
+impl<const N: usize> runtime::Trigger<Tallied> for ReactionTally<'_, N> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut Tallied) {
+        state.count = self.inp.count_present();
+        state.sum = self.inp.iter_present().map(|(_, vote)| vote).sum();
+        state.highest = self
+            .inp
+            .iter_present()
+            .max_by_key(|&(_, vote)| vote)
+            .map(|(index, _)| index);
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(state = "()", connection(from = "voters.out", to = "tally.inp"))]
+struct Main {
+    #[reactor(child = ())]
+    voters: [Voter; 5],
+    #[reactor(child = Tallied::default())]
+    tally: Tally<5>,
+}
+
+#[test]
+fn bank_inputs() {
+    let config = runtime::Config::default().with_fast_forward(true);
+    let (_, sched) =
+        boomerang_util::runner::build_and_test_reactor::<Main>("main", (), config).unwrap();
+    let env = sched.into_env();
+    let tallied = env
+        .find_reactor_by_name("tally")
+        .and_then(|reactor| reactor.get_state::<Tallied>())
+        .unwrap();
+    assert_eq!(
+        tallied,
+        &Tallied {
+            count: 2,
+            sum: 40,
+            highest: Some(3),
+        }
+    );
+}
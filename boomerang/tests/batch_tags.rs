@@ -0,0 +1,101 @@
+//! Checks that batching ready tags in fast-forward mode gives the same results as processing them one at a time.
+
+use boomerang::prelude::*;
+
+/// Emits the count on `fast` at every tick, and on `slow` at every third tick.
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionTick")]
+struct Source {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    fast: TypedPortKey<u32, Output>,
+    slow: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(action = "tick"))]
+struct ReactionTick<'a> {
+    fast: runtime::OutputRef<'a, u32>,
+    slow: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionTick<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u32) {
+        self.fast.set_from(Some(*state));
+        self.slow
+            .set_from(state.is_multiple_of(3).then_some(*state));
+        *state += 1;
+    }
+}
+
+type Joined = Vec<(u32, Option<u32>)>;
+
+/// Records the value of `slow` whenever `fast` is present.
+#[derive(Reactor)]
+#[reactor(state = "Joined", reaction = "ReactionJoin")]
+struct Join {
+    fast: TypedPortKey<u32, Input>,
+    slow: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Join")]
+struct ReactionJoin<'a> {
+    fast: runtime::InputRef<'a, u32>,
+    /// A value left over from an earlier tag would show up here
+    #[reaction(uses)]
+    slow: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<Joined> for ReactionJoin<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut Joined) {
+        state.push((self.fast.unwrap(), *self.slow));
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.fast", to = "join.fast"),
+    connection(from = "source.slow", to = "join.slow")
+)]
+struct Main {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = Joined::new())]
+    join: Join,
+}
+
+fn run(batch_tags: Option<usize>) -> Joined {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(7));
+    let config = match batch_tags {
+        Some(batch_tags) => config.with_batch_tags(batch_tags),
+        None => config,
+    };
+    let (_, sched) =
+        boomerang_util::runner::build_and_test_reactor::<Main>("main", (), config).unwrap();
+    let env = sched.into_env();
+    env.find_reactor_by_name("join")
+        .and_then(|reactor| reactor.get_state::<Joined>())
+        .unwrap()
+        .clone()
+}
+
+#[test]
+fn batch_tags() {
+    let expected = vec![
+        (0, Some(0)),
+        (1, None),
+        (2, None),
+        (3, Some(3)),
+        (4, None),
+        (5, None),
+        (6, Some(6)),
+        (7, None),
+    ];
+    assert_eq!(run(None), expected);
+    assert_eq!(run(Some(3)), expected);
+    assert_eq!(run(Some(64)), expected);
+}
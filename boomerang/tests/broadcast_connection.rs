@@ -0,0 +1,203 @@
+//! Checks that delayed connections from one source share a single connection reactor, and that balancing the delay to
+//! one of its targets gives that target a connection of its own.
+
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionStartup")]
+struct Source {
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(startup))]
+struct ReactionStartup<'a> {
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionStartup<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.out = Some(42);
+    }
+}
+
+/// The values received, with the logical time.
+type Received = Vec<(Duration, u32)>;
+
+#[derive(Reactor)]
+#[reactor(state = "Received", reaction = "ReactionSink")]
+struct Sink {
+    inp: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink")]
+struct ReactionSink<'a> {
+    inp: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<Received> for ReactionSink<'_> {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut Received) {
+        state.extend(
+            self.inp
+                .map(|value| (ctx.get_elapsed_logical_time(), value)),
+        );
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to = "sinks.inp", after = "1 msec", broadcast)
+)]
+struct FanOut {
+    #[reactor(child = ())]
+    source: Source,
+    #[reactor(child = Received::new())]
+    sinks: [Sink; 8],
+}
+
+/// The names of the connection reactors, sorted.
+fn connection_names(env: &runtime::Env) -> Vec<String> {
+    let mut names: Vec<String> = env
+        .reactors
+        .values()
+        .map(|reactor| reactor.name().to_owned())
+        .filter(|name| name.starts_with("connection_"))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn broadcast_connection() {
+    let mut env_builder = EnvBuilder::new();
+    let _ = FanOut::build("main", (), None, None, &mut env_builder).unwrap();
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    assert_eq!(
+        connection_names(&env),
+        ["connection_main::source::out->main::sinks[0]::inp"]
+    );
+    // The source, the sinks and a single pair of connection reactions
+    assert_eq!(env.reactions.len(), 1 + 8 + 2);
+
+    let mut sched = runtime::Scheduler::new(
+        env,
+        graph,
+        runtime::Config::default().with_fast_forward(true),
+    );
+    sched.event_loop().unwrap();
+    let env = sched.into_env();
+    for reactor in env.reactors.values() {
+        if let Some(received) = reactor.get_state::<Received>() {
+            assert_eq!(received, &[(Duration::milliseconds(1), 42)]);
+        }
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionRelay")]
+struct Relay {
+    inp: TypedPortKey<u32, Input>,
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Relay")]
+struct ReactionRelay<'a> {
+    inp: runtime::InputRef<'a, u32>,
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionRelay<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.out = self.inp.map(|v| v + 1);
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "Received", reaction = "ReactionJoin")]
+struct Join {
+    a: TypedPortKey<u32, Input>,
+    b: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Join")]
+struct ReactionJoin<'a> {
+    a: runtime::InputRef<'a, u32>,
+    b: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<Received> for ReactionJoin<'_> {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut Received) {
+        let elapsed = ctx.get_elapsed_logical_time();
+        state.extend(self.a.iter().chain(self.b.iter()).map(|&v| (elapsed, v)));
+    }
+}
+
+/// `source` forks through a shared connection into a path delayed further through `relay`, and a path straight to
+/// `join`.
+#[allow(clippy::duplicated_attributes)]
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to = "join.b", after = "1 msec"),
+    connection(from = "source.out", to = "relay.inp", after = "1 msec"),
+    connection(from = "relay.out", to = "join.a", after = "2 msec")
+)]
+struct ForkJoin {
+    #[reactor(child = ())]
+    source: Source,
+    #[reactor(child = ())]
+    relay: Relay,
+    #[reactor(child = Received::new())]
+    join: Join,
+}
+
+#[test]
+fn balance_shared_connection() {
+    let mut env_builder = EnvBuilder::new();
+    let _ = ForkJoin::build("main", (), None, None, &mut env_builder).unwrap();
+    let adjustments = env_builder.balance_delays().unwrap();
+    assert_eq!(adjustments.len(), 1);
+    assert_eq!(
+        adjustments[0].target,
+        env_builder.find_port_by_fqn("main::join::b").unwrap()
+    );
+    assert_eq!(adjustments[0].after, Duration::milliseconds(3));
+    assert!(env_builder.delay_report().unwrap().is_empty());
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    // The shared connection is renamed after its remaining target
+    assert_eq!(
+        connection_names(&env),
+        [
+            "connection_main::relay::out->main::join::a",
+            "connection_main::source::out->main::join::b",
+            "connection_main::source::out->main::relay::inp",
+        ]
+    );
+
+    let mut sched = runtime::Scheduler::new(
+        env,
+        graph,
+        runtime::Config::default().with_fast_forward(true),
+    );
+    sched.event_loop().unwrap();
+    let mut received = sched
+        .into_env()
+        .find_reactor_by_name("join")
+        .and_then(|reactor| reactor.get_state::<Received>())
+        .cloned()
+        .unwrap();
+    received.sort();
+    assert_eq!(
+        received,
+        [
+            (Duration::milliseconds(3), 42),
+            (Duration::milliseconds(3), 43)
+        ]
+    );
+}
@@ -0,0 +1,67 @@
+//! Checks the sampled execution time budgets of reactions.
+
+use boomerang::builder::{reaction_closure, TimerSpec, TriggerMode};
+use boomerang::prelude::*;
+
+#[test]
+fn budget() {
+    let mut env_builder = EnvBuilder::new();
+    let mut reactor = env_builder.add_reactor("main", None, None, ());
+    let tick = reactor
+        .add_timer(
+            "tick",
+            TimerSpec {
+                period: Some(Duration::milliseconds(1)),
+                offset: None,
+            },
+        )
+        .unwrap();
+    reactor
+        .add_reaction(
+            "slow",
+            reaction_closure!(_ctx, _reactor, _ref_ports, _mut_ports, _actions => {
+                std::thread::sleep(std::time::Duration::from_millis(2));
+            }),
+        )
+        .with_action(tick, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .with_budget(Duration::milliseconds(1))
+        .finish()
+        .unwrap();
+    reactor
+        .add_reaction("fast", reaction_closure!())
+        .with_action(tick, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .with_budget(Duration::seconds(1))
+        .finish()
+        .unwrap();
+    reactor.finish().unwrap();
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(9))
+        .with_budget_sampling(4);
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    let events = sched.subscribe();
+    sched.event_loop().unwrap();
+
+    // Of the 10 invocations at 0..=9ms, those at 0, 4 and 8ms are sampled
+    let stats = sched.budget_stats();
+    assert_eq!(stats.len(), 2);
+    assert!(stats
+        .iter()
+        .all(|stats| stats.invocations == 10 && stats.samples == 3));
+
+    let offenders = sched.budget_offenders();
+    assert_eq!(offenders.len(), 1);
+    assert!(offenders[0].reaction.ends_with("slow"));
+    assert_eq!(offenders[0].exceeded, 3);
+    assert!(offenders[0].max >= Duration::milliseconds(2));
+
+    let exceeded = events
+        .try_iter()
+        .filter(|event| matches!(event, runtime::RuntimeEvent::BudgetExceeded { .. }))
+        .count();
+    assert_eq!(exceeded, 3);
+}
@@ -0,0 +1,140 @@
+//! Test named buses connecting ports at different levels of the hierarchy.
+
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionStartup")]
+struct Source {
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(startup))]
+struct ReactionStartup<'a> {
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionStartup<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u32) {
+        *self.out = Some(*state);
+    }
+}
+
+/// The last value received by a [`Sink`].
+type Received = Option<u32>;
+
+#[derive(Reactor)]
+#[reactor(state = "Received", reaction = "ReactionIn")]
+struct Sink {
+    inp: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink")]
+struct ReactionIn<'a> {
+    inp: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<Received> for ReactionIn<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut Received) {
+        *state = *self.inp;
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct NestedSource {
+    #[reactor(child = 2)]
+    source: Source,
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct NestedSink {
+    #[reactor(child = None)]
+    inner_sink: Sink,
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct Main {
+    #[reactor(child = 1)]
+    source: Source,
+    #[reactor(child = ())]
+    nested_source: NestedSource,
+    #[reactor(child = None)]
+    sink: Sink,
+    #[reactor(child = ())]
+    nested_sink: NestedSink,
+}
+
+fn run_bus(policy: MergePolicy<u32>) -> (Option<u32>, Option<u32>) {
+    let mut env_builder = EnvBuilder::new();
+    env_builder.add_bus("values", policy).unwrap();
+    let main = Main::build("main", (), None, None, &mut env_builder).unwrap();
+    env_builder
+        .attach_bus_producer("values", main.source.out)
+        .unwrap();
+    env_builder
+        .attach_bus_producer("values", main.nested_source.source.out)
+        .unwrap();
+    env_builder
+        .attach_bus_consumer("values", main.sink.inp)
+        .unwrap();
+    env_builder
+        .attach_bus_consumer("values", main.nested_sink.inner_sink.inp)
+        .unwrap();
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default().with_fast_forward(true);
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+
+    let env = sched.into_env();
+    let get = |name| {
+        *env.find_reactor_by_name(name)
+            .and_then(|reactor| reactor.get_state::<Received>())
+            .unwrap()
+    };
+    (get("sink"), get("inner_sink"))
+}
+
+#[test]
+fn bus_merge_policies() {
+    assert_eq!(run_bus(MergePolicy::First), (Some(1), Some(1)));
+    assert_eq!(run_bus(MergePolicy::Last), (Some(2), Some(2)));
+    assert_eq!(
+        run_bus(MergePolicy::Reduce(|a, b| a + b)),
+        (Some(3), Some(3))
+    );
+}
+
+#[test]
+fn bus_errors() {
+    let mut env_builder = EnvBuilder::new();
+    env_builder
+        .add_bus::<u32>("values", MergePolicy::First)
+        .unwrap();
+    let main = Main::build("main", (), None, None, &mut env_builder).unwrap();
+
+    assert!(matches!(
+        env_builder.add_bus::<u32>("values", MergePolicy::Last),
+        Err(BuilderError::DuplicateBusDefinition(_))
+    ));
+    assert!(matches!(
+        env_builder.attach_bus_producer("missing", main.source.out),
+        Err(BuilderError::NamedBusNotFound(_))
+    ));
+
+    let source_key = env_builder
+        .get_port(main.source.out.into())
+        .unwrap()
+        .get_reactor_key();
+    let other = env_builder
+        .add_output_port::<bool>("other", source_key)
+        .unwrap();
+    assert!(matches!(
+        env_builder.attach_bus_producer("values", other),
+        Err(BuilderError::BusError { .. })
+    ));
+}
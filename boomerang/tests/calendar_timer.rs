@@ -0,0 +1,42 @@
+//! Checks that a calendar timer triggers at the occurrences of its schedule in logical time.
+
+use std::sync::{Arc, Mutex};
+
+use boomerang::builder::{reaction_closure, TriggerMode};
+use boomerang::prelude::*;
+
+#[test]
+fn calendar_timer() {
+    let mut env_builder = EnvBuilder::new();
+    let mut reactor = env_builder.add_reactor("main", None, None, ());
+    let spec = "0 9 * * MON-FRI".parse::<runtime::CalendarSpec>().unwrap();
+    let standup = reactor.add_calendar_timer("standup", spec).unwrap();
+
+    let triggered = Arc::new(Mutex::new(Vec::new()));
+    let record = triggered.clone();
+    reactor
+        .add_reaction(
+            "record",
+            reaction_closure!(ctx, _reactor, _ref_ports, _mut_ports, _actions => {
+                record.lock().unwrap().push(ctx.get_elapsed_logical_time());
+            }),
+        )
+        .with_action(standup, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .finish()
+        .unwrap();
+    reactor.finish().unwrap();
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::days(14));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+
+    // Logical time 0 is Monday 2024-01-01 00:00, so the first two weeks have ten weekdays
+    let expected = [0, 1, 2, 3, 4, 7, 8, 9, 10, 11]
+        .map(|day| Duration::days(day) + Duration::hours(9))
+        .to_vec();
+    assert_eq!(*triggered.lock().unwrap(), expected);
+}
@@ -0,0 +1,144 @@
+//! Checks that long-running reactions are cancelled cooperatively on deadline violations and shutdown requests.
+
+use boomerang::builder::{reaction_closure, TriggerMode};
+use boomerang::prelude::*;
+
+/// Spin until the reaction is cancelled, giving up after a generous bound so a failure can't hang the test.
+fn spin_until_cancelled(ctx: &runtime::Context) -> Option<runtime::CancelReason> {
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(5) {
+        if let Some(reason) = ctx.cancellation().reason() {
+            return Some(reason);
+        }
+        std::thread::yield_now();
+    }
+    None
+}
+
+type Reasons = Vec<Option<runtime::CancelReason>>;
+
+fn run(deadline: Option<Duration>, request_shutdown: bool) -> Reasons {
+    let mut env_builder = EnvBuilder::new();
+    let reactor = env_builder.add_reactor("main", None, None, Reasons::new());
+    let startup = reactor.get_startup_action();
+    let reactor_key = reactor.finish().unwrap();
+
+    let reaction = env_builder
+        .add_reaction(
+            "spin",
+            reactor_key,
+            reaction_closure!(ctx, reactor, _ref_ports, _mut_ports, _actions => {
+                if request_shutdown {
+                    let mut send_ctx = ctx.make_send_context();
+                    std::thread::spawn(move || {
+                        std::thread::sleep(std::time::Duration::from_millis(5));
+                        send_ctx.schedule_shutdown(None);
+                    });
+                }
+                let reason = spin_until_cancelled(ctx);
+                reactor
+                    .downcast_mut::<runtime::Reactor<Reasons>>()
+                    .unwrap()
+                    .state
+                    .push(reason);
+            }),
+        )
+        .with_action(startup, 0, TriggerMode::TriggersOnly)
+        .unwrap();
+    let reaction = match deadline {
+        Some(deadline) => reaction.with_deadline(deadline, || {}),
+        None => reaction,
+    };
+    reaction.finish().unwrap();
+
+    let (env, graph, aliases) = env_builder.into_runtime_parts().unwrap();
+    let key = env
+        .typed_reactor_key::<Reasons>(aliases.reactor_aliases[reactor_key])
+        .unwrap();
+    let mut sched = runtime::Scheduler::new(env, graph, runtime::Config::default());
+    sched.event_loop().unwrap();
+    sched.into_env().state(key).clone()
+}
+
+#[test]
+fn cancel_on_deadline() {
+    assert_eq!(
+        run(Some(Duration::milliseconds(5)), false),
+        vec![Some(runtime::CancelReason::DeadlineViolated)]
+    );
+}
+
+#[test]
+fn cancel_on_shutdown_request() {
+    assert_eq!(
+        run(None, true),
+        vec![Some(runtime::CancelReason::ShutdownRequested)]
+    );
+}
+
+/// Schedule a shutdown 30 msec ahead at startup, and record the cancellation at a tick 10 msec in and at shutdown.
+fn run_delayed_shutdown(asynchronous: bool) -> Reasons {
+    let mut env_builder = EnvBuilder::new();
+    let mut reactor = env_builder.add_reactor("main", None, None, Reasons::new());
+    let startup = reactor.get_startup_action();
+    let shutdown = reactor.get_shutdown_action();
+    let tick = reactor.add_logical_action::<()>("tick", None).unwrap();
+
+    reactor
+        .add_reaction(
+            "start",
+            reaction_closure!(ctx, _reactor, _ref_ports, _mut_ports, actions => {
+                let mut tick: runtime::ActionRef = actions.partition_mut().unwrap();
+                tick.schedule(ctx, (), Some(Duration::milliseconds(10))).unwrap();
+                if asynchronous {
+                    ctx.make_send_context().schedule_shutdown(Some(Duration::milliseconds(30)));
+                } else {
+                    ctx.schedule_shutdown(Some(Duration::milliseconds(30)));
+                }
+            }),
+        )
+        .with_action(startup, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .with_action(tick, 1, TriggerMode::EffectsOnly)
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    reactor
+        .add_reaction(
+            "record",
+            reaction_closure!(ctx, reactor, _ref_ports, _mut_ports, _actions => {
+                reactor
+                    .downcast_mut::<runtime::Reactor<Reasons>>()
+                    .unwrap()
+                    .state
+                    .push(ctx.cancellation().reason());
+            }),
+        )
+        .with_action(tick, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .with_action(shutdown, 1, TriggerMode::TriggersOnly)
+        .unwrap()
+        .finish()
+        .unwrap();
+    let reactor = reactor.finish().unwrap();
+
+    let (env, graph, aliases) = env_builder.into_runtime_parts().unwrap();
+    let key = env
+        .typed_reactor_key::<Reasons>(aliases.reactor_aliases[reactor])
+        .unwrap();
+    let mut sched = runtime::Scheduler::new(env, graph, runtime::Config::default());
+    sched.event_loop().unwrap();
+    sched.into_env().state(key).clone()
+}
+
+#[test]
+fn delayed_shutdown_cancels_only_at_shutdown_tag() {
+    for asynchronous in [false, true] {
+        assert_eq!(
+            run_delayed_shutdown(asynchronous),
+            vec![None, Some(runtime::CancelReason::ShutdownRequested)],
+            "asynchronous: {asynchronous}"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Checks the coalescing policies of delayed connections with a producer outpacing the connection delay.
+
+mod common;
+
+use boomerang::prelude::*;
+use common::Source;
+
+type Received = Vec<(Duration, u32)>;
+
+/// Records the received values with their logical time.
+#[derive(Reactor)]
+#[reactor(state = "Received", reaction = "ReactionInp")]
+struct Sink {
+    inp: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink")]
+struct ReactionInp<'a> {
+    inp: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<Received> for ReactionInp<'_> {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut Received) {
+        state.extend(
+            self.inp
+                .map(|value| (ctx.get_elapsed_logical_time(), value)),
+        );
+    }
+}
+
+// Fanning out a port takes multiple `connection` attributes with the same `from`
+#[allow(clippy::duplicated_attributes)]
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to = "all.inp", after = "10 msec"),
+    connection(
+        from = "source.out",
+        to = "latest.inp",
+        after = "10 msec",
+        coalesce = "keep_latest"
+    ),
+    connection(
+        from = "source.out",
+        to = "first.inp",
+        after = "10 msec",
+        coalesce = "keep_first"
+    )
+)]
+struct Main {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = Received::new())]
+    all: Sink,
+    #[reactor(child = Received::new())]
+    latest: Sink,
+    #[reactor(child = Received::new())]
+    first: Sink,
+}
+
+#[test]
+fn coalesce() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(30));
+    let (_, sched) =
+        boomerang_util::runner::build_and_test_reactor::<Main>("coalesce", (), config).unwrap();
+    let env = sched.into_env();
+    let received = |name: &str| {
+        env.find_reactor_by_name(name)
+            .and_then(|reactor| reactor.get_state::<Received>())
+            .cloned()
+            .unwrap()
+    };
+
+    let all = received("all");
+    assert_eq!(all.len(), 21);
+    assert!(all
+        .iter()
+        .all(|&(time, value)| time == Duration::milliseconds(value as i64 + 10)));
+
+    let ms = Duration::milliseconds;
+    assert_eq!(
+        received("latest"),
+        vec![(ms(10), 9), (ms(20), 19), (ms(30), 29)]
+    );
+    assert_eq!(
+        received("first"),
+        vec![(ms(10), 0), (ms(20), 10), (ms(30), 20)]
+    );
+}
+
+#[test]
+fn coalesce_requires_delay() {
+    let mut env_builder = EnvBuilder::new();
+    let source = Source::build("source", 0, None, None, &mut env_builder).unwrap();
+    let sink = Sink::build("sink", Received::new(), None, None, &mut env_builder).unwrap();
+    assert!(matches!(
+        env_builder.connect_ports_coalesced::<u32, _, _>(
+            source.out,
+            sink.inp,
+            None,
+            false,
+            Coalesce::KeepLatest
+        ),
+        Err(BuilderError::PortConnectionError { .. })
+    ));
+}
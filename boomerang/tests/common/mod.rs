@@ -0,0 +1,45 @@
+//! Reactors shared by the integration tests, included with `mod common;`.
+#![allow(dead_code)]
+
+use boomerang::prelude::*;
+
+/// Emits an increasing count every msec.
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionTick")]
+pub struct Source {
+    #[reactor(timer(period = "1 msec"))]
+    pub tick: TimerActionKey,
+    pub out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(action = "tick"))]
+struct ReactionTick<'a> {
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionTick<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u32) {
+        *self.out = Some(*state);
+        *state += 1;
+    }
+}
+
+/// Records all received values.
+#[derive(Reactor)]
+#[reactor(state = "Vec::<u32>", reaction = "ReactionInp")]
+pub struct Sink {
+    pub inp: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink")]
+struct ReactionInp<'a> {
+    inp: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<Vec<u32>> for ReactionInp<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut Vec<u32>) {
+        state.extend(*self.inp);
+    }
+}
@@ -0,0 +1,50 @@
+//! Checks that several separately-authored top-level reactors can be built and wired together by the runner.
+
+mod common;
+
+use boomerang::prelude::*;
+use boomerang_util::runner::{build_and_test_composition, top_level};
+use common::{Sink, Source};
+
+#[test]
+fn composition() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(3));
+    let (_, sched) = build_and_test_composition(
+        (
+            top_level::<Source>("source", 10),
+            top_level::<Sink>("sink", Vec::new()),
+        ),
+        |env_builder, (source, sink)| {
+            env_builder.connect_ports::<u32, _, _>(source.out, sink.inp, None, false)?;
+            Ok(())
+        },
+        config,
+    )
+    .unwrap();
+
+    let env = sched.into_env();
+    let received = env
+        .find_reactor_by_name("sink")
+        .and_then(|reactor| reactor.get_state::<Vec<u32>>())
+        .unwrap();
+    assert_eq!(received, &[10, 11, 12, 13]);
+}
+
+#[test]
+fn composition_wiring_error() {
+    let result = build_and_test_composition(
+        (
+            top_level::<Source>("source", 0),
+            top_level::<Sink>("sink", Vec::new()),
+        ),
+        |env_builder, (source, sink)| {
+            env_builder.connect_ports::<u32, _, _>(sink.inp, source.out, None, false)?;
+            Ok(())
+        },
+        runtime::Config::default().with_fast_forward(true),
+    );
+    let err = result.err().unwrap();
+    assert_eq!(err.to_string(), "Error wiring top-level reactors!");
+}
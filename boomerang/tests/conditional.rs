@@ -0,0 +1,77 @@
+//! Checks that an optional child is only built with its flag, and that the connections to it are pruned otherwise.
+
+mod common;
+
+use boomerang::builder::{BuilderFqn, Reactor as _};
+use boomerang::prelude::*;
+use common::{Sink, Source};
+
+#[derive(Reactor)]
+#[reactor(state = "()", connection(from = "source.out", to = "diag.inp"))]
+#[allow(dead_code)]
+struct Main<const DIAG: bool> {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = DIAG.then(Vec::new))]
+    diag: Option<Sink>,
+}
+
+fn run(env_builder: EnvBuilder) -> runtime::Env {
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(3));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+    sched.into_env()
+}
+
+#[test]
+fn conditional_enabled() -> Result<(), BuilderError> {
+    let mut env_builder = EnvBuilder::new();
+    let main = Main::<true>::build("main", (), None, None, &mut env_builder)?;
+    assert!(main.diag.is_some());
+    assert_eq!(env_builder.excluded_subtrees().count(), 0);
+
+    let env = run(env_builder);
+    let recorded = env
+        .find_reactor_by_name("diag")
+        .and_then(|reactor| reactor.get_state::<Vec<u32>>())
+        .unwrap();
+    assert_eq!(recorded, &[0, 1, 2, 3]);
+    Ok(())
+}
+
+#[test]
+fn conditional_disabled() -> Result<(), BuilderError> {
+    let mut env_builder = EnvBuilder::new();
+    let main = Main::<false>::build("main", (), None, None, &mut env_builder)?;
+    assert!(main.diag.is_none());
+    assert_eq!(
+        env_builder.excluded_subtrees().collect::<Vec<_>>(),
+        [&BuilderFqn::try_from("main::diag")?]
+    );
+
+    // Connections by name to the excluded subtree are pruned, other lookups fail naming the subtree
+    env_builder.connect::<u32>("main::source::out", "main::diag::inp", None, false)?;
+    let err = env_builder
+        .find_port_by_fqn("main::diag::inp")
+        .expect_err("The port was excluded");
+    assert!(
+        matches!(&err, BuilderError::ExcludedElement { subtree, .. } if subtree == "main::diag"),
+        "{err}"
+    );
+
+    // A child added with a disabled flag
+    env_builder.set_parameter("extras", false);
+    let mut builder = env_builder.add_reactor("other", None, None, ());
+    let flag = builder.flag("extras");
+    let extra = builder.child_if::<Sink>(flag, "extra", Vec::new())?;
+    builder.finish()?;
+    assert!(extra.is_none());
+    assert!(env_builder.find_reactor_by_fqn("other::extra").is_err());
+
+    let env = run(env_builder);
+    assert!(env.find_reactor_by_name("diag").is_none());
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! Checks the message counts and latencies recorded by connection monitors.
+#![cfg(feature = "connection_stats")]
+
+mod common;
+
+use boomerang::prelude::*;
+use common::Source;
+
+/// Counts the values received.
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionInp")]
+struct Sink {
+    inp: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink")]
+struct ReactionInp<'a> {
+    inp: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionInp<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut u32) {
+        *state += u32::from(self.inp.is_some());
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to = "delayed.inp", after = "5 msec")
+)]
+struct Main {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = 0)]
+    delayed: Sink,
+    #[reactor(child = 0)]
+    direct: Sink,
+}
+
+#[test]
+fn connection_stats() -> Result<(), BuilderError> {
+    let mut env_builder = EnvBuilder::new();
+    let main = Main::build("main", (), None, None, &mut env_builder)?;
+    env_builder.connect_ports::<u32, _, _>(main.source.out, main.direct.inp, None, false)?;
+
+    let monitor = env_builder.connection_monitor("main::source::out", "main::delayed::inp")?;
+    assert!(env_builder
+        .connection_monitor("main::source::out", "main::direct::inp")
+        .is_err());
+    assert_eq!(
+        env_builder
+            .connection_monitors()?
+            .into_keys()
+            .collect::<Vec<_>>(),
+        [(
+            "main::source::out".to_owned(),
+            "main::delayed::inp".to_owned()
+        )]
+    );
+
+    let (env, graph, _) = env_builder.into_runtime_parts()?;
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(20));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+
+    let stats = monitor.stats();
+    // Values sent after 15 msec are still in flight at the timeout
+    assert_eq!(stats.received, 21);
+    assert_eq!(stats.scheduled, 21);
+    assert_eq!(stats.dropped, 0);
+    assert_eq!(stats.delivered, 16);
+    let delay = stats.delay.unwrap();
+    assert_eq!(
+        (delay.min, delay.max),
+        (Duration::milliseconds(5), Duration::milliseconds(5))
+    );
+    assert!(stats.lag.is_some());
+
+    let env = sched.into_env();
+    let received = |name: &str| {
+        env.find_reactor_by_name(name)
+            .and_then(|reactor| reactor.get_state::<u32>())
+            .copied()
+    };
+    assert_eq!(received("delayed"), Some(16));
+    assert_eq!(received("direct"), Some(21));
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! Checks that the result of a deferred computation is scheduled at the tag fixed when it was spawned, however long the
+//! computation takes.
+
+use boomerang::prelude::*;
+
+/// The events handled, with the logical time in usec.
+type Log = Vec<(i128, String)>;
+
+#[derive(Reactor)]
+#[reactor(
+    state = "Log",
+    reaction = "ReactionStartup",
+    reaction = "ReactionTick",
+    reaction = "ReactionResult"
+)]
+struct Worker {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    result: TypedActionKey<u32>,
+    external: TypedActionKey<u32, Physical>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Worker", triggers(startup))]
+struct ReactionStartup<'a> {
+    result: runtime::ActionRef<'a, u32>,
+    external: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<Log> for ReactionStartup<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut Log) {
+        assert!(matches!(
+            ctx.spawn_deferred(|| 0, &mut self.result, Duration::ZERO),
+            Err(runtime::RuntimeError::InvalidDeferral { .. })
+        ));
+        assert!(matches!(
+            ctx.spawn_deferred(|| 0, &mut self.external, Duration::milliseconds(1)),
+            Err(runtime::RuntimeError::InvalidDeferral { .. })
+        ));
+
+        // Much slower than the timer runs in fast-forward mode
+        let slow = || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            42
+        };
+        ctx.spawn_deferred(slow, &mut self.result, Duration::microseconds(2500))
+            .unwrap();
+        state.push((0, "spawned".to_owned()));
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Worker", triggers(action = "tick"))]
+struct ReactionTick;
+
+impl runtime::Trigger<Log> for ReactionTick {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut Log) {
+        let elapsed = ctx.get_elapsed_logical_time().whole_microseconds();
+        state.push((elapsed, "tick".to_owned()));
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Worker")]
+struct ReactionResult<'a> {
+    #[reaction(triggers)]
+    result: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<Log> for ReactionResult<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut Log) {
+        let elapsed = ctx.get_elapsed_logical_time().whole_microseconds();
+        let value = self.result.get_value(ctx).copied();
+        state.push((elapsed, format!("result {value:?}")));
+    }
+}
+
+#[test]
+fn deferred() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(4));
+    let (_, sched) =
+        boomerang_util::runner::build_and_test_reactor::<Worker>("worker", Log::new(), config)
+            .unwrap();
+    let env = sched.into_env();
+    let log = env
+        .find_reactor_by_name("worker")
+        .and_then(|reactor| reactor.get_state::<Log>())
+        .unwrap();
+    let expected = [
+        (0, "spawned"),
+        (0, "tick"),
+        (1000, "tick"),
+        (2000, "tick"),
+        (2500, "result Some(42)"),
+        (3000, "tick"),
+        (4000, "tick"),
+    ]
+    .map(|(elapsed, what)| (elapsed, what.to_owned()));
+    assert_eq!(log, &expected);
+}
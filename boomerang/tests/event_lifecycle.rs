@@ -0,0 +1,138 @@
+//! Checks that the lifecycle of the events matching the event filter is logged and correlated by event id.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use boomerang::prelude::*;
+use common::Source;
+use tracing_subscriber::util::SubscriberInitExt;
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionInp")]
+struct Sink {
+    inp: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink")]
+struct ReactionInp<'a> {
+    inp: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionInp<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        assert!(self.inp.is_some());
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()", connection(from = "source.out", to = "sink.inp"))]
+struct Main {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = ())]
+    sink: Sink,
+}
+
+/// Collects the formatted log output.
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Get the value of the field `name` in a formatted log line.
+fn field<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let start = line.find(&format!(" {name}="))? + name.len() + 2;
+    let value = line[start..].split(' ').next()?;
+    Some(value.trim_matches('"'))
+}
+
+#[test]
+fn event_lifecycle() {
+    let buffer = LogBuffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .with_target(false)
+        .without_time()
+        .with_env_filter("boomerang::lifecycle=debug")
+        .finish();
+
+    {
+        let _guard = subscriber.set_default();
+        let mut env_builder = EnvBuilder::new();
+        let _ = Main::build("main", (), None, None, &mut env_builder).unwrap();
+        let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+        let config = runtime::Config::default()
+            .with_fast_forward(true)
+            .with_timeout(Duration::milliseconds(1))
+            .with_event_filter("*::tick, main::source::out".parse().unwrap());
+        let mut sched = runtime::Scheduler::new(env, graph, config);
+        sched.event_loop().unwrap();
+    }
+
+    let log = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let steps = |event_id: &str| {
+        log.lines()
+            .filter(|line| field(line, "event_id") == Some(event_id))
+            .map(|line| {
+                line.split_whitespace()
+                    .nth(1)
+                    .unwrap()
+                    .trim_end_matches(':')
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let scheduled = log
+        .lines()
+        .filter(|line| field(line, "source").is_some())
+        .map(|line| {
+            (
+                field(line, "event_id").unwrap(),
+                field(line, "source").unwrap(),
+                field(line, "origin").unwrap_or_default(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // The ticks at 0 and 1 msec each set the output port
+    let ticks = scheduled
+        .iter()
+        .filter(|(_, source, _)| *source == "main::source::tick")
+        .collect::<Vec<_>>();
+    let outs = scheduled
+        .iter()
+        .filter(|(_, source, _)| *source == "main::source::out")
+        .collect::<Vec<_>>();
+    assert!(ticks.len() >= 2, "{log}");
+    assert_eq!(outs.len(), 2, "{log}");
+    assert!(outs
+        .iter()
+        .all(|(_, _, origin)| *origin == "main::source::ReactionTick"));
+
+    // Only the ticks go through the event queue
+    let (tick_id, _, _) = ticks[0];
+    assert_eq!(
+        steps(tick_id),
+        ["scheduled", "enqueued", "dequeued", "completed"]
+    );
+    let (out_id, _, _) = outs[0];
+    assert_eq!(steps(out_id), ["scheduled", "completed"]);
+    let completed = log
+        .lines()
+        .find(|line| field(line, "event_id") == Some(out_id) && line.contains("completed"))
+        .unwrap();
+    assert!(completed.contains("main::sink::ReactionInp"), "{completed}");
+}
@@ -0,0 +1,109 @@
+//! Checks that the expectations on the logical time of port values and shutdown are verified after a test run.
+
+use boomerang::prelude::*;
+use boomerang_util::runner::{build_and_test_reactor_expecting, Expectations};
+
+/// Counts the timer ticks, and re-sends each count one microstep later.
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionTick", reaction = "ReactionEcho")]
+struct Counter {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    echo: TypedActionKey<u32>,
+    out: TypedPortKey<u32, Output>,
+    delayed: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Counter", triggers(action = "tick"))]
+struct ReactionTick<'a> {
+    echo: runtime::ActionRef<'a, u32>,
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionTick<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut u32) {
+        *self.out = Some(*state);
+        self.echo.schedule(ctx, *state, None).unwrap();
+        *state += 1;
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Counter")]
+struct ReactionEcho<'a> {
+    #[reaction(triggers)]
+    echo: runtime::ActionRef<'a, u32>,
+    delayed: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionEcho<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut u32) {
+        *self.delayed = self.echo.get_value(ctx).copied();
+    }
+}
+
+fn config() -> runtime::Config {
+    runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(3))
+}
+
+fn tag(msec: i64, microstep: usize) -> runtime::Tag {
+    runtime::Tag::new(Duration::milliseconds(msec), microstep)
+}
+
+#[test]
+fn expectations_met() {
+    let expectations = Expectations::default()
+        // The first tick is one microstep after startup
+        .expect_port_at(tag(0, 1), "counter::out", 0u32)
+        .expect_port_at(tag(2, 0), "counter::out", 2u32)
+        .expect_port_at(tag(2, 1), "counter::delayed", 2u32)
+        .expect_shutdown_at(tag(3, 0));
+    build_and_test_reactor_expecting::<Counter>("counter", 0, config(), expectations).unwrap();
+}
+
+#[test]
+fn expectations_unmet() {
+    let expectations = Expectations::default()
+        .expect_port_at(tag(1, 0), "counter::out", 1u32)
+        // Off by one microstep
+        .expect_port_at(tag(1, 0), "counter::delayed", 1u32)
+        .expect_shutdown_at(tag(4, 0));
+    let Err(err) =
+        build_and_test_reactor_expecting::<Counter>("counter", 0, config(), expectations)
+    else {
+        panic!("Expected unmet expectations");
+    };
+    let err = err.to_string();
+    assert!(!err.contains("counter::out"), "{err}");
+    assert!(
+        err.contains(
+            "Expected counter::delayed = 1 at [1ms+0], but got [0 at [0s+2], 1 at [1ms+1], 2 at [2ms+1]]"
+        ),
+        "{err}"
+    );
+    assert!(
+        err.contains("Expected shutdown at [4ms+0], but it was at [3ms+0]"),
+        "{err}"
+    );
+}
+
+#[test]
+fn expectations_compare_values_by_type() {
+    // `2u64` has the same `Debug` representation as the `2u32` on the port
+    let expectations = Expectations::default()
+        .expect_port_at(tag(2, 0), "counter::out", 2u32)
+        .expect_port_at(tag(2, 0), "counter::out", 2u64);
+    let Err(err) =
+        build_and_test_reactor_expecting::<Counter>("counter", 0, config(), expectations)
+    else {
+        panic!("Expected unmet expectations");
+    };
+    assert!(
+        err.to_string()
+            .contains("Expected counter::out = 2 at [2ms+0]"),
+        "{err}"
+    );
+}
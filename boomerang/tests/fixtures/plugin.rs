@@ -0,0 +1,26 @@
+//! A plugin exporting a counter reactor, built as the `plugin_fixture` cdylib example and loaded by `tests/plugin.rs`.
+
+use boomerang::prelude::*;
+
+/// Counts the ticks of a 1 msec timer.
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionTick")]
+pub struct Counter {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Counter", triggers(action = "tick"))]
+struct ReactionTick;
+
+impl runtime::Trigger<u32> for ReactionTick {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut u32) {
+        *state += 1;
+    }
+}
+
+boomerang::export_reactors! {
+    "Counter" => Counter = 0,
+    "OffsetCounter" => Counter = 100,
+}
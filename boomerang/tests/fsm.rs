@@ -0,0 +1,98 @@
+//! Test a reactor state driven as a finite state machine.
+
+use boomerang::prelude::*;
+
+#[derive(Debug)]
+enum Event {
+    Start,
+    Stop,
+    Fault,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ReactorFsm)]
+#[fsm(
+    event = "Event",
+    transition(from = "Idle", event = "Start", to = "Running"),
+    transition(from = "Running", event = "Stop", to = "Idle"),
+    transition(from = "*", event = "Fault", to = "Failed")
+)]
+enum Mode {
+    Idle,
+    Running,
+    Failed,
+}
+
+#[derive(Debug)]
+struct State {
+    mode: Mode,
+    history: Vec<Mode>,
+    rejected: usize,
+}
+
+#[derive(Reactor)]
+#[reactor(state = "State", reaction = "ReactionTick")]
+struct Machine {
+    #[reactor(timer(period = "10 msec"))]
+    tick: TimerActionKey,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Machine", triggers(action = "tick"))]
+struct ReactionTick;
+
+impl runtime::Trigger<State> for ReactionTick {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut State) {
+        let events = [
+            Event::Start,
+            Event::Start,
+            Event::Stop,
+            Event::Start,
+            Event::Fault,
+            Event::Stop,
+        ];
+        let step = (ctx.get_elapsed_logical_time().whole_milliseconds() / 10) as usize;
+        match events.into_iter().nth(step) {
+            Some(event) => {
+                if state.mode.transition(event, ctx).is_err() {
+                    state.rejected += 1;
+                }
+                state.history.push(state.mode);
+            }
+            None => ctx.schedule_shutdown(None),
+        }
+    }
+}
+
+#[test]
+fn fsm() {
+    tracing_subscriber::fmt::init();
+    let config = runtime::Config::default().with_fast_forward(true);
+    let (_, sched) = boomerang_util::runner::build_and_test_reactor::<Machine>(
+        "fsm",
+        State {
+            mode: Mode::Idle,
+            history: vec![],
+            rejected: 0,
+        },
+        config,
+    )
+    .unwrap();
+
+    let env = sched.into_env();
+    let state = env
+        .find_reactor_by_name("fsm")
+        .and_then(|reactor| reactor.get_state::<State>())
+        .unwrap();
+    assert_eq!(
+        state.history,
+        vec![
+            Mode::Running,
+            Mode::Running,
+            Mode::Idle,
+            Mode::Running,
+            Mode::Failed,
+            Mode::Failed
+        ]
+    );
+    assert_eq!(state.rejected, 2);
+}
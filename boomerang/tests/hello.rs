@@ -44,7 +44,9 @@ impl runtime::Trigger<Hello> for ReactionT<'_> {
     fn trigger(mut self, ctx: &mut runtime::Context, state: &mut Hello) {
         // Print the current time.
         state.previous_time = ctx.get_elapsed_logical_time();
-        self.a.schedule(ctx, (), Some(Duration::milliseconds(200))); // No payload.
+        self.a
+            .schedule(ctx, (), Some(Duration::milliseconds(200)))
+            .unwrap(); // No payload.
         println!(
             "{} Current time is {:?}",
             state.message, state.previous_time
@@ -0,0 +1,106 @@
+//! Checks that the scheduler keeps a bounded history of port values and states.
+
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionTick")]
+struct Counter {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Counter", triggers(action = "tick"))]
+struct ReactionTick<'a> {
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionTick<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u32) {
+        *self.out = Some(*state);
+        *state += 1;
+    }
+}
+
+#[test]
+fn history() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(9))
+        .with_history_window(4);
+    let (_, sched) =
+        boomerang_util::runner::build_and_test_reactor::<Counter>("counter", 0, config).unwrap();
+
+    let history = sched.history();
+    assert_eq!(history.len(), 4);
+
+    // The most recent tag is the timeout, the shutdown event at the same tag doesn't run any reactions.
+    let last = history.back(0).unwrap();
+    assert_eq!(last.tag, runtime::Tag::new(Duration::milliseconds(9), 0));
+    assert_eq!(
+        last.reactions,
+        vec!["counter::_tick_startup", "counter::ReactionTick"]
+    );
+    assert_eq!(
+        last.ports,
+        vec![("counter::out".to_owned(), Some("9".to_owned()))]
+    );
+    assert_eq!(
+        last.states,
+        vec![("counter".to_owned(), Some("10".to_owned()))]
+    );
+
+    let oldest = history.back(3).unwrap();
+    assert_eq!(
+        oldest.ports,
+        vec![("counter::out".to_owned(), Some("6".to_owned()))]
+    );
+    assert!(history.back(4).is_none());
+}
+
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct Bank {
+    #[reactor(child = 0)]
+    counters: [Counter; 2],
+}
+
+#[test]
+fn history_bank() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(1))
+        .with_history_window(4);
+    let (_, sched) =
+        boomerang_util::runner::build_and_test_reactor::<Bank>("bank", (), config).unwrap();
+
+    // The members of the bank are told apart by their fully-qualified names. Their timer events are processed one
+    // after the other, each recorded separately.
+    let history = sched.history();
+    let last_tag = runtime::Tag::new(Duration::milliseconds(1), 0);
+    let (ports, states): (Vec<_>, Vec<_>) = history
+        .iter()
+        .filter(|record| record.tag == last_tag)
+        .map(|record| (record.ports.clone(), record.states.clone()))
+        .unzip();
+    let mut ports = ports.concat();
+    ports.sort();
+    assert_eq!(
+        ports,
+        vec![
+            ("bank::counters[0]::out".to_owned(), Some("1".to_owned())),
+            ("bank::counters[1]::out".to_owned(), Some("1".to_owned())),
+        ]
+    );
+    let mut states = states.concat();
+    states.sort();
+    assert_eq!(
+        states,
+        vec![
+            ("bank::counters[0]".to_owned(), Some("2".to_owned())),
+            ("bank::counters[1]".to_owned(), Some("2".to_owned())),
+        ]
+    );
+}
@@ -0,0 +1,146 @@
+//! Checks that reactor initializations run concurrently before logical time begins, and abort the run on failure.
+
+use boomerang::prelude::*;
+
+#[derive(Debug, Default)]
+struct State {
+    /// Set by the initialization
+    device: Option<String>,
+    /// The device seen by the startup reaction, with the physical time elapsed since the start
+    opened: Option<(String, std::time::Duration)>,
+}
+
+#[derive(Reactor)]
+#[reactor(state = "State", reaction = "ReactionStartup")]
+struct Device;
+
+#[derive(Reaction)]
+#[reaction(reactor = "Device", triggers(startup))]
+struct ReactionStartup;
+
+impl runtime::Trigger<State> for ReactionStartup {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut State) {
+        let device = state.device.clone().expect("Not initialized");
+        state.opened = Some((device, ctx.get_physical_time() - ctx.get_start_time()));
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct Main {
+    #[reactor(child = State::default())]
+    camera: Device,
+    #[reactor(child = State::default())]
+    lidar: Device,
+}
+
+/// Build `Main`, initializing each device with `init`.
+fn build(
+    init: impl Fn(&'static str) -> Result<String, String> + Clone + Send + 'static,
+    timeout: Option<Duration>,
+) -> (runtime::Env, runtime::ReactionGraph) {
+    let mut env_builder = EnvBuilder::new();
+    let _ = Main::build("main", (), None, None, &mut env_builder).unwrap();
+    for name in ["camera", "lidar"] {
+        let init = init.clone();
+        let reactor_key = env_builder
+            .find_reactor_by_fqn(format!("main::{name}").as_str())
+            .unwrap();
+        env_builder
+            .get_reactor_builder(reactor_key)
+            .unwrap()
+            .add_init(
+                "open",
+                timeout,
+                move || init(name),
+                |state: &mut State, device| state.device = Some(device),
+            )
+            .unwrap();
+    }
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    (env, graph)
+}
+
+fn slow_open(name: &'static str) -> Result<String, String> {
+    std::thread::sleep(std::time::Duration::from_millis(150));
+    Ok(format!("/dev/{name}"))
+}
+
+#[test]
+fn init() {
+    let (env, graph) = build(slow_open, Some(Duration::seconds(5)));
+    assert_eq!(env.inits.len(), 2);
+    assert_eq!(env.inits[0].name(), "main::camera::open");
+
+    let started = std::time::Instant::now();
+    let mut sched = runtime::Scheduler::new(env, graph, runtime::Config::default());
+    sched.event_loop().unwrap();
+    // Both devices are opened concurrently
+    assert!(started.elapsed() < std::time::Duration::from_millis(290));
+
+    let env = sched.into_env();
+    for name in ["camera", "lidar"] {
+        let (device, elapsed) = env
+            .find_reactor_by_name(name)
+            .and_then(|reactor| reactor.get_state::<State>())
+            .and_then(|state| state.opened.clone())
+            .unwrap();
+        assert_eq!(device, format!("/dev/{name}"));
+        // The initialization doesn't count towards the startup tag
+        assert!(
+            elapsed < std::time::Duration::from_millis(50),
+            "{elapsed:?}"
+        );
+    }
+}
+
+#[test]
+fn init_failure() {
+    let (env, graph) = build(
+        |name| match name {
+            "lidar" => Err("no such device".to_owned()),
+            _ => Ok(name.to_owned()),
+        },
+        None,
+    );
+    let mut sched = runtime::Scheduler::new(env, graph, runtime::Config::default());
+    let err = sched.event_loop().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Initialization main::lidar::open failed: no such device"
+    );
+    // No reaction ran
+    let env = sched.into_env();
+    let state = env
+        .find_reactor_by_name("camera")
+        .and_then(|reactor| reactor.get_state::<State>())
+        .unwrap();
+    assert!(state.opened.is_none());
+
+    let (env, graph) = build(slow_open, Some(Duration::milliseconds(10)));
+    let mut sched = runtime::Scheduler::new(env, graph, runtime::Config::default());
+    assert!(matches!(
+        sched.event_loop(),
+        Err(runtime::RuntimeError::InitTimeout { name, .. }) if name == "main::camera::open"
+    ));
+}
+
+#[test]
+fn init_wrong_state() {
+    let mut env_builder = EnvBuilder::new();
+    let mut reactor = env_builder.add_reactor("main", None, None, ());
+    assert!(matches!(
+        reactor.add_init("open", None, || Ok::<_, String>(1), |_: &mut u32, _| {}),
+        Err(BuilderError::InconsistentBuilderState { .. })
+    ));
+    assert!(matches!(
+        reactor.add_init(
+            "open",
+            Some(Duration::milliseconds(-1)),
+            || Ok::<_, String>(()),
+            |_: &mut (), _| {}
+        ),
+        Err(BuilderError::NegativeDelay { .. })
+    ));
+}
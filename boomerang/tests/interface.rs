@@ -0,0 +1,146 @@
+//! Checks that reactors implementing the same interface can be swapped at build time behind a forwarding reactor.
+
+use boomerang::prelude::*;
+
+#[derive(ReactorInterface)]
+struct MotorInterface {
+    command: TypedPortKey<f32, Input>,
+    speed: TypedPortKey<f32, Output>,
+}
+
+/// A simulated motor, reaching twice the commanded speed.
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionSim", implements = "MotorInterface")]
+struct SimMotor {
+    command: TypedPortKey<f32, Input>,
+    speed: TypedPortKey<f32, Output>,
+    /// Not part of the interface
+    temperature: TypedPortKey<f32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "SimMotor")]
+struct ReactionSim<'a> {
+    command: runtime::InputRef<'a, f32>,
+    speed: runtime::OutputRef<'a, f32>,
+}
+
+impl runtime::Trigger<()> for ReactionSim<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.speed = self.command.map(|command| command * 2.0);
+    }
+}
+
+/// A motor driver, reaching the commanded speed.
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    reaction = "ReactionDriver",
+    implements = "MotorInterface"
+)]
+struct DriverMotor {
+    speed: TypedPortKey<f32, Output>,
+    command: TypedPortKey<f32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "DriverMotor")]
+struct ReactionDriver<'a> {
+    command: runtime::InputRef<'a, f32>,
+    speed: runtime::OutputRef<'a, f32>,
+}
+
+impl runtime::Trigger<()> for ReactionDriver<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.speed = *self.command;
+    }
+}
+
+/// The speed reached by the motor.
+type Speed = Option<f32>;
+
+#[derive(Reactor)]
+#[reactor(
+    state = "Speed",
+    reaction = "ReactionStartup",
+    reaction = "ReactionSpeed"
+)]
+struct Controller {
+    command: TypedPortKey<f32, Output>,
+    speed: TypedPortKey<f32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Controller", triggers(startup))]
+struct ReactionStartup<'a> {
+    command: runtime::OutputRef<'a, f32>,
+}
+
+impl runtime::Trigger<Speed> for ReactionStartup<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut Speed) {
+        *self.command = Some(1.5);
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Controller")]
+struct ReactionSpeed<'a> {
+    speed: runtime::InputRef<'a, f32>,
+}
+
+impl runtime::Trigger<Speed> for ReactionSpeed<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut Speed) {
+        *state = *self.speed;
+    }
+}
+
+/// Build a controller driving the motor `M` through a `motor` reactor exposing the interface, and return the speed
+/// it reached.
+fn run<M>() -> Option<f32>
+where
+    M: Reactor<State = ()> + Implements<MotorInterface>,
+{
+    let mut env_builder = EnvBuilder::new();
+    let main = env_builder
+        .add_reactor("main", None, None, ())
+        .finish()
+        .unwrap();
+    let controller =
+        Controller::build("controller", None, Some(main), None, &mut env_builder).unwrap();
+
+    let mut motor = env_builder.add_reactor("motor", Some(main), None, ());
+    let outer = MotorInterface::build(&mut motor).unwrap();
+    let inner = motor
+        .add_child_reactor::<M>("driver", ())
+        .unwrap()
+        .interface();
+    motor.connect_interface(&outer, &inner).unwrap();
+    motor.finish().unwrap();
+
+    let mut main = env_builder.get_reactor_builder(main).unwrap();
+    main.connect_port(controller.command, outer.command, None, false)
+        .unwrap();
+    main.connect_port(outer.speed, controller.speed, None, false)
+        .unwrap();
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let mut sched = runtime::Scheduler::new(
+        env,
+        graph,
+        runtime::Config::default().with_fast_forward(true),
+    );
+    sched.event_loop().unwrap();
+    sched
+        .into_env()
+        .find_reactor_by_name("controller")
+        .and_then(|reactor| reactor.get_state::<Speed>())
+        .copied()
+        .unwrap()
+}
+
+#[test]
+fn interface() {
+    assert_eq!(run::<SimMotor>(), Some(3.0));
+    assert_eq!(run::<DriverMotor>(), Some(1.5));
+}
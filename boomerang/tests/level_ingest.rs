@@ -0,0 +1,123 @@
+//! Checks that the asynchronous events sent during a long level are received before the level completes, so the
+//! sending thread doesn't block on a full channel until then.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+
+use boomerang::prelude::*;
+
+const WORKERS: usize = 40;
+const EVENTS: usize = 8;
+
+/// The number of worker reactions run so far.
+static WORKERS_RUN: AtomicUsize = AtomicUsize::new(0);
+
+/// The sending thread, returning the number of worker reactions run when it was done. It is only joined after the
+/// scheduler is dropped, as it may still be blocked on the channel at shutdown.
+static SENDER: Mutex<Option<JoinHandle<usize>>> = Mutex::new(None);
+
+/// Starts the workers, and sends a burst of physical events from a thread at the same time.
+#[derive(Reactor)]
+#[reactor(
+    state = "Vec::<usize>",
+    reaction = "ReactionStartup",
+    reaction = "ReactionEvent"
+)]
+struct Source {
+    go: TypedPortKey<(), Output>,
+    event: TypedActionKey<usize, Physical>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(startup))]
+struct ReactionStartup<'a> {
+    go: runtime::OutputRef<'a>,
+    event: runtime::AsyncActionRef<usize>,
+}
+
+impl runtime::Trigger<Vec<usize>> for ReactionStartup<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut Vec<usize>) {
+        *self.go = Some(());
+        let send_ctx = ctx.make_send_context();
+        let event = self.event.clone();
+        *SENDER.lock().unwrap() = Some(std::thread::spawn(move || {
+            // Blocks whenever the channel is full, until the scheduler receives the pending events
+            for i in 0..EVENTS {
+                event.schedule(&send_ctx, i, None);
+            }
+            WORKERS_RUN.load(Ordering::SeqCst)
+        }));
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(action = "event"))]
+struct ReactionEvent<'a> {
+    event: runtime::ActionRef<'a, usize>,
+}
+
+impl runtime::Trigger<Vec<usize>> for ReactionEvent<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, received: &mut Vec<usize>) {
+        received.extend(self.event.get_value(ctx).copied());
+    }
+}
+
+/// A slow reaction, all workers together forming one long level.
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionWork")]
+struct Worker {
+    go: TypedPortKey<(), Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Worker", triggers(port = "go"))]
+struct ReactionWork;
+
+impl runtime::Trigger<()> for ReactionWork {
+    fn trigger(self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        WORKERS_RUN.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.go", to = "workers.go", broadcast)
+)]
+struct Main {
+    #[reactor(child = Vec::new())]
+    source: Source,
+    #[reactor(child = ())]
+    workers: [Worker; WORKERS],
+}
+
+#[test]
+fn level_ingest() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_queue_size(2)
+        .with_ingest_interval(1);
+    let (_, sched) =
+        boomerang_util::runner::build_and_test_reactor::<Main>("main", (), config).unwrap();
+    let env = sched.into_env();
+    let run_when_sent = SENDER
+        .lock()
+        .unwrap()
+        .take()
+        .unwrap()
+        .join()
+        .expect("The sender was still blocked at shutdown");
+    let received = env
+        .find_reactor_by_name("source")
+        .and_then(|reactor| reactor.get_state::<Vec<usize>>())
+        .unwrap();
+
+    assert_eq!(received, &(0..EVENTS).collect::<Vec<_>>());
+    assert!(
+        run_when_sent < WORKERS,
+        "The sender was blocked until the level completed"
+    );
+}
@@ -0,0 +1,72 @@
+//! Checks that a `Memo` in the reactor state is computed once per tag by the reactions sharing it.
+
+use boomerang::prelude::*;
+
+#[derive(Debug, Default)]
+struct State {
+    square: runtime::Memo<u64>,
+    computed: Vec<u64>,
+    sums: Vec<u64>,
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "State",
+    reaction = "ReactionFirst",
+    reaction = "ReactionSecond"
+)]
+struct Squares {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+}
+
+/// The expensive computation, recording each time it runs.
+fn square(ctx: &runtime::Context, computed: &mut Vec<u64>) -> u64 {
+    let millis = ctx.get_elapsed_logical_time().whole_milliseconds() as u64;
+    computed.push(millis);
+    millis * millis
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Squares", triggers(action = "tick"))]
+struct ReactionFirst;
+
+impl runtime::Trigger<State> for ReactionFirst {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut State) {
+        let square = *state
+            .square
+            .get_or_compute(ctx, || square(ctx, &mut state.computed));
+        state.sums.push(square);
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Squares", triggers(action = "tick"))]
+struct ReactionSecond;
+
+impl runtime::Trigger<State> for ReactionSecond {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut State) {
+        let square = *state
+            .square
+            .get_or_compute(ctx, || square(ctx, &mut state.computed));
+        *state.sums.last_mut().unwrap() += square;
+    }
+}
+
+#[test]
+fn memo_per_tag() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(3));
+    let (_, sched, key) = boomerang_util::runner::build_and_test_reactor_typed::<Squares>(
+        "squares",
+        State::default(),
+        config,
+    )
+    .unwrap();
+    let env = sched.into_env();
+    let state = env.state(key);
+    assert_eq!(state.computed, [0, 1, 2, 3]);
+    assert_eq!(state.sums, [0, 2, 8, 18]);
+    assert_eq!((state.square.hits(), state.square.misses()), (4, 4));
+}
@@ -0,0 +1,113 @@
+//! Checks that the memory report attributes state, port and action sizes to their reactors by fully-qualified name.
+
+use boomerang::prelude::*;
+use runtime::mem_size::{MemSize, MemoryUsage};
+
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionStartup")]
+struct Source {
+    out: TypedPortKey<String, Output>,
+    later: TypedActionKey<u64>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(startup))]
+struct ReactionStartup<'a> {
+    out: runtime::OutputRef<'a, String>,
+}
+
+impl runtime::Trigger<()> for ReactionStartup<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.out = Some("hello".to_owned());
+    }
+}
+
+/// The samples received, pre-allocated.
+struct Samples(Vec<u64>);
+
+impl MemSize for Samples {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size()
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "Samples", reaction = "ReactionBuffer")]
+struct Buffer {
+    inp: TypedPortKey<String, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Buffer")]
+struct ReactionBuffer<'a> {
+    inp: runtime::InputRef<'a, String>,
+}
+
+impl runtime::Trigger<Samples> for ReactionBuffer<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut Samples) {
+        state
+            .0
+            .extend(self.inp.as_ref().map(|value| value.len() as u64));
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(state = "()", connection(from = "source.out", to = "buffer.inp"))]
+struct Main {
+    #[reactor(child = ())]
+    source: Source,
+    #[reactor(child = Samples(Vec::with_capacity(100)))]
+    buffer: Buffer,
+}
+
+#[test]
+fn memory_report() {
+    let mut env_builder = EnvBuilder::new();
+    let _ = Main::build("main", (), None, None, &mut env_builder).unwrap();
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+
+    // Without a registered size, only the inline size of the state is known
+    let report = env.memory_report(&graph);
+    assert_eq!(
+        report.0.keys().collect::<Vec<_>>(),
+        ["main", "main::buffer", "main::source"]
+    );
+    assert_eq!(report.get("main"), Some(&MemoryUsage::default()));
+    assert_eq!(
+        report.get("main::buffer").unwrap().state,
+        std::mem::size_of::<Samples>()
+    );
+
+    runtime::mem_size::register::<Samples>();
+    let mut sched = runtime::Scheduler::new(
+        env,
+        graph,
+        runtime::Config::default().with_fast_forward(true),
+    );
+    sched.event_loop().unwrap();
+    let report = sched.memory_report();
+
+    let buffer = report.get("main::buffer").unwrap();
+    assert_eq!(
+        buffer.state,
+        std::mem::size_of::<Samples>() + 100 * std::mem::size_of::<u64>()
+    );
+    // The connected port is counted once, for the reactor of the source port
+    assert_eq!(buffer.ports, 0);
+    assert_eq!(buffer.actions, 0);
+    let source = report.get("main::source").unwrap();
+    assert_eq!(source.state, 0);
+    assert_eq!(source.ports, std::mem::size_of::<Option<String>>());
+    assert!(source.actions > 0);
+    assert_eq!(report.total().total(), buffer.total() + source.total());
+
+    let table = report.to_string();
+    let lines = table.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 5, "{table}");
+    assert!(lines[0].starts_with("reactor"));
+    assert!(lines[2].starts_with("main::buffer"));
+    assert!(lines[4].starts_with("total"));
+    assert!(lines[4].ends_with(&report.total().total().to_string()));
+}
@@ -0,0 +1,141 @@
+//! Checks that sustained lag behind physical time is detected, and the configured overload response applied.
+
+use boomerang::builder::{reaction_closure, TriggerMode};
+use boomerang::prelude::*;
+
+/// The number of samples scheduled at startup, one every msec.
+const SAMPLES: u32 = 10;
+
+/// The timeout of the run, in msec.
+const TIMEOUT: i64 = 40;
+
+/// Processes samples scheduled every msec, taking 3 msec for each.
+#[derive(Reactor)]
+#[reactor(
+    state = "Vec::<u32>",
+    reaction = "ReactionStartup",
+    reaction = "ReactionSample"
+)]
+struct Sampler {
+    sample: TypedActionKey<u32>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sampler", triggers(startup))]
+struct ReactionStartup<'a> {
+    sample: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<Vec<u32>> for ReactionStartup<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut Vec<u32>) {
+        for i in 1..=SAMPLES {
+            self.sample
+                .schedule(ctx, i, Some(Duration::milliseconds(i as i64)))
+                .unwrap();
+        }
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sampler")]
+struct ReactionSample<'a> {
+    #[reaction(triggers)]
+    sample: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<Vec<u32>> for ReactionSample<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut Vec<u32>) {
+        state.extend(self.sample.get_value(ctx));
+        std::thread::sleep(std::time::Duration::from_millis(3));
+    }
+}
+
+/// The overload reports received by the supervisor.
+type Reports = Vec<runtime::Overload>;
+
+/// Run the sampler with the given overload response, returning the processed samples, the reports received by the
+/// supervisor and the overloads detected by the scheduler.
+fn run(response: runtime::OverloadResponse) -> (Vec<u32>, Reports, Vec<runtime::Overload>) {
+    let mut env_builder = EnvBuilder::new();
+    let sampler = Sampler::build("sampler", Vec::new(), None, None, &mut env_builder).unwrap();
+    env_builder.mark_conflatable(sampler.sample).unwrap();
+    let sampler = env_builder.find_reactor_by_fqn("sampler").unwrap();
+
+    let mut supervisor = env_builder.add_reactor("supervisor", None, None, Reports::new());
+    let overload = supervisor.add_overload_action("overload").unwrap();
+    let supervisor = supervisor.finish().unwrap();
+    env_builder
+        .add_reaction(
+            "report",
+            supervisor,
+            reaction_closure!(ctx, reactor, _ref_ports, _mut_ports, actions => {
+                let mut overload: runtime::ActionRef<runtime::Overload> =
+                    actions.partition_mut().unwrap();
+                let report = overload.get_value(ctx).cloned();
+                reactor
+                    .downcast_mut::<runtime::Reactor<Reports>>()
+                    .unwrap()
+                    .state
+                    .extend(report);
+            }),
+        )
+        .with_action(overload, 0, TriggerMode::TriggersAndUses)
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    let (env, graph, aliases) = env_builder.into_runtime_parts().unwrap();
+    let sampler = env
+        .typed_reactor_key::<Vec<u32>>(aliases.reactor_aliases[sampler])
+        .unwrap();
+    let supervisor = env
+        .typed_reactor_key::<Reports>(aliases.reactor_aliases[supervisor])
+        .unwrap();
+    let config = runtime::Config::default()
+        .with_timeout(Duration::milliseconds(TIMEOUT))
+        .with_overload(runtime::OverloadConfig::new(
+            Duration::milliseconds(1),
+            response,
+        ));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+    let overloads = sched.overloads().to_vec();
+    let env = sched.into_env();
+    (
+        env.state(sampler).clone(),
+        env.state(supervisor).clone(),
+        overloads,
+    )
+}
+
+#[test]
+fn drop_conflatable() {
+    let (samples, reports, overloads) = run(runtime::OverloadResponse::DropConflatable);
+    assert!(!overloads.is_empty());
+    let dropped = overloads
+        .iter()
+        .map(|overload| overload.dropped)
+        .sum::<usize>();
+    assert!(dropped > 0, "{overloads:?}");
+    assert_eq!(samples.len() + dropped, SAMPLES as usize, "{samples:?}");
+    // The most recent sample is never dropped
+    assert_eq!(samples.last(), Some(&SAMPLES));
+    assert!(reports.is_empty());
+}
+
+#[test]
+fn overload_action() {
+    let (samples, reports, overloads) = run(runtime::OverloadResponse::Action);
+    assert_eq!(samples, (1..=SAMPLES).collect::<Vec<_>>());
+    assert!(!overloads.is_empty());
+    assert!(overloads
+        .iter()
+        .all(|overload| overload.lag > Duration::milliseconds(1) && overload.dropped == 0));
+    // An overload detected at the shutdown tag, when the test runs late, is reported a microstep after shutdown
+    let reported = overloads
+        .iter()
+        .filter(|overload| overload.tag.offset() < Duration::milliseconds(TIMEOUT))
+        .cloned()
+        .collect::<Vec<_>>();
+    assert_eq!(reports, reported);
+}
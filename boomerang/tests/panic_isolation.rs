@@ -0,0 +1,376 @@
+//! Checks that panics in reactions are caught per reactor, and reported to a failure action.
+
+use boomerang::builder::{reaction_closure, TimerSpec, TriggerMode};
+use boomerang::prelude::*;
+
+/// The tags and names of the failing reactors reported to the supervisor.
+type Reports = Vec<(runtime::Tag, String)>;
+
+enum Policy {
+    Isolate,
+    Restart,
+}
+
+/// Run a worker counting the ticks of a 1 msec timer, panicking whenever the count reaches 3.
+fn run(policy: Policy) -> (u32, bool, Vec<runtime::ReactorFailure>, Reports) {
+    let mut env_builder = EnvBuilder::new();
+
+    let mut worker = env_builder.add_reactor("worker", None, None, 0u32);
+    let tick = worker
+        .add_timer(
+            "tick",
+            TimerSpec {
+                period: Some(Duration::milliseconds(1)),
+                offset: None,
+            },
+        )
+        .unwrap();
+    match policy {
+        Policy::Isolate => worker.isolate_panics(),
+        Policy::Restart => worker.restart_on_panic::<u32>().unwrap(),
+    }
+    assert!(matches!(
+        worker.restart_on_panic::<f32>(),
+        Err(BuilderError::InconsistentBuilderState { .. })
+    ));
+    let worker_key = worker.finish().unwrap();
+    env_builder
+        .add_reaction(
+            "count",
+            worker_key,
+            reaction_closure!(_ctx, reactor, _ref_ports, _mut_ports, _actions => {
+                let count = &mut reactor.downcast_mut::<runtime::Reactor<u32>>().unwrap().state;
+                *count += 1;
+                if *count == 3 {
+                    panic!("count reached {count}");
+                }
+            }),
+        )
+        .with_action(tick, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    let mut supervisor = env_builder.add_reactor("supervisor", None, None, Reports::new());
+    let failures = supervisor.add_failure_action("failures").unwrap();
+    let supervisor_key = supervisor.finish().unwrap();
+    env_builder
+        .add_reaction(
+            "report",
+            supervisor_key,
+            reaction_closure!(ctx, reactor, _ref_ports, _mut_ports, actions => {
+                let mut failures: runtime::ActionRef<Vec<runtime::ReactorFailure>> =
+                    actions.partition_mut().unwrap();
+                let reports = failures
+                    .get_value(ctx)
+                    .unwrap()
+                    .iter()
+                    .map(|failure| (ctx.get_tag(), failure.reactor.clone()))
+                    .collect::<Vec<_>>();
+                reactor
+                    .downcast_mut::<runtime::Reactor<Reports>>()
+                    .unwrap()
+                    .state
+                    .extend(reports);
+            }),
+        )
+        .with_action(failures, 0, TriggerMode::TriggersAndUses)
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    let (env, graph, aliases) = env_builder.into_runtime_parts().unwrap();
+    let worker_key = aliases.reactor_aliases[worker_key];
+    let supervisor_key = env
+        .typed_reactor_key::<Reports>(aliases.reactor_aliases[supervisor_key])
+        .unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(5));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+    let failures = sched.failures().to_vec();
+    let env = sched.into_env();
+
+    let worker = &env.reactors[worker_key];
+    (
+        *worker.get_state::<u32>().unwrap(),
+        worker.is_failed(),
+        failures,
+        env.state(supervisor_key).clone(),
+    )
+}
+
+#[test]
+fn isolate_panics() {
+    let (count, failed, failures, reports) = run(Policy::Isolate);
+    assert_eq!(count, 3);
+    assert!(failed);
+
+    let tag = runtime::Tag::new(Duration::milliseconds(2), 0);
+    assert_eq!(
+        failures,
+        [runtime::ReactorFailure {
+            reactor: "worker".to_owned(),
+            reaction: "count".to_owned(),
+            tag,
+            message: "count reached 3".to_owned(),
+            policy: runtime::PanicPolicy::Isolate,
+        }]
+    );
+    assert_eq!(reports, [(tag.delay(Duration::ZERO), "worker".to_owned())]);
+}
+
+#[test]
+fn restart_on_panic() {
+    let (count, failed, failures, reports) = run(Policy::Restart);
+    // The state is reset after the panics at 2 and 5 msec
+    assert_eq!(count, 0);
+    assert!(!failed);
+    assert_eq!(
+        failures
+            .iter()
+            .map(|failure| (failure.tag, failure.policy))
+            .collect::<Vec<_>>(),
+        [
+            (
+                runtime::Tag::new(Duration::milliseconds(2), 0),
+                runtime::PanicPolicy::Restart
+            ),
+            (
+                runtime::Tag::new(Duration::milliseconds(5), 0),
+                runtime::PanicPolicy::Restart
+            ),
+        ]
+    );
+    // The failure at 5 msec is reported after the shutdown tag, so it's never delivered
+    assert_eq!(reports.len(), 1);
+}
+
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionEmit", reaction = "ReactionEcho")]
+struct Emitter {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    #[reactor(action(min_delay = "1 msec"))]
+    later: TypedActionKey<u32>,
+    out: TypedPortKey<u32, Output>,
+    echoed: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Emitter", triggers(action = "tick"))]
+struct ReactionEmit<'a> {
+    out: runtime::OutputRef<'a, u32>,
+    later: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionEmit<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut u32) {
+        *state += 1;
+        *self.out = Some(*state);
+        self.later.schedule(ctx, *state, None).unwrap();
+        if *state == 2 {
+            panic!("count reached {state}");
+        }
+    }
+}
+
+/// Echoes the value scheduled on `later` at the previous tick, if any.
+#[derive(Reaction)]
+#[reaction(reactor = "Emitter", triggers(action = "tick"))]
+struct ReactionEcho<'a> {
+    #[reaction(triggers)]
+    later: runtime::ActionRef<'a, u32>,
+    echoed: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionEcho<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut u32) {
+        *self.echoed = self.later.get_value(ctx).copied();
+    }
+}
+
+/// The values received by the sink, with the msec and the name of their port.
+type Received = Vec<(i128, &'static str, u32)>;
+
+#[derive(Reactor)]
+#[reactor(state = "Received", reaction = "ReactionReceive")]
+struct Receiver {
+    out: TypedPortKey<u32, Input>,
+    echoed: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Receiver")]
+struct ReactionReceive<'a> {
+    out: runtime::InputRef<'a, u32>,
+    echoed: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<Received> for ReactionReceive<'_> {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut Received) {
+        let msec = ctx.get_elapsed_logical_time().whole_milliseconds();
+        let received = [
+            self.out.map(|value| (msec, "out", value)),
+            self.echoed.map(|value| (msec, "echoed", value)),
+        ];
+        // Events at the same tag aren't merged, so the echo can be received twice at a tag
+        for value in received.into_iter().flatten() {
+            if !state.contains(&value) {
+                state.push(value);
+            }
+        }
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "emitter.out", to = "receiver.out"),
+    connection(from = "emitter.echoed", to = "receiver.echoed")
+)]
+struct Effects {
+    #[reactor(child = 0)]
+    emitter: Emitter,
+    #[reactor(child = Received::new())]
+    receiver: Receiver,
+}
+
+#[test]
+fn panic_discards_effects() {
+    let mut env_builder = EnvBuilder::new();
+    let _ = Effects::build("main", (), None, None, &mut env_builder).unwrap();
+    let emitter_key = env_builder.find_reactor_by_fqn("main::emitter").unwrap();
+    env_builder
+        .get_reactor_builder(emitter_key)
+        .unwrap()
+        .restart_on_panic::<u32>()
+        .unwrap();
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(2));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+    assert_eq!(sched.failures().len(), 1);
+
+    // The output set and the action scheduled before the panic at 1 msec are both discarded
+    let env = sched.into_env();
+    let received = env
+        .find_reactor_by_name("receiver")
+        .and_then(|reactor| reactor.get_state::<Received>())
+        .unwrap();
+    assert_eq!(received, &[(0, "out", 1), (1, "echoed", 1), (2, "out", 1)]);
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    reaction = "ReactionWrite",
+    reaction = "ReactionOverwrite",
+    reaction = "ReactionEchoWritten"
+)]
+struct Writer {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    #[reactor(action(min_delay = "1 msec"))]
+    later: TypedActionKey<u32>,
+    out: TypedPortKey<u32, Output>,
+    echoed: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Writer", triggers(action = "tick"))]
+struct ReactionWrite<'a> {
+    out: runtime::OutputRef<'a, u32>,
+    later: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionWrite<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut ()) {
+        let value = ctx.get_elapsed_logical_time().whole_milliseconds() as u32 * 10;
+        *self.out = Some(value);
+        self.later.schedule(ctx, value, None).unwrap();
+    }
+}
+
+/// Overwrites the port and schedules the action at the same tag as [`ReactionWrite`], then panics.
+#[derive(Reaction)]
+#[reaction(reactor = "Writer", triggers(action = "tick"))]
+struct ReactionOverwrite<'a> {
+    out: runtime::OutputRef<'a, u32>,
+    later: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionOverwrite<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut ()) {
+        assert!(self.out.is_some());
+        *self.out = Some(99);
+        self.later.schedule(ctx, 99, None).unwrap();
+        panic!("overwrote {:?}", *self.out);
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Writer")]
+struct ReactionEchoWritten<'a> {
+    #[reaction(triggers)]
+    later: runtime::ActionRef<'a, u32>,
+    echoed: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionEchoWritten<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.echoed = self.later.get_value(ctx).copied();
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "writer.out", to = "receiver.out"),
+    connection(from = "writer.echoed", to = "receiver.echoed")
+)]
+struct Overwrites {
+    #[reactor(child = ())]
+    writer: Writer,
+    #[reactor(child = Received::new())]
+    receiver: Receiver,
+}
+
+#[test]
+fn panic_restores_earlier_effects() {
+    let mut env_builder = EnvBuilder::new();
+    let _ = Overwrites::build("main", (), None, None, &mut env_builder).unwrap();
+    let writer_key = env_builder.find_reactor_by_fqn("main::writer").unwrap();
+    env_builder
+        .get_reactor_builder(writer_key)
+        .unwrap()
+        .restart_on_panic::<()>()
+        .unwrap();
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(2));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+    assert_eq!(sched.failures().len(), 3);
+
+    // The value set and the action scheduled by the first reaction survive the panic of the second one
+    let env = sched.into_env();
+    let received = env
+        .find_reactor_by_name("receiver")
+        .and_then(|reactor| reactor.get_state::<Received>())
+        .unwrap();
+    assert_eq!(
+        received,
+        &[
+            (0, "out", 0),
+            (1, "out", 10),
+            (1, "echoed", 0),
+            (2, "out", 20),
+            (2, "echoed", 10)
+        ]
+    );
+}
@@ -0,0 +1,114 @@
+//! Checks that reloadable parameters are updated at the next tag, and notify the parameter change actions.
+
+use std::sync::{Arc, Mutex};
+
+use boomerang::builder::{reaction_closure, TimerSpec, TriggerMode};
+use boomerang::prelude::*;
+
+#[test]
+fn param_reload() {
+    let mut env_builder = EnvBuilder::new();
+    env_builder.set_parameter("name", "boomerang".to_owned());
+    env_builder.set_reloadable_parameter("threshold", 1.0f64);
+
+    let mut reactor = env_builder.add_reactor("main", None, None, ());
+    let tick = reactor
+        .add_timer(
+            "tick",
+            TimerSpec {
+                period: Some(Duration::milliseconds(1)),
+                offset: None,
+            },
+        )
+        .unwrap();
+    let changed = reactor.add_parameter_change_action("changed").unwrap();
+    let startup = reactor.get_startup_action();
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let record = seen.clone();
+    reactor
+        .add_reaction(
+            "read",
+            reaction_closure!(ctx, _reactor, _ref_ports, _mut_ports, _actions => {
+                let threshold = *ctx.parameter::<f64>("threshold").unwrap();
+                record.lock().unwrap().push((ctx.get_tag(), threshold));
+            }),
+        )
+        .with_action(startup, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .with_action(tick, 1, TriggerMode::TriggersOnly)
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let record = changes.clone();
+    reactor
+        .add_reaction(
+            "on_change",
+            reaction_closure!(ctx, _reactor, _ref_ports, _mut_ports, actions => {
+                let mut changed: runtime::ActionRef<runtime::ParameterChange> =
+                    actions.partition_mut().unwrap();
+                let change = changed.get_value(ctx).cloned().unwrap();
+                let threshold = *ctx.parameter::<f64>("threshold").unwrap();
+                record.lock().unwrap().push((ctx.get_tag(), change, threshold));
+            }),
+        )
+        .with_action(changed, 0, TriggerMode::TriggersAndUses)
+        .unwrap()
+        .finish()
+        .unwrap();
+    reactor.finish().unwrap();
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(2));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    let handle = sched.handle();
+
+    assert!(matches!(
+        handle.update_parameter("name", "other".to_owned()),
+        Err(runtime::RuntimeError::ParameterNotReloadable(_))
+    ));
+    assert!(matches!(
+        handle.update_parameter("threshold", 2u32),
+        Err(runtime::RuntimeError::TypeMismatch { .. })
+    ));
+    assert!(matches!(
+        handle.update_parameter("missing", 2.0f64),
+        Err(runtime::RuntimeError::ParameterNotFound(_))
+    ));
+
+    // Both updates are received after the startup tag, and applied together one microstep later, at the first tick
+    handle.update_parameter("threshold", 2.0f64).unwrap();
+    handle.update_parameter("threshold", 3.0f64).unwrap();
+    sched.event_loop().unwrap();
+
+    let tag = |ms, microstep| runtime::Tag::new(Duration::milliseconds(ms), microstep);
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            (tag(0, 0), 1.0),
+            (tag(0, 1), 3.0),
+            (tag(1, 0), 3.0),
+            (tag(2, 0), 3.0)
+        ]
+    );
+    assert_eq!(
+        *changes.lock().unwrap(),
+        [(
+            tag(0, 1),
+            runtime::ParameterChange {
+                names: vec!["threshold".to_owned()]
+            },
+            3.0
+        )]
+    );
+
+    drop(sched);
+    assert!(matches!(
+        handle.update_parameter("threshold", 4.0f64),
+        Err(runtime::RuntimeError::SchedulerStopped)
+    ));
+}
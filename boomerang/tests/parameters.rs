@@ -0,0 +1,77 @@
+//! Checks that the global parameters set at build time are available to all reactions.
+
+use boomerang::prelude::*;
+
+/// The parameters read at startup.
+#[derive(Debug, Default, PartialEq)]
+struct Read {
+    tick_rate: Option<f64>,
+    name: Option<String>,
+    wrong_type: bool,
+    missing: bool,
+}
+
+#[derive(Reactor)]
+#[reactor(state = "Read", reaction = "ReactionStartup")]
+struct Reader;
+
+#[derive(Reaction)]
+#[reaction(reactor = "Reader", triggers(startup))]
+struct ReactionStartup;
+
+impl runtime::Trigger<Read> for ReactionStartup {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut Read) {
+        state.tick_rate = ctx.parameter::<f64>("tick_rate").ok().copied();
+        state.name = ctx.parameter::<String>("name").ok().cloned();
+        state.wrong_type = matches!(
+            ctx.parameter::<u32>("tick_rate"),
+            Err(runtime::RuntimeError::TypeMismatch { .. })
+        );
+        state.missing = matches!(
+            ctx.parameter::<f64>("gravity"),
+            Err(runtime::RuntimeError::ParameterNotFound(_))
+        );
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct Main {
+    #[reactor(child = Read::default())]
+    first: Reader,
+    #[reactor(child = Read::default())]
+    second: Reader,
+}
+
+#[test]
+fn parameters() {
+    let mut env_builder = EnvBuilder::new();
+    env_builder.set_parameter("tick_rate", 100.0f64);
+    env_builder.set_parameter("tick_rate", 200.0f64);
+    env_builder.set_parameter("name", "boomerang".to_owned());
+    assert_eq!(env_builder.get_parameter::<f64>("tick_rate"), Some(&200.0));
+    assert_eq!(env_builder.get_parameter::<u32>("tick_rate"), None);
+    let _main = Main::build("main", (), None, None, &mut env_builder).unwrap();
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let mut sched = runtime::Scheduler::new(env, graph, runtime::Config::default());
+    sched.event_loop().unwrap();
+
+    let env = sched.into_env();
+    for name in ["first", "second"] {
+        let read = env
+            .find_reactor_by_name(name)
+            .and_then(|reactor| reactor.get_state::<Read>())
+            .unwrap();
+        assert_eq!(
+            read,
+            &Read {
+                tick_rate: Some(200.0),
+                name: Some("boomerang".to_owned()),
+                wrong_type: true,
+                missing: true,
+            }
+        );
+    }
+}
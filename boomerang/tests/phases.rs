@@ -0,0 +1,151 @@
+//! Checks that reactions in named phases run after all reactions of the earlier phases, across reactors.
+
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionSense")]
+struct Sensor {
+    reading: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sensor", triggers(startup), phase = "sense")]
+struct ReactionSense<'a> {
+    reading: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionSense<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.reading = Some(1);
+    }
+}
+
+/// Forwards the reading through a chain of reactions, so sensing finishes late.
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionRelay")]
+struct Relay {
+    inp: TypedPortKey<u32, Input>,
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Relay", phase = "sense")]
+struct ReactionRelay<'a> {
+    inp: runtime::InputRef<'a, u32>,
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionRelay<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.out = self.inp.map(|reading| reading + 1);
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionPlan")]
+struct Planner {
+    plan: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Planner", triggers(startup), phase = "plan")]
+struct ReactionPlan<'a> {
+    plan: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionPlan<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.plan = Some(10);
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionAct")]
+struct Actuator {
+    plan: TypedPortKey<u32, Input>,
+    feedback: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Actuator", phase = "act")]
+struct ReactionAct<'a> {
+    plan: runtime::InputRef<'a, u32>,
+    feedback: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionAct<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.feedback = *self.plan;
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "sensor.reading", to = "relay.inp"),
+    connection(from = "planner.plan", to = "actuator.plan")
+)]
+struct Main {
+    #[reactor(child = ())]
+    sensor: Sensor,
+    #[reactor(child = ())]
+    relay: Relay,
+    #[reactor(child = ())]
+    planner: Planner,
+    #[reactor(child = ())]
+    actuator: Actuator,
+}
+
+fn level(env_builder: &EnvBuilder, reactor: &str, reaction: &str) -> runtime::Level {
+    let reactor_key = env_builder.find_reactor_by_fqn(reactor).unwrap();
+    let reaction_key = env_builder
+        .find_reaction_by_name(reaction, reactor_key)
+        .unwrap();
+    env_builder.build_runtime_level_map().unwrap()[reaction_key]
+}
+
+#[test]
+fn phases() -> Result<(), BuilderError> {
+    let mut env_builder = EnvBuilder::new();
+    let _main = Main::build("main", (), None, None, &mut env_builder)?;
+    assert!(matches!(
+        env_builder.build_runtime_level_map(),
+        Err(BuilderError::NamedPhaseNotFound(phase)) if phase == "sense"
+    ));
+    assert!(matches!(
+        env_builder.set_phases(["sense", "plan", "sense"]),
+        Err(BuilderError::DuplicatePhaseDefinition(phase)) if phase == "sense"
+    ));
+
+    env_builder.set_phases(["sense", "plan", "act"])?;
+    let relay = level(&env_builder, "main::relay", "ReactionRelay");
+    let plan = level(&env_builder, "main::planner", "ReactionPlan");
+    let act = level(&env_builder, "main::actuator", "ReactionAct");
+    // Without phases, the planner would run alongside the sensor
+    assert!(level(&env_builder, "main::sensor", "ReactionSense") < relay);
+    assert!(relay < plan);
+    assert!(plan < act);
+
+    let (env, graph, _) = env_builder.into_runtime_parts()?;
+    let mut sched = runtime::Scheduler::new(
+        env,
+        graph,
+        runtime::Config::default().with_fast_forward(true),
+    );
+    sched.event_loop().unwrap();
+    Ok(())
+}
+
+#[test]
+fn phase_cycle() -> Result<(), BuilderError> {
+    let mut env_builder = EnvBuilder::new();
+    let _main = Main::build("main", (), None, None, &mut env_builder)?;
+    // The actuator depends on the plan, so can't act before planning
+    env_builder.set_phases(["sense", "act", "plan"])?;
+    assert!(matches!(
+        env_builder.build_runtime_level_map(),
+        Err(BuilderError::ReactionGraphCycle { .. })
+    ));
+    Ok(())
+}
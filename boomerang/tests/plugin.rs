@@ -0,0 +1,107 @@
+//! Checks that reactors exported with `export_reactors!` are registered and built by a plugin host, both linked
+//! statically and loaded from the `plugin_fixture` library.
+#![cfg(feature = "plugin")]
+
+use std::path::PathBuf;
+
+use boomerang::plugin::{PluginDeclaration, PluginError, PluginHost, PluginRegistrar};
+use boomerang::prelude::*;
+
+#[path = "fixtures/plugin.rs"]
+mod fixture;
+
+/// The path of the `plugin_fixture` library, which `cargo test` builds into the `examples` directory next to the
+/// directory of the test binary.
+fn fixture_path() -> PathBuf {
+    let exe = std::env::current_exe().unwrap();
+    let path = exe
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("examples")
+        .join(format!(
+            "{}plugin_fixture{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        ));
+    assert!(
+        path.is_file(),
+        "{path:?} is missing, run the tests with `cargo test -p boomerang --features plugin`"
+    );
+    path
+}
+
+/// Build the `OffsetCounter` reactor registered in `host`, and run it for 3 msec.
+fn run_offset_counter(host: &PluginHost) -> u32 {
+    let mut env_builder = EnvBuilder::new();
+    let main = env_builder
+        .add_reactor("main", None, None, ())
+        .finish()
+        .unwrap();
+    let counter = host
+        .build("OffsetCounter", "counter", Some(main), &mut env_builder)
+        .unwrap();
+    assert_eq!(
+        env_builder.find_reactor_by_fqn("main::counter").unwrap(),
+        counter
+    );
+    assert!(matches!(
+        host.build("Missing", "missing", None, &mut env_builder),
+        Err(PluginError::UnknownReactorType(_))
+    ));
+
+    let (env, graph, aliases) = env_builder.into_runtime_parts().unwrap();
+    let counter = env
+        .typed_reactor_key::<u32>(aliases.reactor_aliases[counter])
+        .unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(3));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+    *sched.into_env().state(counter)
+}
+
+#[test]
+fn register_and_build() {
+    let mut host = PluginHost::new();
+    let mut type_names = host.register(&fixture::BOOMERANG_PLUGIN).unwrap();
+    type_names.sort();
+    assert_eq!(type_names, ["Counter", "OffsetCounter"]);
+    assert!(matches!(
+        host.register(&fixture::BOOMERANG_PLUGIN),
+        Err(PluginError::DuplicateReactorType(_))
+    ));
+    assert_eq!(run_offset_counter(&host), 104);
+}
+
+#[test]
+fn load_and_build() {
+    let path = fixture_path();
+    let mut host = PluginHost::new();
+    let mut type_names = unsafe { host.load(&path) }.unwrap();
+    type_names.sort();
+    assert_eq!(type_names, ["Counter", "OffsetCounter"]);
+    assert_eq!(host.loaded(), [path.clone()]);
+    assert!(matches!(
+        unsafe { host.load(&path) },
+        Err(PluginError::DuplicateReactorType(_))
+    ));
+    assert!(matches!(
+        unsafe { host.load(path.with_file_name("missing_plugin")) },
+        Err(PluginError::Load { .. })
+    ));
+    assert_eq!(run_offset_counter(&host), 104);
+}
+
+#[test]
+fn reject_abi_mismatch() {
+    extern "C" fn register(_registrar: &mut PluginRegistrar) {}
+    let mut declaration = PluginDeclaration::new(register);
+    declaration.abi_version += 1;
+    assert!(matches!(
+        PluginHost::new().register(&declaration),
+        Err(PluginError::AbiMismatch { .. })
+    ));
+}
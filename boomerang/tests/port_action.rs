@@ -0,0 +1,102 @@
+//! Checks that connecting a port to a logical action schedules the action with the port values.
+
+mod common;
+
+use boomerang::prelude::*;
+use common::Source;
+
+/// The values received by an action, with the logical time in msec.
+type Received = Vec<(i128, u32)>;
+
+/// Records the values of its action.
+#[derive(Reactor)]
+#[reactor(state = "Received", reaction = "ReactionAct")]
+struct Sink {
+    act: TypedActionKey<u32>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink")]
+struct ReactionAct<'a> {
+    #[reaction(triggers)]
+    act: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<Received> for ReactionAct<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut Received) {
+        let elapsed = ctx.get_elapsed_logical_time().whole_milliseconds();
+        state.extend(self.act.get_value(ctx).map(|&value| (elapsed, value)));
+    }
+}
+
+/// Connects the output of the source to the action of a sibling.
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to_action = "sink.act", after = "2 msec")
+)]
+struct Sibling {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = Received::new())]
+    sink: Sink,
+}
+
+/// Connects the output of a child to its own action.
+#[derive(Reactor)]
+#[reactor(
+    state = "Received",
+    reaction = "ReactionParentAct",
+    connection(from = "source.out", to_action = "act")
+)]
+struct Parent {
+    #[reactor(child = 10)]
+    source: Source,
+    act: TypedActionKey<u32>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Parent")]
+struct ReactionParentAct<'a> {
+    #[reaction(triggers)]
+    act: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<Received> for ReactionParentAct<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut Received) {
+        let elapsed = ctx.get_elapsed_logical_time().whole_milliseconds();
+        state.extend(self.act.get_value(ctx).map(|&value| (elapsed, value)));
+    }
+}
+
+fn config() -> runtime::Config {
+    runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(4))
+}
+
+#[test]
+fn port_action_sibling() {
+    let (_, sched) =
+        boomerang_util::runner::build_and_test_reactor::<Sibling>("main", (), config()).unwrap();
+    let env = sched.into_env();
+    let received = env
+        .find_reactor_by_name("sink")
+        .and_then(|reactor| reactor.get_state::<Received>())
+        .unwrap();
+    assert_eq!(received, &[(2, 0), (3, 1), (4, 2)]);
+}
+
+#[test]
+fn port_action_parent() {
+    let (_, sched) =
+        boomerang_util::runner::build_and_test_reactor::<Parent>("main", Received::new(), config())
+            .unwrap();
+    let env = sched.into_env();
+    let received = env
+        .find_reactor_by_name("main")
+        .and_then(|reactor| reactor.get_state::<Received>())
+        .unwrap();
+    // Scheduled one microstep after each value, so the last value is past the shutdown tag
+    assert_eq!(received, &[(0, 10), (1, 11), (2, 12), (3, 13)]);
+}
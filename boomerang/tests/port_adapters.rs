@@ -0,0 +1,88 @@
+//! Checks the `Option` helpers of port refs and that `skip_if_absent` reactions only run when their input is present.
+
+use boomerang::prelude::*;
+
+/// Emits the count on `even` for even counts, and on `all` for every count.
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionTick")]
+struct Source {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    even: TypedPortKey<u32, Output>,
+    all: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(action = "tick"))]
+struct ReactionTick<'a> {
+    even: runtime::OutputRef<'a, u32>,
+    all: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionTick<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u32) {
+        self.even
+            .set_from(state.is_multiple_of(2).then_some(*state));
+        self.all.set_from(Some(*state));
+        *state += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct SinkState {
+    invocations: usize,
+    received: Vec<u32>,
+}
+
+/// Triggered by both inputs, but only runs when `even` is present.
+#[derive(Reactor)]
+#[reactor(state = "SinkState", reaction = "ReactionInp")]
+struct Sink {
+    even: TypedPortKey<u32, Input>,
+    all: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink")]
+struct ReactionInp<'a> {
+    #[reaction(skip_if_absent)]
+    even: runtime::InputRef<'a, u32>,
+    all: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<SinkState> for ReactionInp<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut SinkState) {
+        state.invocations += 1;
+        assert_eq!(self.even.get_or_err().ok(), self.all.as_ref());
+        state.received.extend(self.even.map_present(|v| v * 10));
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.even", to = "sink.even"),
+    connection(from = "source.all", to = "sink.all")
+)]
+struct Main {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = SinkState::default())]
+    sink: Sink,
+}
+
+#[test]
+fn skip_if_absent() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(5));
+    let (_, sched) =
+        boomerang_util::runner::build_and_test_reactor::<Main>("main", (), config).unwrap();
+    let env = sched.into_env();
+    let state = env
+        .find_reactor_by_name("sink")
+        .and_then(|reactor| reactor.get_state::<SinkState>())
+        .unwrap();
+    assert_eq!(state.received, vec![0, 20, 40]);
+    assert_eq!(state.invocations, 3);
+}
@@ -0,0 +1,113 @@
+//! Checks that a port contract reports out-of-range values, breaks in monotonicity and a port that stops updating.
+
+use boomerang::builder::{Monotonic, PortContract, ViolationKind};
+use boomerang::prelude::*;
+
+const READINGS: [u32; 5] = [10, 20, 30, 500, 40];
+
+/// Emits the readings every msec, then falls silent.
+#[derive(Reactor)]
+#[reactor(state = "usize", reaction = "ReactionTick")]
+struct Sensor {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sensor", triggers(action = "tick"))]
+struct ReactionTick<'a> {
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<usize> for ReactionTick<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, idx: &mut usize) {
+        *self.out = READINGS.get(*idx).copied();
+        *idx += 1;
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()")]
+#[allow(dead_code)]
+struct Main {
+    #[reactor(child = 0)]
+    sensor: Sensor,
+}
+
+fn contract() -> PortContract<u32> {
+    PortContract::new()
+        .with_range(0..=100)
+        .with_monotonic(Monotonic::Increasing)
+        .with_max_interval(Duration::milliseconds(3))
+}
+
+fn run(env_builder: EnvBuilder) {
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(10));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+}
+
+#[test]
+fn port_contract() -> Result<(), BuilderError> {
+    let mut env_builder = EnvBuilder::new();
+    let _main = Main::build("main", (), None, None, &mut env_builder)?;
+    env_builder.set_contract_checks(true);
+    let monitor = env_builder.add_port_contract("main::sensor::out", contract())?;
+    assert!(matches!(
+        env_builder.add_port_contract("main::sensor::out", PortContract::<f32>::new()),
+        Err(BuilderError::PortTypeMismatch { .. })
+    ));
+    run(env_builder);
+
+    let violations = monitor
+        .violations()
+        .into_iter()
+        .inspect(|violation| assert_eq!(violation.port, "main::sensor::out"))
+        .map(|violation| (violation.tag.offset(), violation.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        violations,
+        [
+            (
+                Duration::milliseconds(3),
+                ViolationKind::OutOfRange {
+                    value: "500".to_owned()
+                }
+            ),
+            (
+                Duration::milliseconds(4),
+                ViolationKind::NotMonotonic {
+                    previous: "500".to_owned(),
+                    value: "40".to_owned()
+                }
+            ),
+            // The last reading is at 4 msec, detected at shutdown
+            (
+                Duration::milliseconds(10),
+                ViolationKind::TooInfrequent {
+                    interval: Duration::milliseconds(6)
+                }
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn port_contract_disabled() -> Result<(), BuilderError> {
+    let mut env_builder = EnvBuilder::new();
+    let _main = Main::build("main", (), None, None, &mut env_builder)?;
+    env_builder.set_contract_checks(false);
+    let monitor = env_builder.add_port_contract("main::sensor::out", contract())?;
+    assert!(env_builder
+        .find_reactor_by_fqn("main::sensor::_out_contract")
+        .is_err());
+    run(env_builder);
+
+    assert!(monitor.violations().is_empty());
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! Test value probes on ports and actions.
+
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionTick", reaction = "ReactionAct")]
+struct Counter {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    act: TypedActionKey<u32, Logical>,
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Counter", triggers(action = "tick"))]
+struct ReactionTick<'a> {
+    act: runtime::ActionRef<'a, u32>,
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionTick<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut u32) {
+        *self.out = Some(*state);
+        self.act.schedule(ctx, *state * 10, None).unwrap();
+        *state += 1;
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Counter")]
+struct ReactionAct<'a> {
+    #[reaction(triggers)]
+    act: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionAct<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut u32) {
+        assert!(self.act.is_present(ctx));
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct Main {
+    #[reactor(child = 0)]
+    counter: Counter,
+}
+
+#[test]
+fn probes() {
+    let mut env_builder = EnvBuilder::new();
+    let main = Main::build("main", (), None, None, &mut env_builder).unwrap();
+    assert_eq!(
+        env_builder
+            .port_fqn(main.counter.out.into(), false)
+            .unwrap()
+            .to_string(),
+        "main::counter::out"
+    );
+    env_builder
+        .add_probe("main::counter::out", |value: &u32| value % 4 == 3)
+        .unwrap();
+    env_builder
+        .add_capturing_probe("main::counter::act", |value: &u32| *value == 50)
+        .unwrap();
+
+    assert!(env_builder
+        .add_probe("main::counter::out", |_: &bool| true)
+        .is_err());
+    assert!(matches!(
+        env_builder.add_probe("main::counter::act", |_: &bool| true),
+        Err(BuilderError::InconsistentBuilderState { .. })
+    ));
+    assert!(env_builder
+        .add_probe("main::counter::missing", |_: &u32| true)
+        .is_err());
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(9));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+
+    let hits = sched.probe_hits();
+    assert_eq!(hits.len(), 3);
+
+    assert_eq!(hits[0].probe, "main::counter::out");
+    assert_eq!(hits[0].value, "3");
+    assert_eq!(hits[0].tag.offset(), Duration::milliseconds(3));
+    assert_eq!(hits[0].reactions, vec!["ReactionTick".to_owned()]);
+    assert!(!hits[0].recent_events.is_empty());
+    assert_eq!(hits[0].value_as::<u32>(), None);
+
+    assert_eq!(hits[1].probe, "main::counter::act");
+    assert_eq!(hits[1].value, "50");
+    assert_eq!(hits[1].reactions, vec!["ReactionAct".to_owned()]);
+    assert_eq!(hits[1].value_as::<u32>(), Some(&50));
+    assert_eq!(hits[1].value_as::<u64>(), None);
+
+    assert_eq!(hits[2].value, "7");
+}
@@ -0,0 +1,98 @@
+//! Checks the throttle and debounce decorators on a bursty action.
+
+use boomerang::builder::{reaction_closure, TriggerMode};
+use boomerang::prelude::*;
+
+/// The delays in msec at which the source action is scheduled, also used as the values.
+const BURSTS: [u32; 6] = [1, 2, 3, 11, 12, 31];
+
+/// The (elapsed msec, value) of the throttled and debounced events.
+#[derive(Default)]
+struct Received {
+    throttled: Vec<(i64, u32)>,
+    debounced: Vec<(i64, u32)>,
+}
+
+fn run() -> Received {
+    let mut env_builder = EnvBuilder::new();
+    let mut reactor = env_builder.add_reactor("main", None, None, Received::default());
+    let startup = reactor.get_startup_action();
+    let source = reactor.add_logical_action::<u32>("source", None).unwrap();
+    let throttled = reactor.throttle(source, Duration::milliseconds(5)).unwrap();
+    let debounced = reactor.debounce(source, Duration::milliseconds(5)).unwrap();
+
+    reactor
+        .add_reaction(
+            "burst",
+            reaction_closure!(ctx, _reactor, _ref_ports, _mut_ports, actions => {
+                let mut source: runtime::ActionRef<u32> = actions.partition_mut().unwrap();
+                for delay in BURSTS {
+                    source.schedule(ctx, delay, Some(Duration::milliseconds(delay as i64))).unwrap();
+                }
+            }),
+        )
+        .with_action(startup, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .with_action(source, 1, TriggerMode::EffectsOnly)
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    reactor
+        .add_reaction(
+            "receive",
+            reaction_closure!(ctx, reactor, _ref_ports, _mut_ports, actions => {
+                let (mut throttled, mut debounced): (runtime::ActionRef<u32>, runtime::ActionRef<u32>) =
+                    actions.partition_mut().unwrap();
+                let elapsed = ctx.get_elapsed_logical_time().whole_milliseconds() as i64;
+                let state = &mut reactor
+                    .downcast_mut::<runtime::Reactor<Received>>()
+                    .unwrap()
+                    .state;
+                if let Some(&value) = throttled.get_value(ctx) {
+                    state.throttled.push((elapsed, value));
+                }
+                if let Some(&value) = debounced.get_value(ctx) {
+                    state.debounced.push((elapsed, value));
+                }
+            }),
+        )
+        .with_action(throttled, 0, TriggerMode::TriggersAndUses)
+        .unwrap()
+        .with_action(debounced, 1, TriggerMode::TriggersAndUses)
+        .unwrap()
+        .finish()
+        .unwrap();
+    let reactor = reactor.finish().unwrap();
+
+    let (env, graph, aliases) = env_builder.into_runtime_parts().unwrap();
+    let key = env
+        .typed_reactor_key::<Received>(aliases.reactor_aliases[reactor])
+        .unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(50));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+    let mut env = sched.into_env();
+    std::mem::take(env.state_mut(key))
+}
+
+#[test]
+fn throttle_and_debounce() {
+    let received = run();
+    // Events within 5 msec of the last forwarded one are dropped
+    assert_eq!(received.throttled, [(1, 1), (11, 11), (31, 31)]);
+    // The last event of each burst is forwarded 5 msec after it
+    assert_eq!(received.debounced, [(8, 3), (17, 12), (36, 31)]);
+}
+
+#[test]
+fn decorate_startup_action() {
+    let mut env_builder = EnvBuilder::new();
+    let mut reactor = env_builder.add_reactor("main", None, None, ());
+    let startup = reactor.get_startup_action();
+    assert!(reactor
+        .throttle(startup, Duration::milliseconds(5))
+        .is_err());
+}
@@ -0,0 +1,53 @@
+//! Checks that a rate monitor records the intervals between the invocations of a timer.
+
+use boomerang::builder::{RateMonitor, TimerSpec};
+use boomerang::prelude::*;
+
+fn build(env_builder: &mut EnvBuilder) -> RateMonitor {
+    let mut reactor = env_builder.add_reactor("main", None, None, ());
+    let tick = reactor
+        .add_timer(
+            "tick",
+            TimerSpec {
+                period: Some(Duration::milliseconds(2)),
+                offset: None,
+            },
+        )
+        .unwrap();
+    let monitor = reactor.monitor_rate(tick, None).unwrap();
+    reactor.finish().unwrap();
+    monitor
+}
+
+#[test]
+fn rate_monitor() {
+    let mut env_builder = EnvBuilder::new();
+    let monitor = build(&mut env_builder);
+    assert_eq!(monitor.name(), "tick");
+    assert_eq!(monitor.stats(), None);
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default().with_timeout(Duration::milliseconds(20));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+
+    // Ticks at 0, 2, ..., 20 msec
+    let stats = monitor.stats().unwrap();
+    assert_eq!(stats.intervals, 10);
+    assert_eq!(stats.expected, Some(Duration::milliseconds(2)));
+    assert!(stats.min <= stats.mean && stats.mean <= stats.max);
+    assert!(stats.min <= stats.p99 && stats.p99 <= stats.max);
+    // The scheduler waits for the wall-clock time of each tick, so the ticks can't be much closer than the period on
+    // average. A single interval can be, right after a late tick.
+    assert!(stats.mean > Duration::milliseconds(1), "{stats}");
+    assert!(stats.max_jitter().is_some());
+}
+
+#[test]
+fn rate_monitor_startup() {
+    let mut env_builder = EnvBuilder::new();
+    let reactor = env_builder.add_reactor("main", None, None, ());
+    let startup = reactor.get_startup_action();
+    reactor.finish().unwrap();
+    assert!(env_builder.monitor_rate(startup, None).is_err());
+}
@@ -0,0 +1,112 @@
+//! Checks that the events logged by reactions are filtered by the directive of their reactor subtree.
+
+use std::sync::{Arc, Mutex};
+
+use boomerang::prelude::*;
+use boomerang_util::log_filter::ReactorFilter;
+use tracing_subscriber::{filter::LevelFilter, layer::SubscriberExt, Layer};
+
+/// Logs at every level at startup.
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionStartup")]
+struct Chatty;
+
+#[derive(Reaction)]
+#[reaction(reactor = "Chatty", triggers(startup))]
+struct ReactionStartup;
+
+impl runtime::Trigger<()> for ReactionStartup {
+    fn trigger(self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        tracing::trace!("chatty trace");
+        tracing::debug!("chatty debug");
+        tracing::info!("chatty info");
+        tracing::warn!("chatty warn");
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct Main {
+    #[reactor(child = ())]
+    noisy: Chatty,
+    #[reactor(child = ())]
+    quiet: Chatty,
+    #[reactor(child = ())]
+    other: Chatty,
+}
+
+/// Collects the formatted log output.
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl LogBuffer {
+    /// Take the levels of the chatty messages logged by the reactor `name` so far.
+    fn take_levels(&self, name: &str) -> Vec<String> {
+        let log = String::from_utf8(self.0.lock().unwrap().clone()).unwrap();
+        log.lines()
+            .filter(|line| line.contains(&format!("fqn=\"main::{name}::")))
+            .filter_map(|line| line.split("chatty ").nth(1))
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+fn run() {
+    let mut env_builder = EnvBuilder::new();
+    let _ = Main::build("main", (), None, None, &mut env_builder).unwrap();
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_reaction_spans(true);
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+}
+
+#[test]
+fn reactor_log_filter() {
+    let buffer = LogBuffer::default();
+    let writer = buffer.clone();
+    let filter =
+        ReactorFilter::new("main.noisy=debug,main::quiet=warn", LevelFilter::INFO).unwrap();
+    let handle = filter.handle();
+    let subscriber = tracing_subscriber::registry().with(
+        tracing_subscriber::fmt::layer()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .without_time()
+            .with_filter(filter),
+    );
+
+    tracing::subscriber::with_default(subscriber, || {
+        run();
+        assert_eq!(buffer.take_levels("noisy"), ["debug", "info", "warn"]);
+        assert_eq!(buffer.take_levels("quiet"), ["warn"]);
+        // Not matched by any directive
+        assert_eq!(buffer.take_levels("other"), ["info", "warn"]);
+
+        buffer.0.lock().unwrap().clear();
+        handle.set("main=error,main.quiet=trace").unwrap();
+        assert_eq!(handle.directives(), "main::quiet=trace,main=error");
+        run();
+        assert_eq!(buffer.take_levels("noisy"), Vec::<String>::new());
+        assert_eq!(
+            buffer.take_levels("quiet"),
+            ["trace", "debug", "info", "warn"]
+        );
+        assert_eq!(buffer.take_levels("other"), Vec::<String>::new());
+    });
+
+    assert!(handle.set("main=loud").is_err());
+}
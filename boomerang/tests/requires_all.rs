@@ -0,0 +1,88 @@
+//! Checks that a `requires_all` reaction only runs at tags where all of its triggering inputs are present.
+
+use boomerang::prelude::*;
+
+/// Emits the count on `a` at every tick, on `b` at every third tick, and on `c` at every other tick.
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionTick")]
+struct Source {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    a: TypedPortKey<u32, Output>,
+    b: TypedPortKey<u32, Output>,
+    c: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(action = "tick"))]
+struct ReactionTick<'a> {
+    a: runtime::OutputRef<'a, u32>,
+    b: runtime::OutputRef<'a, u32>,
+    c: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionTick<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u32) {
+        self.a.set_from(Some(*state));
+        self.b.set_from(state.is_multiple_of(3).then_some(*state));
+        self.c.set_from(state.is_multiple_of(2).then_some(*state));
+        *state += 1;
+    }
+}
+
+type Joined = Vec<(u32, Option<u32>)>;
+
+/// Records the values of `b` and `c` whenever it runs.
+#[derive(Reactor)]
+#[reactor(state = "Joined", reaction = "ReactionJoin")]
+struct Join {
+    a: TypedPortKey<u32, Input>,
+    b: TypedPortKey<u32, Input>,
+    c: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Join", requires_all)]
+struct ReactionJoin<'a> {
+    a: runtime::InputRef<'a, u32>,
+    b: runtime::InputRef<'a, u32>,
+    /// Uses-only inputs are not required
+    #[reaction(uses)]
+    c: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<Joined> for ReactionJoin<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut Joined) {
+        assert_eq!(*self.a, *self.b);
+        state.push((self.b.unwrap(), *self.c));
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.a", to = "join.a"),
+    connection(from = "source.b", to = "join.b"),
+    connection(from = "source.c", to = "join.c")
+)]
+struct Main {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = Joined::new())]
+    join: Join,
+}
+
+#[test]
+fn requires_all() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(7));
+    let (_, sched) =
+        boomerang_util::runner::build_and_test_reactor::<Main>("main", (), config).unwrap();
+    let env = sched.into_env();
+    let joined = env
+        .find_reactor_by_name("join")
+        .and_then(|reactor| reactor.get_state::<Joined>())
+        .unwrap();
+    assert_eq!(joined, &vec![(0, Some(0)), (3, None), (6, Some(6))]);
+}
@@ -0,0 +1,84 @@
+//! Checks the runtime events sent to the subscribers of a scheduler.
+
+use boomerang::builder::{reaction_closure, TriggerMode};
+use boomerang::prelude::*;
+
+#[test]
+fn runtime_events() {
+    let mut env_builder = EnvBuilder::new();
+    let reactor = env_builder.add_reactor("main", None, None, ());
+    let startup = reactor.get_startup_action();
+    let reactor_key = reactor.finish().unwrap();
+
+    env_builder
+        .add_reaction(
+            "slow",
+            reactor_key,
+            reaction_closure!(_ctx, _reactor, _ref_ports, _mut_ports, _actions => {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }),
+        )
+        .with_action(startup, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .finish()
+        .unwrap();
+    env_builder
+        .add_reaction("late", reactor_key, reaction_closure!())
+        .with_action(startup, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .with_deadline(Duration::milliseconds(1), || {})
+        .finish()
+        .unwrap();
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let mut sched = runtime::Scheduler::new(env, graph, runtime::Config::default());
+    let events = sched.subscribe();
+    let dropped = sched.subscribe_bounded(1);
+    drop(dropped);
+    sched.event_loop().unwrap();
+
+    let events = events.try_iter().collect::<Vec<_>>();
+    let startup = runtime::Tag::ZERO;
+    assert_eq!(
+        events[0],
+        runtime::RuntimeEvent::TagStarted { tag: startup }
+    );
+    assert!(matches!(
+        &events[1],
+        runtime::RuntimeEvent::ReactionExecuted { tag, reaction }
+            if *tag == startup && reaction.ends_with("slow")
+    ));
+    assert!(matches!(
+        &events[2],
+        runtime::RuntimeEvent::DeadlineViolated { tag, reaction, lag }
+            if *tag == startup && reaction.ends_with("late") && *lag >= Duration::milliseconds(1)
+    ));
+    assert!(matches!(
+        &events[3],
+        runtime::RuntimeEvent::ReactionExecuted { tag, reaction }
+            if *tag == startup && reaction.ends_with("late")
+    ));
+    assert_eq!(
+        events[4],
+        runtime::RuntimeEvent::TagCompleted {
+            tag: startup,
+            reactions: 2
+        }
+    );
+
+    // The shutdown tag follows, with no reactions triggered by it
+    let shutdown = match events[5] {
+        runtime::RuntimeEvent::ShutdownInitiated { tag } => tag,
+        ref event => panic!("Unexpected event {event}"),
+    };
+    assert_eq!(
+        &events[6..],
+        [
+            runtime::RuntimeEvent::TagStarted { tag: shutdown },
+            runtime::RuntimeEvent::TagCompleted {
+                tag: shutdown,
+                reactions: 0
+            },
+        ]
+    );
+}
@@ -0,0 +1,202 @@
+//! Checks that logical and timer actions can't be scheduled at or before the current tag, and that physical actions
+//! behind logical time are moved to the next microstep.
+
+use boomerang::{
+    builder::{BuilderError, TimerSpec},
+    prelude::*,
+};
+
+#[derive(Debug, Default)]
+struct State {
+    /// The tags of the rejected schedules, as `(requested, current)`
+    rejected: Vec<(&'static str, runtime::Tag, runtime::Tag)>,
+    /// The actions triggered, with their tag
+    triggered: Vec<(&'static str, runtime::Tag)>,
+}
+
+impl State {
+    fn check(&mut self, name: &'static str, result: Result<(), runtime::RuntimeError>) {
+        match result {
+            Ok(()) => {}
+            Err(runtime::RuntimeError::TagNotInFuture { requested, current }) => {
+                self.rejected.push((name, requested, current))
+            }
+            Err(err) => panic!("Unexpected error scheduling {name}: {err}"),
+        }
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "State",
+    reaction = "ReactionStartup",
+    reaction = "ReactionLater",
+    reaction = "ReactionRecord"
+)]
+struct Scheduling {
+    logical: TypedActionKey,
+    later: TypedActionKey,
+    physical: TypedActionKey<(), Physical>,
+    #[reactor(action(min_delay = "10 sec"))]
+    delayed: TypedActionKey<(), Physical>,
+    async_logical: TypedActionKey,
+    async_physical: TypedActionKey<(), Physical>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Scheduling", triggers(startup))]
+struct ReactionStartup<'a> {
+    logical: runtime::ActionRef<'a>,
+    later: runtime::ActionRef<'a>,
+}
+
+impl runtime::Trigger<State> for ReactionStartup<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut State) {
+        let result = self
+            .logical
+            .schedule(ctx, (), Some(Duration::milliseconds(-1)));
+        state.check("logical", result);
+        // A zero delay is the next microstep
+        let result = self.logical.schedule(ctx, (), None);
+        state.check("logical", result);
+        self.later
+            .schedule(ctx, (), Some(Duration::seconds(1)))
+            .unwrap();
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Scheduling", triggers(action = "later"))]
+struct ReactionLater<'a> {
+    physical: runtime::ActionRef<'a>,
+    delayed: runtime::ActionRef<'a>,
+    async_logical: runtime::AsyncActionRef,
+    async_physical: runtime::AsyncActionRef,
+}
+
+impl runtime::Trigger<State> for ReactionLater<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut State) {
+        // Logical time is far ahead of physical time in fast-forward mode
+        let result = self.physical.schedule(ctx, (), None);
+        state.check("physical", result);
+        let result = self.delayed.schedule(ctx, (), None);
+        state.check("delayed", result);
+
+        let send_ctx = ctx.make_send_context();
+        self.async_logical
+            .schedule(&send_ctx, (), Some(Duration::milliseconds(-1)));
+        self.async_physical.schedule(&send_ctx, (), None);
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Scheduling")]
+struct ReactionRecord<'a> {
+    #[reaction(triggers)]
+    logical: runtime::ActionRef<'a>,
+    #[reaction(triggers)]
+    physical: runtime::ActionRef<'a>,
+    #[reaction(triggers)]
+    delayed: runtime::ActionRef<'a>,
+    #[reaction(triggers)]
+    async_logical: runtime::ActionRef<'a>,
+    #[reaction(triggers)]
+    async_physical: runtime::ActionRef<'a>,
+}
+
+impl runtime::Trigger<State> for ReactionRecord<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut State) {
+        let tag = ctx.get_tag();
+        for (name, action) in [
+            ("logical", &mut self.logical),
+            ("physical", &mut self.physical),
+            ("delayed", &mut self.delayed),
+            ("async_logical", &mut self.async_logical),
+            ("async_physical", &mut self.async_physical),
+        ] {
+            // Events at the same tag aren't merged, so this reaction can run more than once per tag
+            if action.is_present(ctx) && !state.triggered.contains(&(name, tag)) {
+                state.triggered.push((name, tag));
+            }
+        }
+    }
+}
+
+#[test]
+fn schedule_past() {
+    let config = runtime::Config::default().with_fast_forward(true);
+    let (_, sched) = boomerang_util::runner::build_and_test_reactor::<Scheduling>(
+        "scheduling",
+        State::default(),
+        config,
+    )
+    .unwrap();
+    let env = sched.into_env();
+    let state = env
+        .find_reactor_by_name("scheduling")
+        .and_then(|reactor| reactor.get_state::<State>())
+        .unwrap();
+
+    assert_eq!(state.rejected.len(), 1, "{:?}", state.rejected);
+    assert_eq!(
+        state.rejected[0],
+        (
+            "logical",
+            runtime::Tag::new(Duration::milliseconds(-1), 0),
+            runtime::Tag::ZERO
+        )
+    );
+
+    // The async logical event is dropped, and both physical ones behind logical time are moved to the next microstep
+    let next_microstep = runtime::Tag::new(Duration::seconds(1), 1);
+    assert_eq!(state.triggered.len(), 4, "{:?}", state.triggered);
+    assert_eq!(
+        state.triggered[0],
+        ("logical", runtime::Tag::new(Duration::ZERO, 1))
+    );
+    assert_eq!(state.triggered[1], ("physical", next_microstep));
+    assert_eq!(state.triggered[2], ("async_physical", next_microstep));
+    let (name, tag) = state.triggered[3];
+    assert_eq!(name, "delayed");
+    assert!(tag > runtime::Tag::new(Duration::seconds(10), 0));
+}
+
+#[test]
+fn negative_delays() {
+    let mut env_builder = EnvBuilder::new();
+    let mut reactor = env_builder.add_reactor("main", None, None, ());
+    assert!(matches!(
+        reactor.add_timer(
+            "t",
+            TimerSpec {
+                period: Some(Duration::milliseconds(-1)),
+                offset: None,
+            },
+        ),
+        Err(BuilderError::NegativeDelay { .. })
+    ));
+    assert!(matches!(
+        reactor.add_timer(
+            "t",
+            TimerSpec {
+                period: None,
+                offset: Some(Duration::milliseconds(-1)),
+            },
+        ),
+        Err(BuilderError::NegativeDelay { .. })
+    ));
+    assert!(matches!(
+        reactor.add_logical_action::<()>("a", Some(Duration::milliseconds(-1))),
+        Err(BuilderError::NegativeDelay { .. })
+    ));
+    // A zero period ticks every microstep
+    assert!(reactor
+        .add_timer(
+            "t",
+            TimerSpec {
+                period: Some(Duration::ZERO),
+                offset: None,
+            },
+        )
+        .is_ok());
+}
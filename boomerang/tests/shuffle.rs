@@ -0,0 +1,140 @@
+//! Checks that the shuffled testing mode of the scheduler is reproducible from its seed, and that shuffling the
+//! reactions within a level doesn't change the results.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use boomerang::builder::{reaction_closure, TriggerMode};
+use boomerang::prelude::*;
+use common::Source;
+
+/// Run independent startup reactions of `n` reactors, returning the order they ran in.
+fn run_order(n: usize, seed: u64) -> Vec<usize> {
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let mut env_builder = EnvBuilder::new();
+    for i in 0..n {
+        let reactor = env_builder.add_reactor(&format!("reactor{i}"), None, None, ());
+        let startup = reactor.get_startup_action();
+        let reactor_key = reactor.finish().unwrap();
+        let order = order.clone();
+        let _ = env_builder
+            .add_reaction(
+                "record",
+                reactor_key,
+                reaction_closure!(_ctx, _reactor, _ref_ports, _mut_ports, _actions => {
+                    order.lock().unwrap().push(i);
+                }),
+            )
+            .with_action(startup, 0, TriggerMode::TriggersOnly)
+            .unwrap()
+            .finish()
+            .unwrap();
+    }
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_shuffle_seed(seed);
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+    let order = order.lock().unwrap().clone();
+    order
+}
+
+#[test]
+fn shuffle_reproducible() {
+    let orders: Vec<_> = (0..8).map(|seed| run_order(8, seed)).collect();
+    for order in &orders {
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..8).collect::<Vec<_>>());
+    }
+    assert!(orders.iter().any(|order| order != &orders[0]));
+
+    // The interleaving of parallel workers is up to the OS, so the order is only reproducible when sequential.
+    #[cfg(not(feature = "parallel"))]
+    assert_eq!(orders[3], run_order(8, 3));
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionPass")]
+struct Pass {
+    inp: TypedPortKey<u32, Input>,
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Pass")]
+struct ReactionPass<'a> {
+    inp: runtime::InputRef<'a, u32>,
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionPass<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.out = *self.inp;
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionSum")]
+struct Sum {
+    a: TypedPortKey<u32, Input>,
+    b: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sum")]
+struct ReactionSum<'a> {
+    a: runtime::InputRef<'a, u32>,
+    b: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionSum<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut u32) {
+        assert!(
+            self.a.is_some() && *self.a == *self.b,
+            "Inputs must arrive together"
+        );
+        *state += *self.a.as_ref().unwrap() + *self.b.as_ref().unwrap();
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to = "p1.inp"),
+    connection(from = "source.out", to = "p2.inp"),
+    connection(from = "p1.out", to = "sum.a"),
+    connection(from = "p2.out", to = "sum.b")
+)]
+#[allow(clippy::duplicated_attributes)]
+struct Main {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = ())]
+    p1: Pass,
+    #[reactor(child = ())]
+    p2: Pass,
+    #[reactor(child = 0)]
+    sum: Sum,
+}
+
+#[test]
+fn shuffle_same_results() {
+    for seed in 0..16 {
+        let config = runtime::Config::default()
+            .with_fast_forward(true)
+            .with_timeout(Duration::milliseconds(10))
+            .with_shuffle_seed(seed);
+        let (_, sched) =
+            boomerang_util::runner::build_and_test_reactor::<Main>("main", (), config).unwrap();
+        let env = sched.into_env();
+        let sum = env
+            .find_reactor_by_name("sum")
+            .and_then(|reactor| reactor.get_state::<u32>())
+            .copied();
+        assert_eq!(sum, Some(110), "seed {seed}");
+    }
+}
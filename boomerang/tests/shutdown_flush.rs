@@ -0,0 +1,148 @@
+//! Checks that the flush of a sink runs after all values produced at the shutdown tag were received, including those
+//! set by shutdown reactions.
+
+use std::sync::{Arc, Mutex};
+
+use boomerang::prelude::*;
+
+/// The last value, emitted by the source at shutdown.
+const LAST: u32 = 100;
+
+/// Emits an increasing count every msec, including at the shutdown tag, and [`LAST`] at shutdown.
+#[derive(Reactor)]
+#[reactor(
+    state = "u32",
+    reaction = "ReactionTick",
+    reaction = "ReactionSourceShutdown"
+)]
+struct Source {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(action = "tick"))]
+struct ReactionTick<'a> {
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionTick<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, count: &mut u32) {
+        *self.out = Some(*count);
+        *count += 1;
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(shutdown))]
+struct ReactionSourceShutdown<'a> {
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionSourceShutdown<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _count: &mut u32) {
+        *self.out = Some(LAST);
+    }
+}
+
+/// The log of a sink, persisted by its flush.
+type Log = Vec<String>;
+
+/// Buffers the values received, as a file writer would. Its shutdown reaction runs before its input reaction.
+#[derive(Reactor)]
+#[reactor(state = "Log", reaction = "ReactionShutdown", reaction = "ReactionInp")]
+struct Sink {
+    inp: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink")]
+struct ReactionInp<'a> {
+    inp: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<Log> for ReactionInp<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, log: &mut Log) {
+        log.extend(self.inp.map(|value| format!("received {value}")));
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink", triggers(shutdown))]
+struct ReactionShutdown;
+
+impl runtime::Trigger<Log> for ReactionShutdown {
+    fn trigger(self, ctx: &mut runtime::Context, log: &mut Log) {
+        log.push(format!("shutdown at {}", ctx.get_tag()));
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(state = "()", connection(from = "source.out", to = "sink.inp"))]
+struct Main {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = Log::new())]
+    sink: Sink,
+}
+
+/// Build and run `Main` until 3 msec, flushing the log of the sink with `flush`.
+fn run(
+    flush: impl FnOnce(&mut Log) -> Result<(), String> + Send + 'static,
+) -> Result<(), runtime::RuntimeError> {
+    let mut env_builder = EnvBuilder::new();
+    let _ = Main::build("main", (), None, None, &mut env_builder).unwrap();
+    let reactor_key = env_builder.find_reactor_by_fqn("main::sink").unwrap();
+    env_builder
+        .get_reactor_builder(reactor_key)
+        .unwrap()
+        .add_flush("persist", flush)
+        .unwrap();
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    assert_eq!(env.flushes[0].name(), "main::sink::persist");
+
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(3));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop()
+}
+
+#[test]
+fn shutdown_flush() {
+    let persisted = Arc::new(Mutex::new(Vec::new()));
+    let file = persisted.clone();
+    run(move |log| {
+        file.lock().unwrap().extend(log.drain(..));
+        Ok(())
+    })
+    .unwrap();
+
+    // The value set by the shutdown reaction of the source arrives after the shutdown reaction of the sink
+    assert_eq!(
+        *persisted.lock().unwrap(),
+        [
+            "received 0",
+            "received 1",
+            "received 2",
+            "received 3",
+            "shutdown at [3ms+0]",
+            "received 100",
+        ]
+    );
+}
+
+#[test]
+fn shutdown_flush_failed() {
+    let err = run(|_| Err("disk full".to_owned())).expect_err("The flush failed");
+    assert!(
+        matches!(
+            &err,
+            runtime::RuntimeError::FlushFailed { name, reason }
+                if name == "main::sink::persist" && reason == "disk full"
+        ),
+        "{err}"
+    );
+}
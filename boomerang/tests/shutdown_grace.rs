@@ -0,0 +1,79 @@
+//! Checks that the scheduler abandons shutdown reactions that hang past the shutdown grace period.
+
+use boomerang::builder::{reaction_closure, TriggerMode};
+use boomerang::prelude::*;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Run a reactor whose shutdown reaction sleeps for `shutdown_delay`, setting `flushed` in its flush if it closed.
+fn run(
+    shutdown_delay: std::time::Duration,
+    flushed: Arc<AtomicBool>,
+) -> (runtime::Scheduler, Result<(), runtime::RuntimeError>) {
+    let mut env_builder = EnvBuilder::new();
+    let mut reactor = env_builder.add_reactor("main", None, None, false);
+    let shutdown = reactor.get_shutdown_action();
+    reactor
+        .add_flush("persist", move |closed: &mut bool| {
+            flushed.store(*closed, Ordering::SeqCst);
+            Ok::<_, String>(())
+        })
+        .unwrap();
+    let reactor_key = reactor.finish().unwrap();
+    let _ = env_builder
+        .add_reaction(
+            "close",
+            reactor_key,
+            reaction_closure!(_ctx, reactor, _ref_ports, _mut_ports, _actions => {
+                std::thread::sleep(shutdown_delay);
+                reactor.downcast_mut::<runtime::Reactor<bool>>().unwrap().state = true;
+            }),
+        )
+        .with_action(shutdown, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_shutdown_grace(Duration::milliseconds(100));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    let res = sched.event_loop();
+    (sched, res)
+}
+
+fn closed(env: &runtime::Env) -> Option<bool> {
+    env.find_reactor_by_name("main")
+        .and_then(|reactor| reactor.get_state::<bool>())
+        .copied()
+}
+
+#[test]
+fn shutdown_within_grace() {
+    let flushed = Arc::new(AtomicBool::new(false));
+    let (sched, res) = run(std::time::Duration::ZERO, flushed.clone());
+    res.unwrap();
+    assert!(flushed.load(Ordering::SeqCst));
+    assert_eq!(closed(&sched.into_env()), Some(true));
+}
+
+#[test]
+fn shutdown_hangs() {
+    let start = std::time::Instant::now();
+    let flushed = Arc::new(AtomicBool::new(false));
+    let (sched, res) = run(std::time::Duration::from_secs(1), flushed.clone());
+    assert!(start.elapsed() < std::time::Duration::from_millis(800));
+    assert!(matches!(
+        res,
+        Err(runtime::RuntimeError::ShutdownTimeout { pending, .. }) if pending == ["main::close"]
+    ));
+
+    assert!(!flushed.load(Ordering::SeqCst));
+
+    // The environment is recovered once the abandoned reaction and the flush complete.
+    assert_eq!(closed(&sched.into_env()), Some(true));
+    assert!(flushed.load(Ordering::SeqCst));
+}
@@ -0,0 +1,124 @@
+//! Checks that a shutdown preempts the far-future events on the queue, independently of how many are pending.
+
+use std::time::Instant;
+
+use boomerang::prelude::*;
+
+#[derive(Debug, Default)]
+struct FloodState {
+    /// The number of far-future events to schedule at startup
+    pending: u32,
+    /// When the shutdown was requested
+    requested: Option<Instant>,
+    /// From the shutdown request to the shutdown reaction
+    latency: Option<std::time::Duration>,
+    ticks: u32,
+}
+
+#[derive(Reactor, Clone)]
+#[reactor(
+    state = "FloodState",
+    reaction = "ReactionStartup",
+    reaction = "ReactionStop",
+    reaction = "ReactionTick",
+    reaction = "ReactionShutdown"
+)]
+struct Flood {
+    tick: TypedActionKey<u32>,
+    stop: TypedActionKey,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Flood", triggers(startup))]
+struct ReactionStartup<'a> {
+    #[reaction(effects)]
+    tick: runtime::ActionRef<'a, u32>,
+    #[reaction(effects)]
+    stop: runtime::ActionRef<'a>,
+}
+
+impl runtime::Trigger<FloodState> for ReactionStartup<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut FloodState) {
+        for i in 1..=state.pending {
+            self.tick
+                .schedule(ctx, i, Some(Duration::seconds(i as i64)))
+                .unwrap();
+        }
+        self.stop
+            .schedule(ctx, (), Some(Duration::milliseconds(1)))
+            .unwrap();
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Flood", triggers(action = "stop"))]
+struct ReactionStop<'a> {
+    #[reaction(effects)]
+    tick: runtime::ActionRef<'a, u32>,
+}
+
+impl runtime::Trigger<FloodState> for ReactionStop<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut FloodState) {
+        state.requested = Some(Instant::now());
+        ctx.schedule_shutdown(None);
+        // Scheduled after the shutdown, so discarded instead of queued
+        self.tick
+            .schedule(ctx, 0, Some(Duration::seconds(1)))
+            .unwrap();
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Flood", triggers(action = "tick"))]
+struct ReactionTick;
+
+impl runtime::Trigger<FloodState> for ReactionTick {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut FloodState) {
+        state.ticks += 1;
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Flood", triggers(shutdown))]
+struct ReactionShutdown;
+
+impl runtime::Trigger<FloodState> for ReactionShutdown {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut FloodState) {
+        state.latency = state.requested.map(|requested| requested.elapsed());
+    }
+}
+
+fn shutdown_latency(pending: u32) -> std::time::Duration {
+    let mut env_builder = EnvBuilder::new();
+    let state = FloodState {
+        pending,
+        ..Default::default()
+    };
+    let _ = Flood::build("flood", state, None, None, &mut env_builder).unwrap();
+    let reactor_key = env_builder.find_reactor_by_fqn("flood").unwrap();
+    let (env, graph, aliases) = env_builder.into_runtime_parts().unwrap();
+    let key = env
+        .typed_reactor_key::<FloodState>(aliases.reactor_aliases[reactor_key])
+        .unwrap();
+
+    let config = runtime::Config::default().with_fast_forward(true);
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+
+    let env = sched.into_env();
+    let state = env.state(key);
+    assert_eq!(state.ticks, 0, "No far-future event should be processed");
+    state
+        .latency
+        .expect("The shutdown reaction should have run")
+}
+
+#[test]
+fn shutdown_latency_independent_of_queue() {
+    let small = shutdown_latency(1_000);
+    let large = shutdown_latency(200_000);
+    assert!(
+        large < small * 10 + std::time::Duration::from_millis(20),
+        "Shutdown took {large:?} with 200000 pending events, {small:?} with 1000"
+    );
+}
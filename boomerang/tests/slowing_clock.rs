@@ -42,7 +42,7 @@ struct ReactionStartup<'a> {
 impl runtime::Trigger<SlowingClock> for ReactionStartup<'_> {
     fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut SlowingClock) {
         println!("startup");
-        self.a.schedule(ctx, (), None);
+        self.a.schedule(ctx, (), None).unwrap();
     }
 }
 
@@ -63,7 +63,7 @@ impl runtime::Trigger<SlowingClock> for ReactionA<'_> {
             state.expected_time
         );
 
-        self.a.schedule(ctx, (), Some(state.interval));
+        self.a.schedule(ctx, (), Some(state.interval)).unwrap();
         state.expected_time += Duration::milliseconds(100) + state.interval;
         state.interval += Duration::milliseconds(100);
     }
@@ -0,0 +1,136 @@
+//! Checks that reactions declaring disjoint state fields are not ordered after each other within their reactor.
+
+use boomerang::prelude::*;
+
+/// Emits the count at every tick, directly on `early`, and through a relay on `late`.
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionTick")]
+struct Source {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    early: TypedPortKey<u32, Output>,
+    late: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source", triggers(action = "tick"))]
+struct ReactionTick<'a> {
+    early: runtime::OutputRef<'a, u32>,
+    late: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<u32> for ReactionTick<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u32) {
+        *self.early = Some(*state);
+        *self.late = Some(*state);
+        *state += 1;
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionRelay")]
+struct Relay {
+    inp: TypedPortKey<u32, Input>,
+    out: TypedPortKey<u32, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Relay")]
+struct ReactionRelay<'a> {
+    inp: runtime::InputRef<'a, u32>,
+    out: runtime::OutputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionRelay<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        *self.out = *self.inp;
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Totals {
+    late_sum: u32,
+    early: Vec<u32>,
+}
+
+/// Sums the late values and records the early ones, each in its own field of the state.
+#[derive(Reactor)]
+#[reactor(
+    state = "Totals",
+    reaction = "ReactionLate",
+    reaction = "ReactionEarly"
+)]
+struct Stats {
+    late: TypedPortKey<u32, Input>,
+    early: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Stats", state(fields = "late_sum"))]
+struct ReactionLate<'a> {
+    late: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::TriggerFields<ReactionLateState<'_, u32>> for ReactionLate<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: ReactionLateState<'_, u32>) {
+        *state.late_sum += self.late.unwrap();
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Stats", state(fields = "early"))]
+struct ReactionEarly<'a> {
+    early: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::TriggerFields<ReactionEarlyState<'_, Vec<u32>>> for ReactionEarly<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: ReactionEarlyState<'_, Vec<u32>>) {
+        state.early.extend(*self.early);
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.early", to = "stats.early"),
+    connection(from = "source.late", to = "relay.inp"),
+    connection(from = "relay.out", to = "stats.late")
+)]
+struct Main {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = ())]
+    relay: Relay,
+    #[reactor(child = Totals::default())]
+    stats: Stats,
+}
+
+#[test]
+fn state_fields() -> Result<(), BuilderError> {
+    let mut env_builder = EnvBuilder::new();
+    let _main = Main::build("main", (), None, None, &mut env_builder)?;
+
+    let stats = env_builder.find_reactor_by_fqn("main::stats")?;
+    let late = env_builder.find_reaction_by_name("ReactionLate", stats)?;
+    let early = env_builder.find_reaction_by_name("ReactionEarly", stats)?;
+    let levels = env_builder.build_runtime_level_map()?;
+    // Without declared fields, the later reaction would run after the earlier one
+    assert!(levels[early] < levels[late]);
+
+    let (env, graph, _) = env_builder.into_runtime_parts()?;
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(4));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+
+    let env = sched.into_env();
+    let totals = env
+        .find_reactor_by_name("stats")
+        .and_then(|reactor| reactor.get_state::<Totals>())
+        .unwrap();
+    assert_eq!(totals.early, [0, 1, 2, 3, 4]);
+    assert_eq!(totals.late_sum, 10);
+    Ok(())
+}
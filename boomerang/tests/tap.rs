@@ -0,0 +1,78 @@
+//! Checks that a connection tap sees every value of the tapped connection without disturbing its consumers.
+
+mod common;
+
+use boomerang::builder::{reaction_closure, TriggerMode};
+use boomerang::prelude::*;
+use common::{Sink, Source};
+
+#[derive(Reactor)]
+#[reactor(state = "()", connection(from = "source.out", to = "sink.inp"))]
+struct Main {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = Vec::new())]
+    sink: Sink,
+}
+
+#[test]
+fn tap_connection() -> Result<(), BuilderError> {
+    let mut env_builder = EnvBuilder::new();
+    let _main = Main::build("main", (), None, None, &mut env_builder)?;
+
+    let sink_key = env_builder.find_reactor_by_fqn("main::sink")?;
+    let sink_reaction = env_builder.find_reaction_by_name("ReactionInp", sink_key)?;
+    let sink_level = env_builder.build_runtime_level_map()?[sink_reaction];
+
+    // The logger lives outside of the `main` hierarchy.
+    let logger = env_builder
+        .add_reactor("logger", None, None, Vec::<u32>::new())
+        .finish()?;
+    let tap = env_builder.tap_connection::<u32>("main::source::out", logger)?;
+    assert_eq!(
+        env_builder.find_port_by_fqn("logger::tap_main_source_out")?,
+        tap.into()
+    );
+    let _ = env_builder
+        .add_reaction(
+            "log",
+            logger,
+            reaction_closure!(_ctx, reactor, ref_ports, _mut_ports, _actions => {
+                let tap: runtime::InputRef<u32> = ref_ports.partition().unwrap();
+                let state = &mut reactor
+                    .downcast_mut::<runtime::Reactor<Vec<u32>>>()
+                    .unwrap()
+                    .state;
+                state.extend(*tap);
+            }),
+        )
+        .with_port(tap, 0, TriggerMode::TriggersAndUses)?
+        .finish()?;
+
+    assert_eq!(
+        env_builder.build_runtime_level_map()?[sink_reaction],
+        sink_level
+    );
+    assert!(matches!(
+        env_builder.tap_connection::<f32>("main::source::out", logger),
+        Err(BuilderError::InconsistentBuilderState { .. })
+    ));
+
+    let (env, graph, _) = env_builder.into_runtime_parts()?;
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(5));
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+    let env = sched.into_env();
+
+    let recorded = |name: &str| {
+        env.find_reactor_by_name(name)
+            .and_then(|reactor| reactor.get_state::<Vec<u32>>())
+            .cloned()
+            .unwrap()
+    };
+    assert_eq!(recorded("sink"), (0..=5).collect::<Vec<_>>());
+    assert_eq!(recorded("logger"), recorded("sink"));
+    Ok(())
+}
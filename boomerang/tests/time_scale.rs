@@ -0,0 +1,86 @@
+//! Checks that a time scale runs logical time faster or slower than the wall clock, without the tags drifting.
+
+use boomerang::prelude::*;
+
+/// The logical and physical time elapsed at every tick.
+type Ticks = Vec<(Duration, std::time::Duration)>;
+
+#[derive(Reactor)]
+#[reactor(state = "Ticks", reaction = "ReactionTick")]
+struct Clock {
+    #[reactor(timer(period = "10 msec"))]
+    tick: TimerActionKey,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Clock", triggers(action = "tick"))]
+struct ReactionTick;
+
+impl runtime::Trigger<Ticks> for ReactionTick {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut Ticks) {
+        state.push((
+            ctx.get_elapsed_logical_time(),
+            ctx.get_physical_time() - ctx.get_start_time(),
+        ));
+    }
+}
+
+fn run(config: runtime::Config) -> (Ticks, std::time::Duration) {
+    let mut env_builder = EnvBuilder::new();
+    let _ = Clock::build("clock", Ticks::new(), None, None, &mut env_builder).unwrap();
+    let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+
+    let started = std::time::Instant::now();
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+    let elapsed = started.elapsed();
+
+    let ticks = sched
+        .into_env()
+        .find_reactor_by_name("clock")
+        .and_then(|reactor| reactor.get_state::<Ticks>())
+        .cloned()
+        .unwrap();
+    (ticks, elapsed)
+}
+
+#[test]
+fn time_scale_faster() {
+    let (ticks, elapsed) = run(runtime::Config::default()
+        .with_timeout(Duration::milliseconds(200))
+        .with_time_scale(10.0));
+    assert_eq!(ticks.len(), 21);
+    for (logical, physical) in &ticks {
+        // No tag is processed before its scaled physical time
+        assert!(*physical >= std::time::Duration::try_from(*logical / 10).unwrap());
+    }
+    assert_eq!(ticks.last().unwrap().0, Duration::milliseconds(200));
+    assert!(
+        elapsed < std::time::Duration::from_millis(150),
+        "{elapsed:?}"
+    );
+}
+
+#[test]
+fn time_scale_slower() {
+    let mut config = runtime::Config::default().with_timeout(Duration::milliseconds(20));
+    config.time_scale = runtime::TimeScale::new(1, 3).unwrap();
+    let (ticks, elapsed) = run(config);
+    assert_eq!(ticks.len(), 3);
+    let (logical, physical) = ticks.last().unwrap();
+    assert_eq!(*logical, Duration::milliseconds(20));
+    assert!(
+        *physical >= std::time::Duration::from_millis(60),
+        "{physical:?}"
+    );
+    assert!(
+        elapsed >= std::time::Duration::from_millis(60),
+        "{elapsed:?}"
+    );
+}
+
+#[test]
+#[should_panic(expected = "strictly positive")]
+fn time_scale_invalid() {
+    let _ = runtime::Config::default().with_time_scale(0.0);
+}
@@ -0,0 +1,83 @@
+//! Snapshot tests of derived Reactor topologies, generated with `#[reactor(emit_topology_test)]`.
+//!
+//! The generated test builds the Reactor with `Default::default()` as its state, unless another state is given with
+//! `#[reactor(topology_state = ...)]`.
+
+mod common;
+
+use boomerang::prelude::*;
+use common::{Sink, Source};
+
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to = "sink.inp"),
+    emit_topology_test = r#"
+        reactor main
+        reactor main::sink
+        port main::sink::inp: Input<u32>
+        reaction main::sink::ReactionInp (L2) triggers=[main::sink::inp] uses=[main::sink::inp] effects=[]
+        reactor main::source
+        port main::source::out: Output<u32>
+        action main::source::tick: Logical
+        reaction main::source::_tick_startup (L0) triggers=[main::source::__startup, main::source::tick] uses=[] effects=[]
+        reaction main::source::ReactionTick (L1) triggers=[main::source::tick] uses=[] effects=[main::source::out]
+        binding main::source::out -> main::sink::inp
+    "#
+)]
+struct Main {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = Vec::new())]
+    sink: Sink,
+}
+
+/// A state without a `Default` impl.
+struct NoDefault;
+
+#[derive(Reactor)]
+#[reactor(
+    state = "NoDefault",
+    connection(from = "source.out", to = "sink.inp"),
+    emit_topology_test = r#"
+        reactor main
+        reactor main::sink
+        port main::sink::inp: Input<u32>
+        reaction main::sink::ReactionInp (L2) triggers=[main::sink::inp] uses=[main::sink::inp] effects=[]
+        reactor main::source
+        port main::source::out: Output<u32>
+        action main::source::tick: Logical
+        reaction main::source::_tick_startup (L0) triggers=[main::source::__startup, main::source::tick] uses=[] effects=[]
+        reaction main::source::ReactionTick (L1) triggers=[main::source::tick] uses=[] effects=[main::source::out]
+        binding main::source::out -> main::sink::inp
+    "#,
+    topology_state = NoDefault
+)]
+struct WithState {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = Vec::new())]
+    sink: Sink,
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()", connection(from = "source.out", to = "sink.inp"), debug)]
+struct Debugged {
+    #[reactor(child = 0)]
+    source: Source,
+    #[reactor(child = Vec::new())]
+    sink: Sink,
+}
+
+#[test]
+fn dump_topology() {
+    let topology = Debugged::dump_topology(()).unwrap();
+    assert!(topology.starts_with("reactor main\n"));
+    assert!(topology.ends_with("binding main::source::out -> main::sink::inp"));
+
+    let mut env_builder = EnvBuilder::new();
+    let reactor = Debugged::build("main", (), None, None, &mut env_builder).unwrap();
+    let debug = format!("{reactor:?}");
+    assert!(debug.starts_with("Debugged { source: "), "{debug}");
+    assert!(debug.contains("Source"), "{debug}");
+}
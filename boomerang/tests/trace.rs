@@ -0,0 +1,44 @@
+//! Checks that the scheduler traces reactions and that the trace yields a critical path for every tag.
+
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionTick")]
+struct Busy {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Busy", triggers(action = "tick"))]
+struct ReactionTick;
+
+impl runtime::Trigger<()> for ReactionTick {
+    fn trigger(self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        std::thread::sleep(std::time::Duration::from_micros(200));
+    }
+}
+
+#[test]
+fn trace() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(4))
+        .with_trace(true);
+    let (_, sched) =
+        boomerang_util::runner::build_and_test_reactor::<Busy>("busy", (), config).unwrap();
+
+    let trace = sched.trace();
+    let analysis = runtime::trace::analyze(trace);
+    assert_eq!(analysis.paths.len(), trace.tags.len());
+    // The startup tag only runs the timer startup reaction, which schedules the first tick.
+    assert_eq!(analysis.paths[0].reactions, ["busy::_tick_startup"]);
+    assert!(analysis.paths[1..]
+        .iter()
+        .all(|path| path.reactions == ["busy::_tick_startup", "busy::ReactionTick"]));
+
+    let dominant = analysis.dominant(1);
+    assert_eq!(dominant[0].reaction, "busy::ReactionTick");
+    assert_eq!(dominant[0].critical_invocations, 5);
+    assert!(dominant[0].critical_total >= std::time::Duration::from_millis(1));
+}
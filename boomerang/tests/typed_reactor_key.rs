@@ -0,0 +1,25 @@
+//! Checks that typed reactor keys give infallible access to the state of a reactor, and are only created for the
+//! right state type.
+
+mod common;
+
+use boomerang::prelude::*;
+use common::Source;
+
+#[test]
+fn typed_reactor_key() {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(9));
+    let (_, sched, source) =
+        boomerang_util::runner::build_and_test_reactor_typed::<Source>("source", 0, config)
+            .unwrap();
+    let mut env = sched.into_env();
+    assert_eq!(*env.state(source), 10);
+
+    *env.state_mut(source) = 20;
+    assert_eq!(*env.state(source), 20);
+
+    assert_eq!(env.typed_reactor_key::<u32>(source.key()), Some(source));
+    assert_eq!(env.typed_reactor_key::<i32>(source.key()), None);
+}
@@ -34,12 +34,24 @@ impl ActionTag for Physical {
 /// `TypedActionKey` is a typed wrapper around [`BuilderActionKey`] that is used to associate a type
 /// with an action. This is used to ensure that the type of the action matches the type of the port
 /// that it is connected to.
-#[derive(Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[derive(Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct TypedActionKey<T = (), Q = Logical>(BuilderActionKey, PhantomData<(T, Q)>)
 where
     T: runtime::ReactorData,
     Q: ActionTag;
 
+impl<T: runtime::ReactorData, Q: ActionTag> Debug for TypedActionKey<T, Q> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tag = if Q::IS_LOGICAL { "Logical" } else { "Physical" };
+        write!(
+            f,
+            "TypedActionKey<{}, {tag}>({:?})",
+            std::any::type_name::<T>(),
+            self.0
+        )
+    }
+}
+
 impl<T: runtime::ReactorData, Q: ActionTag> Copy for TypedActionKey<T, Q> {}
 
 impl<T: runtime::ReactorData, Q: ActionTag> Clone for TypedActionKey<T, Q> {
@@ -48,6 +60,12 @@ impl<T: runtime::ReactorData, Q: ActionTag> Clone for TypedActionKey<T, Q> {
     }
 }
 
+impl<T: runtime::ReactorData, Q: ActionTag> TypedActionKey<T, Q> {
+    pub fn iter(&self) -> impl Iterator<Item = &Self> + Clone {
+        std::iter::once(self)
+    }
+}
+
 impl<T: runtime::ReactorData, Q: ActionTag> From<BuilderActionKey> for TypedActionKey<T, Q> {
     fn from(key: BuilderActionKey) -> Self {
         Self(key, PhantomData)
@@ -134,8 +152,15 @@ pub enum ActionType {
     },
 }
 
-pub trait ActionBuilderFn: Fn(&str, runtime::ActionKey) -> Box<dyn runtime::BaseAction> {}
-impl<F> ActionBuilderFn for F where F: Fn(&str, runtime::ActionKey) -> Box<dyn runtime::BaseAction> {}
+/// Creates the runtime action from its name, key and minimum delay.
+pub trait ActionBuilderFn:
+    Fn(&str, runtime::ActionKey, Option<runtime::Duration>) -> Box<dyn runtime::BaseAction>
+{
+}
+impl<F> ActionBuilderFn for F where
+    F: Fn(&str, runtime::ActionKey, Option<runtime::Duration>) -> Box<dyn runtime::BaseAction>
+{
+}
 
 impl Debug for dyn ActionBuilderFn {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -151,6 +176,8 @@ pub struct ActionBuilder {
     reactor_key: BuilderReactorKey,
     /// Logical type of the action
     r#type: ActionType,
+    /// The name of the type of values carried by the action
+    type_name: &'static str,
     /// Out-going Reactions that this action triggers
     pub triggers: SecondaryMap<BuilderReactionKey, ()>,
     /// List of Reactions that may schedule this action
@@ -164,11 +191,16 @@ impl ParentReactorBuilder for ActionBuilder {
 }
 
 impl ActionBuilder {
-    pub fn new(name: &str, reactor_key: BuilderReactorKey, r#type: ActionType) -> Self {
+    pub fn new<T: runtime::ReactorData>(
+        name: &str,
+        reactor_key: BuilderReactorKey,
+        r#type: ActionType,
+    ) -> Self {
         Self {
             name: name.to_owned(),
             reactor_key,
             r#type,
+            type_name: std::any::type_name::<T>(),
             triggers: SecondaryMap::new(),
             schedulers: SecondaryMap::new(),
         }
@@ -185,4 +217,16 @@ impl ActionBuilder {
     pub fn r#type(&self) -> &ActionType {
         &self.r#type
     }
+
+    /// The name of the type of values carried by the action
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Change the minimum delay of a standard action, e.g. to balance a delayed connection.
+    pub(crate) fn set_min_delay(&mut self, delay: Option<runtime::Duration>) {
+        if let ActionType::Standard { min_delay, .. } = &mut self.r#type {
+            *min_delay = delay;
+        }
+    }
 }
@@ -0,0 +1,96 @@
+use crate::{BuilderError, BuilderFqn, BuilderFqnPattern, EnvBuilder};
+
+impl EnvBuilder {
+    /// Declare `old_fqn` as an alias for `new_fqn`, so that references to elements by their old fully-qualified name
+    /// keep working after a reactor is renamed or moved elsewhere in the hierarchy.
+    ///
+    /// Aliases are resolved by all FQN lookups ([`EnvBuilder::find_reactor_by_fqn`],
+    /// [`EnvBuilder::find_port_by_fqn`], [`EnvBuilder::find_physical_action_by_fqn`], probes and taps), and by the
+    /// `find_*_matching` lookups of patterns without wildcards, see [`EnvBuilder::resolve_pattern`]. An alias matches
+    /// any FQN starting with `old_fqn`, so aliasing a reactor also aliases all its ports, actions and children. When
+    /// several aliases match, the longest one wins, and chains of aliases are followed.
+    ///
+    /// The target must already exist, so aliases are declared after the renamed elements are built.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,ignore
+    /// // `filter` used to be a direct child of `main`
+    /// env_builder.add_alias("main::filter", "main::pipeline::filter")?;
+    /// let out = env_builder.find_port_by_fqn("main::filter::out")?;
+    /// ```
+    pub fn add_alias<T, U>(&mut self, old_fqn: T, new_fqn: U) -> Result<(), BuilderError>
+    where
+        T: TryInto<BuilderFqn>,
+        T::Error: Into<BuilderError>,
+        U: TryInto<BuilderFqn>,
+        U::Error: Into<BuilderError>,
+    {
+        let old_fqn: BuilderFqn = old_fqn.try_into().map_err(Into::into)?;
+        let new_fqn: BuilderFqn = new_fqn.try_into().map_err(Into::into)?;
+        let invalid = |what: &str| BuilderError::InvalidAlias {
+            alias: old_fqn.to_string(),
+            target: new_fqn.to_string(),
+            what: what.to_owned(),
+        };
+
+        if let Some(existing) = self.fqn_aliases.get(&old_fqn) {
+            return Err(invalid(&format!("already an alias for '{existing}'")));
+        }
+
+        self.fqn_aliases.insert(old_fqn.clone(), new_fqn.clone());
+        let Ok(resolved) = self.resolve_fqn(old_fqn.clone()) else {
+            self.fqn_aliases.remove(&old_fqn);
+            return Err(invalid("the alias would never resolve"));
+        };
+        if !self.fqn_exists(&resolved)? {
+            self.fqn_aliases.remove(&old_fqn);
+            return Err(invalid(&format!("'{resolved}' does not exist")));
+        }
+        Ok(())
+    }
+
+    /// Whether `fqn` names an existing Reactor, Port, Action or Reaction.
+    fn fqn_exists(&self, fqn: &BuilderFqn) -> Result<bool, BuilderError> {
+        Ok(!self.find_reactors_matching(fqn)?.is_empty()
+            || !self.find_ports_matching(fqn)?.is_empty()
+            || !self.find_actions_matching(fqn)?.is_empty()
+            || !self.find_reactions_matching(fqn)?.is_empty())
+    }
+
+    /// Resolve all aliases declared with [`EnvBuilder::add_alias`] in `fqn`.
+    ///
+    /// Fails with [`BuilderError::ExcludedElement`] if the resolved FQN is in an excluded subtree, see
+    /// [`crate::ReactorBuilderState::child_if`].
+    pub fn resolve_fqn(&self, fqn: BuilderFqn) -> Result<BuilderFqn, BuilderError> {
+        let mut resolved = fqn;
+        // Each step applies an alias, so more steps than aliases means there is a cycle.
+        for _ in 0..=self.fqn_aliases.len() {
+            let Some((old, new)) = self
+                .fqn_aliases
+                .iter()
+                .filter(|(old, _)| resolved.starts_with(old))
+                .max_by_key(|(old, _)| old.len())
+            else {
+                return self.check_included(resolved);
+            };
+            resolved = resolved
+                .replace_prefix(old, new)
+                .expect("prefix was just matched");
+        }
+        Err(BuilderError::InvalidFqn(resolved.to_string()))
+    }
+
+    /// Resolve all aliases in `pattern` if it matches a single FQN, see [`EnvBuilder::resolve_fqn`].
+    ///
+    /// Aliases are not applied to patterns with wildcards or ranges, which only match the current names.
+    pub fn resolve_pattern(
+        &self,
+        pattern: BuilderFqnPattern,
+    ) -> Result<BuilderFqnPattern, BuilderError> {
+        match pattern.to_fqn() {
+            Some(fqn) => self.resolve_fqn(fqn).map(Into::into),
+            None => Ok(pattern),
+        }
+    }
+}
@@ -0,0 +1,404 @@
+//! Delay balancing of fork-join patterns.
+//!
+//! When the output of a reaction forks into several paths with different `after` delays that later rejoin at the same
+//! reaction, the joining reaction sees the values of the different paths at different tags, and never both at once.
+//! [`EnvBuilder::delay_report`] lists these unbalanced fork-join pairs, and the opt-in
+//! [`EnvBuilder::balance_delays`] adds compensating delays to the logical connections on the shorter paths, so all
+//! paths from a fork to a join have the same total delay.
+//!
+//! Only the delays of logical connections made with [`EnvBuilder::connect_ports`] are considered. Actions scheduled
+//! by user reactions have delays chosen at runtime, and physical connections are not aligned to logical time, so paths
+//! through them are not followed. Feedback loops are broken by ignoring the edge closing them.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! for unbalanced in env_builder.delay_report()? {
+//!     tracing::warn!("{unbalanced}");
+//! }
+//! let adjustments = env_builder.balance_delays()?;
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use slotmap::SecondaryMap;
+
+use crate::{
+    runtime, BuilderActionKey, BuilderError, BuilderPortKey, BuilderReactionKey, EnvBuilder,
+};
+
+/// Replaces the connection from `source` to `target` with a connection delayed by `after`.
+pub(crate) type ReconnectFn = fn(
+    &mut EnvBuilder,
+    BuilderPortKey,
+    BuilderPortKey,
+    runtime::Duration,
+) -> Result<(), BuilderError>;
+
+/// A logical connection made with [`EnvBuilder::connect_ports`], recorded so its delay can be balanced.
+pub(crate) struct ConnectionRecord {
+    pub(crate) source: BuilderPortKey,
+    pub(crate) target: BuilderPortKey,
+    pub(crate) delay: ConnectionDelay,
+}
+
+pub(crate) enum ConnectionDelay {
+    /// The ports are bound directly, `reconnect` replaces the binding with a delayed connection.
+    Direct { reconnect: ReconnectFn },
+    /// The connection is implemented by a connection reactor from `input` to `output`, scheduling `action`. The reactor
+    /// may be shared with other targets of the same source, `reconnect` gives this target a connection of its own.
+    After {
+        after: runtime::Duration,
+        action: BuilderActionKey,
+        input: BuilderPortKey,
+        output: BuilderPortKey,
+        reconnect: ReconnectFn,
+    },
+}
+
+/// A fork-join pair whose paths have different total delays, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnbalancedForkJoin {
+    /// The reaction where the paths fork
+    pub fork: BuilderReactionKey,
+    /// The reaction where the paths join
+    pub join: BuilderReactionKey,
+    pub fork_fqn: String,
+    pub join_fqn: String,
+    /// The distinct total delays of the paths from `fork` to `join`, in increasing order
+    pub delays: Vec<runtime::Duration>,
+}
+
+impl std::fmt::Display for UnbalancedForkJoin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Paths from '{}' to '{}' have different delays: {:?}",
+            self.fork_fqn, self.join_fqn, self.delays
+        )
+    }
+}
+
+/// A connection delay changed by [`EnvBuilder::balance_delays`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelayAdjustment {
+    pub source: BuilderPortKey,
+    pub target: BuilderPortKey,
+    /// The new delay of the connection
+    pub after: runtime::Duration,
+}
+
+/// A dependency between two reactions through a logical connection path.
+struct Edge {
+    to: BuilderReactionKey,
+    delay: runtime::Duration,
+    /// The index of the last recorded connection on the path, which can be delayed further
+    connection: Option<usize>,
+}
+
+/// The reaction graph with connection delays, with the edges closing feedback loops removed. The reactions of delayed
+/// connections are not part of the graph, their delays are on the edges between the connected reactions instead.
+struct DelayGraph {
+    edges: SecondaryMap<BuilderReactionKey, Vec<Edge>>,
+    /// The distinct total delays of the paths from each reaction to every reaction downstream of it
+    delays:
+        SecondaryMap<BuilderReactionKey, BTreeMap<BuilderReactionKey, BTreeSet<runtime::Duration>>>,
+}
+
+impl DelayGraph {
+    fn new(env: &EnvBuilder) -> Self {
+        // The connection of each port binding. A shared connection reactor is told apart by the bindings of its output.
+        let mut bindings = HashMap::new();
+        let mut delayed = HashMap::new();
+        let mut delayed_outputs = HashSet::new();
+        for (index, record) in env.connections.iter().enumerate() {
+            match record.delay {
+                ConnectionDelay::Direct { .. } => {
+                    bindings.insert((record.source, record.target), index);
+                }
+                ConnectionDelay::After {
+                    after,
+                    input,
+                    output,
+                    ..
+                } => {
+                    bindings.insert((output, record.target), index);
+                    delayed.insert(input, (after, output));
+                    delayed_outputs.insert(output);
+                }
+            }
+        }
+
+        let mut edges: SecondaryMap<BuilderReactionKey, Vec<Edge>> = env
+            .reaction_builders
+            .keys()
+            .map(|key| (key, Vec::new()))
+            .collect();
+
+        // Edges from the writers of each port to the readers of all ports connected to it, skipping over the reactions
+        // of delayed connections.
+        for (port_key, port) in env.port_builders.iter() {
+            let writers: Vec<_> = port.antideps().collect();
+            if writers.is_empty() || delayed_outputs.contains(&port_key) {
+                continue;
+            }
+            let mut stack = vec![(port_key, runtime::Duration::ZERO, None)];
+            while let Some((key, delay, connection)) = stack.pop() {
+                if let Some(&(after, output)) = delayed.get(&key) {
+                    stack.push((output, delay + after, connection));
+                    continue;
+                }
+                let port = &env.port_builders[key];
+                for reader in port.deps() {
+                    for &writer in &writers {
+                        edges[writer].push(Edge {
+                            to: reader,
+                            delay,
+                            connection,
+                        });
+                    }
+                }
+                stack.extend(port.get_outward_bindings().map(|next| {
+                    let connection = bindings.get(&(key, next)).copied().or(connection);
+                    (next, delay, connection)
+                }));
+            }
+        }
+
+        let mut graph = Self {
+            edges,
+            delays: SecondaryMap::new(),
+        };
+        let mut on_stack = SecondaryMap::new();
+        for key in env.reaction_builders.keys() {
+            graph.visit(key, &mut on_stack);
+        }
+        graph
+    }
+
+    /// Depth-first visit computing the path delays from `key`, dropping the edges back to reactions on the stack.
+    fn visit(
+        &mut self,
+        key: BuilderReactionKey,
+        on_stack: &mut SecondaryMap<BuilderReactionKey, ()>,
+    ) {
+        if self.delays.contains_key(key) || on_stack.contains_key(key) {
+            return;
+        }
+        on_stack.insert(key, ());
+
+        let mut edges = std::mem::take(&mut self.edges[key]);
+        edges.retain(|edge| !on_stack.contains_key(edge.to));
+        for edge in &edges {
+            self.visit(edge.to, on_stack);
+        }
+
+        let mut delays: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+        for edge in &edges {
+            delays.entry(edge.to).or_default().insert(edge.delay);
+            for (&to, downstream) in &self.delays[edge.to] {
+                delays
+                    .entry(to)
+                    .or_default()
+                    .extend(downstream.iter().map(|&delay| edge.delay + delay));
+            }
+        }
+
+        self.edges[key] = edges;
+        self.delays.insert(key, delays);
+        on_stack.remove(key);
+    }
+
+    /// Whether `join` is `key` or downstream of it.
+    fn reaches(&self, key: BuilderReactionKey, join: BuilderReactionKey) -> bool {
+        key == join || self.delays[key].contains_key(&join)
+    }
+
+    /// All fork-join pairs with different path delays, where the paths diverge at the fork itself.
+    fn unbalanced(
+        &self,
+    ) -> Vec<(
+        BuilderReactionKey,
+        BuilderReactionKey,
+        Vec<runtime::Duration>,
+    )> {
+        self.delays
+            .iter()
+            .flat_map(|(fork, delays)| {
+                delays
+                    .iter()
+                    .filter(move |(&join, paths)| {
+                        paths.len() > 1
+                            && self.edges[fork]
+                                .iter()
+                                .filter(|edge| self.reaches(edge.to, join))
+                                .count()
+                                > 1
+                    })
+                    .map(move |(&join, paths)| (fork, join, paths.iter().copied().collect()))
+            })
+            .collect()
+    }
+
+    /// The extra delay for each connection needed to balance all paths from `fork` to `join`.
+    fn compensation(
+        &self,
+        fork: BuilderReactionKey,
+        join: BuilderReactionKey,
+    ) -> Result<BTreeMap<usize, runtime::Duration>, String> {
+        // The longest delay from the fork to each reaction on a path to the join
+        let longest = |key: BuilderReactionKey| {
+            if key == fork {
+                runtime::Duration::ZERO
+            } else {
+                *self.delays[fork][&key].last().unwrap()
+            }
+        };
+
+        let mut compensation = BTreeMap::new();
+        let on_path = std::iter::once(fork).chain(
+            self.delays[fork]
+                .keys()
+                .copied()
+                .filter(|&key| self.reaches(key, join)),
+        );
+        for from in on_path {
+            for edge in self.edges[from]
+                .iter()
+                .filter(|edge| self.reaches(edge.to, join))
+            {
+                let slack = longest(edge.to) - longest(from) - edge.delay;
+                if slack.is_zero() {
+                    continue;
+                }
+                let connection = edge.connection.ok_or_else(|| {
+                    "a shorter path has no logical connection to delay".to_owned()
+                })?;
+                if *compensation.entry(connection).or_insert(slack) != slack {
+                    return Err(
+                        "a connection on a shorter path needs conflicting delays".to_owned()
+                    );
+                }
+            }
+        }
+        Ok(compensation)
+    }
+}
+
+impl EnvBuilder {
+    /// Record a logical connection for [`EnvBuilder::balance_delays`].
+    pub(crate) fn record_connection(
+        &mut self,
+        source: BuilderPortKey,
+        target: BuilderPortKey,
+        delay: ConnectionDelay,
+    ) {
+        self.connections.push(ConnectionRecord {
+            source,
+            target,
+            delay,
+        });
+    }
+
+    /// List the fork-join pairs whose paths have different delays, see the [module documentation](self).
+    pub fn delay_report(&self) -> Result<Vec<UnbalancedForkJoin>, BuilderError> {
+        DelayGraph::new(self)
+            .unbalanced()
+            .into_iter()
+            .map(|(fork, join, delays)| {
+                Ok(UnbalancedForkJoin {
+                    fork,
+                    join,
+                    fork_fqn: self.reaction_fqn(fork, false)?.to_string(),
+                    join_fqn: self.reaction_fqn(join, false)?.to_string(),
+                    delays,
+                })
+            })
+            .collect()
+    }
+
+    /// Add compensating delays to the logical connections on the shorter paths of all unbalanced fork-join pairs, see
+    /// the [module documentation](self).
+    ///
+    /// Direct connections on a shorter path are replaced by delayed connections. Returns the changed connections, or
+    /// a [`BuilderError::UnbalancedDelays`] if a pair can't be balanced, e.g. because a shorter path doesn't contain
+    /// any logical connection.
+    pub fn balance_delays(&mut self) -> Result<Vec<DelayAdjustment>, BuilderError> {
+        let mut adjustments = Vec::new();
+        // Balancing a pair may unbalance another one sharing a connection, so give up after as many rounds as there can be
+        // pairs.
+        for _ in 0..=self.reaction_builders.len().pow(2) {
+            let graph = DelayGraph::new(self);
+            let Some((fork, join, _)) = graph.unbalanced().into_iter().next() else {
+                return Ok(adjustments);
+            };
+            let compensation = match graph.compensation(fork, join) {
+                Ok(compensation) => compensation,
+                Err(what) => {
+                    return Err(BuilderError::UnbalancedDelays {
+                        fork: self.reaction_fqn(fork, false)?.to_string(),
+                        join: self.reaction_fqn(join, false)?.to_string(),
+                        what,
+                    })
+                }
+            };
+
+            // Delayed connections are appended when replacing a direct one, so apply from the last record.
+            for (index, extra) in compensation.into_iter().rev() {
+                let ConnectionRecord { source, target, .. } = self.connections[index];
+                let shared = self.is_shared_connection(index);
+                let after = match self.connections[index].delay {
+                    // The other targets of a shared connection reactor keep their delay
+                    ConnectionDelay::After {
+                        after,
+                        action,
+                        output,
+                        reconnect,
+                        ..
+                    } if shared => {
+                        self.connections.remove(index);
+                        self.detach_shared_connection(source, target, action, output)?;
+                        reconnect(self, source, target, after + extra)?;
+                        after + extra
+                    }
+                    ConnectionDelay::After {
+                        ref mut after,
+                        action,
+                        ..
+                    } => {
+                        *after += extra;
+                        self.action_builders[action].set_min_delay(Some(*after));
+                        // Later connections with the old delay must not share this reactor
+                        self.shared_connections
+                            .retain(|_, connection| connection.action != action);
+                        *after
+                    }
+                    ConnectionDelay::Direct { reconnect } => {
+                        self.connections.remove(index);
+                        self.port_builders[target].set_inward_binding(None);
+                        self.port_builders[source].remove_outward_binding(target);
+                        reconnect(self, source, target, extra)?;
+                        extra
+                    }
+                };
+                tracing::info!(
+                    "Balanced connection {} -> {} to {after}",
+                    self.port_fqn(source, false)?,
+                    self.port_fqn(target, false)?
+                );
+                adjustments.push(DelayAdjustment {
+                    source,
+                    target,
+                    after,
+                });
+            }
+        }
+
+        let (fork, join, _) = DelayGraph::new(self).unbalanced().remove(0);
+        Err(BuilderError::UnbalancedDelays {
+            fork: self.reaction_fqn(fork, false)?.to_string(),
+            join: self.reaction_fqn(join, false)?.to_string(),
+            what: "balancing did not converge".to_owned(),
+        })
+    }
+}
@@ -0,0 +1,301 @@
+//! Named buses connecting producers and consumers anywhere in the Reactor hierarchy.
+//!
+//! A bus is declared on the [`EnvBuilder`] with [`EnvBuilder::add_bus`], after which output ports can be attached as
+//! producers and input ports as consumers by the bus name, independent of where they are in the hierarchy. When the
+//! environment is built, a merging reactor is synthesized in the lowest common parent of all attached ports, together
+//! with the intermediate ports and bindings required to route values up to it and back down to the consumers.
+
+use std::any::Any;
+
+use crate::{
+    runtime, BuilderError, BuilderPortKey, BuilderReactorKey, EnvBuilder, Input, Output,
+    TriggerMode, TypedPortKey,
+};
+
+/// How the values of multiple producers present at the same tag are merged into a single value on the bus.
+pub enum MergePolicy<T> {
+    /// The value of the first present producer (in order of attachment) is used.
+    First,
+    /// The value of the last present producer (in order of attachment) is used.
+    Last,
+    /// The values of all present producers are folded together in order of attachment.
+    Reduce(fn(T, T) -> T),
+}
+
+/// Type-erased interface to a [`BusBuilder`].
+pub(crate) trait BaseBusBuilder {
+    fn name(&self) -> &str;
+    fn type_name(&self) -> &'static str;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// Synthesize the merging reactor, ports and bindings for this bus.
+    fn build(self: Box<Self>, env: &mut EnvBuilder) -> Result<(), BuilderError>;
+}
+
+pub(crate) struct BusBuilder<T: runtime::ReactorData> {
+    name: String,
+    policy: MergePolicy<T>,
+    producers: Vec<BuilderPortKey>,
+    consumers: Vec<BuilderPortKey>,
+}
+
+impl<T: runtime::ReactorData + Clone> BusBuilder<T> {
+    pub(crate) fn new(name: &str, policy: MergePolicy<T>) -> Self {
+        Self {
+            name: name.into(),
+            policy,
+            producers: Vec::new(),
+            consumers: Vec::new(),
+        }
+    }
+
+    fn error(&self, what: impl Into<String>) -> BuilderError {
+        BuilderError::BusError {
+            bus: self.name.clone(),
+            what: what.into(),
+        }
+    }
+}
+
+impl<T: runtime::ReactorData + Clone> BaseBusBuilder for BusBuilder<T> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn build(self: Box<Self>, env: &mut EnvBuilder) -> Result<(), BuilderError> {
+        if self.producers.is_empty() || self.consumers.is_empty() {
+            tracing::warn!(
+                "Bus '{}' has {} producers and {} consumers, skipping.",
+                self.name,
+                self.producers.len(),
+                self.consumers.len()
+            );
+            return Ok(());
+        }
+
+        // Connections to a port are made in the parent of its reactor, so the bus lives in the lowest common
+        // ancestor of those parents.
+        let mut scopes = Vec::with_capacity(self.producers.len() + self.consumers.len());
+        for &port_key in self.producers.iter().chain(&self.consumers) {
+            let reactor_key = env.port_builders[port_key].get_reactor_key();
+            let scope = env.reactor_builders[reactor_key]
+                .parent_reactor_key
+                .ok_or_else(|| {
+                    self.error(format!(
+                        "Port '{}' belongs to a top-level reactor and cannot be attached to a bus",
+                        env.port_fqn(port_key, false)
+                            .map(|fqn| fqn.to_string())
+                            .unwrap_or_default()
+                    ))
+                })?;
+            scopes.push(env.reactor_path(scope));
+        }
+        let common_len = scopes
+            .iter()
+            .map(|path| {
+                path.iter()
+                    .zip(&scopes[0])
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            })
+            .min()
+            .unwrap_or_default();
+        let bus_parent = *scopes[0][..common_len]
+            .last()
+            .ok_or_else(|| self.error("Attached ports do not share a common parent reactor"))?;
+
+        let Self {
+            name,
+            policy,
+            producers,
+            consumers,
+        } = *self;
+
+        // 1. Create the merging reactor
+        let mut builder = env.add_reactor(&format!("bus_{name}"), Some(bus_parent), None, policy);
+        let inputs = (0..producers.len())
+            .map(|idx| {
+                builder.add_port::<T, Input>(
+                    "inputs",
+                    Some(runtime::BankInfo {
+                        idx,
+                        total: producers.len(),
+                    }),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let output = builder.add_output_port::<T>("output")?;
+        let mut reaction = builder.add_reaction(
+            "merge",
+            runtime::ReactionAdapter::<BusMergeReaction<T>, MergePolicy<T>>::default(),
+        );
+        for (order, &input) in inputs.iter().enumerate() {
+            reaction.add_port(input.into(), order, TriggerMode::TriggersAndUses)?;
+        }
+        reaction.add_port(output.into(), 0, TriggerMode::EffectsOnly)?;
+        reaction.finish()?;
+        builder.finish()?;
+
+        // 2. Route each producer up through its ancestors to the bus inputs
+        for (idx, (producer, input)) in producers.into_iter().zip(inputs).enumerate() {
+            let mut port_key = producer;
+            let mut reactor_key = env.port_builders[port_key].get_reactor_key();
+            while let Some(parent_key) = env.reactor_builders[reactor_key]
+                .parent_reactor_key
+                .filter(|&parent_key| parent_key != bus_parent)
+            {
+                let hop = env.internal_add_port::<T, Output>(
+                    &format!("bus_{name}_producer{idx}"),
+                    parent_key,
+                    None,
+                )?;
+                env.bind_port(port_key, hop)?;
+                port_key = hop;
+                reactor_key = parent_key;
+            }
+            env.bind_port(port_key, input)?;
+        }
+
+        // 3. Route the bus output down through the consumer's ancestors
+        for (idx, consumer) in consumers.into_iter().enumerate() {
+            let consumer_reactor_key = env.port_builders[consumer].get_reactor_key();
+            let path = env.reactor_path(consumer_reactor_key);
+            let below = path
+                .iter()
+                .position(|&key| key == bus_parent)
+                .map(|pos| &path[pos + 1..path.len() - 1])
+                .unwrap_or_default();
+
+            let mut port_key: BuilderPortKey = output.into();
+            for &reactor_key in below {
+                let hop = env.internal_add_port::<T, Input>(
+                    &format!("bus_{name}_consumer{idx}"),
+                    reactor_key,
+                    None,
+                )?;
+                env.bind_port(port_key, hop)?;
+                port_key = hop;
+            }
+            env.bind_port(port_key, consumer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl EnvBuilder {
+    /// Declare a new named bus carrying values of type `T`.
+    ///
+    /// Multiple producers present at the same tag are merged according to `policy`.
+    pub fn add_bus<T: runtime::ReactorData + Clone>(
+        &mut self,
+        name: &str,
+        policy: MergePolicy<T>,
+    ) -> Result<(), BuilderError> {
+        if self.buses.iter().any(|bus| bus.name() == name) {
+            return Err(BuilderError::DuplicateBusDefinition(name.to_owned()));
+        }
+        self.buses.push(Box::new(BusBuilder::new(name, policy)));
+        Ok(())
+    }
+
+    /// Attach an output port as a producer to the bus named `name`.
+    pub fn attach_bus_producer<T: runtime::ReactorData + Clone>(
+        &mut self,
+        name: &str,
+        port_key: TypedPortKey<T, Output>,
+    ) -> Result<(), BuilderError> {
+        self.get_bus_mut::<T>(name)?.producers.push(port_key.into());
+        Ok(())
+    }
+
+    /// Attach an input port as a consumer to the bus named `name`.
+    pub fn attach_bus_consumer<T: runtime::ReactorData + Clone>(
+        &mut self,
+        name: &str,
+        port_key: TypedPortKey<T, Input>,
+    ) -> Result<(), BuilderError> {
+        self.get_bus_mut::<T>(name)?.consumers.push(port_key.into());
+        Ok(())
+    }
+
+    /// Synthesize the reactors, ports and bindings for all declared buses.
+    ///
+    /// This is called automatically by [`EnvBuilder::into_runtime_parts`].
+    pub fn build_buses(&mut self) -> Result<(), BuilderError> {
+        for bus in std::mem::take(&mut self.buses) {
+            bus.build(self)?;
+        }
+        Ok(())
+    }
+
+    fn get_bus_mut<T: runtime::ReactorData + Clone>(
+        &mut self,
+        name: &str,
+    ) -> Result<&mut BusBuilder<T>, BuilderError> {
+        let bus = self
+            .buses
+            .iter_mut()
+            .find(|bus| bus.name() == name)
+            .ok_or_else(|| BuilderError::NamedBusNotFound(name.to_owned()))?;
+        let type_name = bus.type_name();
+        bus.as_any_mut()
+            .downcast_mut::<BusBuilder<T>>()
+            .ok_or_else(|| BuilderError::BusError {
+                bus: name.to_owned(),
+                what: format!(
+                    "Expected ports of type {type_name}, found {}",
+                    std::any::type_name::<T>()
+                ),
+            })
+    }
+
+    /// The path of Reactor keys from the top-level Reactor down to (and including) `reactor_key`.
+    fn reactor_path(&self, reactor_key: BuilderReactorKey) -> Vec<BuilderReactorKey> {
+        let mut path = vec![reactor_key];
+        while let Some(parent_key) = self.reactor_builders[*path.last().unwrap()].parent_reactor_key
+        {
+            path.push(parent_key);
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// The Reaction merging the bus inputs into the bus output.
+struct BusMergeReaction<'a, T: runtime::ReactorData> {
+    inputs: Vec<runtime::InputRef<'a, T>>,
+    output: runtime::OutputRef<'a, T>,
+}
+
+impl<T: runtime::ReactorData> runtime::FromRefs for BusMergeReaction<'_, T> {
+    type Marker<'s> = BusMergeReaction<'s, T>;
+
+    fn from_refs<'store>(
+        ports: runtime::Refs<'store, dyn runtime::BasePort>,
+        ports_mut: runtime::RefsMut<'store, dyn runtime::BasePort>,
+        _actions: runtime::RefsMut<'store, dyn runtime::BaseAction>,
+    ) -> Self::Marker<'store> {
+        let inputs = ports.map(runtime::InputRef::from).collect();
+        let output = ports_mut.partition_mut().expect("Output not found");
+        BusMergeReaction { inputs, output }
+    }
+}
+
+impl<T: runtime::ReactorData + Clone> runtime::Trigger<MergePolicy<T>> for BusMergeReaction<'_, T> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, policy: &mut MergePolicy<T>) {
+        let mut values = self.inputs.iter().filter_map(|input| input.as_ref());
+        *self.output = match policy {
+            MergePolicy::First => values.next().cloned(),
+            MergePolicy::Last => values.next_back().cloned(),
+            MergePolicy::Reduce(f) => values.cloned().reduce(*f),
+        };
+    }
+}
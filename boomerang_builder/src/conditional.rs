@@ -0,0 +1,37 @@
+use crate::{BuilderError, BuilderFqn, EnvBuilder};
+
+impl EnvBuilder {
+    /// Whether the feature flag `name` is enabled, i.e., the global parameter `name` is set to `true`.
+    ///
+    /// Flags are plain `bool` parameters (see [`EnvBuilder::set_parameter`]), so they can be set from the runtime
+    /// configuration before the reactors are built, and read by them with [`crate::ReactorBuilderState::flag`].
+    pub fn flag(&self, name: &str) -> bool {
+        self.get_parameter::<bool>(name).copied().unwrap_or(false)
+    }
+
+    /// The fully-qualified names of the subtrees excluded with [`crate::ReactorBuilderState::child_if`].
+    pub fn excluded_subtrees(&self) -> impl Iterator<Item = &BuilderFqn> {
+        self.excluded.iter()
+    }
+
+    /// Record `fqn` as an excluded subtree.
+    pub(crate) fn exclude(&mut self, fqn: BuilderFqn) {
+        tracing::debug!(subtree = %fqn, "Excluding a disabled subtree.");
+        self.excluded.push(fqn);
+    }
+
+    /// Fail with [`BuilderError::ExcludedElement`] if `fqn` is in an excluded subtree.
+    pub(crate) fn check_included(&self, fqn: BuilderFqn) -> Result<BuilderFqn, BuilderError> {
+        match self
+            .excluded
+            .iter()
+            .find(|subtree| fqn.starts_with(subtree))
+        {
+            Some(subtree) => Err(BuilderError::ExcludedElement {
+                fqn: fqn.to_string(),
+                subtree: subtree.to_string(),
+            }),
+            None => Ok(fqn),
+        }
+    }
+}
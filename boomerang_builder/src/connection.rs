@@ -1,8 +1,56 @@
 use crate::{
-    runtime, ActionTag, BuilderError, BuilderReactorKey, EnvBuilder, Input, Output, Reaction,
+    balance::ConnectionDelay, runtime, ActionTag, BuilderActionKey, BuilderError, BuilderPortKey,
+    BuilderReactorKey, EnvBuilder, Input, Logical, Output, PortTag, PortType, Reaction,
     ReactionBuilderState, ReactionField, ReactorBuilderState, ReactorField, TriggerMode,
     TypedActionKey, TypedPortKey,
 };
+use runtime::ActionCommon;
+
+#[cfg(feature = "connection_stats")]
+use crate::ConnectionMonitor;
+
+/// How a delayed or physical connection handles values arriving faster than they are delivered downstream.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Coalesce {
+    /// Every value is delivered at its own tag.
+    #[default]
+    QueueAll,
+    /// While a delivery is pending, newer values replace the pending one (conflation).
+    KeepLatest,
+    /// While a delivery is pending, newer values are dropped.
+    KeepFirst,
+}
+
+/// The parameters of a delayed and/or physical connection. All connections from the same source port with the same
+/// parameters share a single [`ConnectionBuilder`] reactor, whose output port is bound to every target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct SharedConnectionKey {
+    pub(crate) source: BuilderPortKey,
+    /// The reactor containing the connection reactor
+    pub(crate) parent: BuilderReactorKey,
+    pub(crate) after: runtime::Duration,
+    pub(crate) physical: bool,
+    pub(crate) coalesce: Coalesce,
+}
+
+/// The ports and action of a [`ConnectionBuilder`] reactor shared by the targets of a [`SharedConnectionKey`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SharedConnection {
+    pub(crate) action: BuilderActionKey,
+    pub(crate) input: BuilderPortKey,
+    pub(crate) output: BuilderPortKey,
+}
+
+/// State shared by the reactions of a [`ConnectionBuilder`].
+struct ConnectionState<T> {
+    coalesce: Coalesce,
+    /// The number of values scheduled but not yet delivered
+    in_flight: usize,
+    /// A newer value replacing the pending one
+    latest: Option<T>,
+    #[cfg(feature = "connection_stats")]
+    monitor: ConnectionMonitor,
+}
 
 pub struct ConnectionBuilder<T: runtime::ReactorData, Q: ActionTag> {
     pub(crate) input: TypedPortKey<T, Input>,
@@ -10,9 +58,9 @@ pub struct ConnectionBuilder<T: runtime::ReactorData, Q: ActionTag> {
     pub(crate) action: TypedActionKey<T, Q>,
 }
 
-/// We use the `state` to pass the delay duration for the connection.
+/// We use the `state` to pass the delay duration and coalescing policy for the connection.
 impl<T: runtime::ReactorData + Clone, Q: ActionTag> crate::Reactor for ConnectionBuilder<T, Q> {
-    type State = runtime::Duration;
+    type State = (runtime::Duration, Coalesce);
 
     fn build(
         name: &str,
@@ -21,12 +69,26 @@ impl<T: runtime::ReactorData + Clone, Q: ActionTag> crate::Reactor for Connectio
         bank_info: Option<runtime::BankInfo>,
         env: &mut EnvBuilder,
     ) -> Result<Self, BuilderError> {
-        let mut __builder = env.add_reactor(name, parent, bank_info, ());
+        let (delay, coalesce) = state;
+        #[cfg(feature = "connection_stats")]
+        let monitor = ConnectionMonitor::default();
+        let mut __builder = env.add_reactor(
+            name,
+            parent,
+            bank_info,
+            ConnectionState::<T> {
+                coalesce,
+                in_flight: 0,
+                latest: None,
+                #[cfg(feature = "connection_stats")]
+                monitor: monitor.clone(),
+            },
+        );
         let input = <TypedPortKey<T, Input> as ReactorField>::build("input", (), &mut __builder)?;
         let output =
             <TypedPortKey<T, Output> as ReactorField>::build("output", (), &mut __builder)?;
         let action =
-            <TypedActionKey<T, Q> as ReactorField>::build("act", Some(state), &mut __builder)?;
+            <TypedActionKey<T, Q> as ReactorField>::build("act", Some(delay), &mut __builder)?;
         let mut __reactor = Self {
             input,
             output,
@@ -44,6 +106,9 @@ impl<T: runtime::ReactorData + Clone, Q: ActionTag> crate::Reactor for Connectio
             &mut __builder,
         )?
         .finish()?;
+        #[cfg(feature = "connection_stats")]
+        env.connection_monitors
+            .insert(__reactor.action.into(), monitor);
         Ok(__reactor)
     }
 }
@@ -77,7 +142,7 @@ impl<'a, T: runtime::ReactorData + Clone, Q: ActionTag> Reaction<ConnectionBuild
         builder: &'builder mut ReactorBuilderState,
     ) -> Result<ReactionBuilderState<'builder>, BuilderError> {
         let mut __reaction = {
-            let wrapper = runtime::ReactionAdapter::<ConnectionSenderReaction<T>, ()>::default();
+            let wrapper = runtime::ReactionAdapter::<ConnectionSenderReaction<T>, ConnectionState<T>>::default();
             builder.add_reaction(name, wrapper)
         };
         <runtime::InputRef<'a, u32> as ReactionField>::build(
@@ -96,10 +161,45 @@ impl<'a, T: runtime::ReactorData + Clone, Q: ActionTag> Reaction<ConnectionBuild
     }
 }
 
-impl<T: runtime::ReactorData + Clone> runtime::Trigger<()> for ConnectionSenderReaction<'_, T> {
-    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut ()) {
-        self.act
-            .schedule(ctx, self.input.clone().expect("Input value not set"), None);
+impl<T: runtime::ReactorData + Clone> runtime::Trigger<ConnectionState<T>>
+    for ConnectionSenderReaction<'_, T>
+{
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut ConnectionState<T>) {
+        let value = self.input.clone().expect("Input value not set");
+        #[cfg(feature = "connection_stats")]
+        let mut samples = state.monitor.samples.lock().unwrap();
+        #[cfg(feature = "connection_stats")]
+        samples.received();
+        if state.coalesce != Coalesce::QueueAll {
+            // A value delivered at the current tag is no longer pending, the receiver reaction runs after this one.
+            let delivering = usize::from(self.act.is_present(ctx));
+            if state.in_flight > delivering {
+                #[cfg(feature = "connection_stats")]
+                samples.dropped(
+                    (state.coalesce == Coalesce::KeepLatest)
+                        .then(|| ctx.get_elapsed_logical_time()),
+                );
+                if state.coalesce == Coalesce::KeepLatest {
+                    state.latest = Some(value);
+                }
+                return;
+            }
+        }
+        match self.act.schedule(ctx, value, None) {
+            Ok(()) => {
+                if state.coalesce != Coalesce::QueueAll {
+                    state.in_flight += 1;
+                }
+                #[cfg(feature = "connection_stats")]
+                samples.scheduled(ctx.get_elapsed_logical_time());
+            }
+            // A physical connection can lag behind logical time in fast-forward mode
+            Err(err) => {
+                #[cfg(feature = "connection_stats")]
+                samples.dropped(None);
+                tracing::error!("Dropped a value on connection '{}': {err}", self.act.name())
+            }
+        }
     }
 }
 
@@ -132,7 +232,10 @@ impl<'a, T: runtime::ReactorData + Clone, Q: ActionTag> Reaction<ConnectionBuild
         builder: &'builder mut ReactorBuilderState,
     ) -> Result<ReactionBuilderState<'builder>, BuilderError> {
         let mut __reaction = {
-            let wrapper = runtime::ReactionAdapter::<ConnectionReceiverReaction<T>, ()>::default();
+            let wrapper = runtime::ReactionAdapter::<
+                ConnectionReceiverReaction<T>,
+                ConnectionState<T>,
+            >::default();
             builder.add_reaction(name, wrapper)
         };
         <runtime::InputRef<'a, u32> as ReactionField>::build(
@@ -151,8 +254,174 @@ impl<'a, T: runtime::ReactorData + Clone, Q: ActionTag> Reaction<ConnectionBuild
     }
 }
 
-impl<T: runtime::ReactorData + Clone> runtime::Trigger<()> for ConnectionReceiverReaction<'_, T> {
-    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut ()) {
-        *self.output = self.act.get_value(ctx).cloned();
+impl<T: runtime::ReactorData + Clone> runtime::Trigger<ConnectionState<T>>
+    for ConnectionReceiverReaction<'_, T>
+{
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut ConnectionState<T>) {
+        state.in_flight = state.in_flight.saturating_sub(1);
+        #[cfg(feature = "connection_stats")]
+        {
+            let lag = std::time::Instant::now()
+                .saturating_duration_since(ctx.get_logical_time())
+                .try_into()
+                .unwrap_or(runtime::Duration::MAX);
+            let mut samples = state.monitor.samples.lock().unwrap();
+            samples.delivered(ctx.get_elapsed_logical_time(), lag);
+        }
+        *self.output = state
+            .latest
+            .take()
+            .or_else(|| self.act.get_value(ctx).cloned());
+    }
+}
+
+/// Schedules an action with the value of a port, see [`EnvBuilder::add_port_action_connection`].
+struct PortActionFn<T> {
+    delay: Option<runtime::Duration>,
+    _t: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T: runtime::ReactorData + Clone> From<PortActionFn<T>> for runtime::BoxedReactionFn {
+    fn from(value: PortActionFn<T>) -> Self {
+        Box::new(value)
+    }
+}
+
+impl<'store, T: runtime::ReactorData + Clone> runtime::ReactionFn<'store> for PortActionFn<T> {
+    fn trigger(
+        &mut self,
+        ctx: &'store mut runtime::Context,
+        _reactor: &'store mut dyn runtime::BaseReactor,
+        ports: runtime::Refs<'store, dyn runtime::BasePort>,
+        _ports_mut: runtime::RefsMut<'store, dyn runtime::BasePort>,
+        actions: runtime::RefsMut<'store, dyn runtime::BaseAction>,
+    ) {
+        let input: runtime::InputRef<T> = ports.partition().expect("Input not found");
+        let mut act: runtime::ActionRef<T> = actions.partition_mut().expect("Action not found");
+        if let Some(value) = input.clone() {
+            if let Err(err) = act.schedule(ctx, value, self.delay) {
+                // A physical connection can lag behind logical time in fast-forward mode
+                tracing::error!("Dropped a value on connection '{}': {err}", act.name());
+            }
+        }
+    }
+}
+
+impl EnvBuilder {
+    /// The name of the connection reactor from `source_key` to `target_key`.
+    pub(crate) fn connection_name(
+        &self,
+        source_key: BuilderPortKey,
+        target_key: BuilderPortKey,
+    ) -> Result<String, BuilderError> {
+        let source_fqn = self.port_fqn(source_key, false)?;
+        let target_fqn = self.port_fqn(target_key, false)?;
+        Ok(format!("connection_{source_fqn}->{target_fqn}"))
+    }
+
+    /// Whether the connection reactor of the logical connection at `index` also drives other targets.
+    pub(crate) fn is_shared_connection(&self, index: usize) -> bool {
+        let ConnectionDelay::After { action, .. } = self.connections[index].delay else {
+            return false;
+        };
+        self.connections
+            .iter()
+            .filter(|record| matches!(record.delay, ConnectionDelay::After { action: other, .. } if other == action))
+            .count()
+            > 1
+    }
+
+    /// Unbind `target_key` from the shared connection reactor scheduling `action_key`.
+    ///
+    /// The connection reactor is named after its first target, so it is renamed after one of its remaining targets if
+    /// `target_key` was that first target, leaving the name free for a new connection to `target_key`.
+    pub(crate) fn detach_shared_connection(
+        &mut self,
+        source_key: BuilderPortKey,
+        target_key: BuilderPortKey,
+        action_key: BuilderActionKey,
+        output_key: BuilderPortKey,
+    ) -> Result<(), BuilderError> {
+        self.port_builders[target_key].set_inward_binding(None);
+        self.port_builders[output_key].remove_outward_binding(target_key);
+
+        let reactor_key = self.action_builders[action_key].reactor_key();
+        if self.reactor_builders[reactor_key].name()
+            == self.connection_name(source_key, target_key)?
+        {
+            let remaining = self
+                .connections
+                .iter()
+                .find(|record| matches!(record.delay, ConnectionDelay::After { action, .. } if action == action_key))
+                .map(|record| record.target)
+                .ok_or_else(|| {
+                    BuilderError::InternalError("Shared connection without targets".to_owned())
+                })?;
+            let name = self.connection_name(source_key, remaining)?;
+            self.reactor_builders[reactor_key].set_name(name);
+        }
+        Ok(())
+    }
+
+    /// Connect a port to a logical action, scheduling the action with every value of the port after `delay`.
+    ///
+    /// This replaces a relay reaction in the reactor of the action. If the reactions of that reactor can't read the
+    /// port directly, a new input port named `bridge_<port_fqn>` is added to it and connected to the port, with the
+    /// same checks as [`EnvBuilder::connect_ports`]. The value type of the port and the action must match.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,ignore
+    /// env_builder.add_port_action_connection(source.out, sink.act, Some(Duration::milliseconds(1)))?;
+    /// ```
+    pub fn add_port_action_connection<T: runtime::ReactorData + Clone, Q: PortTag>(
+        &mut self,
+        port_key: TypedPortKey<T, Q>,
+        action_key: TypedActionKey<T, Logical>,
+        delay: Option<runtime::Duration>,
+    ) -> Result<(), BuilderError> {
+        let port_key = BuilderPortKey::from(port_key);
+        let action = self.get_action(action_key.into())?;
+        let (action_name, reactor_key) = (action.name().to_owned(), action.reactor_key());
+        let port = self
+            .port_builders
+            .get(port_key)
+            .ok_or(BuilderError::PortKeyNotFound(port_key))?;
+
+        // Reactions can read the inputs of their own reactor, and the outputs of its children
+        let readable = match port.port_type() {
+            PortType::Input => port.get_reactor_key() == reactor_key,
+            PortType::Output => {
+                self.reactor_builders[port.get_reactor_key()].parent_reactor_key
+                    == Some(reactor_key)
+            }
+        };
+        let input_key = if readable {
+            port_key
+        } else {
+            let port_fqn = self.port_fqn(port_key, false)?;
+            let bridge_key = self.internal_add_port::<T, Input>(
+                &format!("bridge_{port_fqn}"),
+                reactor_key,
+                None,
+            )?;
+            self.connect_ports::<T, _, _>(port_key, bridge_key, None, false)?;
+            bridge_key
+        };
+
+        let port_name = self.port_builders[input_key].name().to_owned();
+        let port_action_fn = PortActionFn::<T> {
+            delay,
+            _t: std::marker::PhantomData,
+        };
+        self.add_reaction(
+            &format!("_{port_name}->{action_name}"),
+            reactor_key,
+            port_action_fn,
+        )
+        .with_port(input_key, 0, TriggerMode::TriggersAndUses)?
+        .with_action(action_key, 0, TriggerMode::EffectsOnly)?
+        .finish()?;
+        Ok(())
     }
 }
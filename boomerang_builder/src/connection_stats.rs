@@ -0,0 +1,278 @@
+//! Message counts and latencies of delayed and physical connections.
+//!
+//! With the `connection_stats` feature, the connection reactor of every delayed or physical connection records the
+//! values it receives, schedules, drops and delivers. For each delivered value it measures the two contributions of
+//! the hop to the end-to-end latency:
+//!
+//! - the *delay*, from the tag at which the source sent the value to the tag at which it is delivered, i.e., the
+//!   `after` delay of a logical connection, plus any time a coalesced value waited for the previous delivery,
+//! - the *lag*, from the logical time of the delivery tag to the physical time at which it was processed, i.e., how far
+//!   the scheduler was running behind.
+//!
+//! [`EnvBuilder::connection_monitor`] returns a [`ConnectionMonitor`] for a connection, given the fully-qualified names
+//! of its ports, and [`EnvBuilder::connection_monitors`] returns all of them. The statistics can be queried at any
+//! time, e.g. from another thread while the scheduler is running. Direct connections are bindings of ports without a
+//! connection reactor, so they take no time and are not monitored.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! let monitors = env_builder.connection_monitors()?;
+//! // ...
+//! for ((source, target), monitor) in &monitors {
+//!     println!("{source} -> {target}: {}", monitor.stats());
+//! }
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+
+use crate::{runtime, BuilderError, EnvBuilder};
+
+/// The latencies of the values delivered by a connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    pub min: runtime::Duration,
+    pub max: runtime::Duration,
+    pub mean: runtime::Duration,
+}
+
+impl Display for LatencyStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "min {}, max {}, mean {}", self.min, self.max, self.mean)
+    }
+}
+
+/// Message counts and latencies of a connection, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionStats {
+    /// The values received from the source port
+    pub received: usize,
+    /// The values scheduled for delivery
+    pub scheduled: usize,
+    /// The values dropped, by coalescing or because they could not be scheduled
+    pub dropped: usize,
+    /// The values delivered to the target ports
+    pub delivered: usize,
+    /// From the tag the value was sent at to the tag it was delivered at, `None` until a value was delivered
+    pub delay: Option<LatencyStats>,
+    /// From the logical time of the delivery to the physical time it was processed at, `None` until a value was
+    /// delivered
+    pub lag: Option<LatencyStats>,
+}
+
+impl Display for ConnectionStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} received, {} scheduled, {} dropped, {} delivered",
+            self.received, self.scheduled, self.dropped, self.delivered
+        )?;
+        if let (Some(delay), Some(lag)) = (self.delay, self.lag) {
+            write!(f, "; delay {delay}; lag {lag}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct LatencySamples {
+    min: runtime::Duration,
+    max: runtime::Duration,
+    total: runtime::Duration,
+}
+
+impl LatencySamples {
+    fn record(&mut self, count: usize, latency: runtime::Duration) {
+        if count == 0 {
+            self.min = latency;
+            self.max = latency;
+        } else {
+            self.min = self.min.min(latency);
+            self.max = self.max.max(latency);
+        }
+        self.total = self.total.saturating_add(latency);
+    }
+
+    fn stats(&self, count: usize) -> Option<LatencyStats> {
+        (count > 0).then(|| LatencyStats {
+            min: self.min,
+            max: self.max,
+            mean: self.total / count as u32,
+        })
+    }
+}
+
+/// The samples recorded by the reactions of a connection reactor.
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionSamples {
+    received: usize,
+    scheduled: usize,
+    dropped: usize,
+    delivered: usize,
+    /// The elapsed logical times the scheduled values were sent at, in order of delivery
+    sent_at: VecDeque<runtime::Duration>,
+    /// The elapsed logical time a coalesced value replacing the pending one was sent at
+    latest_sent_at: Option<runtime::Duration>,
+    delay: LatencySamples,
+    lag: LatencySamples,
+}
+
+impl ConnectionSamples {
+    /// A value was received from the source port.
+    pub(crate) fn received(&mut self) {
+        self.received += 1;
+    }
+
+    /// The value received at `sent_at` was scheduled for delivery.
+    pub(crate) fn scheduled(&mut self, sent_at: runtime::Duration) {
+        self.scheduled += 1;
+        self.sent_at.push_back(sent_at);
+    }
+
+    /// A value was dropped. With `latest`, the value received replaces the pending one, which is dropped instead, and
+    /// is delivered in its place.
+    pub(crate) fn dropped(&mut self, latest: Option<runtime::Duration>) {
+        self.dropped += 1;
+        if let Some(sent_at) = latest {
+            self.latest_sent_at = Some(sent_at);
+        }
+    }
+
+    /// A value was delivered at the elapsed logical time `delivered_at`, `lag` behind in physical time.
+    pub(crate) fn delivered(&mut self, delivered_at: runtime::Duration, lag: runtime::Duration) {
+        let scheduled_at = self.sent_at.pop_front();
+        let Some(sent_at) = self.latest_sent_at.take().or(scheduled_at) else {
+            return;
+        };
+        self.delay.record(self.delivered, delivered_at - sent_at);
+        self.lag.record(self.delivered, lag);
+        self.delivered += 1;
+    }
+
+    fn stats(&self) -> ConnectionStats {
+        ConnectionStats {
+            received: self.received,
+            scheduled: self.scheduled,
+            dropped: self.dropped,
+            delivered: self.delivered,
+            delay: self.delay.stats(self.delivered),
+            lag: self.lag.stats(self.delivered),
+        }
+    }
+}
+
+/// A handle to the statistics of a connection, see [`EnvBuilder::connection_monitor`].
+///
+/// The targets of a source port connected with the same delay share a connection reactor, and so share a monitor.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionMonitor {
+    pub(crate) samples: Arc<Mutex<ConnectionSamples>>,
+}
+
+impl ConnectionMonitor {
+    /// The statistics recorded so far.
+    pub fn stats(&self) -> ConnectionStats {
+        self.samples.lock().unwrap().stats()
+    }
+}
+
+impl EnvBuilder {
+    /// Get the monitor of the delayed or physical connection from the port `source_fqn` to the port `target_fqn`.
+    pub fn connection_monitor(
+        &self,
+        source_fqn: &str,
+        target_fqn: &str,
+    ) -> Result<ConnectionMonitor, BuilderError> {
+        let source_key = self.find_port_by_fqn(source_fqn)?;
+        let target_key = self.find_port_by_fqn(target_fqn)?;
+        self.shared_connections
+            .iter()
+            .find(|(key, connection)| {
+                key.source == source_key
+                    && self.port_builders[connection.output]
+                        .get_outward_bindings()
+                        .any(|port_key| port_key == target_key)
+            })
+            .and_then(|(_, connection)| self.connection_monitors.get(connection.action))
+            .cloned()
+            .ok_or_else(|| BuilderError::InconsistentBuilderState {
+                what: format!(
+                    "There is no delayed or physical connection from '{source_fqn}' to '{target_fqn}'"
+                ),
+            })
+    }
+
+    /// Get the monitors of all delayed and physical connections, keyed by the fully-qualified names of their source
+    /// and target ports.
+    pub fn connection_monitors(
+        &self,
+    ) -> Result<BTreeMap<(String, String), ConnectionMonitor>, BuilderError> {
+        let mut monitors = BTreeMap::new();
+        for (key, connection) in &self.shared_connections {
+            let Some(monitor) = self.connection_monitors.get(connection.action) else {
+                continue;
+            };
+            let source_fqn = self.port_fqn(key.source, false)?.to_string();
+            for target_key in self.port_builders[connection.output].get_outward_bindings() {
+                let target_fqn = self.port_fqn(target_key, false)?.to_string();
+                monitors.insert((source_fqn.clone(), target_fqn), monitor.clone());
+            }
+        }
+        Ok(monitors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples() {
+        let ms = runtime::Duration::milliseconds;
+
+        let mut samples = ConnectionSamples::default();
+        assert_eq!(samples.stats(), ConnectionStats::default());
+
+        samples.received();
+        samples.scheduled(ms(0));
+        samples.received();
+        samples.scheduled(ms(1));
+        samples.delivered(ms(10), ms(2));
+        samples.delivered(ms(11), ms(0));
+        // Coalesced while the next delivery is pending
+        samples.received();
+        samples.scheduled(ms(20));
+        samples.received();
+        samples.dropped(Some(ms(25)));
+        samples.delivered(ms(30), ms(1));
+
+        let stats = samples.stats();
+        assert_eq!(
+            (
+                stats.received,
+                stats.scheduled,
+                stats.dropped,
+                stats.delivered
+            ),
+            (4, 3, 1, 3)
+        );
+        assert_eq!(
+            stats.delay,
+            Some(LatencyStats {
+                min: ms(5),
+                max: ms(10),
+                mean: ms(25) / 3,
+            })
+        );
+        assert_eq!(
+            stats.lag,
+            Some(LatencyStats {
+                min: ms(0),
+                max: ms(2),
+                mean: ms(1),
+            })
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! Port contracts, checking the values and update rate of a port at runtime.
+//!
+//! A [`PortContract`] declares what the consumers of a port expect from it: a range of values, a monotonic order, and
+//! bounds on the logical time between updates. [`EnvBuilder::add_port_contract`] checks it with a hidden reactor
+//! tapping the port, and reports every violation as an error in the log, with the fully-qualified name of the port and
+//! the tag, and in the returned [`ContractMonitor`]. A unit mismatch or a dropped sensor then shows up at the first
+//! faulty value instead of as a silent error further downstream.
+//!
+//! Contracts are meant for debugging and testing: by default they are only checked in debug builds, and
+//! [`EnvBuilder::set_contract_checks`] enables or disables them explicitly.
+//!
+//! A gap between updates longer than the maximum interval is detected at the next update, or at shutdown if the port
+//! is never updated again, so the check doesn't keep the program alive on its own.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! let contract = PortContract::new()
+//!     .with_range(0.0..=50.0)
+//!     .with_max_interval(Duration::milliseconds(100));
+//! let monitor = env_builder.add_port_contract::<f32>("main::imu::speed", contract)?;
+//! // ...
+//! assert!(monitor.violations().is_empty());
+//! ```
+
+use std::fmt::{Debug, Display};
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+
+use crate::{runtime, BuilderError, EnvBuilder, TriggerMode};
+
+/// The order that successive values of a port must follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Monotonic {
+    /// Each value is greater than or equal to the previous one
+    Increasing,
+    /// Each value is greater than the previous one
+    StrictlyIncreasing,
+    /// Each value is less than or equal to the previous one
+    Decreasing,
+    /// Each value is less than the previous one
+    StrictlyDecreasing,
+}
+
+impl Monotonic {
+    fn holds<T: PartialOrd>(&self, previous: &T, value: &T) -> bool {
+        match self {
+            Monotonic::Increasing => value >= previous,
+            Monotonic::StrictlyIncreasing => value > previous,
+            Monotonic::Decreasing => value <= previous,
+            Monotonic::StrictlyDecreasing => value < previous,
+        }
+    }
+}
+
+/// The expectations on the values and updates of a port, see the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct PortContract<T> {
+    range: Option<RangeInclusive<T>>,
+    monotonic: Option<Monotonic>,
+    min_interval: Option<runtime::Duration>,
+    max_interval: Option<runtime::Duration>,
+}
+
+impl<T> Default for PortContract<T> {
+    fn default() -> Self {
+        Self {
+            range: None,
+            monotonic: None,
+            min_interval: None,
+            max_interval: None,
+        }
+    }
+}
+
+impl<T> PortContract<T> {
+    /// Create a contract without any expectations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every value must lie in `range`.
+    pub fn with_range(mut self, range: RangeInclusive<T>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Successive values must follow the `monotonic` order.
+    pub fn with_monotonic(mut self, monotonic: Monotonic) -> Self {
+        self.monotonic = Some(monotonic);
+        self
+    }
+
+    /// Successive updates must be at least `interval` apart in logical time, i.e., a maximum rate.
+    pub fn with_min_interval(mut self, interval: runtime::Duration) -> Self {
+        self.min_interval = Some(interval);
+        self
+    }
+
+    /// Successive updates, and the first one from the start, must be at most `interval` apart in logical time, i.e.,
+    /// a minimum rate.
+    pub fn with_max_interval(mut self, interval: runtime::Duration) -> Self {
+        self.max_interval = Some(interval);
+        self
+    }
+}
+
+/// How a [`PortContract`] was violated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// The value is outside of the range, formatted with `Debug`
+    OutOfRange { value: String },
+    /// The value doesn't follow the monotonic order after the previous one
+    NotMonotonic { previous: String, value: String },
+    /// The port was updated sooner than the minimum interval after the previous update
+    TooFrequent { interval: runtime::Duration },
+    /// The port was not updated within the maximum interval after the previous update
+    TooInfrequent { interval: runtime::Duration },
+}
+
+impl Display for ViolationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ViolationKind::OutOfRange { value } => write!(f, "value {value} is out of range"),
+            ViolationKind::NotMonotonic { previous, value } => {
+                write!(f, "value {value} is not monotonic after {previous}")
+            }
+            ViolationKind::TooFrequent { interval } => {
+                write!(f, "updated only {interval} after the previous update")
+            }
+            ViolationKind::TooInfrequent { interval } => {
+                write!(f, "not updated for {interval}")
+            }
+        }
+    }
+}
+
+/// A violation of a [`PortContract`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractViolation {
+    /// The fully-qualified name of the port
+    pub port: String,
+    /// The tag at which the violation was detected
+    pub tag: runtime::Tag,
+    pub kind: ViolationKind,
+}
+
+impl Display for ContractViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Port '{}' at {}: {}", self.port, self.tag, self.kind)
+    }
+}
+
+/// A handle to the violations of a [`PortContract`], see [`EnvBuilder::add_port_contract`].
+#[derive(Debug, Clone, Default)]
+pub struct ContractMonitor {
+    violations: Arc<Mutex<Vec<ContractViolation>>>,
+}
+
+impl ContractMonitor {
+    /// The violations reported so far.
+    pub fn violations(&self) -> Vec<ContractViolation> {
+        self.violations.lock().unwrap().clone()
+    }
+
+    fn report(&self, port: &str, tag: runtime::Tag, kind: ViolationKind) {
+        let violation = ContractViolation {
+            port: port.to_owned(),
+            tag,
+            kind,
+        };
+        tracing::error!(port, %tag, "Port contract violated: {}", violation.kind);
+        self.violations.lock().unwrap().push(violation);
+    }
+}
+
+/// The state of the check of a contract, shared by the update and shutdown reactions.
+struct ContractCheck<T> {
+    port: String,
+    contract: PortContract<T>,
+    monitor: ContractMonitor,
+    /// The previous value
+    previous: Option<T>,
+    /// The elapsed logical time of the previous update
+    updated_at: Option<runtime::Duration>,
+}
+
+impl<T: Clone + PartialOrd + Debug> ContractCheck<T> {
+    /// Check the value of an update at `tag`.
+    fn update(&mut self, tag: runtime::Tag, value: &T) {
+        if let Some(range) = &self.contract.range {
+            if !range.contains(value) {
+                let value = format!("{value:?}");
+                self.monitor
+                    .report(&self.port, tag, ViolationKind::OutOfRange { value });
+            }
+        }
+        if let (Some(monotonic), Some(previous)) = (self.contract.monotonic, &self.previous) {
+            if !monotonic.holds(previous, value) {
+                let kind = ViolationKind::NotMonotonic {
+                    previous: format!("{previous:?}"),
+                    value: format!("{value:?}"),
+                };
+                self.monitor.report(&self.port, tag, kind);
+            }
+        }
+        if let (Some(min_interval), Some(updated_at)) =
+            (self.contract.min_interval, self.updated_at)
+        {
+            let interval = tag.offset() - updated_at;
+            if interval < min_interval {
+                self.monitor
+                    .report(&self.port, tag, ViolationKind::TooFrequent { interval });
+            }
+        }
+        self.check_gap(tag);
+        self.previous = Some(value.clone());
+        self.updated_at = Some(tag.offset());
+    }
+
+    /// Check the time since the previous update, or since the start, against the maximum interval.
+    fn check_gap(&self, tag: runtime::Tag) {
+        if let Some(max_interval) = self.contract.max_interval {
+            let interval = tag.offset() - self.updated_at.unwrap_or_default();
+            if interval > max_interval {
+                self.monitor
+                    .report(&self.port, tag, ViolationKind::TooInfrequent { interval });
+            }
+        }
+    }
+}
+
+/// Checks each update of the port.
+struct UpdateFn<T>(Arc<Mutex<ContractCheck<T>>>);
+
+impl<T: runtime::ReactorData + Clone + PartialOrd + Debug> From<UpdateFn<T>>
+    for runtime::BoxedReactionFn
+{
+    fn from(value: UpdateFn<T>) -> Self {
+        Box::new(value)
+    }
+}
+
+impl<'store, T: runtime::ReactorData + Clone + PartialOrd + Debug> runtime::ReactionFn<'store>
+    for UpdateFn<T>
+{
+    fn trigger(
+        &mut self,
+        ctx: &'store mut runtime::Context,
+        _reactor: &'store mut dyn runtime::BaseReactor,
+        ports: runtime::Refs<'store, dyn runtime::BasePort>,
+        _ports_mut: runtime::RefsMut<'store, dyn runtime::BasePort>,
+        _actions: runtime::RefsMut<'store, dyn runtime::BaseAction>,
+    ) {
+        let input: runtime::InputRef<T> = ports.partition().expect("Input not found");
+        if let Some(value) = input.as_ref() {
+            self.0.lock().unwrap().update(ctx.get_tag(), value);
+        }
+    }
+}
+
+/// Checks the time since the last update at shutdown.
+struct ShutdownFn<T>(Arc<Mutex<ContractCheck<T>>>);
+
+impl<T: runtime::ReactorData + Clone + PartialOrd + Debug> From<ShutdownFn<T>>
+    for runtime::BoxedReactionFn
+{
+    fn from(value: ShutdownFn<T>) -> Self {
+        Box::new(value)
+    }
+}
+
+impl<'store, T: runtime::ReactorData + Clone + PartialOrd + Debug> runtime::ReactionFn<'store>
+    for ShutdownFn<T>
+{
+    fn trigger(
+        &mut self,
+        ctx: &'store mut runtime::Context,
+        _reactor: &'store mut dyn runtime::BaseReactor,
+        _ports: runtime::Refs<'store, dyn runtime::BasePort>,
+        _ports_mut: runtime::RefsMut<'store, dyn runtime::BasePort>,
+        _actions: runtime::RefsMut<'store, dyn runtime::BaseAction>,
+    ) {
+        self.0.lock().unwrap().check_gap(ctx.get_tag());
+    }
+}
+
+impl EnvBuilder {
+    /// Enable or disable the checks of port contracts added afterwards, see [`EnvBuilder::add_port_contract`].
+    ///
+    /// By default, contracts are only checked in debug builds.
+    pub fn set_contract_checks(&mut self, enabled: bool) {
+        self.contract_checks = Some(enabled);
+    }
+
+    /// Whether port contracts added now are checked.
+    pub fn contract_checks(&self) -> bool {
+        self.contract_checks.unwrap_or(cfg!(debug_assertions))
+    }
+
+    /// Check the [`PortContract`] of the port with the fully-qualified name `port_fqn` at runtime.
+    ///
+    /// A reactor `_<port>_contract` is added next to the port, i.e., as a child of the reactor of the port, with a
+    /// tap on the port (see [`EnvBuilder::tap_connection`]) triggering the check of every update, and a reaction
+    /// checking the time since the last update at shutdown. Each violation is logged, and recorded in the returned
+    /// [`ContractMonitor`]. When contract checks are disabled, the port is still validated, but nothing is added and
+    /// the monitor stays empty.
+    pub fn add_port_contract<T>(
+        &mut self,
+        port_fqn: &str,
+        contract: PortContract<T>,
+    ) -> Result<ContractMonitor, BuilderError>
+    where
+        T: runtime::ReactorData + Clone + PartialOrd + Debug,
+    {
+        let port_key = self.find_typed_port_by_fqn::<T>(port_fqn)?;
+        let monitor = ContractMonitor::default();
+        if !self.contract_checks() {
+            return Ok(monitor);
+        }
+
+        let port = &self.port_builders[port_key];
+        let name = match port.bank_info() {
+            Some(bank_info) => format!("_{}{}_contract", port.name(), bank_info.idx),
+            None => format!("_{}_contract", port.name()),
+        };
+        let reactor_builder = self.add_reactor(&name, Some(port.get_reactor_key()), None, ());
+        let shutdown_action = reactor_builder.get_shutdown_action();
+        let reactor_key = reactor_builder.finish()?;
+        let input = self.tap_connection::<T>(port_fqn, reactor_key)?;
+
+        let check = Arc::new(Mutex::new(ContractCheck {
+            port: port_fqn.to_owned(),
+            contract,
+            monitor: monitor.clone(),
+            previous: None,
+            updated_at: None,
+        }));
+        self.add_reaction("check_update", reactor_key, UpdateFn(check.clone()))
+            .with_port(input, 0, TriggerMode::TriggersAndUses)?
+            .finish()?;
+        self.add_reaction("check_shutdown", reactor_key, ShutdownFn(check))
+            .with_action(shutdown_action, 0, TriggerMode::TriggersOnly)?
+            .finish()?;
+        Ok(monitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contract_check() {
+        let ms = runtime::Duration::milliseconds;
+        let tag = |millis| runtime::Tag::new(ms(millis), 0);
+        let monitor = ContractMonitor::default();
+        let mut check = ContractCheck {
+            port: "main::sensor::out".to_owned(),
+            contract: PortContract::new()
+                .with_range(0..=100)
+                .with_monotonic(Monotonic::StrictlyIncreasing)
+                .with_min_interval(ms(5))
+                .with_max_interval(ms(20)),
+            monitor: monitor.clone(),
+            previous: None,
+            updated_at: None,
+        };
+
+        check.update(tag(10), &1);
+        check.update(tag(20), &2);
+        assert!(monitor.violations().is_empty());
+
+        check.update(tag(22), &2);
+        check.update(tag(50), &200);
+        check.check_gap(tag(80));
+        let kinds = monitor
+            .violations()
+            .into_iter()
+            .map(|violation| (violation.tag, violation.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                (
+                    tag(22),
+                    ViolationKind::NotMonotonic {
+                        previous: "2".to_owned(),
+                        value: "2".to_owned()
+                    }
+                ),
+                (tag(22), ViolationKind::TooFrequent { interval: ms(2) }),
+                (
+                    tag(50),
+                    ViolationKind::OutOfRange {
+                        value: "200".to_owned()
+                    }
+                ),
+                (tag(50), ViolationKind::TooInfrequent { interval: ms(28) }),
+                (tag(80), ViolationKind::TooInfrequent { interval: ms(30) }),
+            ]
+        );
+        assert_eq!(
+            monitor.violations()[2].to_string(),
+            "Port 'main::sensor::out' at [50ms+0]: value 200 is out of range"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Decorators rate-limiting the events of an action.
+//!
+//! Both decorators add a new logical action next to the decorated one, along with the reaction forwarding the events
+//! between them. Reactions interested in the rate-limited events trigger on the new action instead of the original.
+
+use std::marker::PhantomData;
+
+use crate::{
+    runtime, ActionTag, ActionType, BuilderActionKey, BuilderError, BuilderReactorKey, EnvBuilder,
+    Logical, TriggerMode, TypedActionKey,
+};
+
+/// Forwards an event if at least `min_period` of logical time passed since the last forwarded event.
+struct ThrottleFn<T> {
+    min_period: runtime::Duration,
+    /// The logical time of the last forwarded event
+    last: Option<runtime::Duration>,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T: runtime::ReactorData + Clone> From<ThrottleFn<T>> for runtime::BoxedReactionFn {
+    fn from(value: ThrottleFn<T>) -> Self {
+        Box::new(value)
+    }
+}
+
+impl<'store, T: runtime::ReactorData + Clone> runtime::ReactionFn<'store> for ThrottleFn<T> {
+    fn trigger(
+        &mut self,
+        ctx: &'store mut runtime::Context,
+        _reactor: &'store mut dyn runtime::BaseReactor,
+        _ports: runtime::Refs<'store, dyn runtime::BasePort>,
+        _ports_mut: runtime::RefsMut<'store, dyn runtime::BasePort>,
+        actions: runtime::RefsMut<'store, dyn runtime::BaseAction>,
+    ) {
+        let (mut source, mut throttled): (runtime::ActionRef<T>, runtime::ActionRef<T>) =
+            actions.partition_mut().expect("Throttle actions not found");
+        let now = ctx.get_elapsed_logical_time();
+        if self.last.is_some_and(|last| now - last < self.min_period) {
+            return;
+        }
+        if let Some(value) = source.get_value(ctx).cloned() {
+            self.last = Some(now);
+            throttled
+                .schedule(ctx, value, None)
+                .expect("A logical action is always scheduled into the future");
+        }
+    }
+}
+
+/// Forwards the latest event once no further events arrived for `quiet_period` of logical time.
+///
+/// Every event is scheduled on a pending action after the quiet period, tagged with a generation count. A pending event
+/// is only forwarded if no newer event arrived in the meantime, i.e. if its generation is still the current one.
+struct DebounceFn<T> {
+    quiet_period: runtime::Duration,
+    generation: u64,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T: runtime::ReactorData + Clone> From<DebounceFn<T>> for runtime::BoxedReactionFn {
+    fn from(value: DebounceFn<T>) -> Self {
+        Box::new(value)
+    }
+}
+
+impl<'store, T: runtime::ReactorData + Clone> runtime::ReactionFn<'store> for DebounceFn<T> {
+    fn trigger(
+        &mut self,
+        ctx: &'store mut runtime::Context,
+        _reactor: &'store mut dyn runtime::BaseReactor,
+        _ports: runtime::Refs<'store, dyn runtime::BasePort>,
+        _ports_mut: runtime::RefsMut<'store, dyn runtime::BasePort>,
+        actions: runtime::RefsMut<'store, dyn runtime::BaseAction>,
+    ) {
+        let (mut source, mut pending, mut debounced): (
+            runtime::ActionRef<T>,
+            runtime::ActionRef<(u64, T)>,
+            runtime::ActionRef<T>,
+        ) = actions.partition_mut().expect("Debounce actions not found");
+
+        // The quiet period of a pending event ending with a new event still counts as quiet
+        if let Some((generation, value)) = pending.get_value(ctx) {
+            if *generation == self.generation {
+                let value = value.clone();
+                debounced
+                    .schedule(ctx, value, None)
+                    .expect("A logical action is always scheduled into the future");
+            }
+        }
+
+        if let Some(value) = source.get_value(ctx).cloned() {
+            self.generation += 1;
+            pending
+                .schedule(ctx, (self.generation, value), Some(self.quiet_period))
+                .expect("The quiet period is checked to be non-negative");
+        }
+    }
+}
+
+impl EnvBuilder {
+    /// Check that `action_key` is a logical or physical action that can be decorated, returning its name and reactor.
+    fn decorated_action(
+        &self,
+        action_key: BuilderActionKey,
+    ) -> Result<(String, BuilderReactorKey), BuilderError> {
+        let action = self.get_action(action_key)?;
+        if !matches!(action.r#type(), ActionType::Standard { .. }) {
+            return Err(BuilderError::InconsistentBuilderState {
+                what: format!(
+                    "Only logical and physical actions can be rate-limited, '{}' is not one",
+                    action.name()
+                ),
+            });
+        }
+        Ok((action.name().to_owned(), action.reactor_key()))
+    }
+
+    /// Throttle the events of `action_key`, forwarding only those at least `min_period` of logical time after the last
+    /// forwarded event.
+    ///
+    /// Returns a new logical action `<name>_throttled` on the same reactor, which is scheduled one microstep after each
+    /// forwarded event with the same value. The first event is always forwarded, and events arriving too soon are
+    /// dropped rather than delayed.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,ignore
+    /// let key_press = reactor.add_physical_action::<char>("key_press", None)?;
+    /// let key_press = reactor.throttle(key_press, Duration::milliseconds(100))?;
+    /// ```
+    pub fn throttle<T, Q: ActionTag>(
+        &mut self,
+        action_key: TypedActionKey<T, Q>,
+        min_period: runtime::Duration,
+    ) -> Result<TypedActionKey<T, Logical>, BuilderError>
+    where
+        T: runtime::ReactorData + Clone,
+    {
+        let (name, reactor_key) = self.decorated_action(action_key.into())?;
+        let throttled = self.internal_add_action::<T, Logical>(
+            &format!("{name}_throttled"),
+            None,
+            reactor_key,
+        )?;
+        let throttle_fn = ThrottleFn::<T> {
+            min_period,
+            last: None,
+            _t: PhantomData,
+        };
+        self.add_reaction(&format!("_{name}_throttle"), reactor_key, throttle_fn)
+            .with_action(action_key, 0, TriggerMode::TriggersAndUses)?
+            .with_action(throttled, 1, TriggerMode::EffectsOnly)?
+            .finish()?;
+        Ok(throttled)
+    }
+
+    /// Debounce the events of `action_key`, forwarding an event only once no further events arrived for `quiet_period`
+    /// of logical time.
+    ///
+    /// Returns a new logical action `<name>_debounced` on the same reactor, which is scheduled with the value of the
+    /// last event in a burst one microstep after its quiet period ended. Events followed by another event within
+    /// `quiet_period` are dropped.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,ignore
+    /// let message = reactor.add_physical_action::<String>("message", None)?;
+    /// let message = reactor.debounce(message, Duration::milliseconds(50))?;
+    /// ```
+    pub fn debounce<T, Q: ActionTag>(
+        &mut self,
+        action_key: TypedActionKey<T, Q>,
+        quiet_period: runtime::Duration,
+    ) -> Result<TypedActionKey<T, Logical>, BuilderError>
+    where
+        T: runtime::ReactorData + Clone,
+    {
+        let (name, reactor_key) = self.decorated_action(action_key.into())?;
+        if quiet_period.is_negative() {
+            return Err(BuilderError::NegativeDelay {
+                what: format!("the quiet period of debounced action '{name}'"),
+                delay: quiet_period,
+            });
+        }
+        let pending = self.internal_add_action::<(u64, T), Logical>(
+            &format!("_{name}_pending"),
+            None,
+            reactor_key,
+        )?;
+        let debounced = self.internal_add_action::<T, Logical>(
+            &format!("{name}_debounced"),
+            None,
+            reactor_key,
+        )?;
+        let debounce_fn = DebounceFn::<T> {
+            quiet_period,
+            generation: 0,
+            _t: PhantomData,
+        };
+        self.add_reaction(&format!("_{name}_debounce"), reactor_key, debounce_fn)
+            .with_action(action_key, 0, TriggerMode::TriggersAndUses)?
+            .with_action(pending, 1, TriggerMode::TriggersAndEffects)?
+            .with_action(debounced, 2, TriggerMode::EffectsOnly)?
+            .finish()?;
+        Ok(debounced)
+    }
+}
@@ -3,8 +3,10 @@ use itertools::Itertools;
 use slotmap::{SecondaryMap, SlotMap};
 
 use crate::{
+    probe::{BuilderProbeKey, ProbeBuilder},
+    reactor::{FlushBuilder, InitBuilder},
     ActionType, BuilderActionKey, BuilderError, BuilderPortKey, BuilderReactionKey,
-    BuilderReactorKey, ReactionBuilder, ReactorBuilder,
+    BuilderReactorKey, ReactionBuilder, Reactor, ReactorBuilder,
 };
 
 use super::EnvBuilder;
@@ -88,7 +90,15 @@ fn build_runtime_reactions(
             .collect();
 
         let reaction_key = runtime_reactions.insert({
-            runtime::Reaction::new(&reaction_builder.name, reaction_builder.reaction_fn, None)
+            let reaction = runtime::Reaction::new(
+                &reaction_builder.name,
+                reaction_builder.reaction_fn,
+                reaction_builder.deadline,
+            );
+            match reaction_builder.budget {
+                Some(budget) => reaction.with_budget(budget),
+                None => reaction,
+            }
         });
         reaction_use_ports.insert(reaction_key, use_port_set);
         reaction_effect_ports.insert(reaction_key, effect_port_set);
@@ -135,6 +145,15 @@ pub struct BuilderAliases {
     pub port_aliases: SecondaryMap<BuilderPortKey, runtime::PortKey>,
 }
 
+/// The runtime parts built by [`EnvBuilder::build_runtime`], with a typed key for the state of the top-level reactor.
+pub struct BuiltRuntime<S> {
+    pub env: runtime::Env,
+    pub reaction_graph: runtime::ReactionGraph,
+    pub aliases: BuilderAliases,
+    /// The key of the top-level reactor, to access its state with [`runtime::Env::state`]
+    pub reactor: runtime::TypedReactorKey<S>,
+}
+
 impl EnvBuilder {
     /// Construct runtime port structures from the builders.
     pub(crate) fn build_runtime_ports(&self) -> RuntimePortParts {
@@ -182,9 +201,16 @@ impl EnvBuilder {
             match action_builder.r#type() {
                 ActionType::Startup => startup_actions.extend(action_builder.triggers.keys()),
                 ActionType::Shutdown => shutdown_actions.extend(action_builder.triggers.keys()),
-                ActionType::Standard { build_fn, .. } => {
+                ActionType::Standard {
+                    build_fn,
+                    min_delay,
+                    ..
+                } => {
                     let action_key = runtime_actions
-                        .insert_with_key(|key| (build_fn)(action_builder.name(), key));
+                        .insert_with_key(|key| (build_fn)(action_builder.name(), key, *min_delay));
+                    if let Some(policy) = self.backpressure.get(builder_action_key) {
+                        runtime_actions[action_key].set_backpressure(*policy);
+                    }
                     action_triggers.insert(action_key, action_builder.triggers.keys().collect());
                     action_alias.insert(builder_action_key, action_key);
                 }
@@ -204,8 +230,14 @@ impl EnvBuilder {
     /// Convert the `EnvBuilder` into a [`runtime::Env`], [`runtime::ReactionGraph`] and
     /// [`BuilderAliases`]
     pub fn into_runtime_parts(
-        self,
+        mut self,
     ) -> Result<(runtime::Env, runtime::ReactionGraph, BuilderAliases), BuilderError> {
+        self.build_buses()?;
+        self.check_strict()?;
+        let probes = std::mem::take(&mut self.probes);
+        let inits = std::mem::take(&mut self.inits);
+        let flushes = std::mem::take(&mut self.flushes);
+        let parameters = runtime::Parameters::new(std::mem::take(&mut self.parameters));
         let reaction_levels = self.build_runtime_level_map()?;
 
         let RuntimePortParts {
@@ -213,6 +245,8 @@ impl EnvBuilder {
             port_triggers,
             port_aliases,
         } = self.build_runtime_ports();
+        self.check_metadata_conflicts(&port_aliases)?;
+        let metadata = std::mem::take(&mut self.metadata);
 
         let RuntimeActionParts {
             actions: runtime_actions,
@@ -222,6 +256,74 @@ impl EnvBuilder {
             aliases: action_aliases,
         } = self.build_runtime_actions();
 
+        // Fully-qualified names of the runtime reactions, actions and ports, each port named after the source of its
+        // connections
+        let reaction_fqns = self
+            .reaction_builders
+            .keys()
+            .map(|builder_reaction_key| {
+                Ok((
+                    builder_reaction_key,
+                    self.reaction_fqn(builder_reaction_key, false)?.to_string(),
+                ))
+            })
+            .collect::<Result<Vec<_>, BuilderError>>()?;
+        let action_fqns = action_aliases
+            .iter()
+            .map(|(builder_action_key, &action_key)| {
+                Ok((
+                    action_key,
+                    self.action_fqn(builder_action_key, false)?.to_string(),
+                ))
+            })
+            .collect::<Result<_, BuilderError>>()?;
+        let port_fqns = port_aliases
+            .iter()
+            .filter(|&(builder_port_key, _)| {
+                self.follow_port_inward_binding(builder_port_key) == builder_port_key
+            })
+            .map(|(builder_port_key, &port_key)| {
+                Ok((
+                    port_key,
+                    self.port_fqn(builder_port_key, false)?.to_string(),
+                ))
+            })
+            .collect::<Result<_, BuilderError>>()?;
+        let reactor_fqns = self
+            .reactor_builders
+            .keys()
+            .map(|builder_reactor_key| {
+                Ok((
+                    builder_reactor_key,
+                    self.reactor_fqn(builder_reactor_key, false)?.to_string(),
+                ))
+            })
+            .collect::<Result<Vec<_>, BuilderError>>()?;
+
+        // The reactor owning each runtime action and port, each port owned by the reactor of the source of its
+        // connections
+        let action_reactors = action_aliases
+            .iter()
+            .map(|(builder_action_key, &action_key)| {
+                (
+                    action_key,
+                    self.action_builders[builder_action_key].reactor_key(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let port_reactors = port_aliases
+            .iter()
+            .filter(|&(builder_port_key, _)| {
+                self.follow_port_inward_binding(builder_port_key) == builder_port_key
+            })
+            .map(|(builder_port_key, &port_key)| {
+                (
+                    port_key,
+                    self.port_builders[builder_port_key].get_reactor_key(),
+                )
+            })
+            .collect::<Vec<_>>();
+
         let RuntimeReactionParts {
             reactions: runtime_reactions,
             use_ports: reaction_use_ports,
@@ -307,10 +409,86 @@ impl EnvBuilder {
             })
             .collect();
 
+        let reaction_fqns = reaction_fqns
+            .into_iter()
+            .map(|(builder_reaction_key, fqn)| (reaction_aliases[builder_reaction_key], fqn))
+            .collect();
+
+        let reactor_fqns = reactor_fqns
+            .into_iter()
+            .map(|(builder_reactor_key, fqn)| (reactor_aliases[builder_reactor_key], fqn))
+            .collect();
+        let action_reactors = action_reactors
+            .into_iter()
+            .map(|(action_key, builder_reactor_key)| {
+                (action_key, reactor_aliases[builder_reactor_key])
+            })
+            .collect();
+        let port_reactors = port_reactors
+            .into_iter()
+            .map(|(port_key, builder_reactor_key)| (port_key, reactor_aliases[builder_reactor_key]))
+            .collect();
+
+        let failure_actions = self
+            .failure_actions
+            .iter()
+            .map(|&builder_action_key| action_aliases[builder_action_key])
+            .collect();
+        let overload_actions = self
+            .overload_actions
+            .iter()
+            .map(|&builder_action_key| action_aliases[builder_action_key])
+            .collect();
+        let parameter_actions = self
+            .parameter_actions
+            .iter()
+            .map(|&builder_action_key| action_aliases[builder_action_key])
+            .collect();
+        // Startup and shutdown actions have no runtime action to conflate
+        let conflatable_actions = self
+            .conflatable_actions
+            .iter()
+            .filter_map(|&builder_action_key| action_aliases.get(builder_action_key).copied())
+            .collect();
+
         let reaction_set_limits = runtime::ReactionSetLimits {
             max_level: reaction_levels.values().copied().max().unwrap_or_default(),
             num_keys: runtime_reactions.len(),
         };
+        let reaction_set_stats =
+            runtime::ReactionSetStats::from_levels(reaction_levels.values().copied());
+
+        let probes = probes
+            .into_iter()
+            .map(|ProbeBuilder { key, build_fn }| match key {
+                BuilderProbeKey::Port(port_key) => {
+                    build_fn(runtime::ProbeKey::Port(port_aliases[port_key]))
+                }
+                BuilderProbeKey::Action(action_key) => {
+                    build_fn(runtime::ProbeKey::Action(action_aliases[action_key]))
+                }
+            })
+            .collect();
+
+        let inits = inits
+            .into_iter()
+            .map(
+                |InitBuilder {
+                     reactor_key,
+                     build_fn,
+                 }| build_fn(reactor_aliases[reactor_key]),
+            )
+            .collect();
+
+        let flushes = flushes
+            .into_iter()
+            .map(
+                |FlushBuilder {
+                     reactor_key,
+                     build_fn,
+                 }| build_fn(reactor_aliases[reactor_key]),
+            )
+            .collect();
 
         // Sanity checks:
         assert_eq!(runtime_port_triggers.len(), runtime_ports.len());
@@ -320,31 +498,81 @@ impl EnvBuilder {
         assert_eq!(reaction_actions.len(), runtime_reactions.len());
         assert_eq!(reaction_reactors.len(), runtime_reactions.len());
 
+        let aliases = BuilderAliases {
+            reactor_aliases,
+            reaction_aliases,
+            action_aliases,
+            port_aliases,
+        };
+        let metadata = Self::build_runtime_metadata(metadata, &aliases);
+
         Ok((
             runtime::Env {
                 reactors: runtime_reactors,
                 actions: runtime_actions,
                 ports: runtime_ports,
                 reactions: runtime_reactions,
+                probes,
+                inits,
+                flushes,
+                parameters,
             },
             runtime::ReactionGraph {
                 port_triggers: runtime_port_triggers,
                 action_triggers: runtime_action_triggers,
                 startup_reactions,
                 shutdown_reactions,
+                failure_actions,
+                overload_actions,
+                conflatable_actions,
+                parameter_actions,
                 reaction_set_limits,
+                reaction_set_stats,
                 reaction_use_ports,
                 reaction_effect_ports,
                 reaction_actions,
                 reaction_reactors,
+                action_reactors,
+                port_reactors,
                 reactor_bank_infos: reactor_bank_indices,
+                reactor_fqns,
+                reaction_fqns,
+                action_fqns,
+                port_fqns,
+                metadata,
             },
-            BuilderAliases {
-                reactor_aliases,
-                reaction_aliases,
-                action_aliases,
-                port_aliases,
-            },
+            aliases,
         ))
     }
+
+    /// Convert the `EnvBuilder` into runtime parts like [`EnvBuilder::into_runtime_parts`], along with a typed key for
+    /// the state of the top-level reactor `R` built with the instance name `name`.
+    pub fn build_runtime<R: Reactor>(
+        self,
+        name: &str,
+    ) -> Result<BuiltRuntime<R::State>, BuilderError> {
+        let reactor_key = self
+            .reactor_builders
+            .iter()
+            .find(|(_, reactor)| reactor.parent_reactor_key.is_none() && reactor.name() == name)
+            .map(|(reactor_key, _)| reactor_key)
+            .ok_or_else(|| BuilderError::NamedReactorNotFound(name.to_owned()))?;
+
+        let (env, reaction_graph, aliases) = self.into_runtime_parts()?;
+        let reactor = env
+            .typed_reactor_key(aliases.reactor_aliases[reactor_key])
+            .ok_or_else(|| BuilderError::InconsistentBuilderState {
+                what: format!(
+                    "Top-level reactor '{name}' does not have state {}",
+                    std::any::type_name::<R::State>()
+                ),
+            })?;
+
+        Ok(BuiltRuntime {
+            env,
+            reaction_graph,
+            aliases,
+            reactor,
+        })
+    }
 }
@@ -4,8 +4,12 @@ use std::{collections::HashMap, fmt::Debug};
 
 use itertools::Itertools;
 use petgraph::prelude::DiGraphMap;
+use slotmap::SecondaryMap;
 
-use crate::{BuilderFqn, BuilderPortKey, BuilderReactorKey};
+use crate::{
+    runtime, ActionType, BuilderActionKey, BuilderError, BuilderFqn, BuilderFqnSegment,
+    BuilderPortKey, BuilderReactionKey, BuilderReactorKey,
+};
 
 use super::EnvBuilder;
 
@@ -186,6 +190,159 @@ impl EnvBuilder {
     }
 }
 
+impl EnvBuilder {
+    /// Returns a stable, line-based description of the structure of all Reactors: their ports, actions and reactions
+    /// (with levels and dependencies), followed by all port bindings.
+    ///
+    /// This is used to snapshot-test the topology of a Reactor, see [`EnvBuilder::assert_topology`].
+    pub fn topology_summary(&self) -> Result<String, BuilderError> {
+        let level_map = self.build_runtime_level_map()?;
+        let mut lines = Vec::new();
+
+        let reactors = self
+            .reactor_builders
+            .keys()
+            .map(|reactor_key| Ok((self.reactor_fqn(reactor_key, false)?, reactor_key)))
+            .collect::<Result<Vec<_>, BuilderError>>()?;
+
+        for (reactor_fqn, reactor_key) in reactors.into_iter().sorted() {
+            let reactor = &self.reactor_builders[reactor_key];
+            lines.push(format!("reactor {reactor_fqn}"));
+
+            for (fqn, port_key) in reactor
+                .ports
+                .keys()
+                .map(|port_key| Ok((self.port_fqn(port_key, false)?, port_key)))
+                .collect::<Result<Vec<_>, BuilderError>>()?
+                .into_iter()
+                .sorted()
+            {
+                lines.push(format!("port {fqn}: {}", self.port_summary(port_key)));
+            }
+
+            for (fqn, action_key) in reactor
+                .actions
+                .keys()
+                .map(|action_key| Ok((self.action_fqn(action_key, false)?, action_key)))
+                .collect::<Result<Vec<_>, BuilderError>>()?
+                .into_iter()
+                .sorted()
+            {
+                if let Some(kind) = self.action_kind(action_key) {
+                    lines.push(format!("action {fqn}: {kind}"));
+                }
+            }
+
+            for reaction_key in reactor
+                .reactions
+                .keys()
+                .sorted_by_key(|&reaction_key| self.reaction_builders[reaction_key].priority)
+            {
+                lines.push(format!(
+                    "reaction {} {}",
+                    self.reaction_fqn(reaction_key, false)?,
+                    self.reaction_summary(reaction_key, level_map[reaction_key])?
+                ));
+            }
+        }
+
+        let bindings = self
+            .port_builders
+            .iter()
+            .filter_map(|(port_key, port)| Some((port.get_inward_binding()?, port_key)))
+            .map(|(from, to)| {
+                Ok(format!(
+                    "binding {} -> {}",
+                    self.port_fqn(from, false)?,
+                    self.port_fqn(to, false)?
+                ))
+            })
+            .collect::<Result<Vec<_>, BuilderError>>()?;
+        lines.extend(bindings.into_iter().sorted());
+
+        Ok(lines.join("\n"))
+    }
+
+    /// The direction and value type of a port, e.g. `Input<u32>`.
+    pub(super) fn port_summary(&self, port_key: BuilderPortKey) -> String {
+        let port = &self.port_builders[port_key];
+        format!("{:?}<{}>", port.port_type(), port.type_name())
+    }
+
+    /// The kind of an action, or `None` for the startup and shutdown actions every reactor has.
+    pub(super) fn action_kind(&self, action_key: BuilderActionKey) -> Option<&'static str> {
+        match self.action_builders[action_key].r#type() {
+            ActionType::Startup | ActionType::Shutdown => None,
+            ActionType::Timer(_) => Some("Timer"),
+            ActionType::Standard {
+                is_logical: true, ..
+            } => Some("Logical"),
+            ActionType::Standard { .. } => Some("Physical"),
+        }
+    }
+
+    /// The level and dependencies of a reaction, e.g. `(1) triggers=[main::tick] uses=[] effects=[main::out]`.
+    pub(super) fn reaction_summary(
+        &self,
+        reaction_key: BuilderReactionKey,
+        level: runtime::Level,
+    ) -> Result<String, BuilderError> {
+        let reaction = &self.reaction_builders[reaction_key];
+        let ports = |ports: &SecondaryMap<BuilderPortKey, usize>| {
+            ports
+                .iter()
+                .sorted_by_key(|(_, order)| **order)
+                .map(|(port_key, _)| self.port_fqn(port_key, false))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        // The shared startup and shutdown actions are shown under the reactor of the reaction
+        let trigger_actions = reaction
+            .trigger_actions
+            .iter()
+            .sorted_by_key(|(_, order)| **order)
+            .map(|(action_key, _)| {
+                if self.is_lifecycle_action(action_key) {
+                    let segment =
+                        BuilderFqnSegment::from_action(&self.action_builders[action_key], false);
+                    self.reactor_fqn(reaction.reactor_key, true)?
+                        .append(segment)
+                } else {
+                    self.action_fqn(action_key, false)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(format!(
+            "({level}) triggers=[{}] uses=[{}] effects=[{}]",
+            trigger_actions
+                .iter()
+                .chain(&ports(&reaction.trigger_ports)?)
+                .join(", "),
+            ports(&reaction.use_ports)?.iter().join(", "),
+            ports(&reaction.effect_ports)?.iter().join(", "),
+        ))
+    }
+
+    /// Assert that [`EnvBuilder::topology_summary`] matches `expected`, ignoring indentation and blank lines.
+    ///
+    /// On a mismatch, the panic message contains the actual summary so it can be pasted into the snapshot.
+    pub fn assert_topology(&self, expected: &str) {
+        let actual = self
+            .topology_summary()
+            .unwrap_or_else(|err| panic!("Failed to summarize the topology: {err}"));
+        let normalize = |text: &str| {
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .join("\n")
+        };
+        assert!(
+            normalize(&actual) == normalize(expected),
+            "Reactor topology does not match the snapshot.\n\nExpected:\n{}\n\nActual:\n{actual}\n",
+            normalize(expected)
+        );
+    }
+}
+
 impl Debug for EnvBuilder {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let reactors = self.reactors_debug_map();
@@ -0,0 +1,212 @@
+//! Structural diff of two [`EnvBuilder`]s, e.g. to check that a refactor leaves the built graph unchanged.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+use crate::BuilderError;
+
+use super::EnvBuilder;
+
+/// The kind of element in a [`StructuralChange`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ElementKind {
+    Reactor,
+    Port,
+    Action,
+    Reaction,
+    Binding,
+}
+
+impl Display for ElementKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ElementKind::Reactor => write!(f, "reactor"),
+            ElementKind::Port => write!(f, "port"),
+            ElementKind::Action => write!(f, "action"),
+            ElementKind::Reaction => write!(f, "reaction"),
+            ElementKind::Binding => write!(f, "binding"),
+        }
+    }
+}
+
+/// A single difference found by [`EnvBuilder::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StructuralChange {
+    /// The element `name` only exists in the other builder.
+    Added {
+        kind: ElementKind,
+        name: String,
+        summary: String,
+    },
+    /// The element `name` only exists in this builder.
+    Removed {
+        kind: ElementKind,
+        name: String,
+        summary: String,
+    },
+    /// The element `name` exists in both builders, with a different summary.
+    Changed {
+        kind: ElementKind,
+        name: String,
+        before: String,
+        after: String,
+    },
+}
+
+impl Display for StructuralChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let with_summary = |summary: &str| {
+            if summary.is_empty() {
+                String::new()
+            } else {
+                format!(": {summary}")
+            }
+        };
+        match self {
+            StructuralChange::Added {
+                kind,
+                name,
+                summary,
+            } => write!(f, "+ {kind} {name}{}", with_summary(summary)),
+            StructuralChange::Removed {
+                kind,
+                name,
+                summary,
+            } => write!(f, "- {kind} {name}{}", with_summary(summary)),
+            StructuralChange::Changed {
+                kind,
+                name,
+                before,
+                after,
+            } => write!(f, "~ {kind} {name}: {before} => {after}"),
+        }
+    }
+}
+
+/// The structural differences between two [`EnvBuilder`]s, see [`EnvBuilder::diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvDiff {
+    /// The changes, ordered by kind and name
+    pub changes: Vec<StructuralChange>,
+}
+
+impl EnvDiff {
+    /// Whether both builders have the same structure.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl Display for EnvDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.changes.iter().join("\n"))
+    }
+}
+
+/// The summaries of all elements of a builder, keyed by kind and fully-qualified name.
+type Structure = BTreeMap<(ElementKind, String), String>;
+
+impl EnvBuilder {
+    /// Summarize the structure of this builder for [`EnvBuilder::diff`].
+    fn structure(&self) -> Result<Structure, BuilderError> {
+        let level_map = self.build_runtime_level_map()?;
+        let mut structure = Structure::new();
+
+        for reactor_key in self.reactor_builders.keys() {
+            let fqn = self.reactor_fqn(reactor_key, false)?;
+            structure.insert((ElementKind::Reactor, fqn.to_string()), String::new());
+        }
+        for port_key in self.port_builders.keys() {
+            let fqn = self.port_fqn(port_key, false)?;
+            structure.insert(
+                (ElementKind::Port, fqn.to_string()),
+                self.port_summary(port_key),
+            );
+            if let Some(from) = self.port_builders[port_key].get_inward_binding() {
+                let from = self.port_fqn(from, false)?;
+                structure.insert(
+                    (ElementKind::Binding, format!("{from} -> {fqn}")),
+                    String::new(),
+                );
+            }
+        }
+        for action_key in self.action_builders.keys() {
+            if let Some(kind) = self.action_kind(action_key) {
+                let fqn = self.action_fqn(action_key, false)?;
+                structure.insert((ElementKind::Action, fqn.to_string()), kind.to_owned());
+            }
+        }
+        for reaction_key in self.reaction_builders.keys() {
+            let fqn = self.reaction_fqn(reaction_key, false)?;
+            structure.insert(
+                (ElementKind::Reaction, fqn.to_string()),
+                self.reaction_summary(reaction_key, level_map[reaction_key])?,
+            );
+        }
+        Ok(structure)
+    }
+
+    /// Compare the structure of this builder with `other`, listing the elements added, removed or changed in `other`.
+    ///
+    /// The reactors, ports, actions, reactions and port bindings are matched by their fully-qualified names. A reaction
+    /// changes if its level or any of its triggers, uses or effects change, so a refactor with an empty diff builds an
+    /// identical reaction graph. Delayed connections show up as the reactors and reactions implementing them.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,ignore
+    /// let diff = before.diff(&after)?;
+    /// for change in &diff.changes {
+    ///     println!("{change}");
+    /// }
+    /// ```
+    pub fn diff(&self, other: &EnvBuilder) -> Result<EnvDiff, BuilderError> {
+        let before = self.structure()?;
+        let after = other.structure()?;
+
+        let changes = before
+            .iter()
+            .merge_join_by(after.iter(), |(a, _), (b, _)| a.cmp(b))
+            .filter_map(|either| match either {
+                itertools::EitherOrBoth::Left(((kind, name), summary)) => {
+                    Some(StructuralChange::Removed {
+                        kind: *kind,
+                        name: name.clone(),
+                        summary: summary.clone(),
+                    })
+                }
+                itertools::EitherOrBoth::Right(((kind, name), summary)) => {
+                    Some(StructuralChange::Added {
+                        kind: *kind,
+                        name: name.clone(),
+                        summary: summary.clone(),
+                    })
+                }
+                itertools::EitherOrBoth::Both(((kind, name), before), (_, after)) => {
+                    (before != after).then(|| StructuralChange::Changed {
+                        kind: *kind,
+                        name: name.clone(),
+                        before: before.clone(),
+                        after: after.clone(),
+                    })
+                }
+            })
+            .collect();
+        Ok(EnvDiff { changes })
+    }
+
+    /// Assert that `other` has the same structure as this builder, see [`EnvBuilder::diff`].
+    ///
+    /// On a mismatch, the panic message lists all structural changes.
+    pub fn assert_same_structure(&self, other: &EnvBuilder) {
+        let diff = self
+            .diff(other)
+            .unwrap_or_else(|err| panic!("Failed to diff the structure: {err}"));
+        assert!(
+            diff.is_empty(),
+            "The structure of the reactors changed:\n{diff}\n"
+        );
+    }
+}
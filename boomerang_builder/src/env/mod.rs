@@ -1,4 +1,13 @@
-use crate::{ActionTag, BuilderFqnSegment, ParentReactorBuilder, PortType};
+use crate::{
+    balance::{ConnectionDelay, ConnectionRecord},
+    bus::BaseBusBuilder,
+    connection::{ConnectionBuilder, SharedConnection, SharedConnectionKey},
+    metadata::BuilderMetadata,
+    probe::ProbeBuilder,
+    reactor::{FlushBuilder, InitBuilder},
+    ActionTag, BuilderFqnPattern, BuilderFqnSegment, Coalesce, ParentReactorBuilder,
+    PhysicalActionKey, PortType,
+};
 
 use super::{
     action::ActionBuilder, port::BasePortBuilder, reaction::ReactionBuilder, runtime, ActionType,
@@ -10,15 +19,22 @@ use itertools::Itertools;
 use petgraph::{prelude::DiGraphMap, EdgeDirection};
 use slotmap::{SecondaryMap, SlotMap};
 use std::{
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     convert::TryInto,
 };
 
 mod build;
 mod debug;
+mod diff;
 #[cfg(test)]
 mod tests;
 
+pub use build::{BuilderAliases, BuiltRuntime};
+pub use diff::{ElementKind, EnvDiff, StructuralChange};
+
+/// The reserved owner in the fully-qualified names of the startup and shutdown actions shared by all Reactors.
+pub const LIFECYCLE_OWNER: &str = "__env";
+
 mod util {
     use petgraph::visit::{IntoNeighborsDirected, IntoNodeIdentifiers, Visitable};
     use std::hash::Hash;
@@ -59,6 +75,15 @@ pub trait FindElements {
     fn get_action_by_name(&self, action_name: &str) -> Result<BuilderActionKey, BuilderError>;
 }
 
+/// How the ports bound by [`EnvBuilder::bind_port_as`] are checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Binding {
+    /// A connection between ports at neighbouring levels of the hierarchy, neither read nor written by reactions
+    Connection,
+    /// A tap anywhere in the hierarchy on a port that may already be read, see [`EnvBuilder::tap_connection`]
+    Tap,
+}
+
 #[derive(Default)]
 pub struct EnvBuilder {
     /// Builder for Actions
@@ -69,6 +94,51 @@ pub struct EnvBuilder {
     pub(super) reaction_builders: SlotMap<BuilderReactionKey, ReactionBuilder>,
     /// Builders for Reactors
     pub(super) reactor_builders: SlotMap<BuilderReactorKey, ReactorBuilder>,
+    /// Declared buses, in order of declaration
+    pub(super) buses: Vec<Box<dyn BaseBusBuilder>>,
+    /// Value probes
+    pub(super) probes: Vec<ProbeBuilder>,
+    /// Initializations run before logical time begins
+    pub(super) inits: Vec<InitBuilder>,
+    /// Flushes run after the shutdown tag
+    pub(super) flushes: Vec<FlushBuilder>,
+    /// Aliases from old to new fully-qualified names, resolved by all FQN lookups
+    pub(super) fqn_aliases: BTreeMap<BuilderFqn, BuilderFqn>,
+    /// The subtrees excluded by a disabled flag, see [`ReactorBuilderState::child_if`]
+    pub(super) excluded: Vec<BuilderFqn>,
+    /// Metadata attached to elements
+    pub(super) metadata: BuilderMetadata,
+    /// Logical connections, for delay balancing
+    pub(crate) connections: Vec<ConnectionRecord>,
+    /// The connection reactors of delayed and/or physical connections, shared by all targets of a source port
+    pub(crate) shared_connections: HashMap<SharedConnectionKey, SharedConnection>,
+    /// Actions scheduled with the panics caught in reactions
+    pub(super) failure_actions: Vec<BuilderActionKey>,
+    /// Actions scheduled with the overload reports
+    pub(super) overload_actions: Vec<BuilderActionKey>,
+    /// Actions whose overdue events may be dropped on overload
+    pub(super) conflatable_actions: Vec<BuilderActionKey>,
+    /// Actions scheduled with the parameter changes
+    pub(super) parameter_actions: Vec<BuilderActionKey>,
+    /// The backpressure policies of physical actions other than the default
+    pub(super) backpressure: SecondaryMap<BuilderActionKey, runtime::BackpressurePolicy>,
+    /// The periods of the periodic timers, e.g. the expected periods of rate monitors
+    pub(super) timer_periods: SecondaryMap<BuilderActionKey, runtime::Duration>,
+    /// Global parameters, by name
+    pub(super) parameters: BTreeMap<String, runtime::Parameter>,
+    /// Named phases of a tag, in execution order
+    pub(super) phases: Vec<String>,
+    /// Whether port contracts are checked, by default in debug builds only
+    pub(super) contract_checks: Option<bool>,
+    /// Whether the rules of strict mode are enforced, see [`EnvBuilder::strict`]
+    pub(super) strict: bool,
+    /// Input ports that may be left unconnected in strict mode
+    pub(super) optional_inputs: SecondaryMap<BuilderPortKey, ()>,
+    /// The startup and shutdown actions shared by all Reactors, see [`EnvBuilder::lifecycle_actions`]
+    pub(super) lifecycle_actions: Option<(TypedActionKey, TypedActionKey)>,
+    /// The monitors of the connection reactors, by their action
+    #[cfg(feature = "connection_stats")]
+    pub(crate) connection_monitors: SecondaryMap<BuilderActionKey, crate::ConnectionMonitor>,
 }
 
 impl EnvBuilder {
@@ -147,6 +217,56 @@ impl EnvBuilder {
         Ok(key)
     }
 
+    /// The startup and shutdown actions shared by all Reactors, named `__env::__startup` and `__env::__shutdown`
+    /// (see [`LIFECYCLE_OWNER`]) rather than under any of them. In the summary of a reaction, they are shown under the
+    /// Reactor of the reaction instead, e.g. `main::sink::__startup`.
+    ///
+    /// Sharing them keeps the number of actions independent of the number of Reactors. The runtime fans out to all the
+    /// reactions they trigger at once, so each Reactor still only sees its own reactions run. They are stored with the
+    /// first Reactor built, usually the top-level one.
+    pub(crate) fn lifecycle_actions(
+        &mut self,
+        reactor_key: BuilderReactorKey,
+    ) -> (TypedActionKey, TypedActionKey) {
+        if let Some(actions) = self.lifecycle_actions {
+            return actions;
+        }
+        let startup_action = self
+            .add_action::<(), Logical>("__startup", reactor_key, ActionType::Startup)
+            .expect("Duplicate startup Action?");
+        let shutdown_action = self
+            .add_action::<(), Logical>("__shutdown", reactor_key, ActionType::Shutdown)
+            .expect("Duplicate shutdown Action?");
+        *self
+            .lifecycle_actions
+            .insert((startup_action, shutdown_action))
+    }
+
+    /// Whether `action_key` is one of the startup and shutdown actions shared by all Reactors.
+    pub(crate) fn is_lifecycle_action(&self, action_key: BuilderActionKey) -> bool {
+        self.lifecycle_actions.is_some_and(|(startup, shutdown)| {
+            action_key == startup.into() || action_key == shutdown.into()
+        })
+    }
+
+    /// The fully-qualified name of the Reactor owning `action_key`, or of [`LIFECYCLE_OWNER`] for the shared startup
+    /// and shutdown actions.
+    fn action_owner_fqn(
+        &self,
+        action_key: BuilderActionKey,
+        grouped: bool,
+    ) -> Result<BuilderFqn, BuilderError> {
+        if self.is_lifecycle_action(action_key) {
+            BuilderFqn::try_from(LIFECYCLE_OWNER)
+        } else {
+            self.reactor_fqn(self.action_builders[action_key].reactor_key(), grouped)
+        }
+    }
+
+    #[deprecated(
+        since = "0.3.1",
+        note = "all reactors already share a startup action, see `ReactorBuilderState::get_startup_action`"
+    )]
     pub fn add_startup_action(
         &mut self,
         name: &str,
@@ -155,6 +275,10 @@ impl EnvBuilder {
         self.add_action::<(), Logical>(name, reactor_key, ActionType::Startup)
     }
 
+    #[deprecated(
+        since = "0.3.1",
+        note = "all reactors already share a shutdown action, see `ReactorBuilderState::get_shutdown_action`"
+    )]
     pub fn add_shutdown_action(
         &mut self,
         name: &str,
@@ -163,19 +287,148 @@ impl EnvBuilder {
         self.add_action::<(), Logical>(name, reactor_key, ActionType::Shutdown)
     }
 
+    /// Add a logical action to the reactor that is scheduled with the panics caught in any reactions at the next
+    /// microstep, see [`runtime::isolation`].
+    pub fn add_failure_action(
+        &mut self,
+        name: &str,
+        reactor_key: BuilderReactorKey,
+    ) -> Result<TypedActionKey<Vec<runtime::ReactorFailure>, Logical>, BuilderError> {
+        let action_key = self.internal_add_action::<Vec<runtime::ReactorFailure>, Logical>(
+            name,
+            None,
+            reactor_key,
+        )?;
+        self.failure_actions.push(action_key.into());
+        Ok(action_key)
+    }
+
+    /// Add a logical action to the reactor that is scheduled with an [`runtime::Overload`] report whenever the
+    /// scheduler responds to sustained overload with [`runtime::OverloadResponse::Action`], see [`runtime::overload`].
+    pub fn add_overload_action(
+        &mut self,
+        name: &str,
+        reactor_key: BuilderReactorKey,
+    ) -> Result<TypedActionKey<runtime::Overload, Logical>, BuilderError> {
+        let action_key =
+            self.internal_add_action::<runtime::Overload, Logical>(name, None, reactor_key)?;
+        self.overload_actions.push(action_key.into());
+        Ok(action_key)
+    }
+
+    /// Add a logical action to the reactor that is scheduled with a [`runtime::ParameterChange`] at the tag reloadable
+    /// parameters are updated at, see [`runtime::params`].
+    pub fn add_parameter_change_action(
+        &mut self,
+        name: &str,
+        reactor_key: BuilderReactorKey,
+    ) -> Result<TypedActionKey<runtime::ParameterChange, Logical>, BuilderError> {
+        let action_key =
+            self.internal_add_action::<runtime::ParameterChange, Logical>(name, None, reactor_key)?;
+        self.parameter_actions.push(action_key.into());
+        Ok(action_key)
+    }
+
+    /// Mark an action as conflatable, allowing the scheduler to drop all but the most recent of its overdue events
+    /// under sustained overload with [`runtime::OverloadResponse::DropConflatable`], see [`runtime::overload`].
+    pub fn mark_conflatable(
+        &mut self,
+        action_key: impl Into<BuilderActionKey>,
+    ) -> Result<(), BuilderError> {
+        let action_key = action_key.into();
+        if !self.action_builders.contains_key(action_key) {
+            return Err(BuilderError::ActionKeyNotFound(action_key));
+        }
+        if !self.conflatable_actions.contains(&action_key) {
+            self.conflatable_actions.push(action_key);
+        }
+        Ok(())
+    }
+
+    /// Set what a physical action does with its events while the queue to the scheduler is full, see
+    /// [`runtime::backpressure`].
+    pub fn set_backpressure(
+        &mut self,
+        action_key: impl Into<PhysicalActionKey>,
+        policy: runtime::BackpressurePolicy,
+    ) -> Result<(), BuilderError> {
+        let action_key = BuilderActionKey::from(action_key.into());
+        if !self.action_builders.contains_key(action_key) {
+            return Err(BuilderError::ActionKeyNotFound(action_key));
+        }
+        self.backpressure.insert(action_key, policy);
+        Ok(())
+    }
+
+    /// Set the global parameter `name` to `value`, replacing any previous value.
+    ///
+    /// Parameters are read-only at runtime, and available to all reactions with [`runtime::Context::parameter`], see
+    /// [`runtime::params`].
+    pub fn set_parameter<T: runtime::ReactorData>(&mut self, name: &str, value: T) {
+        self.parameters
+            .insert(name.to_owned(), runtime::Parameter::new(value));
+    }
+
+    /// Set the global parameter `name` to `value`, replacing any previous value, and allow it to be updated at runtime
+    /// with [`runtime::SchedulerHandle::update_parameter`], see [`runtime::params`].
+    pub fn set_reloadable_parameter<T: runtime::ReactorData>(&mut self, name: &str, value: T) {
+        self.parameters
+            .insert(name.to_owned(), runtime::Parameter::reloadable(value));
+    }
+
+    /// Get the global parameter `name`, if it is set and of type `T`.
+    pub fn get_parameter<T: runtime::ReactorData>(&self, name: &str) -> Option<&T> {
+        self.parameters
+            .get(name)
+            .and_then(|parameter| parameter.downcast_ref())
+    }
+
+    /// Declare the named phases of a tag, in execution order, e.g., `["sense", "plan", "act"]`.
+    ///
+    /// All Reactions in a phase (see [`ReactionBuilderState::with_phase`]) complete before any Reaction of a later phase
+    /// begins, across reactors and without port dependencies between them. Reactions without a phase are only ordered
+    /// by their dependencies. A phase order contradicting the dependencies is reported as a
+    /// [`BuilderError::ReactionGraphCycle`].
+    pub fn set_phases<I>(&mut self, phases: I) -> Result<(), BuilderError>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let mut declared = Vec::new();
+        for phase in phases.into_iter().map(Into::into) {
+            if declared.contains(&phase) {
+                return Err(BuilderError::DuplicatePhaseDefinition(phase));
+            }
+            declared.push(phase);
+        }
+        self.phases = declared;
+        Ok(())
+    }
+
+    /// Get the named phases of a tag, in execution order.
+    pub fn phases(&self) -> &[String] {
+        &self.phases
+    }
+
     pub fn internal_add_action<T: runtime::ReactorData, Q: ActionTag>(
         &mut self,
         name: &str,
         min_delay: Option<runtime::Duration>,
         reactor_key: BuilderReactorKey,
     ) -> Result<TypedActionKey<T, Q>, BuilderError> {
+        if let Some(delay) = min_delay.filter(|delay| delay.is_negative()) {
+            return Err(BuilderError::NegativeDelay {
+                what: format!("the minimum delay of action '{name}'"),
+                delay,
+            });
+        }
         self.add_action::<T, Q>(
             name,
             reactor_key,
             ActionType::Standard {
                 is_logical: Q::IS_LOGICAL,
                 min_delay,
-                build_fn: Box::new(move |name, key| {
+                build_fn: Box::new(|name, key, min_delay| {
                     runtime::Action::<T>::new(name, key, min_delay, Q::IS_LOGICAL).boxed()
                 }),
             },
@@ -219,7 +472,7 @@ impl EnvBuilder {
 
         let key = self
             .action_builders
-            .insert(ActionBuilder::new(name, reactor_key, r#type));
+            .insert(ActionBuilder::new::<T>(name, reactor_key, r#type));
 
         reactor_builder.actions.insert(key, ());
 
@@ -293,22 +546,25 @@ impl EnvBuilder {
         T::Error: Into<BuilderError>,
     {
         let reactor_fqn: BuilderFqn = reactor_fqn.try_into().map_err(Into::into)?;
-        let (_, segment) = reactor_fqn
-            .clone()
-            .split_last()
-            .ok_or(BuilderError::InvalidFqn(reactor_fqn.to_string()))?;
-        self.reactor_builders
-            .iter()
-            .find_map(|(reactor_key, reactor_builder)| {
-                if BuilderFqnSegment::from_reactor(reactor_builder, false) == segment {
-                    Some(reactor_key)
-                } else {
-                    None
-                }
-            })
+        self.find_reactors_matching(&reactor_fqn)?
+            .into_iter()
+            .next()
             .ok_or_else(|| BuilderError::NamedReactorNotFound(reactor_fqn.to_string()))
     }
 
+    /// Find a Port globally in the EnvBuilder given its fully-qualified name
+    pub fn find_port_by_fqn<T>(&self, port_fqn: T) -> Result<BuilderPortKey, BuilderError>
+    where
+        T: TryInto<BuilderFqn>,
+        T::Error: Into<BuilderError>,
+    {
+        let port_fqn: BuilderFqn = port_fqn.try_into().map_err(Into::into)?;
+        self.find_ports_matching(&port_fqn)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| BuilderError::NamedPortNotFound(port_fqn.to_string()))
+    }
+
     /// Find a PhysicalAction globally in the EnvBuilder given its fully-qualified name
     pub fn find_physical_action_by_fqn<T>(
         &self,
@@ -319,21 +575,88 @@ impl EnvBuilder {
         T::Error: Into<BuilderError>,
     {
         let action_fqn: BuilderFqn = action_fqn.try_into().map_err(Into::into)?;
+        self.find_actions_matching(&action_fqn)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| BuilderError::NamedActionNotFound(action_fqn.to_string()))
+    }
 
-        let (reactor_fqn, segment) = action_fqn
-            .clone()
-            .split_last()
-            .ok_or(BuilderError::InvalidFqn(action_fqn.to_string()))?;
+    /// Find all Reactors whose fully-qualified names match `pattern`, e.g. `main::robot[*]::motor`.
+    pub fn find_reactors_matching<T>(
+        &self,
+        pattern: T,
+    ) -> Result<Vec<BuilderReactorKey>, BuilderError>
+    where
+        T: TryInto<BuilderFqnPattern>,
+        T::Error: Into<BuilderError>,
+    {
+        let pattern = self.resolve_pattern(pattern.try_into().map_err(Into::into)?)?;
+        self.reactor_builders
+            .keys()
+            .filter_map(|reactor_key| {
+                self.reactor_fqn(reactor_key, false)
+                    .map(|fqn| pattern.matches(&fqn).then_some(reactor_key))
+                    .transpose()
+            })
+            .collect()
+    }
 
-        let reactor = self.find_reactor_by_fqn(reactor_fqn)?;
+    /// Find all Ports whose fully-qualified names match `pattern`, e.g. `robot.*.motor.cmd`.
+    pub fn find_ports_matching<T>(&self, pattern: T) -> Result<Vec<BuilderPortKey>, BuilderError>
+    where
+        T: TryInto<BuilderFqnPattern>,
+        T::Error: Into<BuilderError>,
+    {
+        let pattern = self.resolve_pattern(pattern.try_into().map_err(Into::into)?)?;
+        self.port_builders
+            .keys()
+            .filter_map(|port_key| {
+                self.port_fqn(port_key, false)
+                    .map(|fqn| pattern.matches(&fqn).then_some(port_key))
+                    .transpose()
+            })
+            .collect()
+    }
 
-        self.reactor_builders[reactor]
-            .actions
+    /// Find all Actions whose fully-qualified names match `pattern`, e.g. `main::**::event`.
+    pub fn find_actions_matching<T>(
+        &self,
+        pattern: T,
+    ) -> Result<Vec<BuilderActionKey>, BuilderError>
+    where
+        T: TryInto<BuilderFqnPattern>,
+        T::Error: Into<BuilderError>,
+    {
+        let pattern = self.resolve_pattern(pattern.try_into().map_err(Into::into)?)?;
+        self.action_builders
+            .iter()
+            .filter_map(|(action_key, action)| {
+                self.action_owner_fqn(action_key, false)
+                    .and_then(|fqn| fqn.append(BuilderFqnSegment::from_action(action, false)))
+                    .map(|fqn| pattern.matches(&fqn).then_some(action_key))
+                    .transpose()
+            })
+            .collect()
+    }
+
+    /// Find all Reactions whose fully-qualified names match `pattern`, e.g. `main::bank[0..2]::*`.
+    pub fn find_reactions_matching<T>(
+        &self,
+        pattern: T,
+    ) -> Result<Vec<BuilderReactionKey>, BuilderError>
+    where
+        T: TryInto<BuilderFqnPattern>,
+        T::Error: Into<BuilderError>,
+    {
+        let pattern = self.resolve_pattern(pattern.try_into().map_err(Into::into)?)?;
+        self.reaction_builders
             .keys()
-            .find(|action_key| {
-                BuilderFqnSegment::from_action(&self.action_builders[*action_key], false) == segment
+            .filter_map(|reaction_key| {
+                self.reaction_fqn(reaction_key, false)
+                    .map(|fqn| pattern.matches(&fqn).then_some(reaction_key))
+                    .transpose()
             })
-            .ok_or_else(|| BuilderError::NamedActionNotFound(action_fqn.to_string()))
+            .collect()
     }
 
     /// Find a possible common parent Reactor for two Reactor elements in the EnvBuilder (if it exists).
@@ -378,8 +701,61 @@ impl EnvBuilder {
         P1: Into<BuilderPortKey>,
         P2: Into<BuilderPortKey>,
     {
+        self.connect_ports_coalesced::<T, _, _>(
+            source_key,
+            target_key,
+            after,
+            physical,
+            Coalesce::QueueAll,
+        )
+    }
+
+    /// Connect two ports together, coalescing values that arrive while a delivery is still pending.
+    ///
+    /// See [`EnvBuilder::connect_ports`]. Coalescing is implemented by the connection reactions, so it is only
+    /// available for delayed and/or physical connections.
+    pub fn connect_ports_coalesced<T, P1, P2>(
+        &mut self,
+        source_key: P1,
+        target_key: P2,
+        after: Option<runtime::Duration>,
+        physical: bool,
+        coalesce: Coalesce,
+    ) -> Result<(), BuilderError>
+    where
+        T: runtime::ReactorData + Clone,
+        P1: Into<BuilderPortKey>,
+        P2: Into<BuilderPortKey>,
+    {
+        if let Some(delay) = after.filter(|delay| delay.is_negative()) {
+            return Err(BuilderError::NegativeDelay {
+                what: "a connection".to_owned(),
+                delay,
+            });
+        }
         if after.is_none() && !physical {
-            self.bind_port(source_key, target_key)
+            if coalesce != Coalesce::QueueAll {
+                let port_a_key = source_key.into();
+                let port_b_key = target_key.into();
+                return Err(BuilderError::PortConnectionError {
+                    port_a_key,
+                    port_b_key,
+                    what: "Coalescing requires a delayed or physical connection".to_owned(),
+                });
+            }
+
+            let (source_key, target_key) = (source_key.into(), target_key.into());
+            self.bind_port(source_key, target_key)?;
+            self.record_connection(
+                source_key,
+                target_key,
+                ConnectionDelay::Direct {
+                    reconnect: |env, source_key, target_key, after| {
+                        env.connect_ports::<T, _, _>(source_key, target_key, Some(after), false)
+                    },
+                },
+            );
+            Ok(())
         } else {
             // Ports connected with a delay and/or physical connections are implemented as a pair of Reactions that trigger and react to an action.
 
@@ -397,41 +773,137 @@ impl EnvBuilder {
                     what: "Ports must belong to the same reactor or a common parent reactor to be connected".to_owned(),
                 })?;
 
-            let source_fqn = self.port_fqn(source_key, false)?;
-            let target_fqn = self.port_fqn(target_key, false)?;
-            let reactor_name = format!("connection_{source_fqn}->{target_fqn}");
-
-            // 1. create a new reactor to hold the action and reactions
-            let (input_port, output_port) = if physical {
-                let reactor =
-                    <crate::connection::ConnectionBuilder<T, Physical> as crate::Reactor>::build(
-                        &reactor_name,
-                        after.unwrap_or_default(),
-                        Some(parent_reactor_key),
-                        None,
-                        self,
-                    )?;
-                (reactor.input, reactor.output)
-            } else {
-                let reactor =
-                    <crate::connection::ConnectionBuilder<T, Logical> as crate::Reactor>::build(
-                        &reactor_name,
-                        after.unwrap_or_default(),
-                        Some(parent_reactor_key),
-                        None,
-                        self,
-                    )?;
-                (reactor.input, reactor.output)
+            let after = after.unwrap_or_default();
+            let shared_key = SharedConnectionKey {
+                source: source_key,
+                parent: parent_reactor_key,
+                after,
+                physical,
+                coalesce,
             };
 
-            // Bind the input and output ports to the source and target ports
-            self.bind_port(source_key, input_port)?;
-            self.bind_port(output_port, target_key)?;
+            // 1. create a new reactor to hold the action and reactions, or broadcast from the one already connected to
+            // the source with the same parameters, so a fan-out costs a single pair of reactions.
+            let connection = match self.shared_connections.get(&shared_key) {
+                Some(&connection) => connection,
+                None => {
+                    let reactor_name = self.connection_name(source_key, target_key)?;
+                    let connection = if physical {
+                        let reactor = <ConnectionBuilder<T, Physical> as crate::Reactor>::build(
+                            &reactor_name,
+                            (after, coalesce),
+                            Some(parent_reactor_key),
+                            None,
+                            self,
+                        )?;
+                        SharedConnection {
+                            action: reactor.action.into(),
+                            input: reactor.input.into(),
+                            output: reactor.output.into(),
+                        }
+                    } else {
+                        let reactor = <ConnectionBuilder<T, Logical> as crate::Reactor>::build(
+                            &reactor_name,
+                            (after, coalesce),
+                            Some(parent_reactor_key),
+                            None,
+                            self,
+                        )?;
+                        SharedConnection {
+                            action: reactor.action.into(),
+                            input: reactor.input.into(),
+                            output: reactor.output.into(),
+                        }
+                    };
+                    self.bind_port(source_key, connection.input)?;
+                    self.shared_connections.insert(shared_key, connection);
+                    connection
+                }
+            };
+
+            if !physical {
+                self.record_connection(
+                    source_key,
+                    target_key,
+                    ConnectionDelay::After {
+                        after,
+                        action: connection.action,
+                        input: connection.input,
+                        output: connection.output,
+                        reconnect: |env, source_key, target_key, after| {
+                            env.connect_ports::<T, _, _>(source_key, target_key, Some(after), false)
+                        },
+                    },
+                );
+            }
+
+            // Bind the output port to the target port
+            self.bind_port(connection.output, target_key)?;
 
             Ok(())
         }
     }
 
+    /// Connect two ports given by their fully-qualified names, e.g. `"main::source::out"`, as when the topology is
+    /// loaded from a configuration file.
+    ///
+    /// Both ports must carry values of type `T`, otherwise a [`BuilderError::PortTypeMismatch`] is returned. See
+    /// [`EnvBuilder::connect_ports`] for the `after` and `physical` arguments. The connection is pruned if either port
+    /// is in a subtree excluded with [`ReactorBuilderState::child_if`].
+    pub fn connect<T>(
+        &mut self,
+        source_fqn: &str,
+        target_fqn: &str,
+        after: Option<runtime::Duration>,
+        physical: bool,
+    ) -> Result<(), BuilderError>
+    where
+        T: runtime::ReactorData + Clone,
+    {
+        let (source_key, target_key) = match (
+            self.find_typed_port_by_fqn::<T>(source_fqn),
+            self.find_typed_port_by_fqn::<T>(target_fqn),
+        ) {
+            (Err(BuilderError::ExcludedElement { subtree, .. }), _)
+            | (_, Err(BuilderError::ExcludedElement { subtree, .. })) => {
+                tracing::debug!(%subtree, "Pruned the connection from '{source_fqn}' to '{target_fqn}'.");
+                return Ok(());
+            }
+            (source_key, target_key) => (source_key?, target_key?),
+        };
+        self.connect_ports::<T, _, _>(source_key, target_key, after, physical)
+            .map_err(|err| match err {
+                BuilderError::PortConnectionError {
+                    port_a_key,
+                    port_b_key,
+                    what,
+                } => BuilderError::PortConnectionError {
+                    port_a_key,
+                    port_b_key,
+                    what: format!("Connecting '{source_fqn}' to '{target_fqn}': {what}"),
+                },
+                err => err,
+            })
+    }
+
+    /// Find a Port given its fully-qualified name, checking that it carries values of type `T`.
+    pub fn find_typed_port_by_fqn<T: runtime::ReactorData>(
+        &self,
+        port_fqn: &str,
+    ) -> Result<BuilderPortKey, BuilderError> {
+        let port_key = self.find_port_by_fqn(port_fqn)?;
+        let found = self.port_builders[port_key].type_name();
+        let expected = std::any::type_name::<T>();
+        if found != expected {
+            return Err(BuilderError::PortTypeMismatch {
+                port: port_fqn.to_owned(),
+                expected,
+                found,
+            });
+        }
+        Ok(port_key)
+    }
+
     /// Bind Port A to Port B
     /// The nominal case is to bind Input A to Output B
     pub fn bind_port<P1, P2>(&mut self, port_a_key: P1, port_b_key: P2) -> Result<(), BuilderError>
@@ -439,17 +911,23 @@ impl EnvBuilder {
         P1: Into<BuilderPortKey>,
         P2: Into<BuilderPortKey>,
     {
-        let port_a_key = port_a_key.into();
-        let port_b_key = port_b_key.into();
+        self.bind_port_as(port_a_key.into(), port_b_key.into(), Binding::Connection)
+    }
 
+    /// Bind Port A to Port B like [`EnvBuilder::bind_port`], only checking the hierarchy and dependencies of the ports
+    /// for a [`Binding::Connection`].
+    pub(crate) fn bind_port_as(
+        &mut self,
+        port_a_key: BuilderPortKey,
+        port_b_key: BuilderPortKey,
+        binding: Binding,
+    ) -> Result<(), BuilderError> {
         let port_a_fqn = self.port_fqn(port_a_key, false)?;
         let port_b_fqn = self.port_fqn(port_b_key, false)?;
 
-        tracing::debug!("Binding ports: {port_a_fqn:?} -> {port_b_fqn:?}",);
+        tracing::debug!("Binding ports ({binding:?}): {port_a_fqn:?} -> {port_b_fqn:?}",);
 
-        let port_a = &self.port_builders[port_a_key];
         let port_b = &self.port_builders[port_b_key];
-
         if port_b.get_inward_binding().is_some() {
             return Err(BuilderError::PortConnectionError {
                 port_a_key,
@@ -461,6 +939,28 @@ impl EnvBuilder {
             });
         }
 
+        if binding == Binding::Connection {
+            self.check_connection(port_a_key, port_b_key, &port_a_fqn, &port_b_fqn)?;
+        }
+
+        // All validity checks passed, so we can now bind the ports
+        self.port_builders[port_b_key].set_inward_binding(Some(port_a_key));
+        self.port_builders[port_a_key].add_outward_binding(port_b_key);
+
+        Ok(())
+    }
+
+    /// Check that Port A may be connected to Port B, given their dependencies and their place in the hierarchy.
+    fn check_connection(
+        &self,
+        port_a_key: BuilderPortKey,
+        port_b_key: BuilderPortKey,
+        port_a_fqn: &BuilderFqn,
+        port_b_fqn: &BuilderFqn,
+    ) -> Result<(), BuilderError> {
+        let port_a = &self.port_builders[port_a_key];
+        let port_b = &self.port_builders[port_b_key];
+
         if !port_a.deps().is_empty() {
             return Err(BuilderError::PortConnectionError {
                 port_a_key,
@@ -494,7 +994,8 @@ impl EnvBuilder {
                 let port_a_grandparent = self.reactor_builders[port_a.get_reactor_key()].parent_reactor_key;
                 let port_b_grandparent = self.reactor_builders[port_b.get_reactor_key()].parent_reactor_key;
                 // VALIDATE(this->container()->container() == port->container()->container(), 
-                if !matches!((port_a_grandparent, port_b_grandparent), (Some(key_a), Some(key_b)) if key_a == key_b) {
+                // Top-level reactors have no container, and are all at the same level
+                if port_a_grandparent != port_b_grandparent {
                     Err(BuilderError::PortConnectionError{
                         port_a_key,
                         port_b_key,
@@ -529,13 +1030,7 @@ impl EnvBuilder {
                     what: "Unexpected case: can't bind an input Port to an output Port.".to_owned()
                 })
             }
-        }?;
-
-        // All validity checks passed, so we can now bind the ports
-        self.port_builders[port_b_key].set_inward_binding(Some(port_a_key));
-        self.port_builders[port_a_key].add_outward_binding(port_b_key);
-
-        Ok(())
+        }
     }
 
     /// Get a fully-qualified string name for the given ActionKey
@@ -551,8 +1046,7 @@ impl EnvBuilder {
             .get(action_key)
             .ok_or(BuilderError::ActionKeyNotFound(action_key))?;
         let segment = BuilderFqnSegment::from_action(action, grouped);
-        self.reactor_fqn(action.reactor_key(), true)?
-            .append(segment)
+        self.action_owner_fqn(action_key, true)?.append(segment)
     }
 
     /// Get a fully-qualified string for the given ReactionKey
@@ -659,17 +1153,47 @@ impl EnvBuilder {
                     .map(move |dep_key| (reaction_key, dep_key))
             });
 
-        // For all Reactions within a Reactor, create a chain of dependencies by priority. This
-        // ensures that Reactions within a Reactor always end up at unique levels.
+        // For all Reactions within a Reactor, create a chain of dependencies by priority. Reactions declaring disjoint
+        // state fields are independent, and only ordered after the earlier Reactions they depend on.
         let internal = self.reactor_builders.values().flat_map(move |reactor| {
-            reactor
+            let reactions = reactor
                 .reactions
                 .keys()
                 .sorted_by_key(|&reaction_key| self.reaction_builders[reaction_key].priority)
-                .rev()
-                .tuple_windows()
+                .collect_vec();
+            if reactions
+                .iter()
+                .all(|&reaction_key| self.reaction_builders[reaction_key].state_fields.is_none())
+            {
+                return reactions.into_iter().rev().tuple_windows().collect_vec();
+            }
+            reactions
+                .iter()
+                .tuple_combinations()
+                .filter(|&(&earlier, &later)| {
+                    !self.reaction_builders[later]
+                        .is_independent_of(&self.reaction_builders[earlier])
+                })
+                .map(|(&earlier, &later)| (later, earlier))
+                .collect_vec()
         });
-        deps.chain(internal)
+
+        // The Reactions in each phase depend on all the Reactions of the previous (non-empty) phase.
+        let phased = self
+            .phases
+            .iter()
+            .map(|phase| {
+                self.reaction_builders
+                    .iter()
+                    .filter(|(_, reaction)| reaction.phase() == Some(phase))
+                    .map(|(reaction_key, _)| reaction_key)
+                    .collect_vec()
+            })
+            .filter(|reactions| !reactions.is_empty())
+            .tuple_windows()
+            .flat_map(|(earlier, later)| later.into_iter().cartesian_product(earlier));
+
+        deps.chain(internal).chain(phased)
     }
 
     /// Build a DAG of Reactions
@@ -709,6 +1233,15 @@ impl EnvBuilder {
     ) -> Result<SecondaryMap<BuilderReactionKey, runtime::Level>, BuilderError> {
         use petgraph::{algo::tred, graph::DefaultIx, graph::NodeIndex};
 
+        if let Some(phase) = self
+            .reaction_builders
+            .values()
+            .filter_map(ReactionBuilder::phase)
+            .find(|phase| !self.phases.iter().any(|declared| declared == phase))
+        {
+            return Err(BuilderError::NamedPhaseNotFound(phase.to_owned()));
+        }
+
         let mut graph = self.build_reaction_graph().into_graph::<DefaultIx>();
 
         // Transitive reduction and closures
@@ -746,6 +1279,10 @@ impl EnvBuilder {
         }));
 
         let mut levels: HashMap<_, runtime::Level> = HashMap::new();
+        // Reactions of a reactor never share a level, even when independent: each borrows the whole reactor at runtime,
+        // so they never run in parallel, and declaring disjoint state fields only lets a reaction move to an earlier
+        // level than the reactions before it.
+        let mut reactor_levels = HashSet::new();
         for &idx in toposort.iter() {
             let max_neighbor = graph
                 .neighbors_directed(idx, EdgeDirection::Incoming)
@@ -753,7 +1290,12 @@ impl EnvBuilder {
                 .max()
                 .unwrap_or_default();
 
-            levels.insert(idx, max_neighbor + 1);
+            let reactor_key = self.reaction_builders[graph[idx]].reactor_key;
+            let mut level = max_neighbor + 1;
+            while !reactor_levels.insert((reactor_key, level)) {
+                level += 1;
+            }
+            levels.insert(idx, level);
         }
 
         // Collect and return a Map with ReactionKey indices instead of NodeIndex
@@ -109,6 +109,52 @@ fn test_reactions1() {
     assert_eq!(env.reactions.len(), 2);
 }
 
+/// All reactors share a single pair of startup and shutdown actions, fanning out to the reactions of each.
+#[test]
+fn test_shared_lifecycle_actions() {
+    let mut env_builder = EnvBuilder::new();
+    let parent_builder = env_builder.add_reactor("parent", None, None, ());
+    let parent_key = parent_builder.get_key();
+    let startup = parent_builder.get_startup_action();
+    let shutdown = BuilderActionKey::from(parent_builder.get_shutdown_action());
+    parent_builder.finish().unwrap();
+
+    for name in ["child0", "child1"] {
+        let mut child_builder = env_builder.add_reactor(name, Some(parent_key), None, ());
+        assert_eq!(
+            BuilderActionKey::from(child_builder.get_startup_action()),
+            startup.into()
+        );
+        assert_eq!(
+            BuilderActionKey::from(child_builder.get_shutdown_action()),
+            shutdown
+        );
+        child_builder
+            .add_reaction("startup", reaction_closure!())
+            .with_action(startup, 0, TriggerMode::TriggersOnly)
+            .unwrap()
+            .finish()
+            .unwrap();
+        child_builder.finish().unwrap();
+    }
+
+    assert_eq!(env_builder.action_builders.len(), 2);
+    // They belong to the environment rather than to the first reactor built
+    assert_eq!(
+        env_builder
+            .action_fqn(startup.into(), false)
+            .unwrap()
+            .to_string(),
+        "__env::__startup"
+    );
+    assert_eq!(
+        env_builder.find_actions_matching("__env::*").unwrap(),
+        [startup.into(), shutdown]
+    );
+    let (_, reaction_graph, _) = env_builder.into_runtime_parts().unwrap();
+    assert_eq!(reaction_graph.startup_reactions.len(), 2);
+}
+
 #[test]
 fn test_actions1() {
     let mut env_builder = EnvBuilder::new();
@@ -165,3 +211,393 @@ fn test_actions1() {
 
     itertools::assert_equal(dep_info.reaction_actions[reaction_b].iter(), [action_a]);
 }
+
+#[test]
+fn test_fqn_aliases() {
+    let mut env_builder = EnvBuilder::new();
+    let main = env_builder
+        .add_reactor("main", None, None, ())
+        .finish()
+        .unwrap();
+    let pipeline = env_builder
+        .add_reactor("pipeline", Some(main), None, ())
+        .finish()
+        .unwrap();
+    let mut filter_builder = env_builder.add_reactor("filter", Some(pipeline), None, ());
+    let action = filter_builder
+        .add_physical_action::<()>("event", None)
+        .unwrap();
+    let filter = filter_builder.finish().unwrap();
+    let out = env_builder.add_output_port::<u32>("out", filter).unwrap();
+
+    // `filter` used to be named `old_filter`, directly under `main`
+    assert!(env_builder
+        .find_port_by_fqn("main::old_filter::out")
+        .is_err());
+    env_builder
+        .add_alias("main::old_filter", "main::pipeline::filter")
+        .unwrap();
+    env_builder
+        .add_alias("main::old_filter::old_out", "main::old_filter::out")
+        .unwrap();
+
+    assert_eq!(
+        env_builder.find_reactor_by_fqn("main::old_filter").unwrap(),
+        filter
+    );
+    assert_eq!(
+        env_builder
+            .find_port_by_fqn("main::old_filter::out")
+            .unwrap(),
+        BuilderPortKey::from(out)
+    );
+    assert_eq!(
+        env_builder
+            .find_port_by_fqn("main::old_filter::old_out")
+            .unwrap(),
+        BuilderPortKey::from(out)
+    );
+    assert_eq!(
+        env_builder
+            .find_physical_action_by_fqn("main::old_filter::event")
+            .unwrap(),
+        BuilderActionKey::from(action)
+    );
+
+    assert!(matches!(
+        env_builder.add_alias("main::old_filter", "main::filter"),
+        Err(BuilderError::InvalidAlias { .. })
+    ));
+    assert!(matches!(
+        env_builder.add_alias("main::pipeline", "main::pipeline::inner"),
+        Err(BuilderError::InvalidAlias { .. })
+    ));
+    assert!(matches!(
+        env_builder.add_alias("main::old_pipeline", "main::missing"),
+        Err(BuilderError::InvalidAlias { what, .. }) if what == "'main::missing' does not exist"
+    ));
+    // Aliases of aliases resolve to existing elements
+    env_builder
+        .add_alias("main::older_filter", "main::old_filter")
+        .unwrap();
+    assert!(env_builder
+        .find_reactor_by_fqn("main::old_pipeline")
+        .is_err());
+}
+
+#[test]
+fn test_find_matching() {
+    let mut env_builder = EnvBuilder::new();
+    let robot = env_builder
+        .add_reactor("robot", None, None, ())
+        .finish()
+        .unwrap();
+    let mut cmds = Vec::new();
+    for idx in 0..3 {
+        let bank_info = runtime::BankInfo { idx, total: 3 };
+        let leg = env_builder
+            .add_reactor("leg", Some(robot), Some(bank_info), ())
+            .finish()
+            .unwrap();
+        let mut motor = env_builder.add_reactor("motor", Some(leg), None, ());
+        let cmd = motor.add_input_port::<f64>("cmd").unwrap();
+        motor.finish().unwrap();
+        cmds.push(BuilderPortKey::from(cmd));
+    }
+
+    assert_eq!(
+        env_builder
+            .find_ports_matching("robot.*.motor.cmd")
+            .unwrap(),
+        cmds
+    );
+    assert_eq!(
+        env_builder.find_ports_matching("robot::**::cmd").unwrap(),
+        cmds
+    );
+    assert_eq!(
+        env_builder
+            .find_ports_matching("robot::leg[0..2]::motor::cmd")
+            .unwrap(),
+        cmds[..2]
+    );
+    assert_eq!(
+        env_builder
+            .find_port_by_fqn("robot.leg[2].motor.cmd")
+            .unwrap(),
+        cmds[2]
+    );
+    assert!(env_builder
+        .find_port_by_fqn("robot::leg::motor::cmd")
+        .is_err());
+    assert_eq!(
+        env_builder
+            .find_reactors_matching("robot::leg[*]")
+            .unwrap()
+            .len(),
+        3
+    );
+    // A reactor with the same name elsewhere in the hierarchy does not match
+    assert!(env_builder.find_reactor_by_fqn("motor").is_err());
+
+    env_builder
+        .add_probe("robot::leg[*]::motor::cmd", |value: &f64| *value > 1.0)
+        .unwrap();
+    assert_eq!(env_builder.probes.len(), 3);
+    assert!(matches!(
+        env_builder.add_probe("robot::*::missing", |_: &f64| true),
+        Err(BuilderError::NamedPortNotFound(_))
+    ));
+}
+
+#[test]
+fn test_connect_by_fqn() {
+    let mut env_builder = EnvBuilder::new();
+    let main = env_builder
+        .add_reactor("main", None, None, ())
+        .finish()
+        .unwrap();
+    let source = env_builder
+        .add_reactor("source", Some(main), None, ())
+        .finish()
+        .unwrap();
+    let sink = env_builder
+        .add_reactor("sink", Some(main), None, ())
+        .finish()
+        .unwrap();
+    let out = env_builder.add_output_port::<u32>("out", source).unwrap();
+    let _ = env_builder.add_output_port::<bool>("flag", source).unwrap();
+    let inp = env_builder.add_input_port::<u32>("inp", sink).unwrap();
+    let _ = env_builder.add_input_port::<u32>("delayed", sink).unwrap();
+
+    assert!(matches!(
+        env_builder.connect::<u32>("main::source::missing", "main::sink::inp", None, false),
+        Err(BuilderError::NamedPortNotFound(name)) if name == "main::source::missing"
+    ));
+    assert!(matches!(
+        env_builder.connect::<bool>("main::source::flag", "main::sink::inp", None, false),
+        Err(BuilderError::PortTypeMismatch { port, expected: "bool", found: "u32" })
+            if port == "main::sink::inp"
+    ));
+    assert!(matches!(
+        env_builder.connect::<u32>("main::sink::inp", "main::source::out", None, false),
+        Err(BuilderError::PortConnectionError { what, .. })
+            if what.starts_with("Connecting 'main::sink::inp' to 'main::source::out'")
+    ));
+
+    env_builder
+        .connect::<u32>("main::source::out", "main::sink::inp", None, false)
+        .unwrap();
+    assert_eq!(
+        env_builder.port_builders[inp.into()].get_inward_binding(),
+        Some(out.into())
+    );
+    env_builder
+        .connect::<u32>(
+            "main::source::out",
+            "main::sink::delayed",
+            Some(runtime::Duration::milliseconds(1)),
+            false,
+        )
+        .unwrap();
+    env_builder.into_runtime_parts().unwrap();
+}
+
+#[test]
+fn test_metadata_passthrough() {
+    let mut env_builder = EnvBuilder::new();
+    let mut reactor_builder = env_builder.add_reactor("test_reactor", None, None, ());
+    let startup = reactor_builder.get_startup_action();
+    let reaction_key = reactor_builder
+        .add_reaction("test", Box::new(reaction_closure!()))
+        .with_action(startup, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .finish()
+        .unwrap();
+    let reactor_key = reactor_builder.finish().unwrap();
+    let port_a = env_builder
+        .add_output_port::<u32>("a", reactor_key)
+        .unwrap();
+
+    env_builder
+        .set_metadata(reactor_key, "model_id", "blk-1")
+        .unwrap();
+    env_builder
+        .set_metadata(reaction_key, "doc", "Does nothing")
+        .unwrap();
+    env_builder.set_metadata(port_a, "unit", "m/s").unwrap();
+    env_builder.set_metadata(port_a, "unit", "km/h").unwrap();
+    // Startup actions have no runtime counterpart
+    env_builder.set_metadata(startup, "doc", "Startup").unwrap();
+
+    assert_eq!(
+        env_builder.metadata(port_a).unwrap()["unit"],
+        "km/h".to_owned()
+    );
+    assert!(env_builder
+        .set_metadata(BuilderReactorKey::default(), "doc", "")
+        .is_err());
+
+    let (_env, graph, aliases) = env_builder.into_runtime_parts().unwrap();
+    let metadata = &graph.metadata;
+    assert_eq!(
+        metadata.reactors[aliases.reactor_aliases[reactor_key]]["model_id"],
+        "blk-1"
+    );
+    assert_eq!(
+        metadata.reactions[aliases.reaction_aliases[reaction_key]]["doc"],
+        "Does nothing"
+    );
+    assert_eq!(
+        metadata.ports[aliases.port_aliases[BuilderPortKey::from(port_a)]]["unit"],
+        "km/h"
+    );
+    assert!(metadata.actions.is_empty());
+}
+
+#[test]
+fn test_metadata_conflict() {
+    fn build(unit: &str) -> EnvBuilder {
+        let mut env_builder = EnvBuilder::new();
+        let source = env_builder
+            .add_reactor("source", None, None, ())
+            .finish()
+            .unwrap();
+        let sink = env_builder
+            .add_reactor("sink", None, None, ())
+            .finish()
+            .unwrap();
+        let out = env_builder.add_output_port::<u32>("out", source).unwrap();
+        let inp = env_builder.add_input_port::<u32>("inp", sink).unwrap();
+        env_builder.bind_port(out, inp).unwrap();
+        env_builder.set_metadata(out, "unit", "m/s").unwrap();
+        env_builder.set_metadata(inp, "unit", unit).unwrap();
+        env_builder.set_metadata(inp, "doc", "Speed").unwrap();
+        env_builder
+    }
+
+    let (_env, graph, aliases) = build("m/s").into_runtime_parts().unwrap();
+    let (_, entries) = graph.metadata.ports.iter().next().unwrap();
+    assert_eq!(entries["unit"], "m/s");
+    assert_eq!(entries["doc"], "Speed");
+    assert_eq!(aliases.port_aliases.len(), 2);
+
+    assert!(matches!(
+        build("km/h").into_runtime_parts(),
+        Err(BuilderError::MetadataConflict { name, port, value, other, other_value })
+            if name == "unit" && port == "source::out" && value == "m/s"
+                && other == "sink::inp" && other_value == "km/h"
+    ));
+}
+
+#[test]
+fn test_json_graph() {
+    let mut env_builder = EnvBuilder::new();
+    let mut reactor_builder = env_builder.add_reactor("source", None, None, ());
+    let startup = reactor_builder.get_startup_action();
+    let reaction_key = reactor_builder
+        .add_reaction("emit", Box::new(reaction_closure!()))
+        .with_action(startup, 0, TriggerMode::TriggersOnly)
+        .unwrap()
+        .finish()
+        .unwrap();
+    let source = reactor_builder.finish().unwrap();
+    let sink = env_builder
+        .add_reactor("sink", None, None, ())
+        .finish()
+        .unwrap();
+    let out = env_builder.add_output_port::<u32>("out", source).unwrap();
+    let inp = env_builder.add_input_port::<u32>("inp", sink).unwrap();
+    env_builder.bind_port(out, inp).unwrap();
+
+    env_builder
+        .set_metadata(source, "model_id", "blk-\"1\"")
+        .unwrap();
+    env_builder
+        .set_metadata(reaction_key, "doc", "Emits\nvalues")
+        .unwrap();
+    env_builder.set_metadata(out, "unit", "m/s").unwrap();
+
+    let json = env_builder.create_json_graph().unwrap();
+    assert!(
+        json.contains(r#"{"fqn": "source", "type": "()", "metadata": {"model_id": "blk-\"1\""}}"#)
+    );
+    assert!(json.contains(r#"{"fqn": "source::emit", "metadata": {"doc": "Emits\nvalues"}}"#));
+    assert!(json.contains(
+        r#"{"fqn": "source::out", "type": "u32", "source": null, "metadata": {"unit": "m/s"}}"#
+    ));
+    assert!(json.contains(
+        r#"{"fqn": "sink::inp", "type": "u32", "source": "source::out", "metadata": {}}"#
+    ));
+    assert!(json.contains(r#""fqn": "__env::__startup", "type": "()""#));
+}
+
+#[test]
+fn test_diff() {
+    /// A source and a sink reactor, with an optional extra output on the source.
+    fn build(extra_port: bool, sink_uses: bool) -> EnvBuilder {
+        let mut env_builder = EnvBuilder::new();
+        let mut source = env_builder.add_reactor("source", None, None, ());
+        let startup = source.get_startup_action();
+        let out = source.add_output_port::<u32>("out").unwrap();
+        if extra_port {
+            source.add_output_port::<u32>("extra").unwrap();
+        }
+        source
+            .add_reaction("emit", reaction_closure!())
+            .with_action(startup, 0, TriggerMode::TriggersOnly)
+            .unwrap()
+            .with_port(out, 0, TriggerMode::EffectsOnly)
+            .unwrap()
+            .finish()
+            .unwrap();
+        source.finish().unwrap();
+
+        let mut sink = env_builder.add_reactor("sink", None, None, ());
+        let startup = sink.get_startup_action();
+        let inp = sink.add_input_port::<u32>("inp").unwrap();
+        let trigger_mode = if sink_uses {
+            TriggerMode::UsesOnly
+        } else {
+            TriggerMode::TriggersOnly
+        };
+        sink.add_reaction("receive", reaction_closure!())
+            .with_action(startup, 0, TriggerMode::TriggersOnly)
+            .unwrap()
+            .with_port(inp, 0, trigger_mode)
+            .unwrap()
+            .finish()
+            .unwrap();
+        sink.finish().unwrap();
+
+        env_builder
+            .connect_ports::<u32, _, _>(out, inp, None, false)
+            .unwrap();
+        env_builder
+    }
+
+    let before = build(false, false);
+    before.assert_same_structure(&build(false, false));
+
+    let diff = before.diff(&build(true, true)).unwrap();
+    assert_eq!(
+        diff.changes,
+        vec![
+            StructuralChange::Added {
+                kind: ElementKind::Port,
+                name: "source::extra".to_owned(),
+                summary: "Output<u32>".to_owned(),
+            },
+            StructuralChange::Changed {
+                kind: ElementKind::Reaction,
+                name: "sink::receive".to_owned(),
+                before: "(L1) triggers=[sink::__startup, sink::inp] uses=[] effects=[]".to_owned(),
+                after: "(L0) triggers=[sink::__startup] uses=[sink::inp] effects=[]".to_owned(),
+            },
+        ]
+    );
+    assert_eq!(
+        diff.to_string(),
+        "+ port source::extra: Output<u32>\n~ reaction sink::receive: (L1) triggers=[sink::__startup, sink::inp] uses=[] effects=[] => (L0) triggers=[sink::__startup] uses=[sink::inp] effects=[]"
+    );
+}
@@ -2,6 +2,8 @@
 
 use std::{fmt::Display, ops::Index};
 
+use boomerang_runtime::wildcard::wildcard_match;
+
 use crate::{ActionBuilder, BasePortBuilder, ReactionBuilder, ReactorBuilder};
 
 use super::BuilderError;
@@ -96,41 +98,76 @@ impl BuilderFqnSegment {
     }
 }
 
+/// Parse the index of a segment between the brackets, e.g. `3` or `0..4`.
+fn parse_index(index: &str, value: &str) -> Result<BuilderFqnSegmentIndex, BuilderError> {
+    let parse = |index: &str| {
+        index
+            .parse()
+            .map_err(|_| BuilderError::InvalidFqn(value.to_string()))
+    };
+    match index.split_once("..") {
+        Some((start, end)) => Ok(BuilderFqnSegmentIndex::Range(parse(start)?, parse(end)?)),
+        None => Ok(BuilderFqnSegmentIndex::Index(parse(index)?)),
+    }
+}
+
+/// Split a segment into its name and the text between the brackets of its index, if any.
+fn split_index(value: &str) -> Result<(&str, Option<&str>), BuilderError> {
+    match value.rfind('[') {
+        Some(index_start) => {
+            let (name, index) = value.split_at(index_start);
+            let index = index
+                .strip_prefix('[')
+                .and_then(|index| index.strip_suffix(']'))
+                .ok_or_else(|| BuilderError::InvalidFqn(value.to_string()))?;
+            Ok((name, Some(index)))
+        }
+        None => Ok((value, None)),
+    }
+}
+
+/// Split a fully-qualified name into its segments, separated by `::`, or by `.` outside of the indices if it contains
+/// no `::`, e.g. `bank[3].out`.
+fn split_segments(value: &str) -> Vec<&str> {
+    if value.contains("::") {
+        return value.split("::").collect();
+    }
+    let mut segments = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            '.' if depth == 0 => {
+                segments.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(&value[start..]);
+    segments
+}
+
 impl TryFrom<&str> for BuilderFqnSegment {
     type Error = BuilderError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         // parse an optional array index from the end of value
-        let (name, index) = match value.rfind('[') {
-            Some(index_start) => {
-                let (name, index) = value.split_at(index_start);
-                let index = index.trim_start_matches('[').trim_end_matches(']');
-                let index = if let Some(range_sep) = index.find("..") {
-                    let (start, end) = index.split_at(range_sep);
-                    let start = start
-                        .parse()
-                        .map_err(|_| BuilderError::InvalidFqn(value.to_string()))?;
-                    let end = end
-                        .trim_start_matches("..")
-                        .parse()
-                        .map_err(|_| BuilderError::InvalidFqn(value.to_string()))?;
-                    BuilderFqnSegmentIndex::Range(start, end)
-                } else {
-                    BuilderFqnSegmentIndex::Index(
-                        index
-                            .parse()
-                            .map_err(|_| BuilderError::InvalidFqn(value.to_string()))?,
-                    )
-                };
-                (name.to_string(), index)
-            }
-            None => (value.to_string(), BuilderFqnSegmentIndex::None),
-        };
+        let (name, index) = split_index(value)?;
+        let index = index
+            .map(|index| parse_index(index, value))
+            .transpose()?
+            .unwrap_or_default();
         // check for empty name
         if name.is_empty() {
             return Err(BuilderError::InvalidFqn(value.to_string()));
         }
-        Ok(Self { name, index })
+        Ok(Self {
+            name: name.to_string(),
+            index,
+        })
     }
 }
 
@@ -168,14 +205,35 @@ impl BuilderFqn {
     pub fn split_last(mut self) -> Option<(Self, BuilderFqnSegment)> {
         self.0.pop().map(|last| (self, last))
     }
+
+    /// The number of segments in the FQN.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether `prefix` matches the leading segments of the FQN.
+    pub fn starts_with(&self, prefix: &Self) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+
+    /// Replace the leading segments matching `prefix` with `replacement`, if the FQN starts with `prefix`.
+    pub fn replace_prefix(&self, prefix: &Self, replacement: &Self) -> Option<Self> {
+        self.0
+            .strip_prefix(prefix.0.as_slice())
+            .map(|rest| replacement.0.iter().chain(rest).cloned().collect::<Self>())
+    }
 }
 
 impl TryFrom<&str> for BuilderFqn {
     type Error = BuilderError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let inner = value
-            .split("::")
+        let inner = split_segments(value)
+            .into_iter()
             .map(BuilderFqnSegment::try_from)
             .collect::<Result<Vec<_>, _>>()?;
         if inner.is_empty() {
@@ -212,6 +270,227 @@ impl Index<usize> for BuilderFqn {
     }
 }
 
+/// Matches the index of a segment in a [`BuilderFqnPattern`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuilderFqnIndexPattern {
+    /// No index, e.g. `motor`, matches a segment that is not an array index.
+    #[default]
+    None,
+    /// `[*]` matches any array index.
+    Any,
+    /// `[3]` matches the array index 3.
+    Index(usize),
+    /// `[0..4]` matches the array indices 0 to 3, and the grouped range itself.
+    Range(usize, usize),
+}
+
+impl BuilderFqnIndexPattern {
+    pub fn matches(&self, index: BuilderFqnSegmentIndex) -> bool {
+        match (*self, index) {
+            (Self::None, BuilderFqnSegmentIndex::None) => true,
+            (Self::Any, index) => index.is_some(),
+            (Self::Index(a), BuilderFqnSegmentIndex::Index(b)) => a == b,
+            (Self::Range(start, end), BuilderFqnSegmentIndex::Index(index)) => {
+                (start..end).contains(&index)
+            }
+            (Self::Range(start, end), BuilderFqnSegmentIndex::Range(from, to)) => {
+                start == from && end == to
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<BuilderFqnSegmentIndex> for BuilderFqnIndexPattern {
+    fn from(index: BuilderFqnSegmentIndex) -> Self {
+        match index {
+            BuilderFqnSegmentIndex::None => Self::None,
+            BuilderFqnSegmentIndex::Index(index) => Self::Index(index),
+            BuilderFqnSegmentIndex::Range(from, to) => Self::Range(from, to),
+        }
+    }
+}
+
+/// A single segment of a [`BuilderFqnPattern`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuilderFqnPatternSegment {
+    /// `**` matches any number of segments, including none.
+    AnyDepth,
+    /// Matches a single segment by its name, or any name if `None` (`*`), and its index.
+    ///
+    /// A bare `*` matches any segment, with or without an index.
+    Segment {
+        name: Option<String>,
+        index: BuilderFqnIndexPattern,
+    },
+}
+
+impl BuilderFqnPatternSegment {
+    /// Whether this pattern matches the single `segment`. [`Self::AnyDepth`] matches any segment.
+    pub fn matches(&self, segment: &BuilderFqnSegment) -> bool {
+        match self {
+            Self::AnyDepth => true,
+            Self::Segment {
+                name: None,
+                index: BuilderFqnIndexPattern::None,
+            } => true,
+            Self::Segment { name, index } => {
+                name.as_deref().is_none_or(|name| name == segment.name)
+                    && index.matches(segment.index)
+            }
+        }
+    }
+}
+
+impl TryFrom<&str> for BuilderFqnPatternSegment {
+    type Error = BuilderError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if value == "**" {
+            return Ok(Self::AnyDepth);
+        }
+        let (name, index) = split_index(value)?;
+        let index = match index {
+            None => BuilderFqnIndexPattern::None,
+            Some("*") => BuilderFqnIndexPattern::Any,
+            Some(index) => parse_index(index, value)?.into(),
+        };
+        let name = match name {
+            "" => return Err(BuilderError::InvalidFqn(value.to_string())),
+            "*" => None,
+            name => Some(name.to_string()),
+        };
+        Ok(Self::Segment { name, index })
+    }
+}
+
+impl From<BuilderFqnSegment> for BuilderFqnPatternSegment {
+    fn from(segment: BuilderFqnSegment) -> Self {
+        Self::Segment {
+            name: Some(segment.name),
+            index: segment.index.into(),
+        }
+    }
+}
+
+impl Display for BuilderFqnPatternSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, index) = match self {
+            Self::AnyDepth => return write!(f, "**"),
+            Self::Segment { name, index } => (name.as_deref().unwrap_or("*"), index),
+        };
+        match index {
+            BuilderFqnIndexPattern::None => write!(f, "{name}"),
+            BuilderFqnIndexPattern::Any => write!(f, "{name}[*]"),
+            BuilderFqnIndexPattern::Index(index) => write!(f, "{name}[{index}]"),
+            BuilderFqnIndexPattern::Range(from, to) => write!(f, "{name}[{from}..{to}]"),
+        }
+    }
+}
+
+/// A pattern matching fully-qualified names, used by all FQN lookups of the [`crate::EnvBuilder`].
+///
+/// Segments are separated by `::`, or by `.` if the pattern contains no `::`. Besides the names and indices of a
+/// [`BuilderFqn`], a segment can be `*` to match any single segment, `name[*]` to match any element of a bank, `**`
+/// to match any number of segments, and an index can be a range, e.g. `bank[0..2].out`. A [`BuilderFqn`] is a pattern
+/// matching only itself.
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// let pattern = BuilderFqnPattern::try_from("robot.*.motor.cmd")?;
+/// let motor_cmds = env_builder.find_ports_matching(pattern)?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuilderFqnPattern(Vec<BuilderFqnPatternSegment>);
+
+impl BuilderFqnPattern {
+    /// Whether the pattern matches all segments of `fqn`.
+    pub fn matches(&self, fqn: &BuilderFqn) -> bool {
+        wildcard_match(
+            &self.0,
+            &fqn.0,
+            |segment| matches!(segment, BuilderFqnPatternSegment::AnyDepth),
+            BuilderFqnPatternSegment::matches,
+        )
+    }
+
+    /// The single FQN matched by the pattern, if it contains no wildcards or ranges.
+    pub fn to_fqn(&self) -> Option<BuilderFqn> {
+        self.0
+            .iter()
+            .map(|segment| match segment {
+                BuilderFqnPatternSegment::Segment {
+                    name: Some(name),
+                    index,
+                } => {
+                    let index = match index {
+                        BuilderFqnIndexPattern::None => BuilderFqnSegmentIndex::None,
+                        BuilderFqnIndexPattern::Index(index) => {
+                            BuilderFqnSegmentIndex::Index(*index)
+                        }
+                        _ => return None,
+                    };
+                    Some(BuilderFqnSegment {
+                        name: name.clone(),
+                        index,
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The number of segments in the pattern.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl TryFrom<&str> for BuilderFqnPattern {
+    type Error = BuilderError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let inner = split_segments(value)
+            .into_iter()
+            .map(BuilderFqnPatternSegment::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        if inner.is_empty() {
+            Err(BuilderError::InvalidFqn(value.to_string()))
+        } else {
+            Ok(Self(inner))
+        }
+    }
+}
+
+impl From<BuilderFqn> for BuilderFqnPattern {
+    fn from(fqn: BuilderFqn) -> Self {
+        Self(fqn.0.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<&BuilderFqn> for BuilderFqnPattern {
+    fn from(fqn: &BuilderFqn) -> Self {
+        fqn.clone().into()
+    }
+}
+
+impl Display for BuilderFqnPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "::")?;
+            }
+            write!(f, "{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
 #[test]
 fn test_fqn() {
     let fqn = BuilderFqn::try_from("boomerang::builder::fqn").unwrap();
@@ -252,3 +531,49 @@ fn test_fqn_segment() {
         BuilderFqn::try_from("boomerang::fqn[1]::test").unwrap()
     );
 }
+
+#[test]
+fn test_fqn_pattern() {
+    let fqn = |value| BuilderFqn::try_from(value).unwrap();
+    let pattern = |value| BuilderFqnPattern::try_from(value).unwrap();
+
+    // `.` separates segments outside of the indices
+    assert_eq!(fqn("bank[3].out"), fqn("bank[3]::out"));
+    assert_eq!(fqn("bank[0..2].out").to_string(), "bank[0..2]::out");
+
+    let motor_cmd = pattern("robot.*.motor.cmd");
+    assert_eq!(motor_cmd.to_string(), "robot::*::motor::cmd");
+    assert!(motor_cmd.matches(&fqn("robot::arm::motor::cmd")));
+    assert!(motor_cmd.matches(&fqn("robot::leg[2]::motor::cmd")));
+    assert!(!motor_cmd.matches(&fqn("robot::motor::cmd")));
+    assert!(!motor_cmd.matches(&fqn("robot::arm::motor::cmd::extra")));
+
+    assert!(pattern("bank[3].out").matches(&fqn("bank[3]::out")));
+    assert!(!pattern("bank[3].out").matches(&fqn("bank[2]::out")));
+    assert!(!pattern("bank.out").matches(&fqn("bank[3]::out")));
+    assert!(pattern("bank[*].out").matches(&fqn("bank[3]::out")));
+    assert!(!pattern("bank[*].out").matches(&fqn("bank::out")));
+    assert!(pattern("bank[0..4].out").matches(&fqn("bank[3]::out")));
+    assert!(!pattern("bank[0..3].out").matches(&fqn("bank[3]::out")));
+    assert!(pattern("bank[0..4].out").matches(&fqn("bank[0..4]::out")));
+
+    let any_depth = pattern("main::**::out");
+    assert!(any_depth.matches(&fqn("main::out")));
+    assert!(any_depth.matches(&fqn("main::a[1]::b::out")));
+    assert!(!any_depth.matches(&fqn("main::a::out::inner")));
+
+    assert_eq!(
+        pattern("main::bank[3]::out").to_fqn(),
+        Some(fqn("main::bank[3]::out"))
+    );
+    assert_eq!(pattern("main::*::out").to_fqn(), None);
+    assert_eq!(pattern("main::bank[*]::out").to_fqn(), None);
+    assert_eq!(
+        BuilderFqnPattern::from(fqn("main::out")),
+        pattern("main::out")
+    );
+
+    assert!(BuilderFqnPattern::try_from("main::").is_err());
+    assert!(BuilderFqnPattern::try_from("main::bank[x]").is_err());
+    assert!(BuilderFqnPattern::try_from("main::bank[3").is_err());
+}
@@ -2,7 +2,8 @@
 //! and understand the Reactor graph.
 
 use super::{
-    ActionType, BuilderError, BuilderPortKey, EnvBuilder, PortType, ReactorBuilder, TimerSpec,
+    ActionType, BuilderElementKey, BuilderError, BuilderPortKey, EnvBuilder, PortType,
+    ReactorBuilder, TimerSpec,
 };
 
 use itertools::Itertools;
@@ -21,6 +22,20 @@ fn port_node_name(env_builder: &EnvBuilder, port_key: BuilderPortKey) -> String
     }
 }
 
+/// Build a tooltip attribute listing the metadata of an element, if it has any
+fn metadata_tooltip(env_builder: &EnvBuilder, key: impl Into<BuilderElementKey>) -> String {
+    env_builder
+        .metadata(key)
+        .map(|metadata| {
+            let entries = metadata
+                .iter()
+                .map(|(name, value)| format!("{name}: {value}").replace('"', "\\\""))
+                .join("\\n");
+            format!(";tooltip=\"{entries}\"")
+        })
+        .unwrap_or_default()
+}
+
 fn build_ports(
     env_builder: &EnvBuilder,
     reactor: &ReactorBuilder,
@@ -61,8 +76,11 @@ fn build_reactions(env_builder: &EnvBuilder, reactor: &ReactorBuilder, output: &
     {
         let reaction_id = reaction_key.data().as_ffi() % env_builder.reaction_builders.len() as u64;
         output.push(format!(
-            "  r{} [label=\"{} ({})\";shape=cds;color=3];",
-            reaction_id, reaction.name, reaction.priority
+            "  r{} [label=\"{} ({})\";shape=cds;color=3{}];",
+            reaction_id,
+            reaction.name,
+            reaction.priority,
+            metadata_tooltip(env_builder, reaction_key)
         ));
         // output.push(format!(
         //    "  inputs{} -> r{} -> outputs{} [style=invis];",
@@ -113,8 +131,9 @@ fn build_actions(env_builder: &EnvBuilder, reactor: &ReactorBuilder, output: &mu
 
         if !action.triggers.is_empty() || !action.schedulers.is_empty() {
             output.push(format!(
-                "  a{action_id} [label=\"{}\"; xlabel=\"{xlabel}\"shape=diamond;color=4];",
+                "  a{action_id} [label=\"{}\"; xlabel=\"{xlabel}\"shape=diamond;color=4{}];",
                 action.name(),
+                metadata_tooltip(env_builder, action_key)
             ));
 
             for reaction_key in action.triggers.keys() {
@@ -180,7 +199,10 @@ pub fn create_full_graph(env_builder: &EnvBuilder) -> Result<String, BuilderErro
                 reactor.type_name(),
                 reactor.name()
             ));
-            output.push("  style=\"rounded\"; node [shape=record];".into());
+            output.push(format!(
+                "  style=\"rounded\"{}; node [shape=record];",
+                metadata_tooltip(env_builder, key)
+            ));
 
             build_ports(env_builder, reactor, reactor_id, &mut output);
             build_reactions(env_builder, reactor, &mut output);
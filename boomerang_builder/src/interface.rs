@@ -0,0 +1,150 @@
+//! Reactor interfaces, sets of ports shared by swappable reactor implementations.
+//!
+//! An interface is a struct of port keys deriving [`ReactorInterface`]. Reactors declare that they satisfy it with
+//! `#[reactor(implements = "MotorInterface")]`, which implements [`Implements`] with the ports of the same name. A
+//! missing port, or one with a different type or direction, is a compile error. Other ports of the reactor are ignored.
+//!
+//! A reactor generic over an implementation, or one selecting the implementation at build time, exposes the interface
+//! on its own ports and forwards them to the child with [`ReactorBuilderState::connect_interface`].
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! #[derive(ReactorInterface)]
+//! struct MotorInterface {
+//!     command: TypedPortKey<f32, Input>,
+//!     speed: TypedPortKey<f32, Output>,
+//! }
+//!
+//! #[derive(Reactor)]
+//! #[reactor(state = "()", implements = "MotorInterface")]
+//! struct SimMotor {
+//!     command: TypedPortKey<f32, Input>,
+//!     speed: TypedPortKey<f32, Output>,
+//! }
+//!
+//! let mut motor = env_builder.add_reactor("motor", None, None, ());
+//! let outer = MotorInterface::build(&mut motor)?;
+//! let inner = if simulated {
+//!     motor.add_child_reactor::<SimMotor>("driver", ())?.interface()
+//! } else {
+//!     motor.add_child_reactor::<CanMotor>("driver", config)?.interface()
+//! };
+//! motor.connect_interface(&outer, &inner)?;
+//! ```
+
+use crate::{runtime, BuilderError, Input, Output, ReactorBuilderState, TypedPortKey};
+
+/// A set of ports satisfied by reactor implementations, see the [module documentation](self).
+pub trait ReactorInterface: Sized {
+    /// Add the ports of the interface to the reactor of `builder`.
+    fn build(builder: &mut ReactorBuilderState) -> Result<Self, BuilderError>;
+
+    /// Forward the ports of `outer` on a reactor to the ports of `inner` on one of its children: inputs from `outer`
+    /// to `inner`, and outputs from `inner` to `outer`.
+    fn forward(
+        outer: &Self,
+        inner: &Self,
+        builder: &mut ReactorBuilderState,
+    ) -> Result<(), BuilderError>;
+}
+
+/// Constructs the interface from the ports of a reactor `R` with the same names and types, implemented by
+/// `#[derive(ReactorInterface)]`.
+pub trait InterfaceOf<R>: ReactorInterface {
+    fn from_reactor(reactor: &R) -> Self;
+}
+
+/// A reactor satisfying the interface `I`.
+pub trait Implements<I: ReactorInterface> {
+    /// The ports of the reactor making up the interface.
+    fn interface(&self) -> I;
+}
+
+impl<R, I: InterfaceOf<R>> Implements<I> for R {
+    fn interface(&self) -> I {
+        I::from_reactor(self)
+    }
+}
+
+/// The port field named `NAME` of a reactor, where `NAME` is the [`port_name_id`] of the field name, implemented by
+/// `#[reactor(implements = "...")]`.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is missing a port required by the interface",
+    label = "the interface has a port not declared on `{Self}`"
+)]
+pub trait NamedPort<const NAME: u64> {
+    type Port;
+
+    fn port(&self) -> Self::Port;
+}
+
+/// The identifier of a port name in [`NamedPort`], the 64-bit FNV-1a hash of the name.
+#[doc(hidden)]
+pub const fn port_name_id(name: &str) -> u64 {
+    let bytes = name.as_bytes();
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
+
+/// A port, or bank of ports, in a [`ReactorInterface`].
+pub trait InterfacePort {
+    /// Forward `outer` to `inner` according to the direction of the port.
+    fn forward(
+        outer: &Self,
+        inner: &Self,
+        builder: &mut ReactorBuilderState,
+    ) -> Result<(), BuilderError>;
+}
+
+impl<T: runtime::ReactorData + Clone> InterfacePort for TypedPortKey<T, Input> {
+    fn forward(
+        outer: &Self,
+        inner: &Self,
+        builder: &mut ReactorBuilderState,
+    ) -> Result<(), BuilderError> {
+        builder.connect_port(*outer, *inner, None, false)
+    }
+}
+
+impl<T: runtime::ReactorData + Clone> InterfacePort for TypedPortKey<T, Output> {
+    fn forward(
+        outer: &Self,
+        inner: &Self,
+        builder: &mut ReactorBuilderState,
+    ) -> Result<(), BuilderError> {
+        builder.connect_port(*inner, *outer, None, false)
+    }
+}
+
+impl<P: InterfacePort, const N: usize> InterfacePort for [P; N] {
+    fn forward(
+        outer: &Self,
+        inner: &Self,
+        builder: &mut ReactorBuilderState,
+    ) -> Result<(), BuilderError> {
+        outer
+            .iter()
+            .zip(inner)
+            .try_for_each(|(outer, inner)| P::forward(outer, inner, builder))
+    }
+}
+
+impl ReactorBuilderState<'_> {
+    /// Forward the interface ports `outer` of this reactor to the same interface `inner` on one of its children, see
+    /// [`ReactorInterface::forward`].
+    pub fn connect_interface<I: ReactorInterface>(
+        &mut self,
+        outer: &I,
+        inner: &I,
+    ) -> Result<(), BuilderError> {
+        I::forward(outer, inner, self)
+    }
+}
@@ -0,0 +1,128 @@
+//! JSON export of the reactor model for external tooling.
+//!
+//! [`EnvBuilder::create_json_graph`] lists all reactors, reactions, ports and actions by their fully-qualified names,
+//! with the type names and [metadata](crate::EnvBuilder::set_metadata) of each. Ports name the port they are bound
+//! to as their `source`, so the connections can be followed. Elements are listed in the order they were added.
+//!
+//! ## Example output
+//!
+//! ```json
+//! {
+//!   "reactors": [{"fqn": "filter", "type": "Filter", "metadata": {"model_id": "blk-42"}}],
+//!   "reactions": [{"fqn": "filter::reaction_in", "metadata": {}}],
+//!   "ports": [{"fqn": "filter::out", "type": "f64", "source": null, "metadata": {"doc": "Filtered samples"}}],
+//!   "actions": []
+//! }
+//! ```
+
+use std::fmt::Write;
+
+use crate::{BuilderElementKey, BuilderError, EnvBuilder};
+
+/// Quote and escape `value` as a JSON string.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl EnvBuilder {
+    /// The metadata of the element `key` as a JSON object.
+    fn json_metadata(&self, key: impl Into<BuilderElementKey>) -> String {
+        let entries = self
+            .metadata(key)
+            .into_iter()
+            .flatten()
+            .map(|(name, value)| format!("{}: {}", json_string(name), json_string(value)))
+            .collect::<Vec<_>>();
+        format!("{{{}}}", entries.join(", "))
+    }
+
+    /// Export the reactor model with the metadata of all elements as JSON, see the [module documentation](self).
+    pub fn create_json_graph(&self) -> Result<String, BuilderError> {
+        let reactors = self
+            .reactor_builders
+            .iter()
+            .map(|(key, reactor)| {
+                Ok(format!(
+                    r#"{{"fqn": {}, "type": {}, "metadata": {}}}"#,
+                    json_string(&self.reactor_fqn(key, false)?.to_string()),
+                    json_string(reactor.type_name()),
+                    self.json_metadata(key)
+                ))
+            })
+            .collect::<Result<Vec<_>, BuilderError>>()?;
+        let reactions = self
+            .reaction_builders
+            .keys()
+            .map(|key| {
+                Ok(format!(
+                    r#"{{"fqn": {}, "metadata": {}}}"#,
+                    json_string(&self.reaction_fqn(key, false)?.to_string()),
+                    self.json_metadata(key)
+                ))
+            })
+            .collect::<Result<Vec<_>, BuilderError>>()?;
+        let ports = self
+            .port_builders
+            .iter()
+            .map(|(key, port)| {
+                let source = match port.get_inward_binding() {
+                    Some(source) => json_string(&self.port_fqn(source, false)?.to_string()),
+                    None => "null".to_owned(),
+                };
+                Ok(format!(
+                    r#"{{"fqn": {}, "type": {}, "source": {source}, "metadata": {}}}"#,
+                    json_string(&self.port_fqn(key, false)?.to_string()),
+                    json_string(port.type_name()),
+                    self.json_metadata(key)
+                ))
+            })
+            .collect::<Result<Vec<_>, BuilderError>>()?;
+        let actions = self
+            .action_builders
+            .iter()
+            .map(|(key, action)| {
+                Ok(format!(
+                    r#"{{"fqn": {}, "type": {}, "metadata": {}}}"#,
+                    json_string(&self.action_fqn(key, false)?.to_string()),
+                    json_string(action.type_name()),
+                    self.json_metadata(key)
+                ))
+            })
+            .collect::<Result<Vec<_>, BuilderError>>()?;
+
+        let section = |name: &str, elements: Vec<String>| {
+            if elements.is_empty() {
+                format!("  {}: []", json_string(name))
+            } else {
+                format!(
+                    "  {}: [\n    {}\n  ]",
+                    json_string(name),
+                    elements.join(",\n    ")
+                )
+            }
+        };
+        Ok(format!(
+            "{{\n{},\n{},\n{},\n{}\n}}\n",
+            section("reactors", reactors),
+            section("reactions", reactions),
+            section("ports", ports),
+            section("actions", actions)
+        ))
+    }
+}
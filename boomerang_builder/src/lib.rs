@@ -5,12 +5,29 @@
 #![deny(clippy::all)]
 
 mod action;
+mod alias;
+mod balance;
+mod bus;
+mod conditional;
 mod connection;
+#[cfg(feature = "connection_stats")]
+pub mod connection_stats;
+mod contract;
+mod decorators;
 mod env;
 mod fqn;
+mod interface;
+mod json;
+mod metadata;
+mod partition;
 mod port;
+mod probe;
+mod rate_monitor;
 mod reaction;
 mod reactor;
+pub mod stable;
+mod strict;
+mod tap;
 #[cfg(test)]
 pub mod tests;
 
@@ -20,11 +37,22 @@ pub mod graphviz;
 pub mod plantuml;
 
 pub use action::*;
+pub use balance::{DelayAdjustment, UnbalancedForkJoin};
+pub use bus::MergePolicy;
+pub use connection::Coalesce;
+#[cfg(feature = "connection_stats")]
+pub use connection_stats::{ConnectionMonitor, ConnectionStats, LatencyStats};
+pub use contract::{ContractMonitor, ContractViolation, Monotonic, PortContract, ViolationKind};
 pub use env::*;
 pub use fqn::*;
+pub use interface::*;
+pub use metadata::BuilderElementKey;
+pub use partition::{Partition, PartitionPart, PartitionWeights};
 pub use port::*;
+pub use rate_monitor::{RateMonitor, RateStats, RATE_MONITOR_WINDOW};
 pub use reaction::*;
 pub use reactor::*;
+pub use strict::StrictViolation;
 
 use boomerang_runtime::{self as runtime};
 
@@ -66,6 +94,21 @@ pub enum BuilderError {
     #[error("A Reactor named '{0}' was not found.")]
     NamedReactorNotFound(String),
 
+    #[error("A Bus named '{0}' was not found.")]
+    NamedBusNotFound(String),
+
+    #[error("A Phase named '{0}' was not declared.")]
+    NamedPhaseNotFound(String),
+
+    #[error("Duplicate Phase Definition: {0}")]
+    DuplicatePhaseDefinition(String),
+
+    #[error("Duplicate Bus Definition: {0}")]
+    DuplicateBusDefinition(String),
+
+    #[error("Error building Bus '{bus}': {what}")]
+    BusError { bus: String, what: String },
+
     #[error("Inconsistent Builder State: {}", what)]
     InconsistentBuilderState {
         what: String,
@@ -85,12 +128,54 @@ pub enum BuilderError {
         what: String,
     },
 
+    #[error("The Port '{port}' carries values of type `{found}`, not `{expected}`")]
+    PortTypeMismatch {
+        port: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+
     #[error("Error building Reaction: {0}")]
     ReactionBuilderError(String),
 
     #[error("Invalid fully-qualified name: {0}")]
     InvalidFqn(String),
 
+    #[error("'{fqn}' is in '{subtree}', which was excluded from the build")]
+    ExcludedElement { fqn: String, subtree: String },
+
+    #[error("Invalid alias '{alias}' -> '{target}': {what}")]
+    InvalidAlias {
+        alias: String,
+        target: String,
+        what: String,
+    },
+
+    #[error("Unable to balance the delays from '{fork}' to '{join}': {what}")]
+    UnbalancedDelays {
+        fork: String,
+        join: String,
+        what: String,
+    },
+
+    #[error("Negative delay {delay} for {what}, events can't be scheduled into the past")]
+    NegativeDelay {
+        what: String,
+        delay: runtime::Duration,
+    },
+
+    #[error("Conflicting metadata '{name}' of the bound Ports '{port}' = '{value}' and '{other}' = '{other_value}'")]
+    MetadataConflict {
+        name: String,
+        port: String,
+        value: String,
+        other: String,
+        other_value: String,
+    },
+
+    #[error("Strict mode violations:\n{}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n"))]
+    StrictViolations(Vec<StrictViolation>),
+
     #[error("Internal Error: {0}")]
     InternalError(String),
 
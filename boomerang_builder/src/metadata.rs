@@ -0,0 +1,190 @@
+//! Free-form metadata attached to builder elements.
+//!
+//! Code generators and other tooling can attach arbitrary key-value [`runtime::Metadata`] to reactors, reactions,
+//! ports and actions, e.g. the ID of the model element an element was generated from or a documentation string. The
+//! metadata is shown in the GraphViz exports and included in the JSON export of [`EnvBuilder::create_json_graph`], and
+//! preserved by [`EnvBuilder::into_runtime_parts`] in [`runtime::ReactionGraph::metadata`], so it is also available
+//! to runtime diagnostics and serialized exports.
+//!
+//! Ports bound together share a single runtime port, which gets the metadata of all of them. Conflicting values of
+//! the same entry on bound ports fail the build with [`BuilderError::MetadataConflict`].
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! env_builder.set_metadata(filter_key, "model_id", "blk-42")?;
+//! env_builder.set_metadata(filter.out, "doc", "Filtered samples")?;
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use slotmap::SecondaryMap;
+
+use crate::{
+    runtime, BuilderActionKey, BuilderError, BuilderPortKey, BuilderReactionKey, BuilderReactorKey,
+    EnvBuilder, PhysicalActionKey, PortTag, TimerActionKey, TypedActionKey, TypedPortKey,
+};
+
+/// The key of any element that can carry metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BuilderElementKey {
+    Reactor(BuilderReactorKey),
+    Reaction(BuilderReactionKey),
+    Port(BuilderPortKey),
+    Action(BuilderActionKey),
+}
+
+impl From<BuilderReactorKey> for BuilderElementKey {
+    fn from(key: BuilderReactorKey) -> Self {
+        Self::Reactor(key)
+    }
+}
+
+impl From<BuilderReactionKey> for BuilderElementKey {
+    fn from(key: BuilderReactionKey) -> Self {
+        Self::Reaction(key)
+    }
+}
+
+impl From<BuilderPortKey> for BuilderElementKey {
+    fn from(key: BuilderPortKey) -> Self {
+        Self::Port(key)
+    }
+}
+
+impl<T: runtime::ReactorData, Q: PortTag> From<TypedPortKey<T, Q>> for BuilderElementKey {
+    fn from(key: TypedPortKey<T, Q>) -> Self {
+        Self::Port(key.into())
+    }
+}
+
+impl From<BuilderActionKey> for BuilderElementKey {
+    fn from(key: BuilderActionKey) -> Self {
+        Self::Action(key)
+    }
+}
+
+impl<T: runtime::ReactorData, Q: crate::ActionTag> From<TypedActionKey<T, Q>>
+    for BuilderElementKey
+{
+    fn from(key: TypedActionKey<T, Q>) -> Self {
+        Self::Action(key.into())
+    }
+}
+
+impl From<PhysicalActionKey> for BuilderElementKey {
+    fn from(key: PhysicalActionKey) -> Self {
+        Self::Action(key.into())
+    }
+}
+
+impl From<TimerActionKey> for BuilderElementKey {
+    fn from(key: TimerActionKey) -> Self {
+        Self::Action(key.into())
+    }
+}
+
+/// The metadata of all elements that have any, ordered by element.
+pub(crate) type BuilderMetadata = BTreeMap<BuilderElementKey, runtime::Metadata>;
+
+impl EnvBuilder {
+    fn contains_element(&self, key: BuilderElementKey) -> bool {
+        match key {
+            BuilderElementKey::Reactor(key) => self.reactor_builders.contains_key(key),
+            BuilderElementKey::Reaction(key) => self.reaction_builders.contains_key(key),
+            BuilderElementKey::Port(key) => self.port_builders.contains_key(key),
+            BuilderElementKey::Action(key) => self.action_builders.contains_key(key),
+        }
+    }
+
+    /// Attach the metadata entry `name` with `value` to the element `key`, replacing any previous value.
+    pub fn set_metadata(
+        &mut self,
+        key: impl Into<BuilderElementKey>,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Result<(), BuilderError> {
+        let key = key.into();
+        if !self.contains_element(key) {
+            return Err(BuilderError::InternalError(format!(
+                "Cannot attach metadata to unknown element {key:?}"
+            )));
+        }
+        self.metadata
+            .entry(key)
+            .or_default()
+            .insert(name.into(), value.into());
+        Ok(())
+    }
+
+    /// Get the metadata attached to the element `key`, if any.
+    pub fn metadata(&self, key: impl Into<BuilderElementKey>) -> Option<&runtime::Metadata> {
+        self.metadata.get(&key.into())
+    }
+
+    /// Check that ports bound together, which share a single runtime port, have no conflicting metadata entries.
+    pub(crate) fn check_metadata_conflicts(
+        &self,
+        port_aliases: &SecondaryMap<BuilderPortKey, runtime::PortKey>,
+    ) -> Result<(), BuilderError> {
+        // The first port, in key order, that set each entry of a runtime port
+        let mut merged: HashMap<(runtime::PortKey, &str), (BuilderPortKey, &str)> = HashMap::new();
+        for (&key, entries) in &self.metadata {
+            let BuilderElementKey::Port(port_key) = key else {
+                continue;
+            };
+            let Some(&runtime_key) = port_aliases.get(port_key) else {
+                continue;
+            };
+            for (name, value) in entries {
+                let &mut (first_key, first_value) = merged
+                    .entry((runtime_key, name))
+                    .or_insert((port_key, value));
+                if first_value != value {
+                    return Err(BuilderError::MetadataConflict {
+                        name: name.clone(),
+                        port: self.port_fqn(first_key, false)?.to_string(),
+                        value: first_value.to_owned(),
+                        other: self.port_fqn(port_key, false)?.to_string(),
+                        other_value: value.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert the metadata to runtime keys. Startup and shutdown actions have no runtime counterpart, so their
+    /// metadata is dropped. Ports bound together share a single runtime port, so their metadata is merged, see
+    /// [`Self::check_metadata_conflicts`].
+    pub(crate) fn build_runtime_metadata(
+        metadata: BuilderMetadata,
+        aliases: &crate::BuilderAliases,
+    ) -> runtime::EnvMetadata {
+        let mut env_metadata = runtime::EnvMetadata::default();
+        for (key, entries) in metadata {
+            let target = match key {
+                BuilderElementKey::Reactor(key) => aliases
+                    .reactor_aliases
+                    .get(key)
+                    .map(|&key| env_metadata.reactors.entry(key).or_default()),
+                BuilderElementKey::Reaction(key) => aliases
+                    .reaction_aliases
+                    .get(key)
+                    .map(|&key| env_metadata.reactions.entry(key).or_default()),
+                BuilderElementKey::Port(key) => aliases
+                    .port_aliases
+                    .get(key)
+                    .map(|&key| env_metadata.ports.entry(key).or_default()),
+                BuilderElementKey::Action(key) => aliases
+                    .action_aliases
+                    .get(key)
+                    .map(|&key| env_metadata.actions.entry(key).or_default()),
+            };
+            if let Some(target) = target {
+                target.extend(entries);
+            }
+        }
+        env_metadata
+    }
+}
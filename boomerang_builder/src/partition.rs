@@ -0,0 +1,400 @@
+//! Partitioning suggestions, splitting a program into groups of reactors with balanced costs and little traffic
+//! between them.
+//!
+//! [`EnvBuilder::suggest_partition`] assigns the children of the top-level reactor, each with all of its descendants,
+//! to a number of parts, e.g. to decide which reactors to run on separate schedulers or machines. The cost of a
+//! reactor is the sum of the costs of its reactions, and the traffic between two reactors the sum of the volumes of
+//! the ports connecting them, both declared in the [`PartitionWeights`], e.g. from the
+//! [`crate::runtime::BudgetStats`] of a previous run. Undeclared reactions and ports weigh 1.
+//!
+//! The reactors are first assigned greedily by decreasing cost to the cheapest part, then refined in passes in the
+//! manner of Kernighan-Lin: the move or swap of reactors between parts that reduces the traffic between parts, the cut
+//! traffic, the most without exceeding the allowed imbalance is applied, until no such move is left. The result is a
+//! local optimum, and only as good as the declared weights.
+//!
+//! The reactors of delayed and physical connections are not assigned, their traffic is counted between the reactors
+//! they connect.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! let weights = PartitionWeights::new()
+//!     .with_reaction_cost("main::planner::plan", 20.0)
+//!     .with_port_volume("main::camera::frame", 100.0);
+//! let partition = env_builder.suggest_partition(2, &weights)?;
+//! println!("{partition}");
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Display;
+
+use crate::{BuilderError, BuilderPortKey, BuilderReactorKey, EnvBuilder, ParentReactorBuilder};
+
+/// The declared costs of reactions and communication volumes of ports, see the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct PartitionWeights {
+    reaction_costs: BTreeMap<String, f64>,
+    port_volumes: BTreeMap<String, f64>,
+    imbalance: f64,
+}
+
+impl Default for PartitionWeights {
+    fn default() -> Self {
+        Self {
+            reaction_costs: BTreeMap::new(),
+            port_volumes: BTreeMap::new(),
+            imbalance: 0.1,
+        }
+    }
+}
+
+impl PartitionWeights {
+    /// Weights of 1 for all reactions and ports, and an allowed imbalance of 10%.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cost of the reaction with the fully-qualified name `reaction_fqn`, e.g. its mean execution time.
+    pub fn with_reaction_cost(mut self, reaction_fqn: impl Into<String>, cost: f64) -> Self {
+        self.reaction_costs.insert(reaction_fqn.into(), cost);
+        self
+    }
+
+    /// The volume of the values sent on the port with the fully-qualified name `port_fqn` to each of its targets.
+    pub fn with_port_volume(mut self, port_fqn: impl Into<String>, volume: f64) -> Self {
+        self.port_volumes.insert(port_fqn.into(), volume);
+        self
+    }
+
+    /// Allow the cost of a part to exceed the mean cost of all parts by the fraction `imbalance`.
+    ///
+    /// Parts may be more imbalanced if a single reactor costs more.
+    pub fn with_imbalance(mut self, imbalance: f64) -> Self {
+        self.imbalance = imbalance;
+        self
+    }
+}
+
+/// A group of reactors suggested by [`EnvBuilder::suggest_partition`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionPart {
+    /// The fully-qualified names of the reactors in this part
+    pub reactors: Vec<String>,
+    /// The total cost of the reactors
+    pub cost: f64,
+}
+
+/// A partition suggested by [`EnvBuilder::suggest_partition`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partition {
+    pub parts: Vec<PartitionPart>,
+    /// The total volume of the connections between reactors in different parts
+    pub cut_traffic: f64,
+    /// The total volume of all connections between the partitioned reactors
+    pub total_traffic: f64,
+}
+
+impl Display for Partition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, part) in self.parts.iter().enumerate() {
+            writeln!(
+                f,
+                "Part {index} (cost {}): {}",
+                part.cost,
+                part.reactors.join(", ")
+            )?;
+        }
+        write!(
+            f,
+            "Cut traffic {} of {}",
+            self.cut_traffic, self.total_traffic
+        )
+    }
+}
+
+/// The reactors to partition, with their costs and the traffic between them.
+struct PartitionGraph {
+    costs: Vec<f64>,
+    traffic: Vec<Vec<f64>>,
+}
+
+impl PartitionGraph {
+    /// The traffic between reactors in different parts of `assignment`.
+    fn cut(&self, assignment: &[usize]) -> f64 {
+        let mut cut = 0.0;
+        for (a, row) in self.traffic.iter().enumerate() {
+            for (b, &volume) in row.iter().enumerate().skip(a + 1) {
+                if assignment[a] != assignment[b] {
+                    cut += volume;
+                }
+            }
+        }
+        cut
+    }
+
+    /// The traffic from reactor `unit` into `part`.
+    fn traffic_into(&self, assignment: &[usize], unit: usize, part: usize) -> f64 {
+        self.traffic[unit]
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != unit && assignment[other] == part)
+            .map(|(_, &volume)| volume)
+            .sum()
+    }
+
+    /// Assign each reactor to one of `parts` parts.
+    fn partition(&self, parts: usize, imbalance: f64) -> Vec<usize> {
+        let mut assignment = vec![0; self.costs.len()];
+        let mut loads = vec![0.0_f64; parts];
+
+        // Greedily assign by decreasing cost to the cheapest part
+        let mut order = (0..self.costs.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| self.costs[b].total_cmp(&self.costs[a]));
+        for unit in order {
+            let part = (0..parts)
+                .min_by(|&a, &b| loads[a].total_cmp(&loads[b]))
+                .unwrap_or_default();
+            assignment[unit] = part;
+            loads[part] += self.costs[unit];
+        }
+
+        let mean = self.costs.iter().sum::<f64>() / parts as f64;
+        let limit = loads
+            .iter()
+            .copied()
+            .fold(mean * (1.0 + imbalance), f64::max);
+
+        // Apply the best move or swap until none reduces the cut
+        loop {
+            let mut best: Option<(f64, usize, usize, Option<usize>)> = None;
+            for unit in 0..self.costs.len() {
+                let from = assignment[unit];
+                let internal = self.traffic_into(&assignment, unit, from);
+                for to in (0..parts).filter(|&to| to != from) {
+                    let external = self.traffic_into(&assignment, unit, to);
+                    if loads[to] + self.costs[unit] <= limit {
+                        let gain = external - internal;
+                        if gain > best.map_or(0.0, |(gain, ..)| gain) {
+                            best = Some((gain, unit, to, None));
+                        }
+                    }
+                    for other in (unit + 1..self.costs.len()).filter(|&o| assignment[o] == to) {
+                        let delta = self.costs[other] - self.costs[unit];
+                        if loads[from] + delta > limit || loads[to] - delta > limit {
+                            continue;
+                        }
+                        let other_internal = self.traffic_into(&assignment, other, to);
+                        let other_external = self.traffic_into(&assignment, other, from);
+                        let gain = external - internal + other_external
+                            - other_internal
+                            - 2.0 * self.traffic[unit][other];
+                        if gain > best.map_or(0.0, |(gain, ..)| gain) {
+                            best = Some((gain, unit, to, Some(other)));
+                        }
+                    }
+                }
+            }
+
+            let Some((_, unit, to, other)) = best else {
+                return assignment;
+            };
+            let from = assignment[unit];
+            loads[from] -= self.costs[unit];
+            loads[to] += self.costs[unit];
+            assignment[unit] = to;
+            if let Some(other) = other {
+                loads[to] -= self.costs[other];
+                loads[from] += self.costs[other];
+                assignment[other] = from;
+            }
+        }
+    }
+}
+
+impl EnvBuilder {
+    /// Suggest how to split the children of the top-level reactor into `parts` parts, see the
+    /// [module documentation](self).
+    pub fn suggest_partition(
+        &self,
+        parts: usize,
+        weights: &PartitionWeights,
+    ) -> Result<Partition, BuilderError> {
+        if parts == 0 {
+            return Err(BuilderError::InternalError(
+                "A partition needs at least one part".to_owned(),
+            ));
+        }
+
+        let connection_reactors = self
+            .shared_connections
+            .values()
+            .map(|connection| self.port_builders[connection.input].get_reactor_key())
+            .collect::<HashSet<_>>();
+
+        // The children of the top-level reactors are the units to assign
+        let units = self
+            .reactor_builders
+            .iter()
+            .filter(|(reactor_key, reactor)| {
+                !connection_reactors.contains(reactor_key)
+                    && reactor.parent_reactor_key().is_some_and(|parent| {
+                        self.reactor_builders[parent].parent_reactor_key().is_none()
+                    })
+            })
+            .map(|(reactor_key, _)| reactor_key)
+            .collect::<Vec<_>>();
+        let unit_of = |mut reactor_key: BuilderReactorKey| loop {
+            if let Some(unit) = units.iter().position(|&unit| unit == reactor_key) {
+                return Some(unit);
+            }
+            reactor_key = self.reactor_builders[reactor_key].parent_reactor_key()?;
+        };
+
+        let mut graph = PartitionGraph {
+            costs: vec![0.0; units.len()],
+            traffic: vec![vec![0.0; units.len()]; units.len()],
+        };
+        for (reaction_key, reaction) in self.reaction_builders.iter() {
+            if let Some(unit) = unit_of(reaction.reactor_key) {
+                let fqn = self.reaction_fqn(reaction_key, false)?.to_string();
+                graph.costs[unit] += weights.reaction_costs.get(&fqn).copied().unwrap_or(1.0);
+            }
+        }
+
+        let mut total_traffic = 0.0;
+        let port_unit =
+            |port_key: BuilderPortKey| unit_of(self.port_builders[port_key].get_reactor_key());
+        for (target_key, target) in self.port_builders.iter() {
+            let Some(mut source_key) = target.get_inward_binding() else {
+                continue;
+            };
+            if connection_reactors.contains(&target.get_reactor_key()) {
+                continue;
+            }
+            // Pass through the reactor of a delayed or physical connection to its source
+            let source_reactor = self.port_builders[source_key].get_reactor_key();
+            if connection_reactors.contains(&source_reactor) {
+                let input = self
+                    .shared_connections
+                    .values()
+                    .find(|connection| connection.output == source_key)
+                    .map(|connection| connection.input);
+                match input.and_then(|input| self.port_builders[input].get_inward_binding()) {
+                    Some(key) => source_key = key,
+                    None => continue,
+                }
+            }
+
+            if let (Some(a), Some(b)) = (port_unit(source_key), port_unit(target_key)) {
+                if a != b {
+                    let fqn = self.port_fqn(source_key, false)?.to_string();
+                    let volume = weights.port_volumes.get(&fqn).copied().unwrap_or(1.0);
+                    graph.traffic[a][b] += volume;
+                    graph.traffic[b][a] += volume;
+                    total_traffic += volume;
+                }
+            }
+        }
+
+        let assignment = graph.partition(parts, weights.imbalance);
+        let cut_traffic = graph.cut(&assignment);
+        let mut partition = Partition {
+            parts: vec![
+                PartitionPart {
+                    reactors: Vec::new(),
+                    cost: 0.0
+                };
+                parts
+            ],
+            cut_traffic,
+            total_traffic,
+        };
+        for (unit, &part) in assignment.iter().enumerate() {
+            let part = &mut partition.parts[part];
+            part.reactors
+                .push(self.reactor_fqn(units[unit], false)?.to_string());
+            part.cost += graph.costs[unit];
+        }
+        Ok(partition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reaction_closure, runtime, TriggerMode};
+
+    /// Two clusters of three reactors each, heavily connected within and lightly between them.
+    #[test]
+    fn test_partition_clusters() {
+        let mut traffic = vec![vec![0.0; 6]; 6];
+        let mut connect = |a: usize, b: usize, volume: f64| {
+            traffic[a][b] += volume;
+            traffic[b][a] += volume;
+        };
+        for (a, b) in [(0, 1), (1, 2), (0, 2), (3, 4), (4, 5), (3, 5)] {
+            connect(a, b, 10.0);
+        }
+        connect(2, 3, 1.0);
+        let graph = PartitionGraph {
+            costs: vec![1.0; 6],
+            traffic,
+        };
+
+        // The greedy assignment interleaves the clusters, only swaps keep the parts balanced
+        let assignment = graph.partition(2, 0.0);
+        assert_eq!(graph.cut(&assignment), 1.0);
+        assert_eq!(assignment[0], assignment[1]);
+        assert_eq!(assignment[0], assignment[2]);
+        assert_eq!(assignment[3], assignment[4]);
+        assert_eq!(assignment[3], assignment[5]);
+        assert_ne!(assignment[0], assignment[3]);
+    }
+
+    #[test]
+    fn test_suggest_partition() {
+        let mut env_builder = EnvBuilder::new();
+        let main_key = env_builder
+            .add_reactor("main", None, None, ())
+            .finish()
+            .unwrap();
+        let mut ports = Vec::new();
+        for name in ["a", "b", "c"] {
+            let mut child = env_builder.add_reactor(name, Some(main_key), None, ());
+            let input = child.add_input_port::<u32>("input").unwrap();
+            let output = child.add_output_port::<u32>("output").unwrap();
+            let startup = child.get_startup_action();
+            child
+                .add_reaction("run", reaction_closure!())
+                .with_action(startup, 0, TriggerMode::TriggersOnly)
+                .unwrap()
+                .finish()
+                .unwrap();
+            child.finish().unwrap();
+            ports.push((input, output));
+        }
+        env_builder
+            .connect_ports::<u32, _, _>(ports[0].1, ports[1].0, None, false)
+            .unwrap();
+        env_builder
+            .connect_ports::<u32, _, _>(
+                ports[1].1,
+                ports[2].0,
+                Some(runtime::Duration::milliseconds(1)),
+                false,
+            )
+            .unwrap();
+
+        let weights = PartitionWeights::new().with_port_volume("main::a::output", 5.0);
+        let partition = env_builder.suggest_partition(2, &weights).unwrap();
+        assert_eq!(partition.total_traffic, 6.0);
+        assert_eq!(partition.cut_traffic, 1.0);
+        let mut parts = partition
+            .parts
+            .iter()
+            .map(|part| part.reactors.clone())
+            .collect::<Vec<_>>();
+        parts.sort();
+        assert_eq!(parts, [vec!["main::a", "main::b"], vec!["main::c"]]);
+    }
+}
@@ -28,12 +28,18 @@ impl PortTag for Output {
 
 pub struct TypedPortKey<T: runtime::ReactorData, Q: PortTag>(BuilderPortKey, PhantomData<(T, Q)>);
 
+/// A port accumulating a batch of values per tag, see [`runtime::Accum`].
+pub type AccumPort<T, Q> = TypedPortKey<runtime::Accum<T>, Q>;
+
 impl<T: runtime::ReactorData, Q: PortTag> Debug for TypedPortKey<T, Q> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("TypedPortKey")
-            .field(&self.0)
-            .field(&self.1)
-            .finish()
+        write!(
+            f,
+            "TypedPortKey<{}, {:?}>({:?})",
+            std::any::type_name::<T>(),
+            Q::TYPE,
+            self.0
+        )
     }
 }
 
@@ -80,8 +86,11 @@ pub trait BasePortBuilder {
     fn set_inward_binding(&mut self, inward_binding: Option<BuilderPortKey>);
     fn get_outward_bindings(&self) -> secondary::Keys<BuilderPortKey, ()>;
     fn add_outward_binding(&mut self, outward_binding: BuilderPortKey);
+    fn remove_outward_binding(&mut self, outward_binding: BuilderPortKey);
     fn port_type(&self) -> &PortType;
     fn bank_info(&self) -> Option<&runtime::BankInfo>;
+    /// The name of the type of values carried by this port
+    fn type_name(&self) -> &'static str;
     fn deps(&self) -> Vec<BuilderReactionKey>;
     fn antideps(&self) -> secondary::Keys<BuilderReactionKey, ()>;
     /// Get the out-going Reactions that this Port triggers
@@ -158,6 +167,10 @@ impl<T: runtime::ReactorData, Q: PortTag> BasePortBuilder for PortBuilder<T, Q>
         self.bank_info.as_ref()
     }
 
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
     fn deps(&self) -> Vec<BuilderReactionKey> {
         self.deps.keys().collect()
     }
@@ -183,6 +196,10 @@ impl<T: runtime::ReactorData, Q: PortTag> BasePortBuilder for PortBuilder<T, Q>
             .insert(outward_binding.data().into(), ());
     }
 
+    fn remove_outward_binding(&mut self, outward_binding: BuilderPortKey) {
+        self.outward_bindings.remove(outward_binding);
+    }
+
     fn register_dependency(&mut self, reaction_key: BuilderReactionKey, is_trigger: bool) {
         assert!(
             self.outward_bindings.is_empty(),
@@ -0,0 +1,126 @@
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{
+    runtime, BuilderActionKey, BuilderError, BuilderFqnPattern, BuilderFqnSegment, BuilderPortKey,
+    EnvBuilder,
+};
+
+/// The builder-side element watched by a probe.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum BuilderProbeKey {
+    Port(BuilderPortKey),
+    Action(BuilderActionKey),
+}
+
+type PredicateFn<T> = dyn Fn(&T) -> bool + Send + Sync;
+
+/// A probe waiting for the runtime keys of its element to be resolved.
+pub(crate) struct ProbeBuilder {
+    pub(crate) key: BuilderProbeKey,
+    pub(crate) build_fn: Box<dyn FnOnce(runtime::ProbeKey) -> runtime::Probe>,
+}
+
+impl EnvBuilder {
+    /// Add a probe on the port or action with the fully-qualified name `fqn`, carrying values of type `T`.
+    ///
+    /// Whenever the element has a value matching `predicate`, the scheduler captures a [`runtime::ProbeSnapshot`],
+    /// and pauses if running in interactive mode. If `fqn` is a [`BuilderFqnPattern`] with wildcards, a probe named by
+    /// the fully-qualified name of each matching port and action is added. Fails if any of them doesn't carry values of
+    /// type `T`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,ignore
+    /// env_builder.add_probe("main::filter::out", |value: &f64| value.is_nan())?;
+    /// env_builder.add_probe("main::filter[*]::out", |value: &f64| value.is_nan())?;
+    /// ```
+    pub fn add_probe<T, F>(&mut self, fqn: &str, predicate: F) -> Result<(), BuilderError>
+    where
+        T: runtime::ReactorData + Debug,
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        self.add_probe_with(fqn, predicate, |name, key, predicate| {
+            runtime::Probe::new(name, key, predicate)
+        })
+    }
+
+    /// Add a probe like [`EnvBuilder::add_probe`], which also captures a clone of each matching value, see
+    /// [`runtime::ProbeSnapshot::value_as`].
+    pub fn add_capturing_probe<T, F>(&mut self, fqn: &str, predicate: F) -> Result<(), BuilderError>
+    where
+        T: runtime::ReactorData + Debug + Clone,
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        self.add_probe_with(fqn, predicate, |name, key, predicate| {
+            runtime::Probe::new_capturing(name, key, predicate)
+        })
+    }
+
+    fn add_probe_with<T, F>(
+        &mut self,
+        fqn: &str,
+        predicate: F,
+        new_probe: fn(&str, runtime::ProbeKey, Box<PredicateFn<T>>) -> runtime::Probe,
+    ) -> Result<(), BuilderError>
+    where
+        T: runtime::ReactorData + Debug,
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        let pattern = BuilderFqnPattern::try_from(fqn)?;
+        let exact = pattern.to_fqn().is_some();
+
+        let mut keys = Vec::new();
+        for port_key in self.find_ports_matching(pattern.clone())? {
+            let port = &self.port_builders[port_key];
+            if port.type_name() != std::any::type_name::<T>() {
+                return Err(BuilderError::InconsistentBuilderState {
+                    what: format!(
+                        "Probe '{fqn}' expects values of type {}, but the port carries {}",
+                        std::any::type_name::<T>(),
+                        port.type_name()
+                    ),
+                });
+            }
+            keys.push((
+                BuilderProbeKey::Port(port_key),
+                self.port_fqn(port_key, false)?,
+            ));
+        }
+        for action_key in self.find_actions_matching(pattern)? {
+            let action = &self.action_builders[action_key];
+            if action.type_name() != std::any::type_name::<T>() {
+                return Err(BuilderError::InconsistentBuilderState {
+                    what: format!(
+                        "Probe '{fqn}' expects values of type {}, but the action carries {}",
+                        std::any::type_name::<T>(),
+                        action.type_name()
+                    ),
+                });
+            }
+            let action_fqn = self
+                .reactor_fqn(action.reactor_key(), false)?
+                .append(BuilderFqnSegment::from_action(action, false))?;
+            keys.push((BuilderProbeKey::Action(action_key), action_fqn));
+        }
+        if keys.is_empty() {
+            return Err(BuilderError::NamedPortNotFound(fqn.to_owned()));
+        }
+
+        let predicate = Arc::new(predicate);
+        for (key, element_fqn) in keys {
+            let name = if exact {
+                fqn.to_owned()
+            } else {
+                element_fqn.to_string()
+            };
+            let predicate = predicate.clone();
+            self.probes.push(ProbeBuilder {
+                key,
+                build_fn: Box::new(move |key| {
+                    new_probe(&name, key, Box::new(move |value: &T| predicate(value)))
+                }),
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+//! Rate monitors recording the invocation times of timers and other periodic actions.
+//!
+//! [`EnvBuilder::monitor_rate`] adds a reaction next to the monitored action, recording the physical time at which each
+//! of its events is handled. The intervals between invocations are summarized as [`RateStats`], which are logged at
+//! shutdown and can be queried at any time from the returned [`RateMonitor`], e.g. from another thread while the
+//! scheduler is running. Comparing them with the expected period separates latency of the scheduler from the
+//! expectations of the application.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! let period = Some(Duration::milliseconds(10));
+//! let tick = reactor.add_timer("tick", TimerSpec { period, offset: None })?;
+//! let monitor = reactor.monitor_rate(tick, None)?;
+//! // ...
+//! if let Some(stats) = monitor.stats() {
+//!     println!("{stats}");
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+
+use crate::{runtime, ActionType, BuilderActionKey, BuilderError, EnvBuilder, TriggerMode};
+
+/// The number of most recent intervals kept to compute the percentiles of a [`RateMonitor`].
+pub const RATE_MONITOR_WINDOW: usize = 4096;
+
+/// Statistics of the intervals between the invocations of a monitored action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateStats {
+    /// The expected period of the action, if known
+    pub expected: Option<runtime::Duration>,
+    /// The number of intervals recorded, one less than the number of invocations
+    pub intervals: usize,
+    /// The shortest interval
+    pub min: runtime::Duration,
+    /// The longest interval
+    pub max: runtime::Duration,
+    /// The mean interval
+    pub mean: runtime::Duration,
+    /// The 99th percentile of the most recent [`RATE_MONITOR_WINDOW`] intervals
+    pub p99: runtime::Duration,
+}
+
+impl RateStats {
+    /// The largest deviation of any interval from the expected period, if known.
+    pub fn max_jitter(&self) -> Option<runtime::Duration> {
+        self.expected
+            .map(|expected| (self.min - expected).abs().max((self.max - expected).abs()))
+    }
+}
+
+impl Display for RateStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} intervals: min {}, max {}, mean {}, p99 {}",
+            self.intervals, self.min, self.max, self.mean, self.p99
+        )?;
+        if let (Some(expected), Some(jitter)) = (self.expected, self.max_jitter()) {
+            write!(f, ", expected {expected}, max jitter {jitter}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The recorded invocations of a monitored action.
+#[derive(Debug, Default)]
+struct RateSamples {
+    expected: Option<runtime::Duration>,
+    /// The physical time of the last invocation
+    last: Option<std::time::Instant>,
+    count: usize,
+    min: runtime::Duration,
+    max: runtime::Duration,
+    total: runtime::Duration,
+    /// The most recent intervals
+    window: VecDeque<runtime::Duration>,
+}
+
+impl RateSamples {
+    fn record(&mut self, now: std::time::Instant) {
+        if let Some(last) = self.last.replace(now) {
+            let interval: runtime::Duration =
+                (now - last).try_into().unwrap_or(runtime::Duration::MAX);
+            if self.count == 0 {
+                self.min = interval;
+                self.max = interval;
+            } else {
+                self.min = self.min.min(interval);
+                self.max = self.max.max(interval);
+            }
+            self.count += 1;
+            self.total = self.total.saturating_add(interval);
+            if self.window.len() == RATE_MONITOR_WINDOW {
+                self.window.pop_front();
+            }
+            self.window.push_back(interval);
+        }
+    }
+
+    fn stats(&self) -> Option<RateStats> {
+        if self.count == 0 {
+            return None;
+        }
+        let mut window = self.window.iter().copied().collect::<Vec<_>>();
+        window.sort_unstable();
+        let p99 = window[(window.len() * 99).div_ceil(100) - 1];
+        Some(RateStats {
+            expected: self.expected,
+            intervals: self.count,
+            min: self.min,
+            max: self.max,
+            mean: self.total / self.count as u32,
+            p99,
+        })
+    }
+}
+
+/// A handle to the statistics of a monitored action, see [`EnvBuilder::monitor_rate`].
+#[derive(Debug, Clone)]
+pub struct RateMonitor {
+    name: String,
+    samples: Arc<Mutex<RateSamples>>,
+}
+
+impl RateMonitor {
+    /// The name of the monitored action.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The statistics recorded so far, or `None` until the action was invoked at least twice.
+    pub fn stats(&self) -> Option<RateStats> {
+        self.samples.lock().unwrap().stats()
+    }
+}
+
+/// Records the physical time of each invocation of the monitored action.
+struct RecordFn(Arc<Mutex<RateSamples>>);
+
+impl From<RecordFn> for runtime::BoxedReactionFn {
+    fn from(value: RecordFn) -> Self {
+        Box::new(value)
+    }
+}
+
+impl<'store> runtime::ReactionFn<'store> for RecordFn {
+    fn trigger(
+        &mut self,
+        _ctx: &'store mut runtime::Context,
+        _reactor: &'store mut dyn runtime::BaseReactor,
+        _ports: runtime::Refs<'store, dyn runtime::BasePort>,
+        _ports_mut: runtime::RefsMut<'store, dyn runtime::BasePort>,
+        _actions: runtime::RefsMut<'store, dyn runtime::BaseAction>,
+    ) {
+        self.0.lock().unwrap().record(std::time::Instant::now());
+    }
+}
+
+/// Logs the statistics of the monitored action at shutdown.
+struct ReportFn(RateMonitor);
+
+impl From<ReportFn> for runtime::BoxedReactionFn {
+    fn from(value: ReportFn) -> Self {
+        Box::new(value)
+    }
+}
+
+impl<'store> runtime::ReactionFn<'store> for ReportFn {
+    fn trigger(
+        &mut self,
+        _ctx: &'store mut runtime::Context,
+        _reactor: &'store mut dyn runtime::BaseReactor,
+        _ports: runtime::Refs<'store, dyn runtime::BasePort>,
+        _ports_mut: runtime::RefsMut<'store, dyn runtime::BasePort>,
+        _actions: runtime::RefsMut<'store, dyn runtime::BaseAction>,
+    ) {
+        let action = self.0.name();
+        match self.0.stats() {
+            Some(stats) => tracing::info!(action, "Rate: {stats}"),
+            None => tracing::info!(action, "Rate: not enough invocations"),
+        }
+    }
+}
+
+impl EnvBuilder {
+    /// Monitor the rate of the timer or action `action_key`, recording the physical time of each of its invocations.
+    ///
+    /// The expected period defaults to the period of a timer, and can be given as `expected_period` for other periodic
+    /// actions. Two reactions are added to the reactor of the action: `_<name>_rate_monitor`, triggered by the action,
+    /// and `_<name>_rate_report`, logging the [`RateStats`] at shutdown. The returned [`RateMonitor`] gives access to
+    /// the statistics at any time.
+    pub fn monitor_rate(
+        &mut self,
+        action_key: impl Into<BuilderActionKey>,
+        expected_period: Option<runtime::Duration>,
+    ) -> Result<RateMonitor, BuilderError> {
+        let action_key = action_key.into();
+        let action = self.get_action(action_key)?;
+        let expected = match action.r#type() {
+            ActionType::Timer(spec) => expected_period.or(spec.period),
+            ActionType::Standard { .. } => {
+                expected_period.or(self.timer_periods.get(action_key).copied())
+            }
+            _ => {
+                return Err(BuilderError::InconsistentBuilderState {
+                    what: format!(
+                        "Only timers and logical or physical actions can be rate-monitored, '{}' is not one",
+                        action.name()
+                    ),
+                })
+            }
+        };
+        let name = action.name().to_owned();
+        let reactor_key = action.reactor_key();
+        let (_, shutdown_action) = self.lifecycle_actions(reactor_key);
+
+        let monitor = RateMonitor {
+            name: name.clone(),
+            samples: Arc::new(Mutex::new(RateSamples {
+                expected,
+                ..Default::default()
+            })),
+        };
+        self.add_reaction(
+            &format!("_{name}_rate_monitor"),
+            reactor_key,
+            RecordFn(monitor.samples.clone()),
+        )
+        .with_action(action_key, 0, TriggerMode::TriggersOnly)?
+        .finish()?;
+        self.add_reaction(
+            &format!("_{name}_rate_report"),
+            reactor_key,
+            ReportFn(monitor.clone()),
+        )
+        .with_action(shutdown_action, 0, TriggerMode::TriggersOnly)?
+        .finish()?;
+        Ok(monitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_stats() {
+        let mut samples = RateSamples {
+            expected: Some(runtime::Duration::milliseconds(10)),
+            ..Default::default()
+        };
+        let start = std::time::Instant::now();
+        samples.record(start);
+        assert_eq!(samples.stats(), None);
+
+        let mut now = start;
+        for interval in [10, 12, 8, 10, 30] {
+            now += std::time::Duration::from_millis(interval);
+            samples.record(now);
+        }
+        let stats = samples.stats().unwrap();
+        assert_eq!(stats.intervals, 5);
+        assert_eq!(stats.min, runtime::Duration::milliseconds(8));
+        assert_eq!(stats.max, runtime::Duration::milliseconds(30));
+        assert_eq!(stats.mean, runtime::Duration::milliseconds(14));
+        assert_eq!(stats.p99, runtime::Duration::milliseconds(30));
+        assert_eq!(
+            stats.max_jitter(),
+            Some(runtime::Duration::milliseconds(20))
+        );
+    }
+}
@@ -1,9 +1,10 @@
 use super::{
-    BuilderActionKey, BuilderError, BuilderPortKey, BuilderReactorKey, EnvBuilder, FindElements,
-    PortType, Reactor, ReactorBuilderState,
+    ActionType, BuilderActionKey, BuilderError, BuilderPortKey, BuilderReactorKey, EnvBuilder,
+    FindElements, PortType, Reactor, ReactorBuilderState,
 };
 use crate::{runtime, ParentReactorBuilder};
 use slotmap::SecondaryMap;
+use std::collections::BTreeSet;
 
 slotmap::new_key_type! {
     pub struct BuilderReactionKey;
@@ -63,7 +64,7 @@ impl<T: runtime::ReactorData> ReactionField for runtime::AsyncActionRef<T> {
         order: usize,
         trigger_mode: TriggerMode,
     ) -> Result<(), BuilderError> {
-        builder.add_action(key, order, trigger_mode)
+        builder.add_async_action(key, order, trigger_mode)
     }
 }
 
@@ -168,6 +169,8 @@ pub struct ReactionBuilder {
     pub(super) trigger_actions: SecondaryMap<BuilderActionKey, usize>,
     /// Actions that can be read or scheduled by this Reaction, and their relative ordering.
     pub(super) use_effect_actions: SecondaryMap<BuilderActionKey, usize>,
+    /// Actions this Reaction uses asynchronously, from outside of the scheduler.
+    pub(super) async_actions: SecondaryMap<BuilderActionKey, ()>,
 
     /// Ports that can trigger this Reaction, and their relative ordering.
     pub(super) trigger_ports: SecondaryMap<BuilderPortKey, usize>,
@@ -177,6 +180,15 @@ pub struct ReactionBuilder {
     /// Ports that this Reaction may set the value of, and their relative ordering. These are used
     /// to build the array of [`runtime::PortRefMut`]` in the reaction function.
     pub(super) effect_ports: SecondaryMap<BuilderPortKey, usize>,
+    /// Optional deadline of this Reaction
+    pub(super) deadline: Option<runtime::Deadline>,
+    /// Optional execution time budget of this Reaction, see [`runtime::budget`]
+    pub(super) budget: Option<runtime::Duration>,
+    /// The fields of the reactor state used by this Reaction, if declared. Reactions of a reactor using disjoint fields
+    /// are not ordered relative to each other, but still run at distinct levels.
+    pub(super) state_fields: Option<BTreeSet<String>>,
+    /// The named phase of the tag this Reaction runs in, see [`EnvBuilder::set_phases`].
+    pub(super) phase: Option<String>,
 }
 
 impl ParentReactorBuilder for ReactionBuilder {
@@ -197,6 +209,10 @@ impl std::fmt::Debug for ReactionBuilder {
             .field("trigger_ports", &self.trigger_ports)
             .field("use_ports", &self.use_ports)
             .field("effect_ports", &self.effect_ports)
+            .field("deadline", &self.deadline)
+            .field("budget", &self.budget)
+            .field("state_fields", &self.state_fields)
+            .field("phase", &self.phase)
             .finish()
     }
 }
@@ -210,6 +226,38 @@ impl ReactionBuilder {
     pub fn priority(&self) -> usize {
         self.priority
     }
+
+    /// The fields of the reactor state used by this Reaction, if declared.
+    pub fn state_fields(&self) -> Option<&BTreeSet<String>> {
+        self.state_fields.as_ref()
+    }
+
+    /// The named phase of the tag this Reaction runs in, if any.
+    pub fn phase(&self) -> Option<&str> {
+        self.phase.as_deref()
+    }
+
+    /// Whether this Reaction and `other`, of the same reactor, can run in either order.
+    ///
+    /// This is the case if both declare the state fields they use and these are disjoint, neither sets a port the other
+    /// accesses, and they don't share any action they read or schedule.
+    pub(crate) fn is_independent_of(&self, other: &ReactionBuilder) -> bool {
+        let (Some(fields), Some(other_fields)) = (&self.state_fields, &other.state_fields) else {
+            return false;
+        };
+        let accesses = |reaction: &ReactionBuilder, port_key| {
+            reaction.trigger_ports.contains_key(port_key)
+                || reaction.use_ports.contains_key(port_key)
+                || reaction.effect_ports.contains_key(port_key)
+        };
+        fields.is_disjoint(other_fields)
+            && !self.effect_ports.keys().any(|key| accesses(other, key))
+            && !other.effect_ports.keys().any(|key| accesses(self, key))
+            && !self
+                .use_effect_actions
+                .keys()
+                .any(|key| other.use_effect_actions.contains_key(key))
+    }
 }
 
 pub struct ReactionBuilderState<'a> {
@@ -286,9 +334,14 @@ impl<'a> ReactionBuilderState<'a> {
                 reaction_fn,
                 trigger_actions: SecondaryMap::new(),
                 use_effect_actions: SecondaryMap::new(),
+                async_actions: SecondaryMap::new(),
                 trigger_ports: SecondaryMap::new(),
                 use_ports: SecondaryMap::new(),
                 effect_ports: SecondaryMap::new(),
+                deadline: None,
+                budget: None,
+                state_fields: None,
+                phase: None,
             },
             env,
         }
@@ -301,7 +354,9 @@ impl<'a> ReactionBuilderState<'a> {
         trigger_mode: TriggerMode,
     ) -> Result<(), BuilderError> {
         let action = &self.env.action_builders[key];
-        if action.reactor_key() != self.builder.reactor_key {
+        // The startup and shutdown actions are shared by all reactors
+        let shared = matches!(action.r#type(), ActionType::Startup | ActionType::Shutdown);
+        if !shared && action.reactor_key() != self.builder.reactor_key {
             return Err(BuilderError::ReactionBuilderError(format!(
                 "Cannot add action '{}' to ReactionBuilder '{}', it must belong to the same reactor as the reaction",
                 action.name(), &self.builder.name
@@ -323,6 +378,18 @@ impl<'a> ReactionBuilderState<'a> {
         Ok(())
     }
 
+    /// Add an Action used asynchronously by this Reaction, i.e. as a [`runtime::AsyncActionRef`].
+    pub fn add_async_action(
+        &mut self,
+        key: BuilderActionKey,
+        order: usize,
+        trigger_mode: TriggerMode,
+    ) -> Result<(), BuilderError> {
+        self.add_action(key, order, trigger_mode)?;
+        self.builder.async_actions.insert(key, ());
+        Ok(())
+    }
+
     /// Indicate how this Reaction interacts with the given Action
     ///
     /// There must be at least one trigger for each reaction.
@@ -452,6 +519,45 @@ impl<'a> ReactionBuilderState<'a> {
         Ok(self)
     }
 
+    /// Set a deadline on the physical time lag of this Reaction, calling `handler` before the Reaction body if it has
+    /// already passed when the Reaction is invoked.
+    ///
+    /// A deadline passing while the Reaction runs cancels its [`runtime::CancellationToken`].
+    pub fn with_deadline(
+        mut self,
+        deadline: runtime::Duration,
+        handler: impl Fn() + Send + Sync + 'static,
+    ) -> Self {
+        self.builder.deadline = Some(runtime::Deadline::new(deadline, handler));
+        self
+    }
+
+    /// Set a budget on the physical time a single invocation of this Reaction takes to run, see [`runtime::budget`].
+    ///
+    /// Unlike a deadline, an exceeded budget doesn't cancel the Reaction, it is only logged and recorded in the
+    /// [`runtime::BudgetStats`] of the scheduler.
+    pub fn with_budget(mut self, budget: runtime::Duration) -> Self {
+        self.builder.budget = Some(budget);
+        self
+    }
+
+    /// Declare the fields of the reactor state used by the Reaction, see [`ReactionBuilder::state_fields`].
+    pub fn with_state_fields<I>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.builder.state_fields = Some(fields.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Run the Reaction in the named `phase` of each tag, after all Reactions of the earlier phases declared with
+    /// [`EnvBuilder::set_phases`] have completed.
+    pub fn with_phase(mut self, phase: impl Into<String>) -> Self {
+        self.builder.phase = Some(phase.into());
+        self
+    }
+
     pub fn finish(self) -> Result<BuilderReactionKey, BuilderError> {
         let Self {
             builder: reaction_builder,
@@ -1,11 +1,12 @@
 use std::fmt::Debug;
 
 use super::{
-    ActionType, BuilderActionKey, BuilderError, BuilderFqn, BuilderPortKey, BuilderReactionKey,
-    EnvBuilder, FindElements, Logical, Output, Physical, PhysicalActionKey, PortTag,
-    ReactionBuilderState, TimerActionKey, TimerSpec, TriggerMode, TypedActionKey, TypedPortKey,
+    BuilderActionKey, BuilderError, BuilderFqn, BuilderFqnSegment, BuilderPortKey,
+    BuilderReactionKey, EnvBuilder, FindElements, Logical, Output, Physical, PhysicalActionKey,
+    PortTag, ReactionBuilderState, TimerActionKey, TimerSpec, TriggerMode, TypedActionKey,
+    TypedPortKey,
 };
-use crate::{runtime, ActionTag, Input};
+use crate::{runtime, ActionTag, Coalesce, Input, MergePolicy, RateMonitor};
 use slotmap::SecondaryMap;
 
 slotmap::new_key_type! {
@@ -33,7 +34,7 @@ pub trait Reactor: Sized {
         env: &mut EnvBuilder,
     ) -> Result<Self, BuilderError>;
 
-    fn iter(&self) -> impl Iterator<Item = &Self> {
+    fn iter(&self) -> impl Iterator<Item = &Self> + Clone {
         std::iter::once(self)
     }
 }
@@ -62,6 +63,22 @@ impl<R: Reactor> ReactorField for R {
     }
 }
 
+/// An optional child reactor, only built if its state is `Some`, see [`ReactorBuilderState::child_if`].
+impl<R: Reactor> ReactorField for Option<R> {
+    type Inner = Option<R::State>;
+
+    fn build(
+        name: &str,
+        inner: Self::Inner,
+        parent: &'_ mut ReactorBuilderState,
+    ) -> Result<Self, BuilderError> {
+        match inner {
+            Some(state) => parent.add_child_reactor(name, state).map(Some),
+            None => parent.exclude_child(name).map(|()| None),
+        }
+    }
+}
+
 /// NOTE: `R::State: Clone` is required because state is cloned for each child reactor.
 impl<R, const N: usize> ReactorField for [R; N]
 where
@@ -141,21 +158,60 @@ impl ReactorField for PhysicalActionKey {
     }
 }
 
-pub(super) struct ReactorState<T: runtime::ReactorData>(T);
+pub(super) struct ReactorState<T: runtime::ReactorData> {
+    state: T,
+    /// How panics in the reactions are handled
+    panic_policy: runtime::PanicPolicy,
+    /// Creates the initial state when restarting after a panic
+    initial_state: Option<Box<dyn Fn() -> T + Send + Sync>>,
+}
+
+impl<T: runtime::ReactorData> ReactorState<T> {
+    fn new(state: T) -> Self {
+        Self {
+            state,
+            panic_policy: runtime::PanicPolicy::Propagate,
+            initial_state: None,
+        }
+    }
+}
 
 pub(super) trait BaseReactorState: Debug {
     fn into_runtime(self: Box<Self>, name: &str) -> Box<dyn runtime::BaseReactor>;
+
+    /// Isolate panics in the reactions, see [`runtime::Reactor::isolate_panics`].
+    fn isolate_panics(&mut self);
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
 }
 
 impl<T: runtime::ReactorData> BaseReactorState for ReactorState<T> {
     fn into_runtime(self: Box<Self>, name: &str) -> Box<dyn runtime::BaseReactor> {
-        runtime::Reactor::new(name, self.0).boxed()
+        let reactor = runtime::Reactor::new(name, self.state);
+        match (self.panic_policy, self.initial_state) {
+            (runtime::PanicPolicy::Isolate, _) => reactor.isolate_panics(),
+            (runtime::PanicPolicy::Restart, Some(initial_state)) => {
+                reactor.with_restart(initial_state)
+            }
+            _ => reactor,
+        }
+        .boxed()
+    }
+
+    fn isolate_panics(&mut self) {
+        self.panic_policy = runtime::PanicPolicy::Isolate;
+        self.initial_state = None;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
     }
 }
 
 impl<T: runtime::ReactorData> Debug for ReactorState<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct(&format!("ReactorState<{}>", std::any::type_name::<T>()))
+            .field("panic_policy", &self.panic_policy)
             .finish()
     }
 }
@@ -197,6 +253,10 @@ impl ReactorBuilder {
         &self.name
     }
 
+    pub(crate) fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     pub fn bank_info(&self) -> Option<&runtime::BankInfo> {
         self.bank_info.as_ref()
     }
@@ -212,6 +272,18 @@ impl ReactorBuilder {
     }
 }
 
+/// An initialization waiting for the runtime key of its reactor to be resolved.
+pub(crate) struct InitBuilder {
+    pub(crate) reactor_key: BuilderReactorKey,
+    pub(crate) build_fn: Box<dyn FnOnce(runtime::ReactorKey) -> runtime::Init>,
+}
+
+/// A flush waiting for the runtime key of its reactor to be resolved.
+pub(crate) struct FlushBuilder {
+    pub(crate) reactor_key: BuilderReactorKey,
+    pub(crate) build_fn: Box<dyn FnOnce(runtime::ReactorKey) -> runtime::Flush>,
+}
+
 /// Builder struct used to facilitate construction of a ReactorBuilder by user/generated code.
 pub struct ReactorBuilderState<'a> {
     /// The ReactorKey of this Builder
@@ -243,7 +315,7 @@ impl<'a> ReactorBuilderState<'a> {
         let reactor_key = env.reactor_builders.insert({
             ReactorBuilder {
                 name: name.into(),
-                state: Box::new(ReactorState(reactor_state)),
+                state: Box::new(ReactorState::new(reactor_state)),
                 type_name: type_name.into(),
                 parent_reactor_key: parent,
                 reactions: SecondaryMap::new(),
@@ -253,13 +325,7 @@ impl<'a> ReactorBuilderState<'a> {
             }
         });
 
-        let startup_action = env
-            .add_startup_action("__startup", reactor_key)
-            .expect("Duplicate startup Action?");
-
-        let shutdown_action = env
-            .add_shutdown_action("__shutdown", reactor_key)
-            .expect("Duplicate shutdown Action?");
+        let (startup_action, shutdown_action) = env.lifecycle_actions(reactor_key);
 
         Self {
             reactor_key,
@@ -274,32 +340,13 @@ impl<'a> ReactorBuilderState<'a> {
         reactor_key: BuilderReactorKey,
         env: &'a mut EnvBuilder,
     ) -> Self {
-        // Find the startup and shutdown actions for this reactor
-        let startup_action = env
-            .action_builders
-            .iter()
-            .find(|(_, action)| {
-                matches!(action.r#type(), ActionType::Startup)
-                    && action.reactor_key() == reactor_key
-            })
-            .map(|(action_key, _)| action_key)
-            .expect("Startup action not found");
-
-        let shutdown_action = env
-            .action_builders
-            .iter()
-            .find(|(_, action)| {
-                matches!(action.r#type(), ActionType::Shutdown)
-                    && action.reactor_key() == reactor_key
-            })
-            .map(|(action_key, _)| action_key)
-            .expect("Shutdown action not found");
+        let (startup_action, shutdown_action) = env.lifecycle_actions(reactor_key);
 
         Self {
             reactor_key,
             env,
-            startup_action: TypedActionKey::from(startup_action),
-            shutdown_action: TypedActionKey::from(shutdown_action),
+            startup_action,
+            shutdown_action,
         }
     }
 
@@ -308,12 +355,12 @@ impl<'a> ReactorBuilderState<'a> {
         self.reactor_key
     }
 
-    /// Get the startup action for this reactor
+    /// Get the startup action, shared by all reactors
     pub fn get_startup_action(&self) -> TypedActionKey {
         self.startup_action
     }
 
-    /// Get the shutdown action for this reactor
+    /// Get the shutdown action, shared by all reactors
     pub fn get_shutdown_action(&self) -> TypedActionKey {
         self.shutdown_action
     }
@@ -324,7 +371,18 @@ impl<'a> ReactorBuilderState<'a> {
         name: &str,
         spec: TimerSpec,
     ) -> Result<TimerActionKey, BuilderError> {
+        for (what, delay) in [("period", spec.period), ("offset", spec.offset)] {
+            if let Some(delay) = delay.filter(|delay| delay.is_negative()) {
+                return Err(BuilderError::NegativeDelay {
+                    what: format!("the {what} of timer '{name}'"),
+                    delay,
+                });
+            }
+        }
         let action_key = self.add_logical_action::<()>(name, None)?;
+        if let Some(period) = spec.period {
+            self.env.timer_periods.insert(action_key.into(), period);
+        }
 
         let startup_key = self.startup_action;
 
@@ -347,6 +405,28 @@ impl<'a> ReactorBuilderState<'a> {
         Ok(TimerActionKey::from(BuilderActionKey::from(action_key)))
     }
 
+    /// Add a new timer action to the reactor, triggering at the times matching the calendar `spec`.
+    ///
+    /// See [`runtime::calendar`] for the format of the spec and how it relates to logical time.
+    pub fn add_calendar_timer(
+        &mut self,
+        name: &str,
+        spec: runtime::CalendarSpec,
+    ) -> Result<TimerActionKey, BuilderError> {
+        let action_key = self.add_logical_action::<()>(name, None)?;
+        let startup_key = self.startup_action;
+
+        self.add_reaction(
+            &format!("_{name}_startup"),
+            runtime::calendar::CalendarTimerFn::new(spec),
+        )
+        .with_action(startup_key, 0, TriggerMode::TriggersOnly)?
+        .with_action(action_key, 1, TriggerMode::TriggersAndEffects)?
+        .finish()?;
+
+        Ok(TimerActionKey::from(BuilderActionKey::from(action_key)))
+    }
+
     /// Add a new action to the reactor.
     ///
     /// This method forwards to the implementation at [`crate::env::EnvBuilder::internal_add_action`].
@@ -381,6 +461,229 @@ impl<'a> ReactorBuilderState<'a> {
             .internal_add_action::<T, Physical>(name, min_delay, self.reactor_key)
     }
 
+    /// Add a new failure action to the reactor.
+    ///
+    /// This method forwards to the implementation at [`crate::env::EnvBuilder::add_failure_action`].
+    pub fn add_failure_action(
+        &mut self,
+        name: &str,
+    ) -> Result<TypedActionKey<Vec<runtime::ReactorFailure>, Logical>, BuilderError> {
+        self.env.add_failure_action(name, self.reactor_key)
+    }
+
+    /// Add a new overload action to the reactor.
+    ///
+    /// This method forwards to the implementation at [`crate::env::EnvBuilder::add_overload_action`].
+    pub fn add_overload_action(
+        &mut self,
+        name: &str,
+    ) -> Result<TypedActionKey<runtime::Overload, Logical>, BuilderError> {
+        self.env.add_overload_action(name, self.reactor_key)
+    }
+
+    /// Add a new parameter change action to the reactor.
+    ///
+    /// This method forwards to the implementation at [`crate::env::EnvBuilder::add_parameter_change_action`].
+    pub fn add_parameter_change_action(
+        &mut self,
+        name: &str,
+    ) -> Result<TypedActionKey<runtime::ParameterChange, Logical>, BuilderError> {
+        self.env.add_parameter_change_action(name, self.reactor_key)
+    }
+
+    /// Mark an action as conflatable.
+    ///
+    /// This method forwards to the implementation at [`crate::env::EnvBuilder::mark_conflatable`].
+    pub fn mark_conflatable(
+        &mut self,
+        action_key: impl Into<BuilderActionKey>,
+    ) -> Result<(), BuilderError> {
+        self.env.mark_conflatable(action_key)
+    }
+
+    /// Set the backpressure policy of a physical action.
+    ///
+    /// This method forwards to the implementation at [`crate::env::EnvBuilder::set_backpressure`].
+    pub fn set_backpressure(
+        &mut self,
+        action_key: impl Into<PhysicalActionKey>,
+        policy: runtime::BackpressurePolicy,
+    ) -> Result<(), BuilderError> {
+        self.env.set_backpressure(action_key, policy)
+    }
+
+    /// Throttle the events of an action of this reactor.
+    ///
+    /// This method forwards to the implementation at [`crate::env::EnvBuilder::throttle`].
+    pub fn throttle<T: runtime::ReactorData + Clone, Q: ActionTag>(
+        &mut self,
+        action_key: TypedActionKey<T, Q>,
+        min_period: runtime::Duration,
+    ) -> Result<TypedActionKey<T, Logical>, BuilderError> {
+        self.env.throttle(action_key, min_period)
+    }
+
+    /// Debounce the events of an action of this reactor.
+    ///
+    /// This method forwards to the implementation at [`crate::env::EnvBuilder::debounce`].
+    pub fn debounce<T: runtime::ReactorData + Clone, Q: ActionTag>(
+        &mut self,
+        action_key: TypedActionKey<T, Q>,
+        quiet_period: runtime::Duration,
+    ) -> Result<TypedActionKey<T, Logical>, BuilderError> {
+        self.env.debounce(action_key, quiet_period)
+    }
+
+    /// Mark an input port as optional, so it may be left unconnected in strict mode.
+    ///
+    /// This method forwards to the implementation at [`crate::env::EnvBuilder::mark_optional`].
+    pub fn mark_optional<T: runtime::ReactorData>(&mut self, port_key: TypedPortKey<T, Input>) {
+        self.env.mark_optional(port_key)
+    }
+
+    /// Monitor the rate of a timer or action of this reactor.
+    ///
+    /// This method forwards to the implementation at [`crate::env::EnvBuilder::monitor_rate`].
+    pub fn monitor_rate(
+        &mut self,
+        action_key: impl Into<BuilderActionKey>,
+        expected_period: Option<runtime::Duration>,
+    ) -> Result<RateMonitor, BuilderError> {
+        self.env.monitor_rate(action_key, expected_period)
+    }
+
+    /// Set a global parameter, available to all reactions.
+    ///
+    /// This method forwards to the implementation at [`crate::env::EnvBuilder::set_parameter`].
+    pub fn set_parameter<T: runtime::ReactorData>(&mut self, name: &str, value: T) {
+        self.env.set_parameter(name, value)
+    }
+
+    /// Set a global parameter that may be updated at runtime.
+    ///
+    /// This method forwards to the implementation at [`crate::env::EnvBuilder::set_reloadable_parameter`].
+    pub fn set_reloadable_parameter<T: runtime::ReactorData>(&mut self, name: &str, value: T) {
+        self.env.set_reloadable_parameter(name, value)
+    }
+
+    /// Get a global parameter.
+    ///
+    /// This method forwards to the implementation at [`crate::env::EnvBuilder::get_parameter`].
+    pub fn get_parameter<T: runtime::ReactorData>(&self, name: &str) -> Option<&T> {
+        self.env.get_parameter(name)
+    }
+
+    /// Catch panics in the reactions of this reactor, and skip all of its reactions after the first panic.
+    ///
+    /// See [`runtime::isolation`] for details.
+    pub fn isolate_panics(&mut self) {
+        self.env.reactor_builders[self.reactor_key]
+            .state
+            .isolate_panics();
+    }
+
+    /// Catch panics in the reactions of this reactor, and reset its state to a clone of the initial state `S` after
+    /// each panic.
+    ///
+    /// See [`runtime::isolation`] for details.
+    pub fn restart_on_panic<S: runtime::ReactorData + Clone>(
+        &mut self,
+    ) -> Result<(), BuilderError> {
+        let reactor_builder = &mut self.env.reactor_builders[self.reactor_key];
+        let reactor_state = reactor_builder
+            .state
+            .as_any_mut()
+            .downcast_mut::<ReactorState<S>>()
+            .ok_or_else(|| BuilderError::InconsistentBuilderState {
+                what: format!(
+                    "The state of reactor '{}' is not a {}",
+                    reactor_builder.name,
+                    std::any::type_name::<S>()
+                ),
+            })?;
+        let initial_state = reactor_state.state.clone();
+        reactor_state.panic_policy = runtime::PanicPolicy::Restart;
+        reactor_state.initial_state = Some(Box::new(move || initial_state.clone()));
+        Ok(())
+    }
+
+    /// Add an initialization of this reactor with state `S`, run before logical time begins.
+    ///
+    /// `init` runs on a background thread, concurrently with the initializations of all other reactors, and its value is
+    /// passed to `apply` with the reactor state before the startup reactions run. The run is aborted if `init` fails or
+    /// doesn't complete within `timeout`. See [`runtime::init`] for details.
+    pub fn add_init<S, V, E, F, A>(
+        &mut self,
+        name: &str,
+        timeout: Option<runtime::Duration>,
+        init: F,
+        apply: A,
+    ) -> Result<(), BuilderError>
+    where
+        S: runtime::ReactorData,
+        V: Send + 'static,
+        E: std::fmt::Display,
+        F: FnOnce() -> Result<V, E> + Send + 'static,
+        A: FnOnce(&mut S, V) + Send + 'static,
+    {
+        let fqn = self.env.reactor_fqn(self.reactor_key, false)?.to_string();
+        if let Some(timeout) = timeout.filter(|timeout| timeout.is_negative()) {
+            return Err(BuilderError::NegativeDelay {
+                what: format!("the timeout of init '{fqn}::{name}'"),
+                delay: timeout,
+            });
+        }
+        let reactor_builder = &mut self.env.reactor_builders[self.reactor_key];
+        if !reactor_builder.state.as_any_mut().is::<ReactorState<S>>() {
+            return Err(BuilderError::InconsistentBuilderState {
+                what: format!(
+                    "The state of reactor '{}' is not a {}",
+                    reactor_builder.name,
+                    std::any::type_name::<S>()
+                ),
+            });
+        }
+
+        let name = format!("{fqn}::{name}");
+        self.env.inits.push(InitBuilder {
+            reactor_key: self.reactor_key,
+            build_fn: Box::new(move |reactor_key| {
+                runtime::Init::new(&name, reactor_key, timeout, init, apply)
+            }),
+        });
+        Ok(())
+    }
+
+    /// Add a flush of this reactor with state `S`, run after the shutdown tag.
+    ///
+    /// `flush` runs with the final state of the reactor, after all reactions at the shutdown tag, so a sink can persist
+    /// every value it received before the run ends. See [`runtime::flush`] for details.
+    pub fn add_flush<S, E, F>(&mut self, name: &str, flush: F) -> Result<(), BuilderError>
+    where
+        S: runtime::ReactorData,
+        E: std::fmt::Display,
+        F: FnOnce(&mut S) -> Result<(), E> + Send + 'static,
+    {
+        let fqn = self.env.reactor_fqn(self.reactor_key, false)?.to_string();
+        let reactor_builder = &mut self.env.reactor_builders[self.reactor_key];
+        if !reactor_builder.state.as_any_mut().is::<ReactorState<S>>() {
+            return Err(BuilderError::InconsistentBuilderState {
+                what: format!(
+                    "The state of reactor '{}' is not a {}",
+                    reactor_builder.name,
+                    std::any::type_name::<S>()
+                ),
+            });
+        }
+
+        let name = format!("{fqn}::{name}");
+        self.env.flushes.push(FlushBuilder {
+            reactor_key: self.reactor_key,
+            build_fn: Box::new(move |reactor_key| runtime::Flush::new(&name, reactor_key, flush)),
+        });
+        Ok(())
+    }
+
     /// Add a new reaction to this reactor.
     pub fn add_reaction(
         &mut self,
@@ -482,6 +785,48 @@ impl<'a> ReactorBuilderState<'a> {
             .map_err(|_| BuilderError::InternalError("Error converting Vec to array".to_owned()))
     }
 
+    /// Whether the feature flag `name` is enabled, see [`EnvBuilder::flag`].
+    pub fn flag(&self, name: &str) -> bool {
+        self.env.flag(name)
+    }
+
+    /// Add a new child reactor to this reactor only if `flag` is set, e.g., a diagnostics subtree.
+    ///
+    /// Without the flag, nothing is built and the child's fully-qualified name is recorded as an excluded subtree.
+    /// Connections to and from the missing child are pruned instead of failing: the connections declared with the
+    /// derive macro iterate over no ports (a child field of type `Option<R>` is built with this method), and
+    /// [`EnvBuilder::connect`] skips the ports in excluded subtrees. Any other lookup of an element in the subtree by
+    /// its FQN, e.g., for a tap or a probe, fails with [`BuilderError::ExcludedElement`] naming the subtree, rather
+    /// than as if the element never existed.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,ignore
+    /// let diagnostics = builder.child_if::<Diagnostics>(builder.flag("diagnostics"), "diagnostics", ())?;
+    /// ```
+    pub fn child_if<R: Reactor>(
+        &mut self,
+        flag: bool,
+        name: &str,
+        state: R::State,
+    ) -> Result<Option<R>, BuilderError> {
+        if flag {
+            self.add_child_reactor(name, state).map(Some)
+        } else {
+            self.exclude_child(name).map(|()| None)
+        }
+    }
+
+    /// Record the child `name` of this reactor as an excluded subtree.
+    fn exclude_child(&mut self, name: &str) -> Result<(), BuilderError> {
+        let fqn = self
+            .env
+            .reactor_fqn(self.reactor_key, false)?
+            .append(BuilderFqnSegment::try_from(name)?)?;
+        self.env.exclude(fqn);
+        Ok(())
+    }
+
     /// Add a new child reactor using a closure to build it.
     pub fn add_child_with<F>(&mut self, f: F) -> Result<BuilderReactorKey, BuilderError>
     where
@@ -517,6 +862,64 @@ impl<'a> ReactorBuilderState<'a> {
         Ok(())
     }
 
+    /// Connect multiple ports on this reactor with a coalescing policy, see [`EnvBuilder::connect_ports_coalesced`].
+    pub fn connect_ports_coalesced<T: runtime::ReactorData + Clone, Q1: PortTag, Q2: PortTag>(
+        &mut self,
+        ports_from: impl Iterator<Item = TypedPortKey<T, Q1>>,
+        ports_to: impl Iterator<Item = TypedPortKey<T, Q2>>,
+        after: Option<runtime::Duration>,
+        physical: bool,
+        coalesce: Coalesce,
+    ) -> Result<(), BuilderError> {
+        for (port_from, port_to) in ports_from.zip(ports_to) {
+            self.env.connect_ports_coalesced::<T, _, _>(
+                port_from, port_to, after, physical, coalesce,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Connect multiple ports to logical actions, see [`EnvBuilder::add_port_action_connection`].
+    pub fn connect_ports_to_actions<T: runtime::ReactorData + Clone, Q: PortTag>(
+        &mut self,
+        ports_from: impl Iterator<Item = TypedPortKey<T, Q>>,
+        actions_to: impl Iterator<Item = TypedActionKey<T, Logical>>,
+        delay: Option<runtime::Duration>,
+    ) -> Result<(), BuilderError> {
+        for (port_from, action_to) in ports_from.zip(actions_to) {
+            self.env
+                .add_port_action_connection(port_from, action_to, delay)?;
+        }
+        Ok(())
+    }
+
+    /// Declare a new named bus, see [`EnvBuilder::add_bus`].
+    pub fn add_bus<T: runtime::ReactorData + Clone>(
+        &mut self,
+        name: &str,
+        policy: MergePolicy<T>,
+    ) -> Result<(), BuilderError> {
+        self.env.add_bus(name, policy)
+    }
+
+    /// Attach an output port as a producer to the bus named `name`.
+    pub fn attach_bus_producer<T: runtime::ReactorData + Clone>(
+        &mut self,
+        name: &str,
+        port_key: TypedPortKey<T, Output>,
+    ) -> Result<(), BuilderError> {
+        self.env.attach_bus_producer(name, port_key)
+    }
+
+    /// Attach an input port as a consumer to the bus named `name`.
+    pub fn attach_bus_consumer<T: runtime::ReactorData + Clone>(
+        &mut self,
+        name: &str,
+        port_key: TypedPortKey<T, Input>,
+    ) -> Result<(), BuilderError> {
+        self.env.attach_bus_consumer(name, port_key)
+    }
+
     pub fn finish(self) -> Result<BuilderReactorKey, BuilderError> {
         Ok(self.reactor_key)
     }
@@ -0,0 +1,76 @@
+//! The stable subset of the builder API.
+//!
+//! Everything re-exported from this module is covered by semver: it only changes in a breaking way in a new major
+//! release (or minor release, while the version is `0.x`), and anything renamed or replaced is first kept as a
+//! `#[deprecated]` shim for at least one release, so downstream code can migrate gradually. The rest of the crate is
+//! driven by the code that [`boomerang_derive`](https://docs.rs/boomerang_derive) generates, and may change in any
+//! release.
+//!
+//! The deprecated items of older styles of building reactors are collected here as well:
+//!
+//! - Reactors no longer keep [`BuilderReactionKey`] fields for their reactions, the reactions are declared as
+//!   [`Reaction`] structs instead.
+//! - Startup and shutdown actions are no longer added explicitly with `EnvBuilder::add_startup_action` and
+//!   `EnvBuilder::add_shutdown_action`, all reactors already share them, see
+//!   [`ReactorBuilderState::get_startup_action`] and [`ReactorBuilderState::get_shutdown_action`].
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! use boomerang::builder::stable::*;
+//!
+//! let mut env_builder = EnvBuilder::new();
+//! let reactor = MyReactor::build("my_reactor", (), None, None, &mut env_builder)?;
+//! let built = env_builder.build_runtime::<MyReactor>("my_reactor")?;
+//! ```
+
+pub use crate::{
+    reaction_closure, BuilderActionKey, BuilderAliases, BuilderError, BuilderFqn, BuilderPortKey,
+    BuilderReactorKey, BuiltRuntime, Coalesce, EnvBuilder, Input, Logical, MergePolicy, Output,
+    Physical, PhysicalActionKey, Reaction, ReactionBuilderState, Reactor, ReactorBuilderState,
+    TimerActionKey, TimerSpec, TriggerMode, TypedActionKey, TypedPortKey,
+};
+
+/// The key of a reaction in the [`EnvBuilder`].
+#[deprecated(
+    since = "0.3.1",
+    note = "declare reactions as `Reaction` structs instead of keeping their keys in reactor fields"
+)]
+pub type BuilderReactionKey = crate::BuilderReactionKey;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime;
+
+    /// The stable API is sufficient to build and connect reactors by hand.
+    #[test]
+    fn test_stable_api() -> Result<(), BuilderError> {
+        let mut env_builder = EnvBuilder::new();
+        let mut source = env_builder.add_reactor("source", None, None, ());
+        let out = source.add_output_port::<u32>("out")?;
+        let startup = source.get_startup_action();
+        source
+            .add_reaction("emit", reaction_closure!())
+            .with_action(startup, 0, TriggerMode::TriggersOnly)?
+            .with_port(out, 0, TriggerMode::EffectsOnly)?
+            .finish()?;
+        source.finish()?;
+
+        let mut sink = env_builder.add_reactor("sink", None, None, ());
+        let inp = sink.add_input_port::<u32>("inp")?;
+        sink.add_reaction("receive", reaction_closure!())
+            .with_port(inp, 0, TriggerMode::TriggersOnly)?
+            .finish()?;
+        sink.finish()?;
+
+        env_builder.connect_ports::<u32, _, _>(out, inp, None, false)?;
+        let (env, _, aliases) = env_builder.into_runtime_parts()?;
+        assert_eq!(env.reactors.len(), 2);
+        assert_eq!(
+            aliases.port_aliases[BuilderPortKey::from(out)],
+            aliases.port_aliases[BuilderPortKey::from(inp)]
+        );
+        Ok(())
+    }
+}
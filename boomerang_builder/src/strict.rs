@@ -0,0 +1,218 @@
+//! Strict mode, enforcing the rules of Lingua Franca that are otherwise not checked.
+//!
+//! With [`EnvBuilder::strict`] enabled, building the environment fails with [`BuilderError::StrictViolations`] listing
+//! every violation of these rules at once, instead of stopping at the first one:
+//!
+//! - Every input port of an instantiated reactor is connected, either bound to another port or set by a reaction of
+//!   its parent, unless it was marked as optional with [`EnvBuilder::mark_optional`].
+//! - A logical action used from an asynchronous context, i.e., as an [`runtime::AsyncActionRef`], has a positive
+//!   minimum delay. Otherwise an event could be scheduled with zero delay, at a tag that depends on when the scheduler
+//!   receives it.
+//!
+//! Reactions setting the ports of reactors they don't contain are always rejected by
+//! [`crate::ReactionBuilderState::add_port`], strict or not.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! let mut env_builder = EnvBuilder::new();
+//! env_builder.strict(true);
+//! let main = MainReactor::build("main", (), None, None, false, &mut env_builder)?;
+//! env_builder.mark_optional(main.filter.calibration);
+//! for violation in env_builder.strict_violations()? {
+//!     tracing::warn!("{violation}");
+//! }
+//! ```
+
+use crate::{
+    runtime, ActionType, BuilderActionKey, BuilderError, BuilderPortKey, BuilderReactionKey,
+    EnvBuilder, Input, ParentReactorBuilder, PortType, TypedPortKey,
+};
+
+/// A violation of a rule enforced in strict mode, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StrictViolation {
+    /// The input port of an instantiated reactor is neither connected nor marked as optional
+    UnconnectedInput { port: BuilderPortKey, fqn: String },
+    /// The reaction uses a logical action without a minimum delay asynchronously
+    AsyncLogicalAction {
+        action: BuilderActionKey,
+        reaction: BuilderReactionKey,
+        action_fqn: String,
+        reaction_fqn: String,
+    },
+}
+
+impl std::fmt::Display for StrictViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StrictViolation::UnconnectedInput { fqn, .. } => write!(
+                f,
+                "Input port '{fqn}' is not connected and not marked as optional"
+            ),
+            StrictViolation::AsyncLogicalAction {
+                action_fqn,
+                reaction_fqn,
+                ..
+            } => write!(
+                f,
+                "Reaction '{reaction_fqn}' uses the logical action '{action_fqn}' asynchronously, but it has no minimum delay"
+            ),
+        }
+    }
+}
+
+impl EnvBuilder {
+    /// Enable or disable strict mode, see the [module documentation](self).
+    ///
+    /// Strict mode is disabled by default.
+    pub fn strict(&mut self, enabled: bool) -> &mut Self {
+        self.strict = enabled;
+        self
+    }
+
+    /// Whether strict mode is enabled.
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Mark an input port as optional, so it may be left unconnected in strict mode.
+    pub fn mark_optional<T: runtime::ReactorData>(&mut self, port_key: TypedPortKey<T, Input>) {
+        self.optional_inputs.insert(port_key.into(), ());
+    }
+
+    /// List the violations of the rules enforced in strict mode, whether it is enabled or not.
+    pub fn strict_violations(&self) -> Result<Vec<StrictViolation>, BuilderError> {
+        let mut violations = Vec::new();
+
+        for (port_key, port) in self.port_builders.iter() {
+            let has_parent = self.reactor_builders[port.get_reactor_key()]
+                .parent_reactor_key()
+                .is_some();
+            if *port.port_type() != PortType::Input
+                || !has_parent
+                || port.get_inward_binding().is_some()
+                || self.optional_inputs.contains_key(port_key)
+            {
+                continue;
+            }
+            let is_set = self
+                .reaction_builders
+                .values()
+                .any(|reaction| reaction.effect_ports.contains_key(port_key));
+            if !is_set {
+                violations.push(StrictViolation::UnconnectedInput {
+                    port: port_key,
+                    fqn: self.port_fqn(port_key, false)?.to_string(),
+                });
+            }
+        }
+
+        for (reaction_key, reaction) in self.reaction_builders.iter() {
+            for action_key in reaction.async_actions.keys() {
+                let zero_delay = match self.action_builders[action_key].r#type() {
+                    ActionType::Standard {
+                        is_logical: true,
+                        min_delay,
+                        ..
+                    } => min_delay.is_none_or(|min_delay| !min_delay.is_positive()),
+                    _ => false,
+                };
+                if zero_delay {
+                    violations.push(StrictViolation::AsyncLogicalAction {
+                        action: action_key,
+                        reaction: reaction_key,
+                        action_fqn: self.action_fqn(action_key, false)?.to_string(),
+                        reaction_fqn: self.reaction_fqn(reaction_key, false)?.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(violations)
+    }
+
+    /// Fail with all violations if strict mode is enabled.
+    pub(crate) fn check_strict(&self) -> Result<(), BuilderError> {
+        if !self.strict {
+            return Ok(());
+        }
+        let violations = self.strict_violations()?;
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(BuilderError::StrictViolations(violations))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reaction_closure, TriggerMode};
+
+    #[test]
+    fn test_strict_violations() {
+        let mut env_builder = EnvBuilder::new();
+        let main_key = env_builder
+            .add_reactor("main", None, None, ())
+            .finish()
+            .unwrap();
+
+        let mut child = env_builder.add_reactor("child", Some(main_key), None, ());
+        let set_by_parent = child.add_input_port::<u32>("set_by_parent").unwrap();
+        let optional = child.add_input_port::<u32>("optional").unwrap();
+        let unconnected = child.add_input_port::<u32>("unconnected").unwrap();
+        let zero_delay = child.add_logical_action::<()>("zero_delay", None).unwrap();
+        let delayed = child
+            .add_logical_action::<()>("delayed", Some(runtime::Duration::milliseconds(1)))
+            .unwrap();
+        let child_startup = child.get_startup_action();
+        let mut reaction = child.add_reaction("spawn", reaction_closure!());
+        reaction
+            .add_action(child_startup.into(), 0, TriggerMode::TriggersOnly)
+            .unwrap();
+        reaction
+            .add_async_action(zero_delay.into(), 1, TriggerMode::EffectsOnly)
+            .unwrap();
+        reaction
+            .add_async_action(delayed.into(), 2, TriggerMode::EffectsOnly)
+            .unwrap();
+        let spawn_key = reaction.finish().unwrap();
+        child.finish().unwrap();
+
+        let mut main = env_builder.get_reactor_builder(main_key).unwrap();
+        let startup = main.get_startup_action();
+        main.add_reaction("set", reaction_closure!())
+            .with_action(startup, 0, TriggerMode::TriggersOnly)
+            .unwrap()
+            .with_port(set_by_parent, 0, TriggerMode::EffectsOnly)
+            .unwrap()
+            .finish()
+            .unwrap();
+        env_builder.mark_optional(optional);
+
+        let violations = env_builder.strict_violations().unwrap();
+        assert_eq!(
+            violations,
+            [
+                StrictViolation::UnconnectedInput {
+                    port: unconnected.into(),
+                    fqn: "main::child::unconnected".to_owned(),
+                },
+                StrictViolation::AsyncLogicalAction {
+                    action: zero_delay.into(),
+                    reaction: spawn_key,
+                    action_fqn: "main::child::zero_delay".to_owned(),
+                    reaction_fqn: "main::child::spawn".to_owned(),
+                },
+            ]
+        );
+
+        env_builder.strict(true);
+        assert!(matches!(
+            env_builder.into_runtime_parts(),
+            Err(BuilderError::StrictViolations(violations)) if violations.len() == 2
+        ));
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{
+    env::Binding, runtime, BuilderError, BuilderPortKey, BuilderReactorKey, EnvBuilder, Input,
+    TypedPortKey,
+};
+
+impl EnvBuilder {
+    /// Tap the connection from the port with the fully-qualified name `source_fqn`, delivering every value flowing
+    /// through it to a new input port on `sink_reactor`.
+    ///
+    /// The new port is named `tap_<source_fqn>`, with the `::` separators (and any other characters not allowed in a
+    /// name) replaced by `_`, e.g. `tap_main_source_out`. It is bound to the source with [`EnvBuilder::bind_port`]
+    /// like any other connection, so reactions of `sink_reactor` triggered by it read the same value at the same tag as
+    /// the original consumers, without a copy. Unlike [`EnvBuilder::connect_ports`], the sink may be anywhere in the
+    /// reactor hierarchy, and the source may already be read by reactions or connected to other ports, so the hierarchy
+    /// and dependency checks of a connection are skipped.
+    ///
+    /// Since the tap only adds downstream dependencies on the source, it has no effect on the levels of the original
+    /// consumers, as long as the reactions of `sink_reactor` don't feed back into them.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,ignore
+    /// let logger = env_builder.add_reactor("logger", None, None, ()).finish()?;
+    /// let tap = env_builder.tap_connection::<u32>("main::source::out", logger)?;
+    /// ```
+    pub fn tap_connection<T: runtime::ReactorData>(
+        &mut self,
+        source_fqn: &str,
+        sink_reactor: BuilderReactorKey,
+    ) -> Result<TypedPortKey<T, Input>, BuilderError> {
+        let source_key = self.find_port_by_fqn(source_fqn)?;
+        let source = &self.port_builders[source_key];
+        if source.type_name() != std::any::type_name::<T>() {
+            return Err(BuilderError::InconsistentBuilderState {
+                what: format!(
+                    "Tap on '{source_fqn}' expects values of type {}, but the port carries {}",
+                    std::any::type_name::<T>(),
+                    source.type_name()
+                ),
+            });
+        }
+        if !self.reactor_builders.contains_key(sink_reactor) {
+            return Err(BuilderError::ReactorKeyNotFound(sink_reactor));
+        }
+
+        let name = source_fqn
+            .replace("::", "_")
+            .replace(|c: char| !c.is_alphanumeric() && c != '_', "_");
+        let tap_key: BuilderPortKey =
+            self.internal_add_port::<T, Input>(&format!("tap_{name}"), sink_reactor, None)?;
+        self.bind_port_as(source_key, tap_key, Binding::Tap)?;
+
+        Ok(tap_key.into())
+    }
+}
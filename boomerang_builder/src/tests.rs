@@ -95,15 +95,15 @@ fn test_dependency_use_on_logical_action() -> anyhow::Result<()> {
 
                     let (mut clock, mut a): (runtime::ActionRef<u32>, runtime::ActionRef<()>) = actions.partition_mut().unwrap();
 
-                    a.schedule(ctx, (), Some(Duration::milliseconds(3))); // out of order on purpose
-                    a.schedule(ctx, (), Some(Duration::milliseconds(1)));
-                    a.schedule(ctx, (), Some(Duration::milliseconds(5)));
+                    a.schedule(ctx, (), Some(Duration::milliseconds(3))).unwrap(); // out of order on purpose
+                    a.schedule(ctx, (), Some(Duration::milliseconds(1))).unwrap();
+                    a.schedule(ctx, (), Some(Duration::milliseconds(5))).unwrap();
 
                     // not scheduled on milli 1 (action is)
-                    clock.schedule(ctx, 2, Some(Duration::milliseconds(2)));
-                    clock.schedule(ctx, 3, Some(Duration::milliseconds(3)));
-                    clock.schedule(ctx, 4, Some(Duration::milliseconds(4)));
-                    clock.schedule(ctx, 5, Some(Duration::milliseconds(5)));
+                    clock.schedule(ctx, 2, Some(Duration::milliseconds(2))).unwrap();
+                    clock.schedule(ctx, 3, Some(Duration::milliseconds(3))).unwrap();
+                    clock.schedule(ctx, 4, Some(Duration::milliseconds(4))).unwrap();
+                    clock.schedule(ctx, 5, Some(Duration::milliseconds(5))).unwrap();
                     // not scheduled on milli 6 (timer is)
                 }
             ),
@@ -205,7 +205,7 @@ fn test_dependency_use_on_logical_action() -> anyhow::Result<()> {
         .with_fast_forward(true)
         .with_timeout(Duration::seconds(1));
     let mut sched = runtime::Scheduler::new(env, reaction_graph, config);
-    sched.event_loop();
+    sched.event_loop().unwrap();
 
     Ok(())
 }
@@ -424,7 +424,7 @@ fn test_dependency_use_accessible() -> anyhow::Result<()> {
 
     let config = runtime::Config::default().with_fast_forward(true);
     let mut sched = runtime::Scheduler::new(env, triggers, config);
-    sched.event_loop();
+    sched.event_loop().unwrap();
 
     Ok(())
 }
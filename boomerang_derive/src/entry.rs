@@ -0,0 +1,132 @@
+//! Implementation of the `#[boomerang::main]` attribute macro.
+
+use std::time::Duration;
+
+use darling::{ast::NestedMeta, FromMeta};
+use quote::{quote, ToTokens};
+
+use crate::util::{duration_quote, handle_duration};
+
+/// Scheduler configuration given in `#[boomerang::main(config(...))]`
+#[derive(Default, Debug, FromMeta)]
+pub struct ConfigAttr {
+    #[darling(default)]
+    pub fast_forward: Option<bool>,
+    #[darling(default)]
+    pub keep_alive: Option<bool>,
+    #[darling(default, map = "handle_duration")]
+    pub timeout: Option<Duration>,
+    #[darling(default)]
+    pub workers: Option<usize>,
+    #[darling(default)]
+    pub queue_size: Option<usize>,
+}
+
+impl ToTokens for ConfigAttr {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let fast_forward = self
+            .fast_forward
+            .map(|fast_forward| quote! { .with_fast_forward(#fast_forward) });
+        let keep_alive = self
+            .keep_alive
+            .map(|keep_alive| quote! { .with_keep_alive(#keep_alive) });
+        let timeout = self.timeout.map(|timeout| {
+            let timeout = duration_quote(&timeout);
+            quote! { .with_timeout(#timeout) }
+        });
+        let workers = self
+            .workers
+            .map(|workers| quote! { .with_workers(#workers) });
+        let queue_size = self
+            .queue_size
+            .map(|queue_size| quote! { .with_queue_size(#queue_size) });
+
+        tokens.extend(quote! {
+            ::boomerang::runtime::Config::default()
+                #fast_forward
+                #keep_alive
+                #timeout
+                #workers
+                #queue_size
+        });
+    }
+}
+
+/// Arguments to `#[boomerang::main(...)]`
+#[derive(Debug, FromMeta)]
+pub struct MainArgs {
+    /// The top-level reactor type to build and run.
+    pub reactor: syn::Path,
+    /// The name of the top-level reactor instance, defaults to the name of the annotated function.
+    #[darling(default)]
+    pub name: Option<String>,
+    #[darling(default)]
+    pub config: ConfigAttr,
+}
+
+pub struct Main {
+    args: MainArgs,
+    func: syn::ItemFn,
+}
+
+impl Main {
+    pub fn new(args: proc_macro2::TokenStream, func: syn::ItemFn) -> Result<Self, darling::Error> {
+        let args = MainArgs::from_list(&NestedMeta::parse_meta_list(args)?)?;
+
+        let sig = &func.sig;
+        if sig.asyncness.is_some() {
+            return Err(
+                darling::Error::custom("`boomerang::main` functions cannot be async")
+                    .with_span(&sig.asyncness),
+            );
+        }
+        if !sig.inputs.is_empty() {
+            return Err(darling::Error::custom(
+                "`boomerang::main` functions cannot take arguments",
+            )
+            .with_span(&sig.inputs));
+        }
+        if !sig.generics.params.is_empty() {
+            return Err(
+                darling::Error::custom("`boomerang::main` functions cannot be generic")
+                    .with_span(&sig.generics),
+            );
+        }
+
+        Ok(Self { args, func })
+    }
+}
+
+impl ToTokens for Main {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let syn::ItemFn {
+            attrs,
+            vis,
+            sig,
+            block,
+        } = &self.func;
+
+        let ident = &sig.ident;
+        let output = &sig.output;
+        let reactor = &self.args.reactor;
+        let name = self.args.name.clone().unwrap_or_else(|| ident.to_string());
+        let config = &self.args.config;
+
+        tokens.extend(quote! {
+            #(#attrs)*
+            #vis fn #ident() {
+                fn __boomerang_initial_state() #output #block
+
+                let config = #config;
+                if let Err(err) = ::boomerang_util::runner::build_and_run_reactor_with_config::<#reactor>(
+                    #name,
+                    __boomerang_initial_state(),
+                    config,
+                ) {
+                    ::std::eprintln!("Error: {err:?}");
+                    ::std::process::exit(1);
+                }
+            }
+        });
+    }
+}
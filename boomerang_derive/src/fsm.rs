@@ -0,0 +1,139 @@
+use darling::{ast, util, FromDeriveInput, FromMeta, FromVariant};
+use quote::{quote, ToTokens};
+use syn::{Generics, Ident};
+
+/// A state in a transition, either a variant name or the wildcard `*`.
+#[derive(Debug)]
+pub enum FromState {
+    Any,
+    Variant(Ident),
+}
+
+impl FromMeta for FromState {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        if value == "*" {
+            Ok(FromState::Any)
+        } else {
+            Ident::from_string(value).map(FromState::Variant)
+        }
+    }
+}
+
+#[derive(Debug, FromMeta)]
+pub struct TransitionAttr {
+    from: FromState,
+    event: Ident,
+    to: Ident,
+}
+
+#[derive(Debug, FromVariant)]
+pub struct FsmVariant {
+    ident: Ident,
+    fields: ast::Fields<util::Ignored>,
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(fsm), supports(enum_any))]
+pub struct FsmReceiver {
+    ident: Ident,
+    generics: Generics,
+    data: ast::Data<FsmVariant, util::Ignored>,
+
+    /// Type of the events driving the state machine
+    event: syn::Path,
+
+    /// The allowed transitions
+    #[darling(multiple, rename = "transition")]
+    transitions: Vec<TransitionAttr>,
+}
+
+pub struct Fsm {
+    ident: Ident,
+    generics: Generics,
+    event: syn::Path,
+    transitions: Vec<TransitionAttr>,
+}
+
+impl TryFrom<FsmReceiver> for Fsm {
+    type Error = darling::Error;
+
+    fn try_from(value: FsmReceiver) -> Result<Self, Self::Error> {
+        let variants = value
+            .data
+            .take_enum()
+            .ok_or(darling::Error::unsupported_shape(
+                "Only enums are supported",
+            ))?;
+
+        let mut errors = darling::Error::accumulator();
+        for transition in value.transitions.iter() {
+            if let FromState::Variant(from) = &transition.from {
+                if !variants.iter().any(|variant| &variant.ident == from) {
+                    errors.push(
+                        darling::Error::custom(format!("Unknown state `{from}`")).with_span(from),
+                    );
+                }
+            }
+
+            match variants
+                .iter()
+                .find(|variant| variant.ident == transition.to)
+            {
+                Some(variant) if variant.fields.is_unit() => {}
+                Some(_) => errors.push(
+                    darling::Error::custom("Transition targets must be unit variants")
+                        .with_span(&transition.to),
+                ),
+                None => errors.push(
+                    darling::Error::custom(format!("Unknown state `{}`", transition.to))
+                        .with_span(&transition.to),
+                ),
+            }
+        }
+        errors.finish()?;
+
+        Ok(Self {
+            ident: value.ident,
+            generics: value.generics,
+            event: value.event,
+            transitions: value.transitions,
+        })
+    }
+}
+
+impl ToTokens for Fsm {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let ident = &self.ident;
+        let event = &self.event;
+        let (impl_generics, type_generics, where_clause) = self.generics.split_for_impl();
+
+        let arms = self.transitions.iter().map(
+            |TransitionAttr {
+                 from,
+                 event: event_variant,
+                 to,
+             }| {
+                let from = match from {
+                    FromState::Any => quote! { _ },
+                    FromState::Variant(from) => quote! { Self::#from { .. } },
+                };
+                quote! { (#from, #event::#event_variant { .. }) => ::std::option::Option::Some(Self::#to), }
+            },
+        );
+
+        tokens.extend(quote! {
+            #[automatically_derived]
+            impl #impl_generics ::boomerang::runtime::StateMachine for #ident #type_generics #where_clause {
+                type Event = #event;
+
+                #[allow(unreachable_patterns)]
+                fn next_state(&self, event: &Self::Event) -> ::std::option::Option<Self> {
+                    match (self, event) {
+                        #(#arms)*
+                        _ => ::std::option::Option::None,
+                    }
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,145 @@
+use darling::{ast, util, FromDeriveInput, FromField};
+use quote::{quote, ToTokens};
+use syn::{Generics, Ident};
+
+#[derive(Debug, FromField)]
+pub struct InterfaceField {
+    ident: Option<Ident>,
+    ty: syn::Type,
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(supports(struct_named))]
+pub struct InterfaceReceiver {
+    ident: Ident,
+    generics: Generics,
+    data: ast::Data<util::Ignored, InterfaceField>,
+}
+
+pub struct Interface {
+    ident: Ident,
+    generics: Generics,
+    fields: Vec<(Ident, syn::Type)>,
+}
+
+impl TryFrom<InterfaceReceiver> for Interface {
+    type Error = darling::Error;
+
+    fn try_from(value: InterfaceReceiver) -> Result<Self, Self::Error> {
+        let fields = value
+            .data
+            .take_struct()
+            .unwrap()
+            .fields
+            .into_iter()
+            .map(|field| (field.ident.unwrap(), field.ty))
+            .collect();
+        Ok(Self {
+            ident: value.ident,
+            generics: value.generics,
+            fields,
+        })
+    }
+}
+
+impl ToTokens for Interface {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let ident = &self.ident;
+        let (impl_generics, type_generics, where_clause) = self.generics.split_for_impl();
+        let field_idents = self
+            .fields
+            .iter()
+            .map(|(ident, _)| ident)
+            .collect::<Vec<_>>();
+        let builds = self.fields.iter().map(|(ident, ty)| {
+            let name = ident.to_string();
+            quote! {
+                let #ident = <#ty as ::boomerang::builder::ReactorField>::build(#name, (), builder)?;
+            }
+        });
+        let bounds = self.fields.iter().map(|(ident, ty)| {
+            let name = ident.to_string();
+            quote! {
+                __R: ::boomerang::builder::NamedPort<{ ::boomerang::builder::port_name_id(#name) }, Port = #ty>
+            }
+        });
+        let ports = self.fields.iter().map(|(ident, _)| {
+            let name = ident.to_string();
+            quote! {
+                #ident: <__R as ::boomerang::builder::NamedPort<{ ::boomerang::builder::port_name_id(#name) }>>::port(reactor)
+            }
+        });
+        let mut of_generics = self.generics.clone();
+        of_generics.params.push(syn::parse_quote!(__R));
+        let (of_impl_generics, _, _) = of_generics.split_for_impl();
+        let of_predicates = where_clause.map(|where_clause| &where_clause.predicates);
+
+        let forwards = self.fields.iter().map(|(ident, ty)| {
+            quote! {
+                <#ty as ::boomerang::builder::InterfacePort>::forward(&outer.#ident, &inner.#ident, builder)?;
+            }
+        });
+
+        tokens.extend(quote! {
+            #[automatically_derived]
+            impl #impl_generics ::boomerang::builder::ReactorInterface for #ident #type_generics #where_clause {
+                fn build(
+                    builder: &mut ::boomerang::builder::ReactorBuilderState,
+                ) -> Result<Self, ::boomerang::builder::BuilderError> {
+                    #(#builds)*
+                    Ok(Self { #(#field_idents),* })
+                }
+
+                fn forward(
+                    outer: &Self,
+                    inner: &Self,
+                    builder: &mut ::boomerang::builder::ReactorBuilderState,
+                ) -> Result<(), ::boomerang::builder::BuilderError> {
+                    #(#forwards)*
+                    Ok(())
+                }
+            }
+
+            #[automatically_derived]
+            impl #of_impl_generics ::boomerang::builder::InterfaceOf<__R> for #ident #type_generics
+            where
+                #(#bounds,)*
+                #of_predicates
+            {
+                fn from_reactor(reactor: &__R) -> Self {
+                    Self { #(#ports),* }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interface() {
+        let input = syn::parse_quote! {
+            struct MotorInterface {
+                command: TypedPortKey<f32, Input>,
+                speed: TypedPortKey<f32, Output>,
+            }
+        };
+        let receiver = InterfaceReceiver::from_derive_input(&input).unwrap();
+        let interface = Interface::try_from(receiver).unwrap();
+        assert_eq!(
+            interface
+                .fields
+                .iter()
+                .map(|(ident, _)| ident.to_string())
+                .collect::<Vec<_>>(),
+            ["command", "speed"]
+        );
+
+        let unit = syn::parse_quote! {
+            struct Empty;
+        };
+        assert!(InterfaceReceiver::from_derive_input(&unit).is_err());
+    }
+}
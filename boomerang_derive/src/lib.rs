@@ -1,6 +1,9 @@
 use darling::FromDeriveInput;
 use quote::ToTokens;
 
+mod entry;
+mod fsm;
+mod interface;
 mod reaction;
 mod reactor;
 mod util;
@@ -18,6 +21,61 @@ pub fn derive_reaction(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
     .into()
 }
 
+/// Implement `StateMachine` for an enum from a declared transition table.
+///
+/// Each `transition` maps a state (or `*` for any state) and an event variant to a new state, which must be a unit
+/// variant. Any other combination is rejected at runtime by `StateMachine::transition`.
+///
+/// ```rust,ignore
+/// #[derive(Debug, ReactorFsm)]
+/// #[fsm(
+///     event = "Event",
+///     transition(from = "Idle", event = "Start", to = "Running"),
+///     transition(from = "Running", event = "Stop", to = "Idle"),
+///     transition(from = "*", event = "Fault", to = "Failed")
+/// )]
+/// enum Mode {
+///     Idle,
+///     Running,
+///     Failed,
+/// }
+/// ```
+#[proc_macro_derive(ReactorFsm, attributes(fsm))]
+pub fn derive_reactor_fsm(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    let fsm: Result<fsm::Fsm, _> =
+        fsm::FsmReceiver::from_derive_input(&ast).and_then(TryFrom::try_from);
+
+    match fsm {
+        Ok(fsm) => fsm.to_token_stream(),
+        Err(err) => err.write_errors(),
+    }
+    .into()
+}
+
+/// Implement `ReactorInterface` for a struct of port keys, shared by reactors declaring
+/// `#[reactor(implements = "...")]`.
+///
+/// ```rust,ignore
+/// #[derive(ReactorInterface)]
+/// struct MotorInterface {
+///     command: TypedPortKey<f32, Input>,
+///     speed: TypedPortKey<f32, Output>,
+/// }
+/// ```
+#[proc_macro_derive(ReactorInterface)]
+pub fn derive_reactor_interface(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    let interface: Result<interface::Interface, _> =
+        interface::InterfaceReceiver::from_derive_input(&ast).and_then(TryFrom::try_from);
+
+    match interface {
+        Ok(interface) => interface.to_token_stream(),
+        Err(err) => err.write_errors(),
+    }
+    .into()
+}
+
 #[proc_macro_derive(Reactor, attributes(reactor))]
 pub fn derive_reactor(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ast = syn::parse_macro_input!(input as syn::DeriveInput);
@@ -30,3 +88,29 @@ pub fn derive_reactor(input: proc_macro::TokenStream) -> proc_macro::TokenStream
     }
     .into()
 }
+
+/// Turn a function returning the initial state of a top-level reactor into a `main` function that builds and runs it.
+///
+/// The generated function initializes logging, parses the common command line arguments (see
+/// `boomerang_util::runner::build_and_run_reactor`) and runs the scheduler. The caller must depend on `boomerang_util`
+/// with the `runner` feature enabled.
+///
+/// ```rust,ignore
+/// #[boomerang::main(reactor = "HelloWorld", config(fast_forward = true, timeout = "1 sec"))]
+/// fn main() -> State {
+///     State { success: false }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn main(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let func = syn::parse_macro_input!(input as syn::ItemFn);
+
+    match entry::Main::new(args.into(), func) {
+        Ok(main) => main.to_token_stream(),
+        Err(err) => err.write_errors(),
+    }
+    .into()
+}
@@ -17,6 +17,8 @@ pub struct FromDefsImpl {
     action_idents: Vec<Ident>,
     port_idents: Vec<Ident>,
     port_mut_idents: Vec<Ident>,
+    /// Conditions under which the trigger body is skipped
+    skip_conditions: Vec<proc_macro2::TokenStream>,
 }
 
 impl FromDefsImpl {
@@ -34,8 +36,22 @@ impl FromDefsImpl {
         let mut action_idents = vec![];
         let mut port_idents = vec![];
         let mut port_mut_idents = vec![];
+        let mut skip_conditions = vec![];
 
         for field in fields.iter() {
+            let ident = field.ident.as_ref().unwrap();
+            if field.skip_if_absent {
+                if !matches!(&field.ty, Type::Path(_))
+                    || extract_path_ident(&field.ty).is_none_or(|ident| *ident != INPUT_REF)
+                {
+                    return Err(darling::Error::custom(
+                        "'skip_if_absent' is only valid for a single InputRef",
+                    )
+                    .with_span(&field.ty));
+                }
+                skip_conditions.push(quote! { reaction.#ident.is_none() });
+            }
+
             let field_inner_type = extract_path_ident(&field.ty).ok_or_else(|| {
                 darling::Error::custom("Unable to extract path ident ").with_span(&field.ty)
             })?;
@@ -59,7 +75,8 @@ impl FromDefsImpl {
                         return Err(darling::Error::custom(format!(
                             "Unexpected ref type: {:?}",
                             ty
-                        )));
+                        ))
+                        .with_span(&field.ty));
                     }
                 }
 
@@ -81,7 +98,8 @@ impl FromDefsImpl {
                         return Err(darling::Error::custom(format!(
                             "Unexpected mut ref type: {:?}",
                             ty
-                        )));
+                        ))
+                        .with_span(&field.ty));
                     }
                 }
 
@@ -89,6 +107,18 @@ impl FromDefsImpl {
                     INPUT_REF => {
                         initializer_idents.push(field.ident.clone().unwrap());
                         port_idents.push(field.ident.clone().unwrap());
+                        // With `requires_all`, every triggering input must be present
+                        if reaction_receiver.requires_all
+                            && !field.skip_if_absent
+                            && field.uses != Some(true)
+                        {
+                            skip_conditions.push(match &field.ty {
+                                Type::Array(_) => {
+                                    quote! { reaction.#ident.iter().any(|port| port.is_none()) }
+                                }
+                                _ => quote! { reaction.#ident.is_none() },
+                            });
+                        }
                     }
                     OUTPUT_REF => {
                         initializer_idents.push(field.ident.clone().unwrap());
@@ -106,10 +136,10 @@ impl FromDefsImpl {
                 },
 
                 _ => {
-                    return Err(darling::Error::custom(format!(
-                        "Not handling {:?}",
-                        field.ty
-                    )));
+                    return Err(
+                        darling::Error::custom(format!("Not handling {:?}", field.ty))
+                            .with_span(&field.ty),
+                    );
                 }
             }
         }
@@ -123,6 +153,7 @@ impl FromDefsImpl {
             action_idents,
             port_idents,
             port_mut_idents,
+            skip_conditions,
         })
     }
 }
@@ -179,6 +210,16 @@ impl ToTokens for FromDefsImpl {
             }
         });
 
+        let skip_conditions = &self.skip_conditions;
+        let skip = (!skip_conditions.is_empty()).then(|| {
+            quote! {
+                #[inline(always)]
+                fn skip(reaction: &Self::Marker<'_>) -> bool {
+                    #(#skip_conditions)||*
+                }
+            }
+        });
+
         let port_muts = (!port_mut_idents.is_empty()).then(|| {
             quote! {
                 let (#(#port_mut_idents,)*) = ports_mut.partition_mut()
@@ -204,6 +245,8 @@ impl ToTokens for FromDefsImpl {
 
                     #reaction_ident { #(#initializer_idents),* }
                 }
+
+                #skip
             }
         });
     }
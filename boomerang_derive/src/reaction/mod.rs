@@ -1,11 +1,14 @@
-use std::{collections::HashMap, hash::Hash};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
 
 use darling::{
     ast::{self},
     util, FromDeriveInput, FromField, FromMeta,
 };
-use quote::{quote, ToTokens};
-use syn::{Expr, GenericParam, Generics, Ident, Type};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{spanned::Spanned, Expr, GenericParam, Generics, Ident, Type};
 
 mod from_defs;
 mod reaction_field_inner;
@@ -46,11 +49,12 @@ impl FromMeta for TriggerAttr {
                 let meta: syn::Meta = syn::parse2(value.tokens.clone())?;
                 Self::from_meta(&meta)
             }
-            syn::Meta::NameValue(ref value) => value
-                .path
-                .segments
-                .first()
-                .map(|path| match path.ident.to_string().as_ref() {
+            syn::Meta::NameValue(ref value) => {
+                let ident = value.path.get_ident().ok_or_else(|| {
+                    darling::Error::custom("Expected `action = ...` or `port = ...`")
+                        .with_span(&value.path)
+                })?;
+                match ident.to_string().as_ref() {
                     "action" => {
                         let value = darling::FromMeta::from_expr(&value.value)?;
                         Ok(TriggerAttr::Action(value))
@@ -61,11 +65,11 @@ impl FromMeta for TriggerAttr {
                     }
                     __other => Err(darling::Error::unknown_field_with_alts(
                         __other,
-                        &["action", "timer", "port"],
+                        &["action", "port"],
                     )
-                    .with_span(&path.ident)),
-                })
-                .expect("oopsie"),
+                    .with_span(ident)),
+                }
+            }
         })
         .map_err(|e| e.with_span(item))
     }
@@ -85,6 +89,15 @@ pub struct ReactionField {
     effects: Option<bool>,
     uses: Option<bool>,
     path: Option<Expr>,
+    /// Skip the trigger body when this input is absent
+    #[darling(default)]
+    skip_if_absent: bool,
+}
+
+/// The reactor state fields used by a reaction, `state(fields = "a, b")`
+#[derive(Debug, Default, FromMeta)]
+pub struct StateAttr {
+    fields: String,
 }
 
 fn parse_bound(item: &syn::Meta) -> Result<syn::GenericParam, darling::Error> {
@@ -110,18 +123,53 @@ fn parse_bound(item: &syn::Meta) -> Result<syn::GenericParam, darling::Error> {
 #[darling(attributes(reaction), supports(struct_named, struct_unit))]
 pub struct ReactionReceiver {
     ident: Ident,
+    vis: syn::Visibility,
     generics: Generics,
     data: ast::Data<util::Ignored, ReactionField>,
 
     /// Type of the reactor
     reactor: syn::Type,
 
-    #[darling(default, multiple, rename = "bound", with = "parse_bound")]
+    #[darling(default, multiple, rename = "bound", with = parse_bound)]
     bounds: Vec<syn::GenericParam>,
 
     /// Connection definitions
     #[darling(default, multiple)]
     triggers: Vec<TriggerAttr>,
+
+    /// Only run the trigger body when all triggering input fields are present (AND instead of OR semantics)
+    #[darling(default)]
+    requires_all: bool,
+
+    /// Only borrow the given fields of the reactor state
+    #[darling(default)]
+    state: Option<StateAttr>,
+
+    /// The named phase of the tag the reaction runs in
+    #[darling(default)]
+    phase: Option<String>,
+}
+
+/// Mark the field at `path` as a trigger, or add a trigger-only field for it if the struct doesn't declare one.
+fn add_trigger(
+    fields_map: &mut HashMap<Expr, (usize, ReactionFieldInner)>,
+    last_idx: &mut usize,
+    path: &Expr,
+    trigger_field: impl FnOnce(Expr) -> ReactionFieldInner,
+) -> darling::Result<()> {
+    match fields_map.get_mut(path) {
+        Some((_, ReactionFieldInner::FieldDefined { triggers, .. })) => {
+            *triggers = true;
+        }
+        Some(_) => {
+            return Err(darling::Error::custom("Duplicate trigger").with_span(path));
+        }
+        None => {
+            *last_idx += 1;
+            fields_map.insert(path.clone(), (*last_idx, trigger_field(path.clone())));
+        }
+    }
+    Ok(())
 }
 
 pub struct Reaction {
@@ -135,6 +183,11 @@ pub struct Reaction {
     trigger_startup: bool,
     /// Whether the reaction has a shutdown trigger
     trigger_shutdown: bool,
+    vis: syn::Visibility,
+    /// The reactor state fields used by the reaction, if declared
+    state_fields: Option<Vec<Ident>>,
+    /// The named phase of the tag the reaction runs in, if any
+    phase: Option<String>,
 }
 
 impl TryFrom<ReactionReceiver> for Reaction {
@@ -189,49 +242,15 @@ impl TryFrom<ReactionReceiver> for Reaction {
         for trigger in value.triggers.iter() {
             match trigger {
                 TriggerAttr::Action(path) => {
-                    fields_map
-                        .entry(path.clone())
-                        .and_modify(|(_idx, field)| {
-                            if let ReactionFieldInner::FieldDefined {
-                                ref mut triggers, ..
-                            } = field
-                            {
-                                *triggers = true;
-                            } else {
-                                panic!("Trigger action path already used");
-                            }
-                        })
-                        .or_insert_with(|| {
-                            last_idx += 1;
-                            (
-                                last_idx,
-                                ReactionFieldInner::TriggerAction {
-                                    action: path.clone(),
-                                },
-                            )
-                        });
+                    add_trigger(&mut fields_map, &mut last_idx, path, |action| {
+                        ReactionFieldInner::TriggerAction { action }
+                    })?;
                 }
 
                 TriggerAttr::Port(path) => {
-                    fields_map
-                        .entry(path.clone())
-                        .and_modify(|(_idx, field)| {
-                            if let ReactionFieldInner::FieldDefined {
-                                ref mut triggers, ..
-                            } = field
-                            {
-                                *triggers = true;
-                            } else {
-                                panic!("Trigger port path already used");
-                            }
-                        })
-                        .or_insert_with(|| {
-                            last_idx += 1;
-                            (
-                                last_idx,
-                                ReactionFieldInner::TriggerPort { port: path.clone() },
-                            )
-                        });
+                    add_trigger(&mut fields_map, &mut last_idx, path, |port| {
+                        ReactionFieldInner::TriggerPort { port }
+                    })?;
                 }
 
                 _ => {}
@@ -251,6 +270,26 @@ impl TryFrom<ReactionReceiver> for Reaction {
         idx_fields.sort_by_key(|(idx, _)| *idx);
         let fields = idx_fields.into_iter().map(|(_, field)| field).collect();
 
+        let state_fields = value
+            .state
+            .map(|state| {
+                let fields = state
+                    .fields
+                    .split(',')
+                    .map(|field| syn::parse_str::<Ident>(field.trim()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| darling::Error::custom(format!("Invalid state field: {err}")))?;
+                let distinct = fields.iter().collect::<HashSet<_>>();
+                if fields.is_empty() || distinct.len() != fields.len() {
+                    return Err(darling::Error::custom(
+                        "Expected a list of distinct state fields",
+                    ));
+                }
+                Ok(fields)
+            })
+            .transpose()
+            .map_err(|err| err.with_span(&value.ident))?;
+
         Ok(Self {
             ident: value.ident,
             generics: value.generics,
@@ -260,6 +299,9 @@ impl TryFrom<ReactionReceiver> for Reaction {
             fromdefs,
             trigger_startup,
             trigger_shutdown,
+            vis: value.vis,
+            state_fields,
+            phase: value.phase,
         })
     }
 }
@@ -304,8 +346,82 @@ impl ToTokens for Reaction {
             }
         });
 
+        let state_fields = self.state_fields.as_ref().map(|state_fields| {
+            let state_ident = quote::format_ident!("{}State", ident);
+            let vis = &self.vis;
+            let field_types = state_fields
+                .iter()
+                .map(|field| quote::format_ident!("__{}", field.to_string().to_uppercase()))
+                .collect::<Vec<_>>();
+            let field_names = state_fields.iter().map(Ident::to_string).collect::<Vec<_>>();
+            let doc = format!("The reactor state fields borrowed by [`{ident}`].");
+            let state_name = state_ident.to_string();
+
+            let projection = quote! {
+                #[doc = #doc]
+                #vis struct #state_ident<'__state, #(#field_types),*> {
+                    #(pub #state_fields: &'__state mut #field_types),*
+                }
+
+                #[automatically_derived]
+                impl<'__state, #(#field_types: ::core::fmt::Debug),*> ::core::fmt::Debug
+                    for #state_ident<'__state, #(#field_types),*>
+                {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        f.debug_struct(#state_name)
+                            #(.field(#field_names, &self.#state_fields))*
+                            .finish()
+                    }
+                }
+
+                #[automatically_derived]
+                impl #impl_generics ::boomerang::runtime::Trigger<<#reactor as ::boomerang::builder::Reactor>::State>
+                    for #ident #type_generics #where_clause
+                {
+                    fn trigger(
+                        self,
+                        ctx: &mut ::boomerang::runtime::Context,
+                        state: &mut <#reactor as ::boomerang::builder::Reactor>::State,
+                    ) {
+                        let fields = #state_ident { #(#state_fields: &mut state.#state_fields),* };
+                        ::boomerang::runtime::TriggerFields::trigger(self, ctx, fields)
+                    }
+                }
+            };
+            let builder = quote! {
+                let __reaction = __reaction.with_state_fields([#(#field_names),*]);
+            };
+            (projection, builder)
+        });
+        let (state_projection, with_state_fields) = state_fields.unzip();
+
+        let with_phase = self.phase.as_ref().map(|phase| {
+            quote! {
+                let __reaction = __reaction.with_phase(#phase);
+            }
+        });
+
+        // Check the `Trigger` implementation first, so a reaction for the wrong reactor or state type is reported at
+        // the `reactor` attribute instead of deep inside the `ReactionAdapter` conversion.
+        let assert_trigger = {
+            let span = reactor.span();
+            let reaction_ident = Ident::new(&ident.to_string(), span);
+            let g = self
+                .generics
+                .const_params()
+                .map(|ty| &ty.ident)
+                .chain(self.generics.type_params().map(|ty| &ty.ident))
+                .map(|ident| Ident::new(&ident.to_string(), span));
+            quote_spanned! {span=>
+                let _ = <#reaction_ident::<#(#g),*> as ::boomerang::runtime::Trigger<
+                    <#reactor as ::boomerang::builder::Reactor>::State
+                >>::trigger;
+            }
+        };
+
         tokens.extend(quote! {
             #fromdefs_impl
+            #state_projection
 
             #[automatically_derived]
             impl #impl_generics ::boomerang::builder::Reaction<#reactor> for #ident #type_generics #where_clause {
@@ -318,6 +434,8 @@ impl ToTokens for Reaction {
                     ::boomerang::builder::BuilderError
                 >
                 {
+                    #assert_trigger
+
                     let __startup_action = builder.get_startup_action();
                     let __shutdown_action = builder.get_shutdown_action();
 
@@ -332,6 +450,8 @@ impl ToTokens for Reaction {
                     #trigger_startup
                     #trigger_shutdown
                     #(#struct_fields;)*
+                    #with_state_fields
+                    #with_phase
                     Ok(__reaction)
                 }
             }
@@ -357,11 +477,13 @@ mod tests {
     triggers(port = "child.y"),
     triggers(startup),
     triggers(shutdown),
+    phase = "sense",
 )]
 struct ReactionT;"#;
         let parsed: DeriveInput = syn::parse_str(input).unwrap();
         let receiver = ReactionReceiver::from_derive_input(&parsed).unwrap();
         assert_eq!(receiver.reactor, parse_quote! {Inner::Count<T>});
+        assert_eq!(receiver.phase.as_deref(), Some("sense"));
         assert_eq!(
             receiver.bounds,
             vec![
@@ -439,6 +561,22 @@ struct ReactionT<'a> {
         );
     }
 
+    #[test]
+    fn test_state_fields() {
+        let parse = |attr: &str| {
+            let input = format!("#[reaction(reactor = \"Foo\", {attr})] struct ReactionT;");
+            let parsed = syn::parse_str(&input).unwrap();
+            ReactionReceiver::from_derive_input(&parsed).and_then(Reaction::try_from)
+        };
+        let reaction = parse(r#"state(fields = "count, samples")"#).unwrap();
+        assert_eq!(
+            reaction.state_fields,
+            Some(vec![parse_quote! {count}, parse_quote! {samples}])
+        );
+        assert!(parse(r#"state(fields = "count, count")"#).is_err());
+        assert!(parse(r#"state(fields = "count, 0")"#).is_err());
+    }
+
     #[test]
     fn test_action_fields() {
         let input = r#"
@@ -79,7 +79,8 @@ impl TryFrom<ReactionField> for ReactionFieldInner {
 
                     (_, _, _, Some(true)) => Err(darling::Error::custom(
                         "Invalid Port field attributes: 'uses' is only valid for InputRef",
-                    )),
+                    )
+                    .with_span(&value.ty)),
 
                     _ => Err(darling::Error::custom("Invalid Port field.").with_span(&value.ty)),
                 }
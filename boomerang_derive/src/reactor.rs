@@ -12,6 +12,7 @@ use crate::util::{extract_path_ident, handle_duration, OptionalDuration};
 const TIMER_ACTION_KEY: &str = "TimerActionKey";
 const TYPED_ACTION_KEY: &str = "TypedActionKey";
 const TYPED_PORT_KEY: &str = "TypedPortKey";
+const ACCUM_PORT: &str = "AccumPort";
 const PHYSICAL_ACTION_KEY: &str = "PhysicalActionKey";
 
 #[derive(Default, Clone, Debug, FromMeta, PartialEq, Eq)]
@@ -155,7 +156,7 @@ impl TryFrom<FieldReceiver> for ReactorField {
                         })
                     }
 
-                    TYPED_PORT_KEY => Ok(ReactorField {
+                    TYPED_PORT_KEY | ACCUM_PORT => Ok(ReactorField {
                         ident,
                         name,
                         ty,
@@ -186,13 +187,19 @@ impl TryFrom<FieldReceiver> for ReactorField {
 #[derive(Debug, FromMeta, Eq, PartialEq)]
 pub struct ConnectionAttr {
     from: syn::Expr,
-    to: syn::Expr,
+    #[darling(default)]
+    to: Option<syn::Expr>,
+    /// Connect to a logical action instead of a port
+    #[darling(default)]
+    to_action: Option<syn::Expr>,
     #[darling(default)]
     broadcast: bool,
     #[darling(default, map = "handle_duration")]
     after: Option<Duration>,
     #[darling(default)]
     physical: bool,
+    #[darling(default)]
+    coalesce: Option<String>,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -204,9 +211,12 @@ struct PortDef {
 struct Connection {
     from: PortDef,
     to: PortDef,
+    /// Whether `to` is a logical action
+    to_action: bool,
     broadcast: bool,
     after: Option<Duration>,
     physical: bool,
+    coalesce: Option<syn::Ident>,
 }
 
 impl TryFrom<syn::Expr> for PortDef {
@@ -243,15 +253,54 @@ impl TryFrom<ConnectionAttr> for Connection {
     type Error = darling::Error;
 
     fn try_from(value: ConnectionAttr) -> Result<Self, Self::Error> {
+        let coalesce = value
+            .coalesce
+            .map(|coalesce| {
+                let variant = match coalesce.as_str() {
+                    "queue_all" => "QueueAll",
+                    "keep_latest" => "KeepLatest",
+                    "keep_first" => "KeepFirst",
+                    other => {
+                        return Err(darling::Error::custom(format!(
+                            "Unknown coalesce policy '{other}', expected one of 'queue_all', 'keep_latest', 'keep_first'"
+                        ))
+                        .with_span(&value.from))
+                    }
+                };
+                Ok(quote::format_ident!("{variant}"))
+            })
+            .transpose()?;
+
+        let (to, to_action) = match (value.to, value.to_action) {
+            (Some(to), None) => (to, false),
+            (None, Some(to_action)) => {
+                if value.physical || coalesce.is_some() {
+                    return Err(darling::Error::custom(
+                        "Connections to actions can't be physical or coalescing",
+                    )
+                    .with_span(&to_action));
+                }
+                (to_action, true)
+            }
+            _ => {
+                return Err(
+                    darling::Error::custom("Expected exactly one of 'to' and 'to_action'")
+                        .with_span(&value.from),
+                )
+            }
+        };
+
         let from = value.from.try_into()?;
-        let to = value.to.try_into()?;
+        let to = to.try_into()?;
 
         Ok(Self {
             from,
             to,
+            to_action,
             broadcast: value.broadcast,
             after: value.after,
             physical: value.physical,
+            coalesce,
         })
     }
 }
@@ -285,8 +334,26 @@ impl ToTokens for Connection {
         let after = OptionalDuration(self.after);
         let physical = self.physical;
 
-        tokens.extend(quote! {
-            __builder.connect_ports(#from_port #broadcast, #to_port, #after, #physical)?;
+        if self.to_action {
+            tokens.extend(quote! {
+                __builder.connect_ports_to_actions(#from_port #broadcast, #to_port, #after)?;
+            });
+            return;
+        }
+
+        tokens.extend(match &self.coalesce {
+            Some(coalesce) => quote! {
+                __builder.connect_ports_coalesced(
+                    #from_port #broadcast,
+                    #to_port,
+                    #after,
+                    #physical,
+                    ::boomerang::builder::Coalesce::#coalesce,
+                )?;
+            },
+            None => quote! {
+                __builder.connect_ports(#from_port #broadcast, #to_port, #after, #physical)?;
+            },
         });
     }
 }
@@ -299,13 +366,27 @@ pub struct ReactorReceiver {
     // pub attrs: Vec<syn::Attribute>,
     pub data: ast::Data<darling::util::Ignored, FieldReceiver>,
     /// Type of the reactor state
-    state: syn::Expr,
+    #[darling(default)]
+    state: Option<syn::Expr>,
     /// Reaction declarations
     #[darling(default, multiple, rename = "reaction")]
     pub reactions: Vec<syn::Type>,
     /// Connection declarations
     #[darling(default, multiple, rename = "connection")]
     pub connections: Vec<ConnectionAttr>,
+    /// Generate a test asserting the built topology against an (optional) inline snapshot. The test builds the
+    /// reactor with `topology_state`, which requires `State: Default` if not given.
+    #[darling(default)]
+    pub emit_topology_test: Option<darling::util::Override<String>>,
+    /// The reactor state to build the topology test with, `Default::default()` if not given
+    #[darling(default)]
+    pub topology_state: Option<syn::Expr>,
+    /// Interfaces satisfied by the ports of the reactor
+    #[darling(default, multiple, rename = "implements")]
+    pub interfaces: Vec<syn::Path>,
+    /// Generate a `Debug` impl and a method dumping the built subtree
+    #[darling(default)]
+    pub debug: bool,
 }
 
 pub struct Reactor {
@@ -315,6 +396,10 @@ pub struct Reactor {
     fields: Vec<ReactorField>,
     reactions: Vec<syn::Type>,
     connections: Vec<Connection>,
+    /// The expected topology snapshot and the state to build with, if a topology test should be generated
+    topology_test: Option<(String, syn::Expr)>,
+    interfaces: Vec<syn::Path>,
+    debug: bool,
 }
 
 impl TryFrom<ReactorReceiver> for Reactor {
@@ -338,13 +423,47 @@ impl TryFrom<ReactorReceiver> for Reactor {
             .map(Connection::try_from)
             .collect::<Result<Vec<_>, _>>()?;
 
+        if value.emit_topology_test.is_none() {
+            if let Some(topology_state) = &value.topology_state {
+                return Err(
+                    darling::Error::custom("topology_state requires emit_topology_test")
+                        .with_span(topology_state),
+                );
+            }
+        }
+        let topology_test = value
+            .emit_topology_test
+            .map(|snapshot| {
+                if !value.generics.params.is_empty() {
+                    return Err(darling::Error::custom(
+                        "emit_topology_test is not supported on generic Reactors",
+                    )
+                    .with_span(&value.generics));
+                }
+                let state = value
+                    .topology_state
+                    .unwrap_or_else(|| syn::parse_quote! { ::core::default::Default::default() });
+                Ok((snapshot.unwrap_or_default(), state))
+            })
+            .transpose()?;
+
+        let state = value.state.ok_or_else(|| {
+            darling::Error::custom(
+                "Missing the reactor state type, e.g. `#[reactor(state = \"()\")]`",
+            )
+            .with_span(&value.ident)
+        })?;
+
         Ok(Self {
             ident: value.ident,
-            state: value.state,
+            state,
             generics: value.generics,
             fields,
             reactions: value.reactions,
             connections,
+            topology_test,
+            interfaces: value.interfaces,
+            debug: value.debug,
         })
     }
 }
@@ -388,6 +507,112 @@ impl ToTokens for Reactor {
                 }
             }
         });
+
+        if !self.interfaces.is_empty() {
+            // Expose the ports by name, for the interfaces to pick theirs
+            for field in &self.fields {
+                if field.kind != ReactorFieldKind::Port {
+                    continue;
+                }
+                let field_ident = &field.ident;
+                let name = field_ident.to_string();
+                let ty = &field.ty;
+                tokens.extend(quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::boomerang::builder::NamedPort<{ ::boomerang::builder::port_name_id(#name) }>
+                        for #ident #type_generics #where_clause
+                    {
+                        type Port = #ty;
+
+                        fn port(&self) -> Self::Port {
+                            self.#field_ident
+                        }
+                    }
+                });
+            }
+
+            // Check at compile time that the ports match each interface
+            let interfaces = &self.interfaces;
+            tokens.extend(quote! {
+                const _: () = {
+                    #[allow(dead_code)]
+                    fn __assert_implements #impl_generics () #where_clause {
+                        fn __implements<__R: ::boomerang::builder::Implements<__I>, __I: ::boomerang::builder::ReactorInterface>() {}
+                        #(__implements::<#ident #type_generics, #interfaces>();)*
+                    }
+                };
+            });
+        }
+
+        if self.debug {
+            let name = ident.to_string();
+            let debug_fields = self.fields.iter().map(|field| {
+                let field_ident = &field.ident;
+                let field_name = field_ident.to_string();
+                match field.kind {
+                    // Child reactors don't necessarily implement `Debug`, so only their type is shown.
+                    ReactorFieldKind::Child { .. } => {
+                        let ty = &field.ty;
+                        quote! {
+                            .field(#field_name, &::core::format_args!("{}", ::core::any::type_name::<#ty>()))
+                        }
+                    }
+                    _ => quote! { .field(#field_name, &self.#field_ident) },
+                }
+            });
+            tokens.extend(quote! {
+                #[automatically_derived]
+                impl #impl_generics ::core::fmt::Debug for #ident #type_generics #where_clause {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        f.debug_struct(#name)
+                            #(#debug_fields)*
+                            .finish()
+                    }
+                }
+
+                #[automatically_derived]
+                impl #impl_generics #ident #type_generics #where_clause {
+                    /// Build this Reactor on its own as `main` and describe the constructed subtree: the ports, actions
+                    /// and reactions of each Reactor, followed by the connections between the ports.
+                    ///
+                    /// See [`::boomerang::builder::EnvBuilder::topology_summary`] for the format.
+                    pub fn dump_topology(
+                        state: <Self as ::boomerang::builder::Reactor>::State,
+                    ) -> Result<String, ::boomerang::builder::BuilderError> {
+                        let mut env_builder = ::boomerang::builder::EnvBuilder::new();
+                        let _ = <Self as ::boomerang::builder::Reactor>::build(
+                            "main",
+                            state,
+                            None,
+                            None,
+                            &mut env_builder,
+                        )?;
+                        env_builder.topology_summary()
+                    }
+                }
+            });
+        }
+
+        if let Some((snapshot, topology_state)) = &self.topology_test {
+            let test_ident = quote::format_ident!("__topology_test_{}", ident);
+            tokens.extend(quote! {
+                #[cfg(test)]
+                #[test]
+                #[allow(non_snake_case)]
+                fn #test_ident() {
+                    let mut env_builder = ::boomerang::builder::EnvBuilder::new();
+                    let _ = <#ident as ::boomerang::builder::Reactor>::build(
+                        "main",
+                        #topology_state,
+                        None,
+                        None,
+                        &mut env_builder,
+                    )
+                    .expect("Failed to build the Reactor");
+                    env_builder.assert_topology(#snapshot);
+                }
+            });
+        }
     }
 }
 
@@ -424,6 +649,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_emit_topology_test() {
+        let input = r#"
+#[derive(Reactor)]
+#[reactor(state = "()", emit_topology_test)]
+struct Test {}"#;
+        let parsed = syn::parse_str(input).unwrap();
+        let receiver = ReactorReceiver::from_derive_input(&parsed).unwrap();
+        let reactor = Reactor::try_from(receiver).unwrap();
+        assert_eq!(
+            reactor.topology_test,
+            Some((
+                String::new(),
+                parse_quote! { ::core::default::Default::default() }
+            ))
+        );
+        assert!(!reactor.debug);
+
+        let input = r#"
+#[derive(Reactor)]
+#[reactor(state = "u32", emit_topology_test, topology_state = 42)]
+struct Test {}"#;
+        let parsed = syn::parse_str(input).unwrap();
+        let receiver = ReactorReceiver::from_derive_input(&parsed).unwrap();
+        let reactor = Reactor::try_from(receiver).unwrap();
+        assert_eq!(reactor.topology_test.unwrap().1, parse_quote! { 42 });
+
+        let input = r#"
+#[derive(Reactor)]
+#[reactor(state = "u32", topology_state = 42)]
+struct Test {}"#;
+        let parsed = syn::parse_str(input).unwrap();
+        let receiver = ReactorReceiver::from_derive_input(&parsed).unwrap();
+        assert!(Reactor::try_from(receiver).is_err());
+
+        let input = r#"
+#[derive(Reactor)]
+#[reactor(state = "()", emit_topology_test = "reactor main")]
+struct Test<T> {}"#;
+        let parsed = syn::parse_str(input).unwrap();
+        let receiver = ReactorReceiver::from_derive_input(&parsed).unwrap();
+        assert!(Reactor::try_from(receiver).is_err());
+    }
+
+    #[test]
+    fn test_debug() {
+        let input = r#"
+#[derive(Reactor)]
+#[reactor(state = "()", debug)]
+struct Test<T> {
+    out: TypedPortKey<T, Output>,
+    #[reactor(child = "()")]
+    child: Child,
+}"#;
+        let parsed = syn::parse_str(input).unwrap();
+        let receiver = ReactorReceiver::from_derive_input(&parsed).unwrap();
+        let reactor = Reactor::try_from(receiver).unwrap();
+        assert!(reactor.debug);
+
+        let tokens = reactor.to_token_stream().to_string();
+        assert!(tokens.contains("fn dump_topology"));
+        assert!(tokens.contains("type_name :: < Child >"));
+    }
+
     #[test]
     fn test_struct_attrs() {
         let input = r#"
@@ -432,7 +721,7 @@ mod tests {
     state = "MyType::Foo::<f32>",
     connection(from = "a.b", to = "c.d"),
     connection(from = "inp", to = "gain.inp"),
-    connection(from = "gain.out", to = "out", after = "1 usec", physical = true),
+    connection(from = "gain.out", to = "out", after = "1 usec", physical = true, coalesce = "keep_latest"),
     reaction = "Reaction1",
     reaction = "Reaction2<WIDTH>"
 )]
@@ -442,35 +731,41 @@ struct Test {}"#;
         let receiver = ReactorReceiver::from_derive_input(&parsed).unwrap();
 
         assert_eq!(receiver.ident.to_string(), "Test");
-        assert_eq!(receiver.state, parse_quote! {MyType::Foo::<f32>});
+        assert_eq!(receiver.state, Some(parse_quote! {MyType::Foo::<f32>}));
         assert_eq!(
             receiver.connections[0],
             ConnectionAttr {
                 from: parse_quote! {a.b},
-                to: parse_quote! {c.d},
+                to: Some(parse_quote! {c.d}),
+                to_action: None,
                 broadcast: false,
                 after: None,
                 physical: false,
+                coalesce: None,
             }
         );
         assert_eq!(
             receiver.connections[1],
             ConnectionAttr {
                 from: parse_quote! {inp},
-                to: parse_quote! {gain.inp},
+                to: Some(parse_quote! {gain.inp}),
+                to_action: None,
                 broadcast: false,
                 after: None,
                 physical: false,
+                coalesce: None,
             }
         );
         assert_eq!(
             receiver.connections[2],
             ConnectionAttr {
                 from: parse_quote! {gain.out},
-                to: parse_quote! {out},
+                to: Some(parse_quote! {out}),
+                to_action: None,
                 broadcast: false,
                 after: Some(Duration::from_micros(1)),
                 physical: true,
+                coalesce: Some("keep_latest".to_owned()),
             }
         );
         assert_eq!(receiver.reactions.len(), 2);
@@ -478,6 +773,29 @@ struct Test {}"#;
         assert_eq!(receiver.reactions[1], parse_quote! {Reaction2<WIDTH>});
     }
 
+    #[test]
+    fn test_connection_to_action() {
+        let connection = |attr: &str| {
+            let input =
+                format!("#[derive(Reactor)]\n#[reactor(state = \"()\", {attr})]\nstruct Test {{}}");
+            let parsed = syn::parse_str(&input).unwrap();
+            let mut receiver = ReactorReceiver::from_derive_input(&parsed).unwrap();
+            Connection::try_from(receiver.connections.remove(0))
+        };
+
+        let good =
+            connection(r#"connection(from = "a.out", to_action = "b.act", after = "1 usec")"#);
+        assert!(good.is_ok_and(|connection| connection.to_action));
+        assert!(connection(r#"connection(from = "a.out")"#).is_err());
+        assert!(
+            connection(r#"connection(from = "a.out", to = "b.inp", to_action = "b.act")"#).is_err()
+        );
+        assert!(
+            connection(r#"connection(from = "a.out", to_action = "b.act", physical = true)"#)
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_actions() {
         let good_input = r#"
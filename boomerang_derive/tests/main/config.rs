@@ -0,0 +1,34 @@
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "u32", reaction = "ReactionTick", reaction = "ReactionShutdown")]
+struct Counter {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Counter", triggers(action = "tick"))]
+struct ReactionTick;
+
+impl runtime::Trigger<u32> for ReactionTick {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut u32) {
+        *state += 1;
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Counter", triggers(shutdown))]
+struct ReactionShutdown;
+
+impl runtime::Trigger<u32> for ReactionShutdown {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut u32) {
+        assert_eq!(ctx.get_elapsed_logical_time(), Duration::milliseconds(5));
+        assert_eq!(*state, 106);
+    }
+}
+
+#[boomerang::main(reactor = "Counter", config(fast_forward = true, timeout = "5 msec"))]
+fn main() -> u32 {
+    100
+}
@@ -0,0 +1,9 @@
+//! Checks the errors of the derive macros for common mistakes, see the `ui` directory, and that the programs in the
+//! `main` directory generated with `#[boomerang::main]` build and run successfully.
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+    t.pass("tests/main/*.rs");
+}
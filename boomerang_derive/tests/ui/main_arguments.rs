@@ -0,0 +1,10 @@
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct Foo {}
+
+#[boomerang::main(reactor = "Foo")]
+fn run(_count: u32) {}
+
+fn main() {}
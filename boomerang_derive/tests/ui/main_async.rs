@@ -0,0 +1,10 @@
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct Foo {}
+
+#[boomerang::main(reactor = "Foo")]
+async fn run() {}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionStartup")]
+struct Foo {}
+
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct Bar {}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Bar", triggers(startup))]
+struct ReactionStartup;
+
+impl runtime::Trigger<()> for ReactionStartup {
+    fn trigger(self, _ctx: &mut runtime::Context, _state: &mut ()) {}
+}
+
+fn main() {}
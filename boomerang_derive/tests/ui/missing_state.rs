@@ -0,0 +1,15 @@
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(reaction = "ReactionStartup")]
+struct Foo {}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Foo", triggers(startup))]
+struct ReactionStartup;
+
+impl runtime::Trigger<()> for ReactionStartup {
+    fn trigger(self, _ctx: &mut runtime::Context, _state: &mut ()) {}
+}
+
+fn main() {}
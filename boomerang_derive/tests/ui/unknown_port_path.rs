@@ -0,0 +1,20 @@
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionIn")]
+struct Sink {
+    inp: TypedPortKey<u32, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink")]
+struct ReactionIn<'a> {
+    #[reaction(path = "input")]
+    _inp: runtime::InputRef<'a, u32>,
+}
+
+impl runtime::Trigger<()> for ReactionIn<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, _state: &mut ()) {}
+}
+
+fn main() {}
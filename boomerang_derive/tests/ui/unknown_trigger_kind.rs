@@ -0,0 +1,18 @@
+use boomerang::prelude::*;
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionTick")]
+struct Clock {
+    #[reactor(timer(period = "1 sec"))]
+    tick: TimerActionKey,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Clock", triggers(timer = "tick"))]
+struct ReactionTick;
+
+impl runtime::Trigger<()> for ReactionTick {
+    fn trigger(self, _ctx: &mut runtime::Context, _state: &mut ()) {}
+}
+
+fn main() {}
@@ -0,0 +1,139 @@
+#![allow(dead_code)]
+
+//! Benchmarks of batching ready tags in fast-forward mode, see [`runtime::Config::with_batch_tags`].
+//!
+//! The synthetic model has a single fast timer producing a tag every usec, next to a bank of slow timers whose ports
+//! are idle most of the time. Each tag only runs a couple of cheap reactions, so the run time is dominated by the
+//! per-tag overhead of the event loop.
+
+use boomerang::prelude::*;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+/// The logical time to run the model for, in usec, i.e. the number of fast timer ticks.
+const TICKS: i64 = 10_000;
+
+#[derive(Reactor)]
+#[reactor(state = "u64", reaction = "ReactionFast")]
+struct Fast {
+    #[reactor(timer(period = "1 usec"))]
+    tick: TimerActionKey,
+    out: TypedPortKey<u64, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Fast", triggers(action = "tick"))]
+struct ReactionFast<'a> {
+    out: runtime::OutputRef<'a, u64>,
+}
+
+impl runtime::Trigger<u64> for ReactionFast<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u64) {
+        *self.out = Some(*state);
+        *state += 1;
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "u64", reaction = "ReactionSlow")]
+struct Slow {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    out: TypedPortKey<u64, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Slow", triggers(action = "tick"))]
+struct ReactionSlow<'a> {
+    out: runtime::OutputRef<'a, u64>,
+}
+
+impl runtime::Trigger<u64> for ReactionSlow<'_> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u64) {
+        *self.out = Some(*state);
+        *state += 1;
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "u64", reaction = "ReactionSink")]
+struct Sink {
+    inp: TypedPortKey<u64, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink")]
+struct ReactionSink<'a> {
+    inp: runtime::InputRef<'a, u64>,
+}
+
+impl runtime::Trigger<u64> for ReactionSink<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut u64) {
+        *state += self.inp.unwrap_or_default();
+    }
+}
+
+/// A fast timer next to a bank of `SLOW` slow timers, each connected to its own sink.
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "fast.out", to = "fast_sink.inp"),
+    connection(from = "slow.out", to = "slow_sinks.inp")
+)]
+struct Timers<const SLOW: usize> {
+    #[reactor(child = "0")]
+    fast: Fast,
+    #[reactor(child = "0")]
+    fast_sink: Sink,
+    #[reactor(child = "0")]
+    slow: [Slow; SLOW],
+    #[reactor(child = "0")]
+    slow_sinks: [Sink; SLOW],
+}
+
+fn run(env: runtime::Env, graph: runtime::ReactionGraph, batch_tags: Option<usize>) {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::microseconds(TICKS - 1));
+    let config = match batch_tags {
+        Some(batch_tags) => config.with_batch_tags(batch_tags),
+        None => config,
+    };
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+}
+
+fn bench_timers<const SLOW: usize>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("timers_{SLOW}"));
+    group.sample_size(20);
+    group.throughput(Throughput::Elements(TICKS as u64));
+    for batch_tags in [None, Some(16), Some(256)] {
+        let param = batch_tags.map_or("unbatched".to_owned(), |b| b.to_string());
+        group.bench_with_input(
+            BenchmarkId::from_parameter(param),
+            &batch_tags,
+            |b, &batch_tags| {
+                b.iter_batched(
+                    || {
+                        let mut env_builder = EnvBuilder::new();
+                        let _reactor =
+                            Timers::<SLOW>::build("timers", (), None, None, &mut env_builder)
+                                .unwrap();
+                        let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+                        (env, graph)
+                    },
+                    |(env, graph)| run(env, graph, batch_tags),
+                    BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+fn timers(c: &mut Criterion) {
+    bench_timers::<16>(c);
+    bench_timers::<256>(c);
+}
+
+criterion_group!(benches, timers);
+criterion_main!(benches);
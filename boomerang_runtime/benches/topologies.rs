@@ -0,0 +1,377 @@
+#![allow(dead_code)]
+
+//! Benchmarks of the scheduler on a set of standard reaction graph topologies.
+//!
+//! Each topology is driven by a periodic timer and runs in fast-forward mode for a fixed number of ticks. Every
+//! `Work` reaction spins for `ITERS` iterations to simulate a compute load. Run with `--features parallel` to compare
+//! against the single-threaded scheduler, and to compare the [`runtime::Config::with_parallel_threshold`] settings on
+//! narrow levels.
+
+use boomerang::prelude::*;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+/// The number of timer ticks to run each topology for.
+const TICKS: i64 = 100;
+
+#[derive(Reactor)]
+#[reactor(state = "u64", reaction = "ReactionTick<WIDTH>")]
+struct Source<const WIDTH: usize> {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    out: [TypedPortKey<u64, Output>; WIDTH],
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Source<WIDTH>", triggers(action = "tick"))]
+struct ReactionTick<'a, const WIDTH: usize> {
+    out: [runtime::OutputRef<'a, u64>; WIDTH],
+}
+
+impl<const WIDTH: usize> runtime::Trigger<u64> for ReactionTick<'_, WIDTH> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u64) {
+        for out in self.out.iter_mut() {
+            **out = Some(*state);
+        }
+        *state += 1;
+    }
+}
+
+/// Emits on one of its `WIDTH` outputs per tick, in turn.
+#[derive(Reactor)]
+#[reactor(state = "u64", reaction = "ReactionRotate<WIDTH>")]
+struct Rotate<const WIDTH: usize> {
+    #[reactor(timer(period = "1 msec"))]
+    tick: TimerActionKey,
+    out: [TypedPortKey<u64, Output>; WIDTH],
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Rotate<WIDTH>", triggers(action = "tick"))]
+struct ReactionRotate<'a, const WIDTH: usize> {
+    out: [runtime::OutputRef<'a, u64>; WIDTH],
+}
+
+impl<const WIDTH: usize> runtime::Trigger<u64> for ReactionRotate<'_, WIDTH> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u64) {
+        *self.out[*state as usize % WIDTH] = Some(*state);
+        *state += 1;
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "()", reaction = "ReactionWork<ITERS>")]
+struct Work<const ITERS: usize> {
+    inp: TypedPortKey<u64, Input>,
+    out: TypedPortKey<u64, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Work<ITERS>")]
+struct ReactionWork<'a, const ITERS: usize> {
+    inp: runtime::InputRef<'a, u64>,
+    out: runtime::OutputRef<'a, u64>,
+}
+
+impl<const ITERS: usize> runtime::Trigger<()> for ReactionWork<'_, ITERS> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+        let mut acc = self.inp.unwrap_or_default();
+        for i in 0..ITERS as u64 {
+            acc = std::hint::black_box(acc.wrapping_mul(31).wrapping_add(i));
+        }
+        *self.out = Some(acc);
+    }
+}
+
+#[derive(Reactor)]
+#[reactor(state = "u64", reaction = "ReactionSink<WIDTH>")]
+struct Sink<const WIDTH: usize> {
+    inp: [TypedPortKey<u64, Input>; WIDTH],
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Sink<WIDTH>")]
+struct ReactionSink<'a, const WIDTH: usize> {
+    inp: [runtime::InputRef<'a, u64>; WIDTH],
+}
+
+impl<const WIDTH: usize> runtime::Trigger<u64> for ReactionSink<'_, WIDTH> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut u64) {
+        *state += self.inp.iter().filter(|inp| inp.is_some()).count() as u64;
+    }
+}
+
+/// A linear chain of `Work` reactors, each one level deeper than the last.
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to = "w0.inp"),
+    connection(from = "w0.out", to = "w1.inp"),
+    connection(from = "w1.out", to = "w2.inp"),
+    connection(from = "w2.out", to = "w3.inp"),
+    connection(from = "w3.out", to = "w4.inp"),
+    connection(from = "w4.out", to = "w5.inp"),
+    connection(from = "w5.out", to = "w6.inp"),
+    connection(from = "w6.out", to = "w7.inp"),
+    connection(from = "w7.out", to = "sink.inp")
+)]
+struct Chain<const ITERS: usize> {
+    #[reactor(child = "0")]
+    source: Source<1>,
+    #[reactor(child = "()")]
+    w0: Work<ITERS>,
+    #[reactor(child = "()")]
+    w1: Work<ITERS>,
+    #[reactor(child = "()")]
+    w2: Work<ITERS>,
+    #[reactor(child = "()")]
+    w3: Work<ITERS>,
+    #[reactor(child = "()")]
+    w4: Work<ITERS>,
+    #[reactor(child = "()")]
+    w5: Work<ITERS>,
+    #[reactor(child = "()")]
+    w6: Work<ITERS>,
+    #[reactor(child = "()")]
+    w7: Work<ITERS>,
+    #[reactor(child = "0")]
+    sink: Sink<1>,
+}
+
+/// A single source fanning out to a bank of `WIDTH` parallel `Work` reactors, joined again in a single sink.
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to = "work.inp"),
+    connection(from = "work.out", to = "sink.inp")
+)]
+struct Diamond<const WIDTH: usize, const ITERS: usize> {
+    #[reactor(child = "0")]
+    source: Source<WIDTH>,
+    #[reactor(child = "()")]
+    work: [Work<ITERS>; WIDTH],
+    #[reactor(child = "0")]
+    sink: Sink<WIDTH>,
+}
+
+/// Like [`Diamond`], with a single path of the `WIDTH` active per tick, so most ports stay idle.
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to = "work.inp"),
+    connection(from = "work.out", to = "sink.inp")
+)]
+struct Sparse<const WIDTH: usize> {
+    #[reactor(child = "0")]
+    source: Rotate<WIDTH>,
+    #[reactor(child = "()")]
+    work: [Work<100>; WIDTH],
+    #[reactor(child = "0")]
+    sink: Sink<WIDTH>,
+}
+
+/// A source -> work -> work -> sink pipeline, used as a bank member in [`Banks`].
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to = "w0.inp"),
+    connection(from = "w0.out", to = "w1.inp"),
+    connection(from = "w1.out", to = "sink.inp")
+)]
+struct Pipeline<const ITERS: usize> {
+    #[reactor(child = "0")]
+    source: Source<1>,
+    #[reactor(child = "()")]
+    w0: Work<ITERS>,
+    #[reactor(child = "()")]
+    w1: Work<ITERS>,
+    #[reactor(child = "0")]
+    sink: Sink<1>,
+}
+
+/// A bank of `WIDTH` fully independent pipelines.
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct Banks<const WIDTH: usize, const ITERS: usize> {
+    #[reactor(child = "()")]
+    pipelines: [Pipeline<ITERS>; WIDTH],
+}
+
+/// A single source broadcasting to a bank of `WIDTH` cheap `Work` reactors through a delayed connection, which is
+/// implemented by a single pair of connection reactions however wide the fan-out.
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    connection(from = "source.out", to = "work.inp", after = "1 msec", broadcast)
+)]
+struct Broadcast<const WIDTH: usize> {
+    #[reactor(child = "0")]
+    source: Source<1>,
+    #[reactor(child = "()")]
+    work: [Work<100>; WIDTH],
+}
+
+/// A reactor with only startup and shutdown reactions, used as a bank member in [`Lifecycle`].
+#[derive(Reactor)]
+#[reactor(
+    state = "()",
+    reaction = "ReactionStartup",
+    reaction = "ReactionShutdown"
+)]
+struct Idle {}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Idle", triggers(startup))]
+struct ReactionStartup;
+
+impl runtime::Trigger<()> for ReactionStartup {
+    fn trigger(self, _ctx: &mut runtime::Context, _state: &mut ()) {}
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "Idle", triggers(shutdown))]
+struct ReactionShutdown;
+
+impl runtime::Trigger<()> for ReactionShutdown {
+    fn trigger(self, _ctx: &mut runtime::Context, _state: &mut ()) {}
+}
+
+/// A bank of `WIDTH` reactors reacting only to startup and shutdown.
+#[derive(Reactor)]
+#[reactor(state = "()")]
+struct Lifecycle<const WIDTH: usize> {
+    #[reactor(child = "()")]
+    idle: [Idle; WIDTH],
+}
+
+fn run(env: runtime::Env, graph: runtime::ReactionGraph, parallel_threshold: usize) {
+    let config = runtime::Config::default()
+        .with_fast_forward(true)
+        .with_timeout(Duration::milliseconds(TICKS - 1))
+        .with_parallel_threshold(parallel_threshold);
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop().unwrap();
+}
+
+fn bench_topology<R: Reactor<State = ()>>(c: &mut Criterion, group_name: &str, param: usize) {
+    let mut group = c.benchmark_group(group_name);
+    group.sample_size(20);
+    group.throughput(Throughput::Elements(TICKS as u64));
+    group.bench_with_input(BenchmarkId::from_parameter(param), &param, |b, _| {
+        b.iter_batched(
+            || {
+                let mut env_builder = EnvBuilder::new();
+                let _reactor = R::build(group_name, (), None, None, &mut env_builder).unwrap();
+                let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+                (env, graph)
+            },
+            |(env, graph)| run(env, graph, runtime::DEFAULT_PARALLEL_THRESHOLD),
+            BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
+
+fn chain(c: &mut Criterion) {
+    bench_topology::<Chain<10_000>>(c, "chain", 8);
+}
+
+fn diamond(c: &mut Criterion) {
+    bench_topology::<Diamond<4, 10_000>>(c, "diamond", 4);
+    bench_topology::<Diamond<16, 10_000>>(c, "diamond", 16);
+}
+
+fn fan_out(c: &mut Criterion) {
+    // Wide levels of cheap reactions stress the per-level dispatch overhead.
+    bench_topology::<Diamond<64, 100>>(c, "fan_out", 64);
+    bench_topology::<Diamond<256, 100>>(c, "fan_out", 256);
+}
+
+fn sparse(c: &mut Criterion) {
+    // Wide graphs with few ports set per tag stress the per-tag cleanup of the ports.
+    bench_topology::<Sparse<256>>(c, "sparse", 256);
+    bench_topology::<Sparse<1024>>(c, "sparse", 1024);
+}
+
+fn broadcast(c: &mut Criterion) {
+    // A 1 -> 1000 fan-out through a delayed connection.
+    bench_topology::<Broadcast<1000>>(c, "broadcast", 1000);
+}
+
+fn banks(c: &mut Criterion) {
+    bench_topology::<Banks<4, 10_000>>(c, "banks", 4);
+    bench_topology::<Banks<16, 10_000>>(c, "banks", 16);
+}
+
+/// Compare running narrow levels on the worker pool (threshold 2, as before the threshold was configurable) against
+/// the default threshold and running every level inline.
+fn bench_threshold<R: Reactor<State = ()>>(c: &mut Criterion, group_name: &str) {
+    let mut group = c.benchmark_group(group_name);
+    group.sample_size(20);
+    group.throughput(Throughput::Elements(TICKS as u64));
+    for threshold in [2, runtime::DEFAULT_PARALLEL_THRESHOLD, usize::MAX] {
+        let param = match threshold {
+            usize::MAX => "inline".to_owned(),
+            threshold => threshold.to_string(),
+        };
+        group.bench_with_input(
+            BenchmarkId::from_parameter(param),
+            &threshold,
+            |b, &threshold| {
+                b.iter_batched(
+                    || {
+                        let mut env_builder = EnvBuilder::new();
+                        let _reactor =
+                            R::build(group_name, (), None, None, &mut env_builder).unwrap();
+                        let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+                        (env, graph)
+                    },
+                    |(env, graph)| run(env, graph, threshold),
+                    BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+fn threshold(c: &mut Criterion) {
+    bench_threshold::<Diamond<2, 100>>(c, "threshold_narrow_cheap");
+    bench_threshold::<Diamond<2, 10_000>>(c, "threshold_narrow_heavy");
+    bench_threshold::<Diamond<8, 100>>(c, "threshold_wide_cheap");
+}
+
+/// Build a wide bank, then run just its startup and shutdown tags.
+fn lifecycle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lifecycle");
+    group.sample_size(10);
+    group.bench_function("build/10000", |b| {
+        b.iter(|| {
+            let mut env_builder = EnvBuilder::new();
+            let _reactor =
+                Lifecycle::<10_000>::build("lifecycle", (), None, None, &mut env_builder).unwrap();
+            env_builder.into_runtime_parts().unwrap()
+        });
+    });
+    group.bench_function("startup/10000", |b| {
+        b.iter_batched(
+            || {
+                let mut env_builder = EnvBuilder::new();
+                let _reactor =
+                    Lifecycle::<10_000>::build("lifecycle", (), None, None, &mut env_builder)
+                        .unwrap();
+                let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+                runtime::Scheduler::new(
+                    env,
+                    graph,
+                    runtime::Config::default().with_fast_forward(true),
+                )
+            },
+            |mut sched| sched.event_loop().unwrap(),
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+criterion_group!(benches, chain, diamond, fan_out, sparse, broadcast, banks, threshold, lifecycle);
+criterion_main!(benches);
@@ -1,4 +1,8 @@
-use crate::{event::AsyncEvent, Context, Duration, SendContext, Tag};
+use crate::{
+    backpressure::{Backpressure, BackpressurePolicy},
+    event::AsyncEvent,
+    Context, Duration, RuntimeError, SendContext, Tag,
+};
 
 use super::{Action, ActionCommon, ActionKey, BaseAction, ReactorData};
 
@@ -13,6 +17,11 @@ impl<'a, T: ReactorData> From<&'a mut dyn BaseAction> for ActionRef<'a, T> {
 }
 
 impl<'a, T: ReactorData> ActionRef<'a, T> {
+    /// Return true if the action is logical
+    pub fn is_logical(&self) -> bool {
+        self.0.is_logical
+    }
+
     /// Return true if the action is present at the current tag
     pub fn is_present(&mut self, context: &Context) -> bool {
         self.0.store.get_current(context.tag).is_some()
@@ -23,8 +32,19 @@ impl<'a, T: ReactorData> ActionRef<'a, T> {
         self.0.store.get_current(context.tag)
     }
 
-    /// Schedule a new value for this action
-    pub fn schedule(&mut self, context: &mut Context, value: T, delay: Option<Duration>) {
+    /// Schedule a new value for this action `delay` after the current tag.
+    ///
+    /// The `min_delay` of the action is added to `delay`. Logical actions are scheduled relative to the current logical
+    /// tag, and must end up strictly after it, otherwise [`RuntimeError::TagNotInFuture`] is returned with both tags and
+    /// nothing is scheduled, e.g. for a negative delay. Physical actions are scheduled relative to the current physical
+    /// time, and moved to one microstep after the current tag if logical time is ahead of it, e.g. in fast-forward mode,
+    /// the same as for [`AsyncActionRef::schedule`].
+    pub fn schedule(
+        &mut self,
+        context: &mut Context,
+        value: T,
+        delay: Option<Duration>,
+    ) -> Result<(), RuntimeError> {
         let action = &mut self.0;
 
         let tag_delay = action.min_delay.unwrap_or_default() + delay.unwrap_or_default();
@@ -34,10 +54,101 @@ impl<'a, T: ReactorData> ActionRef<'a, T> {
             context.tag.delay(tag_delay)
         } else {
             // Physical actions are scheduled at the current physical time + tag_delay
-            Tag::from_physical_time(context.start_time, std::time::Instant::now()).delay(tag_delay)
+            physical_after(context, context.physical_tag().delay(tag_delay))
+        };
+
+        self.push_event(context, value, new_tag)
+    }
+
+    /// Schedule a new value for this action `microsteps` microsteps after the current tag, at the same logical time,
+    /// e.g. to run reactions again later in this tag.
+    ///
+    /// The microsteps are counted like a zero `delay` for [`Self::schedule`], which is the same as `microsteps = 1`. With
+    /// a `min_delay`, that first microstep is microstep 0 at `min_delay` after the current time, and each further
+    /// microstep is added after it. Physical actions count from the current physical time instead, and are moved like
+    /// for [`Self::schedule`] if that is behind logical time. `microsteps` must be at least 1, otherwise
+    /// [`RuntimeError::TagNotInFuture`] is returned and nothing is scheduled.
+    pub fn schedule_microstep(
+        &mut self,
+        context: &mut Context,
+        value: T,
+        microsteps: usize,
+    ) -> Result<(), RuntimeError> {
+        let Some(extra) = microsteps.checked_sub(1) else {
+            return Err(RuntimeError::TagNotInFuture {
+                requested: context.tag,
+                current: context.tag,
+            });
+        };
+
+        let action = &self.0;
+        let base = if action.is_logical {
+            context.tag
+        } else {
+            context.physical_tag()
+        };
+        let new_tag = base
+            .delay(action.min_delay.unwrap_or_default())
+            .delay_microsteps(extra);
+        let new_tag = if action.is_logical {
+            new_tag
+        } else {
+            physical_after(context, new_tag)
+        };
+
+        self.push_event(context, value, new_tag)
+    }
+
+    /// Schedule a new value for this action at an absolute [`Tag`].
+    ///
+    /// The `min_delay` of the action is not applied. The tag must be strictly after the current logical tag, and for
+    /// physical actions also strictly after the current physical time, otherwise
+    /// [`RuntimeError::TagNotInFuture`] is returned and nothing is scheduled.
+    pub fn schedule_at(
+        &mut self,
+        context: &mut Context,
+        value: T,
+        tag: Tag,
+    ) -> Result<(), RuntimeError> {
+        let action = &mut self.0;
+
+        let current = if action.is_logical {
+            context.tag
+        } else {
+            context.tag.max(context.physical_tag())
         };
 
+        if tag <= current {
+            return Err(RuntimeError::TagNotInFuture {
+                requested: tag,
+                current,
+            });
+        }
+
+        action.store.push(tag, value);
+        context
+            .trigger_res
+            .scheduled_actions
+            .push((action.key, tag));
+        Ok(())
+    }
+
+    /// Push `value` at `new_tag`, which must be strictly after the current logical tag.
+    fn push_event(
+        &mut self,
+        context: &mut Context,
+        value: T,
+        new_tag: Tag,
+    ) -> Result<(), RuntimeError> {
+        if new_tag <= context.tag {
+            return Err(RuntimeError::TagNotInFuture {
+                requested: new_tag,
+                current: context.tag,
+            });
+        }
+
         // Push the new value into the store
+        let action = &mut self.0;
         action.store.push(new_tag, value);
 
         // Schedule the action to trigger at the new tag
@@ -45,6 +156,7 @@ impl<'a, T: ReactorData> ActionRef<'a, T> {
             .trigger_res
             .scheduled_actions
             .push((action.key, new_tag));
+        Ok(())
     }
 }
 
@@ -70,6 +182,7 @@ pub struct AsyncActionRef<T: ReactorData = ()> {
     key: ActionKey,
     min_delay: Option<Duration>,
     is_logical: bool,
+    backpressure: Backpressure,
     _phantom: std::marker::PhantomData<fn() -> T>,
 }
 
@@ -81,13 +194,19 @@ impl<'a, T: ReactorData> From<&'a mut dyn BaseAction> for AsyncActionRef<T> {
             key: action.key(),
             min_delay: action.min_delay(),
             is_logical: action.is_logical(),
+            backpressure: action.backpressure.clone(),
             _phantom: Default::default(),
         }
     }
 }
 
 impl<T: ReactorData> AsyncActionRef<T> {
-    /// Schedule a new value for this action
+    /// Schedule a new value for this action `delay` after the current tag.
+    ///
+    /// The tag is only known once the scheduler receives the event, and the same rules as for [`ActionRef::schedule`]
+    /// apply there: an event for a logical action at or before the current tag, i.e. with a negative delay, is logged
+    /// and dropped. A physical event stamped at or before the current tag raced with the scheduler advancing logical
+    /// time, so it is moved to one microstep after the current tag rather than lost.
     pub fn schedule(&self, context: &SendContext, value: T, delay: Option<Duration>) {
         let tag_delay = self.min_delay.unwrap_or_default() + delay.unwrap_or_default();
         let value = Box::new(value) as Box<dyn ReactorData>;
@@ -95,19 +214,87 @@ impl<T: ReactorData> AsyncActionRef<T> {
         let event = if self.is_logical {
             // Logical actions are scheduled at the current logical time + tag_delay
             tracing::info!(tag_delay = ?tag_delay, key = ?self.key, "Scheduling Async LogicalAction");
-            AsyncEvent::logical(self.key, tag_delay, value)
+            AsyncEvent::logical(self.key, tag_delay, 0, value)
         } else {
             // Physical actions are scheduled at the current physical time + tag_delay
-            let new_tag = Tag::from_physical_time(context.start_time, std::time::Instant::now())
-                .delay(tag_delay);
+            let new_tag = context.physical_tag().delay(tag_delay);
             tracing::info!(new_tag = %new_tag, key = ?self.key, "Scheduling Async PhysicalAction");
             AsyncEvent::physical(self.key, new_tag, value)
         };
 
-        context
-            .async_tx
-            .send(event)
-            .expect("Failed to send async event");
+        self.backpressure.send(&context.async_tx, event);
+    }
+
+    /// Schedule a new value for this action `microsteps` microsteps after the current tag, see
+    /// [`ActionRef::schedule_microstep`].
+    ///
+    /// As for [`Self::schedule`], logical events are resolved against the tag of the scheduler when it receives them.
+    /// A `microsteps` of 0 is logged and nothing is scheduled.
+    pub fn schedule_microstep(&self, context: &SendContext, value: T, microsteps: usize) {
+        let Some(extra) = microsteps.checked_sub(1) else {
+            tracing::error!(key = ?self.key, "Dropped async action event scheduled 0 microsteps ahead");
+            return;
+        };
+        let min_delay = self.min_delay.unwrap_or_default();
+        let value = Box::new(value) as Box<dyn ReactorData>;
+
+        let event = if self.is_logical {
+            AsyncEvent::logical(self.key, min_delay, extra, value)
+        } else {
+            let new_tag = context
+                .physical_tag()
+                .delay(min_delay)
+                .delay_microsteps(extra);
+            AsyncEvent::physical(self.key, new_tag, value)
+        };
+
+        self.backpressure.send(&context.async_tx, event);
+    }
+
+    /// Schedule a new value for this action at an absolute [`Tag`].
+    ///
+    /// The `min_delay` of the action is not applied. The tag must be strictly after the current physical time, otherwise
+    /// [`RuntimeError::TagNotInFuture`] is returned and nothing is scheduled. The current logical tag of the scheduler
+    /// is not available from a [`SendContext`], so the scheduler checks the tag against it when it receives the event:
+    /// if logical time has reached the tag in the meantime, e.g. in fast-forward mode, the event is logged and dropped.
+    pub fn schedule_at(
+        &self,
+        context: &SendContext,
+        value: T,
+        tag: Tag,
+    ) -> Result<(), RuntimeError> {
+        let current = context.physical_tag();
+        if tag <= current {
+            return Err(RuntimeError::TagNotInFuture {
+                requested: tag,
+                current,
+            });
+        }
+
+        tracing::info!(tag = %tag, key = ?self.key, "Scheduling Async Action at absolute tag");
+        let event = AsyncEvent::absolute(self.key, tag, Box::new(value));
+
+        self.backpressure.send(&context.async_tx, event);
+        Ok(())
+    }
+}
+
+impl<T: ReactorData> AsyncActionRef<T> {
+    /// What this action does with its events while the queue to the scheduler is full, see
+    /// [`crate::backpressure`].
+    pub fn backpressure(&self) -> BackpressurePolicy {
+        self.backpressure.policy()
+    }
+
+    /// The number of events of this action dropped so far with [`BackpressurePolicy::Drop`].
+    pub fn dropped(&self) -> usize {
+        self.backpressure.dropped()
+    }
+
+    /// The number of pending events of this action replaced by newer ones so far with
+    /// [`BackpressurePolicy::Coalesce`].
+    pub fn coalesced(&self) -> usize {
+        self.backpressure.coalesced()
     }
 }
 
@@ -124,3 +311,14 @@ impl<T: ReactorData> ActionCommon for AsyncActionRef<T> {
         self.min_delay.unwrap_or_default()
     }
 }
+
+/// Move a physical action event stamped at or before the current logical tag to the next microstep, like the scheduler
+/// does for asynchronous physical events.
+fn physical_after(context: &Context, stamped: Tag) -> Tag {
+    if stamped <= context.tag {
+        tracing::debug!(%stamped, "Physical action event is behind logical time, moving it to the next microstep");
+        context.tag.delay(Duration::ZERO)
+    } else {
+        stamped
+    }
+}
@@ -11,10 +11,16 @@
 //! **Asynchronous**: Actions are scheduled asynchronously from oustide of the scheduler thread. This is useful when the
 //!     Action needs to be scheduled from a different thread. An `AsyncEvent` is created and pushed onto the `async_tx`
 //!     channel.
+//!
+//! Both `ActionRef` and `AsyncActionRef` also support scheduling at an absolute [`Tag`] with `schedule_at`, which
+//! fails if the tag is not strictly in the future.
 
 use std::fmt::{Debug, Display};
 
-use crate::{Duration, ReactorData, Tag};
+use crate::{
+    backpressure::{Backpressure, BackpressurePolicy},
+    Duration, ReactorData, Tag,
+};
 
 mod action_ref;
 pub mod store;
@@ -46,6 +52,22 @@ pub trait BaseAction: Debug + Downcast + Send + Sync {
 
     /// Push a new value onto the action store. If the underlying types are not the same, this will panic.
     fn push_value(&mut self, tag: Tag, value: Box<dyn ReactorData>);
+
+    /// Remove the value pushed most recently at `tag`, e.g. for an event that was dropped.
+    fn remove_value(&mut self, tag: Tag);
+
+    /// Mark the values pushed so far, to remove the ones pushed later with [`BaseAction::remove_values_since`].
+    fn push_mark(&self) -> usize;
+
+    /// Remove all values pushed since `mark`, e.g. by a reaction that panicked afterwards.
+    fn remove_values_since(&mut self, mark: usize);
+
+    /// The estimated size of the pending events in the action store in bytes, see [`crate::mem_size`].
+    fn store_mem_size(&self) -> usize;
+
+    /// Set what the action does with its asynchronous events while the queue to the scheduler is full, see
+    /// [`crate::backpressure`].
+    fn set_backpressure(&mut self, policy: BackpressurePolicy);
 }
 
 downcast_rs::impl_downcast!(BaseAction);
@@ -62,6 +84,7 @@ pub struct Action<T: ReactorData = ()> {
     min_delay: Option<Duration>,
     store: ActionStore<T>,
     is_logical: bool,
+    backpressure: Backpressure,
 }
 
 impl<T: ReactorData> Debug for Action<T> {
@@ -72,6 +95,7 @@ impl<T: ReactorData> Debug for Action<T> {
             .field("min_delay", &self.min_delay)
             .field("store", &self.store)
             .field("is_logical", &self.is_logical)
+            .field("backpressure", &self.backpressure.policy())
             .finish()
     }
 }
@@ -100,6 +124,26 @@ impl<T: ReactorData> BaseAction for Action<T> {
             panic!("Type mismatch");
         }
     }
+
+    fn remove_value(&mut self, tag: Tag) {
+        self.store.remove_latest(tag);
+    }
+
+    fn push_mark(&self) -> usize {
+        self.store.next_sequence()
+    }
+
+    fn remove_values_since(&mut self, mark: usize) {
+        self.store.remove_since(mark);
+    }
+
+    fn store_mem_size(&self) -> usize {
+        self.store.mem_size()
+    }
+
+    fn set_backpressure(&mut self, policy: BackpressurePolicy) {
+        self.backpressure = Backpressure::new(policy);
+    }
 }
 
 impl<T: ReactorData> Action<T> {
@@ -110,12 +154,18 @@ impl<T: ReactorData> Action<T> {
             min_delay,
             store: ActionStore::new(),
             is_logical,
+            backpressure: Backpressure::default(),
         }
     }
 
     pub fn boxed(self) -> Box<dyn BaseAction> {
         Box::new(self)
     }
+
+    /// Get the value of this action at `tag`, if any.
+    pub(crate) fn get_current(&mut self, tag: Tag) -> Option<&T> {
+        self.store.get_current(tag)
+    }
 }
 
 #[cfg(test)]
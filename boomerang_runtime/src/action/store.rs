@@ -95,6 +95,17 @@ impl<T: ReactorData> ActionStore<T> {
         self.counter += 1;
     }
 
+    /// The estimated size of the store in bytes, see [`crate::mem_size`].
+    pub fn mem_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.heap.capacity() * std::mem::size_of::<ActionEntry<T>>()
+            + self
+                .heap
+                .iter()
+                .map(|entry| crate::mem_size::heap_size(&entry.data))
+                .sum::<usize>()
+    }
+
     pub fn clear_older_than(&mut self, clear_tag: Tag) {
         while let Some(entry) = self.heap.peek() {
             if entry.tag < clear_tag {
@@ -105,6 +116,31 @@ impl<T: ReactorData> ActionStore<T> {
         }
     }
 
+    /// The sequence number of the next value pushed, to remove all values pushed after it with [`Self::remove_since`].
+    pub fn next_sequence(&self) -> usize {
+        self.counter
+    }
+
+    /// Remove all values pushed since [`Self::next_sequence`] returned `sequence`.
+    pub fn remove_since(&mut self, sequence: usize) {
+        self.heap.retain(|entry| entry.sequence < sequence);
+    }
+
+    /// Remove the value pushed most recently at `tag`, returning whether there was one.
+    pub fn remove_latest(&mut self, tag: Tag) -> bool {
+        let Some(sequence) = self
+            .heap
+            .iter()
+            .filter(|entry| entry.tag == tag)
+            .map(|entry| entry.sequence)
+            .max()
+        else {
+            return false;
+        };
+        self.heap.retain(|entry| entry.sequence != sequence);
+        true
+    }
+
     /// Get the current action data for a given tag.
     ///
     /// This method pops all entries older than `tag` from the store.
@@ -224,6 +260,23 @@ mod tests {
         assert_eq!(store.get_current(tags[4]), None);
     }
 
+    #[test]
+    fn test_remove_latest() {
+        let mut store = ActionStore::<u32>::new();
+
+        let tags = build_tags::<3>();
+        store.push(tags[1], 10);
+        store.push(tags[1], 11);
+        store.push(tags[2], 20);
+
+        assert!(store.remove_latest(tags[1]));
+        assert!(!store.remove_latest(tags[0]));
+        assert_eq!(store.get_current(tags[1]), Some(&10));
+        assert!(store.remove_latest(tags[1]));
+        assert_eq!(store.get_current(tags[1]), None);
+        assert_eq!(store.get_current(tags[2]), Some(&20));
+    }
+
     #[test]
     fn test_empty_store() {
         let mut store = ActionStore::<u32>::new();
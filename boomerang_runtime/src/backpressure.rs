@@ -0,0 +1,173 @@
+//! What producers of asynchronous events do when the scheduler falls behind.
+//!
+//! Events scheduled with an [`crate::AsyncActionRef`] from outside of the scheduler thread are sent to the scheduler
+//! over a single bounded queue, with a capacity set with [`crate::Config::with_queue_size`]. A runaway producer can't
+//! exhaust memory, and the [`BackpressurePolicy`] of each action, e.g. set with `EnvBuilder::set_backpressure`,
+//! decides what happens to its events while the queue is full:
+//!
+//! - [`BackpressurePolicy::Block`] blocks the producer until the scheduler makes room. No event is lost, but a
+//!   producer that must not stall, e.g. a device driver callback, should use one of the other policies.
+//! - [`BackpressurePolicy::Drop`] drops the event and counts it, see [`crate::AsyncActionRef::dropped`].
+//! - [`BackpressurePolicy::Coalesce`] keeps at most one pending event of the action: a new event replaces the pending
+//!   one if the scheduler hasn't received it yet, and the replacements are counted, see
+//!   [`crate::AsyncActionRef::coalesced`]. Only the latest value is processed, e.g. of a sensor reading. A coalescing
+//!   action takes at most one place in the queue, and only blocks to take it.
+//!
+//! The order of the events received by the scheduler is the order in which they were queued, so a run is reproducible
+//! given the same queued events.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! let frame = reactor.add_physical_action::<Frame>("frame", None)?;
+//! reactor.set_backpressure(frame, BackpressurePolicy::Coalesce)?;
+//! // ...
+//! let config = runtime::Config::default().with_queue_size(64);
+//! ```
+
+use std::fmt::Display;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use crossbeam_channel::{Sender, TrySendError};
+
+use crate::event::AsyncEvent;
+
+/// What an action does with its asynchronous events while the queue to the scheduler is full, see the
+/// [module documentation](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BackpressurePolicy {
+    /// Block the producer until there is room.
+    #[default]
+    Block,
+    /// Drop the event, counting it.
+    Drop,
+    /// Replace the pending event of the action, if any.
+    Coalesce,
+}
+
+impl Display for BackpressurePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackpressurePolicy::Block => write!(f, "block"),
+            BackpressurePolicy::Drop => write!(f, "drop"),
+            BackpressurePolicy::Coalesce => write!(f, "coalesce"),
+        }
+    }
+}
+
+/// The policy of an action, and its state shared by all of its [`crate::AsyncActionRef`]s.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Backpressure {
+    policy: BackpressurePolicy,
+    /// The number of events dropped
+    dropped: Arc<AtomicUsize>,
+    /// The number of pending events replaced by newer ones
+    coalesced: Arc<AtomicUsize>,
+    /// The event not yet received by the scheduler, only when coalescing
+    pending: Arc<Mutex<Option<AsyncEvent>>>,
+}
+
+impl Backpressure {
+    pub(crate) fn new(policy: BackpressurePolicy) -> Self {
+        Self {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn policy(&self) -> BackpressurePolicy {
+        self.policy
+    }
+
+    pub(crate) fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn coalesced(&self) -> usize {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+
+    /// Send `event` to the scheduler according to the policy.
+    pub(crate) fn send(&self, async_tx: &Sender<AsyncEvent>, event: AsyncEvent) {
+        match self.policy {
+            BackpressurePolicy::Block => {
+                async_tx.send(event).expect("Failed to send async event");
+            }
+            BackpressurePolicy::Drop => match async_tx.try_send(event) {
+                Ok(()) => {}
+                Err(TrySendError::Full(event)) => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    tracing::debug!(%event, "Dropped async event, the queue is full");
+                }
+                Err(TrySendError::Disconnected(_)) => panic!("Failed to send async event"),
+            },
+            BackpressurePolicy::Coalesce => {
+                let mut pending = self.pending.lock().unwrap();
+                if pending.replace(event).is_some() {
+                    self.coalesced.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                drop(pending);
+                // The scheduler takes the latest pending event when it receives this
+                async_tx
+                    .send(AsyncEvent::Coalesced {
+                        pending: self.pending.clone(),
+                    })
+                    .expect("Failed to send async event");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionKey, Duration, Tag};
+
+    fn event(value: u32) -> AsyncEvent {
+        AsyncEvent::physical(
+            ActionKey::from(0),
+            Tag::new(Duration::milliseconds(value.into()), 0),
+            Box::new(value),
+        )
+    }
+
+    #[test]
+    fn test_drop() {
+        let (tx, rx) = crossbeam_channel::bounded(2);
+        let backpressure = Backpressure::new(BackpressurePolicy::Drop);
+        for value in 0..5 {
+            backpressure.send(&tx, event(value));
+        }
+        assert_eq!(backpressure.dropped(), 3);
+        assert_eq!(rx.len(), 2);
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let (tx, rx) = crossbeam_channel::bounded(2);
+        let backpressure = Backpressure::new(BackpressurePolicy::Coalesce);
+        for value in 0..5 {
+            backpressure.send(&tx, event(value));
+        }
+        assert_eq!(backpressure.coalesced(), 4);
+        assert_eq!(rx.len(), 1);
+
+        let Ok(AsyncEvent::Coalesced { pending }) = rx.try_recv() else {
+            panic!("Expected a coalesced event");
+        };
+        let Some(AsyncEvent::Physical { value, .. }) = pending.lock().unwrap().take() else {
+            panic!("Expected the latest physical event");
+        };
+        assert_eq!(value.downcast_ref::<u32>(), Some(&4));
+
+        // Once received, the next event is queued again
+        backpressure.send(&tx, event(5));
+        assert_eq!(rx.len(), 1);
+        assert_eq!(backpressure.coalesced(), 4);
+    }
+}
@@ -0,0 +1,192 @@
+//! Execution time budgets of reactions.
+//!
+//! A [`crate::Deadline`] bounds when a reaction starts, relative to its logical time. A budget bounds how long its body
+//! runs instead: [`crate::Reaction::with_budget`] sets the physical time a single invocation is expected to take.
+//!
+//! Timing every invocation costs two clock reads, so only 1 of every [`crate::Config::budget_sampling`] invocations of
+//! a reaction is measured, by default every invocation in debug builds and 1 of [`DEFAULT_BUDGET_SAMPLING`] in release
+//! builds. A sampled invocation exceeding the budget is logged as a warning and sent to the subscribers as a
+//! [`crate::RuntimeEvent::BudgetExceeded`]. The measured durations are summarized as [`BudgetStats`], available from
+//! [`crate::Scheduler::budget_stats`] at any time, and the reactions that exceeded their budget from
+//! [`crate::Scheduler::budget_offenders`].
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! reactor
+//!     .add_reaction("filter", reaction_fn)
+//!     .with_port(input, 0, TriggerMode::TriggersAndUses)?
+//!     .with_budget(Duration::microseconds(200))
+//!     .finish()?;
+//! // ...
+//! for offender in sched.budget_offenders() {
+//!     tracing::warn!("{offender}");
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+use crate::Duration;
+
+/// The number of most recent samples kept to compute the percentiles of a [`BudgetStats`].
+pub const BUDGET_WINDOW: usize = 1024;
+
+/// The default [`crate::Config::budget_sampling`] in release builds.
+pub const DEFAULT_BUDGET_SAMPLING: usize = 16;
+
+/// Statistics of the sampled execution times of a reaction with a budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetStats {
+    /// The fully-qualified name of the reaction
+    pub reaction: String,
+    /// The budget of a single invocation
+    pub budget: Duration,
+    /// The number of invocations, sampled or not
+    pub invocations: usize,
+    /// The number of sampled invocations
+    pub samples: usize,
+    /// The number of sampled invocations that exceeded the budget
+    pub exceeded: usize,
+    /// The mean execution time of the sampled invocations
+    pub mean: Duration,
+    /// The longest execution time of the sampled invocations
+    pub max: Duration,
+    /// The 99th percentile of the most recent [`BUDGET_WINDOW`] samples
+    pub p99: Duration,
+}
+
+impl BudgetStats {
+    /// Whether any sampled invocation exceeded the budget.
+    pub fn is_offender(&self) -> bool {
+        self.exceeded > 0
+    }
+}
+
+impl Display for BudgetStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} of {} samples over the budget of {} ({} invocations): mean {}, max {}, p99 {}",
+            self.reaction,
+            self.exceeded,
+            self.samples,
+            self.budget,
+            self.invocations,
+            self.mean,
+            self.max,
+            self.p99
+        )
+    }
+}
+
+/// The budget of a reaction and its sampled execution times.
+#[derive(Debug)]
+pub(crate) struct Budget {
+    budget: Duration,
+    /// Measure 1 of every `sampling` invocations
+    pub(crate) sampling: usize,
+    invocations: usize,
+    samples: usize,
+    exceeded: usize,
+    max: Duration,
+    total: Duration,
+    /// The most recent samples
+    window: VecDeque<Duration>,
+}
+
+impl Budget {
+    pub(crate) fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            sampling: 1,
+            invocations: 0,
+            samples: 0,
+            exceeded: 0,
+            max: Duration::ZERO,
+            total: Duration::ZERO,
+            window: VecDeque::new(),
+        }
+    }
+
+    /// Count an invocation, and return whether it is sampled.
+    pub(crate) fn sample(&mut self) -> bool {
+        let sampled = self.invocations.is_multiple_of(self.sampling.max(1));
+        self.invocations += 1;
+        sampled
+    }
+
+    /// Record the execution time of a sampled invocation, and return it if it exceeded the budget.
+    pub(crate) fn record(&mut self, elapsed: std::time::Duration) -> Option<Duration> {
+        let elapsed = Duration::try_from(elapsed).unwrap_or(Duration::MAX);
+        self.samples += 1;
+        self.max = self.max.max(elapsed);
+        self.total = self.total.saturating_add(elapsed);
+        if self.window.len() == BUDGET_WINDOW {
+            self.window.pop_front();
+        }
+        self.window.push_back(elapsed);
+
+        (elapsed > self.budget).then(|| {
+            self.exceeded += 1;
+            elapsed
+        })
+    }
+
+    pub(crate) fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    pub(crate) fn stats(&self, reaction: String) -> BudgetStats {
+        let mut window = self.window.iter().copied().collect::<Vec<_>>();
+        window.sort_unstable();
+        let p99 = if window.is_empty() {
+            Duration::ZERO
+        } else {
+            window[(window.len() * 99).div_ceil(100) - 1]
+        };
+        BudgetStats {
+            reaction,
+            budget: self.budget,
+            invocations: self.invocations,
+            samples: self.samples,
+            exceeded: self.exceeded,
+            mean: if self.samples == 0 {
+                Duration::ZERO
+            } else {
+                self.total / self.samples as u32
+            },
+            max: self.max,
+            p99,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_sampling() {
+        let ms = std::time::Duration::from_millis;
+        let mut budget = Budget::new(Duration::milliseconds(10));
+        budget.sampling = 4;
+
+        let mut elapsed = [5, 20, 8, 12].into_iter();
+        for _ in 0..16 {
+            if budget.sample() {
+                budget.record(ms(elapsed.next().unwrap()));
+            }
+        }
+        assert_eq!(elapsed.next(), None);
+
+        let stats = budget.stats("main::filter".to_owned());
+        assert!(stats.is_offender());
+        assert_eq!(stats.invocations, 16);
+        assert_eq!(stats.samples, 4);
+        assert_eq!(stats.exceeded, 2);
+        assert_eq!(stats.mean, Duration::microseconds(11_250));
+        assert_eq!(stats.max, Duration::milliseconds(20));
+        assert_eq!(stats.p99, Duration::milliseconds(20));
+    }
+}
@@ -0,0 +1,346 @@
+//! Calendar timers, triggering at the times matching a cron-like schedule.
+//!
+//! A periodic timer can't express schedules like "every weekday at 09:00". A [`CalendarSpec`] is parsed from the five
+//! fields of a cron schedule, `minute hour day-of-month month day-of-week`, each a comma-separated list of values,
+//! ranges `a-b`, or `*` for all values, optionally stepped with `/n`. Months and days of the week can also be named by
+//! their first three letters, e.g. `JAN` or `MON-FRI`, and both 0 and 7 are Sunday. As in cron, if both the day of the
+//! month and the day of the week are restricted, a day matching either of them matches.
+//!
+//! The schedule is evaluated against a calendar time derived from logical time, so it is reproducible: logical time 0
+//! is the [`CalendarEpoch`] of the spec, by default Monday 2024-01-01 00:00. In real-time mode, the epoch can also be
+//! the wall-clock time in UTC when the timer starts, with [`CalendarEpoch::WallClock`]. Only the next occurrence is
+//! scheduled at a time, so sparse schedules don't fill the event queue.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! let spec = "0 9 * * MON-FRI".parse::<CalendarSpec>()?;
+//! let standup = reactor.add_calendar_timer("standup", spec)?;
+//! ```
+
+use std::{fmt::Display, str::FromStr};
+
+use time::{Date, Month, PrimitiveDateTime, Time};
+
+use crate::{
+    ActionRef, BaseAction, BasePort, BaseReactor, BoxedReactionFn, Context, Duration, ReactionFn,
+    Refs, RefsMut,
+};
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// The number of years searched for the next occurrence of a schedule, enough for any day of the week to fall on
+/// February 29.
+const SEARCH_YEARS: i32 = 28;
+
+/// An error parsing a [`CalendarSpec`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("Invalid calendar spec '{spec}': {what}")]
+pub struct CalendarSpecError {
+    pub spec: String,
+    pub what: String,
+}
+
+/// The calendar time of logical time 0, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarEpoch {
+    /// Logical time 0 is the given calendar time
+    At(PrimitiveDateTime),
+    /// Logical time 0 is the wall-clock time in UTC when the timer starts
+    WallClock,
+}
+
+impl Default for CalendarEpoch {
+    fn default() -> Self {
+        let monday = Date::from_calendar_date(2024, Month::January, 1).expect("A valid date");
+        Self::At(monday.midnight())
+    }
+}
+
+/// The values of a field of a [`CalendarSpec`], as a bit set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Field(u64);
+
+impl Field {
+    fn contains(&self, value: u8) -> bool {
+        self.0 & (1 << value) != 0
+    }
+
+    /// Parse a field with values in `min..=max`, where `names[i]` names the value `min + i`.
+    fn parse(field: &str, min: u8, max: u8, names: &[&str]) -> Result<Self, String> {
+        let value = |s: &str| {
+            let value = names
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(s))
+                .map(|index| min + index as u8)
+                .or_else(|| s.parse().ok())
+                .ok_or_else(|| format!("'{s}' is not a value"))?;
+            if (min..=max).contains(&value) {
+                Ok(value)
+            } else {
+                Err(format!("{value} is not in {min}-{max}"))
+            }
+        };
+
+        let mut bits = 0;
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => match step.parse::<usize>() {
+                    Ok(step) if step > 0 => (range, Some(step)),
+                    _ => return Err(format!("'{step}' is not a step")),
+                },
+                None => (part, None),
+            };
+            let (first, last) = if range == "*" {
+                (min, max)
+            } else if let Some((first, last)) = range.split_once('-') {
+                (value(first)?, value(last)?)
+            } else {
+                // A single value with a step ranges to the maximum
+                let first = value(range)?;
+                (first, if step.is_some() { max } else { first })
+            };
+            if first > last {
+                return Err(format!("'{range}' is an empty range"));
+            }
+            for value in (first..=last).step_by(step.unwrap_or(1)) {
+                bits |= 1 << value;
+            }
+        }
+        Ok(Self(bits))
+    }
+}
+
+/// A cron-like schedule of a calendar timer, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarSpec {
+    source: String,
+    minutes: Field,
+    hours: Field,
+    days: Field,
+    months: Field,
+    weekdays: Field,
+    /// Whether the day of the month and the day of the week are restricted, rather than `*`
+    days_restricted: bool,
+    weekdays_restricted: bool,
+    epoch: CalendarEpoch,
+}
+
+impl CalendarSpec {
+    /// Evaluate the schedule with logical time 0 at `epoch`.
+    pub fn with_epoch(mut self, epoch: CalendarEpoch) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    /// The calendar time of logical time 0.
+    pub fn epoch(&self) -> CalendarEpoch {
+        self.epoch
+    }
+
+    /// Whether the schedule matches the day `date`.
+    fn matches_date(&self, date: Date) -> bool {
+        let day = self.days.contains(date.day());
+        let weekday = self
+            .weekdays
+            .contains(date.weekday().number_days_from_sunday());
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    /// The earliest time matching the schedule at or after `from`, or `None` if there is none within 28 years.
+    pub fn next_from(&self, from: PrimitiveDateTime) -> Option<PrimitiveDateTime> {
+        let mut time = from.replace_time(Time::from_hms(from.hour(), from.minute(), 0).ok()?);
+        if time < from {
+            time += Duration::MINUTE;
+        }
+
+        let last_year = from.year() + SEARCH_YEARS;
+        while time.year() <= last_year {
+            if !self.months.contains(u8::from(time.month())) {
+                let (year, month) = match time.month() {
+                    Month::December => (time.year() + 1, Month::January),
+                    month => (time.year(), month.next()),
+                };
+                time = Date::from_calendar_date(year, month, 1).ok()?.midnight();
+            } else if !self.matches_date(time.date()) {
+                time = time.date().next_day()?.midnight();
+            } else if !self.hours.contains(time.hour()) {
+                time = time.replace_minute(0).ok()? + Duration::HOUR;
+            } else if !self.minutes.contains(time.minute()) {
+                time += Duration::MINUTE;
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+impl FromStr for CalendarSpec {
+    type Err = CalendarSpecError;
+
+    /// Parse the five whitespace-separated fields of a cron schedule.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |what: String| CalendarSpecError {
+            spec: s.to_owned(),
+            what,
+        };
+        let fields = s.split_whitespace().collect::<Vec<_>>();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(error(format!("expected 5 fields, found {}", fields.len())));
+        };
+
+        let mut weekday_field = Field::parse(weekdays, 0, 7, &WEEKDAY_NAMES).map_err(error)?;
+        // Both 0 and 7 are Sunday
+        if weekday_field.contains(7) {
+            weekday_field.0 |= 1;
+        }
+        Ok(Self {
+            source: fields.join(" "),
+            minutes: Field::parse(minutes, 0, 59, &[]).map_err(error)?,
+            hours: Field::parse(hours, 0, 23, &[]).map_err(error)?,
+            days: Field::parse(days, 1, 31, &[]).map_err(error)?,
+            months: Field::parse(months, 1, 12, &MONTH_NAMES).map_err(error)?,
+            weekdays: weekday_field,
+            days_restricted: !days.starts_with('*'),
+            weekdays_restricted: !weekdays.starts_with('*'),
+            epoch: CalendarEpoch::default(),
+        })
+    }
+}
+
+impl Display for CalendarSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// ReactionFn of a calendar timer, scheduling the timer action at the next occurrence of its [`CalendarSpec`].
+pub struct CalendarTimerFn {
+    spec: CalendarSpec,
+    /// The calendar time of logical time 0, resolved at startup
+    epoch: Option<PrimitiveDateTime>,
+}
+
+impl CalendarTimerFn {
+    pub fn new(spec: CalendarSpec) -> Self {
+        Self { spec, epoch: None }
+    }
+}
+
+impl From<CalendarTimerFn> for BoxedReactionFn {
+    fn from(value: CalendarTimerFn) -> Self {
+        Box::new(value)
+    }
+}
+
+impl<'store> ReactionFn<'store> for CalendarTimerFn {
+    fn trigger(
+        &mut self,
+        ctx: &'store mut Context,
+        _state: &'store mut dyn BaseReactor,
+        _ports: Refs<'store, dyn BasePort>,
+        _ports_mut: RefsMut<'store, dyn BasePort>,
+        actions: RefsMut<'store, dyn BaseAction>,
+    ) {
+        let mut timer: ActionRef = actions.partition_mut().expect("Expected a timer action");
+
+        let epoch = *self.epoch.get_or_insert_with(|| match self.spec.epoch {
+            CalendarEpoch::At(epoch) => epoch,
+            CalendarEpoch::WallClock => {
+                let now = time::OffsetDateTime::now_utc();
+                PrimitiveDateTime::new(now.date(), now.time())
+            }
+        });
+        let now = epoch + ctx.get_elapsed_logical_time();
+        // At startup an occurrence at the epoch itself is due, afterwards only later ones
+        let from = if timer.is_present(ctx) {
+            now + Duration::NANOSECOND
+        } else {
+            now
+        };
+
+        match self.spec.next_from(from) {
+            Some(next) => timer
+                .schedule(ctx, (), Some(next - now))
+                .expect("A timer is always scheduled into the future"),
+            None => tracing::info!(
+                "Calendar timer '{}' has no occurrence after {now}",
+                self.spec
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: Month, day: u8, hour: u8, minute: u8) -> PrimitiveDateTime {
+        Date::from_calendar_date(year, month, day)
+            .unwrap()
+            .with_hms(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_calendar_spec() {
+        let weekdays = "0 9 * * MON-FRI".parse::<CalendarSpec>().unwrap();
+        // Friday 2024-01-05 10:00 to Monday 09:00
+        assert_eq!(
+            weekdays.next_from(at(2024, Month::January, 5, 10, 0)),
+            Some(at(2024, Month::January, 8, 9, 0))
+        );
+        // An occurrence at `from` itself matches
+        assert_eq!(
+            weekdays.next_from(at(2024, Month::January, 8, 9, 0)),
+            Some(at(2024, Month::January, 8, 9, 0))
+        );
+
+        let steps = "*/20 8-17/4 * * *".parse::<CalendarSpec>().unwrap();
+        assert_eq!(
+            steps.next_from(at(2024, Month::January, 1, 12, 41)),
+            Some(at(2024, Month::January, 1, 16, 0))
+        );
+
+        // Either the 13th or a Friday
+        let either = "0 0 13 * 5".parse::<CalendarSpec>().unwrap();
+        assert_eq!(
+            either.next_from(at(2024, Month::January, 6, 0, 0)),
+            Some(at(2024, Month::January, 12, 0, 0))
+        );
+        assert_eq!(
+            either.next_from(at(2024, Month::January, 12, 0, 1)),
+            Some(at(2024, Month::January, 13, 0, 0))
+        );
+
+        let leap = "30 12 29 FEB *".parse::<CalendarSpec>().unwrap();
+        assert_eq!(
+            leap.next_from(at(2024, Month::March, 1, 0, 0)),
+            Some(at(2028, Month::February, 29, 12, 30))
+        );
+        let never = "0 0 31 FEB *".parse::<CalendarSpec>().unwrap();
+        assert_eq!(never.next_from(at(2024, Month::January, 1, 0, 0)), None);
+
+        for (spec, what) in [
+            ("0 9 * *", "expected 5 fields, found 4"),
+            ("60 * * * *", "60 is not in 0-59"),
+            ("* * * * MON-FOO", "'FOO' is not a value"),
+            ("*/0 * * * *", "'0' is not a step"),
+            ("* 5-3 * * *", "'5-3' is an empty range"),
+        ] {
+            assert_eq!(
+                spec.parse::<CalendarSpec>().unwrap_err().what,
+                what,
+                "{spec}"
+            );
+        }
+    }
+}
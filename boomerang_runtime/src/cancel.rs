@@ -0,0 +1,238 @@
+//! Cooperative cancellation of long-running reactions.
+//!
+//! Reactions run to completion, so the scheduler can't interrupt a reaction that takes too long. Instead, every
+//! reaction [`crate::Context`] carries a [`CancellationToken`], available with [`crate::Context::cancellation`], that
+//! long-running reactions should check regularly, e.g. once per loop iteration, to bail out early. The token is
+//! cancelled when:
+//!
+//! - the [`crate::Deadline`] of the running reaction has passed, either before or during its execution, or
+//! - the shutdown tag has been reached.
+//!
+//! The shutdown tag is reached once the scheduler starts processing it, or, unless running in fast-forward mode, once
+//! the physical time passes it. This holds for shutdowns scheduled by a reaction, by a [`crate::SendContext`] and by
+//! the [`crate::Config::timeout`] alike, so a shutdown requested asynchronously for right now cancels the reaction
+//! running at the time, while a shutdown scheduled further ahead doesn't cancel the reactions at the tags before it.
+//!
+//! Reactions that overrun their deadline without ever seeing their token cancelled are detected after they return and
+//! logged with a warning, so runaway reactions can be found even if they don't cooperate.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! fn trigger(mut self, ctx: &mut runtime::Context, state: &mut State) {
+//!     for chunk in state.work.chunks(64) {
+//!         if ctx.cancellation().is_cancelled() {
+//!             tracing::warn!("Giving up: {:?}", ctx.cancellation().reason());
+//!             return;
+//!         }
+//!         state.process(chunk);
+//!     }
+//! }
+//! ```
+
+use crate::sync::{Arc, AtomicBool, AtomicU64, Ordering};
+
+/// Why a [`CancellationToken`] was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelReason {
+    /// The deadline of the running reaction has passed.
+    DeadlineViolated,
+    /// The shutdown tag has been reached.
+    ShutdownRequested,
+}
+
+impl std::fmt::Display for CancelReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CancelReason::DeadlineViolated => write!(f, "deadline violated"),
+            CancelReason::ShutdownRequested => write!(f, "shutdown requested"),
+        }
+    }
+}
+
+/// The shutdown state shared by all tokens of a scheduler.
+#[derive(Debug)]
+struct ShutdownSignal {
+    /// The physical time `at` is relative to
+    origin: std::time::Instant,
+    /// Whether the shutdown tag is also reached when the physical time passes it, i.e. not in fast-forward mode
+    realtime: bool,
+    /// Nanoseconds after `origin` at which the earliest scheduled shutdown tag is reached, `u64::MAX` if there is none
+    at: AtomicU64,
+    /// Set by the scheduler once it starts processing the shutdown tag
+    reached: AtomicBool,
+}
+
+impl ShutdownSignal {
+    fn new(origin: std::time::Instant, realtime: bool) -> Self {
+        Self {
+            origin,
+            realtime,
+            at: AtomicU64::new(u64::MAX),
+            reached: AtomicBool::new(false),
+        }
+    }
+
+    fn is_reached(&self) -> bool {
+        if self.reached.load(Ordering::Relaxed) {
+            return true;
+        }
+        let at = self.at.load(Ordering::Relaxed);
+        at != u64::MAX && self.origin.elapsed().as_nanos() >= u128::from(at)
+    }
+}
+
+/// A token to check whether the running reaction should stop early, see the [module documentation](self).
+///
+/// Tokens can be cloned, e.g. to hand them to threads spawned by the reaction.
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    /// Shared by all reactions of the scheduler
+    shutdown: Arc<ShutdownSignal>,
+    /// The physical time at which the deadline of the running reaction passes
+    deadline: Option<std::time::Instant>,
+    /// Set once the running reaction saw the token cancelled
+    observed: Arc<AtomicBool>,
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new(std::time::Instant::now(), true)
+    }
+}
+
+impl CancellationToken {
+    /// Create a new root token for a scheduler started at `origin`.
+    ///
+    /// If `realtime`, the shutdown tag is also reached once the physical time passes it.
+    pub(crate) fn new(origin: std::time::Instant, realtime: bool) -> Self {
+        Self {
+            shutdown: Arc::new(ShutdownSignal::new(origin, realtime)),
+            deadline: None,
+            observed: Arc::default(),
+        }
+    }
+
+    /// Create a new token sharing the shutdown state with `self`, but without a deadline.
+    pub(crate) fn new_shared(&self) -> Self {
+        Self {
+            shutdown: self.shutdown.clone(),
+            deadline: None,
+            observed: Arc::default(),
+        }
+    }
+
+    /// Set the deadline for the next reaction, forgetting whether the previous one saw the token cancelled.
+    pub(crate) fn set_deadline(&mut self, deadline: Option<std::time::Instant>) {
+        self.deadline = deadline;
+        self.observed.store(false, Ordering::Relaxed);
+    }
+
+    /// Record a shutdown tag scheduled at the physical time `at`, keeping the earliest one.
+    pub(crate) fn schedule_shutdown(&self, at: std::time::Instant) {
+        if self.shutdown.realtime {
+            let nanos = at
+                .saturating_duration_since(self.shutdown.origin)
+                .as_nanos();
+            let nanos = u64::try_from(nanos).unwrap_or(u64::MAX - 1);
+            self.shutdown.at.fetch_min(nanos, Ordering::Relaxed);
+        }
+    }
+
+    /// Cancel all tokens sharing the shutdown state with `self`, as the scheduler reached the shutdown tag.
+    pub(crate) fn reach_shutdown(&self) {
+        self.shutdown.reached.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the running reaction saw the token cancelled since its deadline was set.
+    pub(crate) fn observed(&self) -> bool {
+        self.observed.load(Ordering::Relaxed)
+    }
+
+    /// Whether the reaction should stop early.
+    pub fn is_cancelled(&self) -> bool {
+        self.reason().is_some()
+    }
+
+    /// Why the reaction should stop early, if it should.
+    pub fn reason(&self) -> Option<CancelReason> {
+        let reason = if self.shutdown.is_reached() {
+            Some(CancelReason::ShutdownRequested)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| std::time::Instant::now() > deadline)
+        {
+            Some(CancelReason::DeadlineViolated)
+        } else {
+            None
+        };
+        if reason.is_some() {
+            self.observed.store(true, Ordering::Relaxed);
+        }
+        reason
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation() {
+        let root = CancellationToken::default();
+        let mut token = root.new_shared();
+        assert!(!token.is_cancelled());
+
+        token.set_deadline(Some(
+            std::time::Instant::now() - std::time::Duration::from_millis(1),
+        ));
+        assert_eq!(token.reason(), Some(CancelReason::DeadlineViolated));
+
+        assert!(token.observed());
+
+        token.set_deadline(None);
+        assert!(!token.observed());
+        root.reach_shutdown();
+        assert_eq!(token.reason(), Some(CancelReason::ShutdownRequested));
+    }
+
+    #[test]
+    fn test_scheduled_shutdown() {
+        let now = std::time::Instant::now();
+        let root = CancellationToken::new(now, true);
+        let token = root.new_shared();
+
+        root.schedule_shutdown(now + std::time::Duration::from_secs(3600));
+        assert!(!token.is_cancelled());
+        root.schedule_shutdown(now);
+        assert_eq!(token.reason(), Some(CancelReason::ShutdownRequested));
+
+        // In fast-forward mode, only reaching the shutdown tag cancels
+        let root = CancellationToken::new(now, false);
+        let token = root.new_shared();
+        root.schedule_shutdown(now);
+        assert!(!token.is_cancelled());
+        root.reach_shutdown();
+        assert!(token.is_cancelled());
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+
+    #[test]
+    fn loom_reach_shutdown() {
+        loom::model(|| {
+            let root = CancellationToken::default();
+            let [token, other] = [root.new_shared(), root.new_shared()];
+            let handle = loom::thread::spawn(move || root.reach_shutdown());
+            while !token.is_cancelled() {
+                loom::thread::yield_now();
+            }
+            handle.join().unwrap();
+            assert_eq!(token.reason(), Some(CancelReason::ShutdownRequested));
+            assert_eq!(other.reason(), Some(CancelReason::ShutdownRequested));
+        });
+    }
+}
@@ -1,16 +1,28 @@
 use crossbeam_channel::Sender;
 
 use crate::{
-    event::AsyncEvent, keepalive, ActionKey, BankInfo, Duration, ReactionGraph, ReactionKey, Tag,
+    cancel::CancellationToken, event::AsyncEvent, keepalive, scratch::Scratch, ActionKey, BankInfo,
+    Duration, Parameters, ReactionGraph, ReactionKey, ReactorData, ReactorFailure, RuntimeError,
+    Tag, TimeScale,
 };
 
 /// Result from a reaction trigger
 #[derive(Debug, Clone)]
 pub(crate) struct TriggerRes {
+    /// The reaction this is the result of
+    pub reaction: ReactionKey,
     /// Actions that have been scheduled to trigger at a future time
     pub scheduled_actions: Vec<(ActionKey, Tag)>,
     /// A shutdown was scheduled
     pub scheduled_shutdown: Option<Tag>,
+    /// The reaction panicked, and the panic was caught
+    pub failure: Option<ReactorFailure>,
+    /// The lag of physical behind logical time when the deadline of the reaction was found violated
+    pub deadline_violated: Option<Duration>,
+    /// The execution time and the budget of the reaction when it was sampled and exceeded it, see [`crate::budget`]
+    pub budget_exceeded: Option<(Duration, Duration)>,
+    /// The tags of the results of the deferred computations spawned, see [`crate::deferred`]
+    pub deferred: Vec<Tag>,
 }
 
 /// Scheduler context passed into reactor functions.
@@ -18,6 +30,8 @@ pub(crate) struct TriggerRes {
 pub struct Context {
     /// Physical time the Scheduler was started
     pub(crate) start_time: std::time::Instant,
+    /// The rate of logical relative to physical time
+    pub(crate) time_scale: TimeScale,
     /// Logical time of the currently executing epoch
     pub(crate) tag: Tag,
     /// Bank index and node count for a multi-bank reactor
@@ -30,6 +44,18 @@ pub struct Context {
 
     /// Trigger result
     pub(crate) trigger_res: TriggerRes,
+
+    /// Reusable buffers for temporaries
+    pub(crate) scratch: Scratch,
+
+    /// Cooperative cancellation of the running reaction
+    pub(crate) cancellation: CancellationToken,
+
+    /// Global parameters, only updated between tags
+    pub(crate) parameters: Parameters,
+
+    /// The span entered while the reaction runs, disabled unless [`crate::Config::reaction_spans`] is set
+    pub(crate) span: tracing::Span,
 }
 
 pub trait ContextCommon {
@@ -45,22 +71,37 @@ pub trait ContextCommon {
 }
 
 impl Context {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
+        reaction_key: ReactionKey,
         start_time: std::time::Instant,
+        time_scale: TimeScale,
         bank_info: Option<BankInfo>,
         async_tx: Sender<AsyncEvent>,
         shutdown_rx: keepalive::Receiver,
+        cancellation: CancellationToken,
+        parameters: Parameters,
     ) -> Self {
         Self {
             start_time,
+            time_scale,
             tag: Tag::NEVER,
             bank_info,
             async_tx,
             shutdown_rx,
             trigger_res: TriggerRes {
+                reaction: reaction_key,
                 scheduled_actions: Vec::new(),
                 scheduled_shutdown: None,
+                failure: None,
+                deadline_violated: None,
+                budget_exceeded: None,
+                deferred: Vec::new(),
             },
+            scratch: Scratch::default(),
+            cancellation,
+            parameters,
+            span: tracing::Span::none(),
         }
     }
 
@@ -68,6 +109,10 @@ impl Context {
         self.tag = tag;
         self.trigger_res.scheduled_actions.clear();
         self.trigger_res.scheduled_shutdown = None;
+        self.trigger_res.failure = None;
+        self.trigger_res.deadline_violated = None;
+        self.trigger_res.budget_exceeded = None;
+        self.trigger_res.deferred.clear();
     }
 
     /// Get the bank index for a multi-bank reactor
@@ -85,8 +130,16 @@ impl Context {
     }
 
     /// Get the current logical time, frozen during the execution of a reaction.
+    ///
+    /// This is the physical time at which the current tag is reached, see [`crate::Config::with_time_scale`].
     pub fn get_logical_time(&self) -> std::time::Instant {
-        self.tag.to_logical_time(self.start_time)
+        self.time_scale.instant_of(self.start_time, self.tag)
+    }
+
+    /// The tag at the current physical time.
+    pub(crate) fn physical_tag(&self) -> Tag {
+        self.time_scale
+            .tag_at(self.start_time, std::time::Instant::now())
     }
 
     /// Get the logical time elapsed since the start of the program.
@@ -94,13 +147,37 @@ impl Context {
         self.tag.offset()
     }
 
+    /// Get the pool of reusable scratch buffers for temporaries, see [`crate::scratch`].
+    ///
+    /// Buffers borrow the context, so they must be dropped before the context is used mutably, e.g. to schedule an
+    /// action.
+    pub fn scratch(&self) -> &Scratch {
+        &self.scratch
+    }
+
+    /// Get the cancellation token of the running reaction, see [`crate::cancel`].
+    ///
+    /// Long-running reactions should check it regularly and return early once it is cancelled.
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Get the global parameter `name`, see [`crate::params`].
+    ///
+    /// Fails if there is no such parameter, or if it is not of type `T`.
+    pub fn parameter<T: ReactorData>(&self, name: &str) -> Result<&T, RuntimeError> {
+        self.parameters.get(name)
+    }
+
     /// Create a new SendContext that can be shared across threads.
     /// This is used to schedule asynchronous events.
     pub fn make_send_context(&self) -> SendContext {
         SendContext {
             start_time: self.start_time,
+            time_scale: self.time_scale,
             async_tx: self.async_tx.clone(),
             shutdown_rx: self.shutdown_rx.clone(),
+            cancellation: self.cancellation.new_shared(),
         }
     }
 }
@@ -125,10 +202,14 @@ impl ContextCommon for Context {
 pub struct SendContext {
     /// Physical time the Scheduler was started
     pub start_time: std::time::Instant,
+    /// The rate of logical relative to physical time
+    pub(crate) time_scale: TimeScale,
     /// Channel for asynchronous events
     pub(crate) async_tx: Sender<AsyncEvent>,
     /// Shutdown channel
     shutdown_rx: keepalive::Receiver,
+    /// Cancels running reactions once the shutdown tag is reached
+    cancellation: CancellationToken,
 }
 
 impl SendContext {
@@ -136,6 +217,12 @@ impl SendContext {
     pub fn is_shutdown(&self) -> bool {
         self.shutdown_rx.is_shutdwon()
     }
+
+    /// The tag at the current physical time.
+    pub(crate) fn physical_tag(&self) -> Tag {
+        self.time_scale
+            .tag_at(self.start_time, std::time::Instant::now())
+    }
 }
 
 impl ContextCommon for SendContext {
@@ -144,32 +231,51 @@ impl ContextCommon for SendContext {
     }
 
     /// Schedule a shutdown event at some future time.
+    ///
+    /// This also cancels the reactions still running once the shutdown tag is reached, see [`crate::cancel`].
     fn schedule_shutdown(&mut self, offset: Option<Duration>) {
-        let tag = Tag::from_physical_time(self.start_time, std::time::Instant::now())
-            .delay(offset.unwrap_or_default());
+        let tag = self.physical_tag().delay(offset.unwrap_or_default());
+        self.cancellation
+            .schedule_shutdown(self.time_scale.instant_of(self.start_time, tag));
         let event = AsyncEvent::shutdown(tag);
         self.async_tx.send(event).unwrap();
     }
 }
 
 /// Build contexts for each reaction
+#[allow(clippy::too_many_arguments)]
 pub fn build_reaction_contexts(
     reaction_graph: &ReactionGraph,
     start_time: std::time::Instant,
+    time_scale: TimeScale,
     event_tx: crossbeam_channel::Sender<AsyncEvent>,
     shutdown_rx: keepalive::Receiver,
+    cancellation: &CancellationToken,
+    parameters: &Parameters,
+    reaction_spans: bool,
 ) -> tinymap::TinySecondaryMap<ReactionKey, Context> {
     reaction_graph
         .reaction_reactors
         .iter()
         .map(|(reaction_key, reactor_key)| {
             let bank_info = &reaction_graph.reactor_bank_infos[*reactor_key];
-            let ctx = Context::new(
+            let mut ctx = Context::new(
+                reaction_key,
                 start_time,
+                time_scale,
                 bank_info.clone(),
                 event_tx.clone(),
                 shutdown_rx.clone(),
+                cancellation.new_shared(),
+                parameters.clone(),
             );
+            if reaction_spans {
+                // Spans at the error level are kept by any level filter, leaving the decision to the subscriber
+                ctx.span = tracing::error_span!(
+                    "reaction",
+                    fqn = reaction_graph.reaction_fqn(reaction_key)
+                );
+            }
             (reaction_key, ctx)
         })
         .collect()
@@ -0,0 +1,140 @@
+//! Deferred computations, offloading heavy work from a reaction to a background thread.
+//!
+//! [`crate::Context::spawn_deferred`] runs a closure on a background thread and schedules a logical action with its
+//! result. The tag of the action is fixed when the computation is spawned, as the current tag plus an explicit, strictly
+//! positive delay, exactly as if the result had been scheduled synchronously. The scheduler keeps processing the tags in
+//! between, and only waits for the computation when it reaches its tag, so the behavior of the program doesn't depend
+//! on how long the computation takes.
+//!
+//! If the closure panics, the panic is logged and the action is not scheduled. Results for tags after the shutdown tag
+//! are discarded.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! fn trigger(mut self, ctx: &mut runtime::Context, state: &mut State) {
+//!     let image = state.image.clone();
+//!     ctx.spawn_deferred(move || detect_features(&image), &mut self.features, Duration::milliseconds(100))
+//!         .unwrap();
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::{
+    event::AsyncEvent, isolation::panic_message, ActionCommon, ActionRef, Context, Duration,
+    ReactorData, RuntimeError, Tag,
+};
+
+impl Context {
+    /// Run `f` on a background thread, and schedule `action` with its result `delay` after the current tag.
+    ///
+    /// The `delay` is added to the minimum delay of the action, and must be strictly positive. Only logical actions can
+    /// be deferred to, since the tag of a physical action would depend on when the computation completes. See
+    /// [`crate::deferred`] for details.
+    pub fn spawn_deferred<T, F>(
+        &mut self,
+        f: F,
+        action: &mut ActionRef<'_, T>,
+        delay: Duration,
+    ) -> Result<(), RuntimeError>
+    where
+        T: ReactorData,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        if !action.is_logical() {
+            return Err(RuntimeError::InvalidDeferral {
+                action: action.name().to_owned(),
+                reason: "only logical actions can be scheduled with a deferred result".to_owned(),
+            });
+        }
+        if !delay.is_positive() {
+            return Err(RuntimeError::InvalidDeferral {
+                action: action.name().to_owned(),
+                reason: format!("the delay must be strictly positive, got {delay}"),
+            });
+        }
+
+        let tag = self.tag.delay(action.min_delay() + delay);
+        let key = action.key();
+        let async_tx = self.async_tx.clone();
+        self.trigger_res.deferred.push(tag);
+
+        std::thread::Builder::new()
+            .name("boomerang-deferred".into())
+            .spawn(move || {
+                let value = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+                    Ok(value) => Some(Box::new(value) as Box<dyn ReactorData>),
+                    Err(payload) => {
+                        tracing::error!(
+                            tag = %tag,
+                            "Deferred computation panicked: {}",
+                            panic_message(payload.as_ref())
+                        );
+                        None
+                    }
+                };
+                // The scheduler may already have shut down
+                let _ = async_tx.send(AsyncEvent::Deferred { tag, key, value });
+            })
+            .expect("Failed to spawn the deferred thread");
+        Ok(())
+    }
+}
+
+/// The number of deferred computations still running, by the tag of their result.
+#[derive(Debug, Default)]
+pub(crate) struct PendingDeferred(BTreeMap<Tag, usize>);
+
+impl PendingDeferred {
+    /// Record a computation spawned for `tag`.
+    pub fn spawned(&mut self, tag: Tag) {
+        *self.0.entry(tag).or_default() += 1;
+    }
+
+    /// Record a computation for `tag` as completed.
+    pub fn completed(&mut self, tag: Tag) {
+        if let Some(count) = self.0.get_mut(&tag) {
+            *count -= 1;
+            if *count == 0 {
+                self.0.remove(&tag);
+            }
+        }
+    }
+
+    /// The earliest tag with a computation still running.
+    pub fn first(&self) -> Option<Tag> {
+        self.0.keys().next().copied()
+    }
+
+    /// Whether a computation for `tag` or before is still running, so `tag` can't be processed yet.
+    pub fn blocks(&self, tag: Tag) -> bool {
+        self.first().is_some_and(|first| first <= tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_deferred() {
+        let t1 = Tag::new(Duration::milliseconds(1), 0);
+        let t2 = Tag::new(Duration::milliseconds(2), 0);
+        let mut pending = PendingDeferred::default();
+        pending.spawned(t2);
+        pending.spawned(t1);
+        pending.spawned(t1);
+        assert_eq!(pending.first(), Some(t1));
+        assert!(!pending.blocks(Tag::ZERO));
+        assert!(pending.blocks(t1));
+
+        pending.completed(t1);
+        assert_eq!(pending.first(), Some(t1));
+        pending.completed(t1);
+        assert_eq!(pending.first(), Some(t2));
+        assert!(!pending.blocks(t1));
+        pending.completed(t2);
+        assert_eq!(pending.first(), None);
+    }
+}
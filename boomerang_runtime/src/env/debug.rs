@@ -46,6 +46,10 @@ impl std::fmt::Debug for Env {
             .field("actions", &actions)
             .field("ports", &ports)
             .field("reactions", &reactions)
+            .field("probes", &self.probes)
+            .field("inits", &self.inits)
+            .field("flushes", &self.flushes)
+            .field("parameters", &self.parameters)
             .finish()
     }
 }
@@ -85,11 +89,23 @@ impl std::fmt::Debug for ReactionGraph {
             .field("port_triggers", &port_triggers)
             .field("startup_reactions", &self.startup_reactions)
             .field("shutdown_reactions", &self.shutdown_reactions)
+            .field("failure_actions", &self.failure_actions)
+            .field("overload_actions", &self.overload_actions)
+            .field("conflatable_actions", &self.conflatable_actions)
+            .field("parameter_actions", &self.parameter_actions)
             .field("reaction_set_limits", &self.reaction_set_limits)
+            .field("reaction_set_stats", &self.reaction_set_stats)
             .field("reaction_use_ports", &self.reaction_use_ports)
             .field("reaction_effect_ports", &self.reaction_effect_ports)
             .field("reaction_actions", &self.reaction_actions)
+            .field("action_reactors", &self.action_reactors)
+            .field("port_reactors", &self.port_reactors)
             .field("reactor_bank_infos", &self.reactor_bank_infos)
+            .field("reactor_fqns", &self.reactor_fqns)
+            .field("reaction_fqns", &self.reaction_fqns)
+            .field("action_fqns", &self.action_fqns)
+            .field("port_fqns", &self.port_fqns)
+            .field("metadata", &self.metadata)
             .finish()
     }
 }
@@ -1,6 +1,7 @@
 use crate::{
-    key_set::KeySetLimits, ActionKey, BaseAction, BasePort, BaseReactor, PortKey, Reaction,
-    ReactionKey, ReactorKey,
+    key_set::{KeySetLimits, KeySetStats},
+    ActionKey, BaseAction, BasePort, BaseReactor, Flush, Init, Parameters, PortKey, Probe,
+    Reaction, ReactionKey, Reactor, ReactorData, ReactorKey, TypedReactorKey,
 };
 
 mod debug;
@@ -56,6 +57,7 @@ pub type LevelReactionKey = (Level, ReactionKey);
 /// `Env` stores the resolved runtime state of all the reactors.
 ///
 /// The reactor heirarchy has been flattened and build by the builder methods.
+#[derive(Default)]
 pub struct Env {
     /// The runtime set of Reactors
     pub reactors: tinymap::TinyMap<ReactorKey, Box<dyn BaseReactor>>,
@@ -65,9 +67,27 @@ pub struct Env {
     pub ports: tinymap::TinyMap<PortKey, Box<dyn BasePort>>,
     /// The runtime set of Reactions
     pub reactions: tinymap::TinyMap<ReactionKey, Reaction>,
+    /// Value probes evaluated by the scheduler
+    pub probes: Vec<Probe>,
+    /// Initializations run before logical time begins, see [`crate::init`]
+    pub inits: Vec<Init>,
+    /// Flushes run after the shutdown tag, see [`crate::flush`]
+    pub flushes: Vec<Flush>,
+    /// Read-only global parameters, shared by all reactions
+    pub parameters: Parameters,
 }
 
 impl Env {
+    /// Estimate the memory used by each reactor, see [`crate::mem_size`].
+    pub fn memory_report(&self, reaction_graph: &ReactionGraph) -> crate::mem_size::MemoryReport {
+        crate::mem_size::MemoryReport::new(
+            &self.reactors,
+            &self.ports,
+            &self.actions,
+            reaction_graph,
+        )
+    }
+
     /// Get a reactor by it's name
     pub fn find_reactor_by_name(&self, name: &str) -> Option<&dyn BaseReactor> {
         self.reactors
@@ -75,6 +95,30 @@ impl Env {
             .find(|(_, reactor)| reactor.name() == name)
             .map(|(_, reactor)| reactor.as_ref())
     }
+
+    /// Get a typed key for the reactor `key`, if its state is of type `S`.
+    pub fn typed_reactor_key<S: ReactorData>(&self, key: ReactorKey) -> Option<TypedReactorKey<S>> {
+        self.reactors
+            .get(key)
+            .filter(|reactor| reactor.is::<Reactor<S>>())
+            .map(|_| TypedReactorKey::new(key))
+    }
+
+    /// Get the state of the reactor `key`.
+    pub fn state<S: ReactorData>(&self, key: TypedReactorKey<S>) -> &S {
+        &self.reactors[key.key()]
+            .downcast_ref::<Reactor<S>>()
+            .expect("TypedReactorKey state type mismatch")
+            .state
+    }
+
+    /// Get the mutable state of the reactor `key`.
+    pub fn state_mut<S: ReactorData>(&mut self, key: TypedReactorKey<S>) -> &mut S {
+        &mut self.reactors[key.key()]
+            .downcast_mut::<Reactor<S>>()
+            .expect("TypedReactorKey state type mismatch")
+            .state
+    }
 }
 
 /// Bank information for a multi-bank port/reactor
@@ -87,10 +131,25 @@ pub struct BankInfo {
     pub total: usize,
 }
 
+/// Free-form metadata attached to an element in the builder, e.g. by code generators to record the ID of the model
+/// element it was generated from.
+pub type Metadata = std::collections::BTreeMap<String, String>;
+
+/// The [`Metadata`] of all elements that have any, preserved from the builder.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnvMetadata {
+    pub reactors: tinymap::TinySecondaryMap<ReactorKey, Metadata>,
+    pub reactions: tinymap::TinySecondaryMap<ReactionKey, Metadata>,
+    pub ports: tinymap::TinySecondaryMap<PortKey, Metadata>,
+    pub actions: tinymap::TinySecondaryMap<ActionKey, Metadata>,
+}
+
 /// Invariant data for the runtime, describing the resolved reaction graph and it's dependencies.
 ///
 /// Maps of triggers for actions and ports. This data is statically resolved by the builder from the
 /// reaction graph.
+#[derive(Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReactionGraph {
     /// For each Action, a set of Reactions triggered by it.
@@ -101,9 +160,19 @@ pub struct ReactionGraph {
     pub startup_reactions: Vec<LevelReactionKey>,
     /// Global shutdown reactions
     pub shutdown_reactions: Vec<LevelReactionKey>,
+    /// Actions scheduled with the [`crate::ReactorFailure`]s whenever a panic is caught, see [`crate::isolation`].
+    pub failure_actions: Vec<ActionKey>,
+    /// Actions scheduled with an [`crate::Overload`] report on sustained overload, see [`crate::overload`].
+    pub overload_actions: Vec<ActionKey>,
+    /// Actions whose overdue events may be dropped on sustained overload, see [`crate::overload`].
+    pub conflatable_actions: Vec<ActionKey>,
+    /// Actions scheduled with a [`crate::ParameterChange`] whenever parameters are updated, see [`crate::params`].
+    pub parameter_actions: Vec<ActionKey>,
     /// The maximum level of any reaction, and the total number of reactions. This is used to
     /// allocate the reaction set.
     pub reaction_set_limits: KeySetLimits,
+    /// The number of reactions at each level, e.g. to pre-size per-level data structures or choose a worker count.
+    pub reaction_set_stats: KeySetStats,
     /// For each reaction, the set of 'use' ports
     pub reaction_use_ports: tinymap::TinySecondaryMap<ReactionKey, tinymap::KeySet<PortKey>>,
     /// For each reaction, the set of 'effect' ports
@@ -112,8 +181,56 @@ pub struct ReactionGraph {
     pub reaction_actions: tinymap::TinySecondaryMap<ReactionKey, tinymap::KeySet<ActionKey>>,
     /// For each reaction, the reactor it belongs to
     pub reaction_reactors: tinymap::TinySecondaryMap<ReactionKey, ReactorKey>,
+    /// For each action, the reactor it belongs to
+    pub action_reactors: tinymap::TinySecondaryMap<ActionKey, ReactorKey>,
+    /// For each port, the reactor it belongs to, the reactor of the source port of connected ports
+    pub port_reactors: tinymap::TinySecondaryMap<PortKey, ReactorKey>,
     /// Bank index for a multi-bank reactor
     pub reactor_bank_infos: tinymap::TinySecondaryMap<ReactorKey, Option<BankInfo>>,
+    /// The fully-qualified name of each reactor
+    pub reactor_fqns: tinymap::TinySecondaryMap<ReactorKey, String>,
+    /// The fully-qualified name of each reaction, e.g. for the [`crate::lifecycle`] events
+    pub reaction_fqns: tinymap::TinySecondaryMap<ReactionKey, String>,
+    /// The fully-qualified name of each action
+    pub action_fqns: tinymap::TinySecondaryMap<ActionKey, String>,
+    /// The fully-qualified name of each port, named after the source port of connected ports
+    pub port_fqns: tinymap::TinySecondaryMap<PortKey, String>,
+    /// Metadata attached to elements in the builder
+    pub metadata: EnvMetadata,
+}
+
+impl ReactionGraph {
+    /// The fully-qualified name of a reactor, or an empty string if the graph wasn't built with names.
+    pub fn reactor_fqn(&self, reactor_key: ReactorKey) -> &str {
+        self.reactor_fqns
+            .get(reactor_key)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// The fully-qualified name of a reaction, or an empty string if the graph wasn't built with names.
+    pub fn reaction_fqn(&self, reaction_key: ReactionKey) -> &str {
+        self.reaction_fqns
+            .get(reaction_key)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// The fully-qualified name of an action, or an empty string if the graph wasn't built with names.
+    pub fn action_fqn(&self, action_key: ActionKey) -> &str {
+        self.action_fqns
+            .get(action_key)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// The fully-qualified name of a port, or an empty string if the graph wasn't built with names.
+    pub fn port_fqn(&self, port_key: PortKey) -> &str {
+        self.port_fqns
+            .get(port_key)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -153,6 +270,10 @@ pub mod tests {
             ]
             .into_iter()
             .collect(),
+            probes: Vec::new(),
+            inits: Vec::new(),
+            flushes: Vec::new(),
+            parameters: Default::default(),
         };
 
         let reactor_key = env.reactors.keys().next().unwrap();
@@ -165,10 +286,15 @@ pub mod tests {
             port_triggers: tinymap::TinySecondaryMap::new(),
             startup_reactions: Vec::new(),
             shutdown_reactions: Vec::new(),
+            failure_actions: Vec::new(),
+            overload_actions: Vec::new(),
+            parameter_actions: Vec::new(),
+            conflatable_actions: Vec::new(),
             reaction_set_limits: ReactionSetLimits {
                 max_level: 0.into(),
                 num_keys: 0,
             },
+            reaction_set_stats: Default::default(),
             reaction_use_ports: [(reaction_key, std::iter::once(port_keys[0]).collect())]
                 .into_iter()
                 .collect(),
@@ -179,7 +305,14 @@ pub mod tests {
                 .into_iter()
                 .collect(),
             reaction_reactors: [(reaction_key, reactor_key)].into_iter().collect(),
+            action_reactors: tinymap::TinySecondaryMap::new(),
+            port_reactors: tinymap::TinySecondaryMap::new(),
             reactor_bank_infos: tinymap::TinySecondaryMap::new(),
+            reactor_fqns: tinymap::TinySecondaryMap::new(),
+            reaction_fqns: tinymap::TinySecondaryMap::new(),
+            action_fqns: tinymap::TinySecondaryMap::new(),
+            port_fqns: tinymap::TinySecondaryMap::new(),
+            metadata: Default::default(),
         };
         (env, reaction_graph)
     }
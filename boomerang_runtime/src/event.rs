@@ -1,6 +1,12 @@
-use std::fmt::{Debug, Display};
+use std::{
+    fmt::{Debug, Display},
+    sync::{Arc, Mutex},
+};
 
-use crate::{ActionKey, Duration, LevelReactionKey, ReactionGraph, ReactionSet, ReactorData, Tag};
+use crate::{
+    lifecycle::TrackedEvent, ActionKey, Duration, LevelReactionKey, Parameter, ReactionGraph,
+    ReactionSet, ReactorData, Tag,
+};
 
 /// `ScheduledEvent` is used internally by the scheduler loop in the event queue. The dependent reactions are already expanded into a single reaction set.
 #[derive(Debug, Clone)]
@@ -11,6 +17,10 @@ pub struct ScheduledEvent {
     pub(crate) reactions: ReactionSet,
     /// Whether the scheduler should terminate after processing this event.
     pub(crate) terminal: bool,
+    /// The action this event was scheduled on, if any.
+    pub(crate) action: Option<ActionKey>,
+    /// The event whose lifecycle is logged, see [`crate::lifecycle`].
+    pub(crate) tracked: Option<TrackedEvent>,
 }
 
 impl Display for ScheduledEvent {
@@ -51,6 +61,8 @@ pub enum AsyncEvent {
         /// The delay that should be applied to this event. This will be added to the current logical time to determine
         /// the tag.
         delay: Duration,
+        /// Additional microsteps after the delayed tag, see [`crate::AsyncActionRef::schedule_microstep`].
+        microsteps: usize,
         /// The key of the action that triggered this event.
         key: ActionKey,
         /// The value associated with this event.
@@ -67,19 +79,62 @@ pub enum AsyncEvent {
         value: Box<dyn ReactorData>,
     },
 
+    /// An event at an absolute `tag` given by the caller, see [`crate::AsyncActionRef::schedule_at`]. It is dropped
+    /// if the tag is not strictly after the current logical tag when the scheduler receives it.
+    Absolute {
+        /// The [`Tag`] at which the reactions in this event should be executed.
+        tag: Tag,
+        /// The [`ActionKey`] of the action that triggered this event.
+        key: ActionKey,
+        /// The value associated with this event.
+        value: Box<dyn ReactorData>,
+    },
+
+    /// The result of a deferred computation, scheduled at the absolute `tag` fixed when the computation was spawned, see
+    /// [`crate::deferred`].
+    Deferred {
+        /// The [`Tag`] at which the reactions in this event should be executed.
+        tag: Tag,
+        /// The [`ActionKey`] of the action to schedule.
+        key: ActionKey,
+        /// The result of the computation, or `None` if it panicked.
+        value: Option<Box<dyn ReactorData>>,
+    },
+
     /// The scheduler should terminate after processing this event.
     Shutdown {
         /// The [`Tag`] at which the reactions in this event should be executed.
         tag: Tag,
     },
+
+    /// The latest event of a coalescing action, taken by the scheduler when it receives this, see
+    /// [`crate::backpressure`].
+    Coalesced {
+        /// The pending event, replaced by newer ones until taken
+        pending: Arc<Mutex<Option<AsyncEvent>>>,
+    },
+
+    /// Update the reloadable parameter `name` at the next tag, see [`crate::params`].
+    Parameter {
+        /// The name of the parameter
+        name: String,
+        /// The new value
+        value: Parameter,
+    },
 }
 
 impl Debug for AsyncEvent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Logical { delay, key, value } => f
+            Self::Logical {
+                delay,
+                microsteps,
+                key,
+                value,
+            } => f
                 .debug_struct("Logical")
                 .field("delay", delay)
+                .field("microsteps", microsteps)
                 .field("key", key)
                 .field(
                     "value",
@@ -95,7 +150,36 @@ impl Debug for AsyncEvent {
                     &format!("Box<{}>", std::any::type_name_of_val(&**value)),
                 )
                 .finish(),
+            Self::Absolute { tag, key, value } => f
+                .debug_struct("Absolute")
+                .field("tag", tag)
+                .field("key", key)
+                .field(
+                    "value",
+                    &format!("Box<{}>", std::any::type_name_of_val(&**value)),
+                )
+                .finish(),
+            Self::Deferred { tag, key, value } => f
+                .debug_struct("Deferred")
+                .field("tag", tag)
+                .field("key", key)
+                .field(
+                    "value",
+                    &value
+                        .as_ref()
+                        .map(|value| format!("Box<{}>", std::any::type_name_of_val(&**value))),
+                )
+                .finish(),
             Self::Shutdown { tag } => f.debug_struct("Shutdown").field("tag", tag).finish(),
+            Self::Coalesced { pending } => f
+                .debug_struct("Coalesced")
+                .field("pending", pending)
+                .finish(),
+            Self::Parameter { name, value } => f
+                .debug_struct("Parameter")
+                .field("name", name)
+                .field("value", value)
+                .finish(),
         }
     }
 }
@@ -105,12 +189,13 @@ impl Display for AsyncEvent {
         match self {
             AsyncEvent::Logical {
                 delay,
+                microsteps,
                 key,
                 value: _,
             } => {
                 write!(
                     f,
-                    "AsyncLogical[delay={delay},key={key:?},value=..]",
+                    "AsyncLogical[delay={delay},microsteps={microsteps},key={key:?},value=..]",
                     delay = delay.as_seconds_f64()
                 )
             }
@@ -122,17 +207,39 @@ impl Display for AsyncEvent {
                     key = key
                 )
             }
+            AsyncEvent::Absolute { tag, key, value: _ } => {
+                write!(f, "AsyncAbsolute[tag={tag},key={key:?},value=..]")
+            }
+            AsyncEvent::Deferred { tag, key, value: _ } => {
+                write!(f, "AsyncDeferred[tag={tag},key={key:?},value=..]")
+            }
             AsyncEvent::Shutdown { tag } => {
                 write!(f, "AsyncShutdown[tag={tag}]")
             }
+            AsyncEvent::Coalesced { pending: _ } => {
+                write!(f, "AsyncCoalesced[pending=..]")
+            }
+            AsyncEvent::Parameter { name, value: _ } => {
+                write!(f, "AsyncParameter[name={name},value=..]")
+            }
         }
     }
 }
 
 impl AsyncEvent {
     /// Create a logical event.
-    pub(crate) fn logical(key: ActionKey, delay: Duration, value: Box<dyn ReactorData>) -> Self {
-        AsyncEvent::Logical { delay, key, value }
+    pub(crate) fn logical(
+        key: ActionKey,
+        delay: Duration,
+        microsteps: usize,
+        value: Box<dyn ReactorData>,
+    ) -> Self {
+        AsyncEvent::Logical {
+            delay,
+            microsteps,
+            key,
+            value,
+        }
     }
 
     /// Create a physical event.
@@ -140,6 +247,11 @@ impl AsyncEvent {
         AsyncEvent::Physical { tag, key, value }
     }
 
+    /// Create an event at an absolute tag.
+    pub(crate) fn absolute(key: ActionKey, tag: Tag, value: Box<dyn ReactorData>) -> Self {
+        AsyncEvent::Absolute { tag, key, value }
+    }
+
     /// Create a shutdown event.
     pub(crate) fn shutdown(tag: Tag) -> Self {
         AsyncEvent::Shutdown { tag }
@@ -152,10 +264,16 @@ impl AsyncEvent {
     ) -> impl Iterator<Item = LevelReactionKey> + 'a {
         match self {
             AsyncEvent::Logical { key, .. } => reaction_graph.action_triggers[*key].iter().copied(),
-            AsyncEvent::Physical { key, .. } => {
+            AsyncEvent::Physical { key, .. }
+            | AsyncEvent::Absolute { key, .. }
+            | AsyncEvent::Deferred { key, .. } => {
                 reaction_graph.action_triggers[*key].iter().copied()
             }
             AsyncEvent::Shutdown { .. } => reaction_graph.shutdown_reactions.iter().copied(),
+            // The reactions of the pending event are triggered when it's taken
+            AsyncEvent::Coalesced { .. } => [].iter().copied(),
+            // The reactions of all parameter change actions are triggered once for all updates at a tag
+            AsyncEvent::Parameter { .. } => [].iter().copied(),
         }
     }
 }
@@ -175,16 +293,22 @@ mod tests {
             tag: Tag::new(Duration::seconds(1), 0),
             reactions: ReactionSet::default(),
             terminal: false,
+            action: None,
+            tracked: None,
         });
         heap.push(ScheduledEvent {
             tag: Tag::new(Duration::seconds(1), 0),
             reactions: ReactionSet::default(),
             terminal: true,
+            action: None,
+            tracked: None,
         });
         heap.push(ScheduledEvent {
             tag: Tag::new(Duration::seconds(0), 0),
             reactions: ReactionSet::default(),
             terminal: false,
+            action: None,
+            tracked: None,
         });
 
         // The top event should NOT be the shutdown event
@@ -0,0 +1,103 @@
+//! Final flush of sink reactors at shutdown.
+//!
+//! At the shutdown tag, the events scheduled at that tag are processed first, each in its own pass as at any other tag,
+//! and the shutdown reactions last. The ports are reset after each pass, so a sink that persists its inputs in a
+//! shutdown reaction, e.g. closing a file, can miss values: those set in earlier passes at the shutdown tag, and those
+//! set by upstream shutdown reactions if it doesn't declare the inputs as used, since it may then run first.
+//!
+//! Reactors that persist data, e.g. file writers or network senders, declare a [`Flush`] instead. After all passes at
+//! the shutdown tag, the scheduler runs the flushes on its own thread in the order they were declared, with the final
+//! state of their reactor, before [`crate::Scheduler::event_loop`] returns. Every value a sink received is in its state
+//! by then. A flush returning an error or panicking doesn't prevent the other flushes from running, and the first
+//! failure is returned as [`RuntimeError::FlushFailed`].
+//!
+//! If the shutdown tag is abandoned after its [`crate::Config::shutdown_grace`], the flushes run on the abandoned thread
+//! once its reactions complete, and their failures are only logged.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! let flush = runtime::Flush::new("main::writer::sync", reactor_key, |writer: &mut Writer| {
+//!     writer.file.flush()?;
+//!     writer.file.sync_all()
+//! });
+//! ```
+
+use std::{fmt::Display, panic::AssertUnwindSafe, pin::Pin};
+
+use crate::{
+    isolation::panic_message, store::Store, BaseReactor, ReactorData, ReactorKey, RuntimeError,
+};
+
+type RunFn = Box<dyn FnOnce(&mut dyn BaseReactor) -> Result<(), String> + Send>;
+
+/// A flush run after the shutdown tag, see the [module documentation](self).
+pub struct Flush {
+    name: String,
+    reactor_key: ReactorKey,
+    run: RunFn,
+}
+
+impl std::fmt::Debug for Flush {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Flush")
+            .field("name", &self.name)
+            .field("reactor_key", &self.reactor_key)
+            .finish()
+    }
+}
+
+impl Flush {
+    /// Create a flush of the reactor `reactor_key` with state `S`, running `flush` with the reactor state.
+    pub fn new<S, E, F>(name: &str, reactor_key: ReactorKey, flush: F) -> Self
+    where
+        S: ReactorData,
+        E: Display,
+        F: FnOnce(&mut S) -> Result<(), E> + Send + 'static,
+    {
+        let run = Box::new(move |reactor: &mut dyn BaseReactor| {
+            let state = reactor
+                .get_state_mut::<S>()
+                .expect("Flush applied to a reactor with a different state type");
+            flush(state).map_err(|err| err.to_string())
+        });
+        Self {
+            name: name.to_owned(),
+            reactor_key,
+            run,
+        }
+    }
+
+    /// The name of the flush
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The key of the reactor flushed
+    pub fn reactor_key(&self) -> ReactorKey {
+        self.reactor_key
+    }
+}
+
+/// Run all `flushes` in order with the reactors in `store`, and return the first failure.
+pub(crate) fn run(flushes: Vec<Flush>, store: &mut Pin<Box<Store>>) -> Result<(), RuntimeError> {
+    let mut failure = None;
+    for Flush {
+        name,
+        reactor_key,
+        run,
+    } in flushes
+    {
+        tracing::debug!(flush = %name, "Flushing.");
+        let reactor = store.get_reactor_mut(reactor_key);
+        let result =
+            std::panic::catch_unwind(AssertUnwindSafe(|| run(reactor))).unwrap_or_else(|payload| {
+                Err(format!("panicked: {}", panic_message(payload.as_ref())))
+            });
+        if let Err(reason) = result {
+            tracing::error!(flush = %name, "Flush failed: {reason}");
+            failure.get_or_insert(RuntimeError::FlushFailed { name, reason });
+        }
+    }
+    failure.map_or(Ok(()), Err)
+}
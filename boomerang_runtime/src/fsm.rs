@@ -0,0 +1,40 @@
+//! Support for reactor states that are finite state machines.
+//!
+//! The [`StateMachine`] trait is usually implemented with `#[derive(ReactorFsm)]`, which generates
+//! [`StateMachine::next_state`] from a declared transition table. Reactions then drive the machine with
+//! [`StateMachine::transition`], which rejects transitions missing from the table and logs every transition with the
+//! current tag.
+
+use std::fmt::Debug;
+
+use crate::{Context, RuntimeError};
+
+pub trait StateMachine: Debug + Sized {
+    /// The type of events that drive the state machine.
+    type Event: Debug;
+
+    /// Look up the state reached from the current state on `event`, or `None` if the transition is not allowed.
+    fn next_state(&self, event: &Self::Event) -> Option<Self>;
+
+    /// Apply `event` to the state machine.
+    ///
+    /// Returns [`RuntimeError::InvalidTransition`] and leaves the state unchanged if the transition is not allowed.
+    fn transition(&mut self, event: Self::Event, ctx: &Context) -> Result<(), RuntimeError> {
+        let tag = ctx.get_tag();
+        match self.next_state(&event) {
+            Some(next) => {
+                tracing::info!(tag = %tag, from = ?self, to = ?next, event = ?event, "State transition");
+                *self = next;
+                Ok(())
+            }
+            None => {
+                tracing::warn!(tag = %tag, state = ?self, event = ?event, "Invalid state transition");
+                Err(RuntimeError::InvalidTransition {
+                    state: format!("{self:?}"),
+                    event: format!("{event:?}"),
+                    tag,
+                })
+            }
+        }
+    }
+}
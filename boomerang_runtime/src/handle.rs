@@ -0,0 +1,42 @@
+//! A handle to control a running [`crate::Scheduler`] from other threads.
+
+use crossbeam_channel::Sender;
+
+use crate::{event::AsyncEvent, Parameter, Parameters, ReactorData, RuntimeError};
+
+/// A cloneable handle to a [`crate::Scheduler`], usable from other threads while its event loop runs, see
+/// [`crate::Scheduler::handle`].
+#[derive(Debug, Clone)]
+pub struct SchedulerHandle {
+    event_tx: Sender<AsyncEvent>,
+    /// The parameters at build time, to check updates against
+    parameters: Parameters,
+}
+
+impl SchedulerHandle {
+    pub(crate) fn new(event_tx: Sender<AsyncEvent>, parameters: Parameters) -> Self {
+        Self {
+            event_tx,
+            parameters,
+        }
+    }
+
+    /// Update the reloadable parameter `name` to `value` at the next tag, see [`crate::params`].
+    ///
+    /// Fails if the parameter doesn't exist, isn't reloadable or has a different type, or if the scheduler is no longer
+    /// running. Blocks while the queue of asynchronous events is full, see [`crate::Config::with_queue_size`].
+    pub fn update_parameter<T: ReactorData>(
+        &self,
+        name: &str,
+        value: T,
+    ) -> Result<(), RuntimeError> {
+        let value = Parameter::reloadable(value);
+        self.parameters.check_update(name, &value)?;
+        self.event_tx
+            .send(AsyncEvent::Parameter {
+                name: name.to_owned(),
+                value,
+            })
+            .map_err(|_| RuntimeError::SchedulerStopped)
+    }
+}
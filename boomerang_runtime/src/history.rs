@@ -0,0 +1,126 @@
+//! Bounded execution history for stepping back over recently processed tags.
+//!
+//! When enabled with [`crate::Config::with_history_window`], the [`crate::Scheduler`] keeps a [`TagRecord`] for each of
+//! the most recently processed tags, holding the reactions that ran, the values of all ports set at that tag and the
+//! states of the reactors whose reactions ran, each by its fully-qualified name. Values and states are formatted with
+//! [`crate::value_fmt`], so only registered types are shown.
+//!
+//! In interactive mode (see [`crate::Config::with_interactive`]), the history can be browsed whenever the scheduler
+//! pauses. The history only records what happened: stepping back inspects earlier tags without rewinding any state,
+//! so resuming continues forward from the paused tag exactly as it would have without the history.
+
+use std::{collections::VecDeque, fmt::Display};
+
+use crate::Tag;
+
+/// What happened at a single tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagRecord {
+    pub tag: Tag,
+    /// The fully-qualified names of the reactions that ran, in order of execution
+    pub reactions: Vec<String>,
+    /// The fully-qualified names and formatted values of the ports set at this tag
+    pub ports: Vec<(String, Option<String>)>,
+    /// The fully-qualified names and formatted states of the reactors whose reactions ran, after the tag was processed
+    pub states: Vec<(String, Option<String>)>,
+}
+
+impl Display for TagRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fmt_entries = |entries: &[(String, Option<String>)]| {
+            entries
+                .iter()
+                .map(|(name, value)| format!("{name} = {}", value.as_deref().unwrap_or("?")))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(f, "Tag {}", self.tag)?;
+        writeln!(f, "  reactions: [{}]", self.reactions.join(", "))?;
+        writeln!(f, "  ports: [{}]", fmt_entries(&self.ports))?;
+        write!(f, "  states: [{}]", fmt_entries(&self.states))
+    }
+}
+
+/// A ring buffer of the most recent [`TagRecord`]s.
+#[derive(Debug, Default)]
+pub struct History {
+    window: usize,
+    records: VecDeque<TagRecord>,
+}
+
+impl History {
+    /// Create a new history keeping at most `window` tags.
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            records: VecDeque::with_capacity(window),
+        }
+    }
+
+    /// Whether tags are recorded at all.
+    pub fn is_enabled(&self) -> bool {
+        self.window > 0
+    }
+
+    /// Record a processed tag, evicting the oldest record if the window is full.
+    pub fn push(&mut self, record: TagRecord) {
+        if !self.is_enabled() {
+            return;
+        }
+        if self.records.len() == self.window {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// The record `steps` tags back from the most recent one, which is `steps = 0`.
+    pub fn back(&self, steps: usize) -> Option<&TagRecord> {
+        self.records
+            .len()
+            .checked_sub(steps + 1)
+            .and_then(|idx| self.records.get(idx))
+    }
+
+    /// The number of recorded tags.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Iterate over the recorded tags, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &TagRecord> {
+        self.records.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Duration;
+
+    fn record(ms: i64) -> TagRecord {
+        TagRecord {
+            tag: Tag::new(Duration::milliseconds(ms), 0),
+            reactions: vec![],
+            ports: vec![],
+            states: vec![],
+        }
+    }
+
+    #[test]
+    fn test_history_window() {
+        let mut history = History::new(3);
+        (0..5).for_each(|ms| history.push(record(ms)));
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.back(0), Some(&record(4)));
+        assert_eq!(history.back(2), Some(&record(2)));
+        assert_eq!(history.back(3), None);
+
+        let mut disabled = History::new(0);
+        disabled.push(record(0));
+        assert!(disabled.is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+//! Pre-startup initialization of reactors.
+//!
+//! Reactors that need I/O-bound initialization before logical time begins, e.g. to open devices or load models,
+//! declare an [`Init`]. At the start of [`crate::Scheduler::event_loop`], the scheduler runs all inits concurrently on
+//! background threads and waits for them before starting the clock, so their duration doesn't count towards the
+//! execution of the startup tag. Each init produces a value, which is applied to the state of its reactor on the
+//! scheduler thread in the order the inits were declared, so startup reactions can assume their resources are ready.
+//!
+//! An init returning an error or panicking aborts the run with [`RuntimeError::InitFailed`], and an init not completing
+//! within its timeout with [`RuntimeError::InitTimeout`], before any reaction runs. A timed-out init can't be
+//! cancelled, its thread is left running in the background.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! let init = runtime::Init::new(
+//!     "main::camera::open",
+//!     reactor_key,
+//!     Some(Duration::seconds(5)),
+//!     || Camera::open("/dev/video0"),
+//!     |state: &mut State, camera| state.camera = Some(camera),
+//! );
+//! ```
+
+use std::{fmt::Display, panic::AssertUnwindSafe};
+
+use crossbeam_channel::RecvTimeoutError;
+
+use crate::{
+    isolation::panic_message, BaseReactor, Duration, ReactorData, ReactorKey, RuntimeError,
+};
+
+/// Applies the value produced by an init to the state of its reactor.
+pub(crate) type ApplyFn = Box<dyn FnOnce(&mut dyn BaseReactor) + Send>;
+
+type RunFn = Box<dyn FnOnce() -> Result<ApplyFn, String> + Send>;
+
+/// An initialization run before logical time begins, see the [module documentation](self).
+pub struct Init {
+    name: String,
+    reactor_key: ReactorKey,
+    timeout: Option<Duration>,
+    run: RunFn,
+}
+
+impl std::fmt::Debug for Init {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Init")
+            .field("name", &self.name)
+            .field("reactor_key", &self.reactor_key)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl Init {
+    /// Create an init for the reactor `reactor_key` with state `S`, running `init` on a background thread and passing
+    /// its value to `apply` with the reactor state.
+    pub fn new<S, V, E, F, A>(
+        name: &str,
+        reactor_key: ReactorKey,
+        timeout: Option<Duration>,
+        init: F,
+        apply: A,
+    ) -> Self
+    where
+        S: ReactorData,
+        V: Send + 'static,
+        E: Display,
+        F: FnOnce() -> Result<V, E> + Send + 'static,
+        A: FnOnce(&mut S, V) + Send + 'static,
+    {
+        let run = Box::new(move || {
+            let value = init().map_err(|err| err.to_string())?;
+            Ok(Box::new(move |reactor: &mut dyn BaseReactor| {
+                let state = reactor
+                    .get_state_mut::<S>()
+                    .expect("Init applied to a reactor with a different state type");
+                apply(state, value);
+            }) as ApplyFn)
+        });
+        Self {
+            name: name.to_owned(),
+            reactor_key,
+            timeout,
+            run,
+        }
+    }
+
+    /// The name of the init
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The key of the reactor initialized
+    pub fn reactor_key(&self) -> ReactorKey {
+        self.reactor_key
+    }
+}
+
+/// An init running on a background thread.
+struct Pending {
+    name: String,
+    reactor_key: ReactorKey,
+    timeout: Option<Duration>,
+    deadline: Option<std::time::Instant>,
+}
+
+/// Run all `inits` concurrently, and return the functions applying their values in the order of `inits`.
+pub(crate) fn run(inits: Vec<Init>) -> Result<Vec<(ReactorKey, ApplyFn)>, RuntimeError> {
+    let start = std::time::Instant::now();
+    let (tx, rx) = crossbeam_channel::unbounded();
+
+    let mut pending = Vec::with_capacity(inits.len());
+    for (index, init) in inits.into_iter().enumerate() {
+        let Init {
+            name,
+            reactor_key,
+            timeout,
+            run,
+        } = init;
+        tracing::debug!(init = %name, "Starting initialization.");
+        let tx = tx.clone();
+        std::thread::Builder::new()
+            .name("boomerang-init".into())
+            .spawn(move || {
+                let result =
+                    std::panic::catch_unwind(AssertUnwindSafe(run)).unwrap_or_else(|payload| {
+                        Err(format!("panicked: {}", panic_message(payload.as_ref())))
+                    });
+                // The scheduler stops listening after the first failure
+                let _ = tx.send((index, result));
+            })
+            .expect("Failed to spawn the init thread");
+        let deadline = timeout
+            .map(|timeout| start + std::time::Duration::try_from(timeout).unwrap_or_default());
+        pending.push(Some(Pending {
+            name,
+            reactor_key,
+            timeout,
+            deadline,
+        }));
+    }
+
+    let mut applied: Vec<Option<(ReactorKey, ApplyFn)>> = pending.iter().map(|_| None).collect();
+    for _ in 0..pending.len() {
+        let next_deadline = pending
+            .iter()
+            .enumerate()
+            .filter_map(|(index, init)| Some((init.as_ref()?.deadline?, index)))
+            .min();
+        let (index, result) = match next_deadline {
+            Some((deadline, index)) => match rx.recv_deadline(deadline) {
+                Ok(received) => received,
+                Err(RecvTimeoutError::Timeout) => {
+                    let init = pending[index].take().expect("Init already completed");
+                    return Err(RuntimeError::InitTimeout {
+                        name: init.name,
+                        timeout: init.timeout.unwrap_or_default(),
+                    });
+                }
+                Err(RecvTimeoutError::Disconnected) => unreachable!("Init threads always report"),
+            },
+            None => rx.recv().expect("Init threads always report"),
+        };
+        let init = pending[index].take().expect("Init completed twice");
+        match result {
+            Ok(apply) => {
+                tracing::debug!(init = %init.name, elapsed = ?start.elapsed(), "Initialization completed.");
+                applied[index] = Some((init.reactor_key, apply));
+            }
+            Err(reason) => {
+                return Err(RuntimeError::InitFailed {
+                    name: init.name,
+                    reason,
+                })
+            }
+        }
+    }
+
+    Ok(applied.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let reactor_key = ReactorKey::from(0);
+        let slow = Init::new(
+            "slow",
+            reactor_key,
+            Some(Duration::seconds(5)),
+            || {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+                Ok::<_, String>(1)
+            },
+            |state: &mut Vec<u32>, value| state.push(value),
+        );
+        let fast = Init::new(
+            "fast",
+            reactor_key,
+            None,
+            || Ok::<_, String>(2),
+            |state: &mut Vec<u32>, value| state.push(value),
+        );
+        let applied = run(vec![slow, fast]).unwrap();
+
+        // Applied in the order declared, not completed
+        let mut reactor = crate::Reactor::new("reactor", Vec::<u32>::new()).boxed();
+        for (_, apply) in applied {
+            apply(reactor.as_mut());
+        }
+        assert_eq!(reactor.get_state::<Vec<u32>>().unwrap(), &[1, 2]);
+
+        let failing = Init::new(
+            "failing",
+            reactor_key,
+            None,
+            || Err::<(), _>("no device"),
+            |_: &mut (), _| {},
+        );
+        assert!(matches!(
+            run(vec![failing]),
+            Err(RuntimeError::InitFailed { name, reason }) if name == "failing" && reason == "no device"
+        ));
+
+        let hanging = Init::new(
+            "hanging",
+            reactor_key,
+            Some(Duration::milliseconds(10)),
+            || {
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                Ok::<_, String>(())
+            },
+            |_: &mut (), _| {},
+        );
+        assert!(matches!(
+            run(vec![hanging]),
+            Err(RuntimeError::InitTimeout { name, .. }) if name == "hanging"
+        ));
+    }
+}
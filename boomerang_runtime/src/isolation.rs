@@ -0,0 +1,106 @@
+//! Per-reactor panic isolation.
+//!
+//! By default, a panic in a reaction unwinds through the [`crate::Scheduler`] and aborts the whole run. Reactors can
+//! opt into isolating their panics with [`crate::Reactor::isolate_panics`] or [`crate::Reactor::restart_on_panic`]:
+//! the scheduler then catches the unwind around each of their reactions, restores the outputs the panicking reaction set
+//! to their values before it ran, discards the actions and shutdown it scheduled, and applies the [`PanicPolicy`] of the
+//! reactor:
+//!
+//! - [`PanicPolicy::Isolate`] marks the reactor failed, after which none of its reactions are run again, or
+//! - [`PanicPolicy::Restart`] resets the state of the reactor to its initial value, and keeps running its reactions.
+//!
+//! Outputs already set at the tag, e.g. by another reaction of the reactor, are moved aside while such a reaction runs.
+//! It still reads their values, but a value it sets replaces them rather than being modified in place, except for an
+//! [`crate::Accum`] batch, which it appends to.
+//!
+//! Every caught panic is recorded as a [`ReactorFailure`], available with [`crate::Scheduler::failures`]. The failures
+//! caught at a tag are also scheduled as a `Vec<ReactorFailure>` on all failure actions one microstep later, so other
+//! reactors can react to them. Failed reactors can be found in the [`crate::Env`] with
+//! [`crate::BaseReactor::is_failed`].
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! env_builder.get_reactor_builder(worker)?.isolate_panics();
+//! let failures = env_builder.add_failure_action("failures", supervisor)?;
+//! ```
+
+use std::fmt::Display;
+
+use crate::Tag;
+
+/// How the panics in the reactions of a reactor are handled, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PanicPolicy {
+    /// Let the panic unwind through the scheduler.
+    #[default]
+    Propagate,
+    /// Mark the reactor failed and skip all of its future reactions.
+    Isolate,
+    /// Reset the state of the reactor to its initial value.
+    Restart,
+}
+
+impl Display for PanicPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PanicPolicy::Propagate => write!(f, "propagate"),
+            PanicPolicy::Isolate => write!(f, "isolate"),
+            PanicPolicy::Restart => write!(f, "restart"),
+        }
+    }
+}
+
+/// A panic caught in a reaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReactorFailure {
+    /// The name of the reactor
+    pub reactor: String,
+    /// The name of the panicking reaction
+    pub reaction: String,
+    /// The tag at which the reaction panicked
+    pub tag: Tag,
+    /// The panic message, if it was a string
+    pub message: String,
+    /// The policy applied to the reactor
+    pub policy: PanicPolicy,
+}
+
+impl Display for ReactorFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Reaction {}::{} panicked at {} ({}): {}",
+            self.reactor, self.reaction, self.tag, self.policy, self.message
+        )
+    }
+}
+
+/// Extract the message of a panic payload.
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "<non-string panic payload>".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_message() {
+        let payload = std::panic::catch_unwind(|| panic!("boom {}", 42)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "boom 42");
+        let payload = std::panic::catch_unwind(|| std::panic::panic_any(42)).unwrap_err();
+        assert_eq!(
+            panic_message(payload.as_ref()),
+            "<non-string panic payload>"
+        );
+    }
+}
@@ -2,10 +2,7 @@
 //!
 //! Originally from <https://users.rust-lang.org/t/using-arc-to-terminate-a-thread/81533/15>
 
-use std::sync::{
-    atomic::{AtomicBool, Ordering::Relaxed},
-    Arc,
-};
+use crate::sync::{Arc, AtomicBool, Ordering::Relaxed};
 
 #[derive(Debug)]
 pub struct Sender(Arc<AtomicBool>);
@@ -44,3 +41,22 @@ impl Receiver {
         self.0.load(Relaxed)
     }
 }
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+
+    #[test]
+    fn loom_drop_sender() {
+        loom::model(|| {
+            let (tx, rx) = channel();
+            let other = rx.clone();
+            let handle = loom::thread::spawn(move || drop(tx));
+            while !other.is_shutdwon() {
+                loom::thread::yield_now();
+            }
+            handle.join().unwrap();
+            assert!(rx.is_shutdwon());
+        });
+    }
+}
@@ -26,7 +26,7 @@ impl<K: tinymap::Key + std::fmt::Display> std::fmt::Display for KeySet<K> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeySetLimits {
     /// The maximum level of any reaction in the trigger map.
@@ -35,6 +35,53 @@ pub struct KeySetLimits {
     pub num_keys: usize,
 }
 
+/// Statistics of the distribution of keys over levels.
+///
+/// These are useful to pre-size data structures for the keys of a level, or to decide how many workers can be kept busy
+/// executing a level in parallel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeySetStats {
+    /// The number of keys at each level.
+    pub level_histogram: Vec<usize>,
+}
+
+impl KeySetStats {
+    /// Build the statistics from the level of each key.
+    pub fn from_levels(levels: impl IntoIterator<Item = Level>) -> Self {
+        let mut level_histogram = Vec::new();
+        for level in levels {
+            if level_histogram.len() <= level.0 {
+                level_histogram.resize(level.0 + 1, 0);
+            }
+            level_histogram[level.0] += 1;
+        }
+        Self { level_histogram }
+    }
+
+    /// The total number of keys.
+    pub fn num_keys(&self) -> usize {
+        self.level_histogram.iter().sum()
+    }
+
+    /// The highest level containing a key, if any.
+    pub fn max_level(&self) -> Option<Level> {
+        self.level_histogram
+            .iter()
+            .rposition(|&count| count > 0)
+            .map(Level)
+    }
+
+    /// The largest number of keys at any single level.
+    pub fn max_level_width(&self) -> usize {
+        self.level_histogram
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or_default()
+    }
+}
+
 impl<K: tinymap::Key> KeySet<K> {
     /// Create a new KeySet with a fixed number of levels and key capacity.
     pub fn new(
@@ -69,6 +116,11 @@ impl<K: tinymap::Key> KeySet<K> {
         }
     }
 
+    /// Iterate over the keys at all levels, in increasing order of level.
+    pub fn iter(&self) -> impl Iterator<Item = K> + '_ {
+        self.levels.iter().flat_map(|keys| keys.iter())
+    }
+
     /// Clear all keys from all levels.
     pub fn clear(&mut self) {
         for level in self.levels.iter_mut() {
@@ -161,6 +213,19 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_stats() {
+        let stats = KeySetStats::from_levels([1, 3, 1, 1, 0].map(Level));
+        assert_eq!(stats.level_histogram, vec![1, 3, 0, 1]);
+        assert_eq!(stats.num_keys(), 5);
+        assert_eq!(stats.max_level(), Some(Level(3)));
+        assert_eq!(stats.max_level_width(), 3);
+
+        let empty = KeySetStats::default();
+        assert_eq!(empty.max_level(), None);
+        assert_eq!(empty.max_level_width(), 0);
+    }
+
     #[test]
     fn test_set1() {
         let mut map = tinymap::TinyMap::<DefaultKey, _>::new();
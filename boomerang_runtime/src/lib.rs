@@ -4,35 +4,77 @@
 #![deny(clippy::all)]
 
 pub mod action;
+pub mod backpressure;
+pub mod budget;
+pub mod calendar;
+pub mod cancel;
 mod context;
+pub mod deferred;
 mod env;
 mod event;
+pub mod flush;
+pub mod fsm;
+mod handle;
+pub mod history;
+pub mod init;
+pub mod isolation;
 pub mod keepalive;
 mod key_set;
+pub mod lifecycle;
+pub mod mem_size;
+pub mod memo;
+pub mod migrate;
+pub mod overload;
+pub mod overrides;
+pub mod params;
 pub mod port;
+pub mod probe;
 pub mod reaction;
 mod reactor;
 mod refs;
 mod sched;
+pub mod scratch;
+mod shuffle;
 pub mod store;
+pub mod subscription;
+mod sync;
 mod time;
+pub mod trace;
+pub mod value_fmt;
+pub mod wildcard;
 
 // Re-exports
 pub use ::time::Duration;
 
 pub use action::{Action, ActionCommon, ActionKey, ActionRef, AsyncActionRef, BaseAction};
+pub use backpressure::BackpressurePolicy;
+pub use budget::BudgetStats;
+pub use calendar::{CalendarEpoch, CalendarSpec};
+pub use cancel::{CancelReason, CancellationToken};
 pub use context::*;
 use downcast_rs::Downcast;
-pub use env::{BankInfo, Env, Level, LevelReactionKey, ReactionGraph};
-pub use key_set::KeySetLimits as ReactionSetLimits;
+pub use env::{BankInfo, Env, EnvMetadata, Level, LevelReactionKey, Metadata, ReactionGraph};
+pub use flush::Flush;
+pub use fsm::StateMachine;
+pub use handle::SchedulerHandle;
+pub use history::{History, TagRecord};
+pub use init::Init;
+pub use isolation::{PanicPolicy, ReactorFailure};
+pub use key_set::{KeySetLimits as ReactionSetLimits, KeySetStats as ReactionSetStats};
+pub use lifecycle::{EventFilter, EventId};
+pub use memo::{Memo, MemoScope};
+pub use overload::{Overload, OverloadConfig, OverloadResponse};
+pub use params::{Parameter, ParameterChange, Parameters};
 pub use port::*;
+pub use probe::{Probe, ProbeKey, ProbeSnapshot, ProbeValue};
 pub use reaction::{
     BoxedReactionFn, Deadline, FromRefs, Reaction, ReactionAdapter, ReactionFn, ReactionKey,
-    ReactionSet, Trigger,
+    ReactionSet, Trigger, TriggerFields,
 };
 pub use reactor::*;
 pub use refs::{Refs, RefsMut};
 pub use sched::*;
+pub use subscription::{EventReceiver, RuntimeEvent};
 pub use time::*;
 
 /// Types implementing this trait can be used as data in ports, actions, and reactors.
@@ -53,8 +95,55 @@ pub enum RuntimeError {
         wanted: &'static str,
     },
 
+    #[error("Port {0} is absent")]
+    PortAbsent(String),
+
+    #[error("Parameter not found: {0}")]
+    ParameterNotFound(String),
+
+    #[error("Parameter {0} is not reloadable")]
+    ParameterNotReloadable(String),
+
+    #[error("The scheduler is no longer running")]
+    SchedulerStopped,
+
     #[error("Destructuring error")]
     DestrError,
+
+    #[error("Cannot schedule at {requested}, it is not strictly after {current}")]
+    TagNotInFuture { requested: Tag, current: Tag },
+
+    #[error("Cannot defer a computation to action {action}: {reason}")]
+    InvalidDeferral { action: String, reason: String },
+
+    #[error("Invalid state transition from {state} on event {event} at {tag}")]
+    InvalidTransition {
+        state: String,
+        event: String,
+        tag: Tag,
+    },
+
+    #[error("Shutdown did not complete within {grace}, reactions still running: {pending:?}")]
+    ShutdownTimeout {
+        grace: Duration,
+        pending: Vec<String>,
+    },
+
+    #[error("Initialization {name} failed: {reason}")]
+    InitFailed { name: String, reason: String },
+
+    #[error("Initialization {name} did not complete within {timeout}")]
+    InitTimeout { name: String, timeout: Duration },
+
+    #[error("Flush {name} failed: {reason}")]
+    FlushFailed { name: String, reason: String },
+
+    #[error("Invalid config value {value:?} for {name}: {reason}")]
+    InvalidConfig {
+        name: String,
+        value: String,
+        reason: String,
+    },
 }
 
 pub mod fmt_utils {
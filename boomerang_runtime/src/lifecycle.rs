@@ -0,0 +1,176 @@
+//! Structured logging of the lifecycle of events, from scheduling to completion.
+//!
+//! With an [`EventFilter`] in the [`crate::Config`], e.g. from the [`crate::overrides::ENV_EVENT_FILTER`] environment
+//! variable, the scheduler assigns a unique [`EventId`] to every event on a matching action or port, and emits a
+//! `tracing` event with the target [`TARGET`] at each step of its lifecycle:
+//!
+//! - `scheduled`: an action was scheduled or a port was set, with the reaction it originates from. Actions scheduled
+//!   from outside the scheduler have the origin `async`.
+//! - `enqueued`: an action event was pushed onto the event queue.
+//! - `discarded`: an action event was scheduled after the shutdown tag, so it was dropped instead of enqueued.
+//! - `dequeued`: an action event was popped from the event queue, and its reactions are about to run.
+//! - `completed`: all reactions triggered by the event have run.
+//!
+//! Port events never reach the event queue, so they are only `scheduled` and `completed`. All steps are logged at the
+//! `DEBUG` level, so they also need to be enabled in the `tracing` subscriber, e.g. with
+//! `RUST_LOG=boomerang::lifecycle=debug`.
+//!
+//! The filter is a comma-separated list of patterns matched against the fully-qualified name of the action or port,
+//! where `*` matches any sequence of characters. Connected ports share a single runtime port, which is named after the
+//! port at the source of the connection.
+//!
+//! ## Example:
+//!
+//! ```shell
+//! BOOMERANG_EVENT_FILTER="main::source::*,*::tick" RUST_LOG=boomerang::lifecycle=debug cargo run
+//! ```
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{wildcard::wildcard_match, ActionKey, PortKey};
+
+/// The `tracing` target of all lifecycle events.
+pub const TARGET: &str = "boomerang::lifecycle";
+
+/// Selects the actions and ports whose events are logged, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventFilter {
+    patterns: Vec<String>,
+}
+
+impl EventFilter {
+    /// Create a filter matching any of the given patterns.
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            patterns: patterns.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// A filter matching all actions and ports.
+    pub fn all() -> Self {
+        Self::new(["*"])
+    }
+
+    /// Whether the fully-qualified name `fqn` matches any of the patterns.
+    pub fn matches(&self, fqn: &str) -> bool {
+        self.patterns.iter().any(|pattern| glob_match(pattern, fqn))
+    }
+}
+
+impl FromStr for EventFilter {
+    type Err = std::convert::Infallible;
+
+    /// Parse a comma-separated list of patterns, ignoring surrounding whitespace and empty patterns.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(
+            s.split(',')
+                .map(str::trim)
+                .filter(|pattern| !pattern.is_empty()),
+        ))
+    }
+}
+
+impl Display for EventFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.patterns.join(","))
+    }
+}
+
+/// Match `text` against `pattern`, where `*` matches any sequence of characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    wildcard_match(
+        pattern.as_bytes(),
+        text.as_bytes(),
+        |&c| c == b'*',
+        |p, t| p == t,
+    )
+}
+
+/// The unique id of a logged event, correlating the steps of its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EventId(pub u64);
+
+impl Display for EventId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "e{}", self.0)
+    }
+}
+
+/// The action or port an event is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EventSource {
+    Action(ActionKey),
+    Port(PortKey),
+}
+
+/// An event whose lifecycle is logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TrackedEvent {
+    pub id: EventId,
+    pub source: EventSource,
+}
+
+/// Assigns ids to the events matching the filter.
+#[derive(Debug)]
+pub(crate) struct Lifecycle {
+    filter: EventFilter,
+    next_id: u64,
+}
+
+impl Lifecycle {
+    pub fn new(filter: EventFilter) -> Self {
+        Self { filter, next_id: 0 }
+    }
+
+    /// Start tracking a new event on `source` named `fqn`, if it matches the filter.
+    pub fn track(&mut self, source: EventSource, fqn: &str) -> Option<TrackedEvent> {
+        if !self.filter.matches(fqn) {
+            return None;
+        }
+        let id = EventId(self.next_id);
+        self.next_id += 1;
+        Some(TrackedEvent { id, source })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_filter() {
+        let filter: EventFilter = " main::source::* , *::tick,".parse().unwrap();
+        assert_eq!(filter.to_string(), "main::source::*,*::tick");
+        assert!(filter.matches("main::source::out"));
+        assert!(filter.matches("main::source::inner::act"));
+        assert!(filter.matches("main::timer::tick"));
+        assert!(!filter.matches("main::sink::in"));
+        assert!(!filter.matches("main::timer::ticks"));
+
+        assert!(EventFilter::new(["main::sink::in"]).matches("main::sink::in"));
+        assert!(!EventFilter::new(["main::sink"]).matches("main::sink::in"));
+        assert!(EventFilter::all().matches(""));
+        assert!(!EventFilter::new(Vec::<String>::new()).matches("main"));
+    }
+
+    #[test]
+    fn test_lifecycle_ids() {
+        let mut lifecycle = Lifecycle::new("*::out".parse().unwrap());
+        let port = PortKey::from(0);
+        assert!(lifecycle
+            .track(EventSource::Port(port), "main::in")
+            .is_none());
+        let first = lifecycle
+            .track(EventSource::Port(port), "main::out")
+            .unwrap();
+        let second = lifecycle
+            .track(EventSource::Port(port), "main::out")
+            .unwrap();
+        assert_eq!((first.id, second.id), (EventId(0), EventId(1)));
+        assert_eq!(first.id.to_string(), "e0");
+    }
+}
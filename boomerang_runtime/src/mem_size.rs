@@ -0,0 +1,344 @@
+//! Estimates of the memory used by each reactor.
+//!
+//! A [`MemoryReport`] sums, for each reactor, the size of its state, of the values held by its ports and of the events
+//! pending in its action stores. The inline size of each value is always counted, while the memory a value owns on the
+//! heap is only known for types implementing [`MemSize`]. Since state and port types are not required to implement
+//! it, the heap size of each type is looked up in a registry; common types are registered by default, other types are
+//! added with [`register`]. Unregistered types only count their inline size, so the report is a lower bound.
+//!
+//! Connected ports share a single runtime port, which is counted for the reactor of the source port.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! runtime::mem_size::register::<Vec<Sample>>();
+//! let mut sched = runtime::Scheduler::new(env, graph, config);
+//! sched.event_loop()?;
+//! println!("{}", sched.memory_report());
+//! ```
+
+use std::{
+    any::{Any, TypeId},
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt::Display,
+    sync::{OnceLock, RwLock},
+};
+
+use crate::{ActionKey, BaseAction, BasePort, BaseReactor, PortKey, ReactionGraph, ReactorKey};
+
+/// A value that knows how much memory it owns on the heap.
+pub trait MemSize {
+    /// The number of bytes owned on the heap, not counting the inline size of the value itself.
+    fn heap_size(&self) -> usize;
+}
+
+macro_rules! impl_mem_size_inline {
+    ($($t:ty),*) => {
+        $(impl MemSize for $t {
+            fn heap_size(&self) -> usize {
+                0
+            }
+        })*
+    };
+}
+
+impl_mem_size_inline!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    &'static str,
+    crate::Duration,
+    crate::Tag
+);
+
+impl MemSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: MemSize> MemSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, MemSize::heap_size)
+    }
+}
+
+impl<T: MemSize> MemSize for Box<T> {
+    fn heap_size(&self) -> usize {
+        std::mem::size_of::<T>() + self.as_ref().heap_size()
+    }
+}
+
+impl<T: MemSize> MemSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * std::mem::size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: MemSize> MemSize for VecDeque<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * std::mem::size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: MemSize, const N: usize> MemSize for [T; N] {
+    fn heap_size(&self) -> usize {
+        self.iter().map(T::heap_size).sum()
+    }
+}
+
+impl<K: MemSize, V: MemSize, S> MemSize for HashMap<K, V, S> {
+    /// Estimated from the capacity, ignoring the control bytes of the table.
+    fn heap_size(&self) -> usize {
+        self.capacity() * std::mem::size_of::<(K, V)>()
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl<K: MemSize, V: MemSize> MemSize for BTreeMap<K, V> {
+    /// Estimated from the number of entries, ignoring the overhead of the tree nodes.
+    fn heap_size(&self) -> usize {
+        self.len() * std::mem::size_of::<(K, V)>()
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+macro_rules! impl_mem_size_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: MemSize),+> MemSize for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn heap_size(&self) -> usize {
+                let ($($name,)+) = self;
+                0 $(+ $name.heap_size())+
+            }
+        }
+    };
+}
+
+impl_mem_size_tuple!(A);
+impl_mem_size_tuple!(A, B);
+impl_mem_size_tuple!(A, B, C);
+impl_mem_size_tuple!(A, B, C, D);
+
+type HeapSizeFn = fn(&dyn Any) -> usize;
+
+fn heap_size_any<T: MemSize + 'static>(value: &dyn Any) -> usize {
+    value.downcast_ref::<T>().map_or(0, T::heap_size)
+}
+
+fn registry() -> &'static RwLock<HashMap<TypeId, HeapSizeFn>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<TypeId, HeapSizeFn>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut map = HashMap::new();
+        macro_rules! register_defaults {
+            ($($t:ty),*) => {
+                $(map.insert(TypeId::of::<$t>(), heap_size_any::<$t> as HeapSizeFn);)*
+            };
+        }
+        register_defaults!(
+            String,
+            Vec<u8>,
+            Vec<u32>,
+            Vec<u64>,
+            Vec<i32>,
+            Vec<i64>,
+            Vec<f32>,
+            Vec<f64>,
+            Vec<String>
+        );
+        RwLock::new(map)
+    })
+}
+
+/// Register `T` so that the memory its values own on the heap is included in memory reports.
+pub fn register<T: MemSize + 'static>() {
+    registry()
+        .write()
+        .expect("Memory size registry poisoned")
+        .insert(TypeId::of::<T>(), heap_size_any::<T>);
+}
+
+/// The number of bytes `value` owns on the heap, or 0 if no size is registered for `T`.
+pub fn heap_size<T: 'static>(value: &T) -> usize {
+    registry()
+        .read()
+        .expect("Memory size registry poisoned")
+        .get(&TypeId::of::<T>())
+        .map_or(0, |heap_size_fn| heap_size_fn(value))
+}
+
+/// The estimated total size of `value`, its inline size plus the heap size registered for `T`.
+pub fn mem_size<T: 'static>(value: &T) -> usize {
+    std::mem::size_of_val(value) + heap_size(value)
+}
+
+/// The estimated memory used by a single reactor, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryUsage {
+    /// The reactor state
+    pub state: usize,
+    /// The values held by the ports of the reactor
+    pub ports: usize,
+    /// The events pending in the action stores of the reactor
+    pub actions: usize,
+}
+
+impl MemoryUsage {
+    pub fn total(&self) -> usize {
+        self.state + self.ports + self.actions
+    }
+}
+
+impl std::ops::AddAssign for MemoryUsage {
+    fn add_assign(&mut self, rhs: Self) {
+        self.state += rhs.state;
+        self.ports += rhs.ports;
+        self.actions += rhs.actions;
+    }
+}
+
+/// The estimated memory used by each reactor, keyed by its fully-qualified name, see the
+/// [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryReport(pub BTreeMap<String, MemoryUsage>);
+
+impl MemoryReport {
+    /// Estimate the memory used by each reactor. Reactors without a name in `reaction_graph` are keyed by their name.
+    pub fn new(
+        reactors: &tinymap::TinyMap<ReactorKey, Box<dyn BaseReactor>>,
+        ports: &tinymap::TinyMap<PortKey, Box<dyn BasePort>>,
+        actions: &tinymap::TinyMap<ActionKey, Box<dyn BaseAction>>,
+        reaction_graph: &ReactionGraph,
+    ) -> Self {
+        let mut usages: tinymap::TinySecondaryMap<ReactorKey, MemoryUsage> = reactors
+            .iter()
+            .map(|(reactor_key, reactor)| {
+                let usage = MemoryUsage {
+                    state: reactor.state_mem_size(),
+                    ..Default::default()
+                };
+                (reactor_key, usage)
+            })
+            .collect();
+        for (port_key, port) in ports.iter() {
+            if let Some(reactor_key) = reaction_graph.port_reactors.get(port_key) {
+                usages[*reactor_key].ports += port.value_mem_size();
+            }
+        }
+        for (action_key, action) in actions.iter() {
+            if let Some(reactor_key) = reaction_graph.action_reactors.get(action_key) {
+                usages[*reactor_key].actions += action.store_mem_size();
+            }
+        }
+
+        let mut report = BTreeMap::new();
+        for (reactor_key, usage) in usages.into_iter() {
+            let fqn = match reaction_graph.reactor_fqn(reactor_key) {
+                "" => reactors[reactor_key].name(),
+                fqn => fqn,
+            };
+            *report.entry(fqn.to_owned()).or_default() += usage;
+        }
+        Self(report)
+    }
+
+    /// The estimated memory used by the reactor `fqn`, if known.
+    pub fn get(&self, fqn: &str) -> Option<&MemoryUsage> {
+        self.0.get(fqn)
+    }
+
+    /// The sum of the memory used by all reactors.
+    pub fn total(&self) -> MemoryUsage {
+        self.0
+            .values()
+            .fold(MemoryUsage::default(), |mut acc, usage| {
+                acc += *usage;
+                acc
+            })
+    }
+}
+
+impl Display for MemoryReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .0
+            .keys()
+            .map(String::len)
+            .chain(std::iter::once("reactor".len()))
+            .max()
+            .unwrap_or_default();
+        let row = |f: &mut std::fmt::Formatter<'_>, name: &str, usage: &MemoryUsage| {
+            writeln!(
+                f,
+                "{name:<width$} {:>10} {:>10} {:>10} {:>10}",
+                usage.state,
+                usage.ports,
+                usage.actions,
+                usage.total()
+            )
+        };
+        writeln!(
+            f,
+            "{:<width$} {:>10} {:>10} {:>10} {:>10}",
+            "reactor", "state", "ports", "actions", "total"
+        )?;
+        for (fqn, usage) in self.0.iter() {
+            row(f, fqn, usage)?;
+        }
+        row(f, "total", &self.total())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Custom(Vec<u64>);
+
+    impl MemSize for Custom {
+        fn heap_size(&self) -> usize {
+            self.0.heap_size()
+        }
+    }
+
+    #[test]
+    fn test_mem_size() {
+        let string = String::with_capacity(16);
+        assert_eq!(mem_size(&string), std::mem::size_of::<String>() + 16);
+        assert_eq!(mem_size(&5u32), 4);
+
+        let custom = Custom(Vec::with_capacity(4));
+        assert_eq!(heap_size(&custom), 0);
+        register::<Custom>();
+        assert_eq!(heap_size(&custom), 32);
+
+        let nested = vec![String::with_capacity(8), String::with_capacity(2)];
+        assert_eq!(
+            nested.heap_size(),
+            nested.capacity() * std::mem::size_of::<String>() + 10
+        );
+        assert_eq!((1u8, Some(String::with_capacity(3))).heap_size(), 3);
+    }
+}
@@ -0,0 +1,224 @@
+//! Tag-aware caching of expensive pure computations.
+//!
+//! A [`Memo`] kept in the reactor state caches the result of a computation for the current tag, so that a reaction
+//! triggered several times in a tag, or several reactions of the reactor, compute it only once. The cached value is
+//! invalidated automatically when logical time advances, according to the [`MemoScope`] of the memo:
+//!
+//! - [`MemoScope::Tag`] recomputes the value at every new tag,
+//! - [`MemoScope::Time`] keeps it across the microsteps of the same logical time,
+//! - [`MemoScope::Input`] keeps it for as long as the input doesn't change.
+//!
+//! With [`Memo::get_or_compute_with`], the value is also keyed by the hash of an input, and recomputed when the input
+//! changes within the scope.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! struct State {
+//!     spectrum: runtime::memo::Memo<Vec<f32>>,
+//! }
+//!
+//! fn trigger(mut self, ctx: &mut runtime::Context, state: &mut State) {
+//!     let samples = self.samples.as_ref().unwrap();
+//!     let spectrum = state.spectrum.get_or_compute_with(ctx, samples, |samples| fft(samples));
+//!     *self.peak = spectrum.iter().copied().reduce(f32::max);
+//! }
+//! ```
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use crate::{Context, Tag};
+
+/// How long the value cached in a [`Memo`] stays valid, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MemoScope {
+    /// Until the next tag
+    #[default]
+    Tag,
+    /// Until logical time advances, across microsteps
+    Time,
+    /// Until the input changes, regardless of logical time
+    Input,
+}
+
+impl MemoScope {
+    /// Whether a value computed at `computed` is still valid at `tag`.
+    fn is_valid(&self, computed: Tag, tag: Tag) -> bool {
+        match self {
+            MemoScope::Tag => computed == tag,
+            MemoScope::Time => computed.offset() == tag.offset(),
+            MemoScope::Input => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MemoEntry<T> {
+    /// The tag the value was computed at
+    tag: Tag,
+    /// The hash of the input the value was computed from
+    input: u64,
+    value: T,
+}
+
+/// A cached value of a pure computation, invalidated at tag boundaries, see the [module documentation](self).
+///
+/// The cached value itself is not serialized, so a deserialized memo starts empty.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Memo<T> {
+    scope: MemoScope,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    entry: Option<MemoEntry<T>>,
+    /// The number of lookups answered from the cache
+    hits: usize,
+    /// The number of lookups that computed the value
+    misses: usize,
+}
+
+impl<T> Default for Memo<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Memo<T> {
+    /// Create an empty memo, invalidated at every new tag.
+    pub fn new() -> Self {
+        Self::with_scope(MemoScope::Tag)
+    }
+
+    /// Create an empty memo with the given `scope`.
+    pub fn with_scope(scope: MemoScope) -> Self {
+        Self {
+            scope,
+            entry: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// How long the cached value stays valid.
+    pub fn scope(&self) -> MemoScope {
+        self.scope
+    }
+
+    /// Get the value cached for the current tag, or compute and cache it with `f`.
+    pub fn get_or_compute(&mut self, ctx: &Context, f: impl FnOnce() -> T) -> &T {
+        self.lookup(ctx.get_tag(), 0, f)
+    }
+
+    /// Get the value cached for the current tag and `input`, or compute and cache it with `f`.
+    ///
+    /// The input is only kept as a hash, so `f` must be a pure function of it.
+    pub fn get_or_compute_with<I: Hash + ?Sized>(
+        &mut self,
+        ctx: &Context,
+        input: &I,
+        f: impl FnOnce(&I) -> T,
+    ) -> &T {
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+        self.lookup(ctx.get_tag(), hasher.finish(), || f(input))
+    }
+
+    /// Get the value cached for the current tag, if any, regardless of its input.
+    pub fn get(&self, ctx: &Context) -> Option<&T> {
+        self.entry
+            .as_ref()
+            .filter(|entry| self.scope.is_valid(entry.tag, ctx.get_tag()))
+            .map(|entry| &entry.value)
+    }
+
+    /// Drop the cached value, so that the next lookup computes it again.
+    pub fn invalidate(&mut self) {
+        self.entry = None;
+    }
+
+    /// The number of lookups answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// The number of lookups that computed the value.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    fn lookup(&mut self, tag: Tag, input: u64, f: impl FnOnce() -> T) -> &T {
+        let valid = self
+            .entry
+            .as_ref()
+            .is_some_and(|entry| entry.input == input && self.scope.is_valid(entry.tag, tag));
+        if valid {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+            self.entry = Some(MemoEntry {
+                tag,
+                input,
+                value: f(),
+            });
+        }
+        &self.entry.as_ref().unwrap().value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_scopes() {
+        let t0 = Tag::new(Duration::ZERO, 0);
+        let t0_1 = Tag::new(Duration::ZERO, 1);
+        let t1 = Tag::new(Duration::milliseconds(1), 0);
+        let mut calls = 0;
+
+        let mut compute = |memo: &mut Memo<u32>, tag| {
+            *memo.lookup(tag, 0, || {
+                calls += 1;
+                calls
+            })
+        };
+
+        let mut memo = Memo::new();
+        assert_eq!(compute(&mut memo, t0), 1);
+        assert_eq!(compute(&mut memo, t0), 1);
+        assert_eq!(compute(&mut memo, t0_1), 2);
+        assert_eq!(compute(&mut memo, t1), 3);
+        assert_eq!((memo.hits(), memo.misses()), (1, 3));
+
+        let mut memo = Memo::with_scope(MemoScope::Time);
+        assert_eq!(compute(&mut memo, t0), 4);
+        assert_eq!(compute(&mut memo, t0_1), 4);
+        assert_eq!(compute(&mut memo, t1), 5);
+
+        let mut memo = Memo::with_scope(MemoScope::Input);
+        assert_eq!(compute(&mut memo, t0), 6);
+        assert_eq!(compute(&mut memo, t1), 6);
+        memo.invalidate();
+        assert_eq!(compute(&mut memo, t1), 7);
+    }
+
+    #[test]
+    fn test_input() {
+        let t0 = Tag::new(Duration::ZERO, 0);
+        let t1 = Tag::new(Duration::milliseconds(1), 0);
+        let mut memo = Memo::with_scope(MemoScope::Input);
+
+        let compute = |memo: &mut Memo<usize>, tag, input: &[u32]| {
+            let mut hasher = DefaultHasher::new();
+            input.hash(&mut hasher);
+            *memo.lookup(tag, hasher.finish(), || input.len())
+        };
+
+        assert_eq!(compute(&mut memo, t0, &[1, 2]), 2);
+        assert_eq!(compute(&mut memo, t1, &[1, 2]), 2);
+        assert_eq!(compute(&mut memo, t1, &[1, 2, 3]), 3);
+        assert_eq!((memo.hits(), memo.misses()), (1, 2));
+    }
+}
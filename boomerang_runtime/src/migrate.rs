@@ -0,0 +1,134 @@
+//! Versioning and migration of reactor states.
+//!
+//! There is no checkpoint/restore support yet. This module defines the hooks a checkpoint format needs to restore
+//! states written by older versions of a program: every state type declares a schema [`StateMigrate::VERSION`], which
+//! is stored alongside the serialized state, and knows how to upgrade any older representation on load.
+
+use std::fmt::Display;
+
+/// Implemented by reactor states that can be restored from older checkpoints.
+pub trait StateMigrate: Sized {
+    /// The current schema version of this state type, stored with every checkpointed state.
+    const VERSION: u32;
+
+    /// The format-specific representation states are decoded into before being upgraded, e.g. `serde_json::Value`.
+    type Repr;
+
+    /// Decode a state stored with the schema `version`, upgrading it to the current version if needed.
+    ///
+    /// Returns an error describing the problem if the version is not supported.
+    fn migrate(version: u32, repr: Self::Repr) -> Result<Self, String>;
+}
+
+/// A checkpointed state that failed to migrate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationFailure {
+    /// The fully-qualified name of the reactor owning the state
+    pub reactor: String,
+    /// The stored schema version
+    pub version: u32,
+    /// The reason reported by [`StateMigrate::migrate`]
+    pub reason: String,
+}
+
+/// Error returned when one or more reactor states fail to migrate.
+#[derive(thiserror::Error, Debug)]
+pub struct MigrationError {
+    pub failures: Vec<MigrationFailure>,
+}
+
+impl Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Failed to migrate the state of {} reactor(s):",
+            self.failures.len()
+        )?;
+        for failure in &self.failures {
+            write!(
+                f,
+                "\n  {} (version {}): {}",
+                failure.reactor, failure.version, failure.reason
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Migrate the stored `(reactor fqn, version, repr)` entries of a state type, collecting all failures.
+pub fn migrate_states<S: StateMigrate>(
+    entries: impl IntoIterator<Item = (String, u32, S::Repr)>,
+) -> Result<Vec<(String, S)>, MigrationError> {
+    let mut states = Vec::new();
+    let mut failures = Vec::new();
+    for (reactor, version, repr) in entries {
+        match S::migrate(version, repr) {
+            Ok(state) => states.push((reactor, state)),
+            Err(reason) => failures.push(MigrationFailure {
+                reactor,
+                version,
+                reason,
+            }),
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(states)
+    } else {
+        Err(MigrationError { failures })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Version 1 stored a bare count, version 2 added a label.
+    #[derive(Debug, PartialEq)]
+    struct Counter {
+        label: String,
+        count: u64,
+    }
+
+    impl StateMigrate for Counter {
+        const VERSION: u32 = 2;
+        type Repr = (Option<String>, u64);
+
+        fn migrate(version: u32, (label, count): Self::Repr) -> Result<Self, String> {
+            match (version, label) {
+                (1, None) => Ok(Self {
+                    label: "default".into(),
+                    count,
+                }),
+                (2, Some(label)) => Ok(Self { label, count }),
+                (version, _) => Err(format!("unsupported version {version}")),
+            }
+        }
+    }
+
+    #[test]
+    fn test_migrate_states() {
+        let states = migrate_states::<Counter>([
+            ("main::a".to_owned(), 1, (None, 3)),
+            ("main::b".to_owned(), 2, (Some("b".to_owned()), 4)),
+        ])
+        .unwrap();
+        assert_eq!(
+            states[0].1,
+            Counter {
+                label: "default".into(),
+                count: 3
+            }
+        );
+        assert_eq!(states[1].1.label, "b");
+
+        let err = migrate_states::<Counter>([
+            ("main::a".to_owned(), 1, (None, 3)),
+            ("main::c".to_owned(), 3, (None, 0)),
+        ])
+        .unwrap_err();
+        assert_eq!(err.failures.len(), 1);
+        assert_eq!(err.failures[0].reactor, "main::c");
+        assert!(err.to_string().contains("main::c (version 3)"));
+    }
+}
@@ -0,0 +1,195 @@
+//! Detection of and responses to sustained overload.
+//!
+//! Outside of fast-forward mode, the scheduler waits for physical time to catch up with the tag of each event before
+//! processing it. If the reactions take longer than the logical time between events, the scheduler falls behind, and
+//! events are processed later and later. With an [`OverloadConfig`] in the [`crate::Config`], the scheduler measures
+//! this lag before each event, and responds once it exceeds the threshold for a number of consecutive events:
+//!
+//! - [`OverloadResponse::Log`] only logs a warning,
+//! - [`OverloadResponse::DropConflatable`] additionally drops all overdue events of the conflatable actions except the
+//!   most recent one of each, so only the latest value of e.g. a sensor reading is processed, or
+//! - [`OverloadResponse::Action`] additionally schedules an [`Overload`] report on all overload actions one microstep
+//!   after the next event, so the application can shed load itself.
+//!
+//! While the overload persists, the scheduler responds again every time the lag exceeded the threshold for the same
+//! number of consecutive events. The lag is measured once per logical time, so later microsteps at the same time,
+//! including the overload reports themselves, don't count as separate events.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! let config = runtime::Config::default().with_overload(
+//!     OverloadConfig::new(Duration::milliseconds(10), OverloadResponse::Action).with_sustain(3),
+//! );
+//! let overload = env_builder.add_overload_action("overload", supervisor)?;
+//! ```
+
+use std::fmt::Display;
+
+use crate::{Duration, Tag};
+
+/// How the scheduler responds to sustained overload, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OverloadResponse {
+    /// Log a warning.
+    #[default]
+    Log,
+    /// Drop the overdue events of conflatable actions, keeping the most recent one of each.
+    DropConflatable,
+    /// Schedule an [`Overload`] report on the overload actions.
+    Action,
+}
+
+impl Display for OverloadResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverloadResponse::Log => write!(f, "log"),
+            OverloadResponse::DropConflatable => write!(f, "drop-conflatable"),
+            OverloadResponse::Action => write!(f, "action"),
+        }
+    }
+}
+
+/// When the scheduler is considered overloaded, and how it responds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverloadConfig {
+    /// The lag of logical time behind physical time above which an event is late
+    threshold: Duration,
+    /// The number of consecutive late events after which the scheduler responds, never 0
+    sustain: usize,
+    /// The response to sustained overload
+    response: OverloadResponse,
+}
+
+impl OverloadConfig {
+    /// Respond with `response` as soon as an event is later than `threshold`.
+    pub fn new(threshold: Duration, response: OverloadResponse) -> Self {
+        Self {
+            threshold,
+            sustain: 1,
+            response,
+        }
+    }
+
+    /// Only respond after `sustain` consecutive late events.
+    ///
+    /// # Panics
+    ///
+    /// If `sustain` is 0.
+    pub fn with_sustain(mut self, sustain: usize) -> Self {
+        assert!(
+            sustain > 0,
+            "An overload must be sustained for at least one event"
+        );
+        self.sustain = sustain;
+        self
+    }
+
+    /// The lag of logical time behind physical time above which an event is late.
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// The number of consecutive late events after which the scheduler responds.
+    pub fn sustain(&self) -> usize {
+        self.sustain
+    }
+
+    /// The response to sustained overload.
+    pub fn response(&self) -> OverloadResponse {
+        self.response
+    }
+}
+
+/// A report of sustained overload, scheduled on the overload actions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Overload {
+    /// The tag of the event at which the overload was detected
+    pub tag: Tag,
+    /// How far logical time lagged behind physical time at that event
+    pub lag: Duration,
+    /// The number of consecutive late events
+    pub late_events: usize,
+    /// The number of events dropped in response, only with [`OverloadResponse::DropConflatable`]
+    pub dropped: usize,
+}
+
+impl Display for Overload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Overloaded at {}: {} behind physical time for {} events, {} dropped",
+            self.tag, self.lag, self.late_events, self.dropped
+        )
+    }
+}
+
+/// Counts the consecutive late events.
+#[derive(Debug)]
+pub(crate) struct LagMonitor {
+    config: OverloadConfig,
+    late_events: usize,
+    /// The logical time of the last observed event
+    last_offset: Option<Duration>,
+}
+
+impl LagMonitor {
+    pub fn new(config: OverloadConfig) -> Self {
+        Self {
+            config,
+            late_events: 0,
+            last_offset: None,
+        }
+    }
+
+    pub fn response(&self) -> OverloadResponse {
+        self.config.response()
+    }
+
+    /// Observe the `lag` of the next event at `tag`, returning the number of consecutive late events if the scheduler
+    /// should respond now.
+    pub fn observe(&mut self, tag: Tag, lag: Duration) -> Option<usize> {
+        if self.last_offset.replace(tag.offset()) == Some(tag.offset()) {
+            return None;
+        }
+        if lag <= self.config.threshold {
+            self.late_events = 0;
+            return None;
+        }
+        self.late_events += 1;
+        self.late_events
+            .is_multiple_of(self.config.sustain)
+            .then_some(self.late_events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lag_monitor() {
+        let config =
+            OverloadConfig::new(Duration::milliseconds(10), OverloadResponse::Log).with_sustain(2);
+        let mut monitor = LagMonitor::new(config);
+        let late = Duration::milliseconds(11);
+        let tag = |ms, microstep| Tag::new(Duration::milliseconds(ms), microstep);
+        assert_eq!(monitor.observe(tag(1, 0), late), None);
+        assert_eq!(monitor.observe(tag(2, 0), Duration::milliseconds(10)), None);
+        assert_eq!(monitor.observe(tag(3, 0), late), None);
+        assert_eq!(monitor.observe(tag(4, 0), late), Some(2));
+        // Microsteps at the same logical time are not observed again
+        assert_eq!(monitor.observe(tag(4, 1), late), None);
+        assert_eq!(monitor.observe(tag(5, 0), late), None);
+        assert_eq!(monitor.observe(tag(6, 0), late), Some(4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_sustain() {
+        let _ =
+            OverloadConfig::new(Duration::milliseconds(10), OverloadResponse::Log).with_sustain(0);
+    }
+}
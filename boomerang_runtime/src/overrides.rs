@@ -0,0 +1,334 @@
+//! Overriding the [`Config`] from environment variables and the command line.
+//!
+//! [`Config::from_env`] and [`Config::with_env_overrides`] read the following environment variables:
+//!
+//! | Variable                       | Config field                      | Example           |
+//! |--------------------------------|-----------------------------------|-------------------|
+//! | `BOOMERANG_FAST_FORWARD`       | [`Config::fast_forward`]          | `1`, `false`      |
+//! | `BOOMERANG_KEEP_ALIVE`         | [`Config::keep_alive`]            | `true`, `0`       |
+//! | `BOOMERANG_TIMEOUT`            | [`Config::timeout`]               | `5s`, `100ms`     |
+//! | `BOOMERANG_WORKERS`            | [`Config::workers`]               | `4`               |
+//! | `BOOMERANG_QUEUE_SIZE`         | [`Config::physical_event_q_size`] | `4096`            |
+//! | `BOOMERANG_SHUFFLE_SEED`       | [`Config::shuffle_seed`]          | `42`              |
+//! | `BOOMERANG_SHUTDOWN_GRACE`     | [`Config::shutdown_grace`]        | `2s`              |
+//! | `BOOMERANG_EVENT_FILTER`       | [`Config::event_filter`]          | `main::*,*::tick` |
+//! | `BOOMERANG_BATCH_TAGS`         | [`Config::batch_tags`]            | `64`              |
+//! | `BOOMERANG_PARALLEL_THRESHOLD` | [`Config::parallel_threshold`]    | `8`               |
+//! | `BOOMERANG_TIME_SCALE`         | [`Config::time_scale`]            | `10`, `1/3`       |
+//!
+//! Boolean variables accept `1`, `true`, `yes` and `on` (or `0`, `false`, `no` and `off`), durations are parsed with
+//! [`humantime::parse_duration`], and time scales as a ratio or a decimal, see [`crate::TimeScale`]. Unset or empty variables leave the field unchanged.
+//!
+//! With the `cli` feature, [`ConfigArgs`] provides the same options as command line arguments, to be flattened into an
+//! application's own `clap` parser or parsed on their own with [`Config::from_args`].
+
+use crate::{Config, Duration, RuntimeError, TimeScale};
+
+/// Whether to skip wall-clock synchronization, see [`Config::fast_forward`].
+pub const ENV_FAST_FORWARD: &str = "BOOMERANG_FAST_FORWARD";
+/// Whether to keep the scheduler alive for asynchronous events, see [`Config::keep_alive`].
+pub const ENV_KEEP_ALIVE: &str = "BOOMERANG_KEEP_ALIVE";
+/// Stop the scheduler after the given amount of logical time, see [`Config::timeout`].
+pub const ENV_TIMEOUT: &str = "BOOMERANG_TIMEOUT";
+/// The number of worker threads, see [`Config::workers`].
+pub const ENV_WORKERS: &str = "BOOMERANG_WORKERS";
+/// The capacity of the physical event queue, see [`Config::physical_event_q_size`].
+pub const ENV_QUEUE_SIZE: &str = "BOOMERANG_QUEUE_SIZE";
+/// The seed for shuffling reactions to test the scheduler, see [`Config::with_shuffle_seed`].
+pub const ENV_SHUFFLE_SEED: &str = "BOOMERANG_SHUFFLE_SEED";
+/// How long to wait for the reactions at the shutdown tag, see [`Config::with_shutdown_grace`].
+pub const ENV_SHUTDOWN_GRACE: &str = "BOOMERANG_SHUTDOWN_GRACE";
+/// The actions and ports whose event lifecycle is logged, see [`Config::with_event_filter`].
+pub const ENV_EVENT_FILTER: &str = "BOOMERANG_EVENT_FILTER";
+/// The maximum number of ready tags processed in one batch, see [`Config::with_batch_tags`].
+pub const ENV_BATCH_TAGS: &str = "BOOMERANG_BATCH_TAGS";
+/// The minimum width of a level to run it on the worker pool, see [`Config::with_parallel_threshold`].
+pub const ENV_PARALLEL_THRESHOLD: &str = "BOOMERANG_PARALLEL_THRESHOLD";
+/// The rate of logical relative to physical time, see [`Config::with_time_scale`].
+pub const ENV_TIME_SCALE: &str = "BOOMERANG_TIME_SCALE";
+
+fn invalid(name: &str, value: &str, reason: impl ToString) -> RuntimeError {
+    RuntimeError::InvalidConfig {
+        name: name.to_owned(),
+        value: value.to_owned(),
+        reason: reason.to_string(),
+    }
+}
+
+fn parse_bool(name: &str, value: &str) -> Result<bool, RuntimeError> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(invalid(name, value, "expected a boolean")),
+    }
+}
+
+fn parse_duration(name: &str, value: &str) -> Result<Duration, RuntimeError> {
+    humantime::parse_duration(value)
+        .map_err(|err| invalid(name, value, err))?
+        .try_into()
+        .map_err(|err| invalid(name, value, err))
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, RuntimeError>
+where
+    T::Err: std::fmt::Display,
+{
+    value.parse().map_err(|err| invalid(name, value, err))
+}
+
+impl Config {
+    /// Create a default `Config` with the overrides from the environment applied, see the
+    /// [module documentation](crate::overrides).
+    pub fn from_env() -> Result<Self, RuntimeError> {
+        Self::default().with_env_overrides()
+    }
+
+    /// Apply the overrides from the environment, see the [module documentation](crate::overrides).
+    pub fn with_env_overrides(self) -> Result<Self, RuntimeError> {
+        self.with_overrides_from(|name| std::env::var(name).ok())
+    }
+
+    fn with_overrides_from(
+        mut self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, RuntimeError> {
+        let var = |name: &'static str| {
+            lookup(name)
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty())
+                .map(|value| (name, value))
+        };
+        if let Some((name, value)) = var(ENV_FAST_FORWARD) {
+            self.fast_forward = parse_bool(name, &value)?;
+        }
+        if let Some((name, value)) = var(ENV_KEEP_ALIVE) {
+            self.keep_alive = parse_bool(name, &value)?;
+        }
+        if let Some((name, value)) = var(ENV_TIMEOUT) {
+            self.timeout = Some(parse_duration(name, &value)?);
+        }
+        if let Some((name, value)) = var(ENV_WORKERS) {
+            self.workers = Some(parse_number(name, &value)?);
+        }
+        if let Some((name, value)) = var(ENV_QUEUE_SIZE) {
+            self.physical_event_q_size = parse_number(name, &value)?;
+        }
+        if let Some((name, value)) = var(ENV_SHUFFLE_SEED) {
+            self.shuffle_seed = Some(parse_number(name, &value)?);
+        }
+        if let Some((name, value)) = var(ENV_SHUTDOWN_GRACE) {
+            self.shutdown_grace = Some(parse_duration(name, &value)?);
+        }
+        if let Some((_, value)) = var(ENV_EVENT_FILTER) {
+            self.event_filter = value.parse().ok();
+        }
+        if let Some((name, value)) = var(ENV_BATCH_TAGS) {
+            self.batch_tags = Some(parse_number(name, &value)?);
+        }
+        if let Some((name, value)) = var(ENV_PARALLEL_THRESHOLD) {
+            self.parallel_threshold = parse_number(name, &value)?;
+        }
+        if let Some((name, value)) = var(ENV_TIME_SCALE) {
+            self.time_scale = parse_number::<TimeScale>(name, &value)?;
+        }
+        Ok(self)
+    }
+}
+
+/// Command line arguments overriding the [`Config`].
+///
+/// Options that are not given leave the corresponding field unchanged. Boolean flags take an optional value, so e.g.
+/// `--keep-alive` sets the field and `--keep-alive=false` clears it again.
+#[cfg(feature = "cli")]
+#[derive(Debug, Default, Clone, clap::Args)]
+pub struct ConfigArgs {
+    /// Skip wall-clock synchronization and execute as fast as possible
+    #[arg(long, short, num_args = 0..=1, require_equals = true, default_missing_value = "true",
+        value_parser = clap::builder::BoolishValueParser::new())]
+    pub fast_forward: Option<bool>,
+
+    /// Keep the scheduler alive waiting for asynchronous events
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true",
+        value_parser = clap::builder::BoolishValueParser::new())]
+    pub keep_alive: Option<bool>,
+
+    /// Stop the scheduler after the given amount of logical time, e.g., "5s" or "100ms"
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub timeout: Option<std::time::Duration>,
+
+    /// The number of worker threads to use for parallel execution
+    #[arg(long)]
+    pub workers: Option<usize>,
+
+    /// The capacity of the physical event queue
+    #[arg(long)]
+    pub queue_size: Option<usize>,
+
+    /// Shuffle the reactions within each level with the given seed, to test the scheduler
+    #[arg(long)]
+    pub shuffle_seed: Option<u64>,
+
+    /// Abandon the shutdown reactions if they don't complete within the given time, e.g., "2s"
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub shutdown_grace: Option<std::time::Duration>,
+
+    /// Log the lifecycle of the events on the matching actions and ports, e.g., "main::*,*::tick"
+    #[arg(long)]
+    pub event_filter: Option<String>,
+
+    /// Process up to the given number of ready tags in one batch in fast-forward mode
+    #[arg(long)]
+    pub batch_tags: Option<usize>,
+
+    /// The minimum number of reactions at a level to run them on the worker pool
+    #[arg(long)]
+    pub parallel_threshold: Option<usize>,
+
+    /// Run logical time the given times faster than the wall clock, e.g., "10", "0.5" or "1/3"
+    #[arg(long, value_parser = str::parse::<TimeScale>)]
+    pub time_scale: Option<TimeScale>,
+}
+
+#[cfg(feature = "cli")]
+impl ConfigArgs {
+    /// Apply the given arguments to `config`.
+    pub fn apply(&self, mut config: Config) -> Result<Config, RuntimeError> {
+        if let Some(fast_forward) = self.fast_forward {
+            config.fast_forward = fast_forward;
+        }
+        if let Some(keep_alive) = self.keep_alive {
+            config.keep_alive = keep_alive;
+        }
+        if let Some(timeout) = self.timeout {
+            config.timeout = Some(
+                timeout
+                    .try_into()
+                    .map_err(|err| invalid("--timeout", &format!("{timeout:?}"), err))?,
+            );
+        }
+        if let Some(workers) = self.workers {
+            config.workers = Some(workers);
+        }
+        if let Some(queue_size) = self.queue_size {
+            config.physical_event_q_size = queue_size;
+        }
+        if let Some(seed) = self.shuffle_seed {
+            config.shuffle_seed = Some(seed);
+        }
+        if let Some(grace) = self.shutdown_grace {
+            config.shutdown_grace = Some(
+                grace
+                    .try_into()
+                    .map_err(|err| invalid("--shutdown-grace", &format!("{grace:?}"), err))?,
+            );
+        }
+        if let Some(filter) = &self.event_filter {
+            config.event_filter = filter.parse().ok();
+        }
+        if let Some(batch_tags) = self.batch_tags {
+            config.batch_tags = Some(batch_tags);
+        }
+        if let Some(parallel_threshold) = self.parallel_threshold {
+            config.parallel_threshold = parallel_threshold;
+        }
+        if let Some(time_scale) = self.time_scale {
+            config.time_scale = time_scale;
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(feature = "cli")]
+impl Config {
+    /// Create a default `Config` with the [`ConfigArgs`] parsed from the command line applied.
+    ///
+    /// On invalid arguments this prints the usage and exits the process, as `clap` does.
+    pub fn from_args() -> Result<Self, RuntimeError> {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[command(flatten)]
+            config: ConfigArgs,
+        }
+        <Cli as clap::Parser>::parse().config.apply(Self::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn overrides(vars: &[(&str, &str)]) -> Result<Config, RuntimeError> {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        Config::default().with_overrides_from(|name| vars.get(name).map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let config = overrides(&[
+            (ENV_FAST_FORWARD, "yes"),
+            (ENV_TIMEOUT, "1s 500ms"),
+            (ENV_WORKERS, " 4 "),
+            (ENV_KEEP_ALIVE, ""),
+            (ENV_SHUFFLE_SEED, "42"),
+            (ENV_EVENT_FILTER, "main::*, *::tick"),
+            (ENV_BATCH_TAGS, "64"),
+            (ENV_PARALLEL_THRESHOLD, "8"),
+            (ENV_TIME_SCALE, "1/3"),
+        ])
+        .unwrap();
+        assert!(config.fast_forward);
+        assert!(!config.keep_alive);
+        assert_eq!(config.timeout, Some(Duration::milliseconds(1500)));
+        assert_eq!(config.workers, Some(4));
+        assert_eq!(config.physical_event_q_size, 1024);
+        assert_eq!(config.shuffle_seed, Some(42));
+        assert_eq!(
+            config.event_filter,
+            Some(crate::EventFilter::new(["main::*", "*::tick"]))
+        );
+        assert_eq!(config.batch_tags, Some(64));
+        assert_eq!(config.parallel_threshold, 8);
+        assert_eq!(config.time_scale, TimeScale::new(1, 3).unwrap());
+
+        assert!(matches!(
+            overrides(&[(ENV_WORKERS, "many")]),
+            Err(RuntimeError::InvalidConfig { name, .. }) if name == ENV_WORKERS
+        ));
+        assert!(overrides(&[(ENV_FAST_FORWARD, "maybe")]).is_err());
+        assert!(overrides(&[(ENV_TIME_SCALE, "0")]).is_err());
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_config_args() {
+        use clap::Parser;
+
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[command(flatten)]
+            config: ConfigArgs,
+        }
+
+        let cli = Cli::parse_from(["app", "-f", "--timeout", "100ms", "--queue-size", "16"]);
+        let config = cli.config.apply(Config::default()).unwrap();
+        assert!(config.fast_forward);
+        assert_eq!(config.timeout, Some(Duration::milliseconds(100)));
+        assert_eq!(config.physical_event_q_size, 16);
+        assert_eq!(config.workers, None);
+        assert_eq!(cli.config.keep_alive, None);
+
+        // Override an environment-provided `true` back to `false`
+        let env_config = overrides(&[(ENV_KEEP_ALIVE, "1"), (ENV_FAST_FORWARD, "1")]).unwrap();
+        let cli = Cli::parse_from(["app", "--keep-alive=false", "-f=no"]);
+        let config = cli.config.apply(env_config).unwrap();
+        assert!(!config.keep_alive);
+        assert!(!config.fast_forward);
+
+        let cli = Cli::parse_from(["app", "--keep-alive"]);
+        assert!(cli.config.apply(Config::default()).unwrap().keep_alive);
+        assert!(Cli::try_parse_from(["app", "--keep-alive=maybe"]).is_err());
+    }
+}
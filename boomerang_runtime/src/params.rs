@@ -0,0 +1,275 @@
+//! Global parameters shared by all reactions.
+//!
+//! Parameters are registered by name at build time, e.g. with `EnvBuilder::set_parameter`, and are available to every
+//! reaction with [`crate::Context::parameter`], so global configuration doesn't have to be threaded through the state
+//! of every reactor that needs it.
+//!
+//! Parameters are read-only for the reactions. Those registered as reloadable, e.g. with
+//! `EnvBuilder::set_reloadable_parameter`, can be updated from outside of the scheduler with
+//! [`crate::SchedulerHandle::update_parameter`], e.g. log levels, rate limits or thresholds. The scheduler applies the
+//! updates received before a tag all at once, one microstep after the current tag, so all reactions at a tag see the
+//! same values. The parameter change actions, e.g. added with `EnvBuilder::add_parameter_change_action`, are scheduled
+//! at the same tag with a [`ParameterChange`] listing the updated parameters, so reactions can respond to the new
+//! values deterministically.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! env_builder.set_parameter("name", "boomerang".to_owned());
+//! env_builder.set_reloadable_parameter("tick_rate", 200.0f64);
+//! // ...
+//! fn trigger(self, ctx: &mut runtime::Context, state: &mut State) {
+//!     let tick_rate = *ctx.parameter::<f64>("tick_rate").unwrap();
+//!     // ...
+//! }
+//! // ...
+//! let handle = sched.handle();
+//! std::thread::spawn(move || handle.update_parameter("tick_rate", 100.0f64));
+//! ```
+
+use std::{collections::BTreeMap, fmt::Display, sync::Arc};
+
+use crate::{ReactorData, RuntimeError, Tag};
+
+/// A single type-erased parameter value.
+pub struct Parameter {
+    /// The name of the type of the value, for error messages
+    type_name: &'static str,
+    /// Whether the value may be updated while the scheduler runs
+    reloadable: bool,
+    value: Box<dyn ReactorData>,
+}
+
+impl Parameter {
+    pub fn new<T: ReactorData>(value: T) -> Self {
+        Self {
+            type_name: std::any::type_name::<T>(),
+            reloadable: false,
+            value: Box::new(value),
+        }
+    }
+
+    /// A parameter that may be updated with [`crate::SchedulerHandle::update_parameter`].
+    pub fn reloadable<T: ReactorData>(value: T) -> Self {
+        Self {
+            reloadable: true,
+            ..Self::new(value)
+        }
+    }
+
+    /// Whether the parameter may be updated while the scheduler runs.
+    pub fn is_reloadable(&self) -> bool {
+        self.reloadable
+    }
+
+    /// Get the value, if it is of type `T`.
+    pub fn downcast_ref<T: ReactorData>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+
+    /// The name of the type of the value.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl std::fmt::Debug for Parameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Parameter<{}>", self.type_name)
+    }
+}
+
+/// The set of global parameters, shared by the contexts of all reactions.
+#[derive(Debug, Clone, Default)]
+pub struct Parameters(Arc<BTreeMap<String, Arc<Parameter>>>);
+
+impl Parameters {
+    pub fn new(parameters: BTreeMap<String, Parameter>) -> Self {
+        Self(Arc::new(
+            parameters
+                .into_iter()
+                .map(|(name, parameter)| (name, Arc::new(parameter)))
+                .collect(),
+        ))
+    }
+
+    /// Get the value of the parameter `name`, if it exists and is of type `T`.
+    pub fn get<T: ReactorData>(&self, name: &str) -> Result<&T, RuntimeError> {
+        let parameter = self
+            .0
+            .get(name)
+            .ok_or_else(|| RuntimeError::ParameterNotFound(name.to_owned()))?;
+        parameter.downcast_ref().ok_or(RuntimeError::TypeMismatch {
+            found: parameter.type_name,
+            wanted: std::any::type_name::<T>(),
+        })
+    }
+
+    /// Iterate over the names and parameters, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Parameter)> {
+        self.0
+            .iter()
+            .map(|(name, parameter)| (name.as_str(), parameter.as_ref()))
+    }
+
+    /// Check that the parameter `name` exists, is reloadable and of the same type as `value`.
+    pub(crate) fn check_update(&self, name: &str, value: &Parameter) -> Result<(), RuntimeError> {
+        let parameter = self
+            .0
+            .get(name)
+            .ok_or_else(|| RuntimeError::ParameterNotFound(name.to_owned()))?;
+        if !parameter.reloadable {
+            return Err(RuntimeError::ParameterNotReloadable(name.to_owned()));
+        }
+        if parameter.type_name != value.type_name {
+            return Err(RuntimeError::TypeMismatch {
+                found: value.type_name,
+                wanted: parameter.type_name,
+            });
+        }
+        Ok(())
+    }
+
+    /// A copy of the parameters with the values of `updates` replaced, sharing the unchanged values.
+    pub(crate) fn updated(&self, updates: impl IntoIterator<Item = (String, Parameter)>) -> Self {
+        let mut parameters = BTreeMap::clone(&self.0);
+        for (name, value) in updates {
+            parameters.insert(name, Arc::new(value));
+        }
+        Self(Arc::new(parameters))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// The parameters updated at a tag, the value of the parameter change actions, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterChange {
+    /// The names of the updated parameters, in the order their first update was received
+    pub names: Vec<String>,
+}
+
+impl Display for ParameterChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Updated parameters: {}", self.names.join(", "))
+    }
+}
+
+/// The parameter updates received by the scheduler, waiting for their tag.
+#[derive(Debug, Default)]
+pub(crate) struct PendingParameters {
+    tag: Option<Tag>,
+    updates: Vec<(String, Parameter)>,
+}
+
+impl PendingParameters {
+    /// Queue an update for the tag after `current`, returning the tag if it's the first update for it, so the caller
+    /// schedules an event at it.
+    pub(crate) fn push(&mut self, current: Tag, name: String, value: Parameter) -> Option<Tag> {
+        self.updates.push((name, value));
+        match self.tag {
+            Some(_) => None,
+            None => {
+                let tag = current.delay(crate::Duration::ZERO);
+                self.tag = Some(tag);
+                Some(tag)
+            }
+        }
+    }
+
+    /// Take the updates due at `tag`.
+    pub(crate) fn take_due(&mut self, tag: Tag) -> Vec<(String, Parameter)> {
+        match self.tag {
+            Some(due) if due <= tag => {
+                self.tag = None;
+                std::mem::take(&mut self.updates)
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameters() {
+        let parameters = Parameters::new(
+            [
+                ("gravity".to_owned(), Parameter::new(9.81f64)),
+                ("name".to_owned(), Parameter::new("boomerang".to_owned())),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters.get::<f64>("gravity").unwrap(), &9.81);
+        assert_eq!(parameters.get::<String>("name").unwrap(), "boomerang");
+        assert!(matches!(
+            parameters.get::<f64>("tick_rate"),
+            Err(RuntimeError::ParameterNotFound(name)) if name == "tick_rate"
+        ));
+        assert!(matches!(
+            parameters.get::<f32>("gravity"),
+            Err(RuntimeError::TypeMismatch {
+                found: "f64",
+                wanted: "f32"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parameter_updates() {
+        let parameters = Parameters::new(
+            [
+                ("gravity".to_owned(), Parameter::new(9.81f64)),
+                ("tick_rate".to_owned(), Parameter::reloadable(100.0f64)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let update = Parameter::reloadable(200.0f64);
+        assert!(parameters.check_update("tick_rate", &update).is_ok());
+        assert!(matches!(
+            parameters.check_update("gravity", &update),
+            Err(RuntimeError::ParameterNotReloadable(name)) if name == "gravity"
+        ));
+        assert!(matches!(
+            parameters.check_update("tick_rate", &Parameter::reloadable(200u32)),
+            Err(RuntimeError::TypeMismatch {
+                found: "u32",
+                wanted: "f64"
+            })
+        ));
+
+        let mut pending = PendingParameters::default();
+        let tag = Tag::new(crate::Duration::milliseconds(5), 0);
+        assert_eq!(
+            pending.push(tag, "tick_rate".to_owned(), update),
+            Some(tag.delay(crate::Duration::ZERO))
+        );
+        assert_eq!(
+            pending.push(tag, "tick_rate".to_owned(), Parameter::reloadable(300.0f64)),
+            None
+        );
+        assert!(pending.take_due(tag).is_empty());
+        let updates = pending.take_due(tag.delay(crate::Duration::ZERO));
+        assert_eq!(updates.len(), 2);
+
+        // The last update wins, the other parameters are unchanged
+        let updated = parameters.updated(updates);
+        assert_eq!(updated.get::<f64>("tick_rate").unwrap(), &300.0);
+        assert_eq!(updated.get::<f64>("gravity").unwrap(), &9.81);
+        assert!(updated
+            .iter()
+            .all(|(name, parameter)| { parameter.is_reloadable() == (name == "tick_rate") }));
+        assert_eq!(parameters.get::<f64>("tick_rate").unwrap(), &100.0);
+    }
+}
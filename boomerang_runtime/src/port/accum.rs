@@ -0,0 +1,185 @@
+//! Ports accumulating a batch of values per tag.
+//!
+//! A port of type [`Accum<T>`] carries all the values pushed to it at the current tag, e.g. the N detections of a
+//! frame pushed one by one by a loop in the producing reaction, and its consumers read the whole batch:
+//!
+//! - [`OutputRef::push`] and [`OutputRef::extend`] append to the batch, setting the port at the first value,
+//! - the port is absent at tags where nothing was pushed, never set to an empty batch,
+//! - at the end of the tag, the batch is cleared but its buffer is kept by the port, so that pushes at later tags
+//!   reuse the allocation.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! #[derive(Reactor)]
+//! #[reactor(state = "()", reaction = "ReactionFrame")]
+//! struct Detector {
+//!     frame: TypedPortKey<Frame, Input>,
+//!     detections: AccumPort<Detection, Output>,
+//! }
+//!
+//! fn trigger(mut self, _ctx: &mut runtime::Context, _state: &mut ()) {
+//!     for detection in detect(self.frame.as_ref().unwrap()) {
+//!         self.detections.push(detection);
+//!     }
+//! }
+//! ```
+
+use std::ops::Deref;
+
+use crate::{mem_size::MemSize, ReactorData};
+
+use super::OutputRef;
+
+/// A batch of values accumulated by a port during a tag, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Accum<T>(Vec<T>);
+
+impl<T> Default for Accum<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T> Accum<T> {
+    /// The values of the batch, in the order they were pushed.
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
+
+    /// Take the values out of the batch, leaving it empty.
+    ///
+    /// The buffer of the batch isn't reused after this.
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+}
+
+impl<T> Deref for Accum<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Accum<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<T> From<Vec<T>> for Accum<T> {
+    fn from(values: Vec<T>) -> Self {
+        Self(values)
+    }
+}
+
+impl<T> FromIterator<T> for Accum<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<T: MemSize> MemSize for Accum<T> {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size()
+    }
+}
+
+impl<'a, T: ReactorData> OutputRef<'a, Accum<T>> {
+    /// Append `value` to the batch of the current tag.
+    pub fn push(&mut self, value: T) {
+        self.batch().0.push(value);
+    }
+
+    /// Append all of `values` to the batch of the current tag, leaving the port untouched if there are none.
+    pub fn extend(&mut self, values: impl IntoIterator<Item = T>) {
+        let mut values = values.into_iter().peekable();
+        if values.peek().is_some() {
+            self.batch().0.extend(values);
+        }
+    }
+
+    /// The batch of the current tag, reusing the buffer of a previous tag when the port is not yet set.
+    fn batch(&mut self) -> &mut Accum<T> {
+        let port = &mut *self.0;
+        port.recycle.get_or_insert(Accum::clear);
+        port.rewind.get_or_insert((Accum::len, Accum::truncate));
+        if port.value.is_none() {
+            // A batch moved aside by a checkpoint is appended to, and truncated back to its length on a rollback
+            if let Some(shelved) = port.shelved.take() {
+                port.mark = Some(shelved.len());
+                port.value = Some(shelved);
+            } else {
+                port.value = Some(port.spare.take().unwrap_or_default());
+            }
+        }
+        port.value.as_mut().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BasePort, Port, PortKey};
+
+    use super::*;
+
+    #[test]
+    fn test_accum() {
+        let mut port = Port::<Accum<u32>>::new("out", PortKey::from(0));
+
+        let mut out = OutputRef::from(&mut port);
+        out.extend([]);
+        assert!(out.is_none());
+        out.push(1);
+        out.extend([2, 3]);
+        assert_eq!(out.as_deref(), Some(&[1, 2, 3][..]));
+        let ptr = out.as_ref().unwrap().as_ptr();
+
+        // Cleared at the end of the tag, keeping the buffer for the next one
+        port.cleanup();
+        assert!(!port.is_set());
+        let mut out = OutputRef::from(&mut port);
+        out.push(4);
+        assert_eq!(out.as_deref(), Some(&[4][..]));
+        assert_eq!(out.as_ref().unwrap().as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_accum_rollback() {
+        let mut port = Port::<Accum<u32>>::new("out", PortKey::from(0));
+        OutputRef::from(&mut port).push(1);
+
+        // The values appended since the checkpoint are removed, the earlier ones kept
+        port.checkpoint();
+        OutputRef::from(&mut port).extend([2, 3]);
+        port.rollback();
+        assert_eq!(port.as_deref(), Some(&[1][..]));
+
+        // A batch set directly is moved aside by the checkpoint, and appended to by the next push
+        let mut port = Port::<Accum<u32>>::new("out", PortKey::from(0));
+        *OutputRef::from(&mut port) = Some(Accum::from(vec![5]));
+        port.checkpoint();
+        assert_eq!(OutputRef::from(&mut port).as_deref(), Some(&[5][..]));
+        OutputRef::from(&mut port).push(6);
+        port.commit();
+        assert_eq!(port.as_deref(), Some(&[5, 6][..]));
+    }
+}
@@ -4,7 +4,11 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
-use crate::ReactorData;
+use crate::{value_fmt, ReactorData, RuntimeError};
+
+mod accum;
+
+pub use accum::Accum;
 
 tinymap::key_type! { pub PortKey }
 
@@ -21,24 +25,61 @@ pub trait BasePort: Debug + Display + Downcast + Send + Sync {
     /// Reset the internal value
     fn cleanup(&mut self);
 
+    /// Record the value before a reaction that isolates its panics runs, see [`crate::isolation`].
+    ///
+    /// A value set by an earlier reaction at this tag is moved aside, so that it can be restored exactly. An [`Accum`]
+    /// batch keeps its values, and only its length is recorded.
+    fn checkpoint(&mut self);
+
+    /// Keep the value set by the reaction since the [`BasePort::checkpoint`], or the earlier one if it set none.
+    fn commit(&mut self);
+
+    /// Restore the value recorded by the [`BasePort::checkpoint`], discarding what the reaction set since.
+    fn rollback(&mut self);
+
     /// Get the internal type name str
     fn type_name(&self) -> &'static str;
+
+    /// Format the current value, if set and its type is registered, see [`crate::value_fmt`].
+    fn debug_value(&self) -> Option<String>;
+
+    /// Include the value in the `Debug` and `Display` output, see [`crate::value_fmt`].
+    fn set_debug_values(&mut self, enabled: bool);
+
+    /// The estimated size of the port value in bytes, see [`crate::mem_size`].
+    fn value_mem_size(&self) -> usize;
 }
 impl_downcast!(BasePort);
 
+/// The length of a batch and how to truncate it back to a length.
+type Rewind<T> = (fn(&T) -> usize, fn(&mut T, usize));
+
 pub struct Port<T: ReactorData> {
     name: String,
     key: PortKey,
     value: Option<T>,
+    /// Clears a value at cleanup so that it can be reused as [`Self::spare`], see [`Accum`]
+    recycle: Option<fn(&mut T)>,
+    /// A cleared value kept from the previous tag
+    spare: Option<T>,
+    /// The length of a batch and how to truncate it, so that appends can be undone, see [`Accum`]
+    rewind: Option<Rewind<T>>,
+    /// The value set by an earlier reaction, moved aside by [`BasePort::checkpoint`]
+    shelved: Option<T>,
+    /// The length of the batch at [`BasePort::checkpoint`]
+    mark: Option<usize>,
+    /// Whether the value is included in the `Debug` and `Display` output
+    debug_values: bool,
 }
 
 impl<T: ReactorData> Debug for Port<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Port")
-            .field("name", &self.name)
-            .field("key", &self.key)
-            //.field("value", &self.value)
-            .finish()
+        let mut debug = f.debug_struct("Port");
+        debug.field("name", &self.name).field("key", &self.key);
+        if let Some(value) = self.formatted_value() {
+            debug.field("value", &value);
+        }
+        debug.finish()
     }
 }
 
@@ -50,7 +91,11 @@ impl<T: ReactorData> Display for Port<T> {
             ty = std::any::type_name::<T>(),
             name = &self.name,
             key = self.key
-        )
+        )?;
+        if let Some(value) = self.formatted_value() {
+            write!(f, " = {value:?}")?;
+        }
+        Ok(())
     }
 }
 
@@ -74,9 +119,23 @@ impl<T: ReactorData> Port<T> {
             name: name.to_owned(),
             key,
             value: None,
+            recycle: None,
+            spare: None,
+            rewind: None,
+            shelved: None,
+            mark: None,
+            debug_values: false,
         }
     }
 
+    /// The value to include in the `Debug` and `Display` output, if enabled.
+    fn formatted_value(&self) -> Option<value_fmt::DebugValue<'_>> {
+        self.value
+            .as_ref()
+            .filter(|_| self.debug_values)
+            .and_then(value_fmt::debug_value)
+    }
+
     pub fn get(&self) -> &Option<T> {
         &self.value
     }
@@ -104,12 +163,59 @@ impl<T: ReactorData> BasePort for Port<T> {
     }
 
     fn cleanup(&mut self) {
-        self.value = None;
+        if let (Some(recycle), Some(mut value)) = (self.recycle, self.value.take()) {
+            recycle(&mut value);
+            self.spare = Some(value);
+        }
+    }
+
+    fn checkpoint(&mut self) {
+        match (self.rewind, &self.value) {
+            (Some((len, _)), Some(value)) => self.mark = Some(len(value)),
+            (None, Some(_)) => self.shelved = self.value.take(),
+            (_, None) => {}
+        }
+    }
+
+    fn commit(&mut self) {
+        self.mark = None;
+        if self.value.is_none() {
+            self.value = self.shelved.take();
+        } else {
+            self.shelved = None;
+        }
+    }
+
+    fn rollback(&mut self) {
+        match (self.mark.take(), self.rewind, self.value.as_mut()) {
+            (Some(mark), Some((_, truncate)), Some(value)) => truncate(value, mark),
+            _ => {
+                self.cleanup();
+                self.value = self.shelved.take();
+            }
+        }
     }
 
     fn type_name(&self) -> &'static str {
         std::any::type_name::<T>()
     }
+
+    fn debug_value(&self) -> Option<String> {
+        self.value
+            .as_ref()
+            .and_then(value_fmt::debug_value)
+            .map(|value| format!("{value:?}"))
+    }
+
+    fn set_debug_values(&mut self, enabled: bool) {
+        self.debug_values = enabled;
+    }
+
+    fn value_mem_size(&self) -> usize {
+        std::mem::size_of::<Option<T>>()
+            + self.value.as_ref().map_or(0, crate::mem_size::heap_size)
+            + self.spare.as_ref().map_or(0, crate::mem_size::heap_size)
+    }
 }
 
 /// A reference to an input port.
@@ -127,6 +233,18 @@ impl<'a, T: ReactorData> InputRef<'a, T> {
     pub fn key(&self) -> PortKey {
         self.0.get_key()
     }
+
+    /// Get the value of the port, or a [`RuntimeError::PortAbsent`] error if it is not set at the current tag.
+    pub fn get_or_err(&self) -> Result<&'a T, RuntimeError> {
+        self.0
+            .as_ref()
+            .ok_or_else(|| RuntimeError::PortAbsent(self.0.get_name().to_owned()))
+    }
+
+    /// Apply `f` to the value of the port if it is set at the current tag.
+    pub fn map_present<U>(&self, f: impl FnOnce(&'a T) -> U) -> Option<U> {
+        self.0.as_ref().map(f)
+    }
 }
 
 impl<'a, T: ReactorData> From<&'a Port<T>> for InputRef<'a, T> {
@@ -152,6 +270,32 @@ impl<'a, T: ReactorData> From<&'a (dyn BasePort)> for InputRef<'a, T> {
     }
 }
 
+/// Bank-wide operations on the inputs from a bank of reactors or a multiport, e.g. `[InputRef<T>; N]`.
+///
+/// Reductions over the present values don't need a manual unwrap-and-check loop:
+///
+/// ```rust,ignore
+/// let total: u32 = self.inputs.iter_present().map(|(_, value)| value).sum();
+/// let loudest = self.inputs.iter_present().max_by_key(|&(_, value)| value).map(|(index, _)| index);
+/// ```
+pub trait BankInputs<'a, T: ReactorData> {
+    /// Iterate over the values present at the current tag, with the bank index of their input.
+    fn iter_present(&self) -> impl Iterator<Item = (usize, &'a T)> + '_;
+
+    /// The number of inputs with a value present at the current tag.
+    fn count_present(&self) -> usize {
+        self.iter_present().count()
+    }
+}
+
+impl<'a, T: ReactorData> BankInputs<'a, T> for [InputRef<'a, T>] {
+    fn iter_present(&self) -> impl Iterator<Item = (usize, &'a T)> + '_ {
+        self.iter()
+            .enumerate()
+            .filter_map(|(index, input)| Some((index, input.0.as_ref()?)))
+    }
+}
+
 /// A reference to an output port.
 ///
 /// `OutputRef` is the type that Reaction functions receive for their input ports.
@@ -167,6 +311,16 @@ impl<'a, T: ReactorData> OutputRef<'a, T> {
     pub fn key(&self) -> PortKey {
         self.0.get_key()
     }
+
+    /// Set the port to the last value yielded by `values`, leaving it untouched if there is none.
+    ///
+    /// Since `Option<T>` and `Result<T, E>` are both iterators over their value, this forwards them without a
+    /// `match`, e.g. `out.set_from(inp.map_present(|v| v * 2))` or `out.set_from(value.parse::<u32>())`.
+    pub fn set_from(&mut self, values: impl IntoIterator<Item = T>) {
+        if let Some(value) = values.into_iter().last() {
+            *self.0.get_mut() = Some(value);
+        }
+    }
 }
 
 impl<'a, T: ReactorData> From<&'a mut Port<T>> for OutputRef<'a, T> {
@@ -178,8 +332,14 @@ impl<'a, T: ReactorData> From<&'a mut Port<T>> for OutputRef<'a, T> {
 impl<'a, T: ReactorData> Deref for OutputRef<'a, T> {
     type Target = <Port<T> as Deref>::Target;
 
+    /// The value of the port, including a value set by an earlier reaction and moved aside by
+    /// [`BasePort::checkpoint`].
     fn deref(&self) -> &Self::Target {
-        self.0.deref()
+        if self.0.value.is_none() {
+            &self.0.shelved
+        } else {
+            &self.0.value
+        }
     }
 }
 
@@ -197,3 +357,65 @@ impl<'a, T: ReactorData> From<&'a mut dyn BasePort> for OutputRef<'a, T> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_port_adapters() {
+        let mut inp = Port::<u32>::new("inp", PortKey::from(0));
+        let mut out = Port::<u32>::new("out", PortKey::from(1));
+
+        let inp_ref = InputRef::from(&inp);
+        assert!(
+            matches!(inp_ref.get_or_err(), Err(RuntimeError::PortAbsent(name)) if name == "inp")
+        );
+        assert_eq!(inp_ref.map_present(|v| v * 2), None);
+
+        *inp.get_mut() = Some(21);
+        let inp_ref = InputRef::from(&inp);
+        assert_eq!(inp_ref.get_or_err().ok(), Some(&21));
+
+        let mut out_ref = OutputRef::from(&mut out);
+        out_ref.set_from(inp_ref.map_present(|v| v * 2));
+        assert_eq!(*out_ref, Some(42));
+        out_ref.set_from(None);
+        assert_eq!(*out_ref, Some(42));
+        out_ref.set_from("x".parse::<u32>());
+        assert_eq!(*out_ref, Some(42));
+        out_ref.set_from([1, 2, 3]);
+        assert_eq!(*out_ref, Some(3));
+    }
+
+    #[test]
+    fn test_debug_values() {
+        let mut port = Port::<u32>::new("out", PortKey::from(0));
+        *port.get_mut() = Some(5);
+        assert!(!port.to_string().contains("= 5"));
+        assert_eq!(port.debug_value().as_deref(), Some("5"));
+
+        port.set_debug_values(true);
+        assert!(port.to_string().ends_with(" = 5"));
+    }
+
+    #[test]
+    fn test_bank_inputs() {
+        let mut ports = (0..4)
+            .map(|i| Port::<u32>::new(&format!("inp{i}"), PortKey::from(i)))
+            .collect::<Vec<_>>();
+        *ports[1].get_mut() = Some(10);
+        *ports[3].get_mut() = Some(30);
+
+        let inputs = ports.iter().map(InputRef::from).collect::<Vec<_>>();
+        assert_eq!(
+            inputs.iter_present().collect::<Vec<_>>(),
+            [(1, &10), (3, &30)]
+        );
+        assert_eq!(inputs.count_present(), 2);
+        assert_eq!(inputs.iter_present().map(|(_, v)| v).sum::<u32>(), 40);
+
+        let array: [InputRef<u32>; 2] = [InputRef::from(&ports[0]), InputRef::from(&ports[3])];
+        assert_eq!(array.iter_present().collect::<Vec<_>>(), [(1, &30)]);
+    }
+}
@@ -0,0 +1,165 @@
+//! Value probes, the reactor equivalent of data breakpoints.
+//!
+//! A [`Probe`] watches a single port or action and evaluates a predicate on its value at every tag it is present.
+//! When the predicate matches, the [`crate::Scheduler`] captures a [`ProbeSnapshot`] with the current tag, the
+//! reactions that produced (or are triggered by) the value, and the most recently processed events. In interactive
+//! mode (see [`crate::Config::with_interactive`]) the scheduler then pauses until Enter is pressed, otherwise the
+//! snapshot is logged.
+
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+use crate::{Action, ActionKey, BaseAction, BasePort, Port, PortKey, ReactorData, Tag};
+
+/// The element watched by a [`Probe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeKey {
+    Port(PortKey),
+    Action(ActionKey),
+}
+
+/// A matching value captured by a probe created with [`Probe::new_capturing`].
+pub type ProbeValue = Arc<dyn Any + Send + Sync>;
+
+type ProbeMatch = (String, Option<ProbeValue>);
+type PortMatcherFn = dyn Fn(&dyn BasePort) -> Option<ProbeMatch> + Send + Sync;
+type ActionMatcherFn = dyn Fn(&mut dyn BaseAction, Tag) -> Option<ProbeMatch> + Send + Sync;
+
+/// Type-erased predicates, returning the formatted and optionally the captured value on a match.
+pub(crate) enum ProbeMatcher {
+    Port(Box<PortMatcherFn>),
+    Action(Box<ActionMatcherFn>),
+}
+
+pub struct Probe {
+    /// The name of the probe, usually the fully-qualified name of the watched element.
+    name: String,
+    key: ProbeKey,
+    pub(crate) matcher: ProbeMatcher,
+}
+
+impl Debug for Probe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Probe")
+            .field("name", &self.name)
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+impl Probe {
+    /// Create a new probe on the port or action `key` carrying values of type `T`.
+    pub fn new<T, F>(name: &str, key: ProbeKey, predicate: F) -> Self
+    where
+        T: ReactorData + Debug,
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        Self::with_capture(name, key, predicate, None)
+    }
+
+    /// Create a new probe like [`Probe::new`], which also captures a clone of each matching value, see
+    /// [`ProbeSnapshot::value_as`].
+    pub fn new_capturing<T, F>(name: &str, key: ProbeKey, predicate: F) -> Self
+    where
+        T: ReactorData + Debug + Clone,
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        Self::with_capture(
+            name,
+            key,
+            predicate,
+            Some(|value: &T| Arc::new(value.clone()) as ProbeValue),
+        )
+    }
+
+    fn with_capture<T, F>(
+        name: &str,
+        key: ProbeKey,
+        predicate: F,
+        capture: Option<fn(&T) -> ProbeValue>,
+    ) -> Self
+    where
+        T: ReactorData + Debug,
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        let matched =
+            move |value: &T| (format!("{value:?}"), capture.map(|capture| capture(value)));
+        let matcher = match key {
+            ProbeKey::Port(_) => ProbeMatcher::Port(Box::new(move |port| {
+                port.downcast_ref::<Port<T>>()?
+                    .get()
+                    .as_ref()
+                    .filter(|value| predicate(value))
+                    .map(matched)
+            })),
+            ProbeKey::Action(_) => ProbeMatcher::Action(Box::new(move |action, tag| {
+                action
+                    .downcast_mut::<Action<T>>()?
+                    .get_current(tag)
+                    .filter(|value| predicate(value))
+                    .map(matched)
+            })),
+        };
+
+        Self {
+            name: name.into(),
+            key,
+            matcher,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn key(&self) -> ProbeKey {
+        self.key
+    }
+}
+
+/// The context captured when a [`Probe`] matches.
+#[derive(Clone)]
+pub struct ProbeSnapshot {
+    /// The name of the matching probe
+    pub probe: String,
+    /// The tag at which the probe matched
+    pub tag: Tag,
+    /// The `Debug` representation of the matching value
+    pub value: String,
+    /// The matching value, if captured by a probe created with [`Probe::new_capturing`]
+    pub captured: Option<ProbeValue>,
+    /// For ports, the reactions that set the value. For actions, the reactions triggered by it.
+    pub reactions: Vec<String>,
+    /// The most recently processed events, oldest first
+    pub recent_events: Vec<String>,
+}
+
+impl ProbeSnapshot {
+    /// The captured matching value, if it was captured and is of type `T`.
+    pub fn value_as<T: 'static>(&self) -> Option<&T> {
+        self.captured.as_deref()?.downcast_ref()
+    }
+}
+
+impl Debug for ProbeSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProbeSnapshot")
+            .field("probe", &self.probe)
+            .field("tag", &self.tag)
+            .field("value", &self.value)
+            .field("reactions", &self.reactions)
+            .field("recent_events", &self.recent_events)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for ProbeSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Probe '{}' matched at {} with value {}",
+            self.probe, self.tag, self.value
+        )?;
+        writeln!(f, "  reactions: [{}]", self.reactions.join(", "))?;
+        write!(f, "  recent events: [{}]", self.recent_events.join(", "))
+    }
+}
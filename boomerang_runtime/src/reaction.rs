@@ -1,6 +1,7 @@
 use std::{fmt::Debug, sync::RwLock};
 
 use crate::{
+    budget::Budget,
     key_set::KeySet,
     refs::{Refs, RefsMut},
     ActionRef, BaseAction, BasePort, BaseReactor, Context, Duration, Reactor, ReactorData,
@@ -36,6 +37,11 @@ pub trait FromRefs {
         ports_mut: RefsMut<'store, dyn BasePort>,
         actions: RefsMut<'store, dyn BaseAction>,
     ) -> Self::Marker<'store>;
+
+    /// Whether to skip the trigger body for the given refs, e.g. because a required input is absent.
+    fn skip(_reaction: &Self::Marker<'_>) -> bool {
+        false
+    }
 }
 
 /// The `Trigger` trait should be implemented by the user for each Reaction struct.
@@ -45,6 +51,19 @@ pub trait Trigger<S: ReactorData> {
     fn trigger(self, ctx: &mut Context, state: &mut S);
 }
 
+/// Implemented by the user instead of [`Trigger`] for Reaction structs declaring the state fields they use with
+/// `#[reaction(state(fields = "..."))]`.
+///
+/// Type parameter `P` is the projection of the state generated by the derive, named after the Reaction struct with a
+/// `State` suffix, holding a mutable borrow of each declared field.
+///
+/// Reactions declaring disjoint fields are not ordered by their priority, so a reaction can run at an earlier level
+/// than the reactions declared before it in its reactor. They never run in parallel with each other though: the
+/// reactions of a reactor are always at distinct levels, as each borrows the whole reactor.
+pub trait TriggerFields<P> {
+    fn trigger(self, ctx: &mut Context, state: P);
+}
+
 /// Adapter struct for implementing the `ReactionFn` trait for a Reaction struct.
 ///
 /// The `ReactionAdapter` struct is used to convert a Reaction struct to a `Box<dyn ReactionFn>`. This is the mechanism
@@ -89,6 +108,9 @@ where
             .expect("Unable to downcast reactor state");
 
         let reaction = Reaction::from_refs(ports, ports_mut, actions);
+        if Reaction::skip(&reaction) {
+            return;
+        }
         reaction.trigger(ctx, &mut reactor.state);
     }
 }
@@ -138,12 +160,24 @@ where
     }
 }
 
+/// A deadline on the physical time lag of a reaction, relative to the logical time of its invocation.
+///
+/// If the deadline has already passed when the reaction is invoked, the handler is called before the reaction body. The
+/// reaction can detect a deadline passing during its execution with [`Context::cancellation`].
 pub struct Deadline {
     pub(crate) deadline: Duration,
-    #[allow(dead_code)]
     pub(crate) handler: RwLock<BoxedHandlerFn>,
 }
 
+impl Deadline {
+    pub fn new(deadline: Duration, handler: impl Fn() + Send + Sync + 'static) -> Self {
+        Self {
+            deadline,
+            handler: RwLock::new(Box::new(handler)),
+        }
+    }
+}
+
 impl Debug for Deadline {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Deadline")
@@ -159,6 +193,8 @@ pub struct Reaction {
     pub(crate) body: BoxedReactionFn,
     /// Local deadline relative to the time stamp for invocation of the reaction.
     pub(crate) deadline: Option<Deadline>,
+    /// Execution time budget of a single invocation, see [`crate::budget`].
+    pub(crate) budget: Option<Budget>,
 }
 
 impl Debug for Reaction {
@@ -167,6 +203,7 @@ impl Debug for Reaction {
             .field("name", &self.name)
             .field("body", &"ReactionFn()")
             .field("deadline", &self.deadline)
+            .field("budget", &self.budget)
             .finish()
     }
 }
@@ -177,9 +214,16 @@ impl Reaction {
             name: name.to_owned(),
             body: body.into(),
             deadline,
+            budget: None,
         }
     }
 
+    /// Set the execution time budget of a single invocation, see [`crate::budget`].
+    pub fn with_budget(mut self, budget: Duration) -> Self {
+        self.budget = Some(Budget::new(budget));
+        self
+    }
+
     pub fn get_name(&self) -> &str {
         &self.name
     }
@@ -204,7 +248,9 @@ pub fn timer_startup_fn(
     actions: RefsMut<dyn BaseAction>,
 ) {
     let mut timer: ActionRef = actions.partition_mut().expect("Expected a timer action");
-    timer.schedule(ctx, (), None);
+    timer
+        .schedule(ctx, (), None)
+        .expect("A timer is always scheduled into the future");
 }
 
 /// Timer ReactionFn for timer actions
@@ -227,11 +273,11 @@ impl<'store> ReactionFn<'store> for TimerFn {
     ) {
         let mut timer: ActionRef = actions.partition_mut().expect("Expected a timer action");
 
-        if timer.is_present(ctx) {
-            timer.schedule(ctx, (), self.0);
-        } else {
-            timer.schedule(ctx, (), None);
-        }
+        // The period of a timer is checked to be non-negative when it is built
+        let period = if timer.is_present(ctx) { self.0 } else { None };
+        timer
+            .schedule(ctx, (), period)
+            .expect("A timer is always scheduled into the future");
     }
 }
 
@@ -1,14 +1,74 @@
-use std::fmt::{Debug, Display};
+use std::{
+    fmt::{Debug, Display},
+    marker::PhantomData,
+};
 
 use downcast_rs::{impl_downcast, Downcast};
 
-use crate::ReactorData;
+use crate::{PanicPolicy, ReactorData};
 
 tinymap::key_type! { pub ReactorKey }
 
+/// A [`ReactorKey`] for a reactor whose state is known to be `S`.
+///
+/// Typed keys are checked once when created with [`crate::Env::typed_reactor_key`], after which the state is accessed
+/// infallibly with [`crate::Env::state`] and [`crate::Env::state_mut`].
+pub struct TypedReactorKey<S>(ReactorKey, PhantomData<fn() -> S>);
+
+impl<S> TypedReactorKey<S> {
+    pub(crate) fn new(key: ReactorKey) -> Self {
+        Self(key, PhantomData)
+    }
+
+    /// The untyped key.
+    pub fn key(&self) -> ReactorKey {
+        self.0
+    }
+}
+
+impl<S> Clone for TypedReactorKey<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for TypedReactorKey<S> {}
+
+impl<S> PartialEq for TypedReactorKey<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<S> Eq for TypedReactorKey<S> {}
+
+impl<S> Debug for TypedReactorKey<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TypedReactorKey")
+            .field(&self.0)
+            .field(&std::any::type_name::<S>())
+            .finish()
+    }
+}
+
 pub trait BaseReactor: Debug + Downcast + Send + Sync {
     /// Get the name of the reactor
     fn name(&self) -> &str;
+
+    /// Format the reactor state, if its type is registered, see [`crate::value_fmt`].
+    fn debug_state(&self) -> Option<String>;
+
+    /// How panics in the reactions of the reactor are handled, see [`crate::isolation`].
+    fn panic_policy(&self) -> PanicPolicy;
+
+    /// Whether a reaction of the reactor panicked, and its reactions are skipped since.
+    fn is_failed(&self) -> bool;
+
+    /// Mark the reactor failed after a panic, or reset its state if its policy is [`PanicPolicy::Restart`].
+    fn fail(&mut self);
+
+    /// The estimated size of the reactor state in bytes, see [`crate::mem_size`].
+    fn state_mem_size(&self) -> usize;
 }
 
 impl_downcast!(BaseReactor);
@@ -17,6 +77,10 @@ impl dyn BaseReactor {
     pub fn get_state<T: ReactorData>(&self) -> Option<&T> {
         self.downcast_ref::<Reactor<T>>().map(|r| &r.state)
     }
+
+    pub fn get_state_mut<T: ReactorData>(&mut self) -> Option<&mut T> {
+        self.downcast_mut::<Reactor<T>>().map(|r| &mut r.state)
+    }
 }
 
 pub struct Reactor<T: ReactorData> {
@@ -24,6 +88,12 @@ pub struct Reactor<T: ReactorData> {
     name: String,
     /// The ReactorState
     pub state: T,
+    /// How panics in the reactions are handled
+    panic_policy: PanicPolicy,
+    /// Creates the initial state when restarting after a panic
+    initial_state: Option<Box<dyn Fn() -> T + Send + Sync>>,
+    /// A reaction panicked with [`PanicPolicy::Isolate`]
+    failed: bool,
 }
 
 impl<T: ReactorData> Debug for Reactor<T> {
@@ -31,6 +101,8 @@ impl<T: ReactorData> Debug for Reactor<T> {
         f.debug_struct("Reactor")
             .field("name", &self.name)
             .field("state", &std::any::type_name::<T>())
+            .field("panic_policy", &self.panic_policy)
+            .field("failed", &self.failed)
             .finish()
     }
 }
@@ -51,6 +123,27 @@ impl<T: ReactorData> Reactor<T> {
         Self {
             name: name.to_owned(),
             state,
+            panic_policy: PanicPolicy::Propagate,
+            initial_state: None,
+            failed: false,
+        }
+    }
+
+    /// Catch panics in the reactions of this reactor, and skip all of its reactions after the first panic.
+    pub fn isolate_panics(self) -> Self {
+        Self {
+            panic_policy: PanicPolicy::Isolate,
+            initial_state: None,
+            ..self
+        }
+    }
+
+    /// Catch panics in the reactions of this reactor, and reset its state with `initial_state` after each panic.
+    pub fn with_restart(self, initial_state: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        Self {
+            panic_policy: PanicPolicy::Restart,
+            initial_state: Some(Box::new(initial_state)),
+            ..self
         }
     }
 
@@ -63,8 +156,40 @@ impl<T: ReactorData> Reactor<T> {
     }
 }
 
+impl<T: ReactorData + Clone> Reactor<T> {
+    /// Catch panics in the reactions of this reactor, and reset its state to a clone of the current state after each
+    /// panic.
+    pub fn restart_on_panic(self) -> Self {
+        let initial_state = self.state.clone();
+        self.with_restart(move || initial_state.clone())
+    }
+}
+
 impl<T: ReactorData> BaseReactor for Reactor<T> {
     fn name(&self) -> &str {
         &self.name
     }
+
+    fn debug_state(&self) -> Option<String> {
+        crate::value_fmt::debug_value(&self.state).map(|state| format!("{state:?}"))
+    }
+
+    fn panic_policy(&self) -> PanicPolicy {
+        self.panic_policy
+    }
+
+    fn is_failed(&self) -> bool {
+        self.failed
+    }
+
+    fn fail(&mut self) {
+        match &self.initial_state {
+            Some(initial_state) => self.state = initial_state(),
+            None => self.failed = true,
+        }
+    }
+
+    fn state_mem_size(&self) -> usize {
+        crate::mem_size::mem_size(&self.state)
+    }
 }
@@ -117,6 +117,15 @@ impl<'a, T: 'a + ?Sized> RefsMut<'a, T> {
         }
     }
 
+    /// Reborrow the remaining references, so they can be used again once the reborrow ends.
+    pub(crate) fn reborrow(&mut self) -> RefsMut<'_, T> {
+        RefsMut {
+            ptr: self.ptr,
+            end: self.end,
+            _marker: PhantomData,
+        }
+    }
+
     /// Wrapper function for dynamic destructuring with mutable references.
     ///
     /// This function is used to destructure `RefsMut` into a tuple of concrete types.
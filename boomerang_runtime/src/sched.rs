@@ -1,15 +1,37 @@
-use crossbeam_channel::{Receiver, RecvTimeoutError};
-use std::{collections::BinaryHeap, pin::Pin};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use std::{
+    collections::{BinaryHeap, VecDeque},
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
 
 use crate::{
+    budget::DEFAULT_BUDGET_SAMPLING,
     build_reaction_contexts,
+    cancel::CancellationToken,
+    context::TriggerRes,
+    deferred::PendingDeferred,
     event::{AsyncEvent, ScheduledEvent},
+    history::{History, TagRecord},
     keepalive,
     key_set::KeySetView,
-    store::Store,
-    Duration, Env, Level, ReactionGraph, ReactionKey, ReactionSet, ReactionSetLimits, Tag,
+    lifecycle::{self, EventSource, Lifecycle, TrackedEvent},
+    overload::LagMonitor,
+    params::PendingParameters,
+    probe::ProbeMatcher,
+    shuffle::ShuffleRng,
+    store::{ReactionTriggerCtx, Store},
+    subscription::{EventReceiver, RuntimeEvent, Subscribers},
+    trace::{ExecutionTrace, ReactionSpan, TagTrace},
+    ActionKey, BudgetStats, Duration, Env, EventFilter, Flush, Init, Level, Overload,
+    OverloadConfig, OverloadResponse, Parameter, ParameterChange, Parameters, PortKey, Probe,
+    ProbeSnapshot, ReactionGraph, ReactionKey, ReactionSet, ReactionSetLimits, ReactorFailure,
+    RuntimeError, SchedulerHandle, Tag, TimeScale,
 };
 
+/// The number of recently processed events included in a [`ProbeSnapshot`].
+const PROBE_RECENT_EVENTS: usize = 8;
+
 #[derive(Debug)]
 struct EventQueue {
     /// Current event queue
@@ -18,6 +40,10 @@ struct EventQueue {
     free_reaction_sets: Vec<ReactionSet>,
     /// Limits for the reaction sets
     reaction_set_limits: ReactionSetLimits,
+    /// The earliest tag of a terminal event pushed so far. Events after it can never be processed.
+    horizon: Option<Tag>,
+    /// The number of events discarded for being after the `horizon`
+    discarded: usize,
 }
 
 impl EventQueue {
@@ -26,7 +52,22 @@ impl EventQueue {
             event_queue: BinaryHeap::new(),
             free_reaction_sets: Vec::new(),
             reaction_set_limits,
+            horizon: None,
+            discarded: 0,
+        }
+    }
+
+    /// Whether an event at `tag` is after the earliest terminal event, and so would never be processed.
+    ///
+    /// Such events are discarded instead of pushed, so that the queue doesn't keep growing after a shutdown is
+    /// scheduled. Events already on the queue after the horizon are never walked: the terminal event is popped before
+    /// them, and they are only dropped with the queue.
+    fn beyond_horizon(&mut self, tag: Tag) -> bool {
+        let beyond = self.horizon.is_some_and(|horizon| tag > horizon);
+        if beyond {
+            self.discarded += 1;
         }
+        beyond
     }
 
     /// Push an event into the event queue
@@ -36,16 +77,104 @@ impl EventQueue {
     where
         I: IntoIterator<Item = (Level, ReactionKey)>,
     {
+        if self.beyond_horizon(tag) {
+            return;
+        }
+        if terminal {
+            self.horizon = Some(tag);
+        }
         let mut reaction_set = self.next_reaction_set();
         reaction_set.extend_above(reactions);
         let event = ScheduledEvent {
             tag,
             reactions: reaction_set,
             terminal,
+            action: None,
+            tracked: None,
         };
         self.event_queue.push(event);
     }
 
+    /// Push an event scheduled on `action_key` into the event queue, logging it as enqueued if it's tracked.
+    ///
+    /// Returns `false` if the event was discarded for being after the shutdown.
+    fn push_action_event<I>(
+        &mut self,
+        tag: Tag,
+        action_key: ActionKey,
+        reactions: I,
+        tracked: Option<TrackedEvent>,
+    ) -> bool
+    where
+        I: IntoIterator<Item = (Level, ReactionKey)>,
+    {
+        if self.beyond_horizon(tag) {
+            if let Some(tracked) = tracked {
+                tracing::debug!(
+                    target: lifecycle::TARGET,
+                    event_id = %tracked.id,
+                    tag = %tag,
+                    "discarded"
+                );
+            }
+            return false;
+        }
+        let mut reaction_set = self.next_reaction_set();
+        reaction_set.extend_above(reactions);
+        let event = ScheduledEvent {
+            tag,
+            reactions: reaction_set,
+            terminal: false,
+            action: Some(action_key),
+            tracked,
+        };
+        self.event_queue.push(event);
+        if let Some(tracked) = tracked {
+            tracing::debug!(
+                target: lifecycle::TARGET,
+                event_id = %tracked.id,
+                tag = %tag,
+                queued = self.event_queue.len(),
+                "enqueued"
+            );
+        }
+        true
+    }
+
+    /// Drop the events of the `conflatable` actions at or before `overdue`, except the most recent one of each action,
+    /// returning the action and tag of each dropped event.
+    fn drop_conflatable(
+        &mut self,
+        conflatable: &[ActionKey],
+        overdue: Tag,
+    ) -> Vec<(ActionKey, Tag)> {
+        // The events are sorted in descending order of tag, so the first overdue event of each action is its most
+        // recent one.
+        let events = std::mem::take(&mut self.event_queue).into_sorted_vec();
+        let mut kept_actions = Vec::new();
+        let mut dropped = Vec::new();
+        for event in events {
+            match event.action {
+                Some(action_key)
+                    if event.tag <= overdue
+                        && conflatable.contains(&action_key)
+                        && kept_actions.contains(&action_key) =>
+                {
+                    // Return the ReactionSet to the free pool
+                    self.free_reaction_sets.push(event.reactions);
+                    dropped.push((action_key, event.tag));
+                }
+                action => {
+                    if event.tag <= overdue {
+                        kept_actions.extend(action);
+                    }
+                    self.event_queue.push(event);
+                }
+            }
+        }
+        dropped
+    }
+
     /// Get a free [`ReactionSet`] or create a new one if none are available.
     fn next_reaction_set(&mut self) -> ReactionSet {
         self.free_reaction_sets
@@ -62,7 +191,7 @@ impl EventQueue {
         self.event_queue.peek().map(|event| event.tag)
     }
 
-    /// If the event queue still has events on it, report that.
+    /// If the event queue still has events on it, or events were discarded after the shutdown, report that.
     fn shutdown(&mut self) {
         if !self.event_queue.is_empty() {
             tracing::warn!(
@@ -75,9 +204,21 @@ impl EventQueue {
                 event.tag.offset()
             );
         }
+        if self.discarded > 0 {
+            tracing::warn!(
+                "---- {} events scheduled after the shutdown were discarded.",
+                self.discarded
+            );
+        }
     }
 }
 
+/// The default [`Config::parallel_threshold`].
+pub const DEFAULT_PARALLEL_THRESHOLD: usize = 4;
+
+/// The default [`Config::ingest_interval`].
+pub const DEFAULT_INGEST_INTERVAL: usize = 256;
+
 #[derive(Debug)]
 pub struct Config {
     /// Whether to skip wall-clock synchronization (execute as fast as possible)
@@ -89,6 +230,40 @@ pub struct Config {
     pub physical_event_q_size: usize,
     /// Stop the scheduler after a certain amount of time has passed.
     pub timeout: Option<Duration>,
+    /// The number of worker threads to use for parallel reaction execution.
+    /// If `None`, the default number of threads is used. Ignored without the `parallel` feature.
+    pub workers: Option<usize>,
+    /// Whether to pause and wait for the user when a probe matches, instead of only logging the snapshot.
+    pub interactive: bool,
+    /// Whether to include port values in debug output and trace events, see [`crate::value_fmt`].
+    pub debug_values: bool,
+    /// The number of most recent tags kept in the execution [`History`], or 0 to disable it.
+    pub history_window: usize,
+    /// Whether to record the [`ExecutionTrace`] of every reaction, see [`crate::trace`].
+    pub trace: bool,
+    /// Seed for shuffling the execution order of reactions within each level, for testing the scheduler.
+    pub shuffle_seed: Option<u64>,
+    /// How long to wait for the reactions at the shutdown tag before abandoning them.
+    pub shutdown_grace: Option<Duration>,
+    /// Log the lifecycle of the events on the matching actions and ports, see [`crate::lifecycle`].
+    pub event_filter: Option<EventFilter>,
+    /// Detect and respond to sustained overload, see [`crate::overload`]. Ignored in fast-forward mode.
+    pub overload: Option<OverloadConfig>,
+    /// The maximum number of ready tags processed in one batch, see [`Config::with_batch_tags`].
+    pub batch_tags: Option<usize>,
+    /// Whether to run every reaction in a `tracing` span, see [`Config::with_reaction_spans`].
+    pub reaction_spans: bool,
+    /// The minimum number of reactions at a level to run them on the worker pool, see
+    /// [`Config::with_parallel_threshold`].
+    pub parallel_threshold: usize,
+    /// The rate of logical relative to physical time in real-time execution, see [`Config::with_time_scale`].
+    pub time_scale: TimeScale,
+    /// The number of reactions run at a level between polls of the asynchronous event channel, see
+    /// [`Config::with_ingest_interval`].
+    pub ingest_interval: usize,
+    /// Measure the execution time of 1 of every `budget_sampling` invocations of the reactions with a budget, see
+    /// [`crate::budget`].
+    pub budget_sampling: usize,
 }
 
 impl Default for Config {
@@ -98,6 +273,25 @@ impl Default for Config {
             keep_alive: false,
             physical_event_q_size: 1024,
             timeout: None,
+            workers: None,
+            interactive: false,
+            debug_values: false,
+            history_window: 0,
+            trace: false,
+            shuffle_seed: None,
+            shutdown_grace: None,
+            event_filter: None,
+            overload: None,
+            batch_tags: None,
+            reaction_spans: false,
+            parallel_threshold: DEFAULT_PARALLEL_THRESHOLD,
+            time_scale: TimeScale::REAL_TIME,
+            ingest_interval: DEFAULT_INGEST_INTERVAL,
+            budget_sampling: if cfg!(debug_assertions) {
+                1
+            } else {
+                DEFAULT_BUDGET_SAMPLING
+            },
         }
     }
 }
@@ -115,7 +309,8 @@ impl Config {
 
     /// Set the capacity of the physical event queue.
     ///
-    /// If the queue is full, this call will block until there is space available.
+    /// If the queue is full, scheduling a physical action blocks until there is space available, or drops or coalesces
+    /// the event, depending on the policy of the action, see [`crate::backpressure`].
     pub fn with_queue_size(mut self, physical_event_q_size: usize) -> Self {
         self.physical_event_q_size = physical_event_q_size;
         self
@@ -127,6 +322,150 @@ impl Config {
         self.timeout = Some(timeout);
         self
     }
+
+    /// Set the number of worker threads used for parallel reaction execution.
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = Some(workers);
+        self
+    }
+
+    /// Pause the scheduler on stdin when a probe matches.
+    pub fn with_interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// Include port values in debug output and trace events.
+    ///
+    /// This has a runtime cost and is best left disabled in production.
+    pub fn with_debug_values(mut self, debug_values: bool) -> Self {
+        self.debug_values = debug_values;
+        self
+    }
+
+    /// Keep the execution [`History`] of the most recent `window` tags, see [`crate::history`].
+    ///
+    /// The history records the values of the ports and states whose types are registered in [`crate::value_fmt`].
+    pub fn with_history_window(mut self, window: usize) -> Self {
+        self.history_window = window;
+        self
+    }
+
+    /// Record the wall-clock [`ExecutionTrace`] of every reaction for [`crate::trace::analyze`].
+    ///
+    /// The trace is kept for the whole run, so this is best left disabled in production.
+    pub fn with_trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Run every reaction in a `reaction` span, with the fully-qualified name of the reaction as its `fqn` field.
+    ///
+    /// The events logged by a reaction can then be attributed to its reactor, e.g. to filter them by reactor subtree.
+    /// The spans are created when the scheduler is, so a global subscriber must already be set by then.
+    pub fn with_reaction_spans(mut self, reaction_spans: bool) -> Self {
+        self.reaction_spans = reaction_spans;
+        self
+    }
+
+    /// Shuffle the reactions within each level with a PRNG seeded by `seed`, for testing the scheduler itself.
+    ///
+    /// Reactions at the same level are independent, so any order must give the same results. In this mode the
+    /// execution order of each level is shuffled, reproducibly from the seed, so a seed that exposes a scheduler bug
+    /// can be replayed, e.g. with [`crate::overrides::ENV_SHUFFLE_SEED`]. With the `parallel` feature every level is
+    /// handed to the worker pool in the shuffled order, but which worker runs which reaction is still decided by work
+    /// stealing, and the interleaving of the workers by the OS.
+    pub fn with_shuffle_seed(mut self, seed: u64) -> Self {
+        self.shuffle_seed = Some(seed);
+        self
+    }
+
+    /// Abandon the reactions at the shutdown tag if they haven't completed after `grace`.
+    ///
+    /// The reactions at the shutdown tag then run one at a time on a separate thread, and if a reaction hangs, e.g.
+    /// blocked on a socket, [`Scheduler::event_loop`] logs the reactions still running and returns
+    /// [`RuntimeError::ShutdownTimeout`] instead of waiting forever. The abandoned thread keeps the state of all
+    /// reactors and runs the flushes once the reactions complete, and [`Scheduler::into_env`] waits for it.
+    pub fn with_shutdown_grace(mut self, grace: Duration) -> Self {
+        self.shutdown_grace = Some(grace);
+        self
+    }
+
+    /// Log the lifecycle of the events on the actions and ports matching `filter`, see [`crate::lifecycle`].
+    pub fn with_event_filter(mut self, filter: EventFilter) -> Self {
+        self.event_filter = Some(filter);
+        self
+    }
+
+    /// Respond to logical time falling behind physical time as configured in `overload`, see [`crate::overload`].
+    pub fn with_overload(mut self, overload: OverloadConfig) -> Self {
+        self.overload = Some(overload);
+        self
+    }
+
+    /// Process up to `batch_tags` ready tags back-to-back in fast-forward mode ("afterburner" mode).
+    ///
+    /// In long fast-forward simulations the per-tag overhead of the event loop, polling the asynchronous event channel
+    /// and cleaning up every port, dominates the run time of cheap reactions. In a batch, the tags are popped off the
+    /// queue without polling the channel in between, and only the effect ports of the reactions that ran are cleaned
+    /// up. A batch only contains tags that are independent of physical time: it ends before the shutdown tag and as
+    /// soon as an asynchronous event is pending. Batching is ignored in real-time mode, and while probes or an
+    /// [`Config::event_filter`] are set, as those inspect every event.
+    pub fn with_batch_tags(mut self, batch_tags: usize) -> Self {
+        self.batch_tags = Some(batch_tags);
+        self
+    }
+
+    /// Only run the reactions at a level on the worker pool if there are at least `parallel_threshold` of them.
+    ///
+    /// Most levels are narrow most of the time, and handing one or two reactions to the pool costs more in
+    /// synchronization than running them in parallel gains, unless they are expensive. Narrower levels are run inline
+    /// on the scheduler thread. Set this to 2 to fan out every level with more than one reaction, or to `usize::MAX`
+    /// to never use the pool. Ignored without the `parallel` feature, and while shuffling with
+    /// [`Config::with_shuffle_seed`].
+    pub fn with_parallel_threshold(mut self, parallel_threshold: usize) -> Self {
+        self.parallel_threshold = parallel_threshold;
+        self
+    }
+
+    /// Run logical time `scale` times as fast as the wall clock, e.g. `10.0` to simulate 10 seconds per second, or
+    /// `0.5` to slow execution down to half speed.
+    ///
+    /// The scheduler waits for the scaled physical time before processing each tag, and physical actions are tagged
+    /// with the scaled physical time elapsed since the start, so timers, physical actions and deadlines stay consistent
+    /// with each other. The scale is approximated by an exact ratio, see [`TimeScale::from_f64`]; set
+    /// [`Config::time_scale`] directly for an exact [`TimeScale::new`]. Ignored in fast-forward mode.
+    ///
+    /// # Panics
+    ///
+    /// If `scale` is not finite and strictly positive.
+    pub fn with_time_scale(mut self, scale: f64) -> Self {
+        self.time_scale = TimeScale::from_f64(scale)
+            .expect("The time scale must be finite and strictly positive");
+        self
+    }
+
+    /// Poll the asynchronous event channel after every `ingest_interval` reactions within a level.
+    ///
+    /// A level with thousands of reactions would otherwise hold off the asynchronous events until it completes, and
+    /// once the channel is full (see [`Config::with_queue_size`]), the threads scheduling physical actions block. The
+    /// events received during a level are only queued, as between tags, so they are tagged relative to the current
+    /// tag and never run before it completes. With the `parallel` feature, the levels on the worker pool are run in
+    /// chunks of `ingest_interval` reactions. Set this to `usize::MAX` to only poll the channel between tags.
+    pub fn with_ingest_interval(mut self, ingest_interval: usize) -> Self {
+        self.ingest_interval = ingest_interval;
+        self
+    }
+
+    /// Measure the execution time of 1 of every `budget_sampling` invocations of the reactions with a budget, see
+    /// [`crate::budget`].
+    ///
+    /// By default every invocation is measured in debug builds, and 1 of [`DEFAULT_BUDGET_SAMPLING`] in release builds.
+    /// A value of 0 is treated as 1.
+    pub fn with_budget_sampling(mut self, budget_sampling: usize) -> Self {
+        self.budget_sampling = budget_sampling;
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -136,9 +475,11 @@ pub struct Scheduler {
     /// The reactor runtime store
     store: Pin<Box<Store>>,
     /// The reaction graph containing all static dependency and relationship information
-    reaction_graph: ReactionGraph,
+    reaction_graph: Arc<ReactionGraph>,
     /// Asynchronous events receiver
     event_rx: Receiver<AsyncEvent>,
+    /// Asynchronous events sender, for the [`SchedulerHandle`]s
+    event_tx: Sender<AsyncEvent>,
     /// Event queue
     events: EventQueue,
     /// Initial wall-clock time.
@@ -147,6 +488,44 @@ pub struct Scheduler {
     shutdown_tag: Option<Tag>,
     /// Shutdown channel
     shutdown_tx: keepalive::Sender,
+    /// Cancels the running reactions once the shutdown tag is reached, see [`crate::cancel`]
+    cancellation: CancellationToken,
+    /// Value probes
+    probes: Vec<Probe>,
+    /// Initializations run before logical time begins
+    inits: Vec<Init>,
+    /// Flushes run after the shutdown tag
+    flushes: Vec<Flush>,
+    /// Snapshots of all probe matches so far
+    probe_hits: Vec<ProbeSnapshot>,
+    /// All panics caught in reactions so far
+    failures: Vec<ReactorFailure>,
+    /// The most recently processed events, only recorded if there are probes.
+    recent_events: VecDeque<String>,
+    /// Records of the most recently processed tags
+    history: History,
+    /// Execution trace of all reactions, only recorded if enabled in the config
+    trace: ExecutionTrace,
+    /// Drives the order of reactions within a level, only in the shuffled testing mode
+    shuffle_rng: Option<ShuffleRng>,
+    /// Returns the store from the thread running the shutdown tag, once abandoned after the shutdown grace period
+    abandoned: Option<Receiver<Pin<Box<Store>>>>,
+    /// Tracks the events whose lifecycle is logged, only with an event filter in the config
+    lifecycle: Option<Lifecycle>,
+    /// Measures the lag behind physical time, only with an overload config
+    lag_monitor: Option<LagMonitor>,
+    /// All overloads detected so far
+    overloads: Vec<Overload>,
+    /// Subscriptions to the runtime events
+    subscribers: Subscribers,
+    /// The deferred computations still running
+    deferred: PendingDeferred,
+    /// The ports set at the current tag, the only ones to reset after it
+    set_ports: tinymap::TinyBitSet<PortKey>,
+    /// The current global parameters, see [`crate::params`]
+    parameters: Parameters,
+    /// The parameter updates waiting for their tag
+    pending_parameters: PendingParameters,
 }
 
 impl Scheduler {
@@ -158,34 +537,94 @@ impl Scheduler {
     ///
     /// * `env` - The environment containing all the runtime data structures.
     /// * `reaction_graph` - The reaction graph containing all static dependency and relationship information.
-    pub fn new(env: Env, reaction_graph: ReactionGraph, config: Config) -> Self {
+    pub fn new(mut env: Env, reaction_graph: ReactionGraph, config: Config) -> Self {
         let (event_tx, event_rx) = crossbeam_channel::bounded(config.physical_event_q_size);
         let (shutdown_tx, shutdown_rx) = keepalive::channel();
         let start_time = std::time::Instant::now();
 
+        let cancellation = CancellationToken::new(start_time, !config.fast_forward);
         if let Some(timeout) = config.timeout {
             let shutdown_tag = Tag::new(timeout, 0);
+            cancellation.schedule_shutdown(config.time_scale.instant_of(start_time, shutdown_tag));
             let shutdown_event = AsyncEvent::Shutdown { tag: shutdown_tag };
             event_tx.send(shutdown_event).unwrap();
         }
 
         // Build contexts for each reaction
-        let contexts = build_reaction_contexts(&reaction_graph, start_time, event_tx, shutdown_rx);
+        let contexts = build_reaction_contexts(
+            &reaction_graph,
+            start_time,
+            config.time_scale,
+            event_tx.clone(),
+            shutdown_rx,
+            &cancellation,
+            &env.parameters,
+            config.reaction_spans,
+        );
+
+        let history = History::new(config.history_window);
+        let shuffle_rng = config.shuffle_seed.map(|seed| {
+            tracing::info!(seed, "Shuffling the reactions within each level.");
+            ShuffleRng::new(seed)
+        });
+
+        let lifecycle = config.event_filter.clone().map(|filter| {
+            tracing::info!(filter = %filter, "Logging the lifecycle of matching events.");
+            Lifecycle::new(filter)
+        });
+
+        let lag_monitor = config.overload.map(LagMonitor::new);
+
+        for budget in env.reactions.values_mut().filter_map(|r| r.budget.as_mut()) {
+            budget.sampling = config.budget_sampling.max(1);
+        }
 
-        let store = Store::new(env, contexts, &reaction_graph);
+        let parameters = env.parameters.clone();
+        let probes = std::mem::take(&mut env.probes);
+        let inits = std::mem::take(&mut env.inits);
+        let flushes = std::mem::take(&mut env.flushes);
+        let mut store = Store::new(env, contexts, &reaction_graph);
+        store.set_debug_values(config.debug_values);
         let events = EventQueue::new(reaction_graph.reaction_set_limits.clone());
+        let set_ports = tinymap::TinyBitSet::with_capacity(reaction_graph.port_triggers.len());
         Self {
             config,
             store,
-            reaction_graph,
+            reaction_graph: Arc::new(reaction_graph),
             event_rx,
+            event_tx,
             events,
             start_time,
             shutdown_tag: None,
             shutdown_tx,
+            cancellation,
+            probes,
+            inits,
+            flushes,
+            probe_hits: Vec::new(),
+            failures: Vec::new(),
+            recent_events: VecDeque::with_capacity(PROBE_RECENT_EVENTS),
+            history,
+            trace: ExecutionTrace::default(),
+            shuffle_rng,
+            abandoned: None,
+            lifecycle,
+            lag_monitor,
+            overloads: Vec::new(),
+            subscribers: Subscribers::default(),
+            deferred: PendingDeferred::default(),
+            set_ports,
+            parameters,
+            pending_parameters: PendingParameters::default(),
         }
     }
 
+    /// A handle to control the scheduler from other threads while its event loop runs, e.g. to update parameters, see
+    /// [`crate::params`].
+    pub fn handle(&self) -> SchedulerHandle {
+        SchedulerHandle::new(self.event_tx.clone(), self.parameters.clone())
+    }
+
     /// Handle an asynchronous event from the event queue
     fn handle_async_event(
         event: AsyncEvent,
@@ -193,35 +632,148 @@ impl Scheduler {
         events: &mut EventQueue,
         store: &mut Pin<Box<Store>>,
         reaction_graph: &ReactionGraph,
+        state: &mut AsyncEventState,
     ) {
         let reactions = event.downstream_reactions(reaction_graph);
         match event {
-            AsyncEvent::Logical { delay, key, value } => {
-                let tag = tag.delay(delay);
-                events.push_event(tag, reactions, false);
-                store.push_action_value(key, tag, value);
+            AsyncEvent::Logical {
+                delay,
+                microsteps,
+                key,
+                value,
+            } => {
+                let current = tag;
+                let tag = tag.delay(delay).delay_microsteps(microsteps);
+                if tag <= current {
+                    let err = RuntimeError::TagNotInFuture {
+                        requested: tag,
+                        current,
+                    };
+                    tracing::error!(?key, "Dropped async logical action event: {err}");
+                    return;
+                }
+                let tracked = track_scheduled(
+                    state.lifecycle.as_deref_mut(),
+                    reaction_graph,
+                    EventSource::Action(key),
+                    "async",
+                    tag,
+                );
+                if events.push_action_event(tag, key, reactions, tracked) {
+                    store.push_action_value(key, tag, value);
+                }
             }
-            AsyncEvent::Physical { tag, key, value } => {
-                events.push_event(tag, reactions, false);
-                store.push_action_value(key, tag, value);
+            AsyncEvent::Physical {
+                tag: stamped,
+                key,
+                value,
+            } => {
+                let tag = if stamped <= tag {
+                    tracing::debug!(
+                        ?key,
+                        %stamped,
+                        "Async physical action event is behind logical time, moving it to the next microstep"
+                    );
+                    tag.delay(Duration::ZERO)
+                } else {
+                    stamped
+                };
+                let tracked = track_scheduled(
+                    state.lifecycle.as_deref_mut(),
+                    reaction_graph,
+                    EventSource::Action(key),
+                    "async",
+                    tag,
+                );
+                if events.push_action_event(tag, key, reactions, tracked) {
+                    store.push_action_value(key, tag, value);
+                }
+            }
+            AsyncEvent::Absolute {
+                tag: requested,
+                key,
+                value,
+            } => {
+                if requested <= tag {
+                    let err = RuntimeError::TagNotInFuture {
+                        requested,
+                        current: tag,
+                    };
+                    tracing::error!(?key, "Dropped async action event at an absolute tag: {err}");
+                    return;
+                }
+                let tracked = track_scheduled(
+                    state.lifecycle.as_deref_mut(),
+                    reaction_graph,
+                    EventSource::Action(key),
+                    "async",
+                    requested,
+                );
+                if events.push_action_event(requested, key, reactions, tracked) {
+                    store.push_action_value(key, requested, value);
+                }
+            }
+            AsyncEvent::Deferred { tag, key, value } => {
+                state.deferred.completed(tag);
+                if let Some(value) = value {
+                    let tracked = track_scheduled(
+                        state.lifecycle.as_deref_mut(),
+                        reaction_graph,
+                        EventSource::Action(key),
+                        "deferred",
+                        tag,
+                    );
+                    if events.push_action_event(tag, key, reactions, tracked) {
+                        store.push_action_value(key, tag, value);
+                    }
+                }
             }
             AsyncEvent::Shutdown { tag } => {
                 events.push_event(tag, reactions, true);
                 //self.shutdown_tag = Some(tag);
             }
+            AsyncEvent::Coalesced { pending } => {
+                let pending = pending.lock().unwrap().take();
+                if let Some(event) = pending {
+                    Self::handle_async_event(event, tag, events, store, reaction_graph, state);
+                }
+            }
+            AsyncEvent::Parameter { name, value } => {
+                tracing::debug!(name, "Received a parameter update.");
+                if let Some(tag) = state.pending_parameters.push(tag, name, value) {
+                    let reactions = reaction_graph
+                        .parameter_actions
+                        .iter()
+                        .flat_map(|&key| reaction_graph.action_triggers[key].iter().copied());
+                    events.push_event(tag, reactions, false);
+                }
+            }
         }
     }
 
     /// Execute startup of the Scheduler.
     #[tracing::instrument(skip(self))]
-    fn startup(&mut self) -> Tag {
-        //#[cfg(feature = "parallel")]
-        //rayon::ThreadPoolBuilder::new()
-        //    .num_threads(4)
-        //    .build_global()
-        //    .unwrap();
+    fn startup(&mut self) -> Result<Tag, RuntimeError> {
+        #[cfg(feature = "parallel")]
+        if let Some(workers) = self.config.workers {
+            if let Err(err) = rayon::ThreadPoolBuilder::new()
+                .num_threads(workers)
+                .build_global()
+            {
+                tracing::warn!("Unable to configure {workers} worker threads: {err}");
+            }
+        }
+
+        if !self.inits.is_empty() {
+            tracing::info!(count = self.inits.len(), "Running the initializations.");
+            for (reactor_key, apply) in crate::init::run(std::mem::take(&mut self.inits))? {
+                apply(self.store.get_reactor_mut(reactor_key));
+            }
+        }
 
+        // Logical time begins after the initializations
         self.start_time = std::time::Instant::now();
+        self.store.set_start_time(self.start_time);
 
         let tag = Tag::new(Duration::ZERO, 0);
 
@@ -229,10 +781,13 @@ impl Scheduler {
         let mut reaction_set = self.events.next_reaction_set();
         reaction_set.extend_above(self.reaction_graph.startup_reactions.iter().copied());
 
+        if self.config.time_scale != TimeScale::REAL_TIME && !self.config.fast_forward {
+            tracing::info!(time_scale = %self.config.time_scale, "Scaling logical time.");
+        }
         tracing::info!(tag = %tag, "Starting the execution.");
         self.process_tag(tag, reaction_set.view());
 
-        tag
+        Ok(tag)
     }
 
     /// Final shutdown of the Scheduler. The last tag has already been processed.
@@ -259,7 +814,7 @@ impl Scheduler {
     #[tracing::instrument(skip(self))]
     fn receive_event(&mut self) -> Option<AsyncEvent> {
         if let Some(shutdown) = self.shutdown_tag {
-            let abs = shutdown.to_logical_time(self.start_time);
+            let abs = self.config.time_scale.instant_of(self.start_time, shutdown);
             if let Some(timeout) = abs.checked_duration_since(std::time::Instant::now()) {
                 tracing::debug!(timeout = ?timeout, "Waiting for async event.");
                 self.event_rx.recv_timeout(timeout).ok()
@@ -275,9 +830,14 @@ impl Scheduler {
         }
     }
 
+    /// Run the event loop until shutdown.
+    ///
+    /// This only fails if an [`Init`] fails or times out before logical time begins, see [`crate::init`], if the
+    /// reactions at the shutdown tag don't complete within the [`Config::shutdown_grace`], or if a [`Flush`] fails after
+    /// the shutdown tag, see [`crate::flush`].
     #[tracing::instrument(skip(self))]
-    pub fn event_loop(&mut self) {
-        let mut current_tag = self.startup();
+    pub fn event_loop(&mut self) -> Result<(), RuntimeError> {
+        let mut current_tag = self.startup()?;
 
         loop {
             // Push pending events into the queue
@@ -288,21 +848,79 @@ impl Scheduler {
                     &mut self.events,
                     &mut self.store,
                     &self.reaction_graph,
+                    &mut AsyncEventState {
+                        lifecycle: self.lifecycle.as_mut(),
+                        deferred: &mut self.deferred,
+                        pending_parameters: &mut self.pending_parameters,
+                    },
                 );
             }
 
+            // Wait for the deferred computations whose results are due before the next event
+            let waiting = match self.events.peek_tag() {
+                Some(next_tag) => self.deferred.blocks(next_tag),
+                None => self.deferred.first().is_some(),
+            };
+            if waiting {
+                tracing::debug!(tag = ?self.deferred.first(), "Waiting for a deferred computation.");
+                if let Ok(async_event) = self.event_rx.recv() {
+                    Self::handle_async_event(
+                        async_event,
+                        current_tag,
+                        &mut self.events,
+                        &mut self.store,
+                        &self.reaction_graph,
+                        &mut AsyncEventState {
+                            lifecycle: self.lifecycle.as_mut(),
+                            deferred: &mut self.deferred,
+                            pending_parameters: &mut self.pending_parameters,
+                        },
+                    );
+                }
+                continue;
+            }
+
             if let Some(next_tag) = self.events.peek_tag() {
                 if !self.config.fast_forward {
-                    let target = next_tag.to_logical_time(self.start_time);
+                    let target = self.config.time_scale.instant_of(self.start_time, next_tag);
                     if self.synchronize_wall_clock(target, current_tag) {
                         // Woken up by async event
                         continue;
                     }
+                    self.monitor_lag(next_tag, target);
+                }
+            }
+
+            if let Some(limit) = self.batch_limit() {
+                if let Some(tag) = self.process_batch(limit) {
+                    current_tag = tag;
+                    continue;
                 }
             }
 
             if let Some(mut event) = self.events.event_queue.pop() {
                 tracing::debug!(event = %event, "Handling event");
+                if let Some(tracked) = event.tracked {
+                    tracing::debug!(
+                        target: lifecycle::TARGET,
+                        event_id = %tracked.id,
+                        tag = %event.tag,
+                        queued = self.events.event_queue.len(),
+                        "dequeued"
+                    );
+                }
+
+                if !self.probes.is_empty() {
+                    if self.recent_events.len() == PROBE_RECENT_EVENTS {
+                        self.recent_events.pop_front();
+                    }
+                    self.recent_events.push_back(event.to_string());
+                }
+
+                if event.terminal {
+                    self.cancellation.reach_shutdown();
+                    self.publish(|| RuntimeEvent::ShutdownInitiated { tag: event.tag });
+                }
 
                 if Some(event.tag) == self.events.peek_tag() {
                     // The next event is at the same time as the one we are processing
@@ -319,10 +937,30 @@ impl Scheduler {
                     tracing::warn!("Next event is at the same time as the one we are processing");
                 }
 
-                self.process_tag(event.tag, event.reactions.view());
+                let reactions = match self.config.shutdown_grace {
+                    Some(grace) if event.terminal => {
+                        match self.process_shutdown_tag(event.tag, event.reactions, grace) {
+                            Ok(reactions) => reactions,
+                            Err(err) => {
+                                // The abandoned thread runs the flushes once the reactions complete.
+                                self.shutdown_tag = Some(event.tag);
+                                self.shutdown();
+                                return Err(err);
+                            }
+                        }
+                    }
+                    _ => {
+                        self.process_tag(event.tag, event.reactions.view());
+                        event.reactions
+                    }
+                };
+
+                if let Some(tracked) = event.tracked {
+                    self.log_completed(tracked, event.tag);
+                }
 
                 // Return the ReactionSet to the free pool
-                self.events.free_reaction_sets.push(event.reactions);
+                self.events.free_reaction_sets.push(reactions);
 
                 current_tag = event.tag;
 
@@ -338,6 +976,11 @@ impl Scheduler {
                     &mut self.events,
                     &mut self.store,
                     &self.reaction_graph,
+                    &mut AsyncEventState {
+                        lifecycle: self.lifecycle.as_mut(),
+                        deferred: &mut self.deferred,
+                        pending_parameters: &mut self.pending_parameters,
+                    },
                 );
             } else {
                 tracing::debug!("No more events in queue. -> Terminate!");
@@ -352,7 +995,142 @@ impl Scheduler {
             }
         } // loop
 
+        let flushed = self.flush();
         self.shutdown();
+        flushed
+    }
+
+    /// Run the flushes after the shutdown tag, see [`crate::flush`].
+    fn flush(&mut self) -> Result<(), RuntimeError> {
+        if self.flushes.is_empty() {
+            return Ok(());
+        }
+        tracing::info!(count = self.flushes.len(), "Running the flushes.");
+        crate::flush::run(std::mem::take(&mut self.flushes), &mut self.store)
+    }
+
+    /// The maximum number of tags to process in a batch, if batching applies to this run.
+    fn batch_limit(&self) -> Option<usize> {
+        self.config
+            .batch_tags
+            .filter(|&limit| limit > 1 && self.config.fast_forward)
+            .filter(|_| self.probes.is_empty() && self.lifecycle.is_none())
+    }
+
+    /// Process up to `limit` ready tags back-to-back, returning the last tag processed, if any.
+    ///
+    /// The batch ends before a terminal event or a tag still waiting for a deferred computation, and as soon as an
+    /// asynchronous event is pending, since that must be queued before any later tags are processed.
+    fn process_batch(&mut self, limit: usize) -> Option<Tag> {
+        let mut last_tag = None;
+        for _ in 0..limit {
+            if !self.event_rx.is_empty()
+                || self
+                    .events
+                    .event_queue
+                    .peek()
+                    .is_none_or(|event| event.terminal || self.deferred.blocks(event.tag))
+            {
+                break;
+            }
+            let Some(mut event) = self.events.event_queue.pop() else {
+                break;
+            };
+            self.execute_tag(event.tag, event.reactions.view());
+            self.store.reset_ports_of(self.set_ports.iter());
+
+            self.events.free_reaction_sets.push(event.reactions);
+            last_tag = Some(event.tag);
+        }
+        last_tag
+    }
+
+    /// Process the shutdown `tag` on a separate thread, abandoning it if it doesn't complete within `grace`.
+    ///
+    /// Only the store and the flushes are moved to the thread, and returned once the reactions complete. If the
+    /// thread is abandoned, it runs the flushes itself and then returns the store through [`Self::abandoned`].
+    fn process_shutdown_tag(
+        &mut self,
+        tag: Tag,
+        reactions: ReactionSet,
+        grace: Duration,
+    ) -> Result<ReactionSet, RuntimeError> {
+        let updates = self.pending_parameters.take_due(tag);
+        if !updates.is_empty() {
+            self.update_parameters(tag, updates);
+        }
+        self.publish(|| RuntimeEvent::TagStarted { tag });
+
+        let empty = Store::new(
+            Env::default(),
+            Default::default(),
+            &ReactionGraph::default(),
+        );
+        let mut pass = ShutdownPass {
+            store: std::mem::replace(&mut self.store, empty),
+            flushes: std::mem::take(&mut self.flushes),
+            reactions,
+            executed: Vec::new(),
+            spans: Vec::new(),
+            failures: Vec::new(),
+        };
+        let reaction_graph = self.reaction_graph.clone();
+        let running = Arc::new(Mutex::new(Vec::new()));
+        let timing = self.config.trace.then(std::time::Instant::now);
+
+        // The rendezvous channel fails to send once the receiver gives up, so the thread knows it was abandoned.
+        let (done_tx, done_rx) = crossbeam_channel::bounded::<ShutdownPass>(0);
+        let (abandoned_tx, abandoned_rx) = crossbeam_channel::bounded(1);
+        let thread_running = running.clone();
+        std::thread::Builder::new()
+            .name("boomerang-shutdown".into())
+            .spawn(move || {
+                pass.run(tag, &reaction_graph, &thread_running, timing);
+                if let Err(crossbeam_channel::SendError(mut pass)) = done_tx.send(pass) {
+                    if !pass.flushes.is_empty() {
+                        let flushes = std::mem::take(&mut pass.flushes);
+                        if let Err(err) = crate::flush::run(flushes, &mut pass.store) {
+                            tracing::error!("Flush after the abandoned shutdown failed: {err}");
+                        }
+                    }
+                    let _ = abandoned_tx.send(pass.store);
+                }
+            })
+            .expect("Failed to spawn the shutdown thread");
+
+        match done_rx.recv_timeout(grace.try_into().unwrap_or_default()) {
+            Ok(pass) => {
+                self.store = pass.store;
+                self.flushes = pass.flushes;
+                if self.history.is_enabled() && !pass.executed.is_empty() {
+                    self.record_history(tag, &pass.executed);
+                }
+                if !pass.spans.is_empty() {
+                    self.trace.tags.push(TagTrace {
+                        tag,
+                        spans: pass.spans,
+                    });
+                }
+                if !pass.failures.is_empty() {
+                    self.schedule_failures(tag, pass.failures);
+                }
+                self.publish(|| RuntimeEvent::TagCompleted {
+                    tag,
+                    reactions: pass.executed.len(),
+                });
+                Ok(pass.reactions)
+            }
+            Err(_) => {
+                drop(done_rx);
+                let pending = running.lock().unwrap().clone();
+                tracing::error!(
+                    "Shutdown did not complete within {grace}, abandoning the reactions still running: {}",
+                    pending.join(", ")
+                );
+                self.abandoned = Some(abandoned_rx);
+                Err(RuntimeError::ShutdownTimeout { grace, pending })
+            }
+        }
     }
 
     // Wait until the wall-clock time is reached
@@ -372,6 +1150,11 @@ impl Scheduler {
                         &mut self.events,
                         &mut self.store,
                         &self.reaction_graph,
+                        &mut AsyncEventState {
+                            lifecycle: self.lifecycle.as_mut(),
+                            deferred: &mut self.deferred,
+                            pending_parameters: &mut self.pending_parameters,
+                        },
                     );
                     return true;
                 }
@@ -396,34 +1179,233 @@ impl Scheduler {
         false
     }
 
+    /// Measure how far `next_tag` lags behind physical time, and respond to sustained overload, see
+    /// [`crate::overload`].
+    fn monitor_lag(&mut self, next_tag: Tag, target: std::time::Instant) {
+        let Some(monitor) = self.lag_monitor.as_mut() else {
+            return;
+        };
+        let now = std::time::Instant::now();
+        let lag =
+            Duration::try_from(now.saturating_duration_since(target)).unwrap_or(Duration::MAX);
+        let Some(late_events) = monitor.observe(next_tag, lag) else {
+            return;
+        };
+        let response = monitor.response();
+
+        let dropped = if response == OverloadResponse::DropConflatable {
+            let overdue = self.config.time_scale.tag_at(self.start_time, now);
+            let dropped = self
+                .events
+                .drop_conflatable(&self.reaction_graph.conflatable_actions, overdue);
+            // The values of the dropped events would otherwise stay in the action stores until a later event pops them
+            for &(action_key, tag) in &dropped {
+                self.store.get_action_mut(action_key).remove_value(tag);
+            }
+            dropped.len()
+        } else {
+            0
+        };
+        let overload = Overload {
+            tag: next_tag,
+            lag,
+            late_events,
+            dropped,
+        };
+        tracing::warn!(response = %response, "{overload}");
+
+        if response == OverloadResponse::Action {
+            let overload_tag = next_tag.delay(Duration::ZERO);
+            for &action_key in &self.reaction_graph.overload_actions {
+                self.store
+                    .push_action_value(action_key, overload_tag, Box::new(overload.clone()));
+                let downstream = self.reaction_graph.action_triggers[action_key]
+                    .iter()
+                    .copied();
+                let tracked = track_scheduled(
+                    self.lifecycle.as_mut(),
+                    &self.reaction_graph,
+                    EventSource::Action(action_key),
+                    "overload",
+                    overload_tag,
+                );
+                self.events
+                    .push_action_event(overload_tag, action_key, downstream, tracked);
+            }
+        }
+        self.overloads.push(overload);
+    }
+
+    /// Apply the parameter updates due at `tag`, and schedule the parameter change actions at it, see
+    /// [`crate::params`].
+    fn update_parameters(&mut self, tag: Tag, updates: Vec<(String, Parameter)>) {
+        let mut names = Vec::<String>::new();
+        for (name, _) in &updates {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        tracing::info!(%tag, ?names, "Updating parameters.");
+        self.parameters = self.parameters.updated(updates);
+        self.store.set_parameters(&self.parameters);
+
+        let change = ParameterChange { names };
+        for &action_key in &self.reaction_graph.parameter_actions {
+            self.store
+                .push_action_value(action_key, tag, Box::new(change.clone()));
+        }
+    }
+
     /// Process the reactions at this tag in increasing order of level.
     ///
-    /// Reactions at a level N may trigger further reactions at levels M>N
-    #[tracing::instrument(skip(self, reaction_view), fields(tag = %tag))]
+    /// Reactions at a level N may trigger further reactions at levels M>N. Only the ports set at the tag are reset after
+    /// it.
     pub fn process_tag(&mut self, tag: Tag, reaction_view: KeySetView<ReactionKey>) {
+        self.execute_tag(tag, reaction_view);
+        self.store.reset_ports_of(self.set_ports.iter());
+    }
+
+    /// Run the reactions in `reaction_view` at `tag`, leaving the ports set for the caller to clean up.
+    #[tracing::instrument(skip(self, reaction_view), fields(tag = %tag))]
+    fn execute_tag(&mut self, tag: Tag, reaction_view: KeySetView<ReactionKey>) {
+        let updates = self.pending_parameters.take_due(tag);
+        if !updates.is_empty() {
+            self.update_parameters(tag, updates);
+        }
+        let probing = !self.probes.is_empty();
+        if probing {
+            self.check_action_probes(tag);
+        }
+        let tracing = self.config.trace;
+        // Shared with the closures running the reactions, while the scheduler is borrowed mutably
+        let reaction_graph = Arc::clone(&self.reaction_graph);
+        let reaction_graph = &*reaction_graph;
+        // Reactions run at this tag, only recorded for the history.
+        let mut executed_at_tag = Vec::new();
+        // Spans of the reactions run at this tag, only recorded if tracing.
+        let tag_start = std::time::Instant::now();
+        let mut spans = Vec::new();
+        // Panics caught at this tag
+        let mut failures = Vec::new();
+        // Port events whose lifecycle is logged, completed at the end of this tag
+        let mut tracked_ports: Vec<TrackedEvent> = Vec::new();
+        // The number of reactions run at this tag, only counted for the subscribers
+        let mut reactions_run = 0;
+        let ingest_interval = self.config.ingest_interval.max(1);
+        let mut set_ports = std::mem::take(&mut self.set_ports);
+        set_ports.clear();
+        self.publish(|| RuntimeEvent::TagStarted { tag });
+
         reaction_view.for_each_level(|level, reaction_keys, next_levels| {
             tracing::trace!(level=?level, "Iter");
 
+            // Reactions run at this level, whose effect ports may trigger further reactions.
+            let mut executed = Vec::new();
+            let reaction_keys = reaction_keys.inspect(|&key| executed.push(key));
+
             // Safety: reaction_keys in the same level are guaranteed to be independent of each other.
             let iter_ctx = unsafe { self.store.iter_borrow_storage(reaction_keys) };
 
-            #[cfg(feature = "parallel")]
-            use rayon::prelude::ParallelIterator;
+            let timing = tracing.then_some((tag_start, level));
 
-            #[cfg(feature = "parallel")]
-            let iter_ctx = rayon::prelude::ParallelBridge::par_bridge(iter_ctx);
+            // Asynchronous events received while running this level, queued once it completes.
+            let mut ingested = Vec::new();
 
-            let iter_ctx_res = iter_ctx.map(|trigger_ctx| trigger_ctx.trigger(tag));
+            #[cfg(not(feature = "parallel"))]
+            let iter_ctx_res =
+                match self.shuffle_rng.as_mut() {
+                    Some(rng) => {
+                        let mut trigger_ctxs = iter_ctx.collect::<Vec<_>>();
+                        rng.shuffle(&mut trigger_ctxs);
+                        itertools::Either::Left(trigger_ctxs.into_iter().map(|trigger_ctx| {
+                            trigger_traced(trigger_ctx, tag, timing, reaction_graph)
+                        }))
+                    }
+                    None => itertools::Either::Right(iter_ctx.map(|trigger_ctx| {
+                        trigger_traced(trigger_ctx, tag, timing, reaction_graph)
+                    })),
+                };
 
+            // Collecting the level up-front lets rayon split it recursively across the worker threads, which then
+            // steal work from each other. Levels narrower than the parallel threshold are run inline to skip the
+            // dispatch overhead. Each level is still a barrier: the next one starts only once all of its reactions
+            // have completed, even those the next level doesn't depend on. Overlapping independent levels is left as
+            // a follow-up.
             #[cfg(feature = "parallel")]
-            let iter_ctx_res = iter_ctx_res.collect::<Vec<_>>();
+            let iter_ctx_res = {
+                use rayon::prelude::{
+                    IntoParallelIterator, ParallelDrainRange, ParallelExtend, ParallelIterator,
+                };
+
+                let mut trigger_ctxs = iter_ctx.collect::<Vec<_>>();
+                if let Some(rng) = self.shuffle_rng.as_mut() {
+                    // Only the order is shuffled, work stealing still decides which worker runs each reaction.
+                    rng.shuffle(&mut trigger_ctxs);
+                    trigger_ctxs
+                        .into_par_iter()
+                        .map(|trigger_ctx| trigger_traced(trigger_ctx, tag, timing, reaction_graph))
+                        .collect::<Vec<_>>()
+                } else if trigger_ctxs.len() >= self.config.parallel_threshold.max(2) {
+                    // Run the level in chunks, polling the asynchronous events in between.
+                    let trigger =
+                        |trigger_ctx| trigger_traced(trigger_ctx, tag, timing, reaction_graph);
+                    let mut results = Vec::with_capacity(trigger_ctxs.len());
+                    loop {
+                        let chunk = ingest_interval.min(trigger_ctxs.len());
+                        results.par_extend(trigger_ctxs.par_drain(..chunk).map(trigger));
+                        if trigger_ctxs.is_empty() {
+                            break;
+                        }
+                        ingested.extend(self.event_rx.try_iter());
+                    }
+                    results
+                } else {
+                    trigger_ctxs
+                        .into_iter()
+                        .map(|trigger_ctx| trigger_traced(trigger_ctx, tag, timing, reaction_graph))
+                        .collect::<Vec<_>>()
+                }
+            };
+
+            // Reactions run at this level, only recorded to find the origin of tracked port events.
+            let mut origins = Vec::new();
+
+            #[cfg(not(feature = "parallel"))]
+            let mut run_at_level = 0;
+
+            for (trigger_res, span) in iter_ctx_res {
+                // Without the `parallel` feature, the reactions are run lazily by this loop.
+                #[cfg(not(feature = "parallel"))]
+                {
+                    run_at_level += 1;
+                    if run_at_level % ingest_interval == 0 {
+                        ingested.extend(self.event_rx.try_iter());
+                    }
+                }
+                spans.extend(span);
+                if self.lifecycle.is_some() {
+                    origins.push(trigger_res.reaction);
+                }
+                if !self.subscribers.is_empty() {
+                    reactions_run += 1;
+                    Self::publish_reaction(
+                        &mut self.subscribers,
+                        &self.reaction_graph,
+                        tag,
+                        trigger_res,
+                    );
+                }
 
-            for trigger_res in iter_ctx_res {
                 if let Some(shutdown_tag) = trigger_res.scheduled_shutdown {
                     // if the new shutdown tag is earlier than the current shutdown tag, update the shutdown tag and
                     // schedule a shutdown event
                     if self.shutdown_tag.map(|t| shutdown_tag < t).unwrap_or(true) {
                         self.shutdown_tag = Some(shutdown_tag);
+                        self.cancellation.schedule_shutdown(
+                            self.config
+                                .time_scale
+                                .instant_of(self.start_time, shutdown_tag),
+                        );
                         self.events.push_event(
                             shutdown_tag,
                             self.reaction_graph.shutdown_reactions.iter().copied(),
@@ -437,29 +1419,560 @@ impl Scheduler {
                     let downstream = self.reaction_graph.action_triggers[action_key]
                         .iter()
                         .copied();
-                    self.events.push_event(tag, downstream, false);
+                    let tracked = track_scheduled(
+                        self.lifecycle.as_mut(),
+                        &self.reaction_graph,
+                        EventSource::Action(action_key),
+                        self.reaction_graph.reaction_fqn(trigger_res.reaction),
+                        tag,
+                    );
+                    self.events
+                        .push_action_event(tag, action_key, downstream, tracked);
+                }
+
+                for &tag in &trigger_res.deferred {
+                    self.deferred.spawned(tag);
+                }
+
+                if let Some(failure) = &trigger_res.failure {
+                    failures.push(failure.clone());
                 }
             }
 
-            // Collect all the reactions that are triggered by the ports
-            let downstream = self
-                .store
-                .iter_set_port_keys()
-                .flat_map(|port_key| self.reaction_graph.port_triggers[port_key].iter());
+            if !ingested.is_empty() {
+                tracing::trace!(
+                    count = ingested.len(),
+                    "Queueing the async events received at the level."
+                );
+            }
+            for async_event in ingested {
+                Self::handle_async_event(
+                    async_event,
+                    tag,
+                    &mut self.events,
+                    &mut self.store,
+                    &self.reaction_graph,
+                    &mut AsyncEventState {
+                        lifecycle: self.lifecycle.as_mut(),
+                        deferred: &mut self.deferred,
+                        pending_parameters: &mut self.pending_parameters,
+                    },
+                );
+            }
+
+            if probing {
+                self.check_port_probes(tag, &executed);
+            }
+            if self.history.is_enabled() {
+                executed_at_tag.extend_from_slice(&executed);
+            }
 
-            if let Some(mut next_levels) = next_levels {
-                next_levels.extend_above(downstream.copied());
+            if self.config.debug_values && tracing::enabled!(tracing::Level::TRACE) {
+                for port_key in self.store.iter_set_port_keys() {
+                    tracing::trace!(port = %self.store.get_port(port_key), "Port set");
+                }
             }
+
+            if self.lifecycle.is_some() {
+                for port_key in self.store.iter_set_port_keys() {
+                    if tracked_ports
+                        .iter()
+                        .any(|tracked| tracked.source == EventSource::Port(port_key))
+                    {
+                        continue;
+                    }
+                    let origin = origins
+                        .iter()
+                        .find(|&&reaction_key| {
+                            self.reaction_graph.reaction_effect_ports[reaction_key]
+                                .iter()
+                                .any(|effect_key| effect_key == port_key)
+                        })
+                        .map_or("", |&reaction_key| {
+                            self.reaction_graph.reaction_fqn(reaction_key)
+                        });
+                    tracked_ports.extend(track_scheduled(
+                        self.lifecycle.as_mut(),
+                        &self.reaction_graph,
+                        EventSource::Port(port_key),
+                        origin,
+                        tag,
+                    ));
+                }
+            }
+
+            // Collect all the reactions that are triggered by the ports set at this level. Only the effect ports of
+            // the reactions that ran can have been set, which saves scanning all ports at every level. The ports are
+            // recorded once, so that only they are reset after the tag.
+            let newly_set = executed
+                .iter()
+                .flat_map(|&reaction_key| {
+                    self.reaction_graph.reaction_effect_ports[reaction_key].iter()
+                })
+                .filter(|&port_key| self.store.get_port(port_key).is_set())
+                .filter(|&port_key| set_ports.insert(port_key));
+
+            match next_levels {
+                Some(mut next_levels) => next_levels.extend_above(
+                    newly_set
+                        .flat_map(|port_key| self.reaction_graph.port_triggers[port_key].iter())
+                        .copied(),
+                ),
+                None => newly_set.for_each(drop),
+            }
+        });
+
+        if !executed_at_tag.is_empty() {
+            self.record_history(tag, &executed_at_tag);
+        }
+        if !spans.is_empty() {
+            self.trace.tags.push(TagTrace { tag, spans });
+        }
+        if !failures.is_empty() {
+            self.schedule_failures(tag, failures);
+        }
+        for tracked in tracked_ports {
+            self.log_completed(tracked, tag);
+        }
+        self.set_ports = set_ports;
+        self.publish(|| RuntimeEvent::TagCompleted {
+            tag,
+            reactions: reactions_run,
+        });
+    }
+
+    /// Subscribe to the runtime events, see [`crate::subscription`].
+    pub fn subscribe(&mut self) -> EventReceiver {
+        self.subscribers.subscribe(None)
+    }
+
+    /// Subscribe to the runtime events through a channel of `capacity`, missing the events sent while it is full.
+    pub fn subscribe_bounded(&mut self, capacity: usize) -> EventReceiver {
+        self.subscribers.subscribe(Some(capacity))
+    }
+
+    /// Send the event created by `event` to the subscribers, if there are any.
+    fn publish(&mut self, event: impl FnOnce() -> RuntimeEvent) {
+        if !self.subscribers.is_empty() {
+            self.subscribers.send(event());
+        }
+    }
+
+    /// Send the events of the reaction that produced `trigger_res` at `tag` to the subscribers.
+    fn publish_reaction(
+        subscribers: &mut Subscribers,
+        reaction_graph: &ReactionGraph,
+        tag: Tag,
+        trigger_res: &TriggerRes,
+    ) {
+        let reaction = reaction_graph.reaction_fqn(trigger_res.reaction);
+        if let Some(lag) = trigger_res.deadline_violated {
+            subscribers.send(RuntimeEvent::DeadlineViolated {
+                tag,
+                reaction: reaction.to_owned(),
+                lag,
+            });
+        }
+        if let Some((elapsed, budget)) = trigger_res.budget_exceeded {
+            subscribers.send(RuntimeEvent::BudgetExceeded {
+                tag,
+                reaction: reaction.to_owned(),
+                elapsed,
+                budget,
+            });
+        }
+        subscribers.send(RuntimeEvent::ReactionExecuted {
+            tag,
+            reaction: reaction.to_owned(),
         });
+    }
+
+    /// Log that all reactions triggered by a tracked event have run at `tag`, see [`crate::lifecycle`].
+    fn log_completed(&self, tracked: TrackedEvent, tag: Tag) {
+        let downstream = match tracked.source {
+            EventSource::Action(action_key) => &self.reaction_graph.action_triggers[action_key],
+            EventSource::Port(port_key) => &self.reaction_graph.port_triggers[port_key],
+        };
+        let reactions = downstream
+            .iter()
+            .map(|&(_, reaction_key)| self.reaction_graph.reaction_fqn(reaction_key))
+            .collect::<Vec<_>>();
+        tracing::debug!(
+            target: lifecycle::TARGET,
+            event_id = %tracked.id,
+            tag = %tag,
+            reactions = ?reactions,
+            "completed"
+        );
+    }
+
+    /// Record the panics caught at `tag`, and schedule them on the failure actions at the next microstep.
+    fn schedule_failures(&mut self, tag: Tag, failures: Vec<ReactorFailure>) {
+        let failure_tag = tag.delay(Duration::ZERO);
+        for &action_key in &self.reaction_graph.failure_actions {
+            self.store
+                .push_action_value(action_key, failure_tag, Box::new(failures.clone()));
+            let downstream = self.reaction_graph.action_triggers[action_key]
+                .iter()
+                .copied();
+            let tracked = track_scheduled(
+                self.lifecycle.as_mut(),
+                &self.reaction_graph,
+                EventSource::Action(action_key),
+                "isolation",
+                failure_tag,
+            );
+            self.events
+                .push_action_event(failure_tag, action_key, downstream, tracked);
+        }
+        self.failures.extend(failures);
+    }
+
+    /// Record the reactions run, ports set and resulting states at `tag` in the history.
+    fn record_history(&mut self, tag: Tag, executed: &[ReactionKey]) {
+        let reactions = executed
+            .iter()
+            .map(|&reaction_key| self.reaction_graph.reaction_fqn(reaction_key).to_owned())
+            .collect();
+        let ports = self
+            .store
+            .iter_set_port_keys()
+            .map(|port_key| {
+                let port = self.store.get_port(port_key);
+                (
+                    self.reaction_graph.port_fqn(port_key).to_owned(),
+                    port.debug_value(),
+                )
+            })
+            .collect();
+        let mut reactor_keys = executed
+            .iter()
+            .map(|&reaction_key| self.reaction_graph.reaction_reactors[reaction_key])
+            .collect::<Vec<_>>();
+        reactor_keys.sort();
+        reactor_keys.dedup();
+        let states = reactor_keys
+            .into_iter()
+            .map(|reactor_key| {
+                let reactor = self.store.get_reactor(reactor_key);
+                (
+                    self.reaction_graph.reactor_fqn(reactor_key).to_owned(),
+                    reactor.debug_state(),
+                )
+            })
+            .collect();
+
+        self.history.push(TagRecord {
+            tag,
+            reactions,
+            ports,
+            states,
+        });
+    }
+
+    /// Browse the history on stdin until the user resumes.
+    fn interactive_pause(&self) {
+        if self.history.is_enabled() {
+            eprintln!(
+                "Paused, press Enter to continue, or 'b N' to show the tag N steps back ({} recorded)...",
+                self.history.len()
+            );
+        } else {
+            eprintln!("Paused, press Enter to continue...");
+        }
+
+        loop {
+            let mut line = String::new();
+            if let Err(err) = std::io::stdin().read_line(&mut line) {
+                tracing::warn!("Unable to read from stdin: {err}");
+                return;
+            }
+            let line = line.trim();
+            if line.is_empty() {
+                return;
+            }
+            match line
+                .strip_prefix('b')
+                .map(|steps| steps.trim().parse::<usize>())
+            {
+                Some(Ok(steps)) => match self.history.back(steps) {
+                    Some(record) => eprintln!("{record}"),
+                    None => eprintln!("Only {} tags are recorded", self.history.len()),
+                },
+                _ => eprintln!("Unknown command '{line}'"),
+            }
+        }
+    }
+
+    /// The execution trace of all reactions, see [`Config::with_trace`].
+    pub fn trace(&self) -> &ExecutionTrace {
+        &self.trace
+    }
+
+    /// The execution history of the most recent tags, see [`Config::with_history_window`].
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Evaluate the probes on actions present at `tag`.
+    fn check_action_probes(&mut self, tag: Tag) {
+        for probe_idx in 0..self.probes.len() {
+            let probe = &self.probes[probe_idx];
+            let ProbeMatcher::Action(matcher) = &probe.matcher else {
+                continue;
+            };
+            let crate::ProbeKey::Action(action_key) = probe.key() else {
+                continue;
+            };
+            if let Some((value, captured)) = matcher(self.store.get_action_mut(action_key), tag) {
+                let reactions = self.reaction_graph.action_triggers[action_key]
+                    .iter()
+                    .map(|&(_, reaction_key)| self.store.reaction_name(reaction_key).to_owned())
+                    .collect();
+                self.probe_hit(probe_idx, tag, value, captured, reactions);
+            }
+        }
+    }
+
+    /// Evaluate the probes on ports set by the `executed` reactions at `tag`.
+    fn check_port_probes(&mut self, tag: Tag, executed: &[ReactionKey]) {
+        for probe_idx in 0..self.probes.len() {
+            let probe = &self.probes[probe_idx];
+            let ProbeMatcher::Port(matcher) = &probe.matcher else {
+                continue;
+            };
+            let crate::ProbeKey::Port(port_key) = probe.key() else {
+                continue;
+            };
+            // Only consider ports set at this level, so each value is checked once.
+            let setters = executed
+                .iter()
+                .filter(|&&reaction_key| {
+                    self.reaction_graph.reaction_effect_ports[reaction_key][port_key]
+                })
+                .map(|&reaction_key| self.store.reaction_name(reaction_key).to_owned())
+                .collect::<Vec<_>>();
+            if setters.is_empty() {
+                continue;
+            }
+            if let Some((value, captured)) = matcher(self.store.get_port(port_key)) {
+                self.probe_hit(probe_idx, tag, value, captured, setters);
+            }
+        }
+    }
+
+    fn probe_hit(
+        &mut self,
+        probe_idx: usize,
+        tag: Tag,
+        value: String,
+        captured: Option<crate::ProbeValue>,
+        reactions: Vec<String>,
+    ) {
+        let snapshot = ProbeSnapshot {
+            probe: self.probes[probe_idx].name().to_owned(),
+            tag,
+            value,
+            captured,
+            reactions,
+            recent_events: self.recent_events.iter().cloned().collect(),
+        };
+
+        if self.config.interactive {
+            eprintln!("{snapshot}");
+            self.interactive_pause();
+        } else {
+            tracing::warn!("{snapshot}");
+        }
+
+        self.probe_hits.push(snapshot);
+    }
+
+    /// Snapshots of all probe matches so far.
+    pub fn probe_hits(&self) -> &[ProbeSnapshot] {
+        &self.probe_hits
+    }
+
+    /// All overloads detected so far, see [`crate::overload`].
+    pub fn overloads(&self) -> &[Overload] {
+        &self.overloads
+    }
+
+    /// All panics caught in reactions so far, see [`crate::isolation`].
+    pub fn failures(&self) -> &[ReactorFailure] {
+        &self.failures
+    }
+
+    /// The execution time statistics of all reactions with a budget, see [`crate::budget`].
+    pub fn budget_stats(&self) -> Vec<BudgetStats> {
+        self.store.budget_stats(&self.reaction_graph)
+    }
 
-        self.store.reset_ports();
+    /// The statistics of the reactions that exceeded their budget, by decreasing number of sampled invocations over
+    /// the budget.
+    pub fn budget_offenders(&self) -> Vec<BudgetStats> {
+        let mut offenders = self
+            .budget_stats()
+            .into_iter()
+            .filter(BudgetStats::is_offender)
+            .collect::<Vec<_>>();
+        offenders.sort_by_key(|offender| std::cmp::Reverse(offender.exceeded));
+        offenders
+    }
+
+    /// Estimate the memory used by each reactor at the current tag, see [`crate::mem_size`].
+    pub fn memory_report(&self) -> crate::mem_size::MemoryReport {
+        self.store.memory_report(&self.reaction_graph)
     }
 
     /// Consume the scheduler and return the `Env` instance.
     ///
     /// This method is useful for testing purposes, as it allows the caller to inspect reactor states after the
-    /// scheduler has been run.
+    /// scheduler has been run. If the shutdown tag was abandoned after the [`Config::shutdown_grace`], this waits for
+    /// its reactions and the flushes to complete.
     pub fn into_env(self) -> Env {
-        self.store.into_env()
+        match self.abandoned {
+            Some(abandoned) => abandoned
+                .recv()
+                .expect("The shutdown thread panicked")
+                .into_env(),
+            None => self.store.into_env(),
+        }
     }
 }
+
+/// The state moved to the thread processing the shutdown tag with a shutdown grace period.
+struct ShutdownPass {
+    store: Pin<Box<Store>>,
+    flushes: Vec<Flush>,
+    reactions: ReactionSet,
+    /// The reactions run, in order
+    executed: Vec<ReactionKey>,
+    /// Spans of the reactions run, only recorded if tracing
+    spans: Vec<ReactionSpan>,
+    /// Panics caught in the reactions
+    failures: Vec<ReactorFailure>,
+}
+
+impl ShutdownPass {
+    /// Run the reactions at the shutdown `tag` one at a time, keeping track of them in `running`.
+    fn run(
+        &mut self,
+        tag: Tag,
+        reaction_graph: &ReactionGraph,
+        running: &Mutex<Vec<String>>,
+        timing: Option<std::time::Instant>,
+    ) {
+        let mut set_ports = tinymap::TinyBitSet::with_capacity(reaction_graph.port_triggers.len());
+        self.reactions
+            .view()
+            .for_each_level(|level, reaction_keys, next_levels| {
+                let mut executed = Vec::new();
+                let reaction_keys = reaction_keys.inspect(|&key| executed.push(key));
+
+                // Safety: reaction_keys in the same level are guaranteed to be independent of each other.
+                let iter_ctx = unsafe { self.store.iter_borrow_storage(reaction_keys) };
+                for trigger_ctx in iter_ctx {
+                    let timing = timing.map(|tag_start| (tag_start, level));
+                    let (trigger_res, span) =
+                        trigger_tracked(trigger_ctx, tag, timing, reaction_graph, running);
+                    self.spans.extend(span);
+                    if let Some(failure) = &trigger_res.failure {
+                        self.failures.push(failure.clone());
+                    }
+                }
+
+                let newly_set = executed
+                    .iter()
+                    .flat_map(|&reaction_key| {
+                        reaction_graph.reaction_effect_ports[reaction_key].iter()
+                    })
+                    .filter(|&port_key| self.store.get_port(port_key).is_set())
+                    .filter(|&port_key| set_ports.insert(port_key));
+                match next_levels {
+                    Some(mut next_levels) => next_levels.extend_above(
+                        newly_set
+                            .flat_map(|port_key| reaction_graph.port_triggers[port_key].iter())
+                            .copied(),
+                    ),
+                    None => newly_set.for_each(drop),
+                }
+                self.executed.extend(executed);
+            });
+        self.store.reset_ports_of(set_ports.iter());
+    }
+}
+
+/// The scheduler state updated by the asynchronous events, besides the event queue and the store.
+struct AsyncEventState<'a> {
+    lifecycle: Option<&'a mut Lifecycle>,
+    deferred: &'a mut PendingDeferred,
+    pending_parameters: &'a mut PendingParameters,
+}
+
+/// Start tracking an event on `source` scheduled for `tag` by `origin`, if it matches the event filter.
+fn track_scheduled(
+    lifecycle: Option<&mut Lifecycle>,
+    reaction_graph: &ReactionGraph,
+    source: EventSource,
+    origin: &str,
+    tag: Tag,
+) -> Option<TrackedEvent> {
+    let fqn = match source {
+        EventSource::Action(action_key) => reaction_graph.action_fqn(action_key),
+        EventSource::Port(port_key) => reaction_graph.port_fqn(port_key),
+    };
+    let tracked = lifecycle?.track(source, fqn)?;
+    tracing::debug!(
+        target: lifecycle::TARGET,
+        event_id = %tracked.id,
+        source = fqn,
+        origin,
+        tag = %tag,
+        "scheduled"
+    );
+    Some(tracked)
+}
+
+/// Trigger the reaction like [`trigger_traced`], keeping track of it in `running` while it runs.
+fn trigger_tracked<'a>(
+    trigger_ctx: ReactionTriggerCtx<'a>,
+    tag: Tag,
+    timing: Option<(std::time::Instant, Level)>,
+    reaction_graph: &ReactionGraph,
+    running: &Mutex<Vec<String>>,
+) -> (&'a TriggerRes, Option<ReactionSpan>) {
+    let fqn = reaction_graph
+        .reaction_fqn(trigger_ctx.context.trigger_res.reaction)
+        .to_owned();
+    running.lock().unwrap().push(fqn.clone());
+    let res = trigger_traced(trigger_ctx, tag, timing, reaction_graph);
+    let mut running = running.lock().unwrap();
+    if let Some(pos) = running.iter().position(|name| *name == fqn) {
+        running.swap_remove(pos);
+    }
+    res
+}
+
+/// Trigger the reaction, recording its [`ReactionSpan`] if `timing` is given.
+fn trigger_traced<'a>(
+    trigger_ctx: ReactionTriggerCtx<'a>,
+    tag: Tag,
+    timing: Option<(std::time::Instant, Level)>,
+    reaction_graph: &ReactionGraph,
+) -> (&'a TriggerRes, Option<ReactionSpan>) {
+    let Some((tag_start, level)) = timing else {
+        return (trigger_ctx.trigger(tag), None);
+    };
+    let key = trigger_ctx.context.trigger_res.reaction;
+    let start = tag_start.elapsed();
+    let trigger_res = trigger_ctx.trigger(tag);
+    let span = ReactionSpan {
+        key,
+        reaction: reaction_graph.reaction_fqn(key).to_owned(),
+        level,
+        start,
+        duration: tag_start.elapsed() - start,
+    };
+    (trigger_res, Some(span))
+}
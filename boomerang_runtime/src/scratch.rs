@@ -0,0 +1,196 @@
+//! Reusable scratch buffers for per-invocation temporaries.
+//!
+//! Every reaction [`crate::Context`] owns a [`Scratch`] pool, available with [`crate::Context::scratch`]. Buffers taken
+//! from the pool borrow the context, so they are always handed back (cleared, but with their capacity retained) before
+//! the reaction returns, and the pool is empty of outstanding buffers at the end of every tag. After the first few
+//! invocations, hot reactions allocating temporaries this way cause no heap traffic.
+//!
+//! The memory retained by a pool is capped: after each tag at which the reaction ran, the scheduler trims its pool to
+//! at most [`MAX_FREE_BUFFERS`] free buffers per element type, and shrinks the buffers to at most
+//! [`MAX_RETAINED_BYTES`] each. A single invocation with an unusually large temporary therefore doesn't keep its
+//! memory for the rest of the run.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! fn trigger(mut self, ctx: &mut runtime::Context, state: &mut State) {
+//!     let mut samples = ctx.scratch().vec::<f32>();
+//!     samples.extend(self.inputs.iter().filter_map(|inp| *inp.get()));
+//!     samples.sort_by(f32::total_cmp);
+//!     *self.median = samples.get(samples.len() / 2).copied();
+//! }
+//! ```
+
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+};
+
+/// The maximum number of free buffers of each element type kept by a pool after a tag.
+pub const MAX_FREE_BUFFERS: usize = 8;
+
+/// The maximum capacity in bytes of each free buffer kept by a pool after a tag.
+pub const MAX_RETAINED_BYTES: usize = 64 * 1024;
+
+/// Trims a `Vec<Vec<T>>` of free buffers to the retention cap.
+type TrimFn = fn(&mut dyn Any);
+
+/// The free buffers of an element type `T`, a `Vec<Vec<T>>`, with the function trimming them.
+type Pool = (Box<dyn Any + Send>, TrimFn);
+
+fn trim_pool<T: Send + 'static>(pool: &mut dyn Any) {
+    let pool = pool
+        .downcast_mut::<Vec<Vec<T>>>()
+        .expect("Scratch pool type mismatch");
+    // The most recently returned buffers are kept, they are handed out first
+    let excess = pool.len().saturating_sub(MAX_FREE_BUFFERS);
+    pool.drain(..excess);
+    let max_len = MAX_RETAINED_BYTES / std::mem::size_of::<T>().max(1);
+    for vec in pool.iter_mut() {
+        vec.shrink_to(max_len);
+    }
+}
+
+/// A pool of reusable buffers, see the [module documentation](self).
+#[derive(Default)]
+pub struct Scratch {
+    /// Free buffers by element type
+    pools: RefCell<HashMap<TypeId, Pool>>,
+}
+
+impl std::fmt::Debug for Scratch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scratch")
+            .field("pools", &self.pools.borrow().len())
+            .finish()
+    }
+}
+
+impl Scratch {
+    /// Take an empty `Vec<T>` from the pool, allocating a new one only if no free buffer is available.
+    ///
+    /// The buffer is returned to the pool when the returned [`ScratchVec`] is dropped.
+    pub fn vec<T: Send + 'static>(&self) -> ScratchVec<'_, T> {
+        let vec = self
+            .pools
+            .borrow_mut()
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|(pool, _)| pool.downcast_mut::<Vec<Vec<T>>>())
+            .and_then(Vec::pop)
+            .unwrap_or_default();
+        ScratchVec { vec, scratch: self }
+    }
+
+    /// The number of free buffers held in the pool.
+    pub fn free_buffers<T: Send + 'static>(&self) -> usize {
+        self.pools
+            .borrow()
+            .get(&TypeId::of::<T>())
+            .and_then(|(pool, _)| pool.downcast_ref::<Vec<Vec<T>>>())
+            .map_or(0, Vec::len)
+    }
+
+    /// Trim the free buffers to the retention cap, see the [module documentation](self).
+    pub(crate) fn trim(&mut self) {
+        for (pool, trim) in self.pools.get_mut().values_mut() {
+            trim(pool.as_mut());
+        }
+    }
+
+    fn give_back<T: Send + 'static>(&self, mut vec: Vec<T>) {
+        vec.clear();
+        self.pools
+            .borrow_mut()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| (Box::new(Vec::<Vec<T>>::new()), trim_pool::<T> as TrimFn))
+            .0
+            .downcast_mut::<Vec<Vec<T>>>()
+            .expect("Scratch pool type mismatch")
+            .push(vec);
+    }
+}
+
+/// A `Vec<T>` borrowed from a [`Scratch`] pool.
+pub struct ScratchVec<'a, T: Send + 'static> {
+    vec: Vec<T>,
+    scratch: &'a Scratch,
+}
+
+impl<T: Send + 'static> Deref for ScratchVec<'_, T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.vec
+    }
+}
+
+impl<T: Send + 'static> DerefMut for ScratchVec<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.vec
+    }
+}
+
+impl<T: Send + std::fmt::Debug + 'static> std::fmt::Debug for ScratchVec<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.vec.fmt(f)
+    }
+}
+
+impl<T: Send + 'static> Drop for ScratchVec<'_, T> {
+    fn drop(&mut self) {
+        self.scratch.give_back(std::mem::take(&mut self.vec));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scratch_reuse() {
+        let scratch = Scratch::default();
+        let ptr = {
+            let mut a = scratch.vec::<u32>();
+            let mut b = scratch.vec::<u32>();
+            a.extend(0..100);
+            b.push(1);
+            a.as_ptr()
+        };
+        assert_eq!(scratch.free_buffers::<u32>(), 2);
+        assert_eq!(scratch.free_buffers::<f64>(), 0);
+
+        // The most recently returned buffer is handed out first, empty but with its capacity retained.
+        let a = scratch.vec::<u32>();
+        assert!(a.is_empty());
+        assert!(a.capacity() >= 100);
+        assert_eq!(a.as_ptr(), ptr);
+        assert_eq!(scratch.free_buffers::<u32>(), 1);
+    }
+
+    #[test]
+    fn test_scratch_trim() {
+        let mut scratch = Scratch::default();
+        {
+            let mut buffers = (0..MAX_FREE_BUFFERS + 2)
+                .map(|_| scratch.vec::<u64>())
+                .collect::<Vec<_>>();
+            buffers
+                .last_mut()
+                .unwrap()
+                .reserve(2 * MAX_RETAINED_BYTES / 8);
+        }
+        assert_eq!(scratch.free_buffers::<u64>(), MAX_FREE_BUFFERS + 2);
+
+        // The excess buffers are dropped, and the large one shrunk to the cap
+        scratch.trim();
+        assert_eq!(scratch.free_buffers::<u64>(), MAX_FREE_BUFFERS);
+        let buffers = (0..MAX_FREE_BUFFERS)
+            .map(|_| scratch.vec::<u64>())
+            .collect::<Vec<_>>();
+        assert!(buffers
+            .iter()
+            .all(|vec| vec.capacity() <= MAX_RETAINED_BYTES / 8));
+    }
+}
@@ -0,0 +1,52 @@
+//! A seedable PRNG driving the scheduler's otherwise arbitrary choices in the shuffled testing mode, see
+//! [`crate::Config::with_shuffle_seed`].
+
+/// A SplitMix64 generator, good enough to explore schedules and trivially reproducible from its seed.
+#[derive(Debug, Clone)]
+pub(crate) struct ShuffleRng(u64);
+
+impl ShuffleRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, for `n > 0`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Shuffle `items` in place (Fisher-Yates).
+    pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shuffle() {
+        let shuffled = |seed| {
+            let mut items: Vec<_> = (0..16).collect();
+            ShuffleRng::new(seed).shuffle(&mut items);
+            items
+        };
+        assert_eq!(shuffled(7), shuffled(7));
+        assert_ne!(shuffled(7), shuffled(8));
+
+        let mut items = shuffled(7);
+        items.sort();
+        assert_eq!(items, (0..16).collect::<Vec<_>>());
+    }
+}
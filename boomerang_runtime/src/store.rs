@@ -1,11 +1,38 @@
 //! Runtime data storage
-
-use std::{marker::PhantomPinned, pin::Pin, ptr::NonNull};
+//!
+//! The [`Store`] owns all reactors, reactions, actions and ports while the scheduler runs, and hands out a
+//! [`ReactionTriggerCtx`] with the borrows each reaction needs. The pointers for these borrows are computed once in
+//! [`Store::new`] and cached, so no lookups are necessary when triggering reactions.
+//!
+//! ## Safety
+//!
+//! The scheduler borrows the contexts of all reactions at a level at once, and runs them concurrently with the
+//! `parallel` feature. This is sound because the reactions at a level borrow disjoint parts of the `Store`:
+//!
+//! - each reaction has its own [`Context`] and [`Reaction`],
+//! - no two reactions at a level belong to the same reactor, or borrow the same action or effect port, and
+//! - no reaction reads a port that a reaction at its level writes.
+//!
+//! The builder guarantees this by ordering the reactions of a reactor, and the writers of a port before its readers,
+//! and [`Store::new`] checks it once up-front, rather than on every borrow. The cached pointers point into the heap
+//! allocations owned by the maps of the `Store`, which are never resized or moved while it is alive.
+//!
+//! The store and the [`crate::refs`] iterators are tested under Miri, and the synchronization primitives shared between
+//! threads (see [`crate::sync`]) with loom. The loom tests also trigger the contexts of a level, as handed out by
+//! [`Store::iter_borrow_storage`], on threads of their own.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    marker::PhantomPinned,
+    pin::Pin,
+    ptr::NonNull,
+};
 
 use crate::{
     refs::{Refs, RefsMut},
-    ActionKey, BaseAction, BasePort, BaseReactor, Context, ContextCommon, Deadline, PortKey,
-    Reaction, ReactionKey, ReactorData, ReactorKey, Tag, TriggerRes,
+    ActionKey, BaseAction, BasePort, BaseReactor, BudgetStats, Context, ContextCommon, Deadline,
+    Duration, Level, PanicPolicy, PortKey, Reaction, ReactionKey, ReactorData, ReactorFailure,
+    ReactorKey, Tag, TriggerRes,
 };
 
 use super::{Env, ReactionGraph};
@@ -20,11 +47,13 @@ pub struct ReactionTriggerCtx<'store> {
     pub mut_ports: RefsMut<'store, dyn BasePort>,
 }
 
+// Safety: the contexts of the reactions at a level borrow disjoint parts of the `Store`, see [`check_disjoint_levels`].
 #[cfg(feature = "parallel")]
 unsafe impl Send for ReactionTriggerCtx<'_> {}
 
 impl<'a> From<&'a mut ReactionTriggerCtxPtrs> for ReactionTriggerCtx<'a> {
     fn from(ptrs: &mut ReactionTriggerCtxPtrs) -> Self {
+        // Safety: the pointers were set in `Store::new`, and are valid for as long as the `Store` is.
         let context = unsafe { ptrs.context.as_mut() };
         let reactor = unsafe { ptrs.reactor.unwrap().as_mut() };
         let reaction = unsafe { ptrs.reaction.as_mut() };
@@ -46,34 +75,197 @@ impl<'a> From<&'a mut ReactionTriggerCtxPtrs> for ReactionTriggerCtx<'a> {
 
 impl<'a> ReactionTriggerCtx<'a> {
     /// Trigger the reaction with the given context and state.
-    pub(crate) fn trigger(self, tag: Tag) -> &'a TriggerRes {
+    pub(crate) fn trigger(mut self, tag: Tag) -> &'a TriggerRes {
+        let span = self.context.span.clone();
+        let _entered = span.enter();
         tracing::trace!(
             "    Executing {reactor_name}/{reaction_name}.",
             reaction_name = self.reaction.get_name(),
             reactor_name = self.reactor.name()
         );
 
-        if let Some(Deadline { deadline, handler }) = self.reaction.deadline.as_ref() {
-            let lag = self.context.get_physical_time() - self.context.get_logical_time();
-            if lag > *deadline {
-                (handler.write().unwrap())();
+        self.context.reset_for_reaction(tag);
+
+        if self.reactor.is_failed() {
+            tracing::trace!("    Skipping reaction of failed reactor.");
+            return &self.context.trigger_res;
+        }
+
+        let mut deadline_violated = None;
+        let deadline = self
+            .reaction
+            .deadline
+            .as_ref()
+            .map(|Deadline { deadline, handler }| {
+                let lag = self.context.get_physical_time() - self.context.get_logical_time();
+                if lag > *deadline {
+                    deadline_violated = Some(lag);
+                    (handler.write().unwrap())();
+                }
+                self.context.get_logical_time() + *deadline
+            });
+        self.context.cancellation.set_deadline(deadline);
+
+        let started = self
+            .reaction
+            .budget
+            .as_mut()
+            .is_some_and(|budget| budget.sample())
+            .then(std::time::Instant::now);
+
+        let panic_policy = self.reactor.panic_policy();
+        if panic_policy == PanicPolicy::Propagate {
+            self.reaction.body.trigger(
+                self.context,
+                self.reactor,
+                self.ref_ports,
+                self.mut_ports,
+                self.actions,
+            );
+        } else {
+            // Record the effects set before the reaction, e.g. by another reaction of the reactor, to restore them
+            for port in self.mut_ports.reborrow() {
+                port.checkpoint();
+            }
+            let marks = self
+                .actions
+                .reborrow()
+                .map(|action| action.push_mark())
+                .collect::<Vec<_>>();
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                self.reaction.body.trigger(
+                    &mut *self.context,
+                    &mut *self.reactor,
+                    self.ref_ports,
+                    self.mut_ports.reborrow(),
+                    self.actions.reborrow(),
+                )
+            }));
+            if let Err(payload) = result {
+                let failure = ReactorFailure {
+                    reactor: self.reactor.name().to_owned(),
+                    reaction: self.reaction.get_name().to_owned(),
+                    tag,
+                    message: crate::isolation::panic_message(payload.as_ref()),
+                    policy: panic_policy,
+                };
+                tracing::error!("{failure}");
+                self.reactor.fail();
+                // Discard the effects of the failed reaction, so that no downstream reaction sees them
+                for port in self.mut_ports {
+                    port.rollback();
+                }
+                for (action, mark) in self.actions.zip(marks) {
+                    action.remove_values_since(mark);
+                }
+                // Deferred computations are already running, and still deliver their results
+                let deferred = std::mem::take(&mut self.context.trigger_res.deferred);
+                self.context.reset_for_reaction(tag);
+                self.context.trigger_res.deferred = deferred;
+                self.context.trigger_res.failure = Some(failure);
+            } else {
+                for port in self.mut_ports {
+                    port.commit();
+                }
             }
         }
 
-        self.context.reset_for_reaction(tag);
+        // Detect reactions that overran their deadline without checking for cancellation
+        if deadline.is_some_and(|deadline| self.context.get_physical_time() > deadline) {
+            if !self.context.cancellation.observed() {
+                tracing::warn!(
+                    "Reaction {} overran its deadline at {tag} without checking for cancellation",
+                    self.reaction.get_name()
+                );
+            }
+            deadline_violated.get_or_insert_with(|| {
+                self.context.get_physical_time() - self.context.get_logical_time()
+            });
+        }
+        self.context.trigger_res.deadline_violated =
+            deadline_violated.map(|lag| lag.try_into().unwrap_or(Duration::MAX));
+
+        if let (Some(started), Some(budget)) = (started, self.reaction.budget.as_mut()) {
+            let exceeded = budget
+                .record(started.elapsed())
+                .map(|elapsed| (elapsed, budget.budget()));
+            if let Some((elapsed, budget)) = exceeded {
+                tracing::warn!(
+                    "Reaction {} ran for {elapsed} at {tag}, over its budget of {budget}",
+                    self.reaction.get_name(),
+                );
+            }
+            self.context.trigger_res.budget_exceeded = exceeded;
+        }
 
-        self.reaction.body.trigger(
-            self.context,
-            self.reactor,
-            self.ref_ports,
-            self.mut_ports,
-            self.actions,
-        );
+        // The scratch buffers were all handed back, so the pool only holds free buffers until the next tag
+        self.context.scratch.trim();
 
         &self.context.trigger_res
     }
 }
 
+/// Check that the reactions at each level of `reaction_graph` borrow disjoint parts of the [`Store`].
+///
+/// See the [module documentation](self) for why this is necessary.
+fn check_disjoint_levels(reaction_graph: &ReactionGraph) -> Result<(), String> {
+    let mut levels: BTreeMap<Level, BTreeSet<ReactionKey>> = BTreeMap::new();
+    for &(level, reaction_key) in reaction_graph
+        .action_triggers
+        .values()
+        .chain(reaction_graph.port_triggers.values())
+        .flatten()
+        .chain(&reaction_graph.startup_reactions)
+        .chain(&reaction_graph.shutdown_reactions)
+    {
+        levels.entry(level).or_default().insert(reaction_key);
+    }
+
+    let fqn = |reaction_key: ReactionKey| match reaction_graph.reaction_fqn(reaction_key) {
+        "" => format!("{reaction_key:?}"),
+        fqn => fqn.to_owned(),
+    };
+
+    for (level, reaction_keys) in levels {
+        let conflict = |first: ReactionKey, second: ReactionKey, what: String| {
+            format!(
+                "Reactions {} and {} at level {level:?} both borrow {what}",
+                fqn(first),
+                fqn(second)
+            )
+        };
+
+        let mut reactors = HashMap::new();
+        let mut actions = HashMap::new();
+        let mut mut_ports = HashMap::new();
+        for &reaction_key in &reaction_keys {
+            let reactor_key = reaction_graph.reaction_reactors[reaction_key];
+            if let Some(other) = reactors.insert(reactor_key, reaction_key) {
+                return Err(conflict(other, reaction_key, format!("{reactor_key:?}")));
+            }
+            for action_key in reaction_graph.reaction_actions[reaction_key].iter() {
+                if let Some(other) = actions.insert(action_key, reaction_key) {
+                    return Err(conflict(other, reaction_key, format!("{action_key:?}")));
+                }
+            }
+            for port_key in reaction_graph.reaction_effect_ports[reaction_key].iter() {
+                if let Some(other) = mut_ports.insert(port_key, reaction_key) {
+                    return Err(conflict(other, reaction_key, format!("{port_key:?}")));
+                }
+            }
+        }
+
+        for &reaction_key in &reaction_keys {
+            for port_key in reaction_graph.reaction_use_ports[reaction_key].iter() {
+                if let Some(&other) = mut_ports.get(&port_key) {
+                    return Err(conflict(other, reaction_key, format!("{port_key:?}")));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Lifetime-erased version of [`ReactionTriggerCtx`]
 ///
 /// This is used to pre-calculate and cache the necessary pointers for each reaction's trigger data.
@@ -100,7 +292,7 @@ impl Default for ReactionTriggerCtxPtrs {
     }
 }
 
-#[cfg(feature = "parallel")]
+// Safety: the pointers refer into the pinned `Store` owning them, whose contents are all `Send`.
 unsafe impl Send for ReactionTriggerCtxPtrs {}
 
 #[derive(Debug)]
@@ -127,6 +319,10 @@ impl Store {
         contexts: tinymap::TinySecondaryMap<ReactionKey, Context>,
         reaction_graph: &ReactionGraph,
     ) -> Pin<Box<Self>> {
+        if let Err(conflict) = check_disjoint_levels(reaction_graph) {
+            panic!("Invalid reaction graph: {conflict}");
+        }
+
         // Create a default `ReactionTriggerCtxPtrs` for each reaction
         let ptrs = env
             .reactions
@@ -252,7 +448,10 @@ impl Store {
     /// This uses the previously stored `ReactionTriggerCtxPtrs`.
     ///
     /// # Safety
-    /// TODO: Document safety
+    ///
+    /// The `keys` must be unique, and all at the same level of the [`ReactionGraph`] the `Store` was built with. The
+    /// returned contexts then borrow disjoint parts of the `Store`, and can be used at the same time, see the
+    /// [module documentation](self).
     pub unsafe fn iter_borrow_storage<'a>(
         self: &'a mut Pin<Box<Self>>,
         keys: impl Iterator<Item = ReactionKey> + 'a,
@@ -271,11 +470,106 @@ impl Store {
             .map(|(key, _)| key)
     }
 
+    /// Get a reference to the port with the given key.
+    pub fn get_port(self: &Pin<Box<Self>>, port_key: PortKey) -> &dyn BasePort {
+        self.inner.ports[port_key].as_ref()
+    }
+
+    /// Get a mutable reference to the action with the given key.
+    pub fn get_action_mut(self: &mut Pin<Box<Self>>, action_key: ActionKey) -> &mut dyn BaseAction {
+        // SAFETY: we are not moving anything from self
+        let actions = &mut unsafe { self.as_mut().get_unchecked_mut() }.inner.actions;
+        actions[action_key].as_mut()
+    }
+
+    /// Get a mutable reference to the reactor with the given key.
+    pub fn get_reactor_mut(
+        self: &mut Pin<Box<Self>>,
+        reactor_key: ReactorKey,
+    ) -> &mut dyn BaseReactor {
+        // SAFETY: we are not moving anything from self
+        let reactors = &mut unsafe { self.as_mut().get_unchecked_mut() }.inner.reactors;
+        reactors[reactor_key].as_mut()
+    }
+
+    /// Set the physical start time of the contexts of all reactions.
+    pub fn set_start_time(self: &mut Pin<Box<Self>>, start_time: std::time::Instant) {
+        // SAFETY: we are not moving anything from self
+        let contexts = &mut unsafe { self.as_mut().get_unchecked_mut() }.inner.contexts;
+        for context in contexts.values_mut() {
+            context.start_time = start_time;
+        }
+    }
+
+    /// Set the global parameters of the contexts of all reactions, see [`crate::params`].
+    pub fn set_parameters(self: &mut Pin<Box<Self>>, parameters: &crate::Parameters) {
+        // SAFETY: we are not moving anything from self
+        let contexts = &mut unsafe { self.as_mut().get_unchecked_mut() }.inner.contexts;
+        for context in contexts.values_mut() {
+            context.parameters = parameters.clone();
+        }
+    }
+
+    /// Get a reference to the reactor with the given key.
+    pub fn get_reactor(self: &Pin<Box<Self>>, reactor_key: ReactorKey) -> &dyn BaseReactor {
+        self.inner.reactors[reactor_key].as_ref()
+    }
+
+    /// Estimate the memory used by each reactor, see [`crate::mem_size`].
+    pub fn memory_report(
+        self: &Pin<Box<Self>>,
+        reaction_graph: &ReactionGraph,
+    ) -> crate::mem_size::MemoryReport {
+        crate::mem_size::MemoryReport::new(
+            &self.inner.reactors,
+            &self.inner.ports,
+            &self.inner.actions,
+            reaction_graph,
+        )
+    }
+
+    /// Get the name of the reaction with the given key.
+    pub fn reaction_name(self: &Pin<Box<Self>>, reaction_key: ReactionKey) -> &str {
+        self.inner.reactions[reaction_key].get_name()
+    }
+
+    /// The statistics of the reactions with a budget, see [`crate::budget`].
+    pub fn budget_stats(self: &Pin<Box<Self>>, reaction_graph: &ReactionGraph) -> Vec<BudgetStats> {
+        self.inner
+            .reactions
+            .iter()
+            .filter_map(|(reaction_key, reaction)| {
+                let budget = reaction.budget.as_ref()?;
+                let fqn = match reaction_graph.reaction_fqn(reaction_key) {
+                    "" => reaction.get_name().to_owned(),
+                    fqn => fqn.to_owned(),
+                };
+                Some(budget.stats(fqn))
+            })
+            .collect()
+    }
+
     pub fn reset_ports(self: &mut Pin<Box<Self>>) {
         let store = unsafe { self.as_mut().get_unchecked_mut() };
         store.inner.ports.values_mut().for_each(|p| p.cleanup());
     }
 
+    /// Reset only the ports in `port_keys`, e.g. the effect ports of the reactions run at a tag.
+    pub fn reset_ports_of(self: &mut Pin<Box<Self>>, port_keys: impl IntoIterator<Item = PortKey>) {
+        let store = unsafe { self.as_mut().get_unchecked_mut() };
+        for port_key in port_keys {
+            store.inner.ports[port_key].cleanup();
+        }
+    }
+
+    /// Include the port values in the `Debug` and `Display` output of the ports, see [`crate::value_fmt`].
+    pub fn set_debug_values(self: &mut Pin<Box<Self>>, enabled: bool) {
+        let store = unsafe { self.as_mut().get_unchecked_mut() };
+        for port in store.inner.ports.values_mut() {
+            port.set_debug_values(enabled);
+        }
+    }
+
     /// Turn this `Store` back into the `Env` it was built from.
     pub fn into_env(self: Pin<Box<Self>>) -> Env {
         // SAFETY: We are the only owner of the `Store` and we are consuming it, and immediately
@@ -286,6 +580,10 @@ impl Store {
             reactions: store.inner.reactions,
             actions: store.inner.actions,
             ports: store.inner.ports,
+            probes: Vec::new(),
+            inits: Vec::new(),
+            flushes: Vec::new(),
+            parameters: Default::default(),
         }
     }
 }
@@ -307,7 +605,16 @@ pub mod tests {
 
         let contexts = [(
             reaction_key,
-            Context::new(std::time::Instant::now(), None, event_tx, shutdown_rx),
+            Context::new(
+                reaction_key,
+                std::time::Instant::now(),
+                crate::TimeScale::REAL_TIME,
+                None,
+                event_tx,
+                shutdown_rx,
+                Default::default(),
+                Default::default(),
+            ),
         )]
         .into_iter()
         .collect();
@@ -315,6 +622,41 @@ pub mod tests {
         Store::new(env, contexts, reaction_graph)
     }
 
+    #[test]
+    fn test_check_disjoint_levels() {
+        let (_, mut graph) = crate::env::tests::create_dummy_env();
+        let first = graph.reaction_reactors.keys().next().unwrap();
+        let second = ReactionKey::from(1);
+        let [port0, port1] = [PortKey::from(0), PortKey::from(1)];
+        graph.reaction_reactors.insert(second, ReactorKey::from(1));
+        graph
+            .reaction_actions
+            .insert(second, std::iter::empty().collect());
+        graph
+            .reaction_effect_ports
+            .insert(second, std::iter::empty().collect());
+        graph
+            .reaction_use_ports
+            .insert(second, std::iter::once(port0).collect());
+        graph.startup_reactions = vec![(Level(0), first), (Level(0), second)];
+
+        // Both reactions read `port0`
+        assert_eq!(check_disjoint_levels(&graph), Ok(()));
+
+        // `first` writes `port1`
+        graph.reaction_use_ports[second] = std::iter::once(port1).collect();
+        assert!(check_disjoint_levels(&graph)
+            .unwrap_err()
+            .contains(&format!("{port1:?}")));
+        graph.startup_reactions[1].0 = Level(1);
+        assert_eq!(check_disjoint_levels(&graph), Ok(()));
+
+        graph.reaction_reactors[second] = graph.reaction_reactors[first];
+        graph.startup_reactions[1].0 = Level(0);
+        graph.reaction_use_ports[second] = std::iter::empty().collect();
+        assert!(check_disjoint_levels(&graph).is_err());
+    }
+
     #[test]
     fn test_iter_borrow_storage() {
         let (env, reaction_graph) = crate::env::tests::create_dummy_env();
@@ -343,3 +685,175 @@ pub mod tests {
         }
     }
 }
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use crate::{
+        keepalive, Action, ActionRef, InputRef, OutputRef, Port, ReactionSetLimits, Reactor,
+    };
+
+    use super::*;
+
+    /// Writes its input plus one to its output and schedules it on its action, counting its runs in the reactor state.
+    fn relay<'a>(
+        context: &'a mut Context,
+        reactor: &'a mut dyn BaseReactor,
+        ref_ports: Refs<'a, dyn BasePort>,
+        mut_ports: RefsMut<'a, dyn BasePort>,
+        actions: RefsMut<'a, dyn BaseAction>,
+    ) {
+        let [inp]: [InputRef<u32>; 1] = ref_ports.partition().unwrap();
+        let mut out: OutputRef<u32> = mut_ports.partition_mut().unwrap();
+        let mut action: ActionRef<u32> = actions.partition_mut().unwrap();
+        let value = inp.unwrap() + 1;
+        *out = Some(value);
+        action.schedule(context, value, None).unwrap();
+        *reactor.get_state_mut::<u32>().unwrap() += 1;
+    }
+
+    /// Two reactors with a reaction each at level 0, both reading `input`, and writing their own output and action.
+    fn create_relay_env() -> (Env, ReactionGraph) {
+        let mut input = Port::<u32>::new("input", PortKey::from(0));
+        *input.get_mut() = Some(41);
+        let env = Env {
+            reactors: [
+                Reactor::new("relay0", 0u32).boxed(),
+                Reactor::new("relay1", 0u32).boxed(),
+            ]
+            .into_iter()
+            .collect(),
+            reactions: [
+                Reaction::new("relay0", relay, None),
+                Reaction::new("relay1", relay, None),
+            ]
+            .into_iter()
+            .collect(),
+            actions: [
+                Action::<u32>::new("action0", ActionKey::from(0), None, true).boxed(),
+                Action::<u32>::new("action1", ActionKey::from(1), None, true).boxed(),
+            ]
+            .into_iter()
+            .collect(),
+            ports: [
+                input.boxed(),
+                Port::<u32>::new("out0", PortKey::from(1)).boxed(),
+                Port::<u32>::new("out1", PortKey::from(2)).boxed(),
+            ]
+            .into_iter()
+            .collect(),
+            probes: Vec::new(),
+            inits: Vec::new(),
+            flushes: Vec::new(),
+            parameters: Default::default(),
+        };
+
+        let reactions = [ReactionKey::from(0), ReactionKey::from(1)];
+        fn per_reaction<T>(values: [T; 2]) -> tinymap::TinySecondaryMap<ReactionKey, T> {
+            [ReactionKey::from(0), ReactionKey::from(1)]
+                .into_iter()
+                .zip(values)
+                .collect()
+        }
+        let reaction_graph = ReactionGraph {
+            action_triggers: tinymap::TinySecondaryMap::new(),
+            port_triggers: tinymap::TinySecondaryMap::new(),
+            startup_reactions: reactions.map(|key| (Level(0), key)).to_vec(),
+            shutdown_reactions: Vec::new(),
+            failure_actions: Vec::new(),
+            overload_actions: Vec::new(),
+            parameter_actions: Vec::new(),
+            conflatable_actions: Vec::new(),
+            reaction_set_limits: ReactionSetLimits {
+                max_level: 0.into(),
+                num_keys: 2,
+            },
+            reaction_set_stats: Default::default(),
+            reaction_use_ports: per_reaction(
+                [0, 0].map(|key| [PortKey::from(key)].into_iter().collect()),
+            ),
+            reaction_effect_ports: per_reaction(
+                [1, 2].map(|key| [PortKey::from(key)].into_iter().collect()),
+            ),
+            reaction_actions: per_reaction(
+                [0, 1].map(|key| [ActionKey::from(key)].into_iter().collect()),
+            ),
+            reaction_reactors: per_reaction([ReactorKey::from(0), ReactorKey::from(1)]),
+            action_reactors: tinymap::TinySecondaryMap::new(),
+            port_reactors: tinymap::TinySecondaryMap::new(),
+            reactor_bank_infos: tinymap::TinySecondaryMap::new(),
+            reactor_fqns: tinymap::TinySecondaryMap::new(),
+            reaction_fqns: tinymap::TinySecondaryMap::new(),
+            action_fqns: tinymap::TinySecondaryMap::new(),
+            port_fqns: tinymap::TinySecondaryMap::new(),
+            metadata: Default::default(),
+        };
+        (env, reaction_graph)
+    }
+
+    /// A context with its lifetime erased, so that it can be moved to a loom thread, which has no scoped threads. The
+    /// threads are always joined before the `Store` is dropped.
+    struct Unscoped(ReactionTriggerCtx<'static>);
+
+    unsafe impl Send for Unscoped {}
+
+    /// Run the reactions of a level on their own threads, with the contexts handed out by the store.
+    #[test]
+    fn loom_trigger_level() {
+        loom::model(|| {
+            let (env, reaction_graph) = create_relay_env();
+            let (event_tx, _event_rx) = crossbeam_channel::unbounded();
+            let contexts = reaction_graph
+                .reaction_reactors
+                .keys()
+                .map(|reaction_key| {
+                    let (_, shutdown_rx) = keepalive::channel();
+                    let context = Context::new(
+                        reaction_key,
+                        std::time::Instant::now(),
+                        crate::TimeScale::REAL_TIME,
+                        None,
+                        event_tx.clone(),
+                        shutdown_rx,
+                        Default::default(),
+                        Default::default(),
+                    );
+                    (reaction_key, context)
+                })
+                .collect();
+            let mut store = Store::new(env, contexts, &reaction_graph);
+
+            let keys = reaction_graph.startup_reactions.iter().map(|&(_, key)| key);
+            let handles = unsafe { store.iter_borrow_storage(keys) }
+                .map(|trigger_ctx| {
+                    // Safety: the threads are joined below, while the store is still borrowed
+                    let trigger_ctx = Unscoped(unsafe { std::mem::transmute(trigger_ctx) });
+                    loom::thread::spawn(move || {
+                        let trigger_ctx = trigger_ctx;
+                        trigger_ctx.0.trigger(Tag::ZERO).scheduled_actions.clone()
+                    })
+                })
+                .collect::<Vec<_>>();
+            let scheduled = handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                scheduled,
+                [
+                    [(ActionKey::from(0), Tag::ZERO.delay(Duration::ZERO))],
+                    [(ActionKey::from(1), Tag::ZERO.delay(Duration::ZERO))]
+                ]
+            );
+            for port_key in [PortKey::from(1), PortKey::from(2)] {
+                assert_eq!(
+                    store.get_port(port_key).debug_value().as_deref(),
+                    Some("42")
+                );
+            }
+            for reactor_key in [ReactorKey::from(0), ReactorKey::from(1)] {
+                assert_eq!(store.get_reactor(reactor_key).get_state::<u32>(), Some(&1));
+            }
+        });
+    }
+}
@@ -0,0 +1,140 @@
+//! Subscriptions to the high-level events of a running scheduler.
+//!
+//! Embedders such as a GUI rendering the execution live can call [`crate::Scheduler::subscribe`] before running the
+//! event loop, and consume the [`RuntimeEvent`]s on another thread. Events are only produced while there are
+//! subscribers, and sending never blocks the scheduler: a subscriber created with
+//! [`crate::Scheduler::subscribe_bounded`] misses the events sent while its channel is full, and subscribers whose
+//! receiver was dropped are removed.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! let mut sched = runtime::Scheduler::new(env, graph, config);
+//! let events = sched.subscribe();
+//! std::thread::spawn(move || {
+//!     for event in events {
+//!         println!("{event}");
+//!     }
+//! });
+//! sched.event_loop()?;
+//! ```
+
+use std::fmt::Display;
+
+use crossbeam_channel::{Sender, TrySendError};
+
+use crate::{Duration, Tag};
+
+/// The receiving end of a subscription.
+pub type EventReceiver = crossbeam_channel::Receiver<RuntimeEvent>;
+
+/// A high-level event of a running scheduler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RuntimeEvent {
+    /// The reactions at `tag` are about to run.
+    TagStarted { tag: Tag },
+    /// The reaction `reaction` ran at `tag`.
+    ReactionExecuted { tag: Tag, reaction: String },
+    /// A deadline of the reaction `reaction` was violated at `tag`, with physical time `lag` behind logical time.
+    DeadlineViolated {
+        tag: Tag,
+        reaction: String,
+        lag: Duration,
+    },
+    /// The reaction `reaction` ran for `elapsed` at `tag`, longer than its `budget`, see [`crate::budget`].
+    BudgetExceeded {
+        tag: Tag,
+        reaction: String,
+        elapsed: Duration,
+        budget: Duration,
+    },
+    /// All `reactions` triggered at `tag` have run.
+    TagCompleted { tag: Tag, reactions: usize },
+    /// The scheduler is about to process the shutdown `tag`.
+    ShutdownInitiated { tag: Tag },
+}
+
+impl Display for RuntimeEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeEvent::TagStarted { tag } => write!(f, "Tag {tag} started"),
+            RuntimeEvent::ReactionExecuted { tag, reaction } => {
+                write!(f, "Reaction {reaction} executed at {tag}")
+            }
+            RuntimeEvent::DeadlineViolated { tag, reaction, lag } => {
+                write!(f, "Deadline of {reaction} violated at {tag}, lagging {lag}")
+            }
+            RuntimeEvent::BudgetExceeded {
+                tag,
+                reaction,
+                elapsed,
+                budget,
+            } => write!(
+                f,
+                "Reaction {reaction} ran for {elapsed} at {tag}, over its budget of {budget}"
+            ),
+            RuntimeEvent::TagCompleted { tag, reactions } => {
+                write!(f, "Tag {tag} completed with {reactions} reactions")
+            }
+            RuntimeEvent::ShutdownInitiated { tag } => write!(f, "Shutdown initiated at {tag}"),
+        }
+    }
+}
+
+/// The senders of all subscriptions.
+#[derive(Debug, Default)]
+pub(crate) struct Subscribers {
+    senders: Vec<Sender<RuntimeEvent>>,
+}
+
+impl Subscribers {
+    /// Add a subscription, unbounded if `capacity` is `None`.
+    pub fn subscribe(&mut self, capacity: Option<usize>) -> EventReceiver {
+        let (tx, rx) = match capacity {
+            Some(capacity) => crossbeam_channel::bounded(capacity),
+            None => crossbeam_channel::unbounded(),
+        };
+        self.senders.push(tx);
+        rx
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.senders.is_empty()
+    }
+
+    /// Send `event` to all subscribers, removing those that were dropped.
+    pub fn send(&mut self, event: RuntimeEvent) {
+        self.senders.retain(|tx| match tx.try_send(event.clone()) {
+            Ok(()) | Err(TrySendError::Full(_)) => true,
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscribers() {
+        let mut subscribers = Subscribers::default();
+        assert!(subscribers.is_empty());
+        let unbounded = subscribers.subscribe(None);
+        let bounded = subscribers.subscribe(Some(1));
+        let dropped = subscribers.subscribe(None);
+        drop(dropped);
+
+        let tag = Tag::ZERO;
+        subscribers.send(RuntimeEvent::TagStarted { tag });
+        subscribers.send(RuntimeEvent::TagCompleted { tag, reactions: 0 });
+        assert_eq!(subscribers.senders.len(), 2);
+
+        assert_eq!(unbounded.try_iter().count(), 2);
+        // The second event was missed while the bounded channel was full
+        assert_eq!(
+            bounded.try_iter().collect::<Vec<_>>(),
+            [RuntimeEvent::TagStarted { tag }]
+        );
+    }
+}
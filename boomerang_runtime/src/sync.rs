@@ -0,0 +1,20 @@
+//! Synchronization primitives shared between the scheduler and other threads.
+//!
+//! These are replaced by their [loom](https://docs.rs/loom) models when built with `--cfg loom`, so the loom tests can
+//! check all interleavings of the threads using them:
+//!
+//! ```sh
+//! RUSTFLAGS="--cfg loom" cargo test -p boomerang_runtime --release --lib loom
+//! ```
+
+#[cfg(loom)]
+pub(crate) use loom::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+
+#[cfg(not(loom))]
+pub(crate) use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
@@ -82,6 +82,14 @@ impl Tag {
         }
     }
 
+    /// Create a new Tag `microsteps` microsteps after the current, at the same logical time.
+    pub fn delay_microsteps(&self, microsteps: usize) -> Self {
+        Self {
+            offset: self.offset,
+            microstep: self.microstep.saturating_add(microsteps),
+        }
+    }
+
     /// Create a new Tag offset strictly in the past from the current.
     pub fn pre(&self, offset: Duration) -> Self {
         if offset.is_zero() {
@@ -117,3 +125,186 @@ impl Tag {
         }
     }
 }
+
+/// The rate of logical time relative to physical (wall-clock) time in real-time execution, see
+/// [`crate::Config::with_time_scale`].
+///
+/// A scale of `10` runs logical time ten times faster than the wall clock, and `1/10` ten times slower. The scale is kept
+/// as an exact ratio, and times are always converted from the start of execution rather than accumulated, so scaled
+/// execution doesn't drift from the wall clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeScale {
+    /// Logical time elapsed per `physical` units of physical time
+    logical: u64,
+    physical: u64,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self::REAL_TIME
+    }
+}
+
+impl std::fmt::Display for TimeScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.physical == 1 {
+            write!(f, "{}", self.logical)
+        } else {
+            write!(f, "{}/{}", self.logical, self.physical)
+        }
+    }
+}
+
+impl std::str::FromStr for TimeScale {
+    type Err = String;
+
+    /// Parse a ratio `logical/physical`, e.g. `1/3`, or a decimal scale, e.g. `0.1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((logical, physical)) => {
+                let parse = |part: &str| part.trim().parse::<u64>().map_err(|err| err.to_string());
+                Self::new(parse(logical)?, parse(physical)?)
+            }
+            None => Self::from_f64(s.trim().parse::<f64>().map_err(|err| err.to_string())?),
+        }
+        .ok_or_else(|| "expected a strictly positive scale".to_owned())
+    }
+}
+
+impl TimeScale {
+    /// Logical time runs at the rate of the wall clock.
+    pub const REAL_TIME: Self = Self {
+        logical: 1,
+        physical: 1,
+    };
+
+    /// The largest numerator or denominator of a scale approximated with [`TimeScale::from_f64`].
+    pub const MAX_TERM: u64 = 1_000_000;
+
+    /// Create a scale where `logical` time elapses for every `physical` time, if both are non-zero.
+    pub fn new(logical: u64, physical: u64) -> Option<Self> {
+        if logical == 0 || physical == 0 {
+            return None;
+        }
+        let (mut a, mut b) = (logical, physical);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        Some(Self {
+            logical: logical / a,
+            physical: physical / a,
+        })
+    }
+
+    /// Create the ratio closest to `scale` with terms of at most [`TimeScale::MAX_TERM`], if `scale` is finite and
+    /// strictly positive.
+    pub fn from_f64(scale: f64) -> Option<Self> {
+        if !scale.is_finite() || scale <= 0.0 {
+            return None;
+        }
+        // Convergents of the continued fraction of `scale`
+        let (mut p0, mut q0, mut p1, mut q1) = (0u64, 1u64, 1u64, 0u64);
+        let mut x = scale;
+        loop {
+            let a = x.floor();
+            if a > Self::MAX_TERM as f64 {
+                break;
+            }
+            let a = a as u64;
+            let (p2, q2) = (a * p1 + p0, a * q1 + q0);
+            if p2 > Self::MAX_TERM || q2 > Self::MAX_TERM {
+                break;
+            }
+            (p0, q0, p1, q1) = (p1, q1, p2, q2);
+            let fraction = x - a as f64;
+            if fraction < 1e-12 {
+                break;
+            }
+            x = fraction.recip();
+        }
+        // Scales too small or too large to approximate are clamped
+        Self::new(p1.max(1), q1.max(1))
+    }
+
+    /// The scale as a floating-point factor.
+    pub fn as_f64(&self) -> f64 {
+        self.logical as f64 / self.physical as f64
+    }
+
+    /// The logical time elapsed over the physical duration `physical`.
+    pub fn to_logical(&self, physical: Duration) -> Duration {
+        Self::mul_div(physical, self.logical, self.physical)
+    }
+
+    /// The physical duration over which the logical time `logical` elapses.
+    pub fn to_physical(&self, logical: Duration) -> Duration {
+        Self::mul_div(logical, self.physical, self.logical)
+    }
+
+    /// The tag at the physical time `time`, with logical time starting at `origin`.
+    pub fn tag_at(&self, origin: std::time::Instant, time: std::time::Instant) -> Tag {
+        Tag::new(self.to_logical(time.signed_duration_since(origin)), 0)
+    }
+
+    /// The physical time at which `tag` is reached, with logical time starting at `origin`.
+    pub fn instant_of(&self, origin: std::time::Instant, tag: Tag) -> std::time::Instant {
+        origin + self.to_physical(tag.offset())
+    }
+
+    fn mul_div(duration: Duration, mul: u64, div: u64) -> Duration {
+        if mul == div {
+            return duration;
+        }
+        let nanos = duration.whole_nanoseconds() * mul as i128 / div as i128;
+        let seconds = nanos / 1_000_000_000;
+        if seconds > i64::MAX as i128 {
+            Duration::MAX
+        } else if seconds < i64::MIN as i128 {
+            Duration::MIN
+        } else {
+            Duration::new(seconds as i64, (nanos % 1_000_000_000) as i32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_scale() {
+        assert_eq!(TimeScale::from_f64(1.0), Some(TimeScale::REAL_TIME));
+        assert_eq!(TimeScale::from_f64(10.0), TimeScale::new(10, 1));
+        assert_eq!(TimeScale::from_f64(0.1), TimeScale::new(1, 10));
+        assert_eq!(TimeScale::from_f64(2.5), TimeScale::new(5, 2));
+        assert_eq!(TimeScale::from_f64(1.0 / 3.0), TimeScale::new(1, 3));
+        assert_eq!(TimeScale::from_f64(0.0), None);
+        assert_eq!(TimeScale::from_f64(f64::NAN), None);
+        assert_eq!(TimeScale::new(4, 6).unwrap().to_string(), "2/3");
+        assert_eq!("1/3".parse(), Ok(TimeScale::new(1, 3).unwrap()));
+        assert_eq!("10".parse(), Ok(TimeScale::new(10, 1).unwrap()));
+        assert!("-1".parse::<TimeScale>().is_err());
+
+        // Exact over long runs
+        let third = TimeScale::new(1, 3).unwrap();
+        let day = Duration::days(1);
+        assert_eq!(third.to_physical(day), Duration::days(3));
+        assert_eq!(third.to_logical(third.to_physical(day)), day);
+        assert_eq!(
+            third.to_logical(Duration::nanoseconds(1_000_000_000_000_001)),
+            Duration::nanoseconds(333_333_333_333_333)
+        );
+
+        let origin = std::time::Instant::now();
+        let tag = Tag::new(Duration::seconds(2), 3);
+        assert_eq!(
+            third.instant_of(origin, tag),
+            origin + std::time::Duration::from_secs(6)
+        );
+        assert_eq!(
+            third.tag_at(origin, origin + std::time::Duration::from_secs(6)),
+            Tag::new(Duration::seconds(2), 0)
+        );
+    }
+}
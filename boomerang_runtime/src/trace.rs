@@ -0,0 +1,293 @@
+//! Per-reaction execution traces and critical path analysis.
+//!
+//! When enabled with [`crate::Config::with_trace`], the [`crate::Scheduler`] records a [`ReactionSpan`] with the
+//! wall-clock start and duration of every reaction it runs, grouped by tag into an [`ExecutionTrace`]. The trace grows
+//! for the whole run, so it is meant for profiling runs rather than production.
+//!
+//! Since all reactions at a level must complete before the next level starts, the completion time of a tag is
+//! determined by the last reaction to finish at each level. [`analyze`] follows this chain of reactions as the
+//! critical path of each tag, and aggregates over the run which reactions dominate latency. Reactions are told apart by
+//! their [`ReactionKey`], e.g. in the reactors of a bank, and shown by their fully-qualified names. The resulting
+//! [`TraceAnalysis`] is serializable with the `serde` feature, and its `Display` output is a human-readable summary.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! let mut sched = runtime::Scheduler::new(env, graph, runtime::Config::default().with_trace(true));
+//! sched.event_loop().unwrap();
+//! let analysis = runtime::trace::analyze(sched.trace());
+//! println!("{analysis}");
+//! ```
+
+use std::{collections::HashMap, fmt::Display, time::Duration};
+
+use crate::{Level, ReactionKey, Tag};
+
+/// The execution of a single reaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReactionSpan {
+    pub key: ReactionKey,
+    /// The fully-qualified name of the reaction
+    pub reaction: String,
+    /// The level the reaction ran at
+    pub level: Level,
+    /// Wall-clock time from the start of the tag to the start of the reaction
+    pub start: Duration,
+    /// Wall-clock duration of the reaction
+    pub duration: Duration,
+}
+
+impl ReactionSpan {
+    /// Wall-clock time from the start of the tag to the end of the reaction.
+    pub fn end(&self) -> Duration {
+        self.start + self.duration
+    }
+}
+
+/// The reactions run at a single tag, in order of level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TagTrace {
+    pub tag: Tag,
+    pub spans: Vec<ReactionSpan>,
+}
+
+/// The reactions run over a whole run, see the [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionTrace {
+    pub tags: Vec<TagTrace>,
+}
+
+/// The critical path of a single tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CriticalPath {
+    pub tag: Tag,
+    /// The fully-qualified names of the last reaction to finish at each level, in order of level
+    pub reactions: Vec<String>,
+    /// The sum of the durations of the reactions on the critical path
+    pub length: Duration,
+    /// Wall-clock time from the start of the tag to the end of its last reaction
+    pub completion: Duration,
+}
+
+/// Aggregated statistics of a single reaction over a run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReactionProfile {
+    pub key: ReactionKey,
+    /// The fully-qualified name of the reaction
+    pub reaction: String,
+    /// The number of times the reaction ran
+    pub invocations: usize,
+    /// The total duration of all invocations
+    pub total: Duration,
+    /// The number of tags at which the reaction was on the critical path
+    pub critical_invocations: usize,
+    /// The total duration of the invocations on the critical path
+    pub critical_total: Duration,
+}
+
+/// The result of [`analyze`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraceAnalysis {
+    /// The critical path of each traced tag
+    pub paths: Vec<CriticalPath>,
+    /// All traced reactions, ordered by decreasing time spent on the critical path
+    pub reactions: Vec<ReactionProfile>,
+}
+
+impl TraceAnalysis {
+    /// The sum of the critical path lengths of all tags.
+    pub fn critical_total(&self) -> Duration {
+        self.paths.iter().map(|path| path.length).sum()
+    }
+
+    /// The `n` reactions contributing most to the critical paths.
+    pub fn dominant(&self, n: usize) -> &[ReactionProfile] {
+        let n = self
+            .reactions
+            .iter()
+            .take(n)
+            .take_while(|profile| profile.critical_invocations > 0)
+            .count();
+        &self.reactions[..n]
+    }
+}
+
+impl Display for TraceAnalysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let critical_total = self.critical_total();
+        writeln!(
+            f,
+            "{} tags traced, {critical_total:?} on critical paths",
+            self.paths.len()
+        )?;
+        if let Some(slowest) = self.paths.iter().max_by_key(|path| path.length) {
+            writeln!(
+                f,
+                "Slowest tag {}: {:?} via [{}]",
+                slowest.tag,
+                slowest.length,
+                slowest.reactions.join(" -> ")
+            )?;
+        }
+        writeln!(f, "Reactions dominating latency:")?;
+        for profile in self.dominant(5) {
+            let share = if critical_total.is_zero() {
+                0.0
+            } else {
+                100.0 * profile.critical_total.as_secs_f64() / critical_total.as_secs_f64()
+            };
+            writeln!(
+                f,
+                "  {:5.1}% {} (critical in {}/{} invocations, {:?} total)",
+                share,
+                profile.reaction,
+                profile.critical_invocations,
+                profile.invocations,
+                profile.total
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Compute the critical path of each tag in `trace` and aggregate them over the run, see the
+/// [module documentation](self).
+pub fn analyze(trace: &ExecutionTrace) -> TraceAnalysis {
+    let mut profiles: HashMap<ReactionKey, ReactionProfile> = HashMap::new();
+
+    let paths = trace
+        .tags
+        .iter()
+        .map(|tag_trace| {
+            for span in &tag_trace.spans {
+                let profile = profiles.entry(span.key).or_insert_with(|| ReactionProfile {
+                    key: span.key,
+                    reaction: span.reaction.clone(),
+                    ..Default::default()
+                });
+                profile.invocations += 1;
+                profile.total += span.duration;
+            }
+
+            // The last reaction to finish at each level
+            let mut critical: Vec<&ReactionSpan> = Vec::new();
+            for span in &tag_trace.spans {
+                match critical.last_mut() {
+                    Some(last) if last.level == span.level => {
+                        if span.end() > last.end() {
+                            *last = span;
+                        }
+                    }
+                    _ => critical.push(span),
+                }
+            }
+
+            for span in &critical {
+                // Every span was already profiled above
+                let profile = profiles.get_mut(&span.key).unwrap();
+                profile.critical_invocations += 1;
+                profile.critical_total += span.duration;
+            }
+
+            CriticalPath {
+                tag: tag_trace.tag,
+                reactions: critical.iter().map(|span| span.reaction.clone()).collect(),
+                length: critical.iter().map(|span| span.duration).sum(),
+                completion: tag_trace
+                    .spans
+                    .iter()
+                    .map(ReactionSpan::end)
+                    .max()
+                    .unwrap_or_default(),
+            }
+        })
+        .collect();
+
+    let mut reactions: Vec<_> = profiles.into_values().collect();
+    reactions.sort_by(|a, b| {
+        b.critical_total
+            .cmp(&a.critical_total)
+            .then_with(|| a.reaction.cmp(&b.reaction))
+    });
+
+    TraceAnalysis { paths, reactions }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(
+        key: usize,
+        reaction: &str,
+        level: usize,
+        start_us: u64,
+        duration_us: u64,
+    ) -> ReactionSpan {
+        ReactionSpan {
+            key: ReactionKey::from(key),
+            reaction: reaction.to_owned(),
+            level: Level::from(level),
+            start: Duration::from_micros(start_us),
+            duration: Duration::from_micros(duration_us),
+        }
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let trace = ExecutionTrace {
+            tags: vec![
+                TagTrace {
+                    tag: Tag::ZERO,
+                    spans: vec![
+                        span(0, "a", 0, 0, 10),
+                        span(1, "b", 1, 10, 5),
+                        span(2, "c", 1, 10, 30),
+                        span(3, "d", 2, 40, 5),
+                    ],
+                },
+                TagTrace {
+                    tag: Tag::new(crate::Duration::milliseconds(1), 0),
+                    spans: vec![span(1, "b", 1, 0, 20), span(2, "c", 1, 0, 10)],
+                },
+            ],
+        };
+
+        let analysis = analyze(&trace);
+        assert_eq!(analysis.paths[0].reactions, ["a", "c", "d"]);
+        assert_eq!(analysis.paths[0].length, Duration::from_micros(45));
+        assert_eq!(analysis.paths[0].completion, Duration::from_micros(45));
+        assert_eq!(analysis.paths[1].reactions, ["b"]);
+
+        let dominant = analysis.dominant(2);
+        assert_eq!(dominant[0].reaction, "c");
+        assert_eq!(dominant[0].critical_invocations, 1);
+        assert_eq!(dominant[0].invocations, 2);
+        assert_eq!(dominant[1].reaction, "b");
+        assert!(analysis.to_string().contains("Slowest tag"));
+    }
+
+    #[test]
+    fn test_profiles_by_key() {
+        // Two reactions with the same name, e.g. the reactions of a bank
+        let trace = ExecutionTrace {
+            tags: vec![TagTrace {
+                tag: Tag::ZERO,
+                spans: vec![span(0, "a", 0, 0, 10), span(1, "a", 0, 0, 20)],
+            }],
+        };
+
+        let analysis = analyze(&trace);
+        assert_eq!(analysis.reactions.len(), 2);
+        assert_eq!(analysis.reactions[0].key, ReactionKey::from(1));
+        assert_eq!(analysis.reactions[0].critical_invocations, 1);
+        assert_eq!(analysis.reactions[1].invocations, 1);
+        assert_eq!(analysis.reactions[1].critical_invocations, 0);
+    }
+}
@@ -0,0 +1,107 @@
+//! Human-readable formatting of port values in debug output.
+//!
+//! Values are only included in the `Debug` and `Display` output of ports (and in dumps of the [`crate::Env`]) when
+//! enabled for a scheduler with [`crate::Config::with_debug_values`], so production runs don't pay for formatting. The
+//! setting is stored in the ports of each scheduler, so schedulers in the same process don't affect each other. Since
+//! port values are not required to implement `Debug`, the formatter for each value type is looked up in a registry.
+//! Common primitive types are registered by default, other types are added with [`register`].
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::Debug,
+    sync::{OnceLock, RwLock},
+};
+
+type DebugFn = fn(&dyn Any, &mut std::fmt::Formatter<'_>) -> std::fmt::Result;
+
+fn debug_any<T: Debug + 'static>(
+    value: &dyn Any,
+    f: &mut std::fmt::Formatter<'_>,
+) -> std::fmt::Result {
+    match value.downcast_ref::<T>() {
+        Some(value) => value.fmt(f),
+        None => f.write_str("<type mismatch>"),
+    }
+}
+
+fn registry() -> &'static RwLock<HashMap<TypeId, DebugFn>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<TypeId, DebugFn>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut map = HashMap::new();
+        macro_rules! register_defaults {
+            ($($t:ty),*) => {
+                $(map.insert(TypeId::of::<$t>(), debug_any::<$t> as DebugFn);)*
+            };
+        }
+        register_defaults!(
+            (),
+            bool,
+            char,
+            u8,
+            u16,
+            u32,
+            u64,
+            u128,
+            usize,
+            i8,
+            i16,
+            i32,
+            i64,
+            i128,
+            isize,
+            f32,
+            f64,
+            String,
+            &'static str,
+            crate::Duration,
+            crate::Tag
+        );
+        RwLock::new(map)
+    })
+}
+
+/// Register `T` so that its values are included in debug output.
+pub fn register<T: Debug + 'static>() {
+    registry()
+        .write()
+        .expect("Value formatter registry poisoned")
+        .insert(TypeId::of::<T>(), debug_any::<T>);
+}
+
+/// A value that can be formatted with the registered formatter for its type.
+pub struct DebugValue<'a> {
+    value: &'a dyn Any,
+    debug_fn: DebugFn,
+}
+
+impl Debug for DebugValue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (self.debug_fn)(self.value, f)
+    }
+}
+
+/// Get a formattable view of `value`, if a formatter is registered for `T`.
+pub fn debug_value<T: 'static>(value: &T) -> Option<DebugValue<'_>> {
+    let debug_fn = *registry()
+        .read()
+        .expect("Value formatter registry poisoned")
+        .get(&TypeId::of::<T>())?;
+    Some(DebugValue { value, debug_fn })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Custom;
+
+    #[test]
+    fn test_debug_value() {
+        assert_eq!(format!("{:?}", debug_value(&5u32).unwrap()), "5");
+        assert!(debug_value(&Custom).is_none());
+        register::<Custom>();
+        assert_eq!(format!("{:?}", debug_value(&Custom).unwrap()), "Custom");
+    }
+}
@@ -0,0 +1,72 @@
+//! Wildcard matching shared by the name patterns of the runtime, the builder and the utilities.
+//!
+//! [`wildcard_match`] matches a sequence against a pattern in which some elements match any number of elements, like
+//! `*` in a glob or `**` in a path pattern. It backtracks only to the most recent wildcard, so it takes at most
+//! `pattern.len() * text.len()` steps.
+
+/// Match `text` against `pattern`, where the pattern elements for which `is_wildcard` holds match any sequence of
+/// elements, including none, and all other pattern elements match a single element for which `matches` holds.
+pub fn wildcard_match<P, T>(
+    pattern: &[P],
+    text: &[T],
+    is_wildcard: impl Fn(&P) -> bool,
+    matches: impl Fn(&P, &T) -> bool,
+) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // The position after the most recent wildcard, and the text position it was retried at
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(wildcard) if is_wildcard(wildcard) => {
+                p += 1;
+                backtrack = Some((p, t));
+            }
+            Some(element) if matches(element, &text[t]) => {
+                p += 1;
+                t += 1;
+            }
+            // Let the most recent wildcard match one more element
+            _ => match backtrack {
+                Some((wildcard_p, wildcard_t)) => {
+                    p = wildcard_p;
+                    t = wildcard_t + 1;
+                    backtrack = Some((wildcard_p, t));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(is_wildcard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str, text: &str) -> bool {
+        wildcard_match(
+            pattern.as_bytes(),
+            text.as_bytes(),
+            |&c| c == b'*',
+            |p, t| p == t,
+        )
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(glob("", ""));
+        assert!(glob("*", ""));
+        assert!(glob("**", "abc"));
+        assert!(glob("a*c", "abbbc"));
+        assert!(glob("a*b*c", "aXbYbZc"));
+        assert!(glob("*::out", "main::source::out"));
+        assert!(!glob("*::out", "main::source::outs"));
+        assert!(!glob("a*c", "abcd"));
+        assert!(!glob("abc", "ab"));
+        assert!(!glob("", "a"));
+
+        // Exponential for a recursive backtracking matcher
+        let text = "a".repeat(100);
+        assert!(!glob(&format!("{}b", "a*".repeat(50)), &text));
+    }
+}
@@ -0,0 +1,267 @@
+//! A compact bitset of keys
+//!
+//! [`TinyBitSet`] stores the keys of a fixed key space as bits in a boxed slice of words, and supports the set algebra
+//! on whole sets in one pass over the words. Unlike [`super::KeySet`], its capacity is fixed when it's created, and
+//! [`TinyBitSet::insert`] reports whether the key was new, so it can be used to skip keys already seen, e.g. the
+//! reactions already queued at a tag.
+use std::marker::PhantomData;
+
+use super::Key;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A set of keys with a fixed capacity.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TinyBitSet<K: Key> {
+    words: Box<[u64]>,
+    _k: PhantomData<K>,
+}
+
+impl<K: Key + std::fmt::Debug> std::fmt::Debug for TinyBitSet<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Key> Default for TinyBitSet<K> {
+    fn default() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
+impl<K: Key> TinyBitSet<K> {
+    /// Construct a new, empty [`TinyBitSet`] for keys with an index below `capacity`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            words: vec![0; capacity.div_ceil(BITS)].into_boxed_slice(),
+            _k: PhantomData,
+        }
+    }
+
+    /// The number of keys the set can hold.
+    pub fn capacity(&self) -> usize {
+        self.words.len() * BITS
+    }
+
+    /// Returns `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// The number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns `true` if `key` is in the set.
+    #[inline]
+    pub fn contains(&self, key: K) -> bool {
+        let index = key.index();
+        self.words
+            .get(index / BITS)
+            .is_some_and(|word| word & (1 << (index % BITS)) != 0)
+    }
+
+    /// Insert a key into the set, returning `true` if it wasn't in the set yet.
+    ///
+    /// # Panics
+    ///
+    /// If the index of `key` is beyond the capacity of the set.
+    #[inline]
+    pub fn insert(&mut self, key: K) -> bool {
+        let index = key.index();
+        let word = &mut self.words[index / BITS];
+        let mask = 1 << (index % BITS);
+        let new = *word & mask == 0;
+        *word |= mask;
+        new
+    }
+
+    /// Extend the set from an iterable.
+    #[inline]
+    pub fn extend(&mut self, keys: impl IntoIterator<Item = K>) {
+        for key in keys {
+            self.insert(key);
+        }
+    }
+
+    /// Clear the set.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Add all keys of `other` to this set.
+    ///
+    /// # Panics
+    ///
+    /// If `other` has keys beyond the capacity of this set.
+    pub fn union_with(&mut self, other: &Self) {
+        for (index, &word) in other.words.iter().enumerate() {
+            if word != 0 {
+                self.words[index] |= word;
+            }
+        }
+    }
+
+    /// Keep only the keys that are also in `other`.
+    pub fn intersect_with(&mut self, other: &Self) {
+        for (index, word) in self.words.iter_mut().enumerate() {
+            *word &= other.words.get(index).copied().unwrap_or(0);
+        }
+    }
+
+    /// Remove all keys of `other` from this set.
+    pub fn difference_with(&mut self, other: &Self) {
+        for (word, &other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word &= !other;
+        }
+    }
+
+    /// Returns `true` if the sets have no keys in common.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .all(|(word, other)| word & other == 0)
+    }
+
+    /// Returns `true` if all keys of this set are in `other`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .enumerate()
+            .all(|(index, &word)| word & !other.words.get(index).copied().unwrap_or(0) == 0)
+    }
+
+    /// Returns an iterator over the keys in the set, in increasing order of index.
+    pub fn iter(&self) -> Iter<'_, K> {
+        Iter {
+            words: self.words.iter(),
+            base: 0,
+            word: 0,
+            _k: PhantomData,
+        }
+    }
+}
+
+/// An iterator over the keys of a [`TinyBitSet`].
+pub struct Iter<'a, K: Key> {
+    words: std::slice::Iter<'a, u64>,
+    /// The index of the first bit of `word`
+    base: usize,
+    /// The bits of the current word not yielded yet
+    word: u64,
+    _k: PhantomData<K>,
+}
+
+impl<'a, K: Key> Iterator for Iter<'a, K> {
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.word == 0 {
+            self.word = *self.words.next()?;
+            self.base += BITS;
+        }
+        let bit = self.word.trailing_zeros() as usize;
+        // Clear the lowest set bit
+        self.word &= self.word - 1;
+        Some(K::from(self.base - BITS + bit))
+    }
+}
+
+impl<'a, K: Key> IntoIterator for &'a TinyBitSet<K> {
+    type Item = K;
+    type IntoIter = Iter<'a, K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Key> FromIterator<K> for TinyBitSet<K> {
+    /// Collect the keys into a set just large enough to hold the largest one.
+    fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
+        let keys = iter.into_iter().collect::<Vec<_>>();
+        let capacity = keys.iter().map(|key| key.index() + 1).max().unwrap_or(0);
+        let mut set = Self::with_capacity(capacity);
+        set.extend(keys);
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DefaultKey;
+
+    fn set(keys: &[usize]) -> TinyBitSet<DefaultKey> {
+        let mut set = TinyBitSet::with_capacity(200);
+        set.extend(keys.iter().map(|&key| DefaultKey::from(key)));
+        set
+    }
+
+    fn keys(set: &TinyBitSet<DefaultKey>) -> Vec<usize> {
+        set.iter().map(|key| key.index()).collect()
+    }
+
+    #[test]
+    fn test_insert_and_contains() {
+        let mut set = TinyBitSet::with_capacity(100);
+        assert!(set.is_empty());
+        assert_eq!(set.capacity(), 128);
+
+        assert!(set.insert(DefaultKey::from(3)));
+        assert!(set.insert(DefaultKey::from(99)));
+        assert!(!set.insert(DefaultKey::from(3)), "Already in the set");
+        assert!(set.contains(DefaultKey::from(3)));
+        assert!(!set.contains(DefaultKey::from(4)));
+        // Beyond the capacity
+        assert!(!set.contains(DefaultKey::from(1000)));
+        assert_eq!(set.len(), 2);
+
+        set.clear();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_iter() {
+        let set = set(&[0, 2, 63, 64, 130, 199]);
+        assert_eq!(keys(&set), [0, 2, 63, 64, 130, 199]);
+        assert!(TinyBitSet::<DefaultKey>::default().iter().next().is_none());
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let a = set(&[1, 2, 70, 150]);
+        let b = set(&[2, 3, 150]);
+
+        let mut union = a.clone();
+        union.union_with(&b);
+        assert_eq!(keys(&union), [1, 2, 3, 70, 150]);
+
+        let mut intersection = a.clone();
+        intersection.intersect_with(&b);
+        assert_eq!(keys(&intersection), [2, 150]);
+
+        let mut difference = a.clone();
+        difference.difference_with(&b);
+        assert_eq!(keys(&difference), [1, 70]);
+
+        assert!(intersection.is_subset(&a));
+        assert!(!a.is_subset(&b));
+        assert!(difference.is_disjoint(&b));
+        assert!(!a.is_disjoint(&b));
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let set: TinyBitSet<DefaultKey> = [5, 70].into_iter().map(DefaultKey::from).collect();
+        assert_eq!(set.capacity(), 128);
+        assert_eq!(keys(&set), [5, 70]);
+    }
+}
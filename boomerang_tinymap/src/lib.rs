@@ -3,10 +3,12 @@
 #![doc = document_features::document_features!()]
 #![deny(clippy::all)]
 
+pub mod bit_set;
 pub mod key_set;
 pub mod map;
 pub mod secondary_map;
 
+pub use bit_set::TinyBitSet;
 pub use key_set::KeySet;
 pub use map::TinyMap;
 pub use secondary_map::TinySecondaryMap;
@@ -155,6 +155,11 @@ impl<K: Key, V> TinyMap<K, V> {
         self.data.len()
     }
 
+    /// Returns a reference to the value corresponding to the key, if it is in the map.
+    pub fn get(&self, key: K) -> Option<&V> {
+        self.data.get(key.index())
+    }
+
     pub fn is_empty(&self) -> bool {
         self.data.is_empty()
     }
@@ -219,6 +224,14 @@ mod tests {
         assert_eq!(map[key2], 20);
     }
 
+    #[test]
+    fn test_get() {
+        let mut map = TinyMap::<TestKey, _>::new();
+        let key = map.insert(10);
+        assert_eq!(map.get(key), Some(&10));
+        assert_eq!(map.get(TestKey::from(1)), None);
+    }
+
     #[test]
     fn test_len_and_is_empty() {
         let mut map = TinyMap::<TestKey, i32>::default();
@@ -0,0 +1,133 @@
+use super::{Key, TinySecondaryMap};
+
+/// A view into a single entry of a [`TinySecondaryMap`], which may either be vacant or occupied.
+///
+/// This is constructed from [`TinySecondaryMap::entry`].
+#[derive(Debug)]
+pub enum Entry<'a, K: Key, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+/// A view into an occupied entry of a [`TinySecondaryMap`].
+#[derive(Debug)]
+pub struct OccupiedEntry<'a, K: Key, V> {
+    key: K,
+    map: &'a mut TinySecondaryMap<K, V>,
+}
+
+/// A view into a vacant entry of a [`TinySecondaryMap`].
+#[derive(Debug)]
+pub struct VacantEntry<'a, K: Key, V> {
+    key: K,
+    map: &'a mut TinySecondaryMap<K, V>,
+}
+
+impl<'a, K: Key, V> Entry<'a, K, V> {
+    pub(super) fn new(map: &'a mut TinySecondaryMap<K, V>, key: K) -> Self {
+        if map.contains_key(key) {
+            Entry::Occupied(OccupiedEntry { key, map })
+        } else {
+            Entry::Vacant(VacantEntry { key, map })
+        }
+    }
+
+    /// Returns the key of this entry.
+    pub fn key(&self) -> K {
+        match self {
+            Entry::Occupied(entry) => entry.key,
+            Entry::Vacant(entry) => entry.key,
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting `default` if empty, and returns a mutable reference to the value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Ensures a value is in the entry by inserting the result of `default` if empty, and returns a mutable reference
+    /// to the value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Like [`Entry::or_insert_with`], but the `default` function is passed the key of the entry.
+    pub fn or_insert_with_key<F: FnOnce(K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = default(entry.key);
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any potential inserts.
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+impl<'a, K: Key, V: Default> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default value if empty, and returns a mutable reference to
+    /// the value.
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K: Key, V> OccupiedEntry<'a, K, V> {
+    /// Returns the key of this entry.
+    pub fn key(&self) -> K {
+        self.key
+    }
+
+    /// Returns a reference to the value in the entry.
+    pub fn get(&self) -> &V {
+        &self.map[self.key]
+    }
+
+    /// Returns a mutable reference to the value in the entry.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map[self.key]
+    }
+
+    /// Converts the entry into a mutable reference to its value, bound to the lifetime of the map.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map[self.key]
+    }
+
+    /// Replaces the value in the entry, returning the previous value.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the value from the map, returning it.
+    pub fn remove(self) -> V {
+        self.map
+            .remove(self.key)
+            .expect("OccupiedEntry has a value")
+    }
+}
+
+impl<'a, K: Key, V> VacantEntry<'a, K, V> {
+    /// Returns the key of this entry.
+    pub fn key(&self) -> K {
+        self.key
+    }
+
+    /// Inserts `value` into the entry, and returns a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.insert(self.key, value);
+        &mut self.map[self.key]
+    }
+}
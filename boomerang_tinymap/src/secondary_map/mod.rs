@@ -5,12 +5,14 @@ use std::{
     ops::{Index, IndexMut},
 };
 
-use super::Key;
+use super::{Key, TinyMap};
 
+mod entry;
 mod iter_many;
 #[cfg(feature = "serde")]
 mod serde_impl;
 
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
 pub use iter_many::IterManyMut;
 
 #[derive(Clone, PartialEq, Eq)]
@@ -77,6 +79,12 @@ pub struct ValuesIter<'a, V: 'a> {
     inner: std::iter::Flatten<std::slice::Iter<'a, Option<V>>>,
 }
 
+#[derive(Debug)]
+pub struct ValuesIterMut<'a, V: 'a> {
+    values_left: usize,
+    inner: std::iter::Flatten<std::slice::IterMut<'a, Option<V>>>,
+}
+
 impl<K: Key, V> Iterator for IntoIter<K, V> {
     type Item = (K, V);
 
@@ -159,6 +167,26 @@ impl<'a, V: 'a> ExactSizeIterator for ValuesIter<'a, V> {
     }
 }
 
+impl<'a, V: 'a> Iterator for ValuesIterMut<'a, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.inner.next()?;
+        self.values_left -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.values_left, Some(self.values_left))
+    }
+}
+
+impl<'a, V: 'a> ExactSizeIterator for ValuesIterMut<'a, V> {
+    fn len(&self) -> usize {
+        self.values_left
+    }
+}
+
 impl<K: Key, V> TinySecondaryMap<K, V> {
     /// Construct a new, empty [`TinySecondaryMap`].
     pub fn new() -> Self {
@@ -196,6 +224,33 @@ impl<K: Key, V> TinySecondaryMap<K, V> {
         }
     }
 
+    /// Removes the value at the given `key` from the map, returning it if it was present.
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        let value = self.data.get_mut(key.index())?.take()?;
+        self.num_values -= 1;
+        Some(value)
+    }
+
+    /// Gets the entry at the given `key` for in-place manipulation.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        Entry::new(self, key)
+    }
+
+    /// Retains only the values for which `f` returns `true`, removing all others.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(K, &mut V) -> bool,
+    {
+        for (idx, slot) in self.data.iter_mut().enumerate() {
+            if let Some(value) = slot {
+                if !f(K::from(idx), value) {
+                    *slot = None;
+                    self.num_values -= 1;
+                }
+            }
+        }
+    }
+
     pub fn extend(&mut self, values: impl IntoIterator<Item = (K, V)>) {
         for (key, value) in values {
             self.insert(key, value);
@@ -262,6 +317,22 @@ impl<K: Key, V> TinySecondaryMap<K, V> {
             inner: self.data.iter().flatten(),
         }
     }
+
+    /// Returns an iterator over mutable references to the values in the map, ordered by key.
+    pub fn values_mut(&mut self) -> ValuesIterMut<'_, V> {
+        ValuesIterMut {
+            values_left: self.num_values,
+            inner: self.data.iter_mut().flatten(),
+        }
+    }
+
+    /// Returns an iterator over the keys of the `primary` map that have no value in this map.
+    pub fn missing_keys<'a, V2>(
+        &'a self,
+        primary: &'a TinyMap<K, V2>,
+    ) -> impl Iterator<Item = K> + 'a {
+        primary.keys().filter(move |&key| !self.contains_key(key))
+    }
 }
 
 impl<K: Key, V> Extend<(K, V)> for TinySecondaryMap<K, V> {
@@ -425,6 +496,66 @@ mod tests {
         assert_eq!(iter_mut.len(), 0);
     }
 
+    #[test]
+    fn test_entry() {
+        let mut map = TinySecondaryMap::<DefaultKey, Vec<u32>>::new();
+        map.entry(DefaultKey(2)).or_default().push(1);
+        map.entry(DefaultKey(2)).or_default().push(2);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map[DefaultKey(2)], [1, 2]);
+
+        let value = map
+            .entry(DefaultKey(0))
+            .and_modify(|v| v.push(0))
+            .or_insert_with(|| vec![3]);
+        assert_eq!(value, &[3]);
+        map.entry(DefaultKey(0)).and_modify(|v| v.push(4));
+        assert_eq!(map[DefaultKey(0)], [3, 4]);
+
+        match map.entry(DefaultKey(2)) {
+            Entry::Occupied(entry) => assert_eq!(entry.remove(), [1, 2]),
+            Entry::Vacant(_) => panic!("expected an occupied entry"),
+        }
+        assert!(matches!(map.entry(DefaultKey(5)), Entry::Vacant(_)));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.keys().collect::<Vec<_>>(), [DefaultKey(0)]);
+    }
+
+    #[test]
+    fn test_retain_values_mut() {
+        let mut map: TinySecondaryMap<DefaultKey, _> = (0..6).map(|i| (DefaultKey(i), i)).collect();
+        map.retain(|key, value| {
+            *value *= 10;
+            key.0 % 2 == 0
+        });
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), [0, 20, 40]);
+
+        let mut values = map.values_mut();
+        assert_eq!(values.len(), 3);
+        *values.next().unwrap() += 1;
+        assert_eq!(values.len(), 2);
+        assert_eq!(map[DefaultKey(0)], 1);
+        assert_eq!(map.remove(DefaultKey(2)), Some(20));
+        assert_eq!(map.remove(DefaultKey(2)), None);
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn test_missing_keys() {
+        let mut primary = TinyMap::<DefaultKey, _>::new();
+        let keys = (0..4).map(|i| primary.insert(i)).collect::<Vec<_>>();
+        let mut map = TinySecondaryMap::new();
+        map.insert(keys[1], ());
+        map.insert(keys[3], ());
+        assert_eq!(
+            map.missing_keys(&primary).collect::<Vec<_>>(),
+            [keys[0], keys[2]]
+        );
+        map.extend([(keys[0], ()), (keys[2], ())]);
+        assert_eq!(map.missing_keys(&primary).next(), None);
+    }
+
     #[test]
     #[cfg(feature = "serde")]
     fn test_serialize_roundtrip() {
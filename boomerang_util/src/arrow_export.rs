@@ -0,0 +1,507 @@
+//! Export of recorded port and action values as Arrow record batches and Parquet files.
+//!
+//! [`ArrowExport`] adds a top-level recorder reactor, and records the values of ports and actions into columns of its
+//! [`ArrowRecorder`] state, each with the elapsed logical time and microstep of its tag:
+//! - Ports are tapped with [`EnvBuilder::tap_connection`], so their consumers are not disturbed.
+//! - Actions are read by a reaction injected into their reactor, which forwards the values to the recorder on a new
+//!   output port named `arrow_<action>`.
+//!
+//! Values of the primitive types implementing [`ArrowValue`] are stored in a column of the matching Arrow type, any
+//! other `serde` type can be recorded as JSON strings with [`ArrowExport::record_json`].
+//!
+//! After the run, [`ArrowRecorder::record_batches`] returns one record batch per recorded element, and
+//! [`ArrowRecorder::write_parquet`] writes them to Parquet files partitioned by reactor FQN, in the Hive layout
+//! `<dir>/reactor=<reactor FQN>/<name>.parquet`, with the `::` separators of the FQN replaced by `.`. The files can be
+//! read e.g. with DuckDB's `read_parquet('<dir>/*/*.parquet', hive_partitioning = true, union_by_name = true)`.
+//! [`ArrowExport::write_parquet_at_shutdown`] writes them in a flush at the end of the run instead.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! let export = ArrowExport::new("arrow_export", &mut env_builder)?;
+//! export.record::<f64>(&mut env_builder, "main::imu::accel")?;
+//! export.record_json::<Pose>(&mut env_builder, "main::localizer::pose")?;
+//! export.write_parquet_at_shutdown(&mut env_builder, "recording")?;
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use arrow::{
+    array::{
+        ArrayBuilder, BooleanBuilder, Float32Builder, Float64Builder, Int16Builder, Int32Builder,
+        Int64Builder, Int8Builder, StringBuilder, UInt16Builder, UInt32Builder, UInt64Builder,
+        UInt8Builder,
+    },
+    datatypes::{Field, Schema},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+use boomerang::{
+    builder::{reaction_closure, BuilderReactorKey, TriggerMode, TypedActionKey},
+    prelude::*,
+};
+
+/// A type of values recorded into an Arrow column of the matching type.
+pub trait ArrowValue: runtime::ReactorData + Clone {
+    /// The builder of the value column
+    type Builder: ArrayBuilder + Default;
+
+    fn append(builder: &mut Self::Builder, value: &Self);
+}
+
+macro_rules! impl_arrow_value {
+    ($($ty:ty => $builder:ty),+ $(,)?) => {
+        $(
+            impl ArrowValue for $ty {
+                type Builder = $builder;
+
+                fn append(builder: &mut Self::Builder, value: &Self) {
+                    builder.append_value(value.clone());
+                }
+            }
+        )+
+    };
+}
+
+impl_arrow_value! {
+    bool => BooleanBuilder,
+    i8 => Int8Builder,
+    i16 => Int16Builder,
+    i32 => Int32Builder,
+    i64 => Int64Builder,
+    u8 => UInt8Builder,
+    u16 => UInt16Builder,
+    u32 => UInt32Builder,
+    u64 => UInt64Builder,
+    f32 => Float32Builder,
+    f64 => Float64Builder,
+    String => StringBuilder,
+}
+
+/// The recorded values of a port or action.
+struct Column {
+    /// The FQN of the reactor of the recorded element
+    reactor: String,
+    /// The name of the recorded element
+    name: String,
+    /// The elapsed logical time of each value in nanoseconds
+    elapsed_ns: Int64Builder,
+    microsteps: UInt64Builder,
+    values: Box<dyn ArrayBuilder>,
+}
+
+impl Column {
+    fn push<T>(&mut self, tag: runtime::Tag, value: &T, append: AppendFn<T>) {
+        self.elapsed_ns
+            .append_value(tag.offset().whole_nanoseconds() as i64);
+        self.microsteps.append_value(tag.microstep() as u64);
+        append(self.values.as_mut(), value);
+    }
+}
+
+impl std::fmt::Debug for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Column")
+            .field("reactor", &self.reactor)
+            .field("name", &self.name)
+            .field("len", &self.values.len())
+            .finish()
+    }
+}
+
+/// The record batch of a recorded port or action.
+#[derive(Debug, Clone)]
+pub struct RecordedBatch {
+    /// The FQN of the reactor of the recorded element
+    pub reactor: String,
+    /// The name of the recorded element
+    pub name: String,
+    /// The `elapsed_ns`, `microstep` and `value` columns
+    pub batch: RecordBatch,
+}
+
+/// The state of the recorder reactor added by [`ArrowExport`], see the [module documentation](self).
+///
+/// The columns are added at startup, in the order the elements were recorded.
+#[derive(Debug, Default)]
+pub struct ArrowRecorder {
+    columns: BTreeMap<usize, Column>,
+}
+
+impl ArrowRecorder {
+    /// The values recorded so far, one record batch per recorded port or action.
+    pub fn record_batches(&self) -> Result<Vec<RecordedBatch>, ArrowError> {
+        self.columns
+            .values()
+            .map(|column| {
+                let values = column.values.finish_cloned();
+                let schema = Schema::new(vec![
+                    Field::new("elapsed_ns", arrow::datatypes::DataType::Int64, false),
+                    Field::new("microstep", arrow::datatypes::DataType::UInt64, false),
+                    Field::new("value", values.data_type().clone(), true),
+                ]);
+                let batch = RecordBatch::try_new(
+                    Arc::new(schema),
+                    vec![
+                        Arc::new(column.elapsed_ns.finish_cloned()),
+                        Arc::new(column.microsteps.finish_cloned()),
+                        values,
+                    ],
+                )?;
+                Ok(RecordedBatch {
+                    reactor: column.reactor.clone(),
+                    name: column.name.clone(),
+                    batch,
+                })
+            })
+            .collect()
+    }
+
+    /// Write the values recorded so far to Parquet files partitioned by reactor FQN under `dir`, returning the paths
+    /// of the written files.
+    pub fn write_parquet(&self, dir: impl AsRef<Path>) -> parquet::errors::Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for recorded in self.record_batches()? {
+            let partition = dir
+                .as_ref()
+                .join(format!("reactor={}", recorded.reactor.replace("::", ".")));
+            std::fs::create_dir_all(&partition)?;
+            let path = partition.join(format!("{}.parquet", recorded.name));
+            let file = std::fs::File::create(&path)?;
+            let mut writer =
+                parquet::arrow::ArrowWriter::try_new(file, recorded.batch.schema(), None)?;
+            writer.write(&recorded.batch)?;
+            writer.close()?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+/// Creates the type-erased builder of the values of a column.
+type ValuesFn = fn() -> Box<dyn ArrayBuilder>;
+
+/// Appends a value to the type-erased builder of a column.
+type AppendFn<T> = fn(&mut dyn ArrayBuilder, &T);
+
+fn new_values<T: ArrowValue>() -> Box<dyn ArrayBuilder> {
+    Box::<T::Builder>::default()
+}
+
+fn new_json_values() -> Box<dyn ArrayBuilder> {
+    Box::new(StringBuilder::new())
+}
+
+fn append_value<T: ArrowValue>(builder: &mut dyn ArrayBuilder, value: &T) {
+    let builder = builder
+        .as_any_mut()
+        .downcast_mut::<T::Builder>()
+        .expect("Column builder type mismatch");
+    T::append(builder, value);
+}
+
+fn append_json<T: serde::Serialize>(builder: &mut dyn ArrayBuilder, value: &T) {
+    let builder = builder
+        .as_any_mut()
+        .downcast_mut::<StringBuilder>()
+        .expect("Column builder type mismatch");
+    match serde_json::to_string(value) {
+        Ok(json) => builder.append_value(json),
+        Err(err) => {
+            tracing::warn!("Unable to record a value as JSON: {err}");
+            builder.append_null();
+        }
+    }
+}
+
+/// Adds the recorder reactor and records ports and actions into it, see the [module documentation](self).
+#[derive(Debug)]
+pub struct ArrowExport {
+    reactor_key: BuilderReactorKey,
+    startup: TypedActionKey,
+    /// The number of recorded elements
+    columns: usize,
+}
+
+impl ArrowExport {
+    /// Add a top-level recorder reactor named `name`, with an [`ArrowRecorder`] state.
+    pub fn new(name: &str, env_builder: &mut EnvBuilder) -> Result<Self, BuilderError> {
+        let recorder = env_builder.add_reactor(name, None, None, ArrowRecorder::default());
+        let startup = recorder.get_startup_action();
+        Ok(Self {
+            reactor_key: recorder.finish()?,
+            startup,
+            columns: 0,
+        })
+    }
+
+    /// The key of the recorder reactor.
+    pub fn reactor_key(&self) -> BuilderReactorKey {
+        self.reactor_key
+    }
+
+    /// Record the values of the port or action with the fully-qualified name `fqn` into a column of the Arrow type
+    /// matching `T`.
+    pub fn record<T: ArrowValue>(
+        &mut self,
+        env_builder: &mut EnvBuilder,
+        fqn: &str,
+    ) -> Result<(), BuilderError> {
+        self.add_column::<T>(env_builder, fqn, new_values::<T>, append_value::<T>)
+    }
+
+    /// Record the values of the port or action with the fully-qualified name `fqn` as JSON strings, for types without
+    /// an Arrow representation. Values that fail to serialize are recorded as nulls.
+    pub fn record_json<T: runtime::ReactorData + Clone + serde::Serialize>(
+        &mut self,
+        env_builder: &mut EnvBuilder,
+        fqn: &str,
+    ) -> Result<(), BuilderError> {
+        self.add_column::<T>(env_builder, fqn, new_json_values, append_json::<T>)
+    }
+
+    /// Write the recorded values to Parquet files under `dir` in a flush at the end of the run, see
+    /// [`ArrowRecorder::write_parquet`].
+    pub fn write_parquet_at_shutdown(
+        &self,
+        env_builder: &mut EnvBuilder,
+        dir: impl Into<PathBuf>,
+    ) -> Result<(), BuilderError> {
+        let dir = dir.into();
+        let mut recorder = env_builder.get_reactor_builder(self.reactor_key)?;
+        recorder.add_flush("write_parquet", move |recorder: &mut ArrowRecorder| {
+            recorder.write_parquet(&dir).map(|_| ())
+        })?;
+        recorder.finish()?;
+        Ok(())
+    }
+
+    fn add_column<T: runtime::ReactorData + Clone>(
+        &mut self,
+        env_builder: &mut EnvBuilder,
+        fqn: &str,
+        values: ValuesFn,
+        append: AppendFn<T>,
+    ) -> Result<(), BuilderError> {
+        // Actions are forwarded to the recorder on a new output port of their reactor
+        let (port_fqn, reactor_key, name) = match env_builder.find_port_by_fqn(fqn) {
+            Ok(port_key) => {
+                let port = env_builder.get_port(port_key)?;
+                (
+                    fqn.to_owned(),
+                    port.get_reactor_key(),
+                    port.name().to_owned(),
+                )
+            }
+            Err(_) => {
+                let action_key = env_builder
+                    .find_actions_matching(fqn)?
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| BuilderError::NamedActionNotFound(fqn.to_owned()))?;
+                let action = env_builder.get_action(action_key)?;
+                if action.type_name() != std::any::type_name::<T>() {
+                    return Err(BuilderError::InconsistentBuilderState {
+                        what: format!(
+                            "Recording '{fqn}' expects values of type {}, but the action carries {}",
+                            std::any::type_name::<T>(),
+                            action.type_name()
+                        ),
+                    });
+                }
+                let reactor_key = action.reactor_key();
+                let name = action.name().to_owned();
+                let port_name = format!("arrow_{name}");
+                let port = env_builder.add_output_port::<T>(&port_name, reactor_key)?;
+                env_builder
+                    .add_reaction(
+                        &port_name,
+                        reactor_key,
+                        reaction_closure!(ctx, _reactor, _ref_ports, mut_ports, actions => {
+                            let mut action: runtime::ActionRef<T> = actions.partition_mut().unwrap();
+                            let mut port: runtime::OutputRef<T> = mut_ports.partition_mut().unwrap();
+                            *port = action.get_value(ctx).cloned();
+                        }),
+                    )
+                    .with_action(action_key, 0, TriggerMode::TriggersAndUses)?
+                    .with_port(port, 0, TriggerMode::EffectsOnly)?
+                    .finish()?;
+                let port_fqn = env_builder.port_fqn(port.into(), false)?.to_string();
+                (port_fqn, reactor_key, name)
+            }
+        };
+
+        let column = self.columns;
+        self.columns += 1;
+        let reactor_fqn = env_builder.reactor_fqn(reactor_key, false)?.to_string();
+        let tap = env_builder.tap_connection::<T>(&port_fqn, self.reactor_key)?;
+        env_builder
+            .add_reaction(
+                &format!("record_{column}"),
+                self.reactor_key,
+                reaction_closure!(ctx, reactor, ref_ports, _mut_ports, _actions => {
+                    let tap: runtime::InputRef<T> = ref_ports.partition().unwrap();
+                    let recorder = &mut reactor
+                        .downcast_mut::<runtime::Reactor<ArrowRecorder>>()
+                        .unwrap()
+                        .state;
+                    // The column is added at startup, so elements without any values still get an empty batch
+                    let column = recorder.columns.entry(column).or_insert_with(|| Column {
+                        reactor: reactor_fqn.clone(),
+                        name: name.clone(),
+                        elapsed_ns: Int64Builder::new(),
+                        microsteps: UInt64Builder::new(),
+                        values: values(),
+                    });
+                    if let Some(value) = tap.as_ref() {
+                        column.push(ctx.get_tag(), value, append);
+                    }
+                }),
+            )
+            .with_action(self.startup, 0, TriggerMode::TriggersOnly)?
+            .with_port(tap, 0, TriggerMode::TriggersAndUses)?
+            .finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Array, AsArray};
+    use arrow::datatypes::{Int64Type, UInt32Type, UInt64Type};
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    use super::*;
+
+    #[derive(Debug, Clone, serde::Serialize)]
+    struct Sample {
+        value: u32,
+    }
+
+    /// Emits an increasing count every msec, and schedules ten times the count on an action.
+    #[derive(Reactor)]
+    #[reactor(state = "u32", reaction = "ReactionTick")]
+    struct Source {
+        #[reactor(timer(period = "1 msec"))]
+        tick: TimerActionKey,
+        out: TypedPortKey<u32, Output>,
+        sample: TypedPortKey<Sample, Output>,
+        /// Never set
+        #[allow(dead_code)]
+        idle: TypedPortKey<u32, Output>,
+        act: TypedActionKey<u32>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Source", triggers(action = "tick"))]
+    struct ReactionTick<'a> {
+        out: runtime::OutputRef<'a, u32>,
+        sample: runtime::OutputRef<'a, Sample>,
+        act: runtime::ActionRef<'a, u32>,
+    }
+
+    impl runtime::Trigger<u32> for ReactionTick<'_> {
+        fn trigger(mut self, ctx: &mut runtime::Context, state: &mut u32) {
+            *self.out = Some(*state);
+            *self.sample = Some(Sample { value: *state });
+            self.act.schedule(ctx, *state * 10, None).unwrap();
+            *state += 1;
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let dir = std::env::temp_dir().join("boomerang_arrow_export");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut env_builder = EnvBuilder::new();
+        let _source = Source::build("source", 0, None, None, &mut env_builder).unwrap();
+        let mut export = ArrowExport::new("arrow_export", &mut env_builder).unwrap();
+        export
+            .record::<u32>(&mut env_builder, "source::out")
+            .unwrap();
+        export
+            .record_json::<Sample>(&mut env_builder, "source::sample")
+            .unwrap();
+        export
+            .record::<u32>(&mut env_builder, "source::idle")
+            .unwrap();
+        export
+            .record::<u32>(&mut env_builder, "source::act")
+            .unwrap();
+        assert!(matches!(
+            export.record::<i32>(&mut env_builder, "source::act"),
+            Err(BuilderError::InconsistentBuilderState { .. })
+        ));
+        assert!(matches!(
+            export.record::<u32>(&mut env_builder, "source::missing"),
+            Err(BuilderError::NamedActionNotFound(_))
+        ));
+        export
+            .write_parquet_at_shutdown(&mut env_builder, &dir)
+            .unwrap();
+
+        let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+        let config = runtime::Config::default()
+            .with_fast_forward(true)
+            .with_timeout(runtime::Duration::milliseconds(3));
+        let mut sched = runtime::Scheduler::new(env, graph, config);
+        sched.event_loop().unwrap();
+
+        let env = sched.into_env();
+        let recorder = env
+            .find_reactor_by_name("arrow_export")
+            .and_then(|reactor| reactor.get_state::<ArrowRecorder>())
+            .unwrap();
+        let batches = recorder.record_batches().unwrap();
+        let names = batches
+            .iter()
+            .map(|recorded| (recorded.reactor.as_str(), recorded.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                ("source", "out"),
+                ("source", "sample"),
+                ("source", "idle"),
+                ("source", "act")
+            ]
+        );
+
+        let out = &batches[0].batch;
+        let elapsed_ns = out.column(0).as_primitive::<Int64Type>();
+        assert_eq!(elapsed_ns.values(), &[0, 1_000_000, 2_000_000, 3_000_000]);
+        let values = out.column(2).as_primitive::<UInt32Type>();
+        assert_eq!(values.values(), &[0, 1, 2, 3]);
+
+        let samples = batches[1].batch.column(2).as_string::<i32>();
+        assert_eq!(samples.value(1), r#"{"value":1}"#);
+
+        assert_eq!(batches[2].batch.num_rows(), 0);
+
+        // The action values are one microstep after the tick
+        let act = &batches[3].batch;
+        let microsteps = act.column(1).as_primitive::<UInt64Type>();
+        let out_microsteps = out.column(1).as_primitive::<UInt64Type>();
+        assert_eq!(act.column(0).as_ref(), out.column(0).slice(0, 3).as_ref());
+        assert!(microsteps
+            .iter()
+            .zip(out_microsteps)
+            .all(|(microstep, out_microstep)| microstep == out_microstep.map(|m| m + 1)));
+        let values = act.column(2).as_primitive::<UInt32Type>();
+        assert_eq!(values.values(), &[0, 10, 20]);
+        assert!(values.is_valid(0));
+
+        for (name, rows) in [("out", 4), ("sample", 4), ("idle", 0), ("act", 3)] {
+            let file =
+                std::fs::File::open(dir.join("reactor=source").join(format!("{name}.parquet")))
+                    .unwrap();
+            let reader = SerializedFileReader::new(file).unwrap();
+            assert_eq!(reader.metadata().file_metadata().num_rows(), rows, "{name}");
+        }
+    }
+}
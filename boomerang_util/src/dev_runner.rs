@@ -0,0 +1,215 @@
+//! A development runner that watches a scenario or configuration file, and re-runs a top-level reactor whenever the
+//! file changes.
+//!
+//! The file is read and parsed by a user function into the initial state of the top-level reactor and the runtime
+//! [`Config`](runtime::Config) of the run, so parameters, input data and timeouts can be tuned without restarting the
+//! program. The file is polled, no platform file watcher is needed.
+//!
+//! Each run builds the reactor program anew: a run consumes the states of its reactors, which the runtime can't reset
+//! to their initial values. Edits that leave the contents of the file unchanged, e.g. an editor saving without
+//! changes, don't trigger a run. Errors while parsing the file, building or running the program are logged, and the
+//! runner waits for the next change.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! fn main() -> anyhow::Result<()> {
+//!     boomerang_util::runner::init_logging();
+//!     DevRunner::new("scenario.json").run::<Simulation, _, _>(
+//!         "sim",
+//!         |contents| {
+//!             let scenario: Scenario = serde_json::from_str(contents)?;
+//!             let config = runtime::Config::default()
+//!                 .with_fast_forward(true)
+//!                 .with_timeout(scenario.duration);
+//!             Ok((scenario.into(), config))
+//!         },
+//!         |env| {
+//!             let stats = env.find_reactor_by_name("stats").and_then(|stats| stats.get_state::<Stats>());
+//!             println!("{stats:?}");
+//!         },
+//!     )
+//! }
+//! ```
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use boomerang::{
+    builder::{EnvBuilder, Reactor},
+    runtime,
+};
+
+/// The default interval between two checks of the watched file.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Re-runs a top-level reactor whenever a scenario file changes, see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct DevRunner {
+    /// The watched file
+    scenario: PathBuf,
+    poll_interval: Duration,
+    /// Stop after this many runs, or never if `None`
+    max_runs: Option<usize>,
+}
+
+impl DevRunner {
+    /// Create a runner watching the file at `scenario`.
+    pub fn new(scenario: impl Into<PathBuf>) -> Self {
+        Self {
+            scenario: scenario.into(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            max_runs: None,
+        }
+    }
+
+    /// Set the interval between two checks of the watched file, [`DEFAULT_POLL_INTERVAL`] by default.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Stop after `max_runs` runs, including failed ones, instead of watching forever.
+    pub fn with_max_runs(mut self, max_runs: usize) -> Self {
+        self.max_runs = Some(max_runs);
+        self
+    }
+
+    /// Watch the scenario file, and build and run the top-level reactor `R` named `name` each time its contents
+    /// change, starting with the current contents.
+    ///
+    /// `load` parses the contents of the file into the initial state of `R` and the configuration of the run.
+    /// `inspect` is called with the environment after each successful run, e.g. to print the results.
+    ///
+    /// This only returns after the configured number of runs, or if the file can't be read.
+    pub fn run<R, L, I>(&self, name: &str, mut load: L, mut inspect: I) -> anyhow::Result<()>
+    where
+        R: Reactor,
+        L: FnMut(&str) -> anyhow::Result<(R::State, runtime::Config)>,
+        I: FnMut(&runtime::Env),
+    {
+        let mut last: Option<String> = None;
+        let mut runs = 0;
+        while self.max_runs.is_none_or(|max_runs| runs < max_runs) {
+            let contents = std::fs::read_to_string(&self.scenario)
+                .with_context(|| format!("Error reading {}", self.scenario.display()))?;
+            if last.as_ref() == Some(&contents) {
+                std::thread::sleep(self.poll_interval);
+                continue;
+            }
+
+            runs += 1;
+            tracing::info!(scenario = %self.scenario.display(), run = runs, "Running the scenario.");
+            match load(&contents)
+                .and_then(|(state, config)| build_and_run::<R>(name, state, config))
+            {
+                Ok(env) => inspect(&env),
+                Err(err) => tracing::error!("Run {runs} failed: {err:#}"),
+            }
+            last = Some(contents);
+        }
+        Ok(())
+    }
+}
+
+/// Build and run the top-level reactor `R`, returning the environment after the run.
+fn build_and_run<R: Reactor>(
+    name: &str,
+    state: R::State,
+    config: runtime::Config,
+) -> anyhow::Result<runtime::Env> {
+    let mut env_builder = EnvBuilder::new();
+    R::build(name, state, None, None, &mut env_builder)
+        .context("Error building top-level reactor!")?;
+    let (env, graph, _) = env_builder
+        .into_runtime_parts()
+        .context("Error building environment!")?;
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop()?;
+    Ok(sched.into_env())
+}
+
+#[cfg(test)]
+mod tests {
+    use boomerang::prelude::*;
+
+    use super::DevRunner;
+
+    /// The values to sum, and the sum so far.
+    type Sum = (Vec<u32>, u32);
+
+    /// Sums the values it was started with, one per msec.
+    #[derive(Reactor)]
+    #[reactor(state = "Sum", reaction = "ReactionTick")]
+    struct Summer {
+        #[reactor(timer(period = "1 msec"))]
+        tick: TimerActionKey,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Summer", triggers(action = "tick"))]
+    struct ReactionTick;
+
+    impl runtime::Trigger<Sum> for ReactionTick {
+        fn trigger(self, ctx: &mut runtime::Context, (inputs, sum): &mut Sum) {
+            match inputs.pop() {
+                Some(value) => *sum += value,
+                None => ctx.schedule_shutdown(None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_rerun_on_change() {
+        let path = std::env::temp_dir().join(format!("dev_runner_{}.txt", std::process::id()));
+        std::fs::write(&path, "1 2 3").unwrap();
+
+        let mut loads = 0;
+        let mut sums = Vec::new();
+        DevRunner::new(&path)
+            .with_poll_interval(std::time::Duration::from_millis(5))
+            .with_max_runs(3)
+            .run::<Summer, _, _>(
+                "summer",
+                |contents| {
+                    loads += 1;
+                    match loads {
+                        // The runner polls the unchanged file for a while before it changes
+                        1 => {
+                            let (path, tmp) = (path.clone(), path.with_extension("tmp"));
+                            std::thread::spawn(move || {
+                                std::thread::sleep(std::time::Duration::from_millis(50));
+                                // Replaced at once, so the runner never reads a partial write
+                                std::fs::write(&tmp, "1 x").unwrap();
+                                std::fs::rename(tmp, path).unwrap();
+                            });
+                        }
+                        2 => std::fs::write(&path, "10 20").unwrap(),
+                        _ => {}
+                    }
+                    let inputs = contents
+                        .split_whitespace()
+                        .map(str::parse)
+                        .collect::<Result<Vec<u32>, _>>()?;
+                    Ok((
+                        (inputs, 0),
+                        runtime::Config::default().with_fast_forward(true),
+                    ))
+                },
+                |env| {
+                    let (_, sum) = env
+                        .find_reactor_by_name("summer")
+                        .and_then(|reactor| reactor.get_state::<Sum>())
+                        .unwrap();
+                    sums.push(*sum);
+                },
+            )
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // The invalid contents count as a failed run
+        assert_eq!(loads, 3);
+        assert_eq!(sums, [6, 30]);
+    }
+}
@@ -0,0 +1,311 @@
+//! gRPC client bridge reactors calling services with [tonic](https://docs.rs/tonic) clients.
+//!
+//! [`GrpcClientBuilder`] executes every request received on its input port as a call of a [`GrpcService`] on a
+//! background tokio runtime, and schedules a physical action with the response once the call completes. Calls run
+//! concurrently, so responses may arrive in a different order than the requests: each [`GrpcRequest`] carries a
+//! correlation ID chosen by the sender, which is returned with the matching [`GrpcResponse`].
+//!
+//! The tokio runtime is started at startup, and the client is created inside of it, so e.g.
+//! [`Endpoint::connect_lazy`](https://docs.rs/tonic/latest/tonic/transport/struct.Endpoint.html#method.connect_lazy)
+//! can be used without blocking the startup on the connection. At shutdown the runtime is shut down, and the calls
+//! still in flight are dropped. Since responses arrive asynchronously, the scheduler should be run with
+//! [`runtime::Config::with_keep_alive`] to wait for them.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! struct Greeter;
+//!
+//! impl GrpcService for Greeter {
+//!     type Client = GreeterClient<tonic::transport::Channel>;
+//!     type Request = HelloRequest;
+//!     type Response = HelloReply;
+//!
+//!     async fn call(mut client: Self::Client, request: HelloRequest) -> Result<HelloReply, Status> {
+//!         client.say_hello(request).await.map(tonic::Response::into_inner)
+//!     }
+//! }
+//!
+//! #[derive(Reactor)]
+//! #[reactor(
+//!     state = "()",
+//!     connection(from = "app.request", to = "greeter.request"),
+//!     connection(from = "greeter.response", to = "app.response")
+//! )]
+//! struct Main {
+//!     #[reactor(child = ())]
+//!     app: App,
+//!     #[reactor(child = GrpcClient::new(|| {
+//!         GreeterClient::new(Endpoint::from_static("http://[::1]:50051").connect_lazy())
+//!     }))]
+//!     greeter: GrpcClientBuilder<Greeter>,
+//! }
+//! ```
+
+use std::future::Future;
+
+use boomerang::prelude::*;
+
+pub use tonic::{Code, Status};
+
+/// A gRPC service called by a [`GrpcClientBuilder`].
+pub trait GrpcService: Send + Sync + 'static {
+    /// The tonic client of the service, cloned for every call
+    type Client: Clone + Send + Sync + 'static;
+    type Request: runtime::ReactorData + Clone;
+    type Response: runtime::ReactorData + Clone;
+
+    /// Call the service with `request`.
+    fn call(
+        client: Self::Client,
+        request: Self::Request,
+    ) -> impl Future<Output = Result<Self::Response, Status>> + Send + 'static;
+}
+
+/// A request to a [`GrpcClientBuilder`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrpcRequest<T> {
+    /// The correlation ID, returned with the response
+    pub id: u64,
+    pub request: T,
+}
+
+/// The response to the [`GrpcRequest`] with the same `id`.
+#[derive(Debug, Clone)]
+pub struct GrpcResponse<T> {
+    /// The correlation ID of the request
+    pub id: u64,
+    pub result: Result<T, Status>,
+}
+
+type ConnectFn<C> = Box<dyn FnOnce() -> C + Send + Sync>;
+
+/// State of the [`GrpcClientBuilder`] reactor.
+pub struct GrpcClient<S: GrpcService> {
+    connect: Option<ConnectFn<S::Client>>,
+    client: Option<S::Client>,
+    runtime: Option<tokio::runtime::Runtime>,
+}
+
+impl<S: GrpcService> std::fmt::Debug for GrpcClient<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrpcClient")
+            .field("connected", &self.client.is_some())
+            .finish()
+    }
+}
+
+impl<S: GrpcService> GrpcClient<S> {
+    /// Call the service with the client created by `connect`, which runs inside the tokio runtime at startup.
+    pub fn new(connect: impl FnOnce() -> S::Client + Send + Sync + 'static) -> Self {
+        Self {
+            connect: Some(Box::new(connect)),
+            client: None,
+            runtime: None,
+        }
+    }
+}
+
+/// Calls a gRPC service with the requests received on its input port, see the [module documentation](self).
+#[derive(Reactor)]
+#[reactor(
+    state = "GrpcClient::<S>",
+    reaction = "ReactionGrpcRequest<S>",
+    reaction = "ReactionGrpcResponse<S>"
+)]
+pub struct GrpcClientBuilder<S: GrpcService> {
+    /// Requests to call the service with.
+    pub request: TypedPortKey<GrpcRequest<S::Request>, Input>,
+    /// The responses of the service, in the order the calls completed.
+    pub response: TypedPortKey<GrpcResponse<S::Response>, Output>,
+
+    rx: TypedActionKey<GrpcResponse<S::Response>, Physical>,
+}
+
+/// Starts the tokio runtime and creates the client at startup, and spawns a call for every request.
+#[derive(Reaction)]
+#[reaction(reactor = "GrpcClientBuilder::<S>", triggers(startup))]
+struct ReactionGrpcRequest<'a, S: GrpcService> {
+    request: runtime::InputRef<'a, GrpcRequest<S::Request>>,
+    rx: runtime::AsyncActionRef<GrpcResponse<S::Response>>,
+}
+
+impl<S: GrpcService> runtime::Trigger<GrpcClient<S>> for ReactionGrpcRequest<'_, S> {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut GrpcClient<S>) {
+        if let Some(connect) = state.connect.take() {
+            let runtime = match tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(err) => {
+                    tracing::error!("Failed to start the tokio runtime: {err}");
+                    ctx.schedule_shutdown(None);
+                    return;
+                }
+            };
+            state.client = Some({
+                let _guard = runtime.enter();
+                connect()
+            });
+            state.runtime = Some(runtime);
+        }
+
+        let (Some(runtime), Some(client), Some(request)) = (
+            state.runtime.as_ref(),
+            state.client.as_ref(),
+            self.request.as_ref(),
+        ) else {
+            return;
+        };
+        let id = request.id;
+        let call = S::call(client.clone(), request.request.clone());
+        let send_ctx = ctx.make_send_context();
+        let rx = self.rx;
+        runtime.spawn(async move {
+            let result = call.await;
+            if let Err(status) = &result {
+                tracing::debug!("gRPC call {id} failed: {status}");
+            }
+            if !send_ctx.is_shutdown() {
+                rx.schedule(&send_ctx, GrpcResponse { id, result }, None);
+            }
+        });
+    }
+}
+
+/// Sends the responses, and shuts the tokio runtime down at shutdown.
+#[derive(Reaction)]
+#[reaction(reactor = "GrpcClientBuilder::<S>", triggers(shutdown))]
+struct ReactionGrpcResponse<'a, S: GrpcService> {
+    #[reaction(triggers)]
+    rx: runtime::ActionRef<'a, GrpcResponse<S::Response>>,
+    response: runtime::OutputRef<'a, GrpcResponse<S::Response>>,
+}
+
+impl<S: GrpcService> runtime::Trigger<GrpcClient<S>> for ReactionGrpcResponse<'_, S> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut GrpcClient<S>) {
+        if self.rx.is_present(ctx) {
+            *self.response = self.rx.get_value(ctx).cloned();
+            return;
+        }
+        state.client = None;
+        if let Some(runtime) = state.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Multiplies the request by the factor of the client after sleeping for as many msecs, and fails for zero.
+    struct Multiply;
+
+    impl GrpcService for Multiply {
+        type Client = u32;
+        type Request = u32;
+        type Response = u32;
+
+        async fn call(factor: u32, request: u32) -> Result<u32, Status> {
+            tokio::time::sleep(std::time::Duration::from_millis(request.into())).await;
+            if request == 0 {
+                return Err(Status::invalid_argument("zero"));
+            }
+            Ok(request * factor)
+        }
+    }
+
+    /// Sends a request with the next of the given values every msec.
+    #[derive(Reactor)]
+    #[reactor(state = "Vec::<u32>", reaction = "ReactionTick")]
+    struct Requester {
+        #[reactor(timer(period = "1 msec"))]
+        tick: TimerActionKey,
+        out: TypedPortKey<GrpcRequest<u32>, Output>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Requester", triggers(action = "tick"))]
+    struct ReactionTick<'a> {
+        out: runtime::OutputRef<'a, GrpcRequest<u32>>,
+    }
+
+    impl runtime::Trigger<Vec<u32>> for ReactionTick<'_> {
+        fn trigger(mut self, ctx: &mut runtime::Context, state: &mut Vec<u32>) {
+            let id = ctx.get_elapsed_logical_time().whole_milliseconds() as u64;
+            if let Some(&request) = state.get(id as usize) {
+                *self.out = Some(GrpcRequest { id, request });
+            }
+        }
+    }
+
+    type Received = Vec<(u64, Result<u32, Code>)>;
+
+    /// Records the responses, and shuts down after the third.
+    #[derive(Reactor)]
+    #[reactor(state = "Received", reaction = "ReactionResponse")]
+    struct Recorder {
+        response: TypedPortKey<GrpcResponse<u32>, Input>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Recorder")]
+    struct ReactionResponse<'a> {
+        response: runtime::InputRef<'a, GrpcResponse<u32>>,
+    }
+
+    impl runtime::Trigger<Received> for ReactionResponse<'_> {
+        fn trigger(self, ctx: &mut runtime::Context, state: &mut Received) {
+            if let Some(response) = self.response.as_ref() {
+                let result = response.result.clone().map_err(|status| status.code());
+                state.push((response.id, result));
+            }
+            if state.len() == 3 {
+                ctx.schedule_shutdown(None);
+            }
+        }
+    }
+
+    #[derive(Reactor)]
+    #[reactor(
+        state = "()",
+        connection(from = "requester.out", to = "client.request"),
+        connection(from = "client.response", to = "recorder.response")
+    )]
+    struct Main {
+        #[reactor(child = vec![200, 2, 0])]
+        requester: Requester,
+        #[reactor(child = GrpcClient::new(|| 10))]
+        client: GrpcClientBuilder<Multiply>,
+        #[reactor(child = Received::new())]
+        recorder: Recorder,
+    }
+
+    #[test]
+    fn test_correlation_ids() {
+        let mut env_builder = EnvBuilder::new();
+        let _main = Main::build("main", (), None, None, &mut env_builder).unwrap();
+        let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+        let config = runtime::Config::default()
+            .with_keep_alive(true)
+            .with_timeout(runtime::Duration::seconds(10));
+        let mut sched = runtime::Scheduler::new(env, graph, config);
+        sched.event_loop().unwrap();
+
+        let env = sched.into_env();
+        let mut received = env
+            .find_reactor_by_name("recorder")
+            .and_then(|reactor| reactor.get_state::<Received>())
+            .unwrap()
+            .clone();
+        // The slow first call completes last
+        assert_eq!(received.last(), Some(&(0, Ok(2000))));
+        received.sort_by_key(|(id, _)| *id);
+        assert_eq!(
+            received,
+            [(0, Ok(2000)), (1, Ok(20)), (2, Err(Code::InvalidArgument))]
+        );
+    }
+}
@@ -4,7 +4,28 @@
 #![deny(unsafe_code)]
 #![deny(clippy::all)]
 
+#[cfg(feature = "arrow")]
+pub mod arrow_export;
+#[cfg(feature = "dev-runner")]
+pub mod dev_runner;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "log_filter")]
+pub mod log_filter;
+pub mod logging;
+#[cfg(feature = "playback")]
+pub mod playback;
+#[cfg(feature = "process")]
+pub mod process;
+#[cfg(feature = "reactor_rust")]
+pub mod reactor_rust;
 #[cfg(feature = "replay")]
 pub mod replay;
 #[cfg(feature = "runner")]
 pub mod runner;
+#[cfg(feature = "serial")]
+pub mod serial;
+pub mod signals;
+pub mod templates;
+#[cfg(feature = "zenoh")]
+pub mod zenoh;
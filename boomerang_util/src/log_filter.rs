@@ -0,0 +1,341 @@
+//! Per-reactor log level control.
+//!
+//! [`ReactorFilter`] is a `tracing-subscriber` filter that sets the verbosity of the events logged by reactions per
+//! reactor subtree, e.g. `snake.keyboard=trace,snake=info`. The reactor of an event is found from the `reaction` span
+//! it was logged in, so the scheduler must be configured with `runtime::Config::with_reaction_spans`. The most
+//! specific directive matching the fully-qualified name of the reaction applies, and all other events are passed to a
+//! fallback filter, e.g. an `EnvFilter`. A reactor segment of a directive can be `*` to match any single reactor, or
+//! `**` to match any number of reactors, e.g. `snake.*.motor=debug`.
+//!
+//! The directives can be replaced at any time with a [`ReactorFilterHandle`], e.g. from a reaction or a command
+//! handler, without restarting the program.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! let filter = ReactorFilter::new("snake.keyboard=trace,snake=info", EnvFilter::from_default_env())?;
+//! let handle = filter.handle();
+//! tracing_subscriber::registry()
+//!     .with(tracing_subscriber::fmt::layer().with_filter(filter))
+//!     .init();
+//! // ...
+//! handle.set("snake.keyboard=warn")?;
+//! ```
+
+use std::{
+    fmt::Display,
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use boomerang::runtime::wildcard::wildcard_match;
+use tracing::{
+    field::{Field, Visit},
+    span, Metadata, Subscriber,
+};
+use tracing_subscriber::{
+    filter::LevelFilter,
+    layer::{Context, Filter},
+    registry::LookupSpan,
+};
+
+/// The name of the spans the scheduler runs reactions in.
+const REACTION_SPAN: &str = "reaction";
+
+/// An invalid directive given to a [`ReactorFilter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReactorFilterError {
+    directive: String,
+    reason: &'static str,
+}
+
+impl Display for ReactorFilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid reactor log directive '{}': {}",
+            self.directive, self.reason
+        )
+    }
+}
+
+impl std::error::Error for ReactorFilterError {}
+
+/// The level of the events logged by the reactions of a reactor subtree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Directive {
+    /// The segments of the fully-qualified name of the reactor
+    path: Vec<String>,
+    level: LevelFilter,
+}
+
+impl Directive {
+    /// Whether the reaction `fqn` is part of the reactor subtree of this directive.
+    fn matches(&self, fqn: &str) -> bool {
+        let segments = fqn.split("::").collect::<Vec<_>>();
+        // The directive applies to the whole subtree, as if its path ended in `**`
+        let pattern = self
+            .path
+            .iter()
+            .map(String::as_str)
+            .chain(["**"])
+            .collect::<Vec<_>>();
+        wildcard_match(
+            &pattern,
+            &segments,
+            |&segment| segment == "**",
+            |&pattern, &segment| pattern == "*" || pattern == segment,
+        )
+    }
+}
+
+impl FromStr for Directive {
+    type Err = ReactorFilterError;
+
+    fn from_str(directive: &str) -> Result<Self, Self::Err> {
+        let err = |reason| ReactorFilterError {
+            directive: directive.to_owned(),
+            reason,
+        };
+        let (path, level) = directive
+            .split_once('=')
+            .ok_or_else(|| err("expected '<reactor>=<level>'"))?;
+        let level = level
+            .trim()
+            .parse::<LevelFilter>()
+            .map_err(|_| err("unknown level"))?;
+        let path = path.trim();
+        let separator = if path.contains("::") { "::" } else { "." };
+        let path = path.split(separator).map(str::to_owned).collect::<Vec<_>>();
+        if path.iter().any(String::is_empty) {
+            return Err(err("empty reactor name"));
+        }
+        Ok(Self { path, level })
+    }
+}
+
+/// A set of directives, ordered from the most to the least specific.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Directives(Vec<Directive>);
+
+impl Directives {
+    /// The level of the most specific directive matching the reaction `fqn`, if any.
+    fn level(&self, fqn: &str) -> Option<LevelFilter> {
+        self.0
+            .iter()
+            .find(|directive| directive.matches(fqn))
+            .map(|directive| directive.level)
+    }
+
+    /// The most verbose level of any directive.
+    fn max_level(&self) -> Option<LevelFilter> {
+        self.0.iter().map(|directive| directive.level).max()
+    }
+}
+
+impl FromStr for Directives {
+    type Err = ReactorFilterError;
+
+    /// Parse comma-separated directives, a later directive for the same reactor replacing an earlier one.
+    fn from_str(directives: &str) -> Result<Self, Self::Err> {
+        let mut parsed: Vec<Directive> = Vec::new();
+        for directive in directives.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+            let directive = directive.parse::<Directive>()?;
+            parsed.retain(|prev| prev.path != directive.path);
+            parsed.push(directive);
+        }
+        parsed.sort_by_key(|directive| std::cmp::Reverse(directive.path.len()));
+        Ok(Self(parsed))
+    }
+}
+
+impl Display for Directives {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, directive) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}={}", directive.path.join("::"), directive.level)?;
+        }
+        Ok(())
+    }
+}
+
+/// The fully-qualified name of a reaction, stored in the extensions of its span.
+struct ReactionFqn(String);
+
+impl Visit for ReactionFqn {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "fqn" {
+            self.0 = value.to_owned();
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "fqn" {
+            self.0 = format!("{value:?}");
+        }
+    }
+}
+
+/// Filters the events of reactions by the directive of their reactor subtree, see the [module documentation](self).
+pub struct ReactorFilter<F = LevelFilter> {
+    directives: Arc<RwLock<Directives>>,
+    /// Decides on all events not matched by any directive
+    fallback: F,
+}
+
+impl<F> ReactorFilter<F> {
+    /// Create a filter from comma-separated `directives`, each `<reactor>=<level>`.
+    ///
+    /// The reactor is a fully-qualified name, with its segments separated by either `.` or `::`.
+    pub fn new(directives: &str, fallback: F) -> Result<Self, ReactorFilterError> {
+        Ok(Self {
+            directives: Arc::new(RwLock::new(directives.parse()?)),
+            fallback,
+        })
+    }
+
+    /// Get a handle to replace the directives of this filter at runtime.
+    pub fn handle(&self) -> ReactorFilterHandle {
+        ReactorFilterHandle(self.directives.clone())
+    }
+
+    /// The level of the events logged by the reaction `fqn`, if any directive matches it.
+    pub fn level_for(&self, fqn: &str) -> Option<LevelFilter> {
+        self.directives.read().unwrap().level(fqn)
+    }
+}
+
+/// A handle to replace the directives of a [`ReactorFilter`] while the program runs.
+#[derive(Debug, Clone)]
+pub struct ReactorFilterHandle(Arc<RwLock<Directives>>);
+
+impl ReactorFilterHandle {
+    /// Replace all directives of the filter, see [`ReactorFilter::new`].
+    pub fn set(&self, directives: &str) -> Result<(), ReactorFilterError> {
+        let directives = directives.parse()?;
+        *self.0.write().unwrap() = directives;
+        // The maximum level of the subscriber depends on the directives
+        tracing::callsite::rebuild_interest_cache();
+        Ok(())
+    }
+
+    /// The current directives, with `::`-separated reactor names.
+    pub fn directives(&self) -> String {
+        self.0.read().unwrap().to_string()
+    }
+}
+
+impl<S, F> Filter<S> for ReactorFilter<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    F: Filter<S>,
+{
+    fn enabled(&self, meta: &Metadata<'_>, cx: &Context<'_, S>) -> bool {
+        if meta.is_span() {
+            return meta.name() == REACTION_SPAN || self.fallback.enabled(meta, cx);
+        }
+        let level = cx.lookup_current().and_then(|span| {
+            span.scope().find_map(|span| {
+                let extensions = span.extensions();
+                let fqn = extensions.get::<ReactionFqn>()?;
+                self.level_for(&fqn.0)
+            })
+        });
+        match level {
+            Some(level) => level >= *meta.level(),
+            None => self.fallback.enabled(meta, cx),
+        }
+    }
+
+    fn callsite_enabled(&self, _meta: &'static Metadata<'static>) -> tracing::subscriber::Interest {
+        // Whether an event is enabled depends on the reaction it is logged in
+        tracing::subscriber::Interest::sometimes()
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        let fallback = self.fallback.max_level_hint()?;
+        let directives = self.directives.read().unwrap().max_level();
+        Some(directives.map_or(fallback, |level| level.max(fallback)))
+    }
+
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, cx: Context<'_, S>) {
+        if attrs.metadata().name() == REACTION_SPAN {
+            if let Some(span) = cx.span(id) {
+                let mut fqn = ReactionFqn(String::new());
+                attrs.record(&mut fqn);
+                span.extensions_mut().insert(fqn);
+            }
+        }
+        self.fallback.on_new_span(attrs, id, cx);
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, cx: Context<'_, S>) {
+        self.fallback.on_record(id, values, cx);
+    }
+
+    fn on_enter(&self, id: &span::Id, cx: Context<'_, S>) {
+        self.fallback.on_enter(id, cx);
+    }
+
+    fn on_exit(&self, id: &span::Id, cx: Context<'_, S>) {
+        self.fallback.on_exit(id, cx);
+    }
+
+    fn on_close(&self, id: span::Id, cx: Context<'_, S>) {
+        self.fallback.on_close(id, cx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directives() {
+        let directives = "snake.keyboard=trace, snake=info,snake::keyboard::left=off,snake=warn"
+            .parse::<Directives>()
+            .unwrap();
+        assert_eq!(
+            directives.to_string(),
+            "snake::keyboard::left=off,snake::keyboard=trace,snake=warn"
+        );
+        assert_eq!(
+            directives.level("snake::keyboard::reaction_key"),
+            Some(LevelFilter::TRACE)
+        );
+        assert_eq!(
+            directives.level("snake::keyboard::left::reaction"),
+            Some(LevelFilter::OFF)
+        );
+        assert_eq!(
+            directives.level("snake::grid::reaction"),
+            Some(LevelFilter::WARN)
+        );
+        assert_eq!(directives.level("snake_two::reaction"), None);
+        assert_eq!(directives.level("snake"), Some(LevelFilter::WARN));
+
+        let directives = "robot.*.motor=debug,**.lidar=trace"
+            .parse::<Directives>()
+            .unwrap();
+        assert_eq!(
+            directives.level("robot::left::motor::reaction"),
+            Some(LevelFilter::DEBUG)
+        );
+        assert_eq!(directives.level("robot::left::wheel::motor"), None);
+        assert_eq!(
+            directives.level("robot::front::lidar::reaction"),
+            Some(LevelFilter::TRACE)
+        );
+        assert_eq!(directives.max_level(), Some(LevelFilter::TRACE));
+
+        assert!("snake".parse::<Directives>().is_err());
+        assert!("snake=loud".parse::<Directives>().is_err());
+        assert!("snake..keyboard=info".parse::<Directives>().is_err());
+        assert_eq!("".parse::<Directives>().unwrap(), Directives::default());
+    }
+}
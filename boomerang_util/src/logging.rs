@@ -0,0 +1,208 @@
+//! Rate-limited logging of port values.
+//!
+//! [`PeriodicLoggerBuilder`] subscribes to a port of any `Debug` type and logs its value at most once per configured
+//! interval of logical time. Values arriving in between are dropped, and the number of dropped samples is reported with
+//! the next logged value and in a summary at shutdown.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! #[derive(Reactor)]
+//! #[reactor(state = "()", connection(from = "sensor.out", to = "logger.value"))]
+//! struct Main {
+//!     #[reactor(child = ())]
+//!     sensor: SensorBuilder,
+//!     // log at most at 1 Hz
+//!     #[reactor(child = PeriodicLogger::new("sensor", Duration::seconds(1)))]
+//!     logger: PeriodicLoggerBuilder<f64>,
+//! }
+//! ```
+
+use std::fmt::Debug;
+
+use boomerang::prelude::*;
+
+/// Emit a `tracing` event at a level chosen at runtime.
+macro_rules! log_at {
+    ($level:expr, $($arg:tt)+) => {
+        match $level {
+            tracing::Level::ERROR => tracing::error!($($arg)+),
+            tracing::Level::WARN => tracing::warn!($($arg)+),
+            tracing::Level::INFO => tracing::info!($($arg)+),
+            tracing::Level::DEBUG => tracing::debug!($($arg)+),
+            tracing::Level::TRACE => tracing::trace!($($arg)+),
+        }
+    };
+}
+
+/// State of the [`PeriodicLoggerBuilder`] reactor.
+#[derive(Debug)]
+pub struct PeriodicLogger {
+    /// Label included in every log message
+    label: String,
+    /// Minimum logical time between two logged values
+    interval: Duration,
+    level: tracing::Level,
+    /// Tag of the last logged value
+    last_logged: Option<runtime::Tag>,
+    /// Number of samples dropped since the last logged value
+    dropped: usize,
+    /// Total number of logged values
+    total_logged: usize,
+    /// Total number of dropped samples
+    total_dropped: usize,
+}
+
+impl PeriodicLogger {
+    /// Create a new logger that logs at most one value per `interval` of logical time at `INFO` level.
+    pub fn new(label: impl Into<String>, interval: Duration) -> Self {
+        Self {
+            label: label.into(),
+            interval,
+            level: tracing::Level::INFO,
+            last_logged: None,
+            dropped: 0,
+            total_logged: 0,
+            total_dropped: 0,
+        }
+    }
+
+    /// Set the level that values are logged at.
+    pub fn with_level(mut self, level: tracing::Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Total number of logged values so far.
+    pub fn total_logged(&self) -> usize {
+        self.total_logged
+    }
+
+    /// Total number of dropped samples so far.
+    pub fn total_dropped(&self) -> usize {
+        self.total_dropped
+    }
+}
+
+/// Logs the values received on `value` at a limited rate.
+#[derive(Reactor)]
+#[reactor(
+    state = "PeriodicLogger",
+    reaction = "ReactionValue<T>",
+    reaction = "ReactionShutdown"
+)]
+pub struct PeriodicLoggerBuilder<T: runtime::ReactorData + Debug> {
+    /// The value to log.
+    pub value: TypedPortKey<T, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "PeriodicLoggerBuilder::<T>")]
+struct ReactionValue<'a, T: runtime::ReactorData + Debug> {
+    value: runtime::InputRef<'a, T>,
+}
+
+impl<T: runtime::ReactorData + Debug> runtime::Trigger<PeriodicLogger> for ReactionValue<'_, T> {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut PeriodicLogger) {
+        let Some(value) = self.value.as_ref() else {
+            return;
+        };
+
+        let tag = ctx.get_tag();
+        let due = match state.last_logged {
+            Some(last) => tag.offset() - last.offset() >= state.interval,
+            None => true,
+        };
+
+        if due {
+            log_at!(
+                state.level,
+                tag = %tag,
+                dropped = state.dropped,
+                "{}: {value:?}",
+                state.label
+            );
+            state.last_logged = Some(tag);
+            state.dropped = 0;
+            state.total_logged += 1;
+        } else {
+            state.dropped += 1;
+            state.total_dropped += 1;
+        }
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(
+    reactor = "PeriodicLoggerBuilder::<T>",
+    bound = "T: runtime::ReactorData + Debug",
+    triggers(shutdown)
+)]
+struct ReactionShutdown;
+
+impl runtime::Trigger<PeriodicLogger> for ReactionShutdown {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut PeriodicLogger) {
+        log_at!(
+            state.level,
+            "{}: logged {} values, dropped {} samples",
+            state.label,
+            state.total_logged,
+            state.total_dropped
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Reactor)]
+    #[reactor(state = "u32", reaction = "ReactionTick")]
+    struct Counter {
+        #[reactor(timer(period = "1 msec"))]
+        tick: TimerActionKey,
+        out: TypedPortKey<u32, Output>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Counter", triggers(action = "tick"))]
+    struct ReactionTick<'a> {
+        out: runtime::OutputRef<'a, u32>,
+    }
+
+    impl runtime::Trigger<u32> for ReactionTick<'_> {
+        fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u32) {
+            *self.out = Some(*state);
+            *state += 1;
+        }
+    }
+
+    #[derive(Reactor)]
+    #[reactor(state = "()", connection(from = "counter.out", to = "logger.value"))]
+    struct Main {
+        #[reactor(child = 0)]
+        counter: Counter,
+        #[reactor(child = PeriodicLogger::new("counter", Duration::milliseconds(10)))]
+        logger: PeriodicLoggerBuilder<u32>,
+    }
+
+    #[test]
+    fn test_periodic_logger() {
+        let mut env_builder = EnvBuilder::new();
+        let _reactor = Main::build("main", (), None, None, &mut env_builder).unwrap();
+        let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+        let config = runtime::Config::default()
+            .with_fast_forward(true)
+            .with_timeout(Duration::milliseconds(99));
+        let mut sched = runtime::Scheduler::new(env, graph, config);
+        sched.event_loop().unwrap();
+
+        let env = sched.into_env();
+        let logger = env
+            .find_reactor_by_name("logger")
+            .and_then(|reactor| reactor.get_state::<PeriodicLogger>())
+            .unwrap();
+        assert_eq!(logger.total_logged(), 10);
+        assert_eq!(logger.total_dropped(), 90);
+    }
+}
@@ -0,0 +1,517 @@
+//! Playback of time-indexed datasets as logical events.
+//!
+//! [`PlaybackBuilder`] loads a dataset at startup, e.g. a recorded sensor log, and sends each row on its `out` port at
+//! a logical time proportional to its timestamp. The first row is sent at the microstep after startup, and each further
+//! row at the difference of its timestamp to the first one, divided by the speedup. Rows with the same timestamp are sent at
+//! consecutive microsteps. Since only logical actions are used, a playback is deterministic and can be run in
+//! fast-forward mode.
+//!
+//! The rows are deserialized with `serde` into the type of the `out` port, while the timestamp is read from the column
+//! named by [`PlaybackConfig::timestamp_column`]. The timestamp must be numeric and non-decreasing, in units of
+//! [`PlaybackConfig::time_unit`] (seconds by default). The supported formats are:
+//! - CSV files with a header row.
+//! - Parquet files, with the `playback-parquet` feature. Timestamp columns with a Parquet timestamp type are converted
+//!   using their own unit instead of [`PlaybackConfig::time_unit`].
+//!
+//! If the dataset fails to load, the error is logged and the scheduler is shut down.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! #[derive(Debug, serde::Deserialize)]
+//! struct Imu {
+//!     accel_x: f64,
+//!     accel_y: f64,
+//! }
+//!
+//! #[derive(Reactor)]
+//! #[reactor(state = "()", connection(from = "log.out", to = "filter.inp"))]
+//! struct Main {
+//!     #[reactor(child = Playback::new(PlaybackConfig::new("imu.csv").with_speedup(10.0)))]
+//!     log: PlaybackBuilder<Imu>,
+//!     #[reactor(child = ())]
+//!     filter: FilterBuilder,
+//! }
+//! ```
+
+use std::{collections::VecDeque, fmt::Display, path::PathBuf};
+
+use boomerang::prelude::*;
+use serde::de::DeserializeOwned;
+
+/// The format of a dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackFormat {
+    Csv,
+    #[cfg(feature = "playback-parquet")]
+    Parquet,
+}
+
+impl PlaybackFormat {
+    /// Guess the format from the extension of `path`, defaulting to CSV.
+    pub fn from_path(path: &std::path::Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            #[cfg(feature = "playback-parquet")]
+            Some("parquet" | "pq") => Self::Parquet,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// Settings used to load and play back a dataset.
+#[derive(Debug, Clone)]
+pub struct PlaybackConfig {
+    pub path: PathBuf,
+    pub format: PlaybackFormat,
+    /// The name of the column holding the timestamp of each row
+    pub timestamp_column: String,
+    /// The duration of one unit of the timestamp column
+    pub time_unit: Duration,
+    /// How much faster than recorded the rows are played back, e.g. `2.0` to halve the time between the rows
+    pub speedup: f64,
+}
+
+impl PlaybackConfig {
+    /// Create a new config playing back `path` in real time, with a `timestamp` column in seconds. The format is
+    /// guessed from the extension of `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            format: PlaybackFormat::from_path(&path),
+            path,
+            timestamp_column: "timestamp".to_owned(),
+            time_unit: Duration::SECOND,
+            speedup: 1.0,
+        }
+    }
+
+    pub fn with_format(mut self, format: PlaybackFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_timestamp_column(mut self, timestamp_column: impl Into<String>) -> Self {
+        self.timestamp_column = timestamp_column.into();
+        self
+    }
+
+    pub fn with_time_unit(mut self, time_unit: Duration) -> Self {
+        self.time_unit = time_unit;
+        self
+    }
+
+    /// # Panics
+    ///
+    /// If `speedup` isn't a positive, finite number.
+    pub fn with_speedup(mut self, speedup: f64) -> Self {
+        assert!(
+            speedup.is_finite() && speedup > 0.0,
+            "The speedup must be positive, got {speedup}"
+        );
+        self.speedup = speedup;
+        self
+    }
+
+    /// Load the rows of the dataset, with their logical time offsets from the first row.
+    pub fn load<T: DeserializeOwned>(&self) -> Result<Vec<(Duration, T)>, PlaybackError> {
+        let rows = match self.format {
+            PlaybackFormat::Csv => self.read_csv()?,
+            #[cfg(feature = "playback-parquet")]
+            PlaybackFormat::Parquet => self.read_parquet()?,
+        };
+
+        let Some(&(first, _)) = rows.first() else {
+            return Ok(Vec::new());
+        };
+        let mut previous = first;
+        rows.into_iter()
+            .enumerate()
+            .map(|(row, (seconds, value))| {
+                if !seconds.is_finite() || seconds < previous {
+                    return Err(PlaybackError::Timestamp {
+                        row,
+                        reason: format!("{seconds} is not after the previous timestamp {previous}"),
+                    });
+                }
+                previous = seconds;
+                Ok((
+                    Duration::seconds_f64((seconds - first) / self.speedup),
+                    value,
+                ))
+            })
+            .collect()
+    }
+
+    /// Read the rows of a CSV file, with their timestamps in seconds.
+    fn read_csv<T: DeserializeOwned>(&self) -> Result<Vec<(f64, T)>, PlaybackError> {
+        let mut reader = csv::Reader::from_path(&self.path).map_err(PlaybackError::source)?;
+        let headers = reader.headers().map_err(PlaybackError::source)?.clone();
+        let column = headers
+            .iter()
+            .position(|header| header == self.timestamp_column)
+            .ok_or_else(|| PlaybackError::MissingColumn(self.timestamp_column.clone()))?;
+        let unit = self.time_unit.as_seconds_f64();
+
+        reader
+            .records()
+            .enumerate()
+            .map(|(row, record)| {
+                let record = record.map_err(PlaybackError::source)?;
+                let timestamp = record[column].trim().parse::<f64>().map_err(|err| {
+                    PlaybackError::Timestamp {
+                        row,
+                        reason: format!("'{}': {err}", &record[column]),
+                    }
+                })?;
+                let value = record
+                    .deserialize(Some(&headers))
+                    .map_err(PlaybackError::source)?;
+                Ok((timestamp * unit, value))
+            })
+            .collect()
+    }
+
+    /// Read the rows of a Parquet file, with their timestamps in seconds.
+    #[cfg(feature = "playback-parquet")]
+    fn read_parquet<T: DeserializeOwned>(&self) -> Result<Vec<(f64, T)>, PlaybackError> {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::Field;
+
+        let file = std::fs::File::open(&self.path).map_err(PlaybackError::source)?;
+        let reader = SerializedFileReader::new(file).map_err(PlaybackError::source)?;
+        let unit = self.time_unit.as_seconds_f64();
+
+        reader
+            .get_row_iter(None)
+            .map_err(PlaybackError::source)?
+            .enumerate()
+            .map(|(row, record)| {
+                let record = record.map_err(PlaybackError::source)?;
+                let (_, field) = record
+                    .get_column_iter()
+                    .find(|(name, _)| **name == self.timestamp_column)
+                    .ok_or_else(|| PlaybackError::MissingColumn(self.timestamp_column.clone()))?;
+                let seconds = match *field {
+                    Field::Byte(ts) => ts as f64 * unit,
+                    Field::Short(ts) => ts as f64 * unit,
+                    Field::Int(ts) => ts as f64 * unit,
+                    Field::Long(ts) => ts as f64 * unit,
+                    Field::UByte(ts) => ts as f64 * unit,
+                    Field::UShort(ts) => ts as f64 * unit,
+                    Field::UInt(ts) => ts as f64 * unit,
+                    Field::ULong(ts) => ts as f64 * unit,
+                    Field::Float(ts) => ts as f64 * unit,
+                    Field::Double(ts) => ts * unit,
+                    Field::TimestampMillis(ts) => ts as f64 * 1e-3,
+                    Field::TimestampMicros(ts) => ts as f64 * 1e-6,
+                    ref other => {
+                        return Err(PlaybackError::Timestamp {
+                            row,
+                            reason: format!("{other} is not numeric"),
+                        })
+                    }
+                };
+                let value = serde_json::from_value(record.to_json_value())
+                    .map_err(PlaybackError::source)?;
+                Ok((seconds, value))
+            })
+            .collect()
+    }
+}
+
+/// Errors loading a dataset for playback.
+#[derive(Debug)]
+pub enum PlaybackError {
+    /// The dataset couldn't be read or a row couldn't be deserialized
+    Source(Box<dyn std::error::Error + Send + Sync>),
+    /// The timestamp column isn't in the dataset
+    MissingColumn(String),
+    /// The timestamp of a row is invalid or earlier than the one of the row before it
+    Timestamp { row: usize, reason: String },
+}
+
+impl PlaybackError {
+    fn source(err: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self::Source(Box::new(err))
+    }
+}
+
+impl Display for PlaybackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Source(err) => write!(f, "Failed to read the dataset: {err}"),
+            Self::MissingColumn(column) => write!(f, "Missing the timestamp column '{column}'"),
+            Self::Timestamp { row, reason } => {
+                write!(f, "Invalid timestamp in row {row}: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlaybackError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Source(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// State of the [`PlaybackBuilder`] reactor.
+#[derive(Debug)]
+pub struct Playback<T> {
+    config: PlaybackConfig,
+    /// The rows not sent yet, with their offsets from the startup tag
+    rows: VecDeque<(Duration, T)>,
+}
+
+impl<T> Playback<T> {
+    pub fn new(config: PlaybackConfig) -> Self {
+        Self {
+            config,
+            rows: VecDeque::new(),
+        }
+    }
+}
+
+/// Plays back the rows of a dataset at logical times proportional to their timestamps, see the
+/// [module documentation](self).
+#[derive(Reactor)]
+#[reactor(state = "Playback::<T>", reaction = "ReactionPlayback<T>")]
+pub struct PlaybackBuilder<T: runtime::ReactorData + DeserializeOwned> {
+    /// The rows of the dataset.
+    pub out: TypedPortKey<T, Output>,
+    /// Set when the last row was sent.
+    pub done: TypedPortKey<(), Output>,
+
+    next: TypedActionKey,
+}
+
+/// Loads the dataset at startup, then sends a row each time `next` is triggered and schedules the following one.
+#[derive(Reaction)]
+#[reaction(reactor = "PlaybackBuilder::<T>", triggers(startup))]
+struct ReactionPlayback<'a, T: runtime::ReactorData + DeserializeOwned> {
+    #[reaction(triggers)]
+    next: runtime::ActionRef<'a>,
+    out: runtime::OutputRef<'a, T>,
+    done: runtime::OutputRef<'a, ()>,
+}
+
+impl<T: runtime::ReactorData + DeserializeOwned> runtime::Trigger<Playback<T>>
+    for ReactionPlayback<'_, T>
+{
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut Playback<T>) {
+        let current = if self.next.is_present(ctx) {
+            let Some((offset, value)) = state.rows.pop_front() else {
+                return;
+            };
+            *self.out = Some(value);
+            if state.rows.is_empty() {
+                *self.done = Some(());
+            }
+            offset
+        } else {
+            match state.config.load() {
+                Ok(rows) => {
+                    tracing::debug!(
+                        "Loaded {} rows from {}",
+                        rows.len(),
+                        state.config.path.display()
+                    );
+                    state.rows = rows.into();
+                }
+                Err(err) => {
+                    tracing::error!("Failed to load {}: {err}", state.config.path.display());
+                    ctx.schedule_shutdown(None);
+                    return;
+                }
+            }
+            Duration::ZERO
+        };
+
+        if let Some(&(offset, _)) = state.rows.front() {
+            self.next
+                .schedule(ctx, (), Some(offset - current))
+                .expect("The rows are in order");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+    struct Sample {
+        value: i32,
+    }
+
+    fn write_dataset(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("boomerang_playback_{name}.csv"));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_csv() {
+        let path = write_dataset("load", "timestamp,value\n10.0,1\n10.5,2\n10.5,3\n12,4\n");
+        let rows = PlaybackConfig::new(&path)
+            .with_speedup(2.0)
+            .load::<Sample>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            [
+                (Duration::ZERO, Sample { value: 1 }),
+                (Duration::milliseconds(250), Sample { value: 2 }),
+                (Duration::milliseconds(250), Sample { value: 3 }),
+                (Duration::seconds(1), Sample { value: 4 }),
+            ]
+        );
+
+        let rows = PlaybackConfig::new(&path)
+            .with_time_unit(Duration::MILLISECOND)
+            .load::<Sample>()
+            .unwrap();
+        assert_eq!(rows[3].0, Duration::milliseconds(2));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let path = write_dataset("no_column", "time,value\n0,1\n");
+        assert!(matches!(
+            PlaybackConfig::new(&path).load::<Sample>(),
+            Err(PlaybackError::MissingColumn(column)) if column == "timestamp"
+        ));
+
+        let path = write_dataset("out_of_order", "timestamp,value\n1,1\n0,2\n");
+        assert!(matches!(
+            PlaybackConfig::new(&path).load::<Sample>(),
+            Err(PlaybackError::Timestamp { row: 1, .. })
+        ));
+
+        let path = write_dataset("bad_value", "timestamp,value\n0,one\n");
+        assert!(matches!(
+            PlaybackConfig::new(&path).load::<Sample>(),
+            Err(PlaybackError::Source(_))
+        ));
+    }
+
+    #[cfg(feature = "playback-parquet")]
+    #[test]
+    fn test_load_parquet() {
+        use std::sync::Arc;
+
+        use parquet::{
+            data_type::{DoubleType, Int32Type},
+            file::{properties::WriterProperties, writer::SerializedFileWriter},
+            schema::parser::parse_message_type,
+        };
+
+        let path = std::env::temp_dir().join("boomerang_playback_load.parquet");
+        let schema = parse_message_type(
+            "message schema { REQUIRED DOUBLE timestamp; REQUIRED INT32 value; }",
+        )
+        .unwrap();
+        let file = std::fs::File::create(&path).unwrap();
+        let mut writer = SerializedFileWriter::new(
+            file,
+            Arc::new(schema),
+            Arc::new(WriterProperties::builder().build()),
+        )
+        .unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<DoubleType>()
+            .write_batch(&[0.0, 0.5, 2.0], None, None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int32Type>()
+            .write_batch(&[1, 2, 3], None, None)
+            .unwrap();
+        column.close().unwrap();
+        row_group.close().unwrap();
+        writer.close().unwrap();
+
+        let rows = PlaybackConfig::new(&path).load::<Sample>().unwrap();
+        assert_eq!(
+            rows,
+            [
+                (Duration::ZERO, Sample { value: 1 }),
+                (Duration::milliseconds(500), Sample { value: 2 }),
+                (Duration::seconds(2), Sample { value: 3 }),
+            ]
+        );
+    }
+
+    type Received = Vec<(runtime::Tag, i32, bool)>;
+
+    /// Records the tags of the received rows, and whether the playback was done.
+    #[derive(Reactor)]
+    #[reactor(state = "Received", reaction = "ReactionRecord")]
+    struct Recorder {
+        inp: TypedPortKey<Sample, Input>,
+        done: TypedPortKey<(), Input>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Recorder")]
+    struct ReactionRecord<'a> {
+        inp: runtime::InputRef<'a, Sample>,
+        done: runtime::InputRef<'a, ()>,
+    }
+
+    impl runtime::Trigger<Received> for ReactionRecord<'_> {
+        fn trigger(self, ctx: &mut runtime::Context, state: &mut Received) {
+            if let Some(sample) = self.inp.as_ref() {
+                state.push((ctx.get_tag(), sample.value, self.done.is_some()));
+            }
+        }
+    }
+
+    /// A dataset played back at twice the recorded speed.
+    fn run_config() -> PlaybackConfig {
+        let path = write_dataset("run", "timestamp,value\n1,1\n2,2\n2,3\n");
+        PlaybackConfig::new(path).with_speedup(2.0)
+    }
+
+    #[derive(Reactor)]
+    #[reactor(
+        state = "()",
+        connection(from = "playback.out", to = "recorder.inp"),
+        connection(from = "playback.done", to = "recorder.done")
+    )]
+    struct Main {
+        #[reactor(child = Playback::new(run_config()))]
+        playback: PlaybackBuilder<Sample>,
+        #[reactor(child = Received::default())]
+        recorder: Recorder,
+    }
+
+    #[test]
+    fn test_playback() {
+        let mut env_builder = EnvBuilder::new();
+        let _main = Main::build("main", (), None, None, &mut env_builder).unwrap();
+        let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+        let config = runtime::Config::default().with_fast_forward(true);
+        let mut sched = runtime::Scheduler::new(env, graph, config);
+        sched.event_loop().unwrap();
+
+        let env = sched.into_env();
+        let received = env
+            .find_reactor_by_name("recorder")
+            .and_then(|reactor| reactor.get_state::<Received>())
+            .unwrap();
+        assert_eq!(
+            received,
+            &[
+                (runtime::Tag::new(Duration::ZERO, 1), 1, false),
+                (runtime::Tag::new(Duration::milliseconds(500), 0), 2, false),
+                (runtime::Tag::new(Duration::milliseconds(500), 1), 3, true),
+            ]
+        );
+    }
+}
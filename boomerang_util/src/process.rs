@@ -0,0 +1,365 @@
+//! Subprocess reactors exchanging JSON lines over stdio.
+//!
+//! [`ChildProcessBuilder`] spawns a subprocess at startup, writes every value received on its input port to the
+//! stdin of the subprocess as a line of JSON, and parses every line the subprocess writes to its stdout as JSON,
+//! scheduling a physical action with the parsed value. When the subprocess exits, its exit code is sent through the
+//! `exit` port. This is a pragmatic way to connect to code written in languages without a Boomerang runtime.
+//!
+//! Lines that fail to parse are logged and skipped, so the subprocess can still write e.g. diagnostics to stdout.
+//! Since values from the subprocess arrive asynchronously, the scheduler should be run with
+//! [`runtime::Config::with_keep_alive`] to wait for them.
+//!
+//! At shutdown, the stdin of the subprocess is closed, and the subprocess is expected to exit on its own.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! #[derive(Reactor)]
+//! #[reactor(
+//!     state = "()",
+//!     connection(from = "source.out", to = "model.input"),
+//!     connection(from = "model.output", to = "sink.inp")
+//! )]
+//! struct Main {
+//!     #[reactor(child = ())]
+//!     source: SourceBuilder,
+//!     #[reactor(child = ChildProcess::new(ProcessConfig::new("python3").with_arg("model.py")))]
+//!     model: ChildProcessBuilder<(Request, Response)>,
+//!     #[reactor(child = ())]
+//!     sink: SinkBuilder,
+//! }
+//! ```
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    marker::PhantomData,
+    path::PathBuf,
+    process::{ChildStdin, Command, Stdio},
+};
+
+use boomerang::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Settings used to spawn a subprocess.
+#[derive(Debug, Clone)]
+pub struct ProcessConfig {
+    /// The program to run, looked up in the `PATH` if it's not a path
+    pub program: String,
+    pub args: Vec<String>,
+    /// Additional environment variables of the subprocess
+    pub envs: Vec<(String, String)>,
+    /// The working directory of the subprocess, defaults to the working directory of the parent
+    pub current_dir: Option<PathBuf>,
+}
+
+impl ProcessConfig {
+    /// Create a new config running `program` without arguments.
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            envs: Vec::new(),
+            current_dir: None,
+        }
+    }
+
+    pub fn with_arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn with_args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn with_current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Build the command described by this config, with piped stdin and stdout.
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .envs(self.envs.iter().map(|(key, value)| (key, value)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped());
+        if let Some(dir) = &self.current_dir {
+            command.current_dir(dir);
+        }
+        command
+    }
+}
+
+/// Parse a line written by the subprocess, logging lines that aren't valid JSON for `T`.
+fn parse_line<T: DeserializeOwned>(line: &str) -> Option<T> {
+    if line.trim().is_empty() {
+        return None;
+    }
+    serde_json::from_str(line)
+        .inspect_err(|err| tracing::warn!("Skipping line from subprocess '{line}': {err}"))
+        .ok()
+}
+
+/// The types of the values exchanged with a subprocess, implemented for `(In, Out)` tuples.
+pub trait ProcessMessages: Send + Sync + 'static {
+    /// Values written to the stdin of the subprocess
+    type In: runtime::ReactorData + Serialize;
+    /// Values parsed from the stdout of the subprocess
+    type Out: runtime::ReactorData + DeserializeOwned + Clone;
+}
+
+impl<In, Out> ProcessMessages for (In, Out)
+where
+    In: runtime::ReactorData + Serialize,
+    Out: runtime::ReactorData + DeserializeOwned + Clone,
+{
+    type In = In;
+    type Out = Out;
+}
+
+/// State of the [`ChildProcessBuilder`] reactor.
+#[derive(Debug)]
+pub struct ChildProcess<M> {
+    config: ProcessConfig,
+    /// The stdin of the running subprocess
+    stdin: Option<ChildStdin>,
+    _phantom: PhantomData<fn() -> M>,
+}
+
+impl<M> ChildProcess<M> {
+    pub fn new(config: ProcessConfig) -> Self {
+        Self {
+            config,
+            stdin: None,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Runs a subprocess, exchanging values with it as JSON lines over its stdio, see the [module documentation](self).
+#[derive(Reactor)]
+#[reactor(
+    state = "ChildProcess::<M>",
+    reaction = "ReactionProcessStartup<M>",
+    reaction = "ReactionProcessInput<M>",
+    reaction = "ReactionProcessOutput<M>"
+)]
+pub struct ChildProcessBuilder<M: ProcessMessages> {
+    /// Values to write to the stdin of the subprocess.
+    pub input: TypedPortKey<M::In, Input>,
+    /// Values parsed from the stdout of the subprocess.
+    pub output: TypedPortKey<M::Out, Output>,
+    /// The exit code of the subprocess, or `None` if it was terminated by a signal.
+    pub exit: TypedPortKey<Option<i32>, Output>,
+
+    rx: TypedActionKey<M::Out, Physical>,
+    exited: TypedActionKey<Option<i32>, Physical>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ChildProcessBuilder::<M>", triggers(startup))]
+struct ReactionProcessStartup<M: ProcessMessages> {
+    rx: runtime::AsyncActionRef<M::Out>,
+    exited: runtime::AsyncActionRef<Option<i32>>,
+}
+
+impl<M: ProcessMessages> runtime::Trigger<ChildProcess<M>> for ReactionProcessStartup<M> {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut ChildProcess<M>) {
+        let mut child = match state.config.command().spawn() {
+            Ok(child) => child,
+            Err(err) => {
+                tracing::error!("Failed to spawn {}: {err}", state.config.program);
+                ctx.schedule_shutdown(None);
+                return;
+            }
+        };
+        state.stdin = child.stdin.take();
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let send_ctx = ctx.make_send_context();
+
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => {
+                        if let Some(value) = parse_line(&line) {
+                            self.rx.schedule(&send_ctx, value, None);
+                        }
+                    }
+                    Err(err) => {
+                        tracing::error!("Error reading from subprocess: {err}");
+                        break;
+                    }
+                }
+            }
+            match child.wait() {
+                Ok(status) => self.exited.schedule(&send_ctx, status.code(), None),
+                Err(err) => tracing::error!("Error waiting for subprocess: {err}"),
+            }
+        });
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ChildProcessBuilder::<M>")]
+struct ReactionProcessInput<'a, M: ProcessMessages> {
+    input: runtime::InputRef<'a, M::In>,
+}
+
+impl<M: ProcessMessages> runtime::Trigger<ChildProcess<M>> for ReactionProcessInput<'_, M> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut ChildProcess<M>) {
+        let (Some(stdin), Some(value)) = (state.stdin.as_mut(), self.input.as_ref()) else {
+            return;
+        };
+        let res = serde_json::to_writer(&mut *stdin, value)
+            .map_err(std::io::Error::from)
+            .and_then(|_| stdin.write_all(b"\n"))
+            .and_then(|_| stdin.flush());
+        if let Err(err) = res {
+            tracing::error!("Error writing to subprocess: {err}");
+            state.stdin = None;
+        }
+    }
+}
+
+/// Sends the values and exit code of the subprocess, and closes its stdin once it exited or at shutdown.
+#[derive(Reaction)]
+#[reaction(reactor = "ChildProcessBuilder::<M>", triggers(shutdown))]
+struct ReactionProcessOutput<'a, M: ProcessMessages> {
+    #[reaction(triggers)]
+    rx: runtime::ActionRef<'a, M::Out>,
+    #[reaction(triggers)]
+    exited: runtime::ActionRef<'a, Option<i32>>,
+    output: runtime::OutputRef<'a, M::Out>,
+    exit: runtime::OutputRef<'a, Option<i32>>,
+}
+
+impl<M: ProcessMessages> runtime::Trigger<ChildProcess<M>> for ReactionProcessOutput<'_, M> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut ChildProcess<M>) {
+        if self.rx.is_present(ctx) {
+            *self.output = self.rx.get_value(ctx).cloned();
+            return;
+        }
+        if let Some(&code) = self.exited.get_value(ctx) {
+            tracing::debug!("{} exited with code {code:?}", state.config.program);
+            *self.exit = Some(code);
+        }
+        drop(state.stdin.take()); // close the stdin of the subprocess
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(parse_line::<Vec<u32>>("[1, 2]"), Some(vec![1, 2]));
+        assert_eq!(parse_line::<u32>(""), None);
+        assert_eq!(parse_line::<u32>("not json"), None);
+    }
+
+    /// Sends a single value at startup.
+    #[derive(Reactor)]
+    #[reactor(state = "u32", reaction = "ReactionSend")]
+    struct Source {
+        out: TypedPortKey<u32, Output>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Source", triggers(startup))]
+    struct ReactionSend<'a> {
+        out: runtime::OutputRef<'a, u32>,
+    }
+
+    impl runtime::Trigger<u32> for ReactionSend<'_> {
+        fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u32) {
+            *self.out = Some(*state);
+        }
+    }
+
+    type Received = (Vec<u32>, Option<Option<i32>>);
+
+    /// Records the received values, and shuts down when the subprocess exited.
+    #[derive(Reactor)]
+    #[reactor(
+        state = "Received",
+        reaction = "ReactionValue",
+        reaction = "ReactionExit"
+    )]
+    struct Sink {
+        value: TypedPortKey<u32, Input>,
+        exit: TypedPortKey<Option<i32>, Input>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Sink")]
+    struct ReactionValue<'a> {
+        value: runtime::InputRef<'a, u32>,
+    }
+
+    impl runtime::Trigger<Received> for ReactionValue<'_> {
+        fn trigger(self, _ctx: &mut runtime::Context, state: &mut Received) {
+            state.0.extend(*self.value);
+        }
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Sink")]
+    struct ReactionExit<'a> {
+        exit: runtime::InputRef<'a, Option<i32>>,
+    }
+
+    impl runtime::Trigger<Received> for ReactionExit<'_> {
+        fn trigger(self, ctx: &mut runtime::Context, state: &mut Received) {
+            state.1 = *self.exit;
+            ctx.schedule_shutdown(None);
+        }
+    }
+
+    #[derive(Reactor)]
+    #[reactor(
+        state = "()",
+        connection(from = "source.out", to = "child.input"),
+        connection(from = "child.output", to = "sink.value"),
+        connection(from = "child.exit", to = "sink.exit")
+    )]
+    struct Main {
+        #[reactor(child = 21)]
+        source: Source,
+        #[reactor(child = ChildProcess::new(ProcessConfig::new("sh").with_args([
+            "-c",
+            "read x; echo $((x * 2)); echo not json; echo 1; exit 3",
+        ])))]
+        child: ChildProcessBuilder<(u32, u32)>,
+        #[reactor(child = Received::default())]
+        sink: Sink,
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_child_process() {
+        let mut env_builder = EnvBuilder::new();
+        let _main = Main::build("main", (), None, None, &mut env_builder).unwrap();
+        let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+        let config = runtime::Config::default()
+            .with_keep_alive(true)
+            .with_timeout(runtime::Duration::seconds(10));
+        let mut sched = runtime::Scheduler::new(env, graph, config);
+        sched.event_loop().unwrap();
+
+        let env = sched.into_env();
+        let received = env
+            .find_reactor_by_name("sink")
+            .and_then(|reactor| reactor.get_state::<Received>())
+            .unwrap();
+        assert_eq!(received, &(vec![42, 1], Some(Some(3))));
+    }
+}
@@ -0,0 +1,407 @@
+//! Compatibility with the reaction API of [reactor-rust](https://github.com/lf-lang/reactor-rust), the runtime of the
+//! Lingua Franca Rust target.
+//!
+//! [`ReactionCtx`] wraps the [`runtime::Context`] of a reaction, and provides the methods of the `ReactionCtx` of
+//! reactor-rust on Boomerang's ports and actions, so that the bodies of reactions written for the LF Rust target can be
+//! pasted into a [`runtime::Trigger`] implementation with few changes. The [`prelude`] re-exports the types under their
+//! reactor-rust names, along with [`Offset`] and the [`delay!`](crate::delay) macro.
+//!
+//! Only the reaction bodies are covered: the reactors, their ports, actions and connections are declared with the
+//! Boomerang derive macros instead of the assembly code generated by `lfc`. The differences to reactor-rust are:
+//!
+//! - Actions always carry a value in Boomerang, so [`ReactionCtx::schedule_with_v`] takes the value instead of an
+//!   `Option`. Reading an action needs a mutable reference, e.g. `ctx.get(&mut self.act)`.
+//! - Times are `std::time::Duration` like in reactor-rust, and the tag is a Boomerang [`runtime::Tag`].
+//! - Events that can't be scheduled, e.g. a physical action behind logical time in fast-forward mode, are logged and
+//!   dropped, as for asynchronous events.
+//! - There is no `spawn_physical_thread`; use [`runtime::Context::make_send_context`] with a [`PhysicalActionRef`]
+//!   and a thread of your own.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! use boomerang_util::reactor_rust::prelude::*;
+//!
+//! impl runtime::Trigger<u32> for ReactionTick<'_> {
+//!     fn trigger(mut self, ctx: &mut runtime::Context, count: &mut u32) {
+//!         let mut ctx = ReactionCtx::new(ctx);
+//!         // The body of the LF reaction
+//!         *count += 1;
+//!         ctx.set(&mut self.out, *count);
+//!         ctx.schedule(&mut self.act, After(delay!(10 ms)));
+//!         if *count == 10 {
+//!             ctx.request_stop(Asap);
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::borrow::BorrowMut;
+
+use boomerang::runtime::{self, ContextCommon, ReactorData};
+
+pub use std::time::{Duration, Instant};
+
+/// The tag of an event, `EventTag` in reactor-rust.
+pub type EventTag = runtime::Tag;
+/// An input port, `ReadablePort` in reactor-rust.
+pub type ReadablePort<'a, T> = runtime::InputRef<'a, T>;
+/// An output port, `WritablePort` in reactor-rust.
+pub type WritablePort<'a, T> = runtime::OutputRef<'a, T>;
+/// A logical action, `LogicalAction` in reactor-rust.
+pub type LogicalAction<'a, T = ()> = runtime::ActionRef<'a, T>;
+/// A physical action that can be scheduled from other threads, `PhysicalActionRef` in reactor-rust.
+pub type PhysicalActionRef<T = ()> = runtime::AsyncActionRef<T>;
+
+/// The delay of a scheduled event after the current tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Offset {
+    /// As soon as possible, i.e. at the next microstep, or after the minimum delay of an action
+    Asap,
+    /// After the given duration
+    After(Duration),
+}
+
+impl Offset {
+    fn to_duration(self) -> runtime::Duration {
+        match self {
+            Offset::Asap => runtime::Duration::ZERO,
+            Offset::After(duration) => {
+                runtime::Duration::try_from(duration).unwrap_or(runtime::Duration::MAX)
+            }
+        }
+    }
+}
+
+/// Create a [`Duration`] from an amount and a time unit, e.g. `delay!(10 ms)`, as in reactor-rust.
+#[macro_export]
+macro_rules! delay {
+    (0) => {
+        ::std::time::Duration::ZERO
+    };
+    ($amount:literal ns) => {
+        ::std::time::Duration::from_nanos($amount)
+    };
+    ($amount:literal nsec) => {
+        ::std::time::Duration::from_nanos($amount)
+    };
+    ($amount:literal us) => {
+        ::std::time::Duration::from_micros($amount)
+    };
+    ($amount:literal usec) => {
+        ::std::time::Duration::from_micros($amount)
+    };
+    ($amount:literal ms) => {
+        ::std::time::Duration::from_millis($amount)
+    };
+    ($amount:literal msec) => {
+        ::std::time::Duration::from_millis($amount)
+    };
+    ($amount:literal s) => {
+        ::std::time::Duration::from_secs($amount)
+    };
+    ($amount:literal sec) => {
+        ::std::time::Duration::from_secs($amount)
+    };
+    ($amount:literal secs) => {
+        ::std::time::Duration::from_secs($amount)
+    };
+    ($amount:literal min) => {
+        ::std::time::Duration::from_secs($amount * 60)
+    };
+    ($amount:literal mins) => {
+        ::std::time::Duration::from_secs($amount * 60)
+    };
+    ($amount:literal h) => {
+        ::std::time::Duration::from_secs($amount * 3600)
+    };
+    ($amount:literal hours) => {
+        ::std::time::Duration::from_secs($amount * 3600)
+    };
+    ($amount:literal d) => {
+        ::std::time::Duration::from_secs($amount * 86400)
+    };
+    ($amount:literal days) => {
+        ::std::time::Duration::from_secs($amount * 86400)
+    };
+}
+
+/// Ports and actions whose value at the current tag can be read, `ReactionTrigger` in reactor-rust.
+pub trait ReactionTrigger<T> {
+    /// Apply `f` to the value at the current tag, if present.
+    fn use_value<O>(self, ctx: &runtime::Context, f: impl FnOnce(Option<&T>) -> O) -> O;
+}
+
+impl<T: ReactorData> ReactionTrigger<T> for &runtime::InputRef<'_, T> {
+    fn use_value<O>(self, _ctx: &runtime::Context, f: impl FnOnce(Option<&T>) -> O) -> O {
+        f(self.as_ref())
+    }
+}
+
+impl<T: ReactorData> ReactionTrigger<T> for &runtime::OutputRef<'_, T> {
+    fn use_value<O>(self, _ctx: &runtime::Context, f: impl FnOnce(Option<&T>) -> O) -> O {
+        f(self.as_ref())
+    }
+}
+
+impl<T: ReactorData> ReactionTrigger<T> for &mut runtime::ActionRef<'_, T> {
+    fn use_value<O>(self, ctx: &runtime::Context, f: impl FnOnce(Option<&T>) -> O) -> O {
+        f(self.get_value(ctx))
+    }
+}
+
+/// Actions that can be scheduled from a reaction, `SchedulableAsAction` in reactor-rust.
+pub trait SchedulableAction<T> {
+    /// Schedule `value` on the action, `delay` after the current tag and the minimum delay of the action.
+    fn schedule_value(
+        &mut self,
+        ctx: &mut runtime::Context,
+        value: T,
+        delay: runtime::Duration,
+    ) -> Result<(), runtime::RuntimeError>;
+}
+
+impl<T: ReactorData> SchedulableAction<T> for runtime::ActionRef<'_, T> {
+    fn schedule_value(
+        &mut self,
+        ctx: &mut runtime::Context,
+        value: T,
+        delay: runtime::Duration,
+    ) -> Result<(), runtime::RuntimeError> {
+        self.schedule(ctx, value, Some(delay))
+    }
+}
+
+impl<T: ReactorData> SchedulableAction<T> for runtime::AsyncActionRef<T> {
+    fn schedule_value(
+        &mut self,
+        ctx: &mut runtime::Context,
+        value: T,
+        delay: runtime::Duration,
+    ) -> Result<(), runtime::RuntimeError> {
+        self.schedule(&ctx.make_send_context(), value, Some(delay));
+        Ok(())
+    }
+}
+
+/// The context of a reaction, with the API of the `ReactionCtx` of reactor-rust, see the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct ReactionCtx<'a> {
+    ctx: &'a mut runtime::Context,
+}
+
+impl<'a> From<&'a mut runtime::Context> for ReactionCtx<'a> {
+    fn from(ctx: &'a mut runtime::Context) -> Self {
+        Self::new(ctx)
+    }
+}
+
+impl<'a> ReactionCtx<'a> {
+    pub fn new(ctx: &'a mut runtime::Context) -> Self {
+        Self { ctx }
+    }
+
+    /// The wrapped Boomerang context.
+    pub fn inner(&mut self) -> &mut runtime::Context {
+        self.ctx
+    }
+
+    /// The physical time at which the program started.
+    pub fn get_start_time(&self) -> Instant {
+        self.ctx.get_start_time()
+    }
+
+    /// The current physical time.
+    pub fn get_physical_time(&self) -> Instant {
+        self.ctx.get_physical_time()
+    }
+
+    /// The current logical time.
+    pub fn get_logical_time(&self) -> Instant {
+        self.ctx.get_logical_time()
+    }
+
+    /// The current tag.
+    pub fn get_tag(&self) -> EventTag {
+        self.ctx.get_tag()
+    }
+
+    /// The logical time elapsed since the start of the program.
+    pub fn get_elapsed_logical_time(&self) -> Duration {
+        self.ctx.get_elapsed_logical_time().unsigned_abs()
+    }
+
+    /// The physical time elapsed since the start of the program.
+    pub fn get_elapsed_physical_time(&self) -> Duration {
+        self.get_physical_time() - self.get_start_time()
+    }
+
+    /// A copy of the value of `container` at the current tag, if present.
+    pub fn get<T: Copy>(&self, container: impl ReactionTrigger<T>) -> Option<T> {
+        container.use_value(self.ctx, |value| value.copied())
+    }
+
+    /// Apply `f` to the value of `container` at the current tag, if present.
+    pub fn use_ref<T, O>(
+        &self,
+        container: impl ReactionTrigger<T>,
+        f: impl FnOnce(Option<&T>) -> O,
+    ) -> O {
+        container.use_value(self.ctx, f)
+    }
+
+    /// Apply `f` to the value of `container` at the current tag, only if present.
+    pub fn use_ref_opt<T, O>(
+        &self,
+        container: impl ReactionTrigger<T>,
+        f: impl FnOnce(&T) -> O,
+    ) -> Option<O> {
+        container.use_value(self.ctx, |value| value.map(f))
+    }
+
+    /// Whether `container` has a value at the current tag.
+    pub fn is_present<T>(&self, container: impl ReactionTrigger<T>) -> bool {
+        container.use_value(self.ctx, |value| value.is_some())
+    }
+
+    /// Set the value of the output `port`, given by value or by reference.
+    pub fn set<'p, T: ReactorData>(
+        &mut self,
+        mut port: impl BorrowMut<runtime::OutputRef<'p, T>>,
+        value: T,
+    ) {
+        **port.borrow_mut() = Some(value);
+    }
+
+    /// Set or clear the value of the output `port`, given by value or by reference.
+    pub fn set_opt<'p, T: ReactorData>(
+        &mut self,
+        mut port: impl BorrowMut<runtime::OutputRef<'p, T>>,
+        value: Option<T>,
+    ) {
+        **port.borrow_mut() = value;
+    }
+
+    /// Schedule the valueless `action` after `offset`.
+    pub fn schedule(&mut self, action: &mut impl SchedulableAction<()>, offset: Offset) {
+        self.schedule_with_v(action, (), offset);
+    }
+
+    /// Schedule `value` on `action` after `offset`.
+    pub fn schedule_with_v<T>(
+        &mut self,
+        action: &mut impl SchedulableAction<T>,
+        value: T,
+        offset: Offset,
+    ) {
+        if let Err(err) = action.schedule_value(self.ctx, value, offset.to_duration()) {
+            tracing::warn!("Dropping an event that can't be scheduled: {err}");
+        }
+    }
+
+    /// Request the program to stop after `offset`.
+    pub fn request_stop(&mut self, offset: Offset) {
+        self.ctx.schedule_shutdown(Some(offset.to_duration()));
+    }
+}
+
+/// The reactor-rust names of the reaction API, see the [module documentation](self).
+pub mod prelude {
+    pub use super::{
+        Duration, EventTag, Instant, LogicalAction, Offset,
+        Offset::{After, Asap},
+        PhysicalActionRef, ReactionCtx, ReactionTrigger, ReadablePort, SchedulableAction,
+        WritablePort,
+    };
+    pub use crate::delay;
+    pub use boomerang::runtime;
+}
+
+#[cfg(test)]
+mod tests {
+    use boomerang::prelude::*;
+
+    use super::prelude::{After, Asap, ReactionCtx};
+
+    type Received = Vec<u32>;
+
+    #[test]
+    fn test_delay() {
+        assert_eq!(delay!(0), std::time::Duration::ZERO);
+        assert_eq!(delay!(10 ms), std::time::Duration::from_millis(10));
+        assert_eq!(delay!(2 min), std::time::Duration::from_secs(120));
+    }
+
+    /// Counts ticks of a timer and re-schedules an action, as written for the LF Rust target.
+    #[derive(Reactor)]
+    #[reactor(
+        state = "Received",
+        reaction = "ReactionTick",
+        reaction = "ReactionAct"
+    )]
+    struct Counter {
+        #[reactor(timer(period = "10 msec"))]
+        tick: TimerActionKey,
+        act: TypedActionKey<u32>,
+        out: TypedPortKey<u32, Output>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Counter", triggers(action = "tick"))]
+    struct ReactionTick<'a> {
+        #[reaction(effects)]
+        act: runtime::ActionRef<'a, u32>,
+    }
+
+    impl runtime::Trigger<Received> for ReactionTick<'_> {
+        fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut Received) {
+            let mut ctx = ReactionCtx::new(ctx);
+            let millis = ctx.get_elapsed_logical_time().as_millis() as u32;
+            ctx.schedule_with_v(&mut self.act, millis, After(delay!(1 ms)));
+        }
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Counter")]
+    struct ReactionAct<'a> {
+        #[reaction(triggers)]
+        act: runtime::ActionRef<'a, u32>,
+        out: runtime::OutputRef<'a, u32>,
+    }
+
+    impl runtime::Trigger<Received> for ReactionAct<'_> {
+        fn trigger(mut self, ctx: &mut runtime::Context, received: &mut Received) {
+            let mut ctx = ReactionCtx::new(ctx);
+            assert!(ctx.is_present(&mut self.act));
+            let value = ctx.get(&mut self.act).unwrap();
+            received.push(value);
+            ctx.set(&mut self.out, value);
+            assert_eq!(ctx.get(&self.out), Some(value));
+            assert_eq!(
+                ctx.get_tag().offset(),
+                runtime::Duration::milliseconds(value as i64 + 1)
+            );
+            if received.len() == 3 {
+                ctx.request_stop(Asap);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reaction_ctx() {
+        let mut env_builder = EnvBuilder::new();
+        let _ = Counter::build("counter", Vec::new(), None, None, &mut env_builder).unwrap();
+        let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+        let config = runtime::Config::default().with_fast_forward(true);
+        let mut sched = runtime::Scheduler::new(env, graph, config);
+        sched.event_loop().unwrap();
+
+        let env = sched.into_env();
+        let received = env
+            .find_reactor_by_name("counter")
+            .and_then(|reactor| reactor.get_state::<Received>())
+            .unwrap();
+        assert_eq!(received, &[0, 10, 20]);
+    }
+}
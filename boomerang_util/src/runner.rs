@@ -8,10 +8,34 @@
 //!         boomerang_util::runner::build_and_run_reactor::<MyReactor>("my_reactor_instance", ()).unwrap();
 //! }
 //! ```
+//!
+//! Or equivalently, using the `#[boomerang::main]` attribute macro:
+//!
+//! ```rust,ignore
+//! #[boomerang::main(reactor = "MyReactor", name = "my_reactor_instance", config(keep_alive = true))]
+//! fn main() {}
+//! ```
+//!
+//! Applications composed of several separately-authored top-level reactors can build them side by side and wire them
+//! together with [`build_and_run_composition`]:
+//!
+//! ```rust,ignore
+//! fn main() {
+//!     let _ = boomerang_util::runner::build_and_run_composition(
+//!         "app",
+//!         (top_level::<Sensor>("sensor", ()), top_level::<Logger>("logger", ())),
+//!         |env_builder, (sensor, logger)| {
+//!             env_builder.connect_ports::<f64, _, _>(sensor.reading, logger.inp, None, false)?;
+//!             Ok(())
+//!         },
+//!     )
+//!     .unwrap();
+//! }
+//! ```
 
 use anyhow::Context;
 use boomerang::{
-    builder::{graphviz, EnvBuilder, Reactor},
+    builder::{graphviz, BuilderError, EnvBuilder, Reactor},
     runtime,
 };
 use clap::Parser;
@@ -25,11 +49,23 @@ struct Args {
     #[arg(long)]
     reaction_graph: bool,
 
+    /// Export the reactor model with the metadata of all elements as JSON
+    #[arg(long)]
+    json_graph: bool,
+
     #[arg(long)]
     print_debug_info: bool,
 
-    #[arg(long, short)]
-    fast_forward: bool,
+    /// The log filter, e.g., "info" or "boomerang_runtime=debug", overriding `BOOMERANG_LOG` and `RUST_LOG`
+    #[arg(long)]
+    log_level: Option<String>,
+
+    /// The log levels of reactor subtrees, e.g., "snake.keyboard=trace,snake=info", overriding `BOOMERANG_REACTOR_LOG`
+    #[arg(long)]
+    reactor_log: Option<String>,
+
+    #[command(flatten)]
+    config: runtime::overrides::ConfigArgs,
 
     /// The filename to serialize recorded actions into
     #[cfg(feature = "replay")]
@@ -48,6 +84,113 @@ pub fn build_and_test_reactor<R: Reactor>(
     state: R::State,
     config: runtime::Config,
 ) -> anyhow::Result<(R, runtime::Scheduler)> {
+    build_and_test_reactor_expecting::<R>(name, state, config, Expectations::default())
+}
+
+/// Utility method to build and run a given top-level `Reactor` from tests, and check `expectations` on the logical
+/// time of its events after the run.
+///
+/// All unmet expectations are reported together in the returned error.
+///
+/// ## Example:
+///
+/// ```rust,ignore
+/// let expectations = Expectations::default()
+///     .expect_port_at(Tag::new(Duration::milliseconds(1), 0), "counter::out", 1u32)
+///     .expect_shutdown_at(Tag::new(Duration::milliseconds(3), 0));
+/// build_and_test_reactor_expecting::<Counter>("counter", 0, config, expectations)?;
+/// ```
+pub fn build_and_test_reactor_expecting<R: Reactor>(
+    name: &str,
+    state: R::State,
+    config: runtime::Config,
+    expectations: Expectations,
+) -> anyhow::Result<(R, runtime::Scheduler)> {
+    let (reactor, mut env_builder) = build_test_reactor::<R>(name, state)?;
+
+    let Expectations {
+        probes,
+        ports,
+        shutdown,
+    } = expectations;
+    for add_probe in probes.into_values() {
+        add_probe(&mut env_builder).context("Error adding an expectation!")?;
+    }
+
+    let (env, graph, _) = env_builder
+        .into_runtime_parts()
+        .context("Error building environment!")?;
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    let events = shutdown.is_some().then(|| sched.subscribe());
+    sched.event_loop()?;
+
+    let mut unmet = Vec::new();
+    for expected in ports {
+        let observed = sched
+            .probe_hits()
+            .iter()
+            .filter(|hit| hit.probe == expected.fqn)
+            .collect::<Vec<_>>();
+        if !observed
+            .iter()
+            .any(|hit| hit.tag == expected.tag && (expected.matches)(hit))
+        {
+            let observed = observed
+                .iter()
+                .map(|hit| format!("{} at {}", hit.value, hit.tag))
+                .collect::<Vec<_>>();
+            unmet.push(format!(
+                "Expected {} = {} at {}, but got [{}]",
+                expected.fqn,
+                expected.debug,
+                expected.tag,
+                observed.join(", ")
+            ));
+        }
+    }
+    if let Some((expected, events)) = shutdown.zip(events) {
+        let actual = events.try_iter().find_map(|event| match event {
+            runtime::RuntimeEvent::ShutdownInitiated { tag } => Some(tag),
+            _ => None,
+        });
+        if actual != Some(expected) {
+            let actual = actual.map_or("never".to_owned(), |tag| format!("at {tag}"));
+            unmet.push(format!(
+                "Expected shutdown at {expected}, but it was {actual}"
+            ));
+        }
+    }
+    if !unmet.is_empty() {
+        anyhow::bail!("Unmet expectations:\n  {}", unmet.join("\n  "));
+    }
+
+    Ok((reactor, sched))
+}
+
+/// Utility method to build and run a given top-level `Reactor` from tests, returning a typed key for its state.
+///
+/// ## Example:
+///
+/// ```rust,ignore
+/// let (_, sched, key) = build_and_test_reactor_typed::<Count>("count", 0, config)?;
+/// assert_eq!(*sched.into_env().state(key), 1000);
+/// ```
+pub fn build_and_test_reactor_typed<R: Reactor>(
+    name: &str,
+    state: R::State,
+    config: runtime::Config,
+) -> anyhow::Result<(R, runtime::Scheduler, runtime::TypedReactorKey<R::State>)> {
+    let (reactor, env_builder) = build_test_reactor::<R>(name, state)?;
+    let built = env_builder
+        .build_runtime::<R>(name)
+        .context("Error building environment!")?;
+    let mut sched = runtime::Scheduler::new(built.env, built.reaction_graph, config);
+    sched.event_loop()?;
+    Ok((reactor, sched, built.reactor))
+}
+
+/// Build the top-level `Reactor` for a test run, writing its plantuml graph if the `PUML` environment variable is set.
+fn build_test_reactor<R: Reactor>(name: &str, state: R::State) -> anyhow::Result<(R, EnvBuilder)> {
     let mut env_builder = EnvBuilder::new();
     let reactor = R::build(name, state, None, None, &mut env_builder)
         .context("Error building top-level reactor!")?;
@@ -59,13 +202,116 @@ pub fn build_and_test_reactor<R: Reactor>(
         std::io::Write::write_all(&mut f, gv.as_bytes())?;
         tracing::info!("Wrote plantuml graph to {path}");
     }
+    Ok((reactor, env_builder))
+}
 
-    let (env, graph, _) = env_builder
-        .into_runtime_parts()
-        .context("Error building environment!")?;
-    let mut sched = runtime::Scheduler::new(env, graph, config);
-    sched.event_loop();
-    Ok((reactor, sched))
+/// A value expected on a port at a tag.
+struct PortExpectation {
+    fqn: String,
+    tag: runtime::Tag,
+    /// The `Debug` representation of the value, for reporting
+    debug: String,
+    /// Whether a probe hit carries the expected value
+    matches: Box<dyn Fn(&runtime::ProbeSnapshot) -> bool>,
+}
+
+/// Adds the probe collecting the values of a port.
+type AddProbeFn = Box<dyn FnOnce(&mut EnvBuilder) -> Result<(), BuilderError>>;
+
+/// Expectations on the logical time of events in a test run, see [`build_and_test_reactor_expecting`].
+///
+/// The values of the ports are captured with probes (see [`EnvBuilder::add_capturing_probe`]), and compared with the
+/// expected values by `PartialEq`.
+#[derive(Default)]
+pub struct Expectations {
+    /// One probe per expected port, by its fully-qualified name
+    probes: std::collections::BTreeMap<String, AddProbeFn>,
+    ports: Vec<PortExpectation>,
+    shutdown: Option<runtime::Tag>,
+}
+
+impl Expectations {
+    /// Expect the port with the fully-qualified name `fqn` to be set to `value` at `tag`.
+    pub fn expect_port_at<T>(mut self, tag: runtime::Tag, fqn: &str, value: T) -> Self
+    where
+        T: runtime::ReactorData + std::fmt::Debug + Clone + PartialEq,
+    {
+        let name = fqn.to_owned();
+        self.probes.entry(fqn.to_owned()).or_insert_with(|| {
+            Box::new(move |env_builder: &mut EnvBuilder| {
+                env_builder.add_capturing_probe(&name, |_: &T| true)
+            })
+        });
+        self.ports.push(PortExpectation {
+            fqn: fqn.to_owned(),
+            tag,
+            debug: format!("{value:?}"),
+            matches: Box::new(move |hit| hit.value_as::<T>() == Some(&value)),
+        });
+        self
+    }
+
+    /// Expect the scheduler to shut down at `tag`.
+    pub fn expect_shutdown_at(mut self, tag: runtime::Tag) -> Self {
+        self.shutdown = Some(tag);
+        self
+    }
+}
+
+/// The environment variable holding the log filter, taking precedence over `RUST_LOG`.
+pub const ENV_LOG: &str = "BOOMERANG_LOG";
+
+/// The environment variable holding the log levels of reactor subtrees, see [`crate::log_filter`].
+pub const ENV_REACTOR_LOG: &str = "BOOMERANG_REACTOR_LOG";
+
+/// Initialize a `tracing` subscriber that logs to stdout, filtered by the `BOOMERANG_LOG` environment variable, or
+/// `RUST_LOG` if it is not set.
+///
+/// This is a no-op if a global subscriber has already been set.
+pub fn init_logging() {
+    init_logging_with_filter(None);
+}
+
+fn env_filter(filter: Option<&str>) -> tracing_subscriber::EnvFilter {
+    match filter
+        .map(str::to_owned)
+        .or_else(|| std::env::var(ENV_LOG).ok())
+    {
+        Some(filter) => tracing_subscriber::EnvFilter::new(filter),
+        None => tracing_subscriber::EnvFilter::from_default_env(),
+    }
+}
+
+fn init_logging_with_filter(filter: Option<&str>) {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(env_filter(filter))
+        .try_init();
+}
+
+/// Initialize a `tracing` subscriber like [`init_logging`], with the events of reactions filtered per reactor subtree
+/// by `directives`, e.g. "snake.keyboard=trace,snake=info".
+///
+/// The returned handle replaces the directives at runtime. The scheduler must be configured with
+/// [`runtime::Config::with_reaction_spans`], see [`crate::log_filter`]. This fails if the directives are invalid, or if
+/// a global subscriber has already been set.
+pub fn init_logging_with_reactor_filter(
+    directives: &str,
+) -> anyhow::Result<crate::log_filter::ReactorFilterHandle> {
+    init_reactor_logging(None, directives)
+}
+
+fn init_reactor_logging(
+    filter: Option<&str>,
+    directives: &str,
+) -> anyhow::Result<crate::log_filter::ReactorFilterHandle> {
+    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
+
+    let filter = crate::log_filter::ReactorFilter::new(directives, env_filter(filter))?;
+    let handle = filter.handle();
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(filter))
+        .try_init()?;
+    Ok(handle)
 }
 
 /// Utility method to build and run a given top-level `Reactor`.
@@ -81,18 +327,81 @@ pub fn build_and_test_reactor<R: Reactor>(
 /// Common arguments are parsed from the command line and passed to the scheduler:
 /// * `--full-graph`: Generate a graphviz graph of the entire reactor hierarchy
 /// * `--reaction-graph`: Generate a graphviz graph of the reaction hierarchy
+/// * `--json-graph`: Export the reactor model with its metadata as JSON, see [`EnvBuilder::create_json_graph`]
 /// * `--print-debug-info`: Print debug information about the environment and triggers
-/// * `--fast-forward`: Run the scheduler in fast-forward mode
+/// * `--log-level`: The log filter, see [`init_logging`]
+/// * `--reactor-log`: The log levels of reactor subtrees, see [`init_logging_with_reactor_filter`]
+/// * `--fast-forward[=<bool>]`: Run the scheduler in fast-forward mode
+/// * `--keep-alive[=<bool>]`: Keep the scheduler alive waiting for asynchronous events
+/// * `--timeout`: Stop the scheduler after the given amount of logical time
+/// * `--workers`: The number of worker threads to use for parallel execution
+/// * `--queue-size`: The capacity of the physical event queue
 /// * `--record-filename`: The filename to serialize recorded actions into
 /// * `--record-actions`: The list of fully-qualified actions to record, e.g., "snake::keyboard::key_press"
 pub fn build_and_run_reactor<R: Reactor>(name: &str, state: R::State) -> anyhow::Result<R> {
+    build_and_run_reactor_with_config::<R>(name, state, runtime::Config::default())
+}
+
+/// Utility method to build and run a given top-level `Reactor` with a base `Config`.
+///
+/// The values in `config` are overridden by the environment variables documented in [`runtime::overrides`], which are
+/// in turn overridden by any options given on the command line (see [`build_and_run_reactor`]).
+///
+/// Logging is initialized with [`init_logging`] unless a global subscriber has already been set.
+pub fn build_and_run_reactor_with_config<R: Reactor>(
+    name: &str,
+    state: R::State,
+    config: runtime::Config,
+) -> anyhow::Result<R> {
+    let (args, config) = parse_args(config)?;
+
     // build the reactor
     let mut env_builder = EnvBuilder::new();
     let reactor = R::build(name, state, None, None, &mut env_builder)
         .context("Error building top-level reactor!")?;
 
+    run_env_builder(name, env_builder, args, config)?;
+    Ok(reactor)
+}
+
+/// Parse the command line arguments, initialize logging and apply the overrides to `config`.
+fn parse_args(config: runtime::Config) -> anyhow::Result<(Args, runtime::Config)> {
     let args = Args::parse();
+    let reactor_log = args
+        .reactor_log
+        .clone()
+        .or_else(|| std::env::var(ENV_REACTOR_LOG).ok());
+    let config = match reactor_log {
+        Some(directives) => {
+            if let Err(err) = init_reactor_logging(args.log_level.as_deref(), &directives) {
+                init_logging_with_filter(args.log_level.as_deref());
+                tracing::warn!("Unable to filter the logs by reactor: {err}");
+            }
+            config.with_reaction_spans(true)
+        }
+        None => {
+            init_logging_with_filter(args.log_level.as_deref());
+            config
+        }
+    };
 
+    let config = config
+        .with_env_overrides()
+        .context("Invalid environment override")?;
+    let config = args
+        .config
+        .apply(config)
+        .context("Invalid command line override")?;
+    Ok((args, config))
+}
+
+/// Run the built `env_builder` according to the command line `args`, naming any generated files after `name`.
+fn run_env_builder(
+    name: &str,
+    #[allow(unused_mut)] mut env_builder: EnvBuilder,
+    args: Args,
+    config: runtime::Config,
+) -> anyhow::Result<()> {
     #[cfg(feature = "replay")]
     if let Some(filename) = args.record_filename {
         tracing::info!("Recording actions to {filename:?}");
@@ -126,6 +435,14 @@ pub fn build_and_run_reactor<R: Reactor>(name: &str, state: R::State) -> anyhow:
         tracing::info!("Wrote plantuml graph to {path}");
     }
 
+    if args.json_graph {
+        let json = env_builder.create_json_graph()?;
+        let path = format!("{name}.json");
+        let mut f = std::fs::File::create(&path)?;
+        std::io::Write::write_all(&mut f, json.as_bytes())?;
+        tracing::info!("Wrote JSON graph to {path}");
+    }
+
     if args.print_debug_info {
         println!("{env_builder:#?}");
     }
@@ -137,13 +454,145 @@ pub fn build_and_run_reactor<R: Reactor>(name: &str, state: R::State) -> anyhow:
         println!("{triggers:#?}");
     }
 
-    let config = runtime::Config {
-        fast_forward: args.fast_forward,
-        ..Default::default()
+    let mut sched = runtime::Scheduler::new(env, triggers, config);
+    sched.event_loop()?;
+    Ok(())
+}
+
+/// A top-level reactor to build as part of a [`Composition`], see [`top_level`].
+pub struct TopLevel<R: Reactor> {
+    name: String,
+    state: R::State,
+}
+
+/// Declare the top-level reactor `R` named `name` with the initial `state`, to build as part of a [`Composition`].
+pub fn top_level<R: Reactor>(name: &str, state: R::State) -> TopLevel<R> {
+    TopLevel {
+        name: name.to_owned(),
+        state,
+    }
+}
+
+/// Several top-level reactors built side by side into a single environment.
+///
+/// This is implemented for tuples of up to 8 [`TopLevel`] reactors, which are built in order.
+pub trait Composition {
+    /// The built reactors, as a tuple of the same arity.
+    type Reactors;
+
+    /// Build all reactors into `env_builder`.
+    fn build(self, env_builder: &mut EnvBuilder) -> Result<Self::Reactors, BuilderError>;
+}
+
+impl<R: Reactor> Composition for TopLevel<R> {
+    type Reactors = R;
+
+    fn build(self, env_builder: &mut EnvBuilder) -> Result<Self::Reactors, BuilderError> {
+        R::build(&self.name, self.state, None, None, env_builder)
+    }
+}
+
+macro_rules! impl_composition {
+    ($($T:ident),+) => {
+        impl<$($T: Composition),+> Composition for ($($T,)+) {
+            type Reactors = ($($T::Reactors,)+);
+
+            #[allow(non_snake_case)]
+            fn build(self, env_builder: &mut EnvBuilder) -> Result<Self::Reactors, BuilderError> {
+                let ($($T,)+) = self;
+                Ok(($($T.build(env_builder)?,)+))
+            }
+        }
     };
+}
 
-    let mut sched = runtime::Scheduler::new(env, triggers, config);
-    sched.event_loop();
+impl_composition!(A);
+impl_composition!(A, B);
+impl_composition!(A, B, C);
+impl_composition!(A, B, C, D);
+impl_composition!(A, B, C, D, E);
+impl_composition!(A, B, C, D, E, F);
+impl_composition!(A, B, C, D, E, F, G);
+impl_composition!(A, B, C, D, E, F, G, H);
 
-    Ok(reactor)
+/// Build the top-level `reactors` of a composition, and `wire` the connections between them.
+///
+/// Returns the built reactors and the combined `EnvBuilder`, ready to be turned into a runtime environment.
+///
+/// ## Example:
+///
+/// ```rust,ignore
+/// let ((sensor, logger), env_builder) = build_composition(
+///     (top_level::<Sensor>("sensor", ()), top_level::<Logger>("logger", ())),
+///     |env_builder, (sensor, logger)| {
+///         env_builder.connect_ports::<f64, _, _>(sensor.reading, logger.inp, None, false)?;
+///         Ok(())
+///     },
+/// )?;
+/// ```
+pub fn build_composition<C, W>(reactors: C, wire: W) -> anyhow::Result<(C::Reactors, EnvBuilder)>
+where
+    C: Composition,
+    W: FnOnce(&mut EnvBuilder, &C::Reactors) -> anyhow::Result<()>,
+{
+    let mut env_builder = EnvBuilder::new();
+    let reactors = reactors
+        .build(&mut env_builder)
+        .context("Error building top-level reactors!")?;
+    wire(&mut env_builder, &reactors).context("Error wiring top-level reactors!")?;
+    Ok((reactors, env_builder))
+}
+
+/// Utility method to build, wire and run the top-level `reactors` of a composition from tests, see
+/// [`build_composition`].
+pub fn build_and_test_composition<C, W>(
+    reactors: C,
+    wire: W,
+    config: runtime::Config,
+) -> anyhow::Result<(C::Reactors, runtime::Scheduler)>
+where
+    C: Composition,
+    W: FnOnce(&mut EnvBuilder, &C::Reactors) -> anyhow::Result<()>,
+{
+    let (reactors, env_builder) = build_composition(reactors, wire)?;
+    let (env, graph, _) = env_builder
+        .into_runtime_parts()
+        .context("Error building environment!")?;
+    let mut sched = runtime::Scheduler::new(env, graph, config);
+    sched.event_loop()?;
+    Ok((reactors, sched))
+}
+
+/// Utility method to build, wire and run the top-level `reactors` of a composition, see [`build_composition`].
+///
+/// This is the counterpart of [`build_and_run_reactor`] for applications composed of several top-level reactors, and
+/// accepts the same command line arguments. Generated files are named after `name`.
+pub fn build_and_run_composition<C, W>(
+    name: &str,
+    reactors: C,
+    wire: W,
+) -> anyhow::Result<C::Reactors>
+where
+    C: Composition,
+    W: FnOnce(&mut EnvBuilder, &C::Reactors) -> anyhow::Result<()>,
+{
+    build_and_run_composition_with_config(name, reactors, wire, runtime::Config::default())
+}
+
+/// Utility method to build, wire and run the top-level `reactors` of a composition with a base `Config`, see
+/// [`build_and_run_reactor_with_config`].
+pub fn build_and_run_composition_with_config<C, W>(
+    name: &str,
+    reactors: C,
+    wire: W,
+    config: runtime::Config,
+) -> anyhow::Result<C::Reactors>
+where
+    C: Composition,
+    W: FnOnce(&mut EnvBuilder, &C::Reactors) -> anyhow::Result<()>,
+{
+    let (args, config) = parse_args(config)?;
+    let (reactors, env_builder) = build_composition(reactors, wire)?;
+    run_env_builder(name, env_builder, args, config)?;
+    Ok(reactors)
 }
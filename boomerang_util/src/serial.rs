@@ -0,0 +1,347 @@
+//! Serial port (UART) source and sink reactors.
+//!
+//! [`SerialSourceBuilder`] reads from a serial device on a background thread, splits the received bytes into frames
+//! according to a [`Framing`], and schedules a physical action with each frame. [`SerialSinkBuilder`] writes every
+//! value received on its input port out to a serial device.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! #[derive(Reactor)]
+//! #[reactor(state = "()", connection(from = "source.frame", to = "sink.data"))]
+//! struct Loopback {
+//!     #[reactor(child = SerialSource::new(SerialConfig::new("/dev/ttyUSB0", 115_200), Framing::Delimiter(b'\n')).unwrap())]
+//!     source: SerialSourceBuilder,
+//!     #[reactor(child = SerialSink::new(SerialConfig::new("/dev/ttyUSB1", 115_200)))]
+//!     sink: SerialSinkBuilder,
+//! }
+//! ```
+
+use std::{
+    io::{Read, Write},
+    sync::Mutex,
+};
+
+use boomerang::prelude::*;
+
+pub use serialport::{DataBits, FlowControl, Parity, StopBits};
+
+/// Settings used to open a serial device.
+#[derive(Debug, Clone)]
+pub struct SerialConfig {
+    /// The path of the device, e.g., "/dev/ttyUSB0" or "COM3"
+    pub path: String,
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub flow_control: FlowControl,
+    /// Read timeout, after which the reader thread checks whether the scheduler has shut down.
+    pub timeout: std::time::Duration,
+}
+
+impl SerialConfig {
+    /// Create a new config with the given device path and baud rate, and 8N1 framing without flow control.
+    pub fn new(path: impl Into<String>, baud_rate: u32) -> Self {
+        Self {
+            path: path.into(),
+            baud_rate,
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+            timeout: std::time::Duration::from_millis(100),
+        }
+    }
+
+    pub fn with_data_bits(mut self, data_bits: DataBits) -> Self {
+        self.data_bits = data_bits;
+        self
+    }
+
+    pub fn with_parity(mut self, parity: Parity) -> Self {
+        self.parity = parity;
+        self
+    }
+
+    pub fn with_stop_bits(mut self, stop_bits: StopBits) -> Self {
+        self.stop_bits = stop_bits;
+        self
+    }
+
+    pub fn with_flow_control(mut self, flow_control: FlowControl) -> Self {
+        self.flow_control = flow_control;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Open the serial device described by this config.
+    pub fn open(&self) -> serialport::Result<Box<dyn serialport::SerialPort>> {
+        serialport::new(&self.path, self.baud_rate)
+            .data_bits(self.data_bits)
+            .parity(self.parity)
+            .stop_bits(self.stop_bits)
+            .flow_control(self.flow_control)
+            .timeout(self.timeout)
+            .open()
+    }
+}
+
+/// How received bytes are split into frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Framing {
+    /// Every successful read is emitted as a frame.
+    #[default]
+    Raw,
+    /// Frames are terminated by the given delimiter byte, which is not included in the frame.
+    Delimiter(u8),
+    /// Frames have a fixed, non-zero length in bytes.
+    FixedLength(usize),
+}
+
+/// Accumulates received bytes and splits them into frames.
+#[derive(Debug)]
+struct FrameDecoder {
+    framing: Framing,
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    /// Create a decoder for `framing`, failing for frames of length 0.
+    fn new(framing: Framing) -> serialport::Result<Self> {
+        if framing == Framing::FixedLength(0) {
+            return Err(serialport::Error::new(
+                serialport::ErrorKind::InvalidInput,
+                "Fixed-length frames must not be empty",
+            ));
+        }
+        Ok(Self {
+            framing,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Push received bytes into the decoder, calling `emit` for each complete frame.
+    fn push(&mut self, bytes: &[u8], mut emit: impl FnMut(Vec<u8>)) {
+        match self.framing {
+            Framing::Raw => {
+                if !bytes.is_empty() {
+                    emit(bytes.to_vec());
+                }
+            }
+            Framing::Delimiter(delimiter) => {
+                for &byte in bytes {
+                    if byte == delimiter {
+                        emit(std::mem::take(&mut self.buffer));
+                    } else {
+                        self.buffer.push(byte);
+                    }
+                }
+            }
+            Framing::FixedLength(len) => {
+                self.buffer.extend_from_slice(bytes);
+                while self.buffer.len() >= len {
+                    let rest = self.buffer.split_off(len);
+                    emit(std::mem::replace(&mut self.buffer, rest));
+                }
+            }
+        }
+    }
+}
+
+/// State of the [`SerialSourceBuilder`] reactor.
+#[derive(Debug)]
+pub struct SerialSource {
+    config: SerialConfig,
+    /// Handed to the reading thread at startup
+    decoder: Option<FrameDecoder>,
+}
+
+impl SerialSource {
+    /// Create the state of a source, failing if `framing` is invalid, i.e. [`Framing::FixedLength`] of 0 bytes.
+    pub fn new(config: SerialConfig, framing: Framing) -> serialport::Result<Self> {
+        Ok(Self {
+            config,
+            decoder: Some(FrameDecoder::new(framing)?),
+        })
+    }
+}
+
+/// Reads frames from a serial device and sends them through an output port.
+#[derive(Reactor, Clone)]
+#[reactor(
+    state = "SerialSource",
+    reaction = "ReactionSourceStartup",
+    reaction = "ReactionFrame"
+)]
+pub struct SerialSourceBuilder {
+    /// The most recently received frame.
+    pub frame: TypedPortKey<Vec<u8>, Output>,
+
+    rx: TypedActionKey<Vec<u8>, Physical>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "SerialSourceBuilder", triggers(startup))]
+struct ReactionSourceStartup {
+    rx: runtime::AsyncActionRef<Vec<u8>>,
+}
+
+impl runtime::Trigger<SerialSource> for ReactionSourceStartup {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut SerialSource) {
+        let mut port = match state.config.open() {
+            Ok(port) => port,
+            Err(err) => {
+                tracing::error!("Failed to open serial port {}: {err}", state.config.path);
+                ctx.schedule_shutdown(None);
+                return;
+            }
+        };
+
+        let Some(mut decoder) = state.decoder.take() else {
+            return;
+        };
+        let send_ctx = ctx.make_send_context();
+
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 1024];
+            while !send_ctx.is_shutdown() {
+                match port.read(&mut buf) {
+                    Ok(n) => decoder.push(&buf[..n], |frame| {
+                        tracing::trace!("received frame {frame:?}");
+                        self.rx.schedule(&send_ctx, frame, None);
+                    }),
+                    Err(err) if err.kind() == std::io::ErrorKind::TimedOut => {}
+                    Err(err) => {
+                        tracing::error!("Error reading from serial port: {err}");
+                        break;
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "SerialSourceBuilder")]
+struct ReactionFrame<'a> {
+    #[reaction(triggers)]
+    rx: runtime::ActionRef<'a, Vec<u8>>,
+    frame: runtime::OutputRef<'a, Vec<u8>>,
+}
+
+impl runtime::Trigger<SerialSource> for ReactionFrame<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut SerialSource) {
+        *self.frame = self.rx.get_value(ctx).cloned();
+    }
+}
+
+/// State of the [`SerialSinkBuilder`] reactor.
+#[derive(Debug)]
+pub struct SerialSink {
+    config: SerialConfig,
+    port: Option<Mutex<Box<dyn serialport::SerialPort>>>,
+}
+
+impl SerialSink {
+    pub fn new(config: SerialConfig) -> Self {
+        Self { config, port: None }
+    }
+}
+
+/// Writes values received on its input port out to a serial device.
+#[derive(Reactor, Clone)]
+#[reactor(
+    state = "SerialSink",
+    reaction = "ReactionSinkStartup",
+    reaction = "ReactionData",
+    reaction = "ReactionSinkShutdown"
+)]
+pub struct SerialSinkBuilder {
+    /// Bytes to write to the serial device.
+    pub data: TypedPortKey<Vec<u8>, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "SerialSinkBuilder", triggers(startup))]
+struct ReactionSinkStartup;
+
+impl runtime::Trigger<SerialSink> for ReactionSinkStartup {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut SerialSink) {
+        match state.config.open() {
+            Ok(port) => state.port = Some(Mutex::new(port)),
+            Err(err) => {
+                tracing::error!("Failed to open serial port {}: {err}", state.config.path);
+                ctx.schedule_shutdown(None);
+            }
+        }
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "SerialSinkBuilder")]
+struct ReactionData<'a> {
+    data: runtime::InputRef<'a, Vec<u8>>,
+}
+
+impl runtime::Trigger<SerialSink> for ReactionData<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut SerialSink) {
+        let (Some(port), Some(data)) = (state.port.as_mut(), self.data.as_ref()) else {
+            return;
+        };
+        let port = port.get_mut().expect("Serial port lock poisoned");
+        if let Err(err) = port.write_all(data).and_then(|_| port.flush()) {
+            tracing::error!("Error writing to serial port: {err}");
+        }
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "SerialSinkBuilder", triggers(shutdown))]
+struct ReactionSinkShutdown;
+
+impl runtime::Trigger<SerialSink> for ReactionSinkShutdown {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut SerialSink) {
+        drop(state.port.take()); // close the device
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(framing: Framing, chunks: &[&[u8]]) -> Vec<Vec<u8>> {
+        let mut decoder = FrameDecoder::new(framing).unwrap();
+        let mut frames = vec![];
+        for chunk in chunks {
+            decoder.push(chunk, |frame| frames.push(frame));
+        }
+        frames
+    }
+
+    #[test]
+    fn test_frame_decoder() {
+        assert_eq!(
+            decode(Framing::Raw, &[b"ab", b"", b"c"]),
+            vec![b"ab".to_vec(), b"c".to_vec()]
+        );
+        assert_eq!(
+            decode(Framing::Delimiter(b'\n'), &[b"ab\nc", b"d\n\ne"]),
+            vec![b"ab".to_vec(), b"cd".to_vec(), vec![]]
+        );
+        assert_eq!(
+            decode(Framing::FixedLength(3), &[b"ab", b"cdefg", b"hi"]),
+            vec![b"abc".to_vec(), b"def".to_vec(), b"ghi".to_vec()]
+        );
+        assert!(FrameDecoder::new(Framing::FixedLength(0)).is_err());
+        assert!(SerialSource::new(
+            SerialConfig::new("/dev/null", 9600),
+            Framing::FixedLength(0)
+        )
+        .is_err());
+    }
+}
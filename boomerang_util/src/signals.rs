@@ -0,0 +1,267 @@
+//! Delivering OS signals (SIGINT, SIGTERM, ...) to reactors as physical actions.
+//!
+//! A [`SignalSourceBuilder`] reactor registers itself at startup to receive the [`Signal`]s it handles, and schedules a
+//! physical action for every signal [`raise`]d while the scheduler runs. The action sets its `signal` output port, so
+//! downstream reactions can clean up at the tag of the signal, and then requests a shutdown through the normal
+//! scheduler path, so the shutdown reactions run instead of the process being killed mid-tag.
+//!
+//! Installing a process signal handler needs platform support outside of `std`, so the application forwards its
+//! signals to [`raise`] from the handler of its choice, e.g., `ctrlc` or `signal-hook`. When no reactor is registered,
+//! [`raise`] returns `false` and the application can fall back to exiting.
+//!
+//! The scheduler only waits for signals while it has nothing else to do with `Config::keep_alive` set. Once it shuts
+//! down, its sources unregister, and further signals are not delivered.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! #[derive(Reactor)]
+//! #[reactor(state = "()", connection(from = "signals.signal", to = "logger.stop"))]
+//! struct Main {
+//!     #[reactor(child = SignalSource::default())]
+//!     signals: SignalSourceBuilder,
+//!     #[reactor(child = ())]
+//!     logger: Logger,
+//! }
+//!
+//! ctrlc::set_handler(|| {
+//!     if !boomerang_util::signals::raise(Signal::Interrupt) {
+//!         std::process::exit(130);
+//!     }
+//! })?;
+//! ```
+
+use std::sync::Mutex;
+
+use boomerang::prelude::*;
+
+/// An OS signal requesting the program to stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// SIGINT, e.g., Ctrl-C in a terminal
+    Interrupt,
+    /// SIGTERM
+    Terminate,
+    /// SIGHUP
+    Hangup,
+}
+
+impl std::fmt::Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Signal::Interrupt => "SIGINT",
+            Signal::Terminate => "SIGTERM",
+            Signal::Hangup => "SIGHUP",
+        })
+    }
+}
+
+/// Delivers a signal to a running source, returning `false` once its scheduler has shut down.
+type Listener = Box<dyn FnMut(Signal) -> bool + Send>;
+
+/// The sources currently running, keyed by their id.
+static LISTENERS: Mutex<Vec<(u64, Vec<Signal>, Listener)>> = Mutex::new(Vec::new());
+
+/// Deliver `signal` to every running [`SignalSourceBuilder`] handling it.
+///
+/// Returns whether any source received the signal.
+pub fn raise(signal: Signal) -> bool {
+    let mut listeners = LISTENERS.lock().expect("Signal listeners poisoned");
+    let mut delivered = false;
+    listeners.retain_mut(|(_, signals, listener)| {
+        if !signals.contains(&signal) {
+            return true;
+        }
+        let running = listener(signal);
+        delivered |= running;
+        running
+    });
+    delivered
+}
+
+/// State of the [`SignalSourceBuilder`] reactor.
+#[derive(Debug, Clone)]
+pub struct SignalSource {
+    signals: Vec<Signal>,
+    shutdown: bool,
+    /// The id of the registered listener, while running
+    id: Option<u64>,
+}
+
+impl Default for SignalSource {
+    /// Handle SIGINT and SIGTERM, and shut down on either.
+    fn default() -> Self {
+        Self::new([Signal::Interrupt, Signal::Terminate])
+    }
+}
+
+impl SignalSource {
+    /// Handle the given signals, and shut down on any of them.
+    pub fn new(signals: impl IntoIterator<Item = Signal>) -> Self {
+        Self {
+            signals: signals.into_iter().collect(),
+            shutdown: true,
+            id: None,
+        }
+    }
+
+    /// Whether to request a shutdown after emitting a signal. If `false`, the signal is only emitted.
+    pub fn with_shutdown(mut self, shutdown: bool) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+}
+
+/// Emits the OS signals [`raise`]d while the scheduler runs, see the [module documentation](self).
+#[derive(Reactor, Clone)]
+#[reactor(
+    state = "SignalSource",
+    reaction = "ReactionSignalStartup",
+    reaction = "ReactionSignal",
+    reaction = "ReactionSignalShutdown"
+)]
+pub struct SignalSourceBuilder {
+    /// The signal received.
+    pub signal: TypedPortKey<Signal, Output>,
+
+    rx: TypedActionKey<Signal, Physical>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "SignalSourceBuilder", triggers(startup))]
+struct ReactionSignalStartup {
+    rx: runtime::AsyncActionRef<Signal>,
+}
+
+impl runtime::Trigger<SignalSource> for ReactionSignalStartup {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut SignalSource) {
+        static NEXT_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        let send_ctx = ctx.make_send_context();
+        let listener: Listener = Box::new(move |signal| {
+            if send_ctx.is_shutdown() {
+                return false;
+            }
+            tracing::info!(%signal, "Received signal.");
+            self.rx.schedule(&send_ctx, signal, None);
+            true
+        });
+        LISTENERS.lock().expect("Signal listeners poisoned").push((
+            id,
+            state.signals.clone(),
+            listener,
+        ));
+        state.id = Some(id);
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "SignalSourceBuilder")]
+struct ReactionSignal<'a> {
+    #[reaction(triggers)]
+    rx: runtime::ActionRef<'a, Signal>,
+    signal: runtime::OutputRef<'a, Signal>,
+}
+
+impl runtime::Trigger<SignalSource> for ReactionSignal<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, state: &mut SignalSource) {
+        *self.signal = self.rx.get_value(ctx).copied();
+        if state.shutdown {
+            // Shut down at the next microstep, after the reactions to the signal
+            ctx.schedule_shutdown(None);
+        }
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "SignalSourceBuilder", triggers(shutdown))]
+struct ReactionSignalShutdown;
+
+impl runtime::Trigger<SignalSource> for ReactionSignalShutdown {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut SignalSource) {
+        if let Some(id) = state.id.take() {
+            LISTENERS
+                .lock()
+                .expect("Signal listeners poisoned")
+                .retain(|(listener_id, ..)| *listener_id != id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Recorded = Vec<Signal>;
+
+    #[derive(Reactor)]
+    #[reactor(state = "Recorded", reaction = "ReactionRecord")]
+    struct Recorder {
+        signal: TypedPortKey<Signal, Input>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Recorder")]
+    struct ReactionRecord<'a> {
+        signal: runtime::InputRef<'a, Signal>,
+    }
+
+    impl runtime::Trigger<Recorded> for ReactionRecord<'_> {
+        fn trigger(self, _ctx: &mut runtime::Context, state: &mut Recorded) {
+            state.extend(*self.signal);
+        }
+    }
+
+    #[allow(dead_code)]
+    #[derive(Reactor)]
+    #[reactor(
+        state = "()",
+        connection(from = "signals.signal", to = "recorder.signal")
+    )]
+    struct Main {
+        #[reactor(child = SignalSource::new([Signal::Terminate]))]
+        signals: SignalSourceBuilder,
+        #[reactor(child = Recorded::new())]
+        recorder: Recorder,
+    }
+
+    #[test]
+    fn test_signals() {
+        let mut env_builder = EnvBuilder::new();
+        let _ = Main::build("main", (), None, None, &mut env_builder).unwrap();
+        let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+
+        let handle = std::thread::spawn(move || {
+            let mut sched = runtime::Scheduler::new(
+                env,
+                graph,
+                runtime::Config::default()
+                    .with_keep_alive(true)
+                    .with_timeout(Duration::seconds(10)),
+            );
+            sched.event_loop().unwrap();
+            sched.into_env()
+        });
+
+        // Wait for the source to register
+        let start = std::time::Instant::now();
+        while LISTENERS.lock().unwrap().is_empty() {
+            assert!(start.elapsed() < std::time::Duration::from_secs(5));
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        // Not handled by the source
+        assert!(!raise(Signal::Hangup));
+        assert!(raise(Signal::Terminate));
+
+        let env = handle.join().unwrap();
+        let recorded = env
+            .find_reactor_by_name("recorder")
+            .and_then(|reactor| reactor.get_state::<Recorded>())
+            .unwrap();
+        assert_eq!(recorded, &[Signal::Terminate]);
+        // The source unregistered at shutdown
+        assert!(LISTENERS.lock().unwrap().is_empty());
+        assert!(!raise(Signal::Terminate));
+    }
+}
@@ -0,0 +1,337 @@
+//! Generic building-block Reactors.
+//!
+//! - [`FifoBuilder`]: a bounded queue, emitting the oldest queued value on every `pop` request.
+//! - [`DelayLineBuilder`]: delays a stream of values by a fixed number of samples.
+//! - [`SamplerBuilder`]: holds the latest input value and emits it whenever `trigger` is present.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! #[derive(Reactor)]
+//! #[reactor(
+//!     state = "()",
+//!     connection(from = "source.out", to = "fifo.input"),
+//!     connection(from = "sink.request", to = "fifo.pop"),
+//!     connection(from = "fifo.output", to = "sink.inp")
+//! )]
+//! struct Main {
+//!     #[reactor(child = ())]
+//!     source: SourceBuilder,
+//!     #[reactor(child = Fifo::new(16))]
+//!     fifo: FifoBuilder<u32>,
+//!     #[reactor(child = ())]
+//!     sink: SinkBuilder,
+//! }
+//! ```
+
+use std::collections::VecDeque;
+
+use boomerang::prelude::*;
+
+/// State of the [`FifoBuilder`] reactor.
+#[derive(Debug)]
+pub struct Fifo<T> {
+    capacity: usize,
+    queue: VecDeque<T>,
+    /// Number of values dropped because the queue was full
+    dropped: usize,
+}
+
+impl<T> Fifo<T> {
+    /// Create a new queue holding at most `capacity` values.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            queue: VecDeque::with_capacity(capacity),
+            dropped: 0,
+        }
+    }
+
+    /// Number of currently queued values.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Total number of values dropped because the queue was full.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+}
+
+/// A bounded queue. Values received on `input` are queued, and the oldest queued value is emitted on `output` whenever
+/// `pop` is present. Values received while the queue is full are dropped.
+///
+/// A value pushed and popped at the same tag is emitted immediately.
+#[derive(Reactor)]
+#[reactor(
+    state = "Fifo::<T>",
+    reaction = "ReactionFifoPush<T>",
+    reaction = "ReactionFifoPop<T>"
+)]
+pub struct FifoBuilder<T: runtime::ReactorData + Clone> {
+    /// Values to queue.
+    pub input: TypedPortKey<T, Input>,
+    /// Request to emit the oldest queued value.
+    pub pop: TypedPortKey<(), Input>,
+    /// The popped values.
+    pub output: TypedPortKey<T, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "FifoBuilder::<T>")]
+struct ReactionFifoPush<'a, T: runtime::ReactorData + Clone> {
+    input: runtime::InputRef<'a, T>,
+}
+
+impl<T: runtime::ReactorData + Clone> runtime::Trigger<Fifo<T>> for ReactionFifoPush<'_, T> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut Fifo<T>) {
+        if let Some(value) = self.input.as_ref() {
+            if state.queue.len() < state.capacity {
+                state.queue.push_back(value.clone());
+            } else {
+                state.dropped += 1;
+                tracing::debug!("Fifo full, dropping value ({} dropped)", state.dropped);
+            }
+        }
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "FifoBuilder::<T>", triggers(port = "pop"))]
+struct ReactionFifoPop<'a, T: runtime::ReactorData + Clone> {
+    output: runtime::OutputRef<'a, T>,
+}
+
+impl<T: runtime::ReactorData + Clone> runtime::Trigger<Fifo<T>> for ReactionFifoPop<'_, T> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut Fifo<T>) {
+        *self.output = state.queue.pop_front();
+    }
+}
+
+/// State of the [`DelayLineBuilder`] reactor.
+#[derive(Debug)]
+pub struct DelayLine<T> {
+    depth: usize,
+    buffer: VecDeque<T>,
+}
+
+impl<T> DelayLine<T> {
+    /// Create a new delay line emitting each value `depth` samples after it was received.
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            buffer: VecDeque::with_capacity(depth + 1),
+        }
+    }
+}
+
+/// Delays a stream of values by a fixed number of samples. Each time a value is present on `input`, the value received
+/// `depth` samples earlier is emitted on `output`. Nothing is emitted until `depth` values have been received.
+#[derive(Reactor)]
+#[reactor(state = "DelayLine::<T>", reaction = "ReactionDelayLine<T>")]
+pub struct DelayLineBuilder<T: runtime::ReactorData + Clone> {
+    pub input: TypedPortKey<T, Input>,
+    pub output: TypedPortKey<T, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "DelayLineBuilder::<T>")]
+struct ReactionDelayLine<'a, T: runtime::ReactorData + Clone> {
+    input: runtime::InputRef<'a, T>,
+    output: runtime::OutputRef<'a, T>,
+}
+
+impl<T: runtime::ReactorData + Clone> runtime::Trigger<DelayLine<T>> for ReactionDelayLine<'_, T> {
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut DelayLine<T>) {
+        if let Some(value) = self.input.as_ref() {
+            state.buffer.push_back(value.clone());
+            if state.buffer.len() > state.depth {
+                *self.output = state.buffer.pop_front();
+            }
+        }
+    }
+}
+
+/// State of the [`SamplerBuilder`] reactor.
+#[derive(Debug)]
+pub struct Sampler<T> {
+    /// The most recently received value
+    latest: Option<T>,
+}
+
+impl<T> Default for Sampler<T> {
+    fn default() -> Self {
+        Self { latest: None }
+    }
+}
+
+impl<T> Sampler<T> {
+    /// The most recently received value.
+    pub fn latest(&self) -> Option<&T> {
+        self.latest.as_ref()
+    }
+}
+
+/// Sample-and-hold. The latest value received on `input` is emitted on `output` whenever `trigger` is present. A value
+/// received at the same tag as the trigger is sampled immediately.
+#[derive(Reactor)]
+#[reactor(
+    state = "Sampler::<T>",
+    reaction = "ReactionSamplerInput<T>",
+    reaction = "ReactionSamplerTrigger<T>"
+)]
+pub struct SamplerBuilder<T: runtime::ReactorData + Clone> {
+    /// The sampled value.
+    pub input: TypedPortKey<T, Input>,
+    /// Take a sample.
+    pub trigger: TypedPortKey<(), Input>,
+    pub output: TypedPortKey<T, Output>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "SamplerBuilder::<T>")]
+struct ReactionSamplerInput<'a, T: runtime::ReactorData + Clone> {
+    input: runtime::InputRef<'a, T>,
+}
+
+impl<T: runtime::ReactorData + Clone> runtime::Trigger<Sampler<T>> for ReactionSamplerInput<'_, T> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut Sampler<T>) {
+        if let Some(value) = self.input.as_ref() {
+            state.latest = Some(value.clone());
+        }
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "SamplerBuilder::<T>", triggers(port = "trigger"))]
+struct ReactionSamplerTrigger<'a, T: runtime::ReactorData + Clone> {
+    output: runtime::OutputRef<'a, T>,
+}
+
+impl<T: runtime::ReactorData + Clone> runtime::Trigger<Sampler<T>>
+    for ReactionSamplerTrigger<'_, T>
+{
+    fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut Sampler<T>) {
+        *self.output = state.latest.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Emits an increasing count every msec, and a tick every 3 msec.
+    #[derive(Reactor)]
+    #[reactor(state = "u32", reaction = "ReactionSource")]
+    struct Source {
+        #[reactor(timer(period = "1 msec"))]
+        tick: TimerActionKey,
+        out: TypedPortKey<u32, Output>,
+        every3: TypedPortKey<(), Output>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Source", triggers(action = "tick"))]
+    struct ReactionSource<'a> {
+        out: runtime::OutputRef<'a, u32>,
+        every3: runtime::OutputRef<'a>,
+    }
+
+    impl runtime::Trigger<u32> for ReactionSource<'_> {
+        fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u32) {
+            *self.out = Some(*state);
+            if *state % 3 == 2 {
+                *self.every3 = Some(());
+            }
+            *state += 1;
+        }
+    }
+
+    /// Records all received values.
+    #[derive(Reactor)]
+    #[reactor(state = "Vec::<u32>", reaction = "ReactionRecord")]
+    struct Recorder {
+        inp: TypedPortKey<u32, Input>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Recorder")]
+    struct ReactionRecord<'a> {
+        inp: runtime::InputRef<'a, u32>,
+    }
+
+    impl runtime::Trigger<Vec<u32>> for ReactionRecord<'_> {
+        fn trigger(self, _ctx: &mut runtime::Context, state: &mut Vec<u32>) {
+            state.extend(self.inp.as_ref().copied());
+        }
+    }
+
+    // Fanning out a port takes multiple `connection` attributes with the same `from`
+    #[allow(clippy::duplicated_attributes)]
+    #[derive(Reactor)]
+    #[reactor(
+        state = "()",
+        connection(from = "source.out", to = "fifo.input"),
+        connection(from = "source.every3", to = "fifo.pop"),
+        connection(from = "fifo.output", to = "fifo_out.inp"),
+        connection(from = "source.out", to = "delay.input"),
+        connection(from = "delay.output", to = "delay_out.inp"),
+        connection(from = "source.out", to = "sampler.input"),
+        connection(from = "source.every3", to = "sampler.trigger"),
+        connection(from = "sampler.output", to = "sampler_out.inp")
+    )]
+    struct Main {
+        #[reactor(child = 0)]
+        source: Source,
+        #[reactor(child = Fifo::new(2))]
+        fifo: FifoBuilder<u32>,
+        #[reactor(child = Vec::new())]
+        fifo_out: Recorder,
+        #[reactor(child = DelayLine::new(2))]
+        delay: DelayLineBuilder<u32>,
+        #[reactor(child = Vec::new())]
+        delay_out: Recorder,
+        #[reactor(child = Sampler::default())]
+        sampler: SamplerBuilder<u32>,
+        #[reactor(child = Vec::new())]
+        sampler_out: Recorder,
+    }
+
+    fn recorded(env: &runtime::Env, name: &str) -> Vec<u32> {
+        env.find_reactor_by_name(name)
+            .and_then(|reactor| reactor.get_state::<Vec<u32>>())
+            .cloned()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_templates() {
+        let mut env_builder = EnvBuilder::new();
+        let _reactor = Main::build("main", (), None, None, &mut env_builder).unwrap();
+        let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+        let config = runtime::Config::default()
+            .with_fast_forward(true)
+            .with_timeout(Duration::milliseconds(8));
+        let mut sched = runtime::Scheduler::new(env, graph, config);
+        sched.event_loop().unwrap();
+        let env = sched.into_env();
+
+        // Values 0..=8 are pushed and pops happen at 2, 5 and 8. Pushes are handled before pops, so with a capacity
+        // of 2 the values 2, 4, 5, 7 and 8 are dropped.
+        assert_eq!(recorded(&env, "fifo_out"), vec![0, 1, 3]);
+        assert_eq!(recorded(&env, "delay_out"), (0..=6).collect::<Vec<_>>());
+        assert_eq!(recorded(&env, "sampler_out"), vec![2, 5, 8]);
+
+        let fifo = env
+            .find_reactor_by_name("fifo")
+            .and_then(|reactor| reactor.get_state::<Fifo<u32>>())
+            .unwrap();
+        assert_eq!(fifo.len(), 1);
+        assert_eq!(fifo.dropped(), 5);
+    }
+}
@@ -0,0 +1,454 @@
+//! [Zenoh](https://zenoh.io) publisher, subscriber and peer discovery reactors.
+//!
+//! [`ZenohPublisherBuilder`] puts every value received on its input port on a key expression, and
+//! [`ZenohSubscriberBuilder`] schedules a physical action with every sample received on a key expression, which may
+//! contain wildcards. Both use a [`Session`] opened by the application, so a program can share a single session
+//! between all its bridges. [`ZenohScoutBuilder`] discovers the Zenoh peers and routers reachable with a [`Config`],
+//! and emits a [`ZenohPeer`] for each one.
+//!
+//! Payloads are raw bytes, the encoding is left to the application.
+//!
+//! ## Example:
+//!
+//! ```rust,ignore
+//! use zenoh::Wait;
+//!
+//! #[derive(Reactor)]
+//! #[reactor(state = "()", connection(from = "commands.sample", to = "robot.command"))]
+//! struct Fleet {
+//!     #[reactor(child = ZenohSubscriber::new(session.clone(), "fleet/robot1/command"))]
+//!     commands: ZenohSubscriberBuilder,
+//!     #[reactor(child = ZenohPublisher::new(session.clone(), "fleet/robot1/odometry"))]
+//!     odometry: ZenohPublisherBuilder,
+//!     #[reactor(child = ())]
+//!     robot: Robot,
+//! }
+//!
+//! let session = zenoh::open(zenoh::Config::default()).wait()?;
+//! ```
+
+use boomerang::prelude::*;
+use zenoh::{pubsub::Publisher, pubsub::Subscriber, scouting::Scout, Wait};
+
+pub use zenoh::{
+    config::{WhatAmI, WhatAmIMatcher, ZenohId},
+    Config, Session,
+};
+
+/// A sample received by a [`ZenohSubscriberBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZenohSample {
+    /// The key the sample was published on
+    pub key_expr: String,
+    pub payload: Vec<u8>,
+}
+
+/// State of the [`ZenohPublisherBuilder`] reactor.
+#[derive(Debug)]
+pub struct ZenohPublisher {
+    session: Session,
+    key_expr: String,
+    publisher: Option<Publisher<'static>>,
+}
+
+impl ZenohPublisher {
+    /// Publish on `key_expr` with `session`.
+    pub fn new(session: Session, key_expr: impl Into<String>) -> Self {
+        Self {
+            session,
+            key_expr: key_expr.into(),
+            publisher: None,
+        }
+    }
+}
+
+/// Publishes the values received on its input port on a Zenoh key expression.
+#[derive(Reactor, Clone)]
+#[reactor(
+    state = "ZenohPublisher",
+    reaction = "ReactionPublisherStartup",
+    reaction = "ReactionPublish",
+    reaction = "ReactionPublisherShutdown"
+)]
+pub struct ZenohPublisherBuilder {
+    /// Bytes to publish.
+    pub data: TypedPortKey<Vec<u8>, Input>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ZenohPublisherBuilder", triggers(startup))]
+struct ReactionPublisherStartup;
+
+impl runtime::Trigger<ZenohPublisher> for ReactionPublisherStartup {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut ZenohPublisher) {
+        match state
+            .session
+            .declare_publisher(state.key_expr.clone())
+            .wait()
+        {
+            Ok(publisher) => state.publisher = Some(publisher),
+            Err(err) => {
+                tracing::error!("Failed to declare publisher on {}: {err}", state.key_expr);
+                ctx.schedule_shutdown(None);
+            }
+        }
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ZenohPublisherBuilder")]
+struct ReactionPublish<'a> {
+    data: runtime::InputRef<'a, Vec<u8>>,
+}
+
+impl runtime::Trigger<ZenohPublisher> for ReactionPublish<'_> {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut ZenohPublisher) {
+        let (Some(publisher), Some(data)) = (state.publisher.as_ref(), self.data.as_ref()) else {
+            return;
+        };
+        if let Err(err) = publisher.put(data.clone()).wait() {
+            tracing::error!("Error publishing on {}: {err}", state.key_expr);
+        }
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ZenohPublisherBuilder", triggers(shutdown))]
+struct ReactionPublisherShutdown;
+
+impl runtime::Trigger<ZenohPublisher> for ReactionPublisherShutdown {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut ZenohPublisher) {
+        if let Some(Err(err)) = state
+            .publisher
+            .take()
+            .map(|publisher| publisher.undeclare().wait())
+        {
+            tracing::warn!("Error undeclaring publisher on {}: {err}", state.key_expr);
+        }
+    }
+}
+
+/// State of the [`ZenohSubscriberBuilder`] reactor.
+#[derive(Debug)]
+pub struct ZenohSubscriber {
+    session: Session,
+    key_expr: String,
+    subscriber: Option<Subscriber<()>>,
+}
+
+impl ZenohSubscriber {
+    /// Subscribe to `key_expr` with `session`.
+    pub fn new(session: Session, key_expr: impl Into<String>) -> Self {
+        Self {
+            session,
+            key_expr: key_expr.into(),
+            subscriber: None,
+        }
+    }
+}
+
+/// Receives the samples published on a Zenoh key expression and sends them through an output port.
+#[derive(Reactor, Clone)]
+#[reactor(
+    state = "ZenohSubscriber",
+    reaction = "ReactionSubscriberStartup",
+    reaction = "ReactionSample",
+    reaction = "ReactionSubscriberShutdown"
+)]
+pub struct ZenohSubscriberBuilder {
+    /// The most recently received sample.
+    pub sample: TypedPortKey<ZenohSample, Output>,
+
+    rx: TypedActionKey<ZenohSample, Physical>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ZenohSubscriberBuilder", triggers(startup))]
+struct ReactionSubscriberStartup {
+    rx: runtime::AsyncActionRef<ZenohSample>,
+}
+
+impl runtime::Trigger<ZenohSubscriber> for ReactionSubscriberStartup {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut ZenohSubscriber) {
+        let send_ctx = ctx.make_send_context();
+        let subscriber = state
+            .session
+            .declare_subscriber(state.key_expr.clone())
+            .callback(move |sample| {
+                if send_ctx.is_shutdown() {
+                    return;
+                }
+                let sample = ZenohSample {
+                    key_expr: sample.key_expr().to_string(),
+                    payload: sample.payload().to_bytes().into_owned(),
+                };
+                tracing::trace!("received sample {sample:?}");
+                self.rx.schedule(&send_ctx, sample, None);
+            })
+            .wait();
+
+        match subscriber {
+            Ok(subscriber) => state.subscriber = Some(subscriber),
+            Err(err) => {
+                tracing::error!("Failed to subscribe to {}: {err}", state.key_expr);
+                ctx.schedule_shutdown(None);
+            }
+        }
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ZenohSubscriberBuilder")]
+struct ReactionSample<'a> {
+    #[reaction(triggers)]
+    rx: runtime::ActionRef<'a, ZenohSample>,
+    sample: runtime::OutputRef<'a, ZenohSample>,
+}
+
+impl runtime::Trigger<ZenohSubscriber> for ReactionSample<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut ZenohSubscriber) {
+        *self.sample = self.rx.get_value(ctx).cloned();
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ZenohSubscriberBuilder", triggers(shutdown))]
+struct ReactionSubscriberShutdown;
+
+impl runtime::Trigger<ZenohSubscriber> for ReactionSubscriberShutdown {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut ZenohSubscriber) {
+        if let Some(Err(err)) = state
+            .subscriber
+            .take()
+            .map(|subscriber| subscriber.undeclare().wait())
+        {
+            tracing::warn!("Error undeclaring subscriber on {}: {err}", state.key_expr);
+        }
+    }
+}
+
+/// A Zenoh peer or router discovered by a [`ZenohScoutBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZenohPeer {
+    pub zid: ZenohId,
+    pub whatami: WhatAmI,
+    /// The locators the peer can be reached on, e.g., "tcp/192.168.1.2:7447"
+    pub locators: Vec<String>,
+}
+
+/// State of the [`ZenohScoutBuilder`] reactor.
+#[derive(Debug)]
+pub struct ZenohScout {
+    config: Config,
+    what: WhatAmIMatcher,
+    scout: Option<Scout<()>>,
+}
+
+impl ZenohScout {
+    /// Discover the peers and routers reachable with the scouting settings of `config`.
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            what: WhatAmI::Peer | WhatAmI::Router,
+            scout: None,
+        }
+    }
+
+    /// Only discover the given kinds of Zenoh nodes.
+    pub fn with_what(mut self, what: impl Into<WhatAmIMatcher>) -> Self {
+        self.what = what.into();
+        self
+    }
+}
+
+/// Discovers Zenoh peers and routers until shutdown, emitting each one through an output port.
+#[derive(Reactor, Clone)]
+#[reactor(
+    state = "ZenohScout",
+    reaction = "ReactionScoutStartup",
+    reaction = "ReactionPeer",
+    reaction = "ReactionScoutShutdown"
+)]
+pub struct ZenohScoutBuilder {
+    /// The most recently discovered peer.
+    pub peer: TypedPortKey<ZenohPeer, Output>,
+
+    rx: TypedActionKey<ZenohPeer, Physical>,
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ZenohScoutBuilder", triggers(startup))]
+struct ReactionScoutStartup {
+    rx: runtime::AsyncActionRef<ZenohPeer>,
+}
+
+impl runtime::Trigger<ZenohScout> for ReactionScoutStartup {
+    fn trigger(self, ctx: &mut runtime::Context, state: &mut ZenohScout) {
+        let send_ctx = ctx.make_send_context();
+        let scout = zenoh::scout(state.what, state.config.clone())
+            .callback(move |hello| {
+                if send_ctx.is_shutdown() {
+                    return;
+                }
+                let peer = ZenohPeer {
+                    zid: hello.zid(),
+                    whatami: hello.whatami(),
+                    locators: hello.locators().iter().map(ToString::to_string).collect(),
+                };
+                tracing::debug!("discovered peer {peer:?}");
+                self.rx.schedule(&send_ctx, peer, None);
+            })
+            .wait();
+
+        match scout {
+            Ok(scout) => state.scout = Some(scout),
+            Err(err) => {
+                tracing::error!("Failed to start scouting: {err}");
+                ctx.schedule_shutdown(None);
+            }
+        }
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ZenohScoutBuilder")]
+struct ReactionPeer<'a> {
+    #[reaction(triggers)]
+    rx: runtime::ActionRef<'a, ZenohPeer>,
+    peer: runtime::OutputRef<'a, ZenohPeer>,
+}
+
+impl runtime::Trigger<ZenohScout> for ReactionPeer<'_> {
+    fn trigger(mut self, ctx: &mut runtime::Context, _state: &mut ZenohScout) {
+        *self.peer = self.rx.get_value(ctx).cloned();
+    }
+}
+
+#[derive(Reaction)]
+#[reaction(reactor = "ZenohScoutBuilder", triggers(shutdown))]
+struct ReactionScoutShutdown;
+
+impl runtime::Trigger<ZenohScout> for ReactionScoutShutdown {
+    fn trigger(self, _ctx: &mut runtime::Context, state: &mut ZenohScout) {
+        if let Some(scout) = state.scout.take() {
+            scout.stop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Received = Vec<ZenohSample>;
+
+    /// Sends the count every msec.
+    #[derive(Reactor)]
+    #[reactor(state = "u8", reaction = "ReactionTick")]
+    struct Counter {
+        #[reactor(timer(period = "1 msec"))]
+        tick: TimerActionKey,
+        out: TypedPortKey<Vec<u8>, Output>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Counter", triggers(action = "tick"))]
+    struct ReactionTick<'a> {
+        out: runtime::OutputRef<'a, Vec<u8>>,
+    }
+
+    impl runtime::Trigger<u8> for ReactionTick<'_> {
+        fn trigger(mut self, _ctx: &mut runtime::Context, state: &mut u8) {
+            *self.out = Some(vec![*state]);
+            *state += 1;
+        }
+    }
+
+    /// Records the samples, and shuts down after the third.
+    #[derive(Reactor)]
+    #[reactor(state = "Received", reaction = "ReactionReceive")]
+    struct Recorder {
+        sample: TypedPortKey<ZenohSample, Input>,
+    }
+
+    #[derive(Reaction)]
+    #[reaction(reactor = "Recorder")]
+    struct ReactionReceive<'a> {
+        sample: runtime::InputRef<'a, ZenohSample>,
+    }
+
+    impl runtime::Trigger<Received> for ReactionReceive<'_> {
+        fn trigger(self, ctx: &mut runtime::Context, state: &mut Received) {
+            state.extend(self.sample.clone());
+            if state.len() == 3 {
+                ctx.schedule_shutdown(None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pubsub() {
+        let mut config = Config::default();
+        config
+            .insert_json5("scouting/multicast/enabled", "false")
+            .unwrap();
+        config.insert_json5("listen/endpoints", "[]").unwrap();
+        let session = zenoh::open(config).wait().unwrap();
+
+        let mut env_builder = EnvBuilder::new();
+        let main = env_builder
+            .add_reactor("main", None, None, ())
+            .finish()
+            .unwrap();
+        let mut builder = env_builder.get_reactor_builder(main).unwrap();
+        let counter = builder.add_child_reactor::<Counter>("counter", 0).unwrap();
+        let publisher = builder
+            .add_child_reactor::<ZenohPublisherBuilder>(
+                "publisher",
+                ZenohPublisher::new(session.clone(), "boomerang/test/count"),
+            )
+            .unwrap();
+        let subscriber = builder
+            .add_child_reactor::<ZenohSubscriberBuilder>(
+                "subscriber",
+                ZenohSubscriber::new(session.clone(), "boomerang/test/*"),
+            )
+            .unwrap();
+        let recorder = builder
+            .add_child_reactor::<Recorder>("recorder", Received::new())
+            .unwrap();
+        builder
+            .connect_port(counter.out, publisher.data, None, false)
+            .unwrap();
+        builder
+            .connect_port(subscriber.sample, recorder.sample, None, false)
+            .unwrap();
+
+        let (env, graph, _) = env_builder.into_runtime_parts().unwrap();
+        let mut sched = runtime::Scheduler::new(
+            env,
+            graph,
+            runtime::Config::default()
+                .with_keep_alive(true)
+                .with_timeout(Duration::seconds(10)),
+        );
+        sched.event_loop().unwrap();
+
+        let env = sched.into_env();
+        let received = env
+            .find_reactor_by_name("recorder")
+            .and_then(|reactor| reactor.get_state::<Received>())
+            .unwrap();
+        assert_eq!(
+            received
+                .iter()
+                .map(|sample| sample.payload[0])
+                .collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+        assert!(received
+            .iter()
+            .all(|sample| sample.key_expr == "boomerang/test/count"));
+        session.close().wait().unwrap();
+    }
+}
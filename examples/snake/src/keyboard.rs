@@ -2,14 +2,12 @@
 //!
 //! Pressing arrow keys will print them to the terminal.
 
-#[cfg(not(windows))]
 mod keyboard_events;
 
-#[cfg(not(windows))]
 mod example {
     use std::io::Write;
 
-    use crate::keyboard_events::{KeyboardEvents, KeyboardEventsBuilder};
+    use crate::keyboard_events::{Key, KeyboardEvents, KeyboardEventsBuilder};
     use boomerang::prelude::*;
 
     /// A simple Reactor that triggers on key_press events.
@@ -26,7 +24,7 @@ mod example {
     #[reaction(reactor = "Example")]
     struct ReactionKeyPress<'a> {
         #[reaction(path = "keyboard.arrow_key_pressed")]
-        arrow_key_pressed: runtime::InputRef<'a, termion::event::Key>,
+        arrow_key_pressed: runtime::InputRef<'a, Key>,
     }
 
     impl runtime::Trigger<()> for ReactionKeyPress<'_> {
@@ -36,10 +34,10 @@ mod example {
 
             // this might be overwritten several times, only committed on screen refreshes
             let c = match *self.arrow_key_pressed {
-                Some(termion::event::Key::Left) => '←',
-                Some(termion::event::Key::Right) => '→',
-                Some(termion::event::Key::Up) => '↑',
-                Some(termion::event::Key::Down) => '↓',
+                Some(Key::Left) => '←',
+                Some(Key::Right) => '→',
+                Some(Key::Up) => '↑',
+                Some(Key::Down) => '↓',
                 _ => unreachable!(),
             };
 
@@ -50,7 +48,6 @@ mod example {
     }
 }
 
-#[cfg(not(windows))]
 fn main() {
     use boomerang::prelude::*;
     tracing_subscriber::fmt::init();
@@ -64,8 +61,5 @@ fn main() {
         .with_fast_forward(false)
         .with_keep_alive(true);
     let mut sched = runtime::Scheduler::new(env, triggers, config);
-    sched.event_loop();
+    sched.event_loop().unwrap();
 }
-
-#[cfg(windows)]
-fn main() {}
@@ -1,18 +1,31 @@
 //! Capture asynchronous key presses, and sends them through an output port.
+//!
+//! Terminal handling is done through `crossterm`, so this works on Unix-like systems as well as on Windows.
 use boomerang::prelude::*;
 
-use std::io::Stdout;
-pub use termion::event::Key;
-use termion::raw::{IntoRawMode, RawTerminal};
+pub use crossterm::event::KeyCode as Key;
+use crossterm::event::{Event, KeyEventKind, KeyModifiers};
 
-#[derive(Default)]
+#[derive(Debug, Default)]
 pub struct KeyboardEvents {
-    raw_terminal: Option<RawTerminal<Stdout>>,
+    /// Whether the terminal has been put into raw mode by this reactor.
+    raw_mode: bool,
 }
 
-impl std::fmt::Debug for KeyboardEvents {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("KeyboardEvents").finish()
+impl KeyboardEvents {
+    /// Restore the terminal to cooked mode if it was made raw.
+    fn restore_terminal(&mut self) {
+        if std::mem::take(&mut self.raw_mode) {
+            if let Err(err) = crossterm::terminal::disable_raw_mode() {
+                tracing::error!("Failed to disable raw mode: {err}");
+            }
+        }
+    }
+}
+
+impl Drop for KeyboardEvents {
+    fn drop(&mut self) {
+        self.restore_terminal();
     }
 }
 
@@ -51,7 +64,7 @@ struct ReactionShutdown;
 
 impl runtime::Trigger<KeyboardEvents> for ReactionShutdown {
     fn trigger(self, _ctx: &mut runtime::Context, state: &mut KeyboardEvents) {
-        drop(state.raw_terminal.take()); // exit raw mode
+        state.restore_terminal(); // exit raw mode
     }
 }
 
@@ -63,31 +76,42 @@ struct ReactionStartup {
 
 impl runtime::Trigger<KeyboardEvents> for ReactionStartup {
     fn trigger(self, ctx: &mut runtime::Context, state: &mut KeyboardEvents) {
-        let stdin = std::io::stdin();
-
         // enter raw mode, to get key presses one by one
-        // this will stay so until this variable is dropped
-        state.raw_terminal = Some(std::io::stdout().into_raw_mode().unwrap());
+        // this will stay so until the state is restored on shutdown
+        crossterm::terminal::enable_raw_mode().expect("Failed to enable raw mode");
+        state.raw_mode = true;
 
         let mut send_ctx = ctx.make_send_context();
 
-        std::thread::spawn(move || {
-            use termion::input::TermRead;
-
-            for c in stdin.keys() {
-                match c.unwrap() {
-                    k @ (Key::Left | Key::Right | Key::Up | Key::Down) => {
-                        tracing::debug!("received {:?}", k);
-                        self.key_press.schedule(&send_ctx, k, None);
-                    }
-                    Key::Ctrl('c') => {
-                        tracing::debug!("Ctrl-C received, shutting down.");
-                        send_ctx.schedule_shutdown(None);
-                        break;
-                    }
-                    k => {
-                        tracing::trace!("received {:?}", k);
-                    }
+        std::thread::spawn(move || loop {
+            let event = match crossterm::event::read() {
+                Ok(event) => event,
+                Err(err) => {
+                    tracing::error!("Error reading terminal event: {err}");
+                    break;
+                }
+            };
+
+            // Windows reports both key presses and releases, we only care about the former.
+            let Event::Key(key_event) = event else {
+                continue;
+            };
+            if key_event.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key_event.code {
+                k @ (Key::Left | Key::Right | Key::Up | Key::Down) => {
+                    tracing::debug!("received {:?}", k);
+                    self.key_press.schedule(&send_ctx, k, None);
+                }
+                Key::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    tracing::debug!("Ctrl-C received, shutting down.");
+                    send_ctx.schedule_shutdown(None);
+                    break;
+                }
+                k => {
+                    tracing::trace!("received {:?}", k);
                 }
             }
         });
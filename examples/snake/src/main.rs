@@ -1,10 +1,7 @@
-#[cfg(not(windows))]
 mod support;
 
-#[cfg(not(windows))]
 mod keyboard_events;
 
-#[cfg(not(windows))]
 mod reactor {
     use super::support::*;
     use crate::keyboard_events::{Key, KeyboardEvents, KeyboardEventsBuilder};
@@ -93,7 +90,8 @@ mod reactor {
 
             // schedule the first one, then it reschedules itself.
             self.screen_refresh
-                .schedule(ctx, (), Some(Duration::milliseconds(1000)));
+                .schedule(ctx, (), Some(Duration::milliseconds(1000)))
+                .unwrap();
         }
     }
 
@@ -113,7 +111,7 @@ mod reactor {
             // select a delay depending on the tempo
             let delay = Duration::milliseconds(400)
                 - (state.tempo_step * state.tempo).min(Duration::milliseconds(300));
-            self.screen_refresh.schedule(ctx, (), Some(delay));
+            self.screen_refresh.schedule(ctx, (), Some(delay)).unwrap();
         }
     }
 
@@ -145,7 +143,7 @@ mod reactor {
                 UpdateResult::FoodEaten => {
                     state.food_on_grid -= 1;
                     if state.food_on_grid == 0 {
-                        self.manually_add_more_food.schedule(ctx, (), None);
+                        self.manually_add_more_food.schedule(ctx, (), None).unwrap();
                     }
                     state.tempo += 1;
                 }
@@ -220,15 +218,7 @@ mod reactor {
     }
 }
 
-#[cfg(not(windows))]
-fn main() {
-    use reactor::{Snake, SnakeBuilder};
-    let _ = boomerang_util::runner::build_and_run_reactor::<SnakeBuilder>(
-        "snake",
-        Snake::new(32, boomerang::runtime::Duration::milliseconds(40), 2),
-    )
-    .unwrap();
+#[boomerang::main(reactor = "reactor::SnakeBuilder", name = "snake")]
+fn main() -> reactor::Snake {
+    reactor::Snake::new(32, boomerang::runtime::Duration::milliseconds(40), 2)
 }
-
-#[cfg(windows)]
-fn main() {}
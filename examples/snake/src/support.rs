@@ -223,16 +223,22 @@ impl SnakeGrid {
 
 pub mod output {
     use super::*;
+    use crossterm::{cursor, terminal};
     use std::io::{Error, ErrorKind, Result, Write};
-    use termcolor::{Buffer, BufferWriter, Color, ColorChoice, ColorSpec, WriteColor};
+    use termcolor::{Buffer, Color, ColorSpec, WriteColor};
 
     pub fn paint_on_raw_console(grid: &SnakeGrid) {
         let str = format_for_raw_console(grid).unwrap();
         let stdout = std::io::stdout();
         let mut stdout = stdout.lock();
 
-        // this escape char clears the terminal
-        write!(stdout, "\x1B[2J\n\r{}", str).unwrap();
+        crossterm::queue!(
+            stdout,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0)
+        )
+        .unwrap();
+        write!(stdout, "\n\r{}", str).unwrap();
         stdout.flush().unwrap();
     }
 
@@ -251,8 +257,8 @@ pub mod output {
     }
 
     fn format_for_raw_console(grid: &SnakeGrid) -> Result<String> {
-        let bufwtr = BufferWriter::stdout(ColorChoice::Always);
-        let mut buf = bufwtr.buffer();
+        // always emit ANSI escapes, since the result is written to the terminal as a plain string
+        let mut buf = Buffer::ansi();
 
         let snake_color = {
             let mut it = ColorSpec::new();